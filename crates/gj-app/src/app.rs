@@ -1,36 +1,89 @@
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 use winit::{
     event::*,
-    event_loop::ActiveEventLoop,
+    event_loop::{ActiveEventLoop, ControlFlow, EventLoopProxy},
 };
 use winit::application::ApplicationHandler;
 use winit::window::{WindowAttributes, WindowId};
+use crate::cli::CliArgs;
 use crate::events::GjEvent;
+use crate::queue_window::QUEUE_WINDOW_TITLE;
+use crate::settings::Settings;
 use crate::state::AppState;
+use crate::tray::Tray;
+
+/// Shortest time between frames while the window is unfocused, so a running
+/// animation in a background window doesn't keep the GPU busy
+const UNFOCUSED_FRAME_INTERVAL: Duration = Duration::from_millis(100);
+
+/// How often worker responses are still polled while the window is hidden,
+/// or idle with a job running
+const HIDDEN_POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+pub const WINDOW_TITLE: &str = "Gaussian Splatting Viewer";
 
-#[derive(Default)]
 pub struct App {
+    cli: CliArgs,
+    proxy: EventLoopProxy<GjEvent>,
     state: Option<AppState>,
     needs_redraw: bool,
+    focused: bool,
+    occluded: bool,
+    last_redraw: Instant,
+}
+
+impl App {
+    pub fn new(cli: CliArgs, proxy: EventLoopProxy<GjEvent>) -> Self {
+        Self {
+            cli,
+            proxy,
+            state: None,
+            needs_redraw: false,
+            focused: true,
+            occluded: false,
+            last_redraw: Instant::now(),
+        }
+    }
 }
 
 impl ApplicationHandler<GjEvent> for App {
     fn resumed(&mut self, event_loop: &ActiveEventLoop) {
-        let window_attributes = WindowAttributes::default()
-            .with_title("Gaussian Splatting Viewer")
-            .with_inner_size(winit::dpi::LogicalSize::new(1600.0, 900.0));
+        let saved = Settings::load().window;
+        let [width, height] = saved.size.unwrap_or([1600.0, 900.0]);
+        let mut window_attributes = WindowAttributes::default()
+            .with_title(WINDOW_TITLE)
+            .with_inner_size(winit::dpi::LogicalSize::new(width, height))
+            .with_maximized(saved.maximized);
+
+        // Skip positions on a monitor that has since been unplugged
+        if let Some([x, y]) = saved.position
+            && event_loop.available_monitors().any(|m| {
+                let (pos, size) = (m.position(), m.size());
+                (pos.x..pos.x + size.width as i32).contains(&x) && (pos.y..pos.y + size.height as i32).contains(&y)
+            })
+        {
+            window_attributes = window_attributes.with_position(winit::dpi::PhysicalPosition::new(x, y));
+        }
 
         let window = Arc::new(event_loop.create_window(window_attributes).unwrap());
 
-        let state = pollster::block_on(AppState::new(window.clone())).unwrap();
+        let mut state = pollster::block_on(AppState::new(window.clone(), &self.cli)).unwrap();
+        state.init();
+        state.tray = Tray::spawn(&state.rt, self.proxy.clone());
+        if let Some(path) = self.cli.open.take() {
+            state.open_file(&path);
+        }
         self.state = Some(state);
         self.needs_redraw = true;
     }
-    fn user_event(&mut self, event_loop: &ActiveEventLoop, event: GjEvent) {
+    fn user_event(&mut self, _event_loop: &ActiveEventLoop, event: GjEvent) {
         if let Some(state) = &mut self.state {
             match event {
                 GjEvent::Ui(e) => {
-
+                    state.ui.push_ui_event(e);
+                    self.needs_redraw = true;
+                    state.window.request_redraw();
                 }
                 GjEvent::App(e) => {
                     state.ui.push_app_event(e);
@@ -50,34 +103,64 @@ impl ApplicationHandler<GjEvent> for App {
             return;
         };
 
+        if state.queue_window.as_ref().is_some_and(|q| q.window.id() == window_id) {
+            // Closing the queue window docks the queue rather than quitting
+            if let WindowEvent::CloseRequested = event {
+                state.close_queue_window();
+                self.needs_redraw = true;
+            } else if state.queue_window_event(&event) {
+                self.needs_redraw = true;
+                state.window.request_redraw();
+            }
+            return;
+        }
+
         if state.window.id() != window_id {
             return;
         }
 
+        if let WindowEvent::Focused(focused) = event {
+            self.focused = focused;
+        }
+
+        // Stop drawing while minimized or fully covered
+        if let WindowEvent::Occluded(_) | WindowEvent::Resized(_) = event {
+            if let WindowEvent::Occluded(occluded) = event {
+                self.occluded = occluded;
+            }
+
+            let hidden = self.occluded || state.window.is_minimized() == Some(true);
+            if hidden != state.paused {
+                state.set_paused(hidden);
+                self.needs_redraw = true;
+            }
+        }
+
         // Let egui handle the event first
-        let response = state.ui.egui_state.on_window_event(&state.window, &event);
+        let response = state.ui.on_window_event(&state.window, &event);
 
         if response.repaint {
             self.needs_redraw = true;
             state.window.request_redraw();
         }
 
-        // For camera controls, we want to handle mouse events even if egui consumes them,
-        // but only if the mouse is NOT over any egui UI elements
-        let handle_camera_input = match &event {
-            WindowEvent::MouseInput { .. } |
-            WindowEvent::CursorMoved { .. } |
-            WindowEvent::MouseWheel { .. } => {
-                // Check if mouse is over UI
-                !state.ui.egui_ctx.is_pointer_over_area()
+        // The viewport is itself an egui widget, so egui takes every pointer
+        // event; the camera decides from the viewport's hit test whether
+        // they're its own
+        if let WindowEvent::MouseInput { .. } | WindowEvent::CursorMoved { .. } | WindowEvent::MouseWheel { .. } = event {
+            if state.input(&event) {
+                self.needs_redraw = true;
+                state.window.request_redraw();
             }
-            _ => false,
-        };
+            return;
+        }
 
         // Handle events not consumed by egui
-        if !response.consumed || handle_camera_input {
+        if !response.consumed {
             match event {
                 WindowEvent::CloseRequested => {
+                    state.save_window_state();
+                    state.end_session();
                     event_loop.exit();
                 }
                 WindowEvent::Resized(physical_size) => {
@@ -85,49 +168,109 @@ impl ApplicationHandler<GjEvent> for App {
                     self.needs_redraw = true;
                 }
                 WindowEvent::RedrawRequested => {
+                    // Over the FPS cap; about_to_wait schedules the frame for later
+                    if let Some(interval) = state.settings.render.min_frame_interval()
+                        && self.last_redraw.elapsed() < interval
+                    {
+                        self.needs_redraw = true;
+                        return;
+                    }
+
+                    self.last_redraw = Instant::now();
                     state.update();
+                    if state.paused {
+                        return;
+                    }
                     let _ = state.render();
-                    self.needs_redraw = false;
-                }
-                WindowEvent::CursorMoved { .. } |
-                WindowEvent::MouseWheel { .. } |
-                WindowEvent::MouseInput { .. } => {
-                    // Mouse events should trigger redraws for smooth camera control
-                    state.input(&event);
-                    self.needs_redraw = true;
-                    state.window.request_redraw();
-                }
-                _ => {
-                    state.input(&event);
-                }
-            }
-        } else {
-            // Even if egui consumed the event, check if we should handle it for camera
-            // Only handle camera input if the mouse is over the central panel (3D viewport)
-            match event {
-                WindowEvent::CursorMoved { .. } |
-                WindowEvent::MouseWheel { .. } => {
-                    // Always process camera input for these events
-                    // The camera controller will only respond if mouse is pressed
-                    state.input(&event);
-                    if state.mouse_pressed {
-                        self.needs_redraw = true;
-                        state.window.request_redraw();
+                    // Keep drawing while the camera animates or UI events are waiting
+                    self.needs_redraw = state.is_animating() || state.ui.has_pending_ui_events();
+
+                    match state.take_queue_window_request() {
+                        Some(true) if state.queue_window.is_none() => {
+                            let attributes = WindowAttributes::default()
+                                .with_title(QUEUE_WINDOW_TITLE)
+                                .with_inner_size(winit::dpi::LogicalSize::new(420.0, 800.0));
+                            match event_loop.create_window(attributes) {
+                                Ok(window) => state.open_queue_window(Arc::new(window)),
+                                Err(e) => log::warn!("Failed to create the queue window: {}", e),
+                            }
+                            self.needs_redraw = true;
+                        }
+                        Some(false) => {
+                            state.close_queue_window();
+                            self.needs_redraw = true;
+                        }
+                        _ => {}
                     }
                 }
-                WindowEvent::MouseInput { .. } => {
-                    // Track mouse state even if egui consumed the click
+                _ => {
                     state.input(&event);
                 }
-                _ => {}
             }
         }
     }
-    fn about_to_wait(&mut self, _event_loop: &ActiveEventLoop) {
+    fn about_to_wait(&mut self, event_loop: &ActiveEventLoop) {
         // Only request redraw if we actually need one
         // Remove the constant redraw requests that were causing performance issues
-        if self.needs_redraw {
-            if let Some(state) = &self.state {
+        let Some(state) = &mut self.state else {
+            return;
+        };
+
+        if let Some(queue_window) = &state.queue_window
+            && queue_window.repaint_due()
+        {
+            queue_window.window.request_redraw();
+        }
+
+        if state.paused {
+            // Keep handling worker responses, but don't draw; the queue
+            // window may still be on screen
+            if Instant::now() >= self.last_redraw + HIDDEN_POLL_INTERVAL {
+                self.last_redraw = Instant::now();
+                state.update();
+                if let Some(queue_window) = &state.queue_window {
+                    queue_window.window.request_redraw();
+                }
+            }
+            event_loop.set_control_flow(ControlFlow::WaitUntil(self.last_redraw + HIDDEN_POLL_INTERVAL));
+            return;
+        }
+
+        if !(self.needs_redraw || state.ui.repaint_due()) {
+            // Keep watching a custom shader for edits while idle
+            if state.shader_poll_deadline().is_some() && state.poll_custom_shader() {
+                state.scene_dirty = true;
+                state.window.request_redraw();
+            }
+            // Worker responses are handled by update(), which a redraw runs
+            let worker_poll = state.active_job.as_ref().map(|_| self.last_redraw + HIDDEN_POLL_INTERVAL);
+            // Scheduled jobs are sent by update() too, so overnight batches
+            // go out without anyone touching the window
+            let scheduled = state.next_scheduled_job();
+            if worker_poll.into_iter().chain(scheduled).any(|at| Instant::now() >= at) {
+                state.window.request_redraw();
+            }
+
+            // Sleep until something is due rather than spinning
+            let deadlines = [state.shader_poll_deadline(), state.ui.repaint_at(), worker_poll, scheduled];
+            event_loop.set_control_flow(match deadlines.into_iter().flatten().min() {
+                Some(deadline) => ControlFlow::WaitUntil(deadline),
+                None => ControlFlow::Wait,
+            });
+            return;
+        }
+
+        let mut interval = state.settings.render.min_frame_interval();
+        if !self.focused {
+            interval = interval.max(Some(UNFOCUSED_FRAME_INTERVAL));
+        }
+
+        match interval.map(|interval| self.last_redraw + interval) {
+            Some(next_frame) if Instant::now() < next_frame => {
+                event_loop.set_control_flow(ControlFlow::WaitUntil(next_frame));
+            }
+            _ => {
+                event_loop.set_control_flow(ControlFlow::Poll);
                 state.window.request_redraw();
             }
         }
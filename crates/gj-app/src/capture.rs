@@ -0,0 +1,306 @@
+use std::path::{Path, PathBuf};
+use egui_wgpu::wgpu;
+use image::RgbaImage;
+
+use gj_splat::camera::Camera;
+use gj_splat::camera_path::CameraPath;
+use gj_splat::renderer::GaussianRenderer;
+
+use crate::gfx::{create_depth_texture, GfxState};
+
+/// Render the splat scene offscreen at the given size and read it back as an image.
+/// The camera's aspect ratio is used as-is.
+pub fn render_scene_image(
+    gfx: &GfxState,
+    renderer: &mut GaussianRenderer,
+    camera: &Camera,
+    width: u32,
+    height: u32,
+) -> anyhow::Result<RgbaImage> {
+    let format = gfx.config.format;
+    let texture = gfx.device.create_texture(&wgpu::TextureDescriptor {
+        label: Some("Capture Texture"),
+        size: wgpu::Extent3d {
+            width,
+            height,
+            depth_or_array_layers: 1,
+        },
+        mip_level_count: 1,
+        sample_count: 1,
+        dimension: wgpu::TextureDimension::D2,
+        format,
+        usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::COPY_SRC,
+        view_formats: &[],
+    });
+    let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+
+    let depth_texture = create_depth_texture(&gfx.device, &winit::dpi::PhysicalSize::new(width, height));
+    let depth_view = depth_texture.create_view(&wgpu::TextureViewDescriptor::default());
+
+    let bytes_per_row = (width * 4).div_ceil(wgpu::COPY_BYTES_PER_ROW_ALIGNMENT) * wgpu::COPY_BYTES_PER_ROW_ALIGNMENT;
+    let buffer = gfx.device.create_buffer(&wgpu::BufferDescriptor {
+        label: Some("Capture Readback Buffer"),
+        size: (bytes_per_row * height) as u64,
+        usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+        mapped_at_creation: false,
+    });
+
+    let mut encoder = gfx.device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+        label: Some("Capture Encoder")
+    });
+
+    renderer.finish_uploads(camera);
+    renderer.render(&mut encoder, &view, &depth_view, camera, (width, height));
+
+    encoder.copy_texture_to_buffer(
+        wgpu::TexelCopyTextureInfo {
+            texture: &texture,
+            mip_level: 0,
+            origin: wgpu::Origin3d::ZERO,
+            aspect: wgpu::TextureAspect::All,
+        },
+        wgpu::TexelCopyBufferInfo {
+            buffer: &buffer,
+            layout: wgpu::TexelCopyBufferLayout {
+                offset: 0,
+                bytes_per_row: Some(bytes_per_row),
+                rows_per_image: Some(height),
+            },
+        },
+        wgpu::Extent3d {
+            width,
+            height,
+            depth_or_array_layers: 1,
+        },
+    );
+
+    gfx.queue.submit(std::iter::once(encoder.finish()));
+
+    let slice = buffer.slice(..);
+    let (tx, rx) = std::sync::mpsc::channel();
+    slice.map_async(wgpu::MapMode::Read, move |result| {
+        let _ = tx.send(result);
+    });
+    gfx.device.poll(wgpu::PollType::wait_indefinitely())?;
+    rx.recv()??;
+
+    let swap_rb = matches!(
+        format,
+        wgpu::TextureFormat::Bgra8Unorm | wgpu::TextureFormat::Bgra8UnormSrgb
+    );
+
+    let mut pixels = Vec::with_capacity((width * height * 4) as usize);
+    {
+        let data = slice.get_mapped_range();
+        for row in data.chunks(bytes_per_row as usize) {
+            for px in row[..(width * 4) as usize].chunks_exact(4) {
+                if swap_rb {
+                    pixels.extend_from_slice(&[px[2], px[1], px[0], px[3]]);
+                } else {
+                    pixels.extend_from_slice(px);
+                }
+            }
+        }
+    }
+    buffer.unmap();
+
+    RgbaImage::from_raw(width, height, pixels)
+        .ok_or_else(|| anyhow::anyhow!("Captured image has unexpected size"))
+}
+
+/// Save an image as `<dir>/<prefix>_<unix time>.png`, returning the path
+pub fn save_timestamped_png(image: &RgbaImage, dir: &Path, prefix: &str) -> anyhow::Result<PathBuf> {
+    std::fs::create_dir_all(dir)?;
+
+    let timestamp = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)?
+        .as_millis();
+    let path = dir.join(format!("{}_{}.png", prefix, timestamp));
+
+    image.save(&path)?;
+    Ok(path)
+}
+/// Camera motion for a rendered frame sequence
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SequenceMotion {
+    /// One full orbit around the target, starting from the current view
+    Turntable,
+    /// The keyframed camera path from start to end
+    CameraPath,
+}
+
+impl SequenceMotion {
+    pub const ALL: [SequenceMotion; 2] = [SequenceMotion::Turntable, SequenceMotion::CameraPath];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            SequenceMotion::Turntable => "Turntable",
+            SequenceMotion::CameraPath => "Camera path",
+        }
+    }
+}
+
+/// One camera per frame of a sequence, framed for an output of `width` x `height`.
+/// The turntable leaves out the closing frame so the sequence loops seamlessly.
+pub fn sequence_cameras(
+    camera: &Camera,
+    path: &CameraPath,
+    motion: SequenceMotion,
+    frames: usize,
+    width: u32,
+    height: u32,
+) -> Vec<Camera> {
+    let mut base = camera.clone();
+    base.aspect_ratio = width as f32 / height.max(1) as f32;
+
+    (0..frames)
+        .map(|i| {
+            let mut frame = base.clone();
+            match motion {
+                SequenceMotion::Turntable => {
+                    frame.rotate(360.0 * i as f32 / frames as f32, 0.0);
+                }
+                SequenceMotion::CameraPath => {
+                    let t = if frames > 1 { i as f32 / (frames - 1) as f32 } else { 0.0 };
+                    if let Some(pose) = path.sample(t * path.duration()) {
+                        pose.apply(&mut frame);
+                    }
+                }
+            }
+            frame
+        })
+        .collect()
+}
+
+/// Render one offscreen frame per camera and save them as
+/// `<dir>/frame_0000.png`, `frame_0001.png`, ...
+pub fn render_png_sequence(
+    gfx: &GfxState,
+    renderer: &mut GaussianRenderer,
+    cameras: &[Camera],
+    width: u32,
+    height: u32,
+    dir: &Path,
+) -> anyhow::Result<()> {
+    std::fs::create_dir_all(dir)?;
+
+    for (i, camera) in cameras.iter().enumerate() {
+        let image = render_scene_image(gfx, renderer, camera, width, height)?;
+        image.save(dir.join(format!("frame_{:04}.png", i)))?;
+    }
+    Ok(())
+}
+
+/// Encode frames as an endlessly looping GIF at `path`, showing each for `frame_ms`.
+/// Frames are made opaque first since GIF only knows fully transparent pixels.
+pub fn save_gif(frames: Vec<RgbaImage>, frame_ms: u32, path: &Path) -> anyhow::Result<()> {
+    use image::codecs::gif::{GifEncoder, Repeat};
+    use image::{Delay, Frame};
+
+    let file = std::io::BufWriter::new(std::fs::File::create(path)?);
+    let mut encoder = GifEncoder::new_with_speed(file, 10);
+    encoder.set_repeat(Repeat::Infinite)?;
+    encoder.encode_frames(frames.into_iter().map(|mut image| {
+        for px in image.pixels_mut() {
+            px[3] = 255;
+        }
+        Frame::from_parts(image, 0, 0, Delay::from_numer_denom_ms(frame_ms, 1))
+    }))?;
+    Ok(())
+}
+
+/// Nerfstudio / Instant-NGP `transforms.json` for frames rendered from `cameras`
+/// at `width` x `height`, with `images/frame_0000.png`, ... as file paths.
+/// Poses are camera-to-world in the OpenGL convention both tools read.
+pub fn transforms_json(cameras: &[Camera], width: u32, height: u32) -> serde_json::Value {
+    let fov_y = cameras.first().map_or(50.0, |c| c.fov).to_radians();
+    let focal = height as f32 / 2.0 / (fov_y / 2.0).tan();
+
+    let frames: Vec<_> = cameras.iter()
+        .enumerate()
+        .map(|(i, camera)| {
+            let pose = camera.view_matrix().inverse();
+            let rows: Vec<[f32; 4]> = (0..4).map(|r| pose.row(r).to_array()).collect();
+            serde_json::json!({
+                "file_path": format!("images/frame_{:04}.png", i),
+                "transform_matrix": rows,
+            })
+        })
+        .collect();
+
+    serde_json::json!({
+        "camera_model": "OPENCV",
+        "w": width,
+        "h": height,
+        "fl_x": focal,
+        "fl_y": focal,
+        "cx": width as f32 / 2.0,
+        "cy": height as f32 / 2.0,
+        "camera_angle_x": 2.0 * (width as f32 / 2.0 / focal).atan(),
+        "camera_angle_y": fov_y,
+        "k1": 0.0,
+        "k2": 0.0,
+        "p1": 0.0,
+        "p2": 0.0,
+        "frames": frames,
+    })
+}
+
+/// Render one frame per camera into `<dir>/images` and describe them in
+/// `<dir>/transforms.json`, ready for NeRF / 3DGS training
+pub fn export_transforms(
+    gfx: &GfxState,
+    renderer: &mut GaussianRenderer,
+    cameras: &[Camera],
+    width: u32,
+    height: u32,
+    dir: &Path,
+) -> anyhow::Result<()> {
+    render_png_sequence(gfx, renderer, cameras, width, height, &dir.join("images"))?;
+    let transforms = transforms_json(cameras, width, height);
+    std::fs::write(dir.join("transforms.json"), serde_json::to_string_pretty(&transforms)?)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_turntable_cameras_orbit_once() {
+        let camera = Camera::default();
+        let cameras = sequence_cameras(&camera, &CameraPath::new(), SequenceMotion::Turntable, 4, 1920, 1080);
+
+        assert_eq!(cameras.len(), 4);
+        assert!((cameras[1].azimuth - camera.azimuth - 90.0).abs() < 1e-4);
+        assert!((cameras[0].aspect_ratio - 16.0 / 9.0).abs() < 1e-4);
+    }
+
+    #[test]
+    fn test_transforms_json_poses() {
+        let camera = Camera { aspect_ratio: 2.0, fov: 90.0, ..Camera::default() };
+        let json = transforms_json(std::slice::from_ref(&camera), 200, 100);
+
+        assert!((json["fl_y"].as_f64().unwrap() - 50.0).abs() < 1e-3);
+        assert_eq!(json["frames"][0]["file_path"], "images/frame_0000.png");
+        // The pose's last column is the camera position
+        let pose = &json["frames"][0]["transform_matrix"];
+        for (axis, expected) in camera.position.to_array().into_iter().enumerate() {
+            assert!((pose[axis][3].as_f64().unwrap() - expected as f64).abs() < 1e-4);
+        }
+    }
+
+    #[test]
+    fn test_path_cameras_span_the_path() {
+        let mut camera = Camera::default();
+        let mut path = CameraPath::new();
+        path.add_keyframe(0.0, &camera);
+        camera.rotate(90.0, 0.0);
+        path.add_keyframe(2.0, &camera);
+
+        let cameras = sequence_cameras(&Camera::default(), &path, SequenceMotion::CameraPath, 3, 100, 100);
+
+        assert!(cameras[0].azimuth.abs() < 1e-4);
+        assert!((cameras[2].azimuth - 90.0).abs() < 1e-4);
+    }
+}
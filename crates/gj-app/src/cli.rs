@@ -0,0 +1,119 @@
+use std::path::PathBuf;
+use crate::logging::LogFormat;
+use crate::settings::{GpuBackend, RenderSettings};
+
+pub const USAGE: &str = "\
+Usage: gj-app [OPTIONS] [FILE]
+
+Arguments:
+  [FILE]             PLY to open in the viewer on startup
+
+Options:
+  --backend <NAME>   Graphics API: vulkan, dx12, metal or gl
+  --adapter <NAME>   GPU to render on, by name as shown in Rendering settings
+  --log-format <FMT> Log output: text (default) or json, one object per line
+  -h, --help         Print this help";
+
+/// Command-line options; these override the settings file for one run
+#[derive(Debug, Clone, Default)]
+pub struct CliArgs {
+    pub backend: Option<GpuBackend>,
+    pub adapter: Option<String>,
+    pub log_format: LogFormat,
+    /// Scene to load on startup, e.g. from the OS "open with"
+    pub open: Option<PathBuf>,
+    pub help: bool,
+}
+
+impl CliArgs {
+    /// Parse the arguments after the program name
+    pub fn parse(args: impl IntoIterator<Item = String>) -> Result<Self, String> {
+        let mut cli = Self::default();
+        let mut args = args.into_iter();
+
+        while let Some(arg) = args.next() {
+            if !arg.starts_with('-') {
+                if cli.open.is_some() {
+                    return Err(format!("Unexpected argument \"{}\"", arg));
+                }
+                cli.open = Some(PathBuf::from(arg));
+                continue;
+            }
+
+            // Accept both `--flag value` and `--flag=value`
+            let (flag, inline) = match arg.split_once('=') {
+                Some((flag, value)) => (flag.to_string(), Some(value.to_string())),
+                None => (arg, None),
+            };
+            let mut value = || inline.clone().or_else(|| args.next())
+                .ok_or_else(|| format!("{} needs a value", flag));
+
+            match flag.as_str() {
+                "--backend" => {
+                    let name = value()?;
+                    cli.backend = Some(GpuBackend::parse(&name)
+                        .ok_or_else(|| format!("Unknown backend \"{}\"", name))?);
+                }
+                "--adapter" => cli.adapter = Some(value()?),
+                "--log-format" => {
+                    let name = value()?;
+                    cli.log_format = LogFormat::parse(&name)
+                        .ok_or_else(|| format!("Unknown log format \"{}\"", name))?;
+                }
+                "-h" | "--help" => cli.help = true,
+                _ => return Err(format!("Unknown argument \"{}\"", flag)),
+            }
+        }
+
+        Ok(cli)
+    }
+
+    /// Render settings with the command-line overrides applied
+    pub fn apply(&self, render: &RenderSettings) -> RenderSettings {
+        let mut render = render.clone();
+        if self.backend.is_some() {
+            render.backend = self.backend;
+        }
+        if self.adapter.is_some() {
+            render.adapter = self.adapter.clone();
+        }
+        render
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Result<CliArgs, String> {
+        CliArgs::parse(args.iter().map(|s| s.to_string()))
+    }
+
+    #[test]
+    fn test_parse_gpu_flags() {
+        let cli = parse(&["--backend", "Vulkan", "--adapter=NVIDIA GeForce RTX 4060"]).unwrap();
+        assert_eq!(cli.backend, Some(GpuBackend::Vulkan));
+        assert_eq!(cli.adapter.as_deref(), Some("NVIDIA GeForce RTX 4060"));
+
+        assert!(parse(&["--backend", "glide"]).is_err());
+        assert!(parse(&["--adapter"]).is_err());
+        assert!(parse(&["--frobnicate"]).is_err());
+    }
+
+    #[test]
+    fn test_parse_file_argument() {
+        let cli = parse(&["--backend", "gl", "/tmp/chair.ply"]).unwrap();
+        assert_eq!(cli.open, Some(PathBuf::from("/tmp/chair.ply")));
+
+        assert!(parse(&["a.ply", "b.ply"]).is_err());
+        assert_eq!(parse(&[]).unwrap().open, None);
+    }
+
+    #[test]
+    fn test_apply_keeps_unset_settings() {
+        let settings = RenderSettings { backend: Some(GpuBackend::Dx12), adapter: Some("iGPU".into()), ..Default::default() };
+        let render = parse(&["--adapter", "dGPU"]).unwrap().apply(&settings);
+        assert_eq!(render.backend, Some(GpuBackend::Dx12));
+        assert_eq!(render.adapter.as_deref(), Some("dGPU"));
+    }
+}
@@ -0,0 +1,214 @@
+use std::time::Duration;
+use serde::Deserialize;
+use crate::error::AppError;
+use crate::settings::{ContentFilterSettings, FilterAction};
+
+/// Longest wait for the moderation endpoint before it counts as unreachable
+const MODERATION_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// What the content filter makes of a prompt
+#[derive(Debug, Clone, PartialEq)]
+pub enum Verdict {
+    Allow,
+    /// Caught, but the filter only warns; the reason says why
+    Warn(String),
+    /// Caught; the job must not be submitted
+    Block(String),
+}
+
+/// Checks prompts against a word list and optionally a moderation API
+/// before they're submitted. Calls may block; they're made from the
+/// worker thread.
+#[derive(Default)]
+pub struct ContentFilter {
+    action: FilterAction,
+    /// Caught words and phrases, each split into lowercase words
+    phrases: Vec<Vec<String>>,
+    moderation: Option<Moderation>,
+    moderation_required: bool,
+}
+
+struct Moderation {
+    url: String,
+    token: Option<String>,
+    client: reqwest::blocking::Client,
+}
+
+#[derive(Deserialize)]
+struct ModerationResponse {
+    results: Vec<ModerationResult>,
+}
+
+#[derive(Deserialize)]
+struct ModerationResult {
+    flagged: bool,
+    #[serde(default)]
+    categories: serde_json::Map<String, serde_json::Value>,
+}
+
+/// Lowercase words of `text`, split at anything not a letter or digit
+fn words(text: &str) -> Vec<String> {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|w| !w.is_empty())
+        .map(str::to_lowercase)
+        .collect()
+}
+
+impl ContentFilter {
+    pub fn new(settings: &ContentFilterSettings) -> Self {
+        let mut entries = settings.blocked_words.clone();
+        if let Some(path) = &settings.word_list {
+            match std::fs::read_to_string(path) {
+                Ok(list) => entries.extend(
+                    list.lines()
+                        .map(|line| line.split('#').next().unwrap_or_default().trim())
+                        .filter(|line| !line.is_empty())
+                        .map(str::to_string)
+                ),
+                Err(e) => log::error!("Could not read content filter word list {}: {}", path.display(), e),
+            }
+        }
+
+        let moderation = settings.moderation_url.as_ref().map(|url| Moderation {
+            url: url.clone(),
+            token: settings.moderation_token.clone(),
+            client: reqwest::blocking::Client::builder()
+                .use_rustls_tls()
+                .timeout(MODERATION_TIMEOUT)
+                .build()
+                .unwrap_or_default(),
+        });
+
+        Self {
+            action: settings.action,
+            phrases: entries.iter().map(|e| words(e)).filter(|p| !p.is_empty()).collect(),
+            moderation,
+            moderation_required: settings.moderation_required,
+        }
+    }
+
+    /// Whether the filter checks anything at all
+    pub fn is_active(&self) -> bool {
+        !self.phrases.is_empty() || self.moderation.is_some()
+    }
+
+    pub fn check(&self, prompt: &str) -> Verdict {
+        let reason = match self.matched_phrase(prompt) {
+            Some(phrase) => format!("contains \"{}\"", phrase),
+            None => match self.moderate(prompt) {
+                Ok(None) => return Verdict::Allow,
+                Ok(Some(categories)) => format!("flagged by moderation ({})", categories),
+                Err(e) if self.moderation_required => {
+                    // Unchecked can't pass when checking is required, even just with a warning
+                    return Verdict::Block(format!("moderation unavailable: {}", e));
+                }
+                Err(e) => {
+                    log::warn!("Moderation unavailable, checked the word list only: {}", e);
+                    return Verdict::Allow;
+                }
+            },
+        };
+        match self.action {
+            FilterAction::Block => Verdict::Block(reason),
+            FilterAction::Warn => Verdict::Warn(reason),
+        }
+    }
+
+    /// First listed word or phrase appearing whole in `prompt`
+    fn matched_phrase(&self, prompt: &str) -> Option<String> {
+        let prompt = words(prompt);
+        self.phrases.iter()
+            .find(|phrase| prompt.windows(phrase.len()).any(|w| w == phrase.as_slice()))
+            .map(|phrase| phrase.join(" "))
+    }
+
+    /// Categories the moderation endpoint flags `prompt` for, None when
+    /// it's fine or there's no endpoint
+    fn moderate(&self, prompt: &str) -> Result<Option<String>, AppError> {
+        let Some(moderation) = &self.moderation else {
+            return Ok(None);
+        };
+
+        let mut request = moderation.client.post(&moderation.url).json(&serde_json::json!({ "input": prompt }));
+        if let Some(token) = &moderation.token {
+            request = request.bearer_auth(token);
+        }
+        let response = request
+            .send()
+            .map_err(|e| AppError::ServiceUnreachable(format!("Moderation request failed: {}", e)))?;
+        if !response.status().is_success() {
+            return Err(AppError::Service(format!("Moderation returned {}", response.status())));
+        }
+        let body: ModerationResponse = response
+            .json()
+            .map_err(|e| AppError::Service(format!("Failed to parse moderation response: {}", e)))?;
+        Ok(flagged_categories(&body))
+    }
+}
+
+/// Comma-separated categories of a flagged moderation result, None when
+/// nothing was flagged
+fn flagged_categories(body: &ModerationResponse) -> Option<String> {
+    let flagged: Vec<&ModerationResult> = body.results.iter().filter(|r| r.flagged).collect();
+    if flagged.is_empty() {
+        return None;
+    }
+    let categories: Vec<&str> = flagged.iter()
+        .flat_map(|r| &r.categories)
+        .filter(|(_, v)| v.as_bool() == Some(true))
+        .map(|(k, _)| k.as_str())
+        .collect();
+    Some(if categories.is_empty() { "unspecified".to_string() } else { categories.join(", ") })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn filter(action: FilterAction, words: &[&str]) -> ContentFilter {
+        ContentFilter::new(&ContentFilterSettings {
+            action,
+            blocked_words: words.iter().map(|w| w.to_string()).collect(),
+            ..Default::default()
+        })
+    }
+
+    #[test]
+    fn test_word_list_matches_whole_words() {
+        let filter = filter(FilterAction::Block, &["gun", "Severed Head"]);
+        assert!(filter.is_active());
+        assert_eq!(filter.check("a toy GUN, painted red"), Verdict::Block("contains \"gun\"".into()));
+        assert_eq!(filter.check("a severed  head"), Verdict::Block("contains \"severed head\"".into()));
+        // Only whole words: no Scunthorpe problem
+        assert_eq!(filter.check("a gundam model"), Verdict::Allow);
+        assert_eq!(filter.check("a severed rope and a head"), Verdict::Allow);
+
+        let warn = self::filter(FilterAction::Warn, &["gun"]);
+        assert!(matches!(warn.check("a gun"), Verdict::Warn(_)));
+        assert!(!ContentFilter::default().is_active());
+    }
+
+    #[test]
+    fn test_moderation_categories() {
+        let body: ModerationResponse = serde_json::from_value(serde_json::json!({ "results": [
+            { "flagged": true, "categories": { "violence": true, "sexual": false } },
+        ]})).unwrap();
+        assert_eq!(flagged_categories(&body).as_deref(), Some("violence"));
+
+        let body: ModerationResponse = serde_json::from_value(serde_json::json!({ "results": [
+            { "flagged": false, "categories": { "violence": false } },
+        ]})).unwrap();
+        assert_eq!(flagged_categories(&body), None);
+    }
+
+    #[test]
+    fn test_required_moderation_blocks_when_unreachable() {
+        let filter = ContentFilter::new(&ContentFilterSettings {
+            action: FilterAction::Warn,
+            moderation_url: Some("http://127.0.0.1:9/moderations".into()),
+            moderation_required: true,
+            ..Default::default()
+        });
+        assert!(matches!(filter.check("a chair"), Verdict::Block(_)));
+    }
+}
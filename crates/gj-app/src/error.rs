@@ -0,0 +1,159 @@
+use gj_core::error::Error;
+use serde::{Deserialize, Serialize};
+
+/// Categorized error for a job, stored with the job record and shown on its card
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, thiserror::Error)]
+pub enum AppError {
+    #[error("Generation service unreachable: {0}")]
+    ServiceUnreachable(String),
+
+    #[error("Model not loaded: {0}")]
+    ModelNotLoaded(String),
+
+    #[error("Out of GPU memory: {0}")]
+    OutOfVram(String),
+
+    #[error("Invalid PLY file: {0}")]
+    InvalidPly(String),
+
+    #[error("Timed out: {0}")]
+    Timeout(String),
+
+    #[error("Generation failed: {0}")]
+    GenerationFailed(String),
+
+    #[error("{0}")]
+    Cancelled(String),
+
+    /// A job this one waits on was deleted, failed or left nothing to use
+    #[error("Dependency failed: {0}")]
+    DependencyFailed(String),
+
+    /// The service answered with something we didn't expect
+    #[error("Unexpected service response: {0}")]
+    Service(String),
+
+    /// The service speaks another version of the protocol
+    #[error("Incompatible service: {0}")]
+    IncompatibleService(String),
+
+    /// The content filter caught the prompt before it was submitted
+    #[error("Prompt blocked: {0}")]
+    Blocked(String),
+
+    #[error("{0}")]
+    Internal(String),
+}
+
+impl AppError {
+    /// Short category name for the UI
+    pub fn title(&self) -> &str {
+        match self {
+            Self::ServiceUnreachable(_) => "Service unreachable",
+            Self::ModelNotLoaded(_) => "Model not loaded",
+            Self::OutOfVram(_) => "Out of GPU memory",
+            Self::InvalidPly(_) => "Invalid output file",
+            Self::Timeout(_) => "Timed out",
+            Self::GenerationFailed(_) => "Generation failed",
+            Self::Cancelled(_) => "Cancelled",
+            Self::DependencyFailed(_) => "Dependency failed",
+            Self::Service(_) => "Service error",
+            Self::IncompatibleService(_) => "Incompatible service",
+            Self::Blocked(_) => "Blocked by content filter",
+            Self::Internal(_) => "Error",
+        }
+    }
+
+    /// The underlying detail message
+    pub fn message(&self) -> &str {
+        match self {
+            Self::ServiceUnreachable(m)
+            | Self::ModelNotLoaded(m)
+            | Self::OutOfVram(m)
+            | Self::InvalidPly(m)
+            | Self::Timeout(m)
+            | Self::GenerationFailed(m)
+            | Self::Cancelled(m)
+            | Self::DependencyFailed(m)
+            | Self::Service(m)
+            | Self::IncompatibleService(m)
+            | Self::Blocked(m)
+            | Self::Internal(m) => m,
+        }
+    }
+
+    /// Suggested fix, shared with [`gj_core::error::Error::remediation`] where the categories overlap
+    pub fn remediation(&self) -> Option<&'static str> {
+        let core = match self {
+            Self::ServiceUnreachable(_) => Error::ServiceUnreachable(String::new()),
+            Self::ModelNotLoaded(_) => Error::ModelNotLoaded(String::new()),
+            Self::OutOfVram(_) => Error::OutOfVram(String::new()),
+            Self::InvalidPly(_) => Error::InvalidPly(String::new()),
+            Self::Timeout(_) => Error::Timeout(String::new()),
+            Self::GenerationFailed(_) => Error::GenerationFailed(String::new()),
+            Self::Service(_) => return Some("Check that the service and the app are the same version"),
+            Self::IncompatibleService(_) => return Some("Update the app and the generation service to the same release"),
+            Self::DependencyFailed(_) => return Some("Re-run the job it depends on, or remove this one"),
+            Self::Blocked(_) => return Some("Rephrase the prompt; the filter is set under `content_filter` in settings.json"),
+            Self::Cancelled(_) | Self::Internal(_) => return None,
+        };
+        core.remediation()
+    }
+
+    /// Worth trying again shortly: the service may just be restarting.
+    /// Timeouts aren't, since the request may have reached the service and
+    /// sending it again would start a second job.
+    pub fn is_transient(&self) -> bool {
+        matches!(self, Self::ServiceUnreachable(_))
+    }
+
+    /// Categorize a raw failure message reported by the Python worker
+    pub fn classify(message: &str) -> Self {
+        let lower = message.to_lowercase();
+        let message = message.to_string();
+
+        if lower.contains("out of memory") || lower.contains("cuda oom") {
+            Self::OutOfVram(message)
+        } else if lower.contains("not available") || lower.contains("not loaded") {
+            Self::ModelNotLoaded(message)
+        } else if lower.contains("timed out") || lower.contains("timeout") {
+            Self::Timeout(message)
+        } else {
+            Self::GenerationFailed(message)
+        }
+    }
+}
+
+impl From<Error> for AppError {
+    fn from(e: Error) -> Self {
+        match e {
+            Error::ServiceUnreachable(m) => Self::ServiceUnreachable(m),
+            Error::ModelNotLoaded(m) => Self::ModelNotLoaded(m),
+            Error::OutOfVram(m) => Self::OutOfVram(m),
+            Error::InvalidPly(m) | Error::InvalidGaussianCloud(m) => Self::InvalidPly(m),
+            Error::Timeout(m) => Self::Timeout(m),
+            Error::GenerationFailed(m) => Self::GenerationFailed(m),
+            other => Self::Internal(other.to_string()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_classify() {
+        assert!(matches!(AppError::classify("CUDA out of memory. Tried to allocate 2 GiB"), AppError::OutOfVram(_)));
+        assert!(matches!(AppError::classify("Model 'lgm' not available"), AppError::ModelNotLoaded(_)));
+        assert!(matches!(AppError::classify("Generated mesh is flat/invalid"), AppError::GenerationFailed(_)));
+        assert!(AppError::classify("anything").remediation().is_some());
+    }
+
+    #[test]
+    fn test_is_transient() {
+        assert!(AppError::ServiceUnreachable("connection refused".into()).is_transient());
+        assert!(!AppError::Timeout("operation timed out".into()).is_transient());
+        assert!(!AppError::Service("500 Internal Server Error".into()).is_transient());
+    }
+}
\ No newline at end of file
@@ -1,24 +1,205 @@
-use gj_core::Model3D;
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::sync::Arc;
+use image::RgbaImage;
+use gj_core::array::ArrayLayout;
+use gj_core::cloud_stats::CloudStats;
+use gj_core::color_grading::ColorGrade;
+use gj_core::comparison::ComparisonMetrics;
+use gj_core::mesh::MeshSettings;
+use gj_core::ply_profile::PlyProfile;
+use gj_core::volume::Volume;
+use gj_splat::depth_of_field::DepthOfField;
+use gj_splat::clipping::ClipPlane;
+use gj_splat::renderer::ColorMode;
+use gj_splat::selection::{PaintBrush, SelectionShape};
+use crate::capture::SequenceMotion;
+use crate::error::AppError;
+use crate::generator::{ModelPresets, ModelWeights, ParamSpec, WorkerGpu};
+use crate::images::InputImage;
+use crate::jobs::{ArtifactKind, CaptureView, DecodeSettings, JobInputs, JobMetadata, RefineSettings, Submitter};
+use crate::settings::{AppearanceSettings, ConfirmSettings, GpuBackend, PresentMode, RenderSettings};
 
+#[allow(dead_code)]
 #[derive(Debug, Clone)]
 pub enum GjEvent {
     Ui(UiEvent),
     App(AppEvent)
 }
 
+#[allow(dead_code)]
 #[derive(Debug, Clone)]
 pub enum UiEvent {
     ResetCamera,
+    Screenshot,
+    FocusAt { x: f32, y: f32 },
+    SetAutoOrbit { enabled: bool, speed: f32 },
+    AddCameraKeyframe(f32),
+    RemoveCameraKeyframe(usize),
+    ClearCameraPath,
+    PlayCameraPath { looping: bool },
+    StopCameraPath,
     LoadImages,
-    GenerateWithModel {
-        prompt: String,
-        model: Model3D,
-    },
+    /// Load a finished generation that was held back because a scene was already open
+    LoadPendingScene,
+    /// Reopen the scene, view and layout of the run that crashed
+    RestoreSession,
+    Generate(JobInputs),
+    /// Record a job now and send it to the worker at this Unix time
+    ScheduleJob { inputs: JobInputs, run_at: u64 },
+    /// Pick a PNG or JPEG for new jobs to be conditioned on
+    BrowseInputImage,
+    /// Prepare the image at this path and add it to the input image library
+    AddInputImage(PathBuf),
+    /// Drop an image from the input image library by id, deleting its files
+    RemoveInputImage(String),
+    /// Pick the folder of a COLMAP sparse model to build a capture set from
+    BrowseColmap,
+    /// Build a capture set from the photos of the COLMAP model in this folder
+    ImportColmap(PathBuf),
+    /// Pre-fill the generation form from an earlier job
+    DuplicateJob(String),
+    UpdateJobNotes { id: String, notes: String },
+    /// Remove job records, and their output files with `delete_files`
+    DeleteJobs { ids: Vec<String>, delete_files: bool },
+    RetryJobs(Vec<String>),
+    /// Decode the latent a finished job kept again with other settings, as a new job
+    RedecodeJob { id: String, settings: DecodeSettings },
+    /// Refine a finished job's output against its prompt, as a new version of it
+    RefineJob { id: String, settings: RefineSettings },
+    /// Stop the job the worker is currently processing
+    CancelJob,
+    /// Hold new jobs back from the worker, or release the held ones
+    SetQueuePaused(bool),
+    /// Pop the queue panel out into its own window, or dock it again
+    DetachQueue(bool),
+    /// Bring the window back from the tray or taskbar
+    ShowWindow,
+    /// Ask every worker which model weights it has
+    RefreshModelWeights,
+    /// Ask every worker for its GPUs' memory use
+    RefreshWorkerGpus,
+    /// Have a worker fetch a model's weights, or delete them with `remove`
+    ManageWeights { worker: String, model: String, remove: bool },
+    OpenOutputFolder,
+    /// Resubmit a stuck job, keeping its record
+    RequeueJob(String),
+    /// Load a finished job's output into the viewer
+    OpenJobScene(String),
+    /// Open one of a job's files: splats in the viewer, anything else in its default app
+    OpenArtifact { kind: ArtifactKind, path: PathBuf },
+    /// Save a copy of one of a job's files where the user picks
+    ExportArtifact(PathBuf),
+    /// Render a turntable GIF of a finished job's output as its preview
+    ExportJobGif(String),
+    /// Bake a finished job's output into a textured GLB next to it, recorded as its mesh
+    ExportJobMesh(String),
+    /// Pick a new location for a job's missing output file
+    BrowseRelink(String),
+    RelinkJob { id: String, path: PathBuf },
+    TagJobs { ids: Vec<String>, tag: String },
+    /// Copy job outputs to a folder; PLYs over `max_ply_bytes` are decimated to fit
+    /// and written in `profile`
+    ExportJobs { ids: Vec<String>, max_ply_bytes: Option<u64>, profile: PlyProfile },
+    /// Save the current scene as a PLY in `profile`
+    ExportPly { profile: PlyProfile },
+    /// Save the current scene as a MagicaVoxel model, `resolution` cells along its longest axis
+    ExportVoxels { resolution: usize },
+    /// Save the current scene as a mesh with its splat colors baked into a
+    /// texture, as GLB or OBJ
+    ExportMesh(MeshSettings),
+    /// Save the current scene as a single HTML file with a built-in WebGL viewer
+    ExportWebViewer,
+    /// Pick a reference PLY to compare the open scene against
+    BrowseComparison,
+    /// Compare the open scene against a reference cloud, optionally aligning it first
+    CompareWith { path: PathBuf, align: bool },
+    /// Color splats by their distance to the reference instead of their own colors
+    ShowDifferences(bool),
+    ClearComparison,
+    /// Pick a PLY to morph the open scene into
+    BrowseMorphTarget,
+    /// Pair the open scene's splats with those of another cloud, optionally
+    /// aligning it first, and show the morph between them
+    MorphTo { path: PathBuf, align: bool },
+    /// Show the morph at this point, from 0 for the scene to 1 for the target
+    SetMorphPosition(f32),
+    /// Swing the morph back and forth, or stop it
+    PlayMorph(bool),
+    /// Render the morph from start to end into a numbered PNG sequence
+    ExportMorph { frames: usize },
+    ClearMorph,
+    /// Render the scene frame by frame into a numbered PNG sequence
+    ExportSequence { motion: SequenceMotion, width: u32, height: u32, frames: usize },
+    /// Render the camera path keyframes, or the current view, with a
+    /// `transforms.json` for NeRF / 3DGS training
+    ExportTransforms { width: u32, height: u32 },
+    /// Select the splats inside a screen region; `additive` keeps the current selection
+    SelectRegion { shape: SelectionShape, additive: bool },
+    ClearSelection,
+    DeleteSelection,
+    /// Keep only the selected splats
+    IsolateSelection,
+    /// Place a box, or a sphere with `sphere`, around the middle of the scene
+    PlaceVolume { sphere: bool },
+    SetVolume(Option<Volume>),
+    /// Remove the splats inside the volume
+    DeleteVolume,
+    /// Keep only the splats inside the volume
+    IsolateVolume,
+    /// Drag in the viewport to paint with `brush`, or orbit with None
+    SetBrush(Option<PaintBrush>),
+    /// One dab of the brush centered on the given viewport pixel
+    Paint { x: f32, y: f32 },
+    /// Turn the scene upright and rest it on the ground plane
+    SnapToGround,
+    /// Center the scene at the origin and scale it into a unit box
+    NormalizeScene,
+    /// Replace the scene with copies of it laid out on the ground
+    ArrayScene(ArrayLayout),
     PromptChanged(String),
     ToggleWireframe(bool),
+    /// Store splats at reduced precision on the GPU to fit larger scenes
+    SetCompactSplats(bool),
+    /// Show the splats' colors or their estimated normals
+    SetColorMode(ColorMode),
+    /// Viewport clear color in linear RGB; None restores the default
+    SetBackground(Option<[f32; 3]>),
+    /// Shade the ground under the scene
+    SetGroundShadow(bool),
+    /// Pick a WGSL file to draw the splats with
+    BrowseCustomShader,
+    /// Draw the splats with a WGSL file, watched for edits; None restores the built-in shader
+    SetCustomShader(Option<PathBuf>),
+    /// Frame the whole scene
+    FrameScene,
+    /// Adjust colors live in the shader
+    SetColorGrade(ColorGrade),
+    /// Write the current color grade into the scene's splat colors
+    BakeColorGrade,
+    /// Blur what's out of focus, or stop with None
+    SetDepthOfField(Option<DepthOfField>),
+    /// Focus on the splat under the given viewport pixel
+    FocusDepthAt { x: f32, y: f32 },
+    /// Add a clipping plane across world axis `axis` through the scene center
+    AddClipPlane { axis: usize },
+    SetClipPlanes(Vec<ClipPlane>),
+    /// Move clipping plane `index` by `delta` along its normal
+    MoveClipPlane { index: usize, delta: f32 },
+    SetPresentMode(PresentMode),
+    /// Graphics API and GPU to use from the next start; None picks the default
+    SetGpu { backend: Option<GpuBackend>, adapter: Option<String> },
+    /// Cap the frame rate, or None for uncapped
+    SetFpsLimit(Option<u32>),
+    /// Scene resolution relative to the viewport
+    SetRenderScale(f32),
+    SetStereo(bool),
+    SetAppearance(AppearanceSettings),
+    SetConfirmations(ConfirmSettings),
     Log(String),
 }
 
+#[allow(dead_code)]
 #[derive(Debug, Clone)]
 pub enum AppEvent {
     ImagesLoaded,
@@ -26,7 +207,88 @@ pub enum AppEvent {
     CameraResetDone,
     Status(String),
     Progress(f32),
-    Log(String),
+    /// Share of the loaded cloud on the GPU; None once it's all uploaded
+    UploadProgress(Option<f32>),
+    RenderSettings(RenderSettings),
+    Appearance(AppearanceSettings),
+    Confirmations(ConfirmSettings),
+    /// Bad splats were repaired or removed on load; summary of what was fixed
+    CloudSanitized(String),
+    /// Statistics of the cloud just loaded into the viewer
+    CloudStats(CloudStats),
+    /// Name of the GPU in use and of every GPU that could be picked instead
+    GpuAdapters { active: String, available: Vec<String> },
     WireframeState(bool),
-    SceneReady
+    ColorMode(ColorMode),
+    CameraPathChanged(Vec<f32>),
+    CameraPathPlaying(bool),
+    SceneReady,
+    JobCompleted { gaussians: usize, loaded: bool },
+    JobFailed(AppError),
+    ExportFinished(PathBuf),
+    ServiceDisconnected(String),
+    /// The generation service speaks another protocol version
+    ServiceIncompatible(AppError),
+    /// Local id of a job the service has no record of
+    JobStuck(String),
+    /// A job's output file isn't where it was and couldn't be found
+    RelinkNeeded { id: String, missing: PathBuf },
+    /// File picked in the relink dialog's browser
+    RelinkPicked { id: String, path: PathBuf },
+    /// File picked as the comparison reference
+    ComparisonPicked(PathBuf),
+    /// File picked as the morph target
+    MorphTargetPicked(PathBuf),
+    /// Number of splat pairs of the morph shown; None once it was cleared
+    Morph(Option<usize>),
+    MorphPosition { position: f32, playing: bool },
+    /// File picked as the custom splat shader
+    CustomShaderPicked(PathBuf),
+    /// File picked as the input image of new jobs, yet to be added to the library
+    InputImagePicked(PathBuf),
+    /// Images of the input image library, oldest first
+    InputImages(Vec<InputImage>),
+    /// Image added to the library, to condition new jobs on
+    InputImageAdded(PathBuf),
+    /// Folder picked as a COLMAP sparse model, yet to be imported
+    ColmapPicked(PathBuf),
+    /// Capture set imported from a COLMAP model, replacing the form's
+    CaptureSet(Vec<CaptureView>),
+    /// Why the custom splat shader couldn't be used; None once it compiles
+    ShaderError(Option<String>),
+    /// Metrics against the reference cloud; None when the comparison was cleared
+    Comparison(Option<ComparisonMetrics>),
+    /// Number of selected splats
+    SelectionChanged(usize),
+    ClipPlanes(Vec<ClipPlane>),
+    Brush(Option<PaintBrush>),
+    Volume(Option<Volume>),
+    /// The color grade was baked into the scene and the live grade reset
+    ColorGradeBaked,
+    DepthOfField(Option<DepthOfField>),
+    SceneLoadFailed(AppError),
+    JobsChanged(Vec<JobMetadata>),
+    /// The last run didn't exit cleanly and left a session to restore
+    SessionRecoverable,
+    /// Who jobs submitted from this app are attributed to
+    LocalSubmitter(Submitter),
+    /// Intermediate render of a running job, by local id
+    JobPreview { id: String, image: Arc<RgbaImage> },
+    QueuePaused(bool),
+    /// GPU memory of the smallest generation worker in MiB, if known
+    WorkerVram(Option<u64>),
+    /// Ids of the models the generation backend runs
+    BackendModels(Vec<String>),
+    /// Parameters each model takes by id, as the service describes them
+    ModelParams(BTreeMap<String, Vec<ParamSpec>>),
+    /// Presets each model has by id
+    ModelPresets(BTreeMap<String, ModelPresets>),
+    /// Jobs on each configured worker by name; None when it can't be reached
+    WorkerLoads(Vec<(String, Option<usize>)>),
+    /// Model weights on each configured worker by name
+    ModelWeights(Vec<(String, Result<Vec<ModelWeights>, AppError>)>),
+    /// GPUs of each configured worker by name
+    WorkerGpus(Vec<(String, Result<Vec<WorkerGpu>, AppError>)>),
+    /// Fill the generation form with these inputs
+    EditJobInputs(JobInputs),
 }
\ No newline at end of file
@@ -0,0 +1,290 @@
+mod http;
+#[cfg(test)]
+pub mod mock;
+pub mod router;
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::AtomicBool;
+use std::time::Duration;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use crate::error::AppError;
+use crate::jobs::{JobInputs, Preset};
+
+pub use http::HttpBackend;
+pub use router::RoutingBackend;
+
+/// Where a submitted job is, as reported by the backend
+#[derive(Debug, Clone, PartialEq)]
+pub enum RemoteState {
+    Queued,
+    Running,
+    /// The backend has no record of the job, e.g. its queue was lost in a restart
+    Unknown,
+    /// Finished; the output PLY is at `path` once [`GenerationBackend::fetch_output`]
+    /// has run for it. `latent` names the latent the service kept for
+    /// decoding it again, if it did. `samples` are the further PLYs of a job
+    /// drawing several, fetched likewise.
+    Succeeded { path: PathBuf, latent: Option<String>, samples: Vec<PathBuf> },
+    Failed(AppError),
+    Cancelled,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct RemoteStatus {
+    pub state: RemoteState,
+    pub progress: Option<f32>,
+    pub message: Option<String>,
+}
+
+/// What a backend can run, from [`GenerationBackend::list_models`]
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ModelList {
+    /// Ids of the models, see [`gj_core::Model3D::id`]
+    pub models: Vec<String>,
+    /// GPU memory of the smallest worker in MiB, if the backend knows it
+    pub vram_mb: Option<u64>,
+    /// Parameters each model takes by id; empty from services older than
+    /// schemas, see [`ParamSpec::builtin`]
+    pub params: BTreeMap<String, Vec<ParamSpec>>,
+    /// Presets of each model by id, likewise; see [`builtin_presets`]
+    pub presets: BTreeMap<String, ModelPresets>,
+    /// Ids of the generators LGM jobs can draw their views with, see
+    /// [`gj_core::pipeline::TextToMultiviewConfig::model`]
+    pub multiview: Vec<String>,
+}
+
+/// Parameter values each preset sets, by [`Preset::id`]
+pub type ModelPresets = BTreeMap<String, BTreeMap<String, Value>>;
+
+/// Presets of `model` for services from before presets, in the parameters
+/// those take
+pub fn builtin_presets(model: &str) -> ModelPresets {
+    if model != gj_core::Model3D::ShapE.id() {
+        return ModelPresets::new();
+    }
+    [(Preset::Draft, 12.0, 32), (Preset::Standard, 15.0, 64), (Preset::High, 17.5, 128)]
+        .into_iter()
+        .map(|(preset, guidance, steps)| {
+            let values = BTreeMap::from([
+                ("guidance_scale".to_string(), json!(guidance)),
+                ("num_inference_steps".to_string(), json!(steps)),
+            ]);
+            (preset.id().to_string(), values)
+        })
+        .collect()
+}
+
+/// Type and range of a model parameter
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ParamKind {
+    Float { min: f64, max: f64 },
+    Int { min: i64, max: i64 },
+    Bool,
+    Choice { options: Vec<String> },
+    /// A type from a newer service; the default is sent unchanged
+    #[serde(other)]
+    Unknown,
+}
+
+/// One knob of a model, as the service's `/models` describes it; the
+/// parameters form is built from these
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct ParamSpec {
+    /// Key in [`JobInputs::params`]
+    pub name: String,
+    #[serde(default)]
+    pub label: String,
+    #[serde(flatten)]
+    pub kind: ParamKind,
+    pub default: Value,
+    #[serde(default)]
+    pub description: String,
+}
+
+impl ParamSpec {
+    /// What services from before schemas accept for `model`
+    pub fn builtin(model: &str) -> Vec<ParamSpec> {
+        if model != gj_core::Model3D::ShapE.id() {
+            return Vec::new();
+        }
+        vec![
+            ParamSpec {
+                name: "guidance_scale".into(),
+                label: "Guidance".into(),
+                kind: ParamKind::Float { min: 1.0, max: 30.0 },
+                default: json!(15.0),
+                description: String::new(),
+            },
+            ParamSpec {
+                name: "num_inference_steps".into(),
+                label: "Steps".into(),
+                kind: ParamKind::Int { min: 16, max: 256 },
+                default: json!(64),
+                description: String::new(),
+            },
+        ]
+    }
+
+    pub fn label(&self) -> &str {
+        if self.label.is_empty() { &self.name } else { &self.label }
+    }
+
+    /// `value` if it fits this parameter, brought into range if it's a
+    /// number out of it, otherwise the default
+    pub fn sanitize(&self, value: Option<&Value>) -> Value {
+        let Some(value) = value else {
+            return self.default.clone();
+        };
+        match &self.kind {
+            ParamKind::Float { min, max } => value.as_f64().map_or(self.default.clone(), |v| json!(v.clamp(*min, *max))),
+            ParamKind::Int { min, max } => value.as_i64().map_or(self.default.clone(), |v| json!(v.clamp(*min, *max))),
+            ParamKind::Bool if value.is_boolean() => value.clone(),
+            ParamKind::Choice { options } if value.as_str().is_some_and(|v| options.iter().any(|o| o == v)) => value.clone(),
+            ParamKind::Unknown => value.clone(),
+            _ => self.default.clone(),
+        }
+    }
+}
+
+/// Where a model's weights are on a service's workers
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WeightsStatus {
+    Missing,
+    /// Download or removal requested, waiting for a free worker
+    Queued,
+    Downloading,
+    Ready,
+    Removing,
+    Failed,
+    /// No worker has reported yet
+    #[serde(other)]
+    Unknown,
+}
+
+impl WeightsStatus {
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Missing => "Not downloaded",
+            Self::Queued => "Queued",
+            Self::Downloading => "Downloading",
+            Self::Ready => "Ready",
+            Self::Removing => "Removing",
+            Self::Failed => "Failed",
+            Self::Unknown => "Unknown",
+        }
+    }
+
+    /// Waiting on a worker, so worth checking again soon
+    pub fn is_busy(self) -> bool {
+        matches!(self, Self::Queued | Self::Downloading | Self::Removing)
+    }
+}
+
+/// A model's weights as the service's model manager reports them
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct ModelWeights {
+    pub id: String,
+    pub name: String,
+    pub description: String,
+    pub status: WeightsStatus,
+    /// Rough size once downloaded
+    pub expected_bytes: u64,
+    #[serde(default)]
+    pub disk_bytes: Option<u64>,
+    /// Share downloaded so far, while downloading
+    #[serde(default)]
+    pub progress: Option<f32>,
+    /// Why the last download failed
+    #[serde(default)]
+    pub error: Option<String>,
+}
+
+/// A GPU of a generation worker, as reported by the service's `/health`
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct WorkerGpu {
+    /// Host name of the worker process
+    pub worker: String,
+    /// Device index jobs pass to run on it
+    pub index: u32,
+    pub name: String,
+    pub total_mb: u64,
+    /// Memory in use, by every process when the worker can tell
+    pub used_mb: u64,
+    /// Whether the worker runs its jobs on this one
+    #[serde(default)]
+    pub active: bool,
+}
+
+impl WorkerGpu {
+    pub fn usage(&self) -> f32 {
+        if self.total_mb == 0 { 0.0 } else { self.used_mb as f32 / self.total_mb as f32 }
+    }
+}
+
+/// Something that can run generation jobs: the Python service over HTTP today,
+/// local inference or a remote farm later.
+/// Calls may block; they are only made from the worker thread.
+pub trait GenerationBackend: Send {
+    /// Display name for logs and status messages
+    fn name(&self) -> &str;
+
+    /// Submit a job, returning the backend's id for it
+    fn submit(&self, inputs: &JobInputs) -> Result<String, AppError>;
+
+    fn cancel(&self, remote_id: &str) -> Result<(), AppError>;
+
+    fn status(&self, remote_id: &str) -> Result<RemoteStatus, AppError>;
+
+    /// Log lines the backend recorded for a job, starting at line `since`
+    fn logs(&self, _remote_id: &str, _since: usize) -> Result<Vec<String>, AppError> {
+        Ok(Vec::new())
+    }
+
+    /// Latest intermediate render of a running job as PNG, if it has one
+    fn preview(&self, _remote_id: &str) -> Result<Option<Vec<u8>>, AppError> {
+        Ok(None)
+    }
+
+    /// Make an output of a finished job readable at `path`, copying it over
+    /// when the backend wrote it where this machine can't see it. Reports
+    /// the fraction copied to `progress`, and gives up with
+    /// [`AppError::Cancelled`] once `cancel` is set.
+    fn fetch_output(
+        &self,
+        _remote_id: &str,
+        _path: &Path,
+        _progress: &mut dyn FnMut(f32),
+        _cancel: &AtomicBool,
+    ) -> Result<(), AppError> {
+        Ok(())
+    }
+
+    /// Models this backend can run and the memory it has for them
+    fn list_models(&self) -> Result<ModelList, AppError>;
+
+    /// Jobs queued or running on the backend, from every client
+    fn load(&self) -> Result<usize, AppError> {
+        Ok(0)
+    }
+
+    /// How long to wait between status checks
+    fn poll_interval(&self) -> Duration {
+        Duration::from_secs(2)
+    }
+
+    /// Wait before the first retry of a failed submission; doubled for each
+    /// retry after that
+    fn retry_delay(&self) -> Duration {
+        Duration::from_secs(1)
+    }
+
+    /// How long a job may stay queued with no record on the backend before
+    /// it's reported as stuck
+    fn stuck_after(&self) -> Duration {
+        Duration::from_secs(120)
+    }
+}
\ No newline at end of file
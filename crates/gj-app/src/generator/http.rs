@@ -0,0 +1,788 @@
+use std::collections::{BTreeMap, HashMap};
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::SystemTime;
+use reqwest::Method;
+use reqwest::blocking::RequestBuilder;
+use serde::{Deserialize, Serialize};
+use crate::error::AppError;
+use crate::generator::{GenerationBackend, ModelList, ModelPresets, ModelWeights, ParamSpec, RemoteState, RemoteStatus, WorkerGpu};
+use crate::jobs::{CaptureCamera, CaptureView, JobInputs};
+
+/// Backend talking to the FastAPI/Celery service in `python/`
+pub struct HttpBackend {
+    base_url: String,
+    /// Outputs root the service is asked to write to
+    output_dir: PathBuf,
+    /// Bearer token the service was started with (its `API_TOKEN`)
+    token: Option<String>,
+    /// Set once the service's protocol version is known to match
+    compatible: OnceLock<()>,
+    /// Upload ids of the input images sent so far, by path and modification
+    /// time, so jobs given the same image don't upload it again
+    uploads: Mutex<HashMap<(PathBuf, SystemTime), String>>,
+    client: reqwest::blocking::Client,
+}
+
+impl HttpBackend {
+    pub const DEFAULT_URL: &str = "http://127.0.0.1:5000";
+    /// Shape of the service's requests and responses this app speaks, the
+    /// service's `PROTOCOL_VERSION`
+    pub const PROTOCOL_VERSION: u64 = 1;
+
+    pub fn new(base_url: &str, output_dir: PathBuf) -> Self {
+        Self {
+            base_url: base_url.trim_end_matches('/').to_string(),
+            output_dir,
+            token: None,
+            compatible: OnceLock::new(),
+            uploads: Mutex::new(HashMap::new()),
+            // rustls, so https:// services work without system OpenSSL
+            client: reqwest::blocking::Client::builder()
+                .use_rustls_tls()
+                .build()
+                .unwrap_or_default(),
+        }
+    }
+
+    /// Authenticate every request with `token`
+    pub fn with_token(mut self, token: Option<String>) -> Self {
+        self.token = token;
+        self
+    }
+
+    fn url(&self, path: &str) -> String {
+        format!("{}{}", self.base_url, path)
+    }
+
+    /// Refuse to go on with a service on another protocol version, rather
+    /// than failing later on responses we can't parse
+    fn ensure_compatible(&self) -> Result<(), AppError> {
+        if self.compatible.get().is_none() {
+            self.list_models()?;
+        }
+        Ok(())
+    }
+
+    /// Fetch a result the service wrote where we can't see it, e.g. on
+    /// another machine, to `path`
+    fn download_output(
+        &self,
+        remote_id: &str,
+        path: &Path,
+        progress: &mut dyn FnMut(f32),
+        cancel: &AtomicBool,
+    ) -> Result<(), AppError> {
+        let name = path
+            .file_name()
+            .and_then(|n| n.to_str())
+            .ok_or_else(|| AppError::Service(format!("Invalid output path: {}", path.display())))?;
+        let url = self.url(&format!("/job/{}/files/{}", remote_id, name));
+        log::info!("GET {}", url);
+
+        let mut response = self.request(Method::GET, &url)
+            .send()
+            .map_err(|e| request_error("Failed to download result", e))?;
+
+        if !response.status().is_success() {
+            return Err(AppError::Service(format!("Downloading result failed: {}", response.status())));
+        }
+
+        // Written aside first, so a broken transfer never passes for a result
+        let partial = path.with_extension("part");
+        let total = response.content_length().filter(|&len| len > 0);
+        let written = (|| -> std::io::Result<bool> {
+            if let Some(parent) = path.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            let mut file = std::fs::File::create(&partial)?;
+            let mut buf = vec![0; 64 * 1024];
+            let mut copied = 0;
+            loop {
+                if cancel.swap(false, Ordering::SeqCst) {
+                    return Ok(false);
+                }
+                let read = response.read(&mut buf)?;
+                if read == 0 {
+                    break;
+                }
+                file.write_all(&buf[..read])?;
+                copied += read as u64;
+                if let Some(total) = total {
+                    progress((copied as f32 / total as f32).min(1.0));
+                }
+            }
+            std::fs::rename(&partial, path)?;
+            Ok(true)
+        })();
+
+        match written {
+            Ok(true) => Ok(()),
+            Ok(false) => {
+                let _ = std::fs::remove_file(&partial);
+                Err(AppError::Cancelled("Cancelled by user".into()))
+            }
+            Err(e) => {
+                let _ = std::fs::remove_file(&partial);
+                Err(AppError::Internal(format!("Failed to save {}: {}", path.display(), e)))
+            }
+        }
+    }
+
+    /// GPUs of the service's running workers with their memory in use
+    pub fn health(&self) -> Result<Vec<WorkerGpu>, AppError> {
+        let url = self.url("/health");
+        log::debug!("GET {}", url);
+
+        let response = self.request(Method::GET, &url)
+            .send()
+            .map_err(|e| request_error("Health check failed", e))?;
+
+        if !response.status().is_success() {
+            return Err(AppError::Service(format!("Health check failed: {}", response.status())));
+        }
+
+        let health: HealthResponse = response
+            .json()
+            .map_err(|e| AppError::Service(format!("Failed to parse health check: {}", e)))?;
+        Ok(health.gpus)
+    }
+
+    /// Weights of each model on the service's workers, for the model manager
+    pub fn list_weights(&self) -> Result<Vec<ModelWeights>, AppError> {
+        let url = self.url("/weights");
+        log::debug!("GET {}", url);
+
+        let response = self.request(Method::GET, &url)
+            .send()
+            .map_err(|e| request_error("Failed to list model weights", e))?;
+
+        if response.status() == reqwest::StatusCode::UNAUTHORIZED {
+            return Err(unauthorized());
+        }
+        if response.status() == reqwest::StatusCode::NOT_FOUND {
+            return Err(AppError::IncompatibleService("the service has no model manager; update it".into()));
+        }
+        if !response.status().is_success() {
+            return Err(AppError::Service(format!("Listing model weights failed: {}", response.status())));
+        }
+
+        let weights: WeightsResponse = response
+            .json()
+            .map_err(|e| AppError::Service(format!("Failed to parse model weights: {}", e)))?;
+        Ok(weights.weights)
+    }
+
+    /// Have a worker fetch `model`'s weights ahead of its first job
+    pub fn download_weights(&self, model: &str) -> Result<(), AppError> {
+        self.weights_request(Method::POST, &format!("/weights/{}/download", model), "Download")
+    }
+
+    /// Have a worker delete `model`'s weights
+    pub fn remove_weights(&self, model: &str) -> Result<(), AppError> {
+        self.weights_request(Method::DELETE, &format!("/weights/{}", model), "Removal")
+    }
+
+    fn weights_request(&self, method: Method, path: &str, action: &str) -> Result<(), AppError> {
+        let url = self.url(path);
+        log::info!("{} {}", method, url);
+
+        let response = self.request(method, &url)
+            .send()
+            .map_err(|e| request_error(&format!("{} request failed", action), e))?;
+
+        if response.status() == reqwest::StatusCode::UNAUTHORIZED {
+            return Err(unauthorized());
+        }
+        if !response.status().is_success() {
+            return Err(AppError::Service(format!("{} of model weights failed: {}", action, response.status())));
+        }
+        Ok(())
+    }
+
+    /// Send the conditioning image at `path` to the service, returning the
+    /// upload id `/generate` takes it by; images sent before aren't sent again
+    fn upload_image(&self, path: &Path) -> Result<String, AppError> {
+        let key = upload_key(path);
+        if let Some(id) = key.as_ref().and_then(|key| self.uploads.lock().ok()?.get(key).cloned()) {
+            return Ok(id);
+        }
+
+        let data = std::fs::read(path)
+            .map_err(|e| AppError::Internal(format!("Failed to read input image {}: {}", path.display(), e)))?;
+        let url = self.url("/uploads");
+        log::info!("POST {} ({}, {} bytes)", url, path.display(), data.len());
+
+        let response = self.request(Method::POST, &url)
+            .body(data)
+            .send()
+            .map_err(|e| request_error("Failed to upload input image", e))?;
+
+        if response.status() == reqwest::StatusCode::UNAUTHORIZED {
+            return Err(unauthorized());
+        }
+        if response.status().is_client_error() {
+            // The service says what's wrong with the image, e.g. its format or size
+            let detail = response.json::<ErrorResponse>().map(|e| e.detail).unwrap_or_default();
+            return Err(AppError::GenerationFailed(format!("Input image {} was refused: {}", path.display(), detail)));
+        }
+        if !response.status().is_success() {
+            return Err(AppError::Service(format!("Uploading input image failed: {}", response.status())));
+        }
+
+        let upload: UploadResponse = response
+            .json()
+            .map_err(|e| AppError::Service(format!("Failed to parse upload response: {}", e)))?;
+        if let (Some(key), Ok(mut uploads)) = (key, self.uploads.lock()) {
+            uploads.insert(key, upload.upload_id.clone());
+        }
+        Ok(upload.upload_id)
+    }
+
+    fn upload_capture_view(&self, view: &CaptureView) -> Result<CaptureUpload, AppError> {
+        Ok(CaptureUpload {
+            upload: self.upload_image(&view.path)?,
+            azimuth: view.azimuth,
+            elevation: view.elevation,
+            camera: view.camera,
+        })
+    }
+
+    /// Stop reusing the upload of the image at `path`, e.g. once the service
+    /// lost it. Returns whether there was one.
+    fn forget_upload(&self, path: &Path) -> bool {
+        let key = upload_key(path);
+        key.is_some_and(|key| self.uploads.lock().is_ok_and(|mut uploads| uploads.remove(&key).is_some()))
+    }
+
+    fn request(&self, method: Method, url: &str) -> RequestBuilder {
+        let request = self.client.request(method, url);
+        match &self.token {
+            Some(token) => request.bearer_auth(token),
+            None => request,
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct GenerateRequest {
+    prompt: String,
+    model: String,
+    guidance_scale: f32,
+    num_inference_steps: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    seed: Option<u64>,
+    output_dir: PathBuf,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    device: Option<u32>,
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    params: BTreeMap<String, serde_json::Value>,
+    /// Upload id of the conditioning image
+    #[serde(skip_serializing_if = "Option::is_none")]
+    image: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    capture: Vec<CaptureUpload>,
+}
+
+/// A photo of a capture set, sent ahead as an upload
+#[derive(Serialize)]
+struct CaptureUpload {
+    upload: String,
+    azimuth: f32,
+    elevation: f32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    camera: Option<CaptureCamera>,
+}
+
+#[derive(Serialize)]
+struct RedecodeRequest {
+    latent: String,
+    model: String,
+    mesh_resolution: u32,
+    splat_scale: f32,
+    output_dir: PathBuf,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    device: Option<u32>,
+}
+
+#[derive(Serialize)]
+struct RefineRequest {
+    source: String,
+    prompt: String,
+    iterations: u32,
+    guidance_scale: f32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    seed: Option<u64>,
+    output_dir: PathBuf,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    device: Option<u32>,
+}
+
+#[allow(dead_code)]
+#[derive(Deserialize)]
+struct JobResponse {
+    job_id: String,
+    status: String,
+}
+
+#[derive(Deserialize)]
+struct UploadResponse {
+    upload_id: String,
+}
+
+/// Body of FastAPI's HTTP errors
+#[derive(Deserialize)]
+struct ErrorResponse {
+    detail: String,
+}
+
+#[allow(dead_code)]
+#[derive(Deserialize)]
+struct JobStatusResponse {
+    job_id: String,
+    status: String,
+    progress: Option<f32>,
+    message: Option<String>,
+    result: Option<JobResult>,
+    error: Option<String>,
+}
+
+#[allow(dead_code)]
+#[derive(Deserialize)]
+struct JobResult {
+    output_path: String,
+    /// Latent kept for re-decoding, from models that keep one
+    #[serde(default)]
+    latent_path: Option<String>,
+    /// Further samples of jobs drawing several
+    #[serde(default)]
+    sample_paths: Vec<String>,
+    model: String,
+    prompt: String,
+}
+
+#[derive(Deserialize)]
+struct JobLogResponse {
+    lines: Vec<String>,
+}
+
+/// Celery's view of the queue: task lists keyed by worker host
+#[derive(Deserialize)]
+struct QueueResponse {
+    active: HashMap<String, Vec<serde_json::Value>>,
+    scheduled: HashMap<String, Vec<serde_json::Value>>,
+    reserved: HashMap<String, Vec<serde_json::Value>>,
+}
+
+#[derive(Deserialize)]
+struct ModelsResponse {
+    models: Vec<String>,
+    /// Older services don't report worker memory
+    #[serde(default)]
+    vram_mb: Option<u64>,
+    /// Nor parameter schemas
+    #[serde(default)]
+    params: BTreeMap<String, Vec<ParamSpec>>,
+    #[serde(default)]
+    presets: BTreeMap<String, ModelPresets>,
+    #[serde(default)]
+    multiview: Vec<String>,
+}
+
+#[derive(Deserialize)]
+struct HealthResponse {
+    /// Older services don't report their GPUs
+    #[serde(default)]
+    gpus: Vec<WorkerGpu>,
+}
+
+#[derive(Deserialize)]
+struct WeightsResponse {
+    weights: Vec<ModelWeights>,
+}
+
+/// Categorize a failed HTTP request to the generation service
+fn request_error(context: &str, e: reqwest::Error) -> AppError {
+    let message = format!("{}: {}", context, e);
+    if e.is_connect() {
+        AppError::ServiceUnreachable(message)
+    } else if e.is_timeout() {
+        AppError::Timeout(message)
+    } else {
+        AppError::Service(message)
+    }
+}
+
+/// The service wants a different token, or one where none was sent
+fn unauthorized() -> AppError {
+    AppError::Service("The service rejected the API token; check `token` for this worker in settings.json".into())
+}
+
+/// Check the `protocol` a service reports in its handshake; services from
+/// before versioning send none and count as version 0
+fn check_protocol(body: &serde_json::Value) -> Result<(), AppError> {
+    let version = body.get("protocol").and_then(serde_json::Value::as_u64).unwrap_or(0);
+    if version != HttpBackend::PROTOCOL_VERSION {
+        return Err(AppError::IncompatibleService(format!(
+            "the service speaks protocol version {}, this app version {}",
+            version,
+            HttpBackend::PROTOCOL_VERSION
+        )));
+    }
+    Ok(())
+}
+
+/// What an uploaded image is remembered by: its path and when it last changed
+fn upload_key(path: &Path) -> Option<(PathBuf, SystemTime)> {
+    let modified = std::fs::metadata(path).and_then(|m| m.modified()).ok()?;
+    Some((path.to_path_buf(), modified))
+}
+
+/// Map an output path as seen by the service to one readable from here.
+/// A local service writes straight into `output_dir`; one in a container
+/// reports its own mount point, so fall back to the same file name under
+/// `output_dir`, where it's downloaded to if the mount isn't shared with us.
+fn host_output_path(output_path: &str, output_dir: &Path) -> Result<PathBuf, AppError> {
+    let path = Path::new(output_path);
+    if path.is_absolute() && path.exists() {
+        return Ok(path.to_path_buf());
+    }
+
+    let filename = path
+        .file_name()
+        .ok_or_else(|| AppError::Service(format!("Invalid output path: {}", output_path)))?;
+    Ok(output_dir.join(filename))
+}
+
+impl GenerationBackend for HttpBackend {
+    fn name(&self) -> &str {
+        "generation service"
+    }
+
+    fn submit(&self, inputs: &JobInputs) -> Result<String, AppError> {
+        self.ensure_compatible()?;
+
+        let request = match (&inputs.redecode, &inputs.refine) {
+            (Some(redecode), _) => {
+                let url = self.url("/redecode");
+                log::info!("POST {} (model: {}, latent: {})", url, inputs.model, redecode.latent);
+                self.request(Method::POST, &url).json(&RedecodeRequest {
+                    latent: redecode.latent.clone(),
+                    model: inputs.model.clone(),
+                    mesh_resolution: redecode.settings.mesh_resolution,
+                    splat_scale: redecode.settings.splat_scale,
+                    output_dir: self.output_dir.clone(),
+                    device: inputs.device,
+                })
+            }
+            (None, Some(refine)) => {
+                let url = self.url("/refine");
+                log::info!("POST {} (source: {}, prompt: {:?})", url, refine.ply, inputs.prompt);
+                self.request(Method::POST, &url).json(&RefineRequest {
+                    source: refine.ply.clone(),
+                    prompt: inputs.prompt.clone(),
+                    iterations: refine.settings.iterations,
+                    guidance_scale: refine.settings.guidance_scale,
+                    seed: inputs.seed,
+                    output_dir: self.output_dir.clone(),
+                    device: inputs.device,
+                })
+            }
+            (None, None) => {
+                let url = self.url("/generate");
+                log::info!("POST {} (model: {}, prompt: {:?})", url, inputs.model, inputs.prompt);
+                self.request(Method::POST, &url).json(&GenerateRequest {
+                    prompt: inputs.prompt.clone(),
+                    model: inputs.model.clone(),
+                    guidance_scale: inputs.guidance_scale,
+                    num_inference_steps: inputs.num_inference_steps,
+                    seed: inputs.seed,
+                    output_dir: self.output_dir.clone(),
+                    device: inputs.device,
+                    params: inputs.params.clone(),
+                    image: inputs.image.as_deref().map(|path| self.upload_image(path)).transpose()?,
+                    capture: inputs.capture.iter()
+                        .map(|view| self.upload_capture_view(view))
+                        .collect::<Result<_, _>>()?,
+                })
+            }
+        };
+
+        let response = request
+            .send()
+            .map_err(|e| request_error("Failed to submit job", e))?;
+
+        if response.status() == reqwest::StatusCode::UNAUTHORIZED {
+            return Err(unauthorized());
+        }
+        // The service forgot an image we uploaded before; send them again
+        if response.status() == reqwest::StatusCode::UNPROCESSABLE_ENTITY {
+            let images = inputs.image.iter().chain(inputs.capture.iter().map(|view| &view.path));
+            let forgotten = images.filter(|path| self.forget_upload(path)).count();
+            if forgotten > 0 {
+                return self.submit(inputs);
+            }
+        }
+        if response.status() == reqwest::StatusCode::NOT_FOUND && inputs.source_job().is_some() {
+            return Err(AppError::IncompatibleService("the service can't run second-stage jobs; update it".into()));
+        }
+        if !response.status().is_success() {
+            return Err(AppError::Service(format!("Service returned error: {}", response.status())));
+        }
+
+        let result: JobResponse = response
+            .json()
+            .map_err(|e| AppError::Service(format!("Failed to parse response: {}", e)))?;
+
+        log::info!("Job {} submitted", result.job_id);
+        Ok(result.job_id)
+    }
+
+    fn cancel(&self, remote_id: &str) -> Result<(), AppError> {
+        let url = self.url(&format!("/cancel/{}", remote_id));
+        log::info!("DELETE {}", url);
+
+        let response = self.request(Method::DELETE, &url)
+            .send()
+            .map_err(|e| request_error("Failed to cancel job", e))?;
+
+        if !response.status().is_success() {
+            return Err(AppError::Service(format!("Cancel failed: {}", response.status())));
+        }
+        Ok(())
+    }
+
+    fn status(&self, remote_id: &str) -> Result<RemoteStatus, AppError> {
+        let url = self.url(&format!("/status/{}", remote_id));
+        log::debug!("GET {}", url);
+
+        let response = self.request(Method::GET, &url)
+            .send()
+            .map_err(|e| request_error("Failed to check status", e))?;
+
+        if !response.status().is_success() {
+            return Err(AppError::Service(format!("Status check failed: {}", response.status())));
+        }
+
+        let status: JobStatusResponse = response
+            .json()
+            .map_err(|e| AppError::Service(format!("Failed to parse status: {}", e)))?;
+
+        let state = match status.status.as_str() {
+            "SUCCESS" => {
+                let result = status.result
+                    .ok_or_else(|| AppError::Service("Job succeeded but no result path returned".into()))?;
+                let path = host_output_path(&result.output_path, &self.output_dir)?;
+                // Only the name; the service finds it in its own output directory
+                let latent = result.latent_path
+                    .and_then(|p| Some(Path::new(&p).file_name()?.to_str()?.to_string()));
+                let samples = result.sample_paths.iter()
+                    .map(|sample| host_output_path(sample, &self.output_dir))
+                    .collect::<Result<_, AppError>>()?;
+                RemoteState::Succeeded { path, latent, samples }
+            }
+            "FAILURE" => {
+                let error_msg = status.error.unwrap_or_else(|| "Unknown error".into());
+                RemoteState::Failed(AppError::classify(&error_msg))
+            }
+            "REVOKED" => RemoteState::Cancelled,
+            "STARTED" | "RETRY" => RemoteState::Running,
+            "UNKNOWN" => RemoteState::Unknown,
+            // PENDING, or anything we don't know yet
+            _ => RemoteState::Queued,
+        };
+
+        Ok(RemoteStatus {
+            state,
+            progress: status.progress,
+            message: status.message,
+        })
+    }
+
+    /// Outputs under a shared output directory are already in place
+    fn fetch_output(
+        &self,
+        remote_id: &str,
+        path: &Path,
+        progress: &mut dyn FnMut(f32),
+        cancel: &AtomicBool,
+    ) -> Result<(), AppError> {
+        if path.exists() {
+            return Ok(());
+        }
+        self.download_output(remote_id, path, progress, cancel)
+    }
+
+    fn logs(&self, remote_id: &str, since: usize) -> Result<Vec<String>, AppError> {
+        let url = self.url(&format!("/job/{}/log?since={}", remote_id, since));
+        log::debug!("GET {}", url);
+
+        let response = self.request(Method::GET, &url)
+            .send()
+            .map_err(|e| request_error("Failed to fetch job log", e))?;
+
+        if !response.status().is_success() {
+            return Err(AppError::Service(format!("Fetching job log failed: {}", response.status())));
+        }
+
+        let log: JobLogResponse = response
+            .json()
+            .map_err(|e| AppError::Service(format!("Failed to parse job log: {}", e)))?;
+        Ok(log.lines)
+    }
+
+    fn preview(&self, remote_id: &str) -> Result<Option<Vec<u8>>, AppError> {
+        let url = self.url(&format!("/job/{}/preview", remote_id));
+        log::debug!("GET {}", url);
+
+        let response = self.request(Method::GET, &url)
+            .send()
+            .map_err(|e| request_error("Failed to fetch job preview", e))?;
+
+        if response.status() == reqwest::StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+        if !response.status().is_success() {
+            return Err(AppError::Service(format!("Fetching job preview failed: {}", response.status())));
+        }
+
+        let png = response
+            .bytes()
+            .map_err(|e| request_error("Failed to read job preview", e))?;
+        Ok(Some(png.to_vec()))
+    }
+
+    fn list_models(&self) -> Result<ModelList, AppError> {
+        let url = self.url("/models");
+        log::debug!("GET {}", url);
+
+        let response = self.request(Method::GET, &url)
+            .send()
+            .map_err(|e| request_error("Failed to list models", e))?;
+
+        if response.status() == reqwest::StatusCode::UNAUTHORIZED {
+            return Err(unauthorized());
+        }
+        if !response.status().is_success() {
+            return Err(AppError::Service(format!("Listing models failed: {}", response.status())));
+        }
+
+        // Versions are checked before the rest of the body is trusted
+        let body: serde_json::Value = response
+            .json()
+            .map_err(|e| AppError::Service(format!("Failed to parse models: {}", e)))?;
+        check_protocol(&body)?;
+        let _ = self.compatible.set(());
+
+        let models: ModelsResponse = serde_json::from_value(body)
+            .map_err(|e| AppError::Service(format!("Failed to parse models: {}", e)))?;
+        Ok(ModelList {
+            models: models.models,
+            vram_mb: models.vram_mb,
+            params: models.params,
+            presets: models.presets,
+            multiview: models.multiview,
+        })
+    }
+
+    fn load(&self) -> Result<usize, AppError> {
+        let url = self.url("/queue");
+        log::debug!("GET {}", url);
+
+        let response = self.request(Method::GET, &url)
+            .send()
+            .map_err(|e| request_error("Failed to check queue", e))?;
+
+        if !response.status().is_success() {
+            return Err(AppError::Service(format!("Queue check failed: {}", response.status())));
+        }
+
+        let queue: QueueResponse = response
+            .json()
+            .map_err(|e| AppError::Service(format!("Failed to parse queue: {}", e)))?;
+        Ok([queue.active, queue.scheduled, queue.reserved]
+            .iter()
+            .flat_map(|tasks| tasks.values())
+            .map(Vec::len)
+            .sum())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+    use crate::generator::{ParamKind, WeightsStatus};
+    use super::*;
+
+    #[test]
+    fn test_protocol_check() {
+        assert!(check_protocol(&json!({ "models": [], "protocol": HttpBackend::PROTOCOL_VERSION })).is_ok());
+        assert!(matches!(
+            check_protocol(&json!({ "models": [] })),
+            Err(AppError::IncompatibleService(_))
+        ));
+        assert!(matches!(
+            check_protocol(&json!({ "protocol": HttpBackend::PROTOCOL_VERSION + 1 })),
+            Err(AppError::IncompatibleService(_))
+        ));
+    }
+
+    #[test]
+    fn test_parse_weights() {
+        let body = json!({ "weights": [
+            {
+                "id": "shap_e", "name": "Shap-E", "description": "", "expected_bytes": 100,
+                "status": "downloading", "disk_bytes": 40, "progress": 0.4, "error": null
+            },
+            { "id": "next", "name": "Next", "description": "", "expected_bytes": 5, "status": "evicted" },
+        ]});
+        let weights: WeightsResponse = serde_json::from_value(body).unwrap();
+
+        assert_eq!(weights.weights[0].status, WeightsStatus::Downloading);
+        assert_eq!(weights.weights[0].progress, Some(0.4));
+        // States from newer services don't break older apps
+        assert_eq!(weights.weights[1].status, WeightsStatus::Unknown);
+        assert_eq!(weights.weights[1].disk_bytes, None);
+    }
+
+    #[test]
+    fn test_parse_param_schemas() {
+        let body = json!({ "models": ["shap_e"], "protocol": 1, "params": { "shap_e": [
+            { "name": "guidance_scale", "label": "Guidance", "type": "float", "min": 1.0, "max": 30.0, "default": 15.0 },
+            { "name": "sampler", "type": "choice", "options": ["ddim", "karras"], "default": "karras" },
+            { "name": "camera", "type": "vec3", "default": [0, 0, 1] },
+        ]}, "presets": { "shap_e": { "draft": { "guidance_scale": 12.0 }, "ultra": {} } } });
+        let models: ModelsResponse = serde_json::from_value(body).unwrap();
+        let params = &models.params["shap_e"];
+
+        assert_eq!(params[0].kind, ParamKind::Float { min: 1.0, max: 30.0 });
+        assert_eq!(params[0].sanitize(Some(&json!(99.0))), json!(30.0));
+        assert_eq!(params[1].label(), "sampler");
+        assert_eq!(params[1].sanitize(Some(&json!("euler"))), json!("karras"));
+        // Types from newer services don't break older apps
+        assert_eq!(params[2].kind, ParamKind::Unknown);
+        assert_eq!(models.presets["shap_e"]["draft"]["guidance_scale"], json!(12.0));
+
+        let old: ModelsResponse = serde_json::from_value(json!({ "models": ["shap_e"] })).unwrap();
+        assert!(old.params.is_empty());
+        assert!(old.presets.is_empty());
+        assert!(old.multiview.is_empty());
+    }
+
+    #[test]
+    fn test_parse_health_gpus() {
+        let body = json!({ "status": "healthy", "gpus": [
+            { "worker": "gpu-box", "index": 1, "name": "RTX 4090", "total_mb": 24564, "used_mb": 6141, "active": true },
+        ]});
+        let health: HealthResponse = serde_json::from_value(body).unwrap();
+        assert_eq!(health.gpus[0].index, 1);
+        assert!(health.gpus[0].active);
+        assert!((health.gpus[0].usage() - 0.25).abs() < 0.01);
+
+        // Services from before GPU reporting
+        let health: HealthResponse = serde_json::from_value(json!({ "status": "healthy" })).unwrap();
+        assert!(health.gpus.is_empty());
+    }
+}
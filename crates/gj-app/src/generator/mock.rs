@@ -0,0 +1,123 @@
+use std::collections::VecDeque;
+use std::path::Path;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+use crate::error::AppError;
+use crate::generator::{GenerationBackend, ModelList, RemoteState, RemoteStatus};
+use crate::jobs::JobInputs;
+
+/// Backend that replays a scripted sequence of states, for tests
+pub struct MockBackend {
+    states: Mutex<VecDeque<RemoteState>>,
+    models: Vec<String>,
+    load: usize,
+    /// Submissions left to fail as if the service were down
+    submit_failures: Mutex<usize>,
+    /// Contents of outputs missing locally, as if written on another machine
+    output: Option<Vec<u8>>,
+}
+
+impl MockBackend {
+    /// Each status check returns the next state; the last one repeats
+    pub fn new(states: Vec<RemoteState>) -> Self {
+        Self {
+            states: Mutex::new(states.into()),
+            models: vec!["shap_e".into()],
+            load: 0,
+            submit_failures: Mutex::new(0),
+            output: None,
+        }
+    }
+
+    pub fn with_models(mut self, models: &[&str]) -> Self {
+        self.models = models.iter().map(|m| m.to_string()).collect();
+        self
+    }
+
+    /// Fail the first `count` submissions as unreachable
+    pub fn with_submit_failures(self, count: usize) -> Self {
+        *self.submit_failures.lock().unwrap() = count;
+        self
+    }
+
+    /// Serve `output` for results that aren't on disk yet
+    pub fn with_output(mut self, output: Vec<u8>) -> Self {
+        self.output = Some(output);
+        self
+    }
+
+    /// Jobs the backend reports as already queued
+    pub fn with_load(mut self, load: usize) -> Self {
+        self.load = load;
+        self
+    }
+}
+
+impl GenerationBackend for MockBackend {
+    fn name(&self) -> &str {
+        "mock"
+    }
+
+    fn submit(&self, _inputs: &JobInputs) -> Result<String, AppError> {
+        let mut failures = self.submit_failures.lock().unwrap();
+        if *failures > 0 {
+            *failures -= 1;
+            return Err(AppError::ServiceUnreachable("mock is down".into()));
+        }
+        Ok("mock-job".into())
+    }
+
+    fn cancel(&self, _remote_id: &str) -> Result<(), AppError> {
+        Ok(())
+    }
+
+    fn status(&self, _remote_id: &str) -> Result<RemoteStatus, AppError> {
+        let mut states = self.states.lock().unwrap();
+        let state = if states.len() > 1 {
+            states.pop_front().unwrap()
+        } else {
+            states.front().cloned().unwrap_or(RemoteState::Queued)
+        };
+
+        Ok(RemoteStatus { state, progress: None, message: None })
+    }
+
+    fn fetch_output(
+        &self,
+        _remote_id: &str,
+        path: &Path,
+        progress: &mut dyn FnMut(f32),
+        cancel: &AtomicBool,
+    ) -> Result<(), AppError> {
+        let Some(output) = self.output.as_ref().filter(|_| !path.exists()) else {
+            return Ok(());
+        };
+        progress(0.5);
+        if cancel.swap(false, Ordering::SeqCst) {
+            return Err(AppError::Cancelled("Cancelled by user".into()));
+        }
+        progress(1.0);
+        std::fs::write(path, output).map_err(|e| AppError::Internal(e.to_string()))
+    }
+
+    fn list_models(&self) -> Result<ModelList, AppError> {
+        Ok(ModelList { models: self.models.clone(), ..Default::default() })
+    }
+
+    fn load(&self) -> Result<usize, AppError> {
+        Ok(self.load)
+    }
+
+    fn poll_interval(&self) -> Duration {
+        Duration::ZERO
+    }
+
+    fn retry_delay(&self) -> Duration {
+        Duration::ZERO
+    }
+
+    fn stuck_after(&self) -> Duration {
+        Duration::ZERO
+    }
+}
\ No newline at end of file
@@ -0,0 +1,243 @@
+use std::path::Path;
+use std::sync::Mutex;
+use std::sync::atomic::AtomicBool;
+use std::time::Duration;
+use crate::error::AppError;
+use crate::generator::{GenerationBackend, ModelList, RemoteStatus};
+use crate::jobs::JobInputs;
+
+/// Backend spreading jobs over several named workers. Each job goes to the
+/// least loaded worker that runs its model; its remote id becomes
+/// `<worker>/<id>` so later calls find the same worker again.
+pub struct RoutingBackend {
+    routes: Vec<Route>,
+}
+
+struct Route {
+    name: String,
+    backend: Box<dyn GenerationBackend>,
+    /// Models the worker offered when last asked
+    models: Mutex<Option<Vec<String>>>,
+}
+
+impl Route {
+    fn supports(&self, model: &str) -> bool {
+        let mut models = self.models.lock().unwrap();
+        if models.is_none() {
+            match self.backend.list_models() {
+                Ok(list) => *models = Some(list.models),
+                Err(e) => log::warn!("Could not list models on worker {}: {}", self.name, e),
+            }
+        }
+        models.as_ref().is_some_and(|m| m.iter().any(|id| id == model))
+    }
+}
+
+/// Worker a routed remote id was sent to
+pub fn worker_of(remote_id: &str) -> Option<&str> {
+    remote_id.split_once('/').map(|(worker, _)| worker)
+}
+
+impl RoutingBackend {
+    pub fn new(workers: Vec<(String, Box<dyn GenerationBackend>)>) -> Self {
+        let routes = workers
+            .into_iter()
+            .map(|(name, backend)| Route { name, backend, models: Mutex::new(None) })
+            .collect();
+        Self { routes }
+    }
+
+    /// Worker and its own job id for a routed remote id. Ids from before
+    /// routing was set up belong to the first worker.
+    fn route(&self, remote_id: &str) -> Result<(&Route, String), AppError> {
+        let first = self.routes.first().ok_or_else(|| AppError::Internal("No workers configured".into()))?;
+        let Some((name, id)) = remote_id.split_once('/') else {
+            return Ok((first, remote_id.to_string()));
+        };
+        let route = self.routes.iter()
+            .find(|r| r.name == name)
+            .ok_or_else(|| AppError::Service(format!("Unknown worker {}", name)))?;
+        Ok((route, id.to_string()))
+    }
+}
+
+impl GenerationBackend for RoutingBackend {
+    fn name(&self) -> &str {
+        "generation workers"
+    }
+
+    fn submit(&self, inputs: &JobInputs) -> Result<String, AppError> {
+        // Files of earlier jobs are only on the worker that wrote them
+        if let Some(source) = inputs.source_job() {
+            let (route, _) = self.route(source)?;
+            log::info!("Routing job to worker {}, which ran job {}", route.name, source);
+            let id = route.backend.submit(inputs)?;
+            return Ok(format!("{}/{}", route.name, id));
+        }
+
+        let mut best: Option<(&Route, usize)> = None;
+        let mut last_error = None;
+        for route in self.routes.iter().filter(|r| r.supports(&inputs.model)) {
+            match route.backend.load() {
+                Ok(load) if best.is_none_or(|(_, least)| load < least) => best = Some((route, load)),
+                Ok(_) => {}
+                Err(e) => {
+                    log::warn!("Skipping worker {}: {}", route.name, e);
+                    last_error = Some(e);
+                }
+            }
+        }
+
+        let (route, load) = best.ok_or_else(|| {
+            last_error.unwrap_or_else(|| AppError::ModelNotLoaded(format!("No worker runs {}", inputs.model)))
+        })?;
+        log::info!("Routing job to worker {} ({} jobs ahead)", route.name, load);
+        let id = route.backend.submit(inputs)?;
+        Ok(format!("{}/{}", route.name, id))
+    }
+
+    fn cancel(&self, remote_id: &str) -> Result<(), AppError> {
+        let (route, id) = self.route(remote_id)?;
+        route.backend.cancel(&id)
+    }
+
+    fn status(&self, remote_id: &str) -> Result<RemoteStatus, AppError> {
+        let (route, id) = self.route(remote_id)?;
+        route.backend.status(&id)
+    }
+
+    fn logs(&self, remote_id: &str, since: usize) -> Result<Vec<String>, AppError> {
+        let (route, id) = self.route(remote_id)?;
+        route.backend.logs(&id, since)
+    }
+
+    fn preview(&self, remote_id: &str) -> Result<Option<Vec<u8>>, AppError> {
+        let (route, id) = self.route(remote_id)?;
+        route.backend.preview(&id)
+    }
+
+    fn fetch_output(
+        &self,
+        remote_id: &str,
+        path: &Path,
+        progress: &mut dyn FnMut(f32),
+        cancel: &AtomicBool,
+    ) -> Result<(), AppError> {
+        let (route, id) = self.route(remote_id)?;
+        route.backend.fetch_output(&id, path, progress, cancel)
+    }
+
+    /// Every model some worker runs, with the smallest worker's memory.
+    /// Also refreshes which worker runs what.
+    fn list_models(&self) -> Result<ModelList, AppError> {
+        let mut all = ModelList::default();
+        let mut last_error = None;
+        for route in &self.routes {
+            match route.backend.list_models() {
+                Ok(list) => {
+                    for model in &list.models {
+                        if !all.models.contains(model) {
+                            all.models.push(model.clone());
+                        }
+                    }
+                    for (model, params) in &list.params {
+                        all.params.entry(model.clone()).or_insert_with(|| params.clone());
+                    }
+                    for (model, presets) in &list.presets {
+                        all.presets.entry(model.clone()).or_insert_with(|| presets.clone());
+                    }
+                    for generator in &list.multiview {
+                        if !all.multiview.contains(generator) {
+                            all.multiview.push(generator.clone());
+                        }
+                    }
+                    all.vram_mb = match (all.vram_mb, list.vram_mb) {
+                        (Some(a), Some(b)) => Some(a.min(b)),
+                        (a, b) => a.or(b),
+                    };
+                    *route.models.lock().unwrap() = Some(list.models);
+                }
+                Err(e) => {
+                    log::warn!("Could not list models on worker {}: {}", route.name, e);
+                    last_error = Some(e);
+                }
+            }
+        }
+
+        match last_error {
+            Some(e) if all.models.is_empty() => Err(e),
+            _ => Ok(all),
+        }
+    }
+
+    fn load(&self) -> Result<usize, AppError> {
+        Ok(self.routes.iter().filter_map(|r| r.backend.load().ok()).sum())
+    }
+
+    fn poll_interval(&self) -> Duration {
+        self.routes.iter().map(|r| r.backend.poll_interval()).min().unwrap_or(Duration::from_secs(2))
+    }
+
+    fn stuck_after(&self) -> Duration {
+        self.routes.iter().map(|r| r.backend.stuck_after()).max().unwrap_or(Duration::from_secs(120))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generator::RemoteState;
+    use crate::generator::mock::MockBackend;
+    use crate::jobs::{DecodeSettings, Redecode};
+
+    fn worker(name: &str, models: &[&str], load: usize) -> (String, Box<dyn GenerationBackend>) {
+        let backend = MockBackend::new(vec![RemoteState::Running])
+            .with_models(models)
+            .with_load(load);
+        (name.to_string(), Box::new(backend))
+    }
+
+    #[test]
+    fn test_routes_to_least_loaded_worker() {
+        let router = RoutingBackend::new(vec![
+            worker("busy", &["shap_e"], 3),
+            worker("idle", &["shap_e"], 0),
+            worker("lgm-box", &["lgm"], 0),
+        ]);
+
+        let inputs = JobInputs::default();
+        let id = router.submit(&inputs).unwrap();
+        assert_eq!(worker_of(&id), Some("idle"));
+        assert_eq!(router.status(&id).unwrap().state, RemoteState::Running);
+
+        let lgm = JobInputs { model: "lgm".into(), ..Default::default() };
+        assert_eq!(worker_of(&router.submit(&lgm).unwrap()), Some("lgm-box"));
+
+        let other = JobInputs { model: "nope".into(), ..Default::default() };
+        assert!(matches!(router.submit(&other), Err(AppError::ModelNotLoaded(_))));
+
+        let models = router.list_models().unwrap();
+        assert_eq!(models.models, vec!["shap_e".to_string(), "lgm".to_string()]);
+    }
+
+    #[test]
+    fn test_redecode_goes_to_source_worker() {
+        let router = RoutingBackend::new(vec![
+            worker("busy", &["shap_e"], 3),
+            worker("idle", &["shap_e"], 0),
+        ]);
+
+        let redecode = |source: &str| JobInputs {
+            redecode: Some(Redecode {
+                latent: "shap_e_chair.latent.pt".into(),
+                source: Some(source.into()),
+                settings: DecodeSettings::default(),
+            }),
+            ..Default::default()
+        };
+        assert_eq!(worker_of(&router.submit(&redecode("busy/abc")).unwrap()), Some("busy"));
+        // From before routing, so the first worker's
+        assert_eq!(worker_of(&router.submit(&redecode("abc")).unwrap()), Some("busy"));
+        assert!(router.submit(&redecode("gone/abc")).is_err());
+    }
+}
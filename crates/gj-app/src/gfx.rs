@@ -1,34 +1,108 @@
 use std::sync::Arc;
 use egui_wgpu::wgpu;
 use winit::window::Window;
+use crate::settings::RenderSettings;
 
 pub struct GfxState {
+    /// Kept for creating surfaces for further windows
+    pub instance: wgpu::Instance,
+    pub adapter: wgpu::Adapter,
     pub surface: wgpu::Surface<'static>,
     pub device: wgpu::Device,
     pub queue: wgpu::Queue,
     pub config: wgpu::SurfaceConfiguration,
-    depth_texture: wgpu::Texture,
-    pub(crate) depth_view: wgpu::TextureView,
+    /// Present modes the surface supports
+    present_modes: Vec<wgpu::PresentMode>,
+    /// Adapter rendering the app
+    pub adapter_info: wgpu::AdapterInfo,
+    /// Every adapter that can present to the window, for choosing another one
+    pub adapters: Vec<wgpu::AdapterInfo>,
+    /// 3D scene, rendered offscreen and shown by the central panel
+    pub(crate) scene: SceneTarget,
+    /// Right eye's view in stereo, `scene` then holding the left eye's
+    pub(crate) right_eye: Option<SceneTarget>,
+}
+
+/// Offscreen color and depth targets the splats render into, sized to the
+/// viewport rather than the window. The last frame stays in it, so UI-only
+/// repaints can skip the splat pass.
+pub struct SceneTarget {
+    pub texture: wgpu::Texture,
+    pub view: wgpu::TextureView,
+    pub depth_view: wgpu::TextureView,
+}
+
+impl SceneTarget {
+    pub fn new(device: &wgpu::Device, format: wgpu::TextureFormat, width: u32, height: u32) -> Self {
+        let texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("Scene Texture"),
+            size: wgpu::Extent3d {
+                width: width.max(1),
+                height: height.max(1),
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT
+                | wgpu::TextureUsages::TEXTURE_BINDING
+                | wgpu::TextureUsages::COPY_SRC,
+            view_formats: &[],
+        });
+        let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+        let depth_texture = create_depth_texture(device, &winit::dpi::PhysicalSize::new(width.max(1), height.max(1)));
+        let depth_view = depth_texture.create_view(&wgpu::TextureViewDescriptor::default());
+        Self { texture, view, depth_view }
+    }
+
+    /// Size in pixels
+    pub fn size(&self) -> (u32, u32) {
+        (self.texture.width(), self.texture.height())
+    }
 }
 
 impl GfxState {
-    pub async fn new(window: Arc<Window>) -> anyhow::Result<Self> {
+    pub async fn new(window: Arc<Window>, render: &RenderSettings) -> anyhow::Result<Self> {
         let size = window.inner_size();
 
+        let backends = render.backend.map_or(wgpu::Backends::PRIMARY, |b| b.to_wgpu());
         let instance = wgpu::Instance::new(&wgpu::InstanceDescriptor {
-            backends: wgpu::Backends::PRIMARY,
+            backends,
             ..Default::default()
         });
 
         let surface = instance.create_surface(window.clone()).unwrap();
 
-        let adapter = instance
-            .request_adapter(&wgpu::RequestAdapterOptions {
-                power_preference: wgpu::PowerPreference::HighPerformance,
-                compatible_surface: Some(&surface),
-                force_fallback_adapter: false,
-            })
-            .await?;
+        let mut candidates: Vec<wgpu::Adapter> = instance.enumerate_adapters(backends)
+            .into_iter()
+            .filter(|adapter| adapter.is_surface_supported(&surface))
+            .collect();
+        let adapters = candidates.iter().map(|adapter| adapter.get_info()).collect();
+
+        // The default pick is often the integrated GPU on hybrid laptops, so
+        // a named adapter wins when it's present
+        let named = render.adapter.as_ref().and_then(|name| {
+            let found = candidates.iter().position(|adapter| adapter.get_info().name == *name);
+            if found.is_none() {
+                log::warn!("GPU adapter \"{}\" not found, using the default", name);
+            }
+            found
+        });
+
+        let adapter = match named {
+            Some(index) => candidates.swap_remove(index),
+            None => instance
+                .request_adapter(&wgpu::RequestAdapterOptions {
+                    power_preference: wgpu::PowerPreference::HighPerformance,
+                    compatible_surface: Some(&surface),
+                    force_fallback_adapter: false,
+                })
+                .await?,
+        };
+
+        let adapter_info = adapter.get_info();
+        log::info!("Rendering with {} ({:?})", adapter_info.name, adapter_info.backend);
 
         let (device, queue) = adapter
             .request_device(
@@ -56,37 +130,75 @@ impl GfxState {
             format: surface_format,
             width: size.width,
             height: size.height,
-            present_mode: surface_caps.present_modes[0],
+            present_mode: if surface_caps.present_modes.contains(&render.present_mode.to_wgpu()) {
+                render.present_mode.to_wgpu()
+            } else {
+                wgpu::PresentMode::Fifo
+            },
             alpha_mode: surface_caps.alpha_modes[0],
             view_formats: vec![],
             desired_maximum_frame_latency: 2,
         };
         surface.configure(&device, &config);
 
-        let depth_texture = create_depth_texture(&device, &size);
-        let depth_view = depth_texture.create_view(&wgpu::TextureViewDescriptor::default());
-        
+        // Resized to the viewport once the UI has laid it out
+        let scene = SceneTarget::new(&device, config.format, size.width, size.height);
+
         Ok(Self {
+            instance,
+            adapter,
             surface,
             device,
             queue,
             config,
-            depth_texture,
-            depth_view
+            present_modes: surface_caps.present_modes,
+            adapter_info,
+            adapters,
+            scene,
+            right_eye: None,
         })
     }
     
+    /// Switch the present mode, falling back to Fifo (always available) if
+    /// the surface doesn't support `mode`. Returns whether `mode` was applied.
+    pub fn set_present_mode(&mut self, mode: wgpu::PresentMode) -> bool {
+        let supported = self.present_modes.contains(&mode);
+        self.config.present_mode = if supported { mode } else { wgpu::PresentMode::Fifo };
+        self.surface.configure(&self.device, &self.config);
+        supported
+    }
+
     pub fn resize(&mut self, new_size: winit::dpi::PhysicalSize<u32>) {
         self.config.width = new_size.width;
         self.config.height = new_size.height;
         self.surface.configure(&self.device, &self.config);
+    }
+
+    /// Resize the scene target to `width` x `height` pixels, returning
+    /// whether it changed
+    pub fn resize_scene(&mut self, width: u32, height: u32) -> bool {
+        if self.scene.size() == (width.max(1), height.max(1)) {
+            return false;
+        }
+        self.scene = SceneTarget::new(&self.device, self.config.format, width, height);
+        if self.right_eye.is_some() {
+            self.right_eye = Some(SceneTarget::new(&self.device, self.config.format, width, height));
+        }
+        true
+    }
 
-        self.depth_texture = create_depth_texture(&self.device, &new_size);
-        self.depth_view = self.depth_texture.create_view(&wgpu::TextureViewDescriptor::default());
+    /// Add or drop the right eye's target, returning whether it changed
+    pub fn set_stereo(&mut self, enabled: bool) -> bool {
+        if self.right_eye.is_some() == enabled {
+            return false;
+        }
+        let (width, height) = self.scene.size();
+        self.right_eye = enabled.then(|| SceneTarget::new(&self.device, self.config.format, width, height));
+        true
     }
 }
 
-fn create_depth_texture(device: &wgpu::Device, size: &winit::dpi::PhysicalSize<u32>) -> wgpu::Texture {
+pub(crate) fn create_depth_texture(device: &wgpu::Device, size: &winit::dpi::PhysicalSize<u32>) -> wgpu::Texture {
     device.create_texture(&wgpu::TextureDescriptor {
         label: Some("Depth Texture"),
         size: wgpu::Extent3d {
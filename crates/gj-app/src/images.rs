@@ -0,0 +1,249 @@
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+use image::RgbaImage;
+use image::imageops::{self, FilterType};
+use serde::{Deserialize, Serialize};
+use crate::error::AppError;
+
+/// Longest side images are scaled down to when added; the models condition
+/// on a few hundred pixels, and smaller files upload faster
+pub const MAX_SIDE: u32 = 1024;
+/// Longest side of the gallery's thumbnails
+pub const THUMBNAIL_SIDE: u32 = 96;
+/// Extensions of the files the library takes; the service accepts these
+pub const EXTENSIONS: [&str; 3] = ["png", "jpg", "jpeg"];
+
+/// Whether `path` names a file the library takes, by its extension
+pub fn is_image(path: &Path) -> bool {
+    path.extension()
+        .and_then(|e| e.to_str())
+        .is_some_and(|e| EXTENSIONS.iter().any(|x| x.eq_ignore_ascii_case(e)))
+}
+
+/// What was done to an image when it was added to the library
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Preprocessing {
+    /// Cropped to the bounds of its visible pixels, as x, y, width and
+    /// height in the source; only images with transparency are
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cropped: Option<[u32; 4]>,
+    /// Scaled down to [`MAX_SIDE`] from this size, after cropping
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scaled_from: Option<[u32; 2]>,
+}
+
+impl Preprocessing {
+    /// What was done, for the gallery's tooltips
+    pub fn describe(&self) -> String {
+        let mut steps = Vec::new();
+        if let Some([x, y, w, h]) = self.cropped {
+            steps.push(format!("cropped to the object ({}×{} at {}, {})", w, h, x, y));
+        }
+        if let Some([w, h]) = self.scaled_from {
+            steps.push(format!("scaled down from {}×{}", w, h));
+        }
+        if steps.is_empty() {
+            return "Used as it was".to_string();
+        }
+        let mut text = steps.join(", ");
+        text[..1].make_ascii_uppercase();
+        text
+    }
+}
+
+/// A conditioning image kept for reuse by new jobs
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct InputImage {
+    pub id: String,
+    /// The prepared image, an RGBA PNG; what jobs are given
+    pub path: PathBuf,
+    pub thumbnail: PathBuf,
+    /// File it was added from
+    pub source: PathBuf,
+    pub preprocessing: Preprocessing,
+    pub width: u32,
+    pub height: u32,
+    /// Unix time it was added
+    pub added_at: u64,
+}
+
+/// Crop `image` to its visible pixels when it has transparency, then scale
+/// it down to fit [`MAX_SIDE`]
+pub fn prepare(mut image: RgbaImage) -> (RgbaImage, Preprocessing) {
+    let mut preprocessing = Preprocessing::default();
+
+    let visible = image.enumerate_pixels().filter(|(_, _, p)| p[3] > 0);
+    let bounds = visible.fold(None, |b: Option<[u32; 4]>, (x, y, _)| match b {
+        None => Some([x, y, x, y]),
+        Some([x0, y0, x1, y1]) => Some([x0.min(x), y0.min(y), x1.max(x), y1.max(y)]),
+    });
+    if let Some([x0, y0, x1, y1]) = bounds {
+        let crop = [x0, y0, x1 - x0 + 1, y1 - y0 + 1];
+        if [crop[2], crop[3]] != [image.width(), image.height()] {
+            image = imageops::crop_imm(&image, crop[0], crop[1], crop[2], crop[3]).to_image();
+            preprocessing.cropped = Some(crop);
+        }
+    }
+
+    let (width, height) = image.dimensions();
+    if width.max(height) > MAX_SIDE {
+        let [w, h] = fit(width, height, MAX_SIDE);
+        image = imageops::resize(&image, w, h, FilterType::Lanczos3);
+        preprocessing.scaled_from = Some([width, height]);
+    }
+
+    (image, preprocessing)
+}
+
+/// Size of a `width` × `height` image scaled to `side` along its longer side
+fn fit(width: u32, height: u32, side: u32) -> [u32; 2] {
+    let scale = side as f32 / width.max(height) as f32;
+    [((width as f32 * scale).round() as u32).max(1), ((height as f32 * scale).round() as u32).max(1)]
+}
+
+/// Conditioning images added so far, prepared once and kept in the outputs
+/// directory so jobs can be given them again without picking a file. The
+/// index sits next to the job file.
+pub struct ImageLibrary {
+    /// The index, a JSON list of [`InputImage`]
+    path: PathBuf,
+    /// Where prepared images and thumbnails are written
+    dir: PathBuf,
+    images: Vec<InputImage>,
+}
+
+impl ImageLibrary {
+    /// Open the index at `path`, keeping new images in `dir`. Starts empty
+    /// if the index is missing or unreadable.
+    pub fn new(path: PathBuf, dir: PathBuf) -> Self {
+        let images = match std::fs::read_to_string(&path) {
+            Ok(contents) => serde_json::from_str(&contents).unwrap_or_else(|e| {
+                log::warn!("Ignoring invalid image library {}: {}", path.display(), e);
+                Vec::new()
+            }),
+            Err(_) => Vec::new(),
+        };
+        Self { path, dir, images }
+    }
+
+    /// All images, oldest first
+    pub fn images(&self) -> &[InputImage] {
+        &self.images
+    }
+
+    /// Prepare the image at `source` and keep it. An image added from the
+    /// same file before is returned instead, as long as it's still there.
+    pub fn add(&mut self, source: &Path) -> Result<InputImage, AppError> {
+        if let Some(existing) = self.images.iter().find(|i| i.source == source && i.path.is_file()) {
+            return Ok(existing.clone());
+        }
+
+        let image = image::open(source)
+            .map_err(|e| AppError::Internal(format!("Can't read {}: {}", source.display(), e)))?
+            .to_rgba8();
+        let (image, preprocessing) = prepare(image);
+
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default();
+        let id = format!("image-{}", now.as_millis());
+        let path = self.dir.join(format!("{}.png", id));
+        let thumbnail = self.dir.join(format!("{}.thumb.png", id));
+        let [w, h] = fit(image.width(), image.height(), THUMBNAIL_SIDE.min(image.width().max(image.height())));
+        let written = std::fs::create_dir_all(&self.dir).map_err(image::ImageError::IoError)
+            .and_then(|_| image.save(&path))
+            .and_then(|_| imageops::thumbnail(&image, w, h).save(&thumbnail));
+        written.map_err(|e| AppError::Internal(format!("Failed to save input image to {}: {}", self.dir.display(), e)))?;
+
+        let entry = InputImage {
+            id,
+            path,
+            thumbnail,
+            source: source.to_path_buf(),
+            preprocessing,
+            width: image.width(),
+            height: image.height(),
+            added_at: now.as_secs(),
+        };
+        self.images.push(entry.clone());
+        self.save();
+        Ok(entry)
+    }
+
+    /// Forget an image and delete its files. Jobs given it fail if they're
+    /// run again.
+    pub fn remove(&mut self, id: &str) -> bool {
+        let Some(index) = self.images.iter().position(|i| i.id == id) else {
+            return false;
+        };
+        let image = self.images.remove(index);
+        for path in [&image.path, &image.thumbnail] {
+            if let Err(e) = std::fs::remove_file(path)
+                && e.kind() != std::io::ErrorKind::NotFound
+            {
+                log::warn!("Failed to delete {}: {}", path.display(), e);
+            }
+        }
+        self.save();
+        true
+    }
+
+    fn save(&self) {
+        let saved = self.path.parent().map_or(Ok(()), std::fs::create_dir_all)
+            .and_then(|_| std::fs::write(&self.path, serde_json::to_string_pretty(&self.images).unwrap_or_default()));
+        if let Err(e) = saved {
+            log::warn!("Failed to save image library {}: {}", self.path.display(), e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use image::Rgba;
+    use super::*;
+
+    #[test]
+    fn test_prepare_crops_and_scales() {
+        // A transparent canvas with an opaque block
+        let mut image = RgbaImage::from_pixel(1500, 1000, Rgba([0, 0, 0, 0]));
+        for y in 200..800 {
+            for x in 100..1300 {
+                image.put_pixel(x, y, Rgba([255, 0, 0, 255]));
+            }
+        }
+        let (prepared, preprocessing) = prepare(image);
+        assert_eq!(preprocessing.cropped, Some([100, 200, 1200, 600]));
+        assert_eq!(preprocessing.scaled_from, Some([1200, 600]));
+        assert_eq!(prepared.dimensions(), (MAX_SIDE, 512));
+
+        // Small opaque images are left alone
+        let (prepared, preprocessing) = prepare(RgbaImage::from_pixel(64, 48, Rgba([1, 2, 3, 255])));
+        assert_eq!(preprocessing, Preprocessing::default());
+        assert_eq!(prepared.dimensions(), (64, 48));
+        assert_eq!(preprocessing.describe(), "Used as it was");
+    }
+
+    #[test]
+    fn test_library_add_and_remove() {
+        let dir = std::env::temp_dir().join(format!("gj_images_{}", std::process::id()));
+        let source = dir.join("source.png");
+        std::fs::create_dir_all(&dir).unwrap();
+        RgbaImage::from_pixel(200, 100, Rgba([0, 255, 0, 255])).save(&source).unwrap();
+
+        let index = dir.join("images.json");
+        let mut library = ImageLibrary::new(index.clone(), dir.join("inputs"));
+        let added = library.add(&source).unwrap();
+        assert!(added.path.is_file() && added.thumbnail.is_file());
+        assert_eq!(image::open(&added.thumbnail).unwrap().width(), THUMBNAIL_SIDE);
+        // Adding the same file again reuses it
+        assert_eq!(library.add(&source).unwrap().id, added.id);
+
+        let reopened = ImageLibrary::new(index.clone(), dir.join("inputs"));
+        assert_eq!(reopened.images(), std::slice::from_ref(&added));
+
+        library.remove(&added.id);
+        assert!(library.images().is_empty());
+        assert!(!added.path.exists());
+        assert!(ImageLibrary::new(index, dir.join("inputs")).images().is_empty());
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,1440 @@
+mod migrations;
+
+use std::collections::{BTreeMap, HashSet};
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use gj_core::Model3D;
+use gj_core::decimation::max_splats_for_ply_size;
+use gj_core::ply_profile::PlyProfile;
+use gj_core::gaussian_cloud::GaussianCloud;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use crate::error::AppError;
+
+/// Service log lines kept per job
+pub const MAX_LOG_LINES: usize = 1000;
+
+/// Recent runs of a model its typical duration is taken from
+const DURATION_HISTORY: usize = 20;
+
+/// How long a save waits for another instance to release the job file
+const LOCK_WAIT: Duration = Duration::from_secs(1);
+/// Age past which a lock was left by an instance that died holding it
+const STALE_LOCK: Duration = Duration::from_secs(30);
+
+/// Parameters a generation job is submitted with
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct JobInputs {
+    pub prompt: String,
+    /// Model id, see [`Model3D::id`]
+    pub model: String,
+    pub guidance_scale: f32,
+    pub num_inference_steps: u32,
+    /// Fixed seed for reproducible results, random when None
+    pub seed: Option<u64>,
+    /// Decode an earlier job's latent again instead of running diffusion
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub redecode: Option<Redecode>,
+    /// Refine an earlier job's output instead of generating from scratch
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub refine: Option<Refine>,
+    /// Index of the worker GPU to run on, whichever it uses now when None
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub device: Option<u32>,
+    /// The model's parameters by name, see [`crate::generator::ParamSpec`].
+    /// Holds `guidance_scale` and `num_inference_steps` too where the model
+    /// has them; the fields above mirror those for older services.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub params: BTreeMap<String, Value>,
+    /// Preset the parameters were left at, None when they were set by hand
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub preset: Option<Preset>,
+    /// Id of the pipeline the job is a stage of, see
+    /// [`gj_core::pipeline::PipelineGraph::builtin`]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pipeline: Option<String>,
+    /// Bake a textured mesh from the output once the job completes
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub export_mesh: bool,
+    /// Local image the job is conditioned on, uploaded to the service with
+    /// it; see [`gj_core::pipeline::TextToMultiviewConfig::needs_image`]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub image: Option<PathBuf>,
+    /// Photos of a real object the job reconstructs, in the order they
+    /// were taken; see [`gj_core::pipeline::TextToMultiviewConfig::Capture`]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub capture: Vec<CaptureView>,
+}
+
+/// A photo of a capture set, with where it was taken from
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CaptureView {
+    pub path: PathBuf,
+    /// Degrees around the object, 0 facing its front and 90 its right side
+    pub azimuth: f32,
+    /// Degrees above the object's middle
+    pub elevation: f32,
+    /// Where exactly the photo was taken, when a COLMAP model says; the
+    /// reconstruction then uses it over the azimuth and elevation
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub camera: Option<CaptureCamera>,
+}
+
+/// A capture set photo's camera, see [`gj_core::colmap::PosedPhoto`]
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct CaptureCamera {
+    /// Camera-to-object transform, row-major, OpenGL axes
+    pub to_object: [[f32; 4]; 4],
+    /// Vertical field of view in degrees of the photo's centered square
+    pub fov_y: f32,
+}
+
+impl Default for JobInputs {
+    fn default() -> Self {
+        Self {
+            prompt: String::new(),
+            model: Model3D::default().id().to_string(),
+            guidance_scale: 15.0,
+            num_inference_steps: 64,
+            seed: None,
+            redecode: None,
+            refine: None,
+            device: None,
+            params: BTreeMap::new(),
+            preset: None,
+            pipeline: None,
+            export_mesh: false,
+            image: None,
+            capture: Vec::new(),
+        }
+    }
+}
+
+impl JobInputs {
+    pub fn model(&self) -> Model3D {
+        Model3D::from_id(&self.model).unwrap_or_default()
+    }
+
+    /// Remote id of the job whose files this one works on; only that job's
+    /// worker is sure to have them
+    pub fn source_job(&self) -> Option<&str> {
+        let redecode = self.redecode.as_ref().and_then(|r| r.source.as_deref());
+        redecode.or_else(|| self.refine.as_ref().and_then(|r| r.source.as_deref()))
+    }
+
+    /// Set the model's parameters, mirroring the ones older services take
+    /// as fields of their own
+    pub fn set_params(&mut self, params: BTreeMap<String, Value>) {
+        if let Some(guidance) = params.get("guidance_scale").and_then(Value::as_f64) {
+            self.guidance_scale = guidance as f32;
+        }
+        if let Some(steps) = params.get("num_inference_steps").and_then(Value::as_u64) {
+            self.num_inference_steps = steps as u32;
+        }
+        self.params = params;
+    }
+
+    /// Point a second-stage job at `dependency`'s results: the splats to
+    /// refine or the latent to re-decode, and the worker that has them
+    pub fn with_outputs_of(mut self, dependency: &JobMetadata) -> Result<Self, AppError> {
+        let outputs = dependency.outputs.as_ref();
+        if let Some(refine) = &mut self.refine {
+            refine.ply = outputs
+                .and_then(JobOutputs::ply_path)
+                .and_then(|p| Some(p.file_name()?.to_str()?.to_string()))
+                .ok_or_else(|| AppError::DependencyFailed(format!("Job {} left no splats to refine", dependency.id)))?;
+            refine.source = dependency.remote_id.clone();
+        }
+        if let Some(redecode) = &mut self.redecode {
+            redecode.latent = outputs
+                .and_then(|o| o.latent.clone())
+                .ok_or_else(|| AppError::DependencyFailed(format!("Job {} kept no latent to re-decode", dependency.id)))?;
+            redecode.source = dependency.remote_id.clone();
+        }
+        Ok(self)
+    }
+
+    /// The model's parameters; for jobs from before schemas, the two the
+    /// inputs had fields for
+    pub fn param_values(&self) -> BTreeMap<String, Value> {
+        if !self.params.is_empty() {
+            return self.params.clone();
+        }
+        BTreeMap::from([
+            ("guidance_scale".to_string(), json!(self.guidance_scale)),
+            ("num_inference_steps".to_string(), json!(self.num_inference_steps)),
+        ])
+    }
+}
+
+/// One-click quality/speed trade-off; what each sets is up to the model,
+/// see [`crate::generator::ModelList::presets`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Preset {
+    Draft,
+    Standard,
+    High,
+}
+
+impl Preset {
+    pub const ALL: [Self; 3] = [Self::Draft, Self::Standard, Self::High];
+
+    /// Key in the service's presets
+    pub fn id(self) -> &'static str {
+        match self {
+            Self::Draft => "draft",
+            Self::Standard => "standard",
+            Self::High => "high",
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Draft => "Draft",
+            Self::Standard => "Standard",
+            Self::High => "High",
+        }
+    }
+
+    pub fn description(self) -> &'static str {
+        match self {
+            Self::Draft => "Quickest, for trying out prompts",
+            Self::Standard => "The model's usual settings",
+            Self::High => "Slower, with finer detail",
+        }
+    }
+}
+
+/// A job that turns a kept Shap-E latent into splats again, e.g. at a finer
+/// mesh resolution, without sampling a new one
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Redecode {
+    /// Latent file on the service, see [`JobOutputs::latent`]
+    pub latent: String,
+    /// Remote id of the job that sampled the latent; only its worker has it
+    pub source: Option<String>,
+    pub settings: DecodeSettings,
+}
+
+/// How a latent is turned into splats
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct DecodeSettings {
+    /// Cells along each side of the grid the mesh is extracted on; splats
+    /// are placed at the mesh's vertices
+    pub mesh_resolution: u32,
+    /// Multiplier on the splat sizes taken from the vertex spacing
+    pub splat_scale: f32,
+}
+
+impl DecodeSettings {
+    /// Mesh resolutions the service accepts
+    pub const MESH_RESOLUTIONS: std::ops::RangeInclusive<u32> = 32..=512;
+}
+
+impl Default for DecodeSettings {
+    /// What a generation is decoded with
+    fn default() -> Self {
+        Self { mesh_resolution: 128, splat_scale: 1.0 }
+    }
+}
+
+/// A second-stage job that refines an earlier job's output with score
+/// distillation against a 2D diffusion prior, as DreamGaussian does, towards
+/// the job's prompt
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Refine {
+    /// File name of the output being refined, as the service wrote it
+    pub ply: String,
+    /// Remote id of the job that wrote it
+    pub source: Option<String>,
+    pub settings: RefineSettings,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct RefineSettings {
+    /// Optimization steps; each renders the splats from a random view
+    pub iterations: u32,
+    /// Classifier-free guidance of the diffusion prior; SDS wants it far
+    /// higher than sampling does
+    pub guidance_scale: f32,
+}
+
+impl RefineSettings {
+    /// Iterations the service accepts
+    pub const ITERATIONS: std::ops::RangeInclusive<u32> = 10..=5000;
+    pub const GUIDANCE_SCALES: std::ops::RangeInclusive<f32> = 1.0..=200.0;
+}
+
+impl Default for RefineSettings {
+    fn default() -> Self {
+        Self { iterations: 500, guidance_scale: 100.0 }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum JobStatus {
+    /// Held back until its `run_at` time
+    Scheduled,
+    /// Held back until the jobs in its `depends_on` are complete
+    Waiting,
+    /// Waiting for the worker to pick it up
+    Queued,
+    Running,
+    Completed,
+    Failed,
+}
+
+impl JobStatus {
+    pub fn label(&self) -> &str {
+        match self {
+            Self::Scheduled => "Scheduled",
+            Self::Waiting => "Waiting",
+            Self::Queued => "Queued",
+            Self::Running => "Running",
+            Self::Completed => "Completed",
+            Self::Failed => "Failed",
+        }
+    }
+
+    pub fn is_finished(&self) -> bool {
+        matches!(self, Self::Completed | Self::Failed)
+    }
+}
+
+/// What an output file of a job holds
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ArtifactKind {
+    /// Gaussian splats the job generated
+    Splats,
+    /// Splats a refinement job made from the job's; the file is that job's
+    Refined,
+    /// Further splats of a job drawing several samples of its prompt
+    Sample,
+    /// Mesh, for engines that don't render splats
+    Mesh,
+    /// Turntable GIF, shown on the job card
+    PreviewGif,
+    PreviewVideo,
+}
+
+impl ArtifactKind {
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Splats => "Splats",
+            Self::Refined => "Refined",
+            Self::Sample => "Sample",
+            Self::Mesh => "Mesh",
+            Self::PreviewGif => "Preview GIF",
+            Self::PreviewVideo => "Preview video",
+        }
+    }
+
+    pub fn icon(self) -> &'static str {
+        match self {
+            Self::Splats => "☁",
+            Self::Refined => "✨",
+            Self::Sample => "🎲",
+            Self::Mesh => "🔺",
+            Self::PreviewGif | Self::PreviewVideo => "🎞",
+        }
+    }
+
+    /// Opened in the viewer, rather than in another app
+    pub fn is_splats(self) -> bool {
+        matches!(self, Self::Splats | Self::Refined | Self::Sample)
+    }
+}
+
+/// A file written for a job
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Artifact {
+    pub kind: ArtifactKind,
+    /// Absolute, canonical path
+    pub path: PathBuf,
+    /// Unix time in seconds it was recorded
+    pub created_at: u64,
+}
+
+/// Files produced by a finished job
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct JobOutputs {
+    /// Everything written for the job, oldest first. Running the job again
+    /// adds a new PLY and keeps the older ones.
+    pub artifacts: Vec<Artifact>,
+    /// File name of the latent the service kept for the job, for decoding it
+    /// again with other settings. Stays on the service, so it's not one of
+    /// [`Self::files`].
+    #[serde(default)]
+    pub latent: Option<String>,
+}
+
+impl JobOutputs {
+    pub fn new(ply_path: &Path) -> Self {
+        let mut outputs = Self { artifacts: Vec::new(), latent: None };
+        outputs.add(ArtifactKind::Splats, ply_path);
+        outputs
+    }
+
+    /// Record a file, replacing any record of the same path
+    pub fn add(&mut self, kind: ArtifactKind, path: &Path) {
+        let path = canonical_path(path);
+        let created_at = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs();
+        self.artifacts.retain(|a| a.path != path);
+        self.artifacts.push(Artifact { kind, path, created_at });
+    }
+
+    /// The newest file of `kind`
+    pub fn latest(&self, kind: ArtifactKind) -> Option<&Path> {
+        self.artifacts.iter().rev().find(|a| a.kind == kind).map(|a| a.path.as_path())
+    }
+
+    /// The job's current result, its newest PLY
+    pub fn ply_path(&self) -> Option<&Path> {
+        self.latest(ArtifactKind::Splats)
+    }
+
+    pub fn preview_gif(&self) -> Option<&Path> {
+        self.latest(ArtifactKind::PreviewGif)
+    }
+
+    /// Every file written for the job; refined splats belong to the job that
+    /// refined them
+    pub fn files(&self) -> impl Iterator<Item = &Path> {
+        self.artifacts.iter().filter(|a| a.kind != ArtifactKind::Refined).map(|a| a.path.as_path())
+    }
+}
+
+/// Bytes on disk taken by the jobs' output files; missing files count as 0
+pub fn disk_usage<'a>(jobs: impl IntoIterator<Item = &'a JobMetadata>) -> u64 {
+    jobs.into_iter()
+        .filter_map(|job| job.outputs.as_ref())
+        .flat_map(JobOutputs::files)
+        .filter_map(|path| std::fs::metadata(path).ok())
+        .map(|meta| meta.len())
+        .sum()
+}
+
+/// Canonicalize `path`, or at least make it absolute if it doesn't exist (yet)
+pub fn canonical_path(path: &Path) -> PathBuf {
+    std::fs::canonicalize(path)
+        .or_else(|_| std::path::absolute(path))
+        .unwrap_or_else(|_| path.to_path_buf())
+}
+
+/// Where the dependencies of a waiting job stand
+#[derive(Debug, Clone, PartialEq)]
+pub enum Dependencies {
+    /// Some haven't finished yet
+    Pending,
+    /// All complete; the job's inputs with their outputs filled in
+    Ready(Box<JobInputs>),
+    /// One failed, was deleted or left nothing to work on; the job can't run
+    Failed(AppError),
+}
+
+/// A generation job and what became of it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JobMetadata {
+    /// Local id, stable across restarts
+    pub id: String,
+    /// Job id assigned by the generation service
+    pub remote_id: Option<String>,
+    pub inputs: JobInputs,
+    pub status: JobStatus,
+    /// Unix time in seconds
+    pub created_at: u64,
+    pub outputs: Option<JobOutputs>,
+    pub error: Option<AppError>,
+    /// Free-form notes, lightly formatted (see the job inspector)
+    #[serde(default)]
+    pub notes: String,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// Queued, but nothing will ever run it: the service lost it or the app
+    /// restarted before it was picked up
+    #[serde(default)]
+    pub stuck: bool,
+    /// Log lines streamed from the generation service, newest last; only
+    /// kept for this session, so polling a job never rewrites the history
+    #[serde(skip)]
+    pub log: Vec<String>,
+    /// Unix time in seconds the worker picked the job up
+    #[serde(default)]
+    pub started_at: Option<u64>,
+    /// Seconds from start to a successful finish
+    #[serde(default)]
+    pub duration_secs: Option<u64>,
+    /// Unix time in seconds a scheduled job is sent to the worker at
+    #[serde(default)]
+    pub run_at: Option<u64>,
+    /// Who sent the job; None for jobs from before attribution
+    #[serde(default)]
+    pub submitter: Option<Submitter>,
+    /// Job whose output this one was refined or re-decoded from; the jobs
+    /// linked this way are versions of one object, see [`versions`]
+    #[serde(default)]
+    pub parent: Option<String>,
+    /// Jobs whose outputs this one works on; it's sent once they're all
+    /// complete, see [`JobDatabase::resolve_dependencies`]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub depends_on: Vec<String>,
+    /// What the worker is doing with the job when it isn't running yet,
+    /// e.g. "retrying (attempt 3)"; only kept for this session
+    #[serde(skip)]
+    pub message: Option<String>,
+}
+
+impl JobMetadata {
+    /// Submitted by `me`. Jobs from before attribution count as everyone's.
+    pub fn is_from(&self, me: &Submitter) -> bool {
+        self.submitter.as_ref().is_none_or(|s| s == me)
+    }
+
+    /// Submitted from `machine`, so this app instance is the one tracking it
+    pub fn is_from_machine(&self, machine: &str) -> bool {
+        self.submitter.as_ref().is_none_or(|s| s.machine == machine)
+    }
+
+    /// Case-insensitive match against the prompt and tags
+    pub fn matches_filter(&self, filter: &str) -> bool {
+        let filter = filter.trim().to_lowercase();
+        filter.is_empty()
+            || self.inputs.prompt.to_lowercase().contains(&filter)
+            || self.tags.iter().any(|t| t.to_lowercase().contains(&filter))
+    }
+}
+
+/// Who submitted a job, to tell people's jobs apart in a shared history
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Submitter {
+    /// Display name, the login name unless set in the settings
+    pub user: String,
+    /// Host name of the submitting machine
+    pub machine: String,
+}
+
+impl Submitter {
+    /// This machine, attributed to `user` or else the login name
+    pub fn local(user: Option<&str>) -> Self {
+        let env = |keys: &[&str]| {
+            keys.iter().find_map(|key| std::env::var(key).ok().filter(|v| !v.trim().is_empty()))
+        };
+        let machine = env(&["HOSTNAME", "COMPUTERNAME"])
+            .or_else(|| {
+                let name = std::fs::read_to_string("/etc/hostname").ok()?;
+                Some(name.trim().to_string()).filter(|n| !n.is_empty())
+            })
+            .unwrap_or_else(|| "unknown".into());
+        let user = user
+            .map(str::trim)
+            .filter(|u| !u.is_empty())
+            .map(String::from)
+            .or_else(|| env(&["USER", "USERNAME"]))
+            .unwrap_or_else(|| "unknown".into());
+        Self { user, machine }
+    }
+}
+
+/// Next time the local clock reads `hour:minute` after `now`, as Unix
+/// seconds. Tonight's 02:00 when asked in the evening, for example.
+pub fn next_local_time(now: &jiff::Zoned, hour: i8, minute: i8) -> Option<u64> {
+    let time = jiff::civil::Time::new(hour, minute, 0, 0).ok()?;
+    let today = now.date().to_datetime(time).to_zoned(now.time_zone().clone()).ok()?;
+    let next = if today > *now {
+        today
+    } else {
+        today.tomorrow().ok()?
+    };
+    u64::try_from(next.timestamp().as_second()).ok()
+}
+
+/// Job history persisted as JSON in the platform data directory.
+/// The file carries a `schema_version`; older files are migrated on open.
+///
+/// Several machines may share one file (on a network drive, say). Each save
+/// bumps the `revision` stored in the file; changes made elsewhere are
+/// picked up by [`JobDatabase::reload_if_changed`], and each save merges in
+/// what others wrote since, under a [`FileLock`], so only the jobs this
+/// instance touched overwrite theirs. File times aren't relied on, since
+/// network shares and FAT drives keep them too coarse to tell saves apart.
+pub struct JobDatabase {
+    path: PathBuf,
+    jobs: Vec<JobMetadata>,
+    /// Set when the file was written by a newer build, so we never clobber it
+    read_only: bool,
+    /// Revision of the file when we last read or wrote it
+    revision: u64,
+    /// Modification time of the file when we last read or wrote it; only
+    /// consulted for files written without a revision
+    synced_at: Option<SystemTime>,
+    /// Jobs added or changed here since the last save
+    touched: HashSet<String>,
+    /// Jobs removed here since the last save
+    removed: HashSet<String>,
+    /// Recorded on every job inserted here
+    submitter: Submitter,
+}
+
+impl JobDatabase {
+    /// Default location of the job file (`<data dir>/genjutsu/jobs.json`)
+    pub fn default_path() -> PathBuf {
+        dirs::data_dir()
+            .unwrap_or_else(|| PathBuf::from("."))
+            .join("genjutsu")
+            .join("jobs.json")
+    }
+
+    /// Open the job file at `path`, running any pending schema migrations.
+    /// Starts empty if the file is missing; an unreadable file is backed up first.
+    pub fn new(path: PathBuf) -> Self {
+        let mut db = Self {
+            path,
+            jobs: Vec::new(),
+            read_only: false,
+            revision: 0,
+            synced_at: None,
+            touched: HashSet::new(),
+            removed: HashSet::new(),
+            submitter: Submitter::local(None),
+        };
+
+        let Ok(contents) = std::fs::read_to_string(&db.path) else {
+            return db;
+        };
+        db.synced_at = db.modified();
+
+        let mut doc: Value = match serde_json::from_str(&contents) {
+            Ok(doc) => doc,
+            Err(e) => {
+                log::warn!("Ignoring invalid job file {}: {}", db.path.display(), e);
+                db.backup(&contents, "corrupt");
+                return db;
+            }
+        };
+
+        db.revision = revision_of(&doc);
+        let version = migrations::version_of(&doc);
+        if version > migrations::CURRENT_VERSION {
+            log::warn!(
+                "Job file {} has schema version {} but this build only knows {}; opening read-only",
+                db.path.display(), version, migrations::CURRENT_VERSION
+            );
+            db.read_only = true;
+        }
+
+        let from = migrations::migrate(&mut doc);
+        db.jobs = decode_jobs(doc);
+
+        if from < migrations::CURRENT_VERSION {
+            db.backup(&contents, &format!("v{}", from));
+            db.save();
+        }
+
+        db
+    }
+
+    fn modified(&self) -> Option<SystemTime> {
+        std::fs::metadata(&self.path).and_then(|m| m.modified()).ok()
+    }
+
+    /// Revision of the file on disk, read from its head where saves put
+    /// it; None when it's missing or was written without one
+    fn disk_revision(&self) -> Option<u64> {
+        let mut head = Vec::new();
+        std::fs::File::open(&self.path).and_then(|f| f.take(128).read_to_end(&mut head)).ok()?;
+        let head = String::from_utf8_lossy(&head);
+        let rest = head.split_once("\"revision\":")?.1.trim_start();
+        let end = rest.find(|c: char| !c.is_ascii_digit()).unwrap_or(rest.len());
+        rest[..end].parse().ok()
+    }
+
+    /// Whether another instance saved the file since we last read or wrote it
+    fn changed_on_disk(&self) -> bool {
+        match self.disk_revision() {
+            Some(revision) => revision != self.revision,
+            // Written by a build from before revisions
+            None => self.modified().is_some_and(|m| Some(m) != self.synced_at),
+        }
+    }
+
+    /// Pick up jobs another instance saved to the file since we last looked.
+    /// Returns whether anything was read.
+    pub fn reload_if_changed(&mut self) -> bool {
+        if !self.changed_on_disk() {
+            return false;
+        }
+        self.merge_from_disk();
+        true
+    }
+
+    /// Replace our jobs with the file's, keeping the ones changed here since
+    /// the last save and leaving out the ones removed here
+    fn merge_from_disk(&mut self) {
+        self.synced_at = self.modified();
+        let Some(mut doc) = std::fs::read_to_string(&self.path).ok()
+            .and_then(|contents| serde_json::from_str::<Value>(&contents).ok())
+        else {
+            return;
+        };
+        if migrations::version_of(&doc) > migrations::CURRENT_VERSION {
+            self.read_only = true;
+            return;
+        }
+        self.revision = revision_of(&doc);
+        migrations::migrate(&mut doc);
+
+        let mut merged: Vec<JobMetadata> = decode_jobs(doc)
+            .into_iter()
+            .filter(|job| !self.removed.contains(&job.id))
+            .map(|job| match self.get(&job.id) {
+                Some(ours) if self.touched.contains(&job.id) => ours.clone(),
+                // Session-only state isn't in the file
+                Some(ours) => JobMetadata { message: ours.message.clone(), log: ours.log.clone(), ..job },
+                None => job,
+            })
+            .collect();
+        for job in &self.jobs {
+            if self.touched.contains(&job.id) && !merged.iter().any(|j| j.id == job.id) {
+                merged.push(job.clone());
+            }
+        }
+        merged.sort_by_key(|job| job.created_at);
+        self.jobs = merged;
+    }
+
+    /// Keep a copy of the original file contents as `jobs.<suffix>.bak.json`
+    fn backup(&self, contents: &str, suffix: &str) {
+        let backup = self.path.with_extension(format!("{}.bak.json", suffix));
+        if let Err(e) = std::fs::write(&backup, contents) {
+            log::warn!("Failed to back up job file to {}: {}", backup.display(), e);
+        }
+    }
+
+    /// Attribute jobs inserted from now on to `submitter`
+    pub fn with_submitter(mut self, submitter: Submitter) -> Self {
+        self.submitter = submitter;
+        self
+    }
+
+    /// Who jobs inserted here are attributed to
+    pub fn submitter(&self) -> &Submitter {
+        &self.submitter
+    }
+
+    /// All jobs, oldest first
+    pub fn jobs(&self) -> &[JobMetadata] {
+        &self.jobs
+    }
+
+    pub fn get(&self, id: &str) -> Option<&JobMetadata> {
+        self.jobs.iter().find(|j| j.id == id)
+    }
+
+    /// Record a new queued job and return its id
+    pub fn insert(&mut self, inputs: JobInputs) -> String {
+        self.insert_version(inputs, None)
+    }
+
+    /// Record a new queued job working on `parent`'s output, or a new
+    /// object when None, and return its id
+    pub fn insert_version(&mut self, inputs: JobInputs, parent: Option<String>) -> String {
+        // Ids taken on other machines sharing the file count too
+        self.reload_if_changed();
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default();
+
+        let mut id = format!("job-{}", now.as_millis());
+        let mut suffix = 1;
+        while self.get(&id).is_some() {
+            id = format!("job-{}-{}", now.as_millis(), suffix);
+            suffix += 1;
+        }
+
+        self.touched.insert(id.clone());
+        self.jobs.push(JobMetadata {
+            id: id.clone(),
+            remote_id: None,
+            inputs,
+            status: JobStatus::Queued,
+            created_at: now.as_secs(),
+            outputs: None,
+            error: None,
+            notes: String::new(),
+            tags: Vec::new(),
+            stuck: false,
+            log: Vec::new(),
+            started_at: None,
+            duration_secs: None,
+            run_at: None,
+            submitter: Some(self.submitter.clone()),
+            parent,
+            depends_on: Vec::new(),
+            message: None,
+        });
+        self.save();
+        id
+    }
+
+    /// Where the dependencies of a waiting job stand; when they're all
+    /// complete, its inputs with their outputs passed along
+    pub fn resolve_dependencies(&self, job: &JobMetadata) -> Dependencies {
+        let mut inputs = job.inputs.clone();
+        for id in &job.depends_on {
+            let Some(dependency) = self.get(id) else {
+                return Dependencies::Failed(AppError::DependencyFailed(format!("Job {} it depended on was deleted", id)));
+            };
+            match dependency.status {
+                JobStatus::Completed => match inputs.with_outputs_of(dependency) {
+                    Ok(resolved) => inputs = resolved,
+                    Err(e) => return Dependencies::Failed(e),
+                },
+                JobStatus::Failed => {
+                    return Dependencies::Failed(AppError::DependencyFailed(format!("Job {} it depended on failed", id)));
+                }
+                _ => return Dependencies::Pending,
+            }
+        }
+        Dependencies::Ready(Box::new(inputs))
+    }
+
+    /// This machine's scheduled jobs due at Unix time `now`, marked queued,
+    /// with their inputs to send to the worker. Other machines sharing the
+    /// history send their own.
+    pub fn take_due_scheduled(&mut self, now: u64) -> Vec<(String, JobInputs)> {
+        let machine = &self.submitter.machine;
+        let due: Vec<_> = self.jobs.iter()
+            .filter(|job| job.status == JobStatus::Scheduled && job.run_at.is_none_or(|t| t <= now))
+            .filter(|job| job.is_from_machine(machine))
+            .map(|job| (job.id.clone(), job.inputs.clone()))
+            .collect();
+        for (id, _) in &due {
+            self.update(id, |job| job.status = JobStatus::Queued);
+        }
+        due
+    }
+
+    /// Unix time the next of this machine's scheduled jobs is due; 0 for
+    /// ones without a time, which are due at once
+    pub fn next_scheduled(&self) -> Option<u64> {
+        self.jobs.iter()
+            .filter(|job| job.status == JobStatus::Scheduled && job.is_from_machine(&self.submitter.machine))
+            .map(|job| job.run_at.unwrap_or(0))
+            .min()
+    }
+
+    /// Modify a job in place and persist the change
+    pub fn update(&mut self, id: &str, f: impl FnOnce(&mut JobMetadata)) {
+        if let Some(job) = self.jobs.iter_mut().find(|j| j.id == id) {
+            f(job);
+            self.touched.insert(id.to_string());
+            self.save();
+        }
+    }
+
+    pub fn update_notes(&mut self, id: &str, notes: &str) {
+        self.update(id, |job| job.notes = notes.to_string());
+    }
+
+    /// Point a job at the new location of its output file
+    pub fn relink(&mut self, id: &str, ply_path: &Path) {
+        self.update(id, |job| {
+            let outputs = job.outputs.get_or_insert_with(|| JobOutputs::new(ply_path));
+            match outputs.artifacts.iter_mut().rev().find(|a| a.kind == ArtifactKind::Splats) {
+                Some(current) => current.path = canonical_path(ply_path),
+                None => outputs.add(ArtifactKind::Splats, ply_path),
+            }
+        });
+    }
+
+    /// Append service log lines to a job, keeping the last [`MAX_LOG_LINES`].
+    /// The log isn't saved, so this never touches the file.
+    pub fn append_log(&mut self, id: &str, lines: Vec<String>) {
+        if let Some(job) = self.jobs.iter_mut().find(|j| j.id == id) {
+            job.log.extend(lines);
+            let excess = job.log.len().saturating_sub(MAX_LOG_LINES);
+            job.log.drain(..excess);
+        }
+    }
+
+    /// Remove the given jobs from the history
+    pub fn remove(&mut self, ids: &[String]) {
+        let before = self.jobs.len();
+        self.jobs.retain(|j| !ids.contains(&j.id));
+        if self.jobs.len() != before {
+            self.removed.extend(ids.iter().cloned());
+            self.save();
+        }
+    }
+
+    /// Write our jobs, merging in what others saved since we last looked.
+    /// Left for the next save when another instance holds the file; the
+    /// changes stay marked until then.
+    fn save(&mut self) {
+        if self.read_only {
+            return;
+        }
+        if let Some(parent) = self.path.parent()
+            && let Err(e) = std::fs::create_dir_all(parent)
+        {
+            log::warn!("Failed to create {}: {}", parent.display(), e);
+            return;
+        }
+        let holder = format!("{}@{}", self.submitter.user, self.submitter.machine);
+        let Some(_lock) = FileLock::acquire(&self.path, &holder) else {
+            log::warn!("Job file {} is locked by another instance; saving later", self.path.display());
+            return;
+        };
+
+        // Someone else saved since we last looked
+        if self.changed_on_disk() {
+            self.merge_from_disk();
+            if self.read_only {
+                return;
+            }
+        }
+
+        let revision = self.revision + 1;
+        let result = (|| -> anyhow::Result<()> {
+            let file = JobFile { revision, schema_version: migrations::CURRENT_VERSION, jobs: &self.jobs };
+            // Readers on other machines never see a half-written file
+            let temp = self.path.with_extension("json.tmp");
+            std::fs::write(&temp, serde_json::to_string_pretty(&file)?)?;
+            std::fs::rename(&temp, &self.path)?;
+            Ok(())
+        })();
+
+        match result {
+            Ok(()) => {
+                self.revision = revision;
+                self.synced_at = self.modified();
+                self.touched.clear();
+                self.removed.clear();
+            }
+            Err(e) => log::warn!("Failed to save job file {}: {}", self.path.display(), e),
+        }
+    }
+}
+
+/// The job file as saved. The revision goes first so
+/// [`JobDatabase::disk_revision`] finds it without parsing the jobs.
+#[derive(Serialize)]
+struct JobFile<'a> {
+    revision: u64,
+    schema_version: u64,
+    jobs: &'a [JobMetadata],
+}
+
+/// Revision of a job file document; 0 for files written without one
+fn revision_of(doc: &Value) -> u64 {
+    doc.get("revision").and_then(Value::as_u64).unwrap_or(0)
+}
+
+/// Advisory lock on a job file, held while reading, merging and writing it
+/// so two machines saving at once don't drop each other's jobs: a
+/// `.lock` file next to it naming the holder, removed when dropped
+struct FileLock(PathBuf);
+
+impl FileLock {
+    /// Take the lock on the job file at `path`, waiting up to [`LOCK_WAIT`]
+    /// for another holder; a lock older than [`STALE_LOCK`] is broken
+    fn acquire(path: &Path, holder: &str) -> Option<Self> {
+        let lock = path.with_extension("json.lock");
+        let deadline = Instant::now() + LOCK_WAIT;
+        loop {
+            match std::fs::OpenOptions::new().write(true).create_new(true).open(&lock) {
+                Ok(mut file) => {
+                    let _ = file.write_all(holder.as_bytes());
+                    return Some(Self(lock));
+                }
+                Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => {
+                    let age = std::fs::metadata(&lock).and_then(|m| m.modified()).ok()
+                        .and_then(|modified| modified.elapsed().ok());
+                    if age.is_some_and(|age| age > STALE_LOCK) {
+                        log::warn!("Breaking stale job file lock {}", lock.display());
+                        let _ = std::fs::remove_file(&lock);
+                        continue;
+                    }
+                    if Instant::now() >= deadline {
+                        return None;
+                    }
+                    std::thread::sleep(Duration::from_millis(20));
+                }
+                Err(e) => {
+                    log::warn!("Failed to lock job file {}: {}", lock.display(), e);
+                    return None;
+                }
+            }
+        }
+    }
+}
+
+impl Drop for FileLock {
+    fn drop(&mut self) {
+        if let Err(e) = std::fs::remove_file(&self.0) {
+            log::warn!("Failed to unlock job file {}: {}", self.0.display(), e);
+        }
+    }
+}
+
+/// Decode record by record so one bad job doesn't lose the whole history
+fn decode_jobs(mut doc: Value) -> Vec<JobMetadata> {
+    let records = doc.get_mut("jobs").map(Value::take).unwrap_or_default();
+    records.as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|record| {
+            serde_json::from_value(record.clone())
+                .inspect_err(|e| log::warn!("Skipping unreadable job record: {}", e))
+                .ok()
+        })
+        .collect()
+}
+
+/// Median duration of the model's recent successful runs
+pub fn typical_duration(jobs: &[JobMetadata], model: &str) -> Option<f64> {
+    let mut durations: Vec<u64> = jobs.iter()
+        .rev()
+        .filter(|job| job.inputs.model == model)
+        .filter_map(|job| job.duration_secs)
+        .take(DURATION_HISTORY)
+        .collect();
+    if durations.is_empty() {
+        return None;
+    }
+
+    durations.sort_unstable();
+    let mid = durations.len() / 2;
+    Some(if durations.len().is_multiple_of(2) {
+        (durations[mid - 1] + durations[mid]) as f64 / 2.0
+    } else {
+        durations[mid] as f64
+    })
+}
+
+/// Seconds left on a job `elapsed` seconds in. Early on the model's
+/// `typical` duration dominates; the rate of `progress` takes over as it
+/// advances.
+pub fn estimate_remaining(elapsed: f64, progress: Option<f32>, typical: Option<f64>) -> Option<f64> {
+    let progress = progress.filter(|p| *p > 0.0).map(|p| p.min(1.0) as f64);
+    let total = match (progress, typical) {
+        (Some(p), Some(typical)) => {
+            let observed = elapsed / p;
+            typical * (1.0 - p) + observed * p
+        }
+        (Some(p), None) => elapsed / p,
+        (None, Some(typical)) => typical,
+        (None, None) => return None,
+    };
+    Some((total - elapsed).max(0.0))
+}
+
+/// Every version of the object job `id` made: the job it was first generated
+/// by and everything refined or re-decoded from that since, oldest first
+pub fn versions<'a>(jobs: &'a [JobMetadata], id: &str) -> Vec<&'a JobMetadata> {
+    let find = |id: &str| jobs.iter().find(|j| j.id == id);
+    let Some(mut root) = find(id) else {
+        return Vec::new();
+    };
+    // Bounded, in case a hand-edited history links jobs in a circle
+    for _ in 0..jobs.len() {
+        match root.parent.as_deref().and_then(find) {
+            Some(parent) => root = parent,
+            None => break,
+        }
+    }
+
+    let mut family = vec![root];
+    let mut next = 0;
+    while let Some(job) = family.get(next) {
+        let id = job.id.as_str();
+        let children: Vec<_> = jobs.iter()
+            .filter(|j| j.parent.as_deref() == Some(id) && !family.iter().any(|f| f.id == j.id))
+            .collect();
+        family.extend(children);
+        next += 1;
+    }
+    family.sort_by_key(|job| job.created_at);
+    family
+}
+
+/// Look for an output file that is no longer at `missing`, by file name, in
+/// each of `dirs` and their immediate subdirectories
+pub fn find_moved_output(missing: &Path, dirs: &[PathBuf]) -> Option<PathBuf> {
+    let name = missing.file_name()?;
+
+    dirs.iter()
+        .flat_map(|dir| {
+            let subdirs = std::fs::read_dir(dir)
+                .into_iter()
+                .flatten()
+                .flatten()
+                .map(|entry| entry.path())
+                .filter(|path| path.is_dir());
+            std::iter::once(dir.clone()).chain(subdirs)
+        })
+        .map(|dir| dir.join(name))
+        .find(|candidate| candidate.is_file())
+}
+
+/// Copy the jobs' output files into `dir` alongside a `jobs.json` manifest,
+/// returning how many output files were copied. PLYs larger than
+/// `max_ply_bytes` are decimated to fit, and PLYs for another `profile` are
+/// converted, instead of copied as-is.
+pub fn export_jobs(
+    jobs: &[JobMetadata],
+    dir: &Path,
+    max_ply_bytes: Option<u64>,
+    profile: PlyProfile,
+) -> anyhow::Result<usize> {
+    std::fs::create_dir_all(dir)?;
+
+    let mut copied = 0;
+    for path in jobs.iter().filter_map(|j| j.outputs.as_ref()?.ply_path()) {
+        let Some(name) = path.file_name() else {
+            continue;
+        };
+
+        let result = match max_ply_bytes {
+            Some(max) if std::fs::metadata(path).is_ok_and(|m| m.len() > max) => {
+                export_converted(path, &dir.join(name), Some(max), profile)
+            }
+            _ if profile != PlyProfile::Genjutsu => export_converted(path, &dir.join(name), None, profile),
+            _ => std::fs::copy(path, dir.join(name)).map(|_| ()).map_err(Into::into),
+        };
+
+        match result {
+            Ok(()) => copied += 1,
+            Err(e) => log::warn!("Skipping {}: {}", path.display(), e),
+        }
+    }
+
+    std::fs::write(dir.join("jobs.json"), serde_json::to_string_pretty(jobs)?)?;
+    Ok(copied)
+}
+
+/// Write the PLY at `src` to `dst` in `profile`, with as many splats as fit
+/// in `max_bytes` if given
+fn export_converted(src: &Path, dst: &Path, max_bytes: Option<u64>, profile: PlyProfile) -> anyhow::Result<()> {
+    let mut cloud = GaussianCloud::from_ply(src)?;
+    if let Some(max) = max_bytes {
+        let decimated = cloud.decimate(max_splats_for_ply_size(max, profile));
+        log::info!("Decimated {} from {} to {} splats", src.display(), cloud.count, decimated.count);
+        cloud = decimated;
+    }
+    std::fs::write(dst, cloud.to_ply_as(profile)?)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_job_database_roundtrip() {
+        let path = std::env::temp_dir().join(format!("gj_jobs_test_{}.json", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+
+        let mut db = JobDatabase::new(path.clone());
+        let inputs = JobInputs {
+            prompt: "a red chair".into(),
+            seed: Some(7),
+            capture: vec![CaptureView {
+                path: PathBuf::from("front.png"),
+                azimuth: 0.0,
+                elevation: 15.0,
+                camera: Some(CaptureCamera { to_object: [[0.0; 4]; 4], fov_y: 50.0 }),
+            }],
+            ..Default::default()
+        };
+        let id = db.insert(inputs.clone());
+        let other = db.insert(JobInputs::default());
+        assert_ne!(id, other);
+
+        db.update(&id, |job| job.status = JobStatus::Completed);
+        db.update_notes(&id, "keep this one");
+
+        // Service logs stay in memory, through reloads but not restarts
+        let saved = std::fs::read(&path).unwrap();
+        db.append_log(&id, vec!["step 1/64".into()]);
+        assert_eq!(std::fs::read(&path).unwrap(), saved);
+        db.merge_from_disk();
+        assert_eq!(db.get(&id).unwrap().log, ["step 1/64"]);
+
+        let reopened = JobDatabase::new(path.clone());
+        assert!(reopened.get(&id).unwrap().log.is_empty());
+        assert_eq!(reopened.jobs().len(), 2);
+        assert_eq!(reopened.get(&id).unwrap().inputs, inputs);
+        assert_eq!(reopened.get(&id).unwrap().status, JobStatus::Completed);
+        assert_eq!(reopened.get(&id).unwrap().notes, "keep this one");
+        assert!(reopened.get(&other).unwrap().notes.is_empty());
+
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_param_values() {
+        // Jobs from before schemas only had the two fields
+        let old = JobInputs { guidance_scale: 20.0, ..Default::default() };
+        assert_eq!(old.param_values()["guidance_scale"], json!(20.0));
+
+        let mut inputs = JobInputs::default();
+        inputs.set_params(BTreeMap::from([
+            ("num_inference_steps".to_string(), json!(128)),
+            ("mesh_resolution".to_string(), json!(256)),
+        ]));
+        assert_eq!(inputs.num_inference_steps, 128);
+        assert_eq!(inputs.param_values()["mesh_resolution"], json!(256));
+    }
+
+    #[test]
+    fn test_shared_job_file() {
+        let path = std::env::temp_dir().join(format!("gj_jobs_shared_{}.json", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+
+        let alice_id = Submitter { user: "alice".into(), machine: "studio-1".into() };
+        let bob_id = Submitter { user: "bob".into(), machine: "studio-2".into() };
+        let mut alice = JobDatabase::new(path.clone()).with_submitter(alice_id.clone());
+        let mut bob = JobDatabase::new(path.clone()).with_submitter(bob_id.clone());
+        let first = alice.insert(JobInputs { prompt: "a chair".into(), ..Default::default() });
+        let second = bob.insert(JobInputs { prompt: "a table".into(), ..Default::default() });
+        assert_eq!(bob.jobs().len(), 2);
+
+        // File times too coarse to tell the saves apart, as on network shares
+        let pin_mtime = || std::fs::File::options().write(true).open(&path).unwrap()
+            .set_modified(UNIX_EPOCH + Duration::from_secs(1_000_000)).unwrap();
+        pin_mtime();
+        assert!(alice.reload_if_changed());
+        assert!(!alice.reload_if_changed());
+        let theirs = alice.get(&second).unwrap();
+        assert!(theirs.is_from(&bob_id) && !theirs.is_from(&alice_id));
+        assert!(!theirs.is_from_machine("studio-1"));
+
+        alice.update_notes(&first, "mine");
+        pin_mtime();
+        alice.remove(std::slice::from_ref(&second));
+        pin_mtime();
+        assert!(bob.reload_if_changed());
+        assert_eq!(bob.get(&first).unwrap().notes, "mine");
+        assert!(bob.get(&second).is_none());
+
+        // While someone else holds the file, changes wait for the next save
+        let lock = path.with_extension("json.lock");
+        std::fs::write(&lock, "carol@studio-3").unwrap();
+        bob.update_notes(&first, "ours");
+        assert!(!alice.reload_if_changed());
+        std::fs::remove_file(&lock).unwrap();
+        let third = bob.insert(JobInputs::default());
+        assert!(alice.reload_if_changed());
+        assert_eq!(alice.get(&first).unwrap().notes, "ours");
+        assert!(alice.get(&third).is_some());
+
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_job_database_migrates_legacy_file() {
+        let path = std::env::temp_dir().join(format!("gj_jobs_legacy_{}.json", std::process::id()));
+        let legacy = json!([{
+            "id": "job-1",
+            "remote_id": null,
+            "inputs": JobInputs::default(),
+            "status": "Completed",
+            "created_at": 0,
+            "output_path": null,
+            "error": null,
+        }, {
+            "id": "job-2",
+            "status": "NotAStatus",
+        }]);
+        std::fs::write(&path, legacy.to_string()).unwrap();
+
+        let db = JobDatabase::new(path.clone());
+        assert_eq!(db.jobs().len(), 1);
+        assert_eq!(db.get("job-1").unwrap().status, JobStatus::Completed);
+
+        let saved: Value = serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(migrations::version_of(&saved), migrations::CURRENT_VERSION);
+
+        let backup = path.with_extension("v0.bak.json");
+        assert!(backup.exists());
+
+        let _ = std::fs::remove_file(&path);
+        let _ = std::fs::remove_file(&backup);
+    }
+
+    #[test]
+    fn test_job_filter() {
+        let path = std::env::temp_dir().join(format!("gj_jobs_filter_{}.json", std::process::id()));
+        let mut db = JobDatabase::new(path.clone());
+        let id = db.insert(JobInputs { prompt: "A Red Chair".into(), ..Default::default() });
+        db.update(&id, |job| job.tags.push("furniture".into()));
+
+        let job = db.get(&id).unwrap();
+        assert!(job.matches_filter(""));
+        assert!(job.matches_filter("red"));
+        assert!(job.matches_filter("FURN"));
+        assert!(!job.matches_filter("sword"));
+
+        db.remove(std::slice::from_ref(&id));
+        assert!(db.get(&id).is_none());
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_next_local_time() {
+        let tz = jiff::tz::TimeZone::UTC;
+        let evening = jiff::civil::date(2025, 3, 1).at(21, 30, 0, 0).to_zoned(tz.clone()).unwrap();
+        let tonight = jiff::civil::date(2025, 3, 2).at(2, 0, 0, 0).to_zoned(tz.clone()).unwrap();
+        assert_eq!(next_local_time(&evening, 2, 0), Some(tonight.timestamp().as_second() as u64));
+
+        let later_today = jiff::civil::date(2025, 3, 1).at(23, 0, 0, 0).to_zoned(tz).unwrap();
+        assert_eq!(next_local_time(&evening, 23, 0), Some(later_today.timestamp().as_second() as u64));
+        assert_eq!(next_local_time(&evening, 25, 0), None);
+    }
+
+    #[test]
+    fn test_scheduled_jobs_come_due() {
+        let path = std::env::temp_dir().join(format!("gj_jobs_scheduled_{}.json", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let mut db = JobDatabase::new(path.clone());
+        let tonight = db.insert(JobInputs::default());
+        let tomorrow = db.insert(JobInputs::default());
+        db.update(&tonight, |job| (job.status, job.run_at) = (JobStatus::Scheduled, Some(1_000)));
+        db.update(&tomorrow, |job| (job.status, job.run_at) = (JobStatus::Scheduled, Some(2_000)));
+        assert_eq!(db.next_scheduled(), Some(1_000));
+
+        // Nothing but the clock moves
+        assert!(db.take_due_scheduled(999).is_empty());
+        let due = db.take_due_scheduled(1_500);
+        assert_eq!(due.iter().map(|(id, _)| id).collect::<Vec<_>>(), [&tonight]);
+        assert_eq!(db.get(&tonight).unwrap().status, JobStatus::Queued);
+        assert!(db.take_due_scheduled(1_500).is_empty());
+        assert_eq!(db.next_scheduled(), Some(2_000));
+
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_duration_estimates() {
+        let path = std::env::temp_dir().join(format!("gj_jobs_eta_{}.json", std::process::id()));
+        let mut db = JobDatabase::new(path.clone());
+        for duration in [30, 50, 40] {
+            let id = db.insert(JobInputs::default());
+            db.update(&id, |job| job.duration_secs = Some(duration));
+        }
+        let jobs = db.jobs().to_vec();
+        let model = JobInputs::default().model;
+
+        assert_eq!(typical_duration(&jobs, &model), Some(40.0));
+        assert_eq!(typical_duration(&jobs, "other-model"), None);
+
+        assert_eq!(estimate_remaining(10.0, None, Some(40.0)), Some(30.0));
+        assert_eq!(estimate_remaining(10.0, Some(0.5), None), Some(10.0));
+        // Halfway between the history and the observed rate
+        assert_eq!(estimate_remaining(10.0, Some(0.5), Some(40.0)), Some(20.0));
+        assert_eq!(estimate_remaining(90.0, None, Some(40.0)), Some(0.0));
+        assert_eq!(estimate_remaining(10.0, None, None), None);
+
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_job_outputs_keep_runs() {
+        let dir = std::env::temp_dir();
+        let mut outputs = JobOutputs::new(&dir.join("run1.ply"));
+        outputs.add(ArtifactKind::PreviewGif, &dir.join("run1.gif"));
+        outputs.add(ArtifactKind::Splats, &dir.join("run2.ply"));
+        outputs.add(ArtifactKind::Refined, &dir.join("run2_refined.ply"));
+
+        assert_eq!(outputs.ply_path(), Some(canonical_path(&dir.join("run2.ply")).as_path()));
+        assert_eq!(outputs.preview_gif(), Some(canonical_path(&dir.join("run1.gif")).as_path()));
+        // The refined file is the refinement job's to delete
+        assert_eq!(outputs.files().count(), 3);
+
+        // Recording a path again replaces it
+        outputs.add(ArtifactKind::Splats, &dir.join("run1.ply"));
+        assert_eq!(outputs.artifacts.len(), 4);
+        assert_eq!(outputs.ply_path(), Some(canonical_path(&dir.join("run1.ply")).as_path()));
+
+        // Further samples are the job's files but not its result
+        outputs.add(ArtifactKind::Sample, &dir.join("run1.2.ply"));
+        assert_eq!(outputs.ply_path(), Some(canonical_path(&dir.join("run1.ply")).as_path()));
+        assert_eq!(outputs.files().count(), 4);
+    }
+
+    #[test]
+    fn test_job_versions() {
+        let path = std::env::temp_dir().join(format!("gj_jobs_versions_{}.json", std::process::id()));
+        let mut db = JobDatabase::new(path.clone());
+        let original = db.insert(JobInputs::default());
+        let refined = db.insert_version(JobInputs::default(), Some(original.clone()));
+        let redecoded = db.insert_version(JobInputs::default(), Some(refined.clone()));
+        let unrelated = db.insert(JobInputs::default());
+
+        let ids = |db: &JobDatabase, id: &str| versions(db.jobs(), id).iter().map(|j| j.id.clone()).collect::<Vec<_>>();
+        let chain = vec![original.clone(), refined.clone(), redecoded.clone()];
+        assert_eq!(ids(&db, &original), chain);
+        assert_eq!(ids(&db, &redecoded), chain);
+        assert_eq!(ids(&db, &unrelated), vec![unrelated.clone()]);
+        assert!(ids(&db, "missing").is_empty());
+
+        // A loop doesn't hang
+        db.update(&original, |job| job.parent = Some(redecoded.clone()));
+        assert_eq!(ids(&db, &refined).len(), 3);
+
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_resolve_dependencies() {
+        let path = std::env::temp_dir().join(format!("gj_jobs_dependencies_{}.json", std::process::id()));
+        let mut db = JobDatabase::new(path.clone());
+        let original = db.insert(JobInputs::default());
+        let refine = Refine { ply: String::new(), source: None, settings: RefineSettings::default() };
+        let refined = db.insert_version(JobInputs { refine: Some(refine), ..Default::default() }, Some(original.clone()));
+        db.update(&refined, |job| {
+            job.status = JobStatus::Waiting;
+            job.depends_on = vec![original.clone()];
+        });
+        let resolve = |db: &JobDatabase| db.resolve_dependencies(db.get(&refined).unwrap());
+
+        assert!(matches!(resolve(&db), Dependencies::Pending));
+
+        db.update(&original, |job| {
+            job.status = JobStatus::Completed;
+            job.remote_id = Some("remote-1".into());
+            job.outputs = Some(JobOutputs::new(Path::new("/outputs/chair.ply")));
+        });
+        let Dependencies::Ready(inputs) = resolve(&db) else {
+            panic!("dependency is complete");
+        };
+        let refine = inputs.refine.unwrap();
+        assert_eq!(refine.ply, "chair.ply");
+        assert_eq!(refine.source.as_deref(), Some("remote-1"));
+
+        // Nothing to pass along fails the waiting job rather than sending it
+        db.update(&original, |job| job.outputs = None);
+        assert!(matches!(resolve(&db), Dependencies::Failed(AppError::DependencyFailed(_))));
+        db.update(&original, |job| job.status = JobStatus::Failed);
+        assert!(matches!(resolve(&db), Dependencies::Failed(AppError::DependencyFailed(_))));
+        db.remove(std::slice::from_ref(&original));
+        assert!(matches!(resolve(&db), Dependencies::Failed(AppError::DependencyFailed(_))));
+
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_find_moved_output() {
+        let root = std::env::temp_dir().join(format!("gj_relink_test_{}", std::process::id()));
+        let sorted = root.join("chairs");
+        std::fs::create_dir_all(&sorted).unwrap();
+        std::fs::write(sorted.join("chair.ply"), b"ply").unwrap();
+
+        let missing = Path::new("/gone/outputs/chair.ply");
+        assert_eq!(find_moved_output(missing, std::slice::from_ref(&root)), Some(sorted.join("chair.ply")));
+        assert_eq!(find_moved_output(Path::new("/gone/table.ply"), std::slice::from_ref(&root)), None);
+
+        let _ = std::fs::remove_dir_all(&root);
+    }
+}
\ No newline at end of file
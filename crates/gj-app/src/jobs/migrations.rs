@@ -0,0 +1,151 @@
+use std::path::Path;
+use serde_json::{json, Value};
+use crate::error::AppError;
+
+/// A migration upgrades the job file document by one schema version
+type Migration = fn(&mut Value);
+
+/// Ordered migrations; migration `i` upgrades a document from version `i` to `i + 1`.
+/// Append new migrations here whenever `JobMetadata` or `JobInputs` change shape.
+const MIGRATIONS: &[Migration] = &[
+    add_notes_and_tags,
+    categorize_errors,
+    add_job_logs,
+    add_stuck_flag,
+    absolute_outputs,
+    add_submitter,
+    artifact_lists,
+];
+
+/// Schema version written by this build
+pub const CURRENT_VERSION: u64 = MIGRATIONS.len() as u64;
+
+/// Version of a job file document. Files written before versioning was
+/// introduced are a bare array of jobs and count as version 0.
+pub fn version_of(doc: &Value) -> u64 {
+    doc.get("schema_version").and_then(Value::as_u64).unwrap_or(0)
+}
+
+/// Bring `doc` up to [`CURRENT_VERSION`], returning the version it started at
+pub fn migrate(doc: &mut Value) -> u64 {
+    if doc.is_array() {
+        *doc = json!({ "schema_version": 0, "jobs": doc.take() });
+    }
+
+    let from = version_of(doc);
+    for (version, migration) in MIGRATIONS.iter().enumerate().skip(from as usize) {
+        log::info!("Migrating job file from schema version {} to {}", version, version + 1);
+        migration(doc);
+        doc["schema_version"] = json!(version + 1);
+    }
+
+    from
+}
+
+fn jobs_mut(doc: &mut Value) -> impl Iterator<Item = &mut serde_json::Map<String, Value>> {
+    doc.get_mut("jobs")
+        .and_then(Value::as_array_mut)
+        .into_iter()
+        .flatten()
+        .filter_map(Value::as_object_mut)
+}
+
+/// v0 -> v1: jobs gained free-form notes and tags
+fn add_notes_and_tags(doc: &mut Value) {
+    for job in jobs_mut(doc) {
+        job.entry("notes").or_insert_with(|| json!(""));
+        job.entry("tags").or_insert_with(|| json!([]));
+    }
+}
+
+/// v1 -> v2: job errors changed from plain strings to categorized [`AppError`]s
+fn categorize_errors(doc: &mut Value) {
+    for job in jobs_mut(doc) {
+        if let Some(Value::String(message)) = job.get("error") {
+            let error = AppError::classify(message);
+            job.insert("error".into(), serde_json::to_value(error).unwrap_or(Value::Null));
+        }
+    }
+}
+
+/// v2 -> v3: jobs keep the log streamed from the generation service
+fn add_job_logs(doc: &mut Value) {
+    for job in jobs_mut(doc) {
+        job.entry("log").or_insert_with(|| json!([]));
+    }
+}
+
+/// v3 -> v4: queued jobs can be flagged as stuck
+fn add_stuck_flag(doc: &mut Value) {
+    for job in jobs_mut(doc) {
+        job.entry("stuck").or_insert_with(|| json!(false));
+    }
+}
+
+/// v4 -> v5: `output_path`, relative to wherever the app was started, became
+/// `outputs.ply_path`, an absolute path
+fn absolute_outputs(doc: &mut Value) {
+    for job in jobs_mut(doc) {
+        let outputs = match job.remove("output_path") {
+            Some(Value::String(path)) => {
+                json!({ "ply_path": crate::jobs::canonical_path(Path::new(&path)) })
+            }
+            _ => Value::Null,
+        };
+        job.entry("outputs").or_insert(outputs);
+    }
+}
+
+/// v5 -> v6: jobs record who submitted them, for shared histories
+fn add_submitter(doc: &mut Value) {
+    for job in jobs_mut(doc) {
+        job.entry("submitter").or_insert(Value::Null);
+    }
+}
+
+/// v6 -> v7: `outputs.ply_path` and `outputs.preview_gif` became a list of
+/// tagged `outputs.artifacts`, so jobs can keep several files and versions
+fn artifact_lists(doc: &mut Value) {
+    for job in jobs_mut(doc) {
+        let created_at = job.get("created_at").cloned().unwrap_or(json!(0));
+        let Some(outputs) = job.get_mut("outputs").and_then(Value::as_object_mut) else {
+            continue;
+        };
+        let artifacts: Vec<Value> = [("ply_path", "Splats"), ("preview_gif", "PreviewGif")]
+            .into_iter()
+            .filter_map(|(key, kind)| match outputs.remove(key) {
+                Some(Value::String(path)) => Some(json!({ "kind": kind, "path": path, "created_at": created_at })),
+                _ => None,
+            })
+            .collect();
+        outputs.entry("artifacts").or_insert(json!(artifacts));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_migrate_legacy_array() {
+        let mut doc = json!([{ "id": "job-1", "error": "CUDA out of memory", "output_path": "outputs/a.ply" }]);
+        assert_eq!(migrate(&mut doc), 0);
+        assert_eq!(version_of(&doc), CURRENT_VERSION);
+        assert_eq!(doc["jobs"][0]["notes"], json!(""));
+        assert_eq!(doc["jobs"][0]["tags"], json!([]));
+        assert_eq!(doc["jobs"][0]["log"], json!([]));
+        assert!(doc["jobs"][0].get("output_path").is_none());
+        let artifact = &doc["jobs"][0]["outputs"]["artifacts"][0];
+        assert_eq!(artifact["kind"], json!("Splats"));
+        assert!(Path::new(artifact["path"].as_str().unwrap()).is_absolute());
+        assert_eq!(doc["jobs"][0]["error"], json!({ "OutOfVram": "CUDA out of memory" }));
+    }
+
+    #[test]
+    fn test_migrate_current_is_noop() {
+        let mut doc = json!({ "schema_version": CURRENT_VERSION, "jobs": [{ "id": "job-1", "notes": "hi" }] });
+        let before = doc.clone();
+        assert_eq!(migrate(&mut doc), CURRENT_VERSION);
+        assert_eq!(doc, before);
+    }
+}
\ No newline at end of file
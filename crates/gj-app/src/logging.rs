@@ -0,0 +1,206 @@
+use std::collections::VecDeque;
+use std::io::Write;
+use std::sync::{Mutex, OnceLock};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Instant;
+use log::kv::{self, VisitSource};
+use log::{Level, LevelFilter, Log, Metadata, Record};
+use serde_json::{Map, Value};
+
+/// Maximum number of entries kept for the log panel
+const MAX_ENTRIES: usize = 2000;
+
+#[derive(Debug, Clone)]
+pub struct LogEntry {
+    pub level: Level,
+    pub target: String,
+    pub message: String,
+    /// Seconds since startup
+    pub elapsed: f32,
+}
+
+/// In-memory ring buffer of recent log records, shown in the log panel
+pub struct LogBuffer {
+    start: Instant,
+    entries: Mutex<VecDeque<LogEntry>>,
+    /// Bumped on every change, so readers only copy the entries when it moves
+    generation: AtomicU64,
+}
+
+impl LogBuffer {
+    pub(crate) fn new() -> Self {
+        Self {
+            start: Instant::now(),
+            entries: Mutex::new(VecDeque::with_capacity(MAX_ENTRIES)),
+            generation: AtomicU64::new(0),
+        }
+    }
+
+    pub(crate) fn push(&self, record: &Record) {
+        let entry = LogEntry {
+            level: record.level(),
+            target: record.target().to_string(),
+            message: record.args().to_string(),
+            elapsed: self.start.elapsed().as_secs_f32(),
+        };
+
+        let mut entries = self.entries.lock().unwrap();
+        if entries.len() == MAX_ENTRIES {
+            entries.pop_front();
+        }
+        entries.push_back(entry);
+        self.generation.fetch_add(1, Ordering::Release);
+    }
+
+    /// Copy of the current entries
+    pub fn snapshot(&self) -> Vec<LogEntry> {
+        self.entries.lock().unwrap().iter().cloned().collect()
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.lock().unwrap().len()
+    }
+
+    pub fn clear(&self) {
+        self.entries.lock().unwrap().clear();
+        self.generation.fetch_add(1, Ordering::Release);
+    }
+
+    /// Changes whenever entries are added or cleared
+    pub fn generation(&self) -> u64 {
+        self.generation.load(Ordering::Acquire)
+    }
+}
+
+/// Global log buffer fed by the logger installed in [`init`]
+pub fn buffer() -> &'static LogBuffer {
+    static BUFFER: OnceLock<LogBuffer> = OnceLock::new();
+    BUFFER.get_or_init(LogBuffer::new)
+}
+
+/// Logger that forwards to env_logger (stdout, honoring RUST_LOG) and also
+/// records into the in-app log buffer: warnings from any crate, info from
+/// our own crates, plus whatever RUST_LOG enables
+struct GjLogger {
+    inner: env_logger::Logger,
+}
+
+impl Log for GjLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.level() <= Level::Warn
+            || (metadata.level() <= Level::Info && metadata.target().starts_with("gj_"))
+            || self.inner.enabled(metadata)
+    }
+
+    fn log(&self, record: &Record) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+
+        if self.inner.matches(record) {
+            self.inner.log(record);
+        }
+        buffer().push(record);
+    }
+
+    fn flush(&self) {
+        self.inner.flush();
+    }
+}
+
+/// How records are written to stdout
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LogFormat {
+    /// env_logger's lines, for people
+    #[default]
+    Text,
+    /// One JSON object per line, for collectors like Loki or Elasticsearch
+    Json,
+}
+
+impl LogFormat {
+    pub fn parse(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "text" => Some(Self::Text),
+            "json" => Some(Self::Json),
+            _ => None,
+        }
+    }
+}
+
+/// Collects a record's key-values (`job_id`, `model`, `stage`...) as JSON fields
+struct JsonFields<'a>(&'a mut Map<String, Value>);
+
+impl<'kvs> VisitSource<'kvs> for JsonFields<'_> {
+    fn visit_pair(&mut self, key: kv::Key<'kvs>, value: kv::Value<'kvs>) -> Result<(), kv::Error> {
+        let value = if let Some(n) = value.to_u64() {
+            Value::from(n)
+        } else if let Some(n) = value.to_i64() {
+            Value::from(n)
+        } else if let Some(n) = value.to_f64() {
+            Value::from(n)
+        } else if let Some(b) = value.to_bool() {
+            Value::from(b)
+        } else {
+            // Absent options log as "None"; leave them out
+            let text = value.to_string();
+            if text == "None" {
+                return Ok(());
+            }
+            Value::from(text)
+        };
+        self.0.insert(key.to_string(), value);
+        Ok(())
+    }
+}
+
+/// A record as a JSON object: time, level, target and message, plus its key-values
+fn json_record(record: &Record, time: jiff::Timestamp) -> Value {
+    let mut fields = Map::new();
+    fields.insert("time".into(), time.to_string().into());
+    fields.insert("level".into(), record.level().as_str().into());
+    fields.insert("target".into(), record.target().into());
+    fields.insert("message".into(), record.args().to_string().into());
+    let _ = record.key_values().visit(&mut JsonFields(&mut fields));
+    Value::Object(fields)
+}
+
+/// Install the global logger, writing to stdout in `format`
+pub fn init(format: LogFormat) {
+    let mut builder = env_logger::Builder::from_default_env();
+    if format == LogFormat::Json {
+        builder.format(|buf, record| writeln!(buf, "{}", json_record(record, jiff::Timestamp::now())));
+    }
+    let inner = builder.build();
+    let max_level = inner.filter().max(LevelFilter::Info);
+
+    if log::set_boxed_logger(Box::new(GjLogger { inner })).is_ok() {
+        log::set_max_level(max_level);
+    }
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_json_record() {
+        let fields: &[(&str, &dyn kv::ToValue)] = &[("job_id", &"job-1"), ("stage", &"progress"), ("progress", &0.5f64)];
+        let record = Record::builder()
+            .level(Level::Info)
+            .target("gj_app::worker")
+            .args(format_args!("Job job-1 [Running]: sampling"))
+            .key_values(&fields)
+            .build();
+
+        let json = json_record(&record, jiff::Timestamp::UNIX_EPOCH);
+        assert_eq!(json["level"], "INFO");
+        assert_eq!(json["message"], "Job job-1 [Running]: sampling");
+        assert_eq!(json["job_id"], "job-1");
+        assert_eq!(json["stage"], "progress");
+        assert_eq!(json["progress"], 0.5);
+        assert_eq!(json["time"], "1970-01-01T00:00:00Z");
+
+        assert_eq!(LogFormat::parse("JSON"), Some(LogFormat::Json));
+        assert_eq!(LogFormat::parse("xml"), None);
+    }
+}
@@ -1,23 +1,46 @@
 #![recursion_limit = "256"]
 
 mod app;
+mod capture;
+mod cli;
+mod content_filter;
+mod error;
+mod session;
+mod settings;
 mod state;
+mod tray;
 mod ui;
 mod events;
+mod generator;
 mod gfx;
+mod images;
+mod jobs;
+mod logging;
+mod queue_window;
+mod shader_watch;
+mod telemetry;
 mod worker;
+mod web_viewer;
+mod mesh_export;
 
 use std::error::Error;
-use winit::event_loop::{ControlFlow, EventLoop, EventLoopProxy};
+use winit::event_loop::{ControlFlow, EventLoop};
+use crate::cli::CliArgs;
 use crate::events::GjEvent;
 
 fn main() -> Result<(), Box<dyn Error>> {
-    env_logger::init();
+    let cli = CliArgs::parse(std::env::args().skip(1))
+        .map_err(|e| format!("{}\n\n{}", e, cli::USAGE))?;
+    if cli.help {
+        println!("{}", cli::USAGE);
+        return Ok(());
+    }
+    logging::init(cli.log_format);
 
-    let mut event_loop: EventLoop<GjEvent> = EventLoop::with_user_event().build()?;
+    let event_loop: EventLoop<GjEvent> = EventLoop::with_user_event().build()?;
     event_loop.set_control_flow(ControlFlow::Poll);
 
-    let mut app = app::App::default();
+    let mut app = app::App::new(cli, event_loop.create_proxy());
     event_loop.run_app(&mut app)?;
 
     Ok(())
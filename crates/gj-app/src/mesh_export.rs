@@ -0,0 +1,57 @@
+use std::io::Cursor;
+use std::path::Path;
+use image::{ImageFormat, RgbaImage};
+use gj_core::mesh::TexturedMesh;
+
+/// Write `mesh` to `path`: binary glTF with the texture embedded for `.glb`,
+/// otherwise OBJ with `<name>.mtl` and `<name>_diffuse.png` next to it
+pub fn save_textured_mesh(mesh: &TexturedMesh, path: &Path) -> anyhow::Result<()> {
+    let texture = RgbaImage::from_raw(mesh.texture_size, mesh.texture_size, mesh.texture.clone())
+        .ok_or_else(|| anyhow::anyhow!("Baked texture has unexpected size"))?;
+
+    let is_glb = path.extension().is_some_and(|e| e.eq_ignore_ascii_case("glb"));
+    if is_glb {
+        let mut png = Cursor::new(Vec::new());
+        texture.write_to(&mut png, ImageFormat::Png)?;
+        std::fs::write(path, mesh.to_glb(png.get_ref()))?;
+        return Ok(());
+    }
+
+    let stem = path.file_stem().and_then(|s| s.to_str()).unwrap_or("mesh");
+    let mtl_name = format!("{}.mtl", stem);
+    let texture_name = format!("{}_diffuse.png", stem);
+    texture.save_with_format(path.with_file_name(&texture_name), ImageFormat::Png)?;
+    std::fs::write(path.with_file_name(&mtl_name), mesh.to_mtl(&texture_name))?;
+    std::fs::write(path, mesh.to_obj(&mtl_name))?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use gj_core::gaussian_cloud::GaussianCloud;
+    use gj_core::mesh::MeshSettings;
+
+    #[test]
+    fn test_obj_writes_material_and_texture() {
+        let mut cloud = GaussianCloud::new();
+        for i in 0..27 {
+            let p = [(i % 3) as f32, (i / 3 % 3) as f32, (i / 9) as f32].map(|c| c * 0.1);
+            cloud.add_gaussian(p, [0.05; 3], [1.0, 0.0, 0.0, 0.0], [0.0, 1.0, 0.0], 1.0);
+        }
+        let mesh = cloud.to_textured_mesh(MeshSettings { resolution: 3, texture_size: 64 }).unwrap();
+
+        let dir = std::env::temp_dir().join(format!("gj_mesh_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        save_textured_mesh(&mesh, &dir.join("cube.obj")).unwrap();
+        save_textured_mesh(&mesh, &dir.join("cube.glb")).unwrap();
+
+        let mtl = std::fs::read_to_string(dir.join("cube.mtl")).unwrap();
+        assert!(mtl.contains("map_Kd cube_diffuse.png"));
+        let texture = image::open(dir.join("cube_diffuse.png")).unwrap();
+        assert_eq!(texture.width(), mesh.texture_size);
+        assert!(std::fs::read(dir.join("cube.glb")).unwrap().starts_with(b"glTF"));
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,144 @@
+use std::sync::Arc;
+use std::time::Instant;
+use egui_wgpu::wgpu;
+use winit::event::WindowEvent;
+use winit::window::Window;
+use crate::gfx::GfxState;
+use crate::ui::UiEventSender;
+
+pub const QUEUE_WINDOW_TITLE: &str = "Jobs";
+
+/// The job queue popped out into its own OS window, say on a second monitor
+/// while the main window shows nothing but the viewport. It has its own
+/// surface and egui context; the queue panel itself stays in [`crate::ui::UiState`].
+pub struct QueueWindow {
+    pub window: Arc<Window>,
+    surface: wgpu::Surface<'static>,
+    config: wgpu::SurfaceConfiguration,
+    egui_ctx: egui::Context,
+    egui_state: egui_winit::State,
+    egui_renderer: egui_wgpu::Renderer,
+    /// When egui asked to be redrawn without further input
+    repaint_at: Option<Instant>,
+}
+
+impl QueueWindow {
+    pub fn new(window: Arc<Window>, gfx: &GfxState) -> anyhow::Result<Self> {
+        let surface = gfx.instance.create_surface(window.clone())?;
+        let size = window.inner_size();
+        let caps = surface.get_capabilities(&gfx.adapter);
+        let format = caps.formats.iter().find(|f| f.is_srgb()).copied()
+            .or_else(|| caps.formats.first().copied())
+            .ok_or_else(|| anyhow::anyhow!("The GPU can't present to the new window"))?;
+
+        let config = wgpu::SurfaceConfiguration {
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+            format,
+            width: size.width.max(1),
+            height: size.height.max(1),
+            present_mode: wgpu::PresentMode::Fifo,
+            alpha_mode: caps.alpha_modes[0],
+            view_formats: vec![],
+            desired_maximum_frame_latency: 2,
+        };
+        surface.configure(&gfx.device, &config);
+
+        let egui_ctx = egui::Context::default();
+        let egui_state = egui_winit::State::new(
+            egui_ctx.clone(),
+            egui::ViewportId::ROOT,
+            &window,
+            Some(window.scale_factor() as f32),
+            None,
+            None,
+        );
+        let egui_renderer = egui_wgpu::Renderer::new(&gfx.device, format, egui_wgpu::RendererOptions::default());
+
+        Ok(Self { window, surface, config, egui_ctx, egui_state, egui_renderer, repaint_at: None })
+    }
+
+    pub fn context(&self) -> &egui::Context {
+        &self.egui_ctx
+    }
+
+    /// Feed a window event to egui, returning whether a redraw is needed
+    pub fn on_window_event(&mut self, gfx: &GfxState, event: &WindowEvent) -> bool {
+        if let WindowEvent::Resized(size) = event
+            && size.width > 0
+            && size.height > 0
+        {
+            self.config.width = size.width;
+            self.config.height = size.height;
+            self.surface.configure(&gfx.device, &self.config);
+        }
+        self.egui_state.on_window_event(&self.window, event).repaint
+    }
+
+    /// True once a delayed repaint requested by egui is due
+    pub fn repaint_due(&self) -> bool {
+        self.repaint_at.is_some_and(|at| Instant::now() >= at)
+    }
+
+    /// Draw a frame of `draw`
+    pub fn render(
+        &mut self,
+        gfx: &GfxState,
+        sender: &mut UiEventSender,
+        mut draw: impl FnMut(&egui::Context, &mut UiEventSender),
+    ) -> anyhow::Result<()> {
+        let size = self.window.inner_size();
+        if size.width == 0 || size.height == 0 {
+            return Ok(());
+        }
+
+        let raw_input = self.egui_state.take_egui_input(&self.window);
+        let full_output = self.egui_ctx.run(raw_input, |ctx| draw(ctx, sender));
+        self.repaint_at = full_output
+            .viewport_output
+            .get(&egui::ViewportId::ROOT)
+            .and_then(|v| Instant::now().checked_add(v.repaint_delay));
+        self.egui_state.handle_platform_output(&self.window, full_output.platform_output);
+
+        let output = self.surface.get_current_texture()?;
+        let view = output.texture.create_view(&wgpu::TextureViewDescriptor::default());
+        let mut encoder = gfx.device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+            label: Some("Queue Window Encoder"),
+        });
+
+        let paint_jobs = self.egui_ctx.tessellate(full_output.shapes, full_output.pixels_per_point);
+        let screen_desc = egui_wgpu::ScreenDescriptor {
+            size_in_pixels: [size.width, size.height],
+            pixels_per_point: self.window.scale_factor() as f32,
+        };
+        for (id, delta) in &full_output.textures_delta.set {
+            self.egui_renderer.update_texture(&gfx.device, &gfx.queue, *id, delta);
+        }
+        self.egui_renderer.update_buffers(&gfx.device, &gfx.queue, &mut encoder, &paint_jobs, &screen_desc);
+
+        {
+            let rpass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("Queue Window egui pass"),
+                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                    view: &view,
+                    resolve_target: None,
+                    depth_slice: None,
+                    ops: wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(wgpu::Color::BLACK),
+                        store: wgpu::StoreOp::Store,
+                    },
+                })],
+                depth_stencil_attachment: None,
+                ..Default::default()
+            });
+            self.egui_renderer.render(&mut rpass.forget_lifetime(), &paint_jobs, &screen_desc);
+        }
+
+        for id in &full_output.textures_delta.free {
+            self.egui_renderer.free_texture(id);
+        }
+
+        gfx.queue.submit(std::iter::once(encoder.finish()));
+        output.present();
+        Ok(())
+    }
+}
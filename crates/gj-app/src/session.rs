@@ -0,0 +1,116 @@
+use std::path::{Path, PathBuf};
+use serde::{Deserialize, Serialize};
+use gj_splat::camera::Camera;
+use gj_splat::camera_path::CameraKeyframe;
+use crate::settings::PanelLayout;
+
+/// What's on screen, autosaved while the app runs and removed on a clean
+/// exit. A session file found at startup means the last run crashed (or
+/// lost power), and it's offered back.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Session {
+    /// Job whose output is open
+    pub job_id: Option<String>,
+    /// PLY the open scene was loaded from
+    pub scene_path: Option<PathBuf>,
+    pub camera: Option<SessionCamera>,
+    pub layout: PanelLayout,
+}
+
+/// Orbit camera pose; lens settings aren't kept
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct SessionCamera {
+    pub target: [f32; 3],
+    pub distance: f32,
+    pub azimuth: f32,
+    pub elevation: f32,
+}
+
+impl SessionCamera {
+    pub fn from_camera(camera: &Camera) -> Self {
+        Self {
+            target: camera.target.to_array(),
+            distance: camera.distance,
+            azimuth: camera.azimuth,
+            elevation: camera.elevation,
+        }
+    }
+
+    pub fn apply(&self, camera: &mut Camera) {
+        let pose = CameraKeyframe {
+            time: 0.0,
+            target: self.target.into(),
+            distance: self.distance,
+            azimuth: self.azimuth,
+            elevation: self.elevation,
+        };
+        pose.apply(camera);
+    }
+}
+
+impl Session {
+    /// Location of the session file (`<config dir>/genjutsu/session.json`)
+    pub fn path() -> PathBuf {
+        dirs::config_dir()
+            .unwrap_or_else(|| PathBuf::from("."))
+            .join("genjutsu")
+            .join("session.json")
+    }
+
+    /// Whether there's anything worth offering back
+    pub fn has_scene(&self) -> bool {
+        self.job_id.is_some() || self.scene_path.is_some()
+    }
+
+    /// Session left behind by a run that didn't exit cleanly
+    pub fn load_unclean(path: &Path) -> Option<Self> {
+        let contents = std::fs::read_to_string(path).ok()?;
+        serde_json::from_str(&contents)
+            .inspect_err(|e| log::warn!("Ignoring invalid session file {}: {}", path.display(), e))
+            .ok()
+    }
+
+    /// Write the session, replacing the file in one step so a crash
+    /// mid-write leaves the previous autosave intact
+    pub fn save(&self, path: &Path) -> std::io::Result<()> {
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        let json = serde_json::to_string_pretty(self)?;
+        let tmp = path.with_extension("json.tmp");
+        std::fs::write(&tmp, json)?;
+        std::fs::rename(&tmp, path)
+    }
+
+    /// Forget the session on a clean exit
+    pub fn clear(path: &Path) {
+        if let Err(e) = std::fs::remove_file(path)
+            && e.kind() != std::io::ErrorKind::NotFound
+        {
+            log::warn!("Failed to remove session file {}: {}", path.display(), e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_session_round_trip() {
+        let path = std::env::temp_dir().join(format!("gj_session_{}.json", std::process::id()));
+        let session = Session {
+            job_id: Some("job-1".into()),
+            scene_path: Some("/outputs/chair.ply".into()),
+            camera: Some(SessionCamera { target: [0.0, 0.5, 0.0], distance: 2.5, azimuth: 30.0, elevation: 10.0 }),
+            layout: PanelLayout { show_side: false, ..Default::default() },
+        };
+        session.save(&path).unwrap();
+        assert_eq!(Session::load_unclean(&path), Some(session));
+
+        Session::clear(&path);
+        assert_eq!(Session::load_unclean(&path), None);
+        assert!(!Session::default().has_scene());
+    }
+}
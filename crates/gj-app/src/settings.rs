@@ -0,0 +1,395 @@
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use egui_wgpu::wgpu;
+use serde::{Deserialize, Serialize};
+use crate::generator::HttpBackend;
+use crate::jobs::{JobDatabase, Submitter};
+
+/// User settings persisted as JSON in the platform config directory
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    /// Keybinding overrides: action id -> shortcut, e.g. `"reset_camera": "Ctrl+R"`
+    pub shortcuts: BTreeMap<String, String>,
+    /// Where the generation service writes results, `outputs/` in the
+    /// working directory when unset
+    pub output_dir: Option<PathBuf>,
+    /// Frame presentation and pacing
+    pub render: RenderSettings,
+    /// Window geometry and panel layout from the last run
+    pub window: WindowSettings,
+    pub appearance: AppearanceSettings,
+    pub confirm: ConfirmSettings,
+    /// Generation services to route jobs between; the local one when empty
+    pub workers: Vec<WorkerEndpoint>,
+    /// Job history file, `jobs.json` in the platform data directory when
+    /// unset. Point several machines at one on a shared drive to share a
+    /// team's history.
+    pub history_path: Option<PathBuf>,
+    /// Name jobs are attributed to in a shared history, the login name
+    /// when unset
+    pub user_name: Option<String>,
+    /// OTLP/HTTP collector to export job traces to, e.g.
+    /// `http://localhost:4318`; `OTEL_EXPORTER_OTLP_ENDPOINT` when unset
+    pub otlp_endpoint: Option<String>,
+    /// Prompt checks before jobs are submitted; off when it has no words
+    /// and no moderation endpoint
+    pub content_filter: ContentFilterSettings,
+}
+
+/// A generation service jobs can be sent to
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WorkerEndpoint {
+    /// Shown in the queue; must not contain `/`
+    pub name: String,
+    /// Base URL, e.g. `http://gpu-box:5000`, or `https://` when the
+    /// service has a certificate
+    pub url: String,
+    /// Bearer token the service expects, see its `API_TOKEN`
+    #[serde(default)]
+    pub token: Option<String>,
+}
+
+/// UI color scheme
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Theme {
+    Dark,
+    Light,
+    /// Dark or light, following the desktop
+    #[default]
+    System,
+}
+
+impl Theme {
+    pub const ALL: [Self; 3] = [Self::Dark, Self::Light, Self::System];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Dark => "Dark",
+            Self::Light => "Light",
+            Self::System => "Follow system",
+        }
+    }
+
+    pub fn to_egui(self) -> egui::ThemePreference {
+        match self {
+            Self::Dark => egui::ThemePreference::Dark,
+            Self::Light => egui::ThemePreference::Light,
+            Self::System => egui::ThemePreference::System,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AppearanceSettings {
+    pub theme: Theme,
+    /// sRGB color of selections, links and active widgets
+    pub accent: [u8; 3],
+    /// Status colors distinguishable with red-green color blindness
+    pub colorblind: bool,
+}
+
+impl Default for AppearanceSettings {
+    fn default() -> Self {
+        Self { theme: Theme::System, accent: [0, 120, 215], colorblind: false }
+    }
+}
+
+/// Destructive actions that ask first; each dialog can switch its own off
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ConfirmSettings {
+    pub delete_jobs: bool,
+    pub clear_completed: bool,
+}
+
+impl Default for ConfirmSettings {
+    fn default() -> Self {
+        Self { delete_jobs: true, clear_completed: true }
+    }
+}
+
+/// What happens to a prompt the content filter catches
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FilterAction {
+    /// The job fails without reaching the service
+    #[default]
+    Block,
+    /// The job goes ahead with a warning in the status bar and the log
+    Warn,
+}
+
+/// Words and an optional moderation API that prompts are checked against,
+/// e.g. for a classroom or lab install
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ContentFilterSettings {
+    pub action: FilterAction,
+    /// Words and phrases to catch, matched whole and ignoring case
+    pub blocked_words: Vec<String>,
+    /// File with more of them, one per line; `#` starts a comment
+    pub word_list: Option<PathBuf>,
+    /// OpenAI-compatible moderation endpoint, e.g.
+    /// `https://api.openai.com/v1/moderations`
+    pub moderation_url: Option<String>,
+    /// Bearer token the moderation endpoint expects
+    pub moderation_token: Option<String>,
+    /// Hold prompts back when the moderation endpoint can't be asked,
+    /// rather than letting them through on the word list alone
+    pub moderation_required: bool,
+}
+
+/// Window geometry, saved on exit and restored at startup
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WindowSettings {
+    /// Inner size in logical pixels, 1600x900 when unset
+    pub size: Option<[f64; 2]>,
+    /// Outer position in physical pixels, placed by the OS when unset
+    pub position: Option<[i32; 2]>,
+    pub maximized: bool,
+    pub layout: PanelLayout,
+}
+
+/// Panel sizes (in points) and visibility
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PanelLayout {
+    pub side_width: f32,
+    pub queue_width: f32,
+    /// Height of the job inspector at the bottom of the queue panel
+    pub job_inspector_height: f32,
+    /// Height of the log panel while expanded
+    pub log_height: f32,
+    pub show_side: bool,
+    pub log_expanded: bool,
+    pub inspector_open: bool,
+}
+
+impl Default for PanelLayout {
+    fn default() -> Self {
+        Self {
+            side_width: 340.0,
+            queue_width: 280.0,
+            job_inspector_height: 240.0,
+            log_height: 180.0,
+            show_side: true,
+            log_expanded: false,
+            inspector_open: false,
+        }
+    }
+}
+
+/// How finished frames are handed to the display
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PresentMode {
+    /// VSync; always supported
+    #[default]
+    Fifo,
+    /// Low-latency VSync that replaces queued frames instead of waiting
+    Mailbox,
+    /// No VSync, may tear
+    Immediate,
+}
+
+impl PresentMode {
+    pub const ALL: [Self; 3] = [Self::Fifo, Self::Mailbox, Self::Immediate];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Fifo => "VSync",
+            Self::Mailbox => "Fast VSync (mailbox)",
+            Self::Immediate => "Off (uncapped)",
+        }
+    }
+
+    pub fn to_wgpu(self) -> wgpu::PresentMode {
+        match self {
+            Self::Fifo => wgpu::PresentMode::Fifo,
+            Self::Mailbox => wgpu::PresentMode::Mailbox,
+            Self::Immediate => wgpu::PresentMode::Immediate,
+        }
+    }
+}
+
+/// Graphics API to render with
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GpuBackend {
+    Vulkan,
+    Dx12,
+    Metal,
+    Gl,
+}
+
+impl GpuBackend {
+    pub const ALL: [Self; 4] = [Self::Vulkan, Self::Dx12, Self::Metal, Self::Gl];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Vulkan => "Vulkan",
+            Self::Dx12 => "DirectX 12",
+            Self::Metal => "Metal",
+            Self::Gl => "OpenGL",
+        }
+    }
+
+    /// Parse a backend name as given on the command line, e.g. `vulkan` or `dx12`
+    pub fn parse(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "vulkan" | "vk" => Some(Self::Vulkan),
+            "dx12" | "d3d12" => Some(Self::Dx12),
+            "metal" => Some(Self::Metal),
+            "gl" | "opengl" | "gles" => Some(Self::Gl),
+            _ => None,
+        }
+    }
+
+    pub fn to_wgpu(self) -> wgpu::Backends {
+        match self {
+            Self::Vulkan => wgpu::Backends::VULKAN,
+            Self::Dx12 => wgpu::Backends::DX12,
+            Self::Metal => wgpu::Backends::METAL,
+            Self::Gl => wgpu::Backends::GL,
+        }
+    }
+}
+
+/// Smallest and largest render scale offered
+pub const RENDER_SCALE_RANGE: std::ops::RangeInclusive<f32> = 0.5..=2.0;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RenderSettings {
+    pub present_mode: PresentMode,
+    /// Upper bound on frames per second, uncapped when unset
+    pub fps_limit: Option<u32>,
+    /// Graphics API, the platform's preferred one when unset. Read at startup.
+    pub backend: Option<GpuBackend>,
+    /// Name of the GPU to render on, the high-performance default when unset.
+    /// Read at startup.
+    pub adapter: Option<String>,
+    /// Viewport clear color in linear RGB, dark gray when unset
+    pub background: Option<[f32; 3]>,
+    /// Soft shadow on the ground under the scene
+    pub ground_shadow: bool,
+    /// WGSL file drawing the splats instead of the built-in shader,
+    /// reloaded whenever it's saved
+    pub custom_shader: Option<PathBuf>,
+    /// Scene resolution relative to the viewport; below 1 renders faster,
+    /// above 1 supersamples
+    pub render_scale: f32,
+    /// Experimental side-by-side stereo preview, one half of the viewport
+    /// per eye
+    pub stereo: bool,
+}
+
+impl Default for RenderSettings {
+    fn default() -> Self {
+        Self {
+            present_mode: PresentMode::default(),
+            fps_limit: None,
+            backend: None,
+            adapter: None,
+            background: None,
+            ground_shadow: false,
+            custom_shader: None,
+            render_scale: 1.0,
+            stereo: false,
+        }
+    }
+}
+
+impl RenderSettings {
+    /// Size of the scene target drawn for a `viewport` of physical pixels
+    pub fn scene_size(&self, viewport: (u32, u32)) -> (u32, u32) {
+        let scale = self.render_scale.clamp(*RENDER_SCALE_RANGE.start(), *RENDER_SCALE_RANGE.end());
+        let scaled = |pixels: u32| ((pixels as f32 * scale).round() as u32).max(1);
+        (scaled(viewport.0), scaled(viewport.1))
+    }
+
+    /// Shortest time allowed between frames under the FPS cap
+    pub fn min_frame_interval(&self) -> Option<std::time::Duration> {
+        self.fps_limit
+            .filter(|&fps| fps > 0)
+            .map(|fps| std::time::Duration::from_secs_f64(1.0 / fps as f64))
+    }
+}
+
+impl Settings {
+    /// Location of the settings file (`<config dir>/genjutsu/settings.json`)
+    pub fn path() -> PathBuf {
+        dirs::config_dir()
+            .unwrap_or_else(|| PathBuf::from("."))
+            .join("genjutsu")
+            .join("settings.json")
+    }
+
+    /// Absolute outputs root
+    pub fn output_dir(&self) -> PathBuf {
+        let dir = self.output_dir.clone().unwrap_or_else(|| PathBuf::from("outputs"));
+        std::path::absolute(&dir).unwrap_or(dir)
+    }
+
+    /// Job history file to open
+    pub fn history_path(&self) -> PathBuf {
+        self.history_path.clone().unwrap_or_else(JobDatabase::default_path)
+    }
+
+    /// Who jobs submitted from here are attributed to
+    pub fn submitter(&self) -> Submitter {
+        Submitter::local(self.user_name.as_deref())
+    }
+
+    /// Configured generation services, or the local one
+    pub fn workers(&self) -> Vec<WorkerEndpoint> {
+        if self.workers.is_empty() {
+            return vec![WorkerEndpoint { name: "local".into(), url: HttpBackend::DEFAULT_URL.into(), token: None }];
+        }
+        self.workers.clone()
+    }
+
+    /// Load settings, falling back to defaults if the file is missing or invalid
+    pub fn load() -> Self {
+        let path = Self::path();
+        match std::fs::read_to_string(&path) {
+            Ok(contents) => serde_json::from_str(&contents).unwrap_or_else(|e| {
+                log::warn!("Ignoring invalid settings file {}: {}", path.display(), e);
+                Self::default()
+            }),
+            Err(_) => Self::default(),
+        }
+    }
+
+    pub fn save(&self) -> anyhow::Result<()> {
+        let path = Self::path();
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(&path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_scale_scene_size() {
+        let mut render = RenderSettings::default();
+        assert_eq!(render.scene_size((1280, 720)), (1280, 720));
+
+        render.render_scale = 0.5;
+        assert_eq!(render.scene_size((1281, 720)), (641, 360));
+        render.render_scale = 2.0;
+        assert_eq!(render.scene_size((1280, 720)), (2560, 1440));
+
+        // Out-of-range values from a hand-edited file are clamped
+        render.render_scale = 0.0;
+        assert_eq!(render.scene_size((1280, 720)), (640, 360));
+    }
+}
@@ -0,0 +1,60 @@
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+/// A WGSL file standing in for the built-in splat shader. It's checked for
+/// edits while the app runs, so a new splat kernel can be tried out by
+/// saving the file instead of rebuilding.
+pub struct ShaderWatch {
+    path: PathBuf,
+    /// Modification time of the source last read; None once read means the
+    /// file was missing
+    read_at: Option<Option<SystemTime>>,
+}
+
+impl ShaderWatch {
+    pub fn new(path: PathBuf) -> Self {
+        Self { path, read_at: None }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// The file's source if it changed since the last call, or the error
+    /// reading it. The first call always reads.
+    pub fn poll(&mut self) -> Option<std::io::Result<String>> {
+        let modified = std::fs::metadata(&self.path).and_then(|m| m.modified()).ok();
+        if self.read_at == Some(modified) {
+            return None;
+        }
+        self.read_at = Some(modified);
+        Some(std::fs::read_to_string(&self.path))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn test_shader_watch_poll() {
+        let path = std::env::temp_dir().join(format!("gj_shader_{}.wgsl", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let mut watch = ShaderWatch::new(path.clone());
+        assert!(matches!(watch.poll(), Some(Err(_))));
+        assert!(watch.poll().is_none());
+
+        std::fs::write(&path, "// v1").unwrap();
+        assert_eq!(watch.poll().unwrap().unwrap(), "// v1");
+        assert!(watch.poll().is_none());
+
+        // Saved again; pushed forward so coarse timestamps still differ
+        std::fs::write(&path, "// v2").unwrap();
+        let file = std::fs::File::options().write(true).open(&path).unwrap();
+        file.set_modified(SystemTime::now() + Duration::from_secs(5)).unwrap();
+        assert_eq!(watch.poll().unwrap().unwrap(), "// v2");
+
+        std::fs::remove_file(&path).unwrap();
+    }
+}
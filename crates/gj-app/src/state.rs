@@ -1,60 +1,218 @@
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 use egui_wgpu::wgpu;
 use egui_wgpu::wgpu::StoreOp;
 use winit::event::WindowEvent;
+use winit::keyboard::ModifiersState;
 use winit::window::Window;
+use serde_json::{json, Value};
 
+use gj_core::bounding_box::BoundingBox;
+use gj_core::colmap::{self, ColmapScene};
+use gj_core::color_grading::ColorGrade;
 use gj_core::gaussian_cloud::GaussianCloud;
-use gj_splat::camera::Camera;
-use gj_splat::renderer::GaussianRenderer;
+use gj_core::mesh::MeshSettings;
+use gj_core::morph::Morph;
+use gj_core::pipeline::{PipelineGraph, Stage, StageKind, TextToMultiviewConfig, CAPTURE_PHOTOS};
+use gj_core::volume::Volume;
+use gj_splat::camera::{Camera, CameraTransition};
+use gj_splat::camera_path::CameraPath;
+use gj_splat::clipping::{ClipHandle, ClipPlane, MAX_CLIP_PLANES};
+use gj_splat::renderer::{ColorMode, GaussianRenderer, SplatPrecision, DEFAULT_BACKGROUND};
+use gj_splat::selection::{self, PaintBrush};
+use gj_splat::stereo::{Eye, DEFAULT_IPD};
 
 use crate::events::{AppEvent, UiEvent};
+use crate::app::WINDOW_TITLE;
+use crate::capture::{self, SequenceMotion};
+use crate::cli::CliArgs;
+use crate::error::AppError;
 use crate::gfx::GfxState;
+use crate::images::{self, ImageLibrary};
+use crate::jobs::{self, ArtifactKind, CaptureCamera, CaptureView, Dependencies, JobDatabase, JobInputs, JobMetadata, JobOutputs, JobStatus, Redecode, Refine, RefineSettings};
+use crate::queue_window::QueueWindow;
+use crate::session::{Session, SessionCamera};
+use crate::settings::{Settings, WorkerEndpoint};
+use crate::shader_watch::ShaderWatch;
+use crate::telemetry;
+use crate::tray::{self, Tray};
+use crate::generator::{GenerationBackend, HttpBackend, ModelWeights, WorkerGpu};
+use crate::content_filter::ContentFilter;
 use crate::worker::{InferenceWorker, WorkerResponse};
-use crate::ui::UiState;
-use crate::worker;
+use crate::ui::{self, UiEventSender, UiState};
+use crate::ui::shortcuts::Keymap;
+use crate::web_viewer;
+use crate::mesh_export;
+
+/// Side length of job preview GIFs, in pixels
+const GIF_SIZE: u32 = 256;
+const GIF_FRAMES: usize = 36;
+const GIF_FRAME_MS: u32 = 80;
+
+/// Jobs quicker than this finish without a desktop notification
+const NOTIFY_AFTER_SECS: u64 = 30;
+
+/// How often the queue panel's worker loads are refreshed
+const WORKER_POLL_INTERVAL: Duration = Duration::from_secs(10);
+/// How often the job history file is checked for other machines' changes
+const HISTORY_POLL_INTERVAL: Duration = Duration::from_secs(5);
+/// How often a custom splat shader is checked for edits
+const SHADER_POLL_INTERVAL: Duration = Duration::from_millis(500);
+/// How often the session is autosaved for recovery after a crash
+const AUTOSAVE_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Seconds a playing morph takes from the scene to the target, before it
+/// swings back
+const MORPH_DURATION: f32 = 3.0;
+/// How often a playing morph rebuilds and uploads the blended cloud
+const MORPH_FRAME_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Playback position along the camera path
+pub struct PathPlayback {
+    pub time: f32,
+    pub looping: bool,
+}
 
 pub struct AppState {
     pub(crate) window: Arc<Window>,
 
     pub gfx: GfxState,
     pub ui: UiState,
+    /// Job queue popped out of the main window
+    pub queue_window: Option<QueueWindow>,
+    /// Queue window to open (true) or close (false) at the next chance
+    queue_window_request: Option<bool>,
 
     // 3D renderer state
     pub renderer: GaussianRenderer,
     pub camera: Camera,
+    pub camera_transition: Option<CameraTransition>,
+    /// Turntable speed in degrees per second, None when auto-orbit is off
+    pub auto_orbit: Option<f32>,
+    pub camera_path: CameraPath,
+    pub path_playback: Option<PathPlayback>,
+    /// Open scene paired with another cloud, shown in between the two
+    morph: Option<Morph>,
+    /// Point shown of the morph, from 0 to 1
+    morph_position: f32,
+    /// Time into a back-and-forth swing of the morph while it plays
+    morph_playback: Option<f32>,
+    last_morph_frame: Instant,
     pub gaussian_cloud: Option<GaussianCloud>,
+    /// Last upload progress sent to the UI
+    pub upload_progress: Option<f32>,
+    /// Forces the next frame to redraw the 3D scene rather than reuse the last one
+    pub scene_dirty: bool,
+    /// PLY the open scene was loaded from
+    scene_path: Option<PathBuf>,
+    /// Finished generation waiting to replace the open scene, and its PLY
+    pub pending_cloud: Option<(GaussianCloud, PathBuf)>,
+    /// Open scene recolored by distance to a comparison reference
+    pub difference_cloud: Option<GaussianCloud>,
+    pub show_differences: bool,
+    /// Selected splats by cloud index; empty when nothing is selected
+    pub selection: Vec<bool>,
+    /// Paint brush taking viewport drags instead of the camera
+    brush: Option<PaintBrush>,
+    /// Box or sphere the splats inside of can be deleted or isolated
+    volume: Option<Volume>,
 
     // App-side state exposed to UI
     pub prompt: String,
     pub status: String,
 
     pub lgm_worker: InferenceWorker,
+    pub jobs: JobDatabase,
+    /// Conditioning images kept for reuse
+    images: ImageLibrary,
+    /// Outputs root from the settings, searched when a job's output went missing
+    pub output_dir: PathBuf,
+    /// Local id of the job the worker is processing
+    pub active_job: Option<String>,
+    /// Progress of the active job, shown in the window title and taskbar
+    job_progress: Option<f32>,
+    /// New jobs wait in `held_jobs` instead of going to the worker
+    pub queue_paused: bool,
+    held_jobs: Vec<(String, JobInputs)>,
+    pub tray: Option<Tray>,
+    pub settings: Settings,
 
     // Mouse state
+    /// A camera drag is in progress; only presses over the viewport start one
     pub mouse_pressed: bool,
     pub last_mouse_pos: Option<(f32, f32)>,
+    /// Shift and Alt drags draw selections instead of orbiting
+    pub modifiers: ModifiersState,
+
+    // Frame timing for camera animation
+    pub last_frame: Instant,
+    /// Window is minimized or occluded; animations hold still and nothing is drawn
+    pub paused: bool,
+    last_worker_poll: Instant,
+    last_history_poll: Instant,
+    /// Custom splat shader being watched
+    shader_watch: Option<ShaderWatch>,
+    last_shader_poll: Instant,
+    last_autosave: Instant,
+    /// Last session written, so unchanged sessions aren't rewritten
+    saved_session: Session,
+    /// Left behind by a run that crashed, until restored
+    crashed_session: Option<Session>,
 
     // Tokio runtime for background tasks
     pub rt: tokio::runtime::Runtime,
 }
 
 impl AppState {
-    pub async fn new(window: Arc<Window>) -> anyhow::Result<Self> {
-        let gfx = GfxState::new(window.clone()).await?;
-        let ui = UiState::new(&gfx, window.clone());
+    pub async fn new(window: Arc<Window>, cli: &CliArgs) -> anyhow::Result<Self> {
+        let mut settings = Settings::load();
+        let mut gfx = GfxState::new(window.clone(), &cli.apply(&settings.render)).await?;
+        gfx.set_stereo(settings.render.stereo);
+
+        let keymap = Keymap::from_settings(&settings.shortcuts);
+
+        // Write out any missing bindings so every shortcut is editable in the settings file
+        let known = settings.shortcuts.len();
+        for (id, shortcut) in keymap.to_settings() {
+            settings.shortcuts.entry(id).or_insert(shortcut);
+        }
+        if settings.shortcuts.len() != known
+            && let Err(e) = settings.save()
+        {
+            log::warn!("Failed to save settings: {}", e);
+        }
 
-        let renderer = GaussianRenderer::new(
+        let mut ui = UiState::new(&gfx, window.clone(), keymap);
+        ui.update_scene_texture(&gfx);
+        ui.apply_layout(&settings.window.layout);
+        ui.apply_appearance(&settings.appearance);
+
+        let mut renderer = GaussianRenderer::new(
             gfx.device.clone(),
             gfx.queue.clone(),
             gfx.config.format
         ).await;
+        renderer.set_background(settings.render.background.unwrap_or(DEFAULT_BACKGROUND));
+        renderer.set_ground_shadow(settings.render.ground_shadow);
+        let shader_watch = settings.render.custom_shader.clone().map(ShaderWatch::new);
 
         let mut camera = Camera::default();
         let size = window.inner_size();
         camera.aspect_ratio = size.width as f32 / size.height as f32;
         
-        let lgm_worker = InferenceWorker::new();
+        let output_dir = settings.output_dir();
+        if let Err(e) = std::fs::create_dir_all(&output_dir) {
+            log::warn!("Failed to create output directory {}: {}", output_dir.display(), e);
+        }
+        telemetry::init(settings.otlp_endpoint.as_deref());
+        let lgm_worker = InferenceWorker::new(&settings.workers(), output_dir.clone());
+        let filter = ContentFilter::new(&settings.content_filter);
+        if filter.is_active() {
+            log::info!("Prompts are checked by the content filter before submission");
+            lgm_worker.set_content_filter(filter);
+        }
 
         let rt = tokio::runtime::Builder::new_multi_thread()
             .worker_threads(4)
@@ -65,16 +223,55 @@ impl AppState {
             window,
             renderer,
             camera,
+            camera_transition: None,
+            auto_orbit: None,
+            camera_path: CameraPath::new(),
+            path_playback: None,
+            morph: None,
+            morph_position: 0.0,
+            morph_playback: None,
+            last_morph_frame: Instant::now(),
             lgm_worker,
+            jobs: JobDatabase::new(settings.history_path()).with_submitter(settings.submitter()),
+            images: ImageLibrary::new(settings.history_path().with_file_name("images.json"), output_dir.join("inputs")),
+            output_dir,
+            active_job: None,
+            job_progress: None,
+            queue_paused: false,
+            held_jobs: Vec::new(),
+            tray: None,
+            settings,
             gfx,
             ui,
+            queue_window: None,
+            queue_window_request: None,
             gaussian_cloud: None,
+            upload_progress: None,
+            scene_dirty: true,
+            scene_path: None,
+            pending_cloud: None,
+            difference_cloud: None,
+            show_differences: false,
+            selection: Vec::new(),
+            brush: None,
+            volume: None,
 
             prompt: String::new(),
             status: "Ready".into(),
 
             mouse_pressed: false,
             last_mouse_pos: None,
+            modifiers: ModifiersState::empty(),
+
+            last_frame: Instant::now(),
+            paused: false,
+            last_worker_poll: Instant::now().checked_sub(WORKER_POLL_INTERVAL).unwrap_or_else(Instant::now),
+            last_history_poll: Instant::now(),
+            shader_watch,
+            last_shader_poll: Instant::now(),
+            last_autosave: Instant::now(),
+            saved_session: Session::default(),
+            crashed_session: Session::load_unclean(&Session::path()).filter(Session::has_scene),
 
             rt,
         })
@@ -83,6 +280,22 @@ impl AppState {
     pub fn init(&mut self) {
         // Seed UI with initial state
         self.ui.push_app_event(AppEvent::Status(self.status.clone()));
+        self.ui.push_app_event(AppEvent::RenderSettings(self.settings.render.clone()));
+        self.ui.push_app_event(AppEvent::Appearance(self.settings.appearance.clone()));
+        self.ui.push_app_event(AppEvent::Confirmations(self.settings.confirm.clone()));
+        self.ui.push_app_event(AppEvent::GpuAdapters {
+            active: self.gfx.adapter_info.name.clone(),
+            available: self.gfx.adapters.iter().map(|info| info.name.clone()).collect(),
+        });
+        self.ui.push_app_event(AppEvent::LocalSubmitter(self.jobs.submitter().clone()));
+        if self.crashed_session.is_some() {
+            self.ui.push_app_event(AppEvent::SessionRecoverable);
+        }
+        self.flag_orphaned_jobs();
+        self.push_jobs();
+        self.ui.push_app_event(AppEvent::InputImages(self.images.images().to_vec()));
+        self.lgm_worker.request_models();
+        self.poll_model_weights();
 
         if self.gaussian_cloud.is_some() {
             self.ui.push_app_event(AppEvent::SceneReady);
@@ -91,10 +304,19 @@ impl AppState {
 
     // --- Window resizing ----------------------------------------------------
 
+    /// Hold animations and drawing while the window can't be seen
+    pub fn set_paused(&mut self, paused: bool) {
+        self.paused = paused;
+        if !paused {
+            self.last_frame = Instant::now();
+            self.scene_dirty = true;
+        }
+    }
+
     pub fn resize(&mut self, new_size: winit::dpi::PhysicalSize<u32>) {
+        // The scene target follows the viewport, which the next frame lays out
         if new_size.width > 0 && new_size.height > 0 {
             self.gfx.resize(new_size);
-            self.camera.aspect_ratio = new_size.width as f32 / new_size.height as f32;
         }
     }
 
@@ -104,29 +326,60 @@ impl AppState {
         use winit::event::{ElementState, MouseScrollDelta};
 
         match event {
-            WindowEvent::MouseInput { state, .. } => {
-                self.mouse_pressed = *state == ElementState::Pressed;
-                if !self.mouse_pressed {
-                    self.last_mouse_pos = None;
-                }
-                true
+            // A drag that starts over the viewport keeps the camera until
+            // released, wherever the pointer goes; one that starts over a
+            // panel never reaches it
+            WindowEvent::MouseInput { state: ElementState::Pressed, .. } => {
+                self.mouse_pressed = self.ui.pointer_over_viewport();
+                self.mouse_pressed
+            }
+            WindowEvent::MouseInput { state: ElementState::Released, .. } => {
+                self.mouse_pressed = false;
+                self.last_mouse_pos = None;
+                false
             }
 
             WindowEvent::CursorMoved { position, .. } => {
                 let pos = (position.x as f32, position.y as f32);
 
-                if self.mouse_pressed {
-                    if let Some((lx, ly)) = self.last_mouse_pos {
-                        let dx = pos.0 - lx;
-                        let dy = pos.1 - ly;
-                        self.camera.rotate(dx * 0.1, -dy * 0.1);
-                    }
+                let selecting = self.modifiers.shift_key() || self.modifiers.alt_key() || self.brush.is_some();
+                if self.mouse_pressed
+                    && !selecting
+                    && let Some((lx, ly)) = self.last_mouse_pos
+                {
+                    let dx = pos.0 - lx;
+                    let dy = pos.1 - ly;
+                    self.camera.rotate(dx * 0.1, -dy * 0.1);
                 }
 
                 self.last_mouse_pos = Some(pos);
+                self.mouse_pressed
+            }
+
+            // Photos go to the input image library, e.g. to build a capture set
+            WindowEvent::DroppedFile(path) if images::is_image(path) => {
+                match self.images.add(path) {
+                    Ok(image) => {
+                        self.ui.push_app_event(AppEvent::InputImages(self.images.images().to_vec()));
+                        self.ui.push_app_event(AppEvent::Status(format!("Added {} to the input images", image.source.display())));
+                    }
+                    Err(e) => self.ui.push_app_event(AppEvent::Status(format!("Error: {}", e))),
+                }
+                true
+            }
+
+            WindowEvent::DroppedFile(path) => {
+                self.open_file(path);
+                self.scene_dirty = true;
                 true
             }
 
+            WindowEvent::ModifiersChanged(modifiers) => {
+                self.modifiers = modifiers.state();
+                false
+            }
+
+            WindowEvent::MouseWheel { .. } if !self.ui.pointer_over_viewport() => false,
             WindowEvent::MouseWheel { delta, .. } => {
                 let scroll = match delta {
                     MouseScrollDelta::LineDelta(_, y) => *y,
@@ -144,26 +397,214 @@ impl AppState {
     // --- Event processing from UI ------------------------------------------
 
     pub fn update(&mut self) {
+        let now = Instant::now();
+        // Clamp so a long idle period doesn't make animations jump
+        let dt = if self.paused { 0.0 } else { (now - self.last_frame).as_secs_f32().min(0.1) };
+        self.last_frame = now;
+
+        if let Some(transition) = &mut self.camera_transition
+            && transition.step(&mut self.camera, dt)
+        {
+            self.camera_transition = None;
+        }
+
+        if let Some(speed) = self.auto_orbit {
+            self.camera.rotate(speed * dt, 0.0);
+        }
+
+        if let Some(playback) = &mut self.path_playback {
+            playback.time += dt;
+
+            let duration = self.camera_path.duration();
+            if playback.looping && duration > 0.0 {
+                playback.time %= duration;
+            }
+
+            if let Some(pose) = self.camera_path.sample(playback.time) {
+                pose.apply(&mut self.camera);
+            }
+
+            if !playback.looping && playback.time >= duration {
+                self.path_playback = None;
+                self.ui.push_app_event(AppEvent::CameraPathPlaying(false));
+            }
+        }
+
+        if let Some(time) = &mut self.morph_playback {
+            *time = (*time + dt) % (2.0 * MORPH_DURATION);
+            // Every blend is a new cloud to upload, so only a few a second
+            if self.last_morph_frame.elapsed() >= MORPH_FRAME_INTERVAL {
+                self.last_morph_frame = Instant::now();
+                let position = 1.0 - (*time / MORPH_DURATION - 1.0).abs();
+                if position != self.morph_position {
+                    self.morph_position = position;
+                    self.reload_splats();
+                    self.ui.push_app_event(AppEvent::MorphPosition { position, playing: true });
+                }
+            }
+        }
+
+        self.dispatch_scheduled_jobs();
+        self.dispatch_waiting_jobs();
+        if self.settings.workers.len() > 1 && self.last_worker_poll.elapsed() >= WORKER_POLL_INTERVAL {
+            self.poll_worker_loads();
+        }
+        if self.last_history_poll.elapsed() >= HISTORY_POLL_INTERVAL {
+            self.last_history_poll = Instant::now();
+            if self.jobs.reload_if_changed() {
+                self.push_jobs();
+            }
+        }
+        if self.poll_custom_shader() {
+            self.scene_dirty = true;
+        }
+        if self.last_autosave.elapsed() >= AUTOSAVE_INTERVAL {
+            self.autosave_session();
+        }
+
         // Check for responses from the LGM worker
         while let Some(response) = self.lgm_worker.try_recv_response() {
             match response {
-                WorkerResponse::Success(cloud) => {
-                    self.load_gaussian_cloud(cloud);
-                    self.ui.push_app_event(AppEvent::SceneReady);
+                WorkerResponse::Success { cloud, path, latent, samples } => {
+                    let gaussians = cloud.count;
+                    self.notify_if_hidden("Generation complete", &format!("{} Gaussians", gaussians));
+                    let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs();
+                    let refined_from = self.active_job.as_deref()
+                        .and_then(|id| self.jobs.get(id))
+                        .filter(|job| job.inputs.refine.is_some())
+                        .and_then(|job| job.parent.clone());
+                    let export_mesh = self.active_job.clone()
+                        .filter(|id| self.jobs.get(id).is_some_and(|job| job.inputs.export_mesh));
+                    self.finish_active_job(|job| {
+                        job.status = JobStatus::Completed;
+                        job.error = None;
+                        // A re-run keeps the files of the earlier runs
+                        match &mut job.outputs {
+                            Some(outputs) => {
+                                outputs.add(ArtifactKind::Splats, &path);
+                                outputs.latent = latent;
+                            }
+                            None => job.outputs = Some(JobOutputs { latent, ..JobOutputs::new(&path) }),
+                        }
+                        if let Some(outputs) = &mut job.outputs {
+                            for sample in &samples {
+                                outputs.add(ArtifactKind::Sample, sample);
+                            }
+                        }
+                        job.duration_secs = job.started_at.map(|started| now.saturating_sub(started));
+                    });
+                    if let Some(parent) = refined_from {
+                        self.jobs.update(&parent, |job| {
+                            if let Some(outputs) = &mut job.outputs {
+                                outputs.add(ArtifactKind::Refined, &path);
+                            }
+                        });
+                        self.push_jobs();
+                    }
+                    if let Some(id) = export_mesh {
+                        self.export_job_mesh(&id);
+                    }
+
+                    // Don't yank away a scene the user is looking at
+                    let loaded = self.gaussian_cloud.is_none();
+                    if loaded {
+                        self.load_gaussian_cloud(*cloud);
+                        self.scene_path = Some(path);
+                        self.ui.push_app_event(AppEvent::SceneReady);
+                    } else {
+                        self.pending_cloud = Some((*cloud, path));
+                    }
+                    self.ui.push_app_event(AppEvent::JobCompleted { gaussians, loaded });
                 }
                 WorkerResponse::Error(err) => {
                     self.status = format!("Error: {}", err);
                     self.ui.push_app_event(AppEvent::Status(self.status.clone()));
-                    self.ui.push_app_event(AppEvent::Log(format!("Pipeline error: {}", err)));
+                    if let AppError::ServiceUnreachable(msg) = &err {
+                        self.ui.push_app_event(AppEvent::ServiceDisconnected(msg.clone()));
+                    } else {
+                        self.ui.push_app_event(AppEvent::JobFailed(err.clone()));
+                    }
+                    log::error!("Pipeline error: {}", err);
+                    self.notify_if_hidden("Generation failed", &err.to_string());
+                    self.fail_active_job(err);
                 }
                 WorkerResponse::Progress(p, ..) => {
                     self.ui.push_app_event(AppEvent::Progress(p));
+                    self.set_job_progress(Some(p));
                 }
                 WorkerResponse::Status(s) => {
                     self.status = s.clone();
                     self.ui.push_app_event(AppEvent::Status(s));
                 },
-                WorkerResponse::JobSubmitted(jobId) => self.ui.push_app_event(AppEvent::Status(jobId))
+                WorkerResponse::JobStarted(id) => {
+                    let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs();
+                    self.jobs.update(&id, |job| {
+                        job.status = JobStatus::Running;
+                        job.started_at = Some(now);
+                    });
+                    self.active_job = Some(id);
+                    self.set_job_progress(Some(0.0));
+                    self.push_jobs();
+                }
+                WorkerResponse::Retrying { attempt, error } => {
+                    // Nothing is running on the service yet
+                    if let Some(id) = &self.active_job {
+                        self.jobs.update(id, |job| {
+                            job.status = JobStatus::Queued;
+                            job.message = Some(format!("{}, retrying (attempt {})", error.title(), attempt));
+                        });
+                        self.push_jobs();
+                    }
+                    self.ui.push_app_event(AppEvent::Status(format!("{}, retrying (attempt {})", error, attempt)));
+                }
+                WorkerResponse::JobSubmitted(job_id) => {
+                    if let Some(id) = &self.active_job {
+                        self.jobs.update(id, |job| {
+                            job.remote_id = Some(job_id.clone());
+                            job.status = JobStatus::Running;
+                            job.message = None;
+                        });
+                        self.push_jobs();
+                    }
+                    self.ui.push_app_event(AppEvent::Status(job_id));
+                }
+                WorkerResponse::JobStuck => {
+                    if let Some(id) = self.active_job.take() {
+                        self.jobs.update(&id, |job| job.stuck = true);
+                        self.push_jobs();
+                        self.ui.push_app_event(AppEvent::JobStuck(id));
+                    }
+                }
+                WorkerResponse::Log(lines) => {
+                    if let Some(id) = &self.active_job {
+                        self.jobs.append_log(id, lines);
+                        self.push_jobs();
+                    }
+                }
+                WorkerResponse::Preview(image) => {
+                    if let Some(id) = &self.active_job {
+                        self.ui.push_app_event(AppEvent::JobPreview { id: id.clone(), image: Arc::new(image) });
+                    }
+                }
+                WorkerResponse::Incompatible(err) => {
+                    log::error!("{}", err);
+                    self.ui.push_app_event(AppEvent::ServiceIncompatible(err));
+                }
+                WorkerResponse::Models(list) => {
+                    log::info!("Generation backend offers models: {}", list.models.join(", "));
+                    for model in gj_core::Model3D::all() {
+                        if !list.models.iter().any(|m| m == model.id()) {
+                            log::warn!("Model {} is not available on the generation backend", model.name());
+                        }
+                    }
+                    if let Some(vram_mb) = list.vram_mb {
+                        log::info!("Generation workers have {} MiB of GPU memory", vram_mb);
+                    }
+                    self.ui.push_app_event(AppEvent::WorkerVram(list.vram_mb));
+                    self.ui.push_app_event(AppEvent::BackendModels([list.models, list.multiview].concat()));
+                    self.ui.push_app_event(AppEvent::ModelParams(list.params));
+                    self.ui.push_app_event(AppEvent::ModelPresets(list.presets));
+                }
             }
         }
 
@@ -172,202 +613,2168 @@ impl AppState {
         for ev in ui_events {
             match ev {
                 UiEvent::ResetCamera => {
+                    self.camera_transition = None;
                     self.camera = Camera::default();
-                    let size = self.window.inner_size();
-                    self.camera.aspect_ratio = size.width as f32 / size.height as f32;
+                    let (width, height) = self.gfx.scene.size();
+                    self.camera.aspect_ratio = width as f32 / height as f32;
 
                     self.ui.push_app_event(AppEvent::Status("Camera reset".into()));
                 }
 
+                UiEvent::Screenshot => {
+                    self.save_screenshot();
+                }
+
+                UiEvent::ExportSequence { motion, width, height, frames } => {
+                    self.export_sequence(motion, width, height, frames);
+                }
+
+                UiEvent::ExportTransforms { width, height } => {
+                    self.export_transforms(width, height);
+                }
+
+                UiEvent::FocusAt { x, y } => {
+                    self.focus_at(x, y);
+                }
+
+                UiEvent::FrameScene => {
+                    if let Some(cloud) = &self.gaussian_cloud {
+                        let (target, distance) = framing(&cloud.bounds());
+                        self.camera_transition = Some(CameraTransition::new(&self.camera, target, distance, 0.35));
+                    }
+                }
+
+                UiEvent::SetAutoOrbit { enabled, speed } => {
+                    self.auto_orbit = enabled.then_some(speed);
+                    self.last_frame = Instant::now();
+                }
+
+                UiEvent::AddCameraKeyframe(time) => {
+                    self.camera_path.add_keyframe(time, &self.camera);
+                    self.push_camera_path();
+                }
+
+                UiEvent::RemoveCameraKeyframe(index) => {
+                    self.camera_path.remove_keyframe(index);
+                    self.push_camera_path();
+                }
+
+                UiEvent::ClearCameraPath => {
+                    self.camera_path.clear();
+                    self.path_playback = None;
+                    self.push_camera_path();
+                    self.ui.push_app_event(AppEvent::CameraPathPlaying(false));
+                }
+
+                UiEvent::PlayCameraPath { looping } => {
+                    if !self.camera_path.is_empty() {
+                        self.camera_transition = None;
+                        self.path_playback = Some(PathPlayback { time: 0.0, looping });
+                        self.last_frame = Instant::now();
+                        self.ui.push_app_event(AppEvent::CameraPathPlaying(true));
+                    }
+                }
+
+                UiEvent::StopCameraPath => {
+                    self.path_playback = None;
+                    self.ui.push_app_event(AppEvent::CameraPathPlaying(false));
+                }
+
                 UiEvent::ToggleWireframe(enabled) => {
                     self.ui.push_app_event(AppEvent::WireframeState(enabled));
                 }
 
-                UiEvent::GenerateWithModel { prompt, model } => {
-                    let worker_tx = self.lgm_worker.command_tx.clone();
+                UiEvent::SetCompactSplats(compact) => {
+                    self.renderer.set_precision(if compact { SplatPrecision::Compact } else { SplatPrecision::Full });
+                    self.reload_splats();
+                }
+
+                UiEvent::SetColorMode(mode) => {
+                    self.renderer.set_color_mode(mode);
+                    self.reload_splats();
+                    self.ui.push_app_event(AppEvent::ColorMode(mode));
+                }
+
+                UiEvent::SetBackground(background) => {
+                    self.renderer.set_background(background.unwrap_or(DEFAULT_BACKGROUND));
+                    self.scene_dirty = true;
+                    self.settings.render.background = background;
+                    self.save_settings();
+                    self.ui.push_app_event(AppEvent::RenderSettings(self.settings.render.clone()));
+                }
+
+                UiEvent::SetGroundShadow(enabled) => {
+                    self.renderer.set_ground_shadow(enabled);
+                    self.scene_dirty = true;
+                    self.settings.render.ground_shadow = enabled;
+                    self.save_settings();
+                    self.ui.push_app_event(AppEvent::RenderSettings(self.settings.render.clone()));
+                }
+
+                UiEvent::BrowseCustomShader => {
                     let ui_tx = self.ui.app_event_sender_clone();
                     let window = self.window.clone();
-                    let prompt_clone = prompt.clone();
-
-                    self.prompt = prompt;
 
                     self.rt.spawn_blocking(move || {
-                        let _ = ui_tx.send(AppEvent::Status(
-                            format!("Generating with {:?}...", model)
-                        ));
-
-                        if let Err(e) = worker_tx.send(worker::WorkerCommand::GenerateFromPrompt {
-                            prompt: prompt_clone,
-                            model: model.into() // Convert UI model to worker model
-                        }) {
-                            let _ = ui_tx.send(AppEvent::Status(format!("Worker error: {}", e)));
+                        if let Some(path) = rfd::FileDialog::new()
+                            .add_filter("WGSL shader", &["wgsl"])
+                            .pick_file()
+                        {
+                            let _ = ui_tx.send(AppEvent::CustomShaderPicked(path));
                         }
-
                         window.request_redraw();
                     });
                 }
 
-                UiEvent::PromptChanged(new_prompt) => {
-                    self.prompt = new_prompt;
-                }
-
-                UiEvent::LoadImages => {
-                    let window = self.window.clone();
-                    let worker_tx = self.lgm_worker.command_tx.clone();
+                UiEvent::BrowseInputImage => {
                     let ui_tx = self.ui.app_event_sender_clone();
+                    let window = self.window.clone();
 
-                    // Spawn file picker on blocking thread pool
                     self.rt.spawn_blocking(move || {
-                        let _ = ui_tx.send(AppEvent::Status("Opening file dialog...".into()));
-
-                        if let Some(files) = rfd::FileDialog::new()
-                            .add_filter("Images", &["png", "jpg", "jpeg"])
-                            .pick_files()
+                        if let Some(path) = rfd::FileDialog::new()
+                            .add_filter("Image", &images::EXTENSIONS)
+                            .pick_file()
                         {
-                            let _ = ui_tx.send(AppEvent::Status("Loading images...".into()));
+                            let _ = ui_tx.send(AppEvent::InputImagePicked(path));
+                        }
+                        window.request_redraw();
+                    });
+                }
 
-                            // Load images on this thread
-                            let images: Result<Vec<_>, _> = files.iter()
-                                .enumerate()
-                                .map(|(i, path)| {
-                                    let progress = (i as f32) / (files.len() as f32);
-                                    let _ = ui_tx.send(AppEvent::Progress(progress));
-                                    image::open(path).map(|img| img.to_rgba8())
-                                })
-                                .collect();
+                UiEvent::AddInputImage(path) => {
+                    match self.images.add(&path) {
+                        Ok(image) => {
+                            self.ui.push_app_event(AppEvent::InputImages(self.images.images().to_vec()));
+                            self.ui.push_app_event(AppEvent::InputImageAdded(image.path));
+                        }
+                        Err(e) => self.ui.push_app_event(AppEvent::Status(format!("Error: {}", e))),
+                    }
+                }
 
-                            match images {
-                                Ok(images) => {
-                                    let _ = ui_tx.send(AppEvent::Status("Generating 3D model...".into()));
+                UiEvent::RemoveInputImage(id) => {
+                    if self.images.remove(&id) {
+                        self.ui.push_app_event(AppEvent::InputImages(self.images.images().to_vec()));
+                    }
+                }
 
-                                    // Send images to worker for processing
-                                    if let Err(e) = worker_tx.send(crate::worker::WorkerCommand::GenerateFromImages(images)) {
-                                        let _ = ui_tx.send(AppEvent::Status(format!("Worker error: {}", e)));
-                                    }
-                                }
-                                Err(e) => {
-                                    let _ = ui_tx.send(AppEvent::Status(format!("Failed to load images: {}", e)));
-                                    let _ = ui_tx.send(AppEvent::Log(format!("Image load error: {}", e)));
-                                }
-                            }
-                        } else {
-                            let _ = ui_tx.send(AppEvent::Status("File selection cancelled".into()));
-                        }
+                UiEvent::BrowseColmap => {
+                    let ui_tx = self.ui.app_event_sender_clone();
+                    let window = self.window.clone();
 
+                    self.rt.spawn_blocking(move || {
+                        if let Some(dir) = rfd::FileDialog::new().set_title("COLMAP sparse model (cameras.txt, images.txt)").pick_folder() {
+                            let _ = ui_tx.send(AppEvent::ColmapPicked(dir));
+                        }
                         window.request_redraw();
                     });
                 }
 
-                UiEvent::Log(msg) => {
-                    self.ui.push_app_event(AppEvent::Log(format!("UI: {}", msg)));
+                UiEvent::ImportColmap(dir) => {
+                    let imported = self.import_colmap(&dir);
+                    // Photos added before a failure stay in the library
+                    self.ui.push_app_event(AppEvent::InputImages(self.images.images().to_vec()));
+                    match imported {
+                        Ok(capture) => {
+                            self.ui.push_app_event(AppEvent::Status(format!(
+                                "Imported {} posed photos from {}", capture.len(), dir.display()
+                            )));
+                            self.ui.push_app_event(AppEvent::CaptureSet(capture));
+                        }
+                        Err(e) => self.ui.push_app_event(AppEvent::Status(format!("Error: {}", e))),
+                    }
                 }
-                _ => {}
-            }
-        }
-    }
 
-    pub fn load_gaussian_cloud(&mut self, cloud: GaussianCloud) {
-        // Compute bounds
-        let bounds = cloud.bounds();
-        let center = bounds.center();
-        let size = bounds.size();
-        let max_dim = size[0].max(size[1]).max(size[2]);
+                UiEvent::SetCustomShader(path) => {
+                    self.settings.render.custom_shader = path.clone();
+                    self.save_settings();
+                    self.ui.push_app_event(AppEvent::RenderSettings(self.settings.render.clone()));
 
-        println!("Mesh bounds:");
-        println!("  Center: [{:.3}, {:.3}, {:.3}]", center[0], center[1], center[2]);
-        println!("  Size: [{:.3}, {:.3}, {:.3}]", size[0], size[1], size[2]);
-        println!("  Max dimension: {:.3}", max_dim);
+                    self.shader_watch = path.map(ShaderWatch::new);
+                    if self.shader_watch.is_none() {
+                        if let Err(e) = pollster::block_on(self.renderer.set_shader(None)) {
+                            log::error!("Built-in splat shader failed to compile: {}", e);
+                        }
+                        self.ui.push_app_event(AppEvent::ShaderError(None));
+                    }
+                    // Read right away rather than at the next poll
+                    self.last_shader_poll = Instant::now().checked_sub(SHADER_POLL_INTERVAL).unwrap_or_else(Instant::now);
+                    self.poll_custom_shader();
+                    self.scene_dirty = true;
+                }
 
-        // Auto-adjust camera distance based on mesh size
-        self.camera.distance = max_dim * 2.5;
-        self.camera.target = glam::Vec3::new(center[0], center[1], center[2]);
-        self.camera.update_position();
+                UiEvent::AddClipPlane { axis } => {
+                    let mut planes = self.renderer.clip_planes().to_vec();
+                    if planes.len() < MAX_CLIP_PLANES {
+                        let center = self.gaussian_cloud.as_ref().map_or([0.0; 3], |cloud| cloud.bounds().center());
+                        planes.push(ClipPlane::axis(axis, center[axis.min(2)]));
+                        self.set_clip_planes(planes);
+                    }
+                }
 
-        self.renderer.load_gaussians(&cloud);
-        self.gaussian_cloud = Some(cloud);
-    }
+                UiEvent::SetClipPlanes(planes) => {
+                    self.set_clip_planes(planes);
+                }
 
-    // --- 3D rendering + UI rendering ---------------------------------------
+                UiEvent::MoveClipPlane { index, delta } => {
+                    let mut planes = self.renderer.clip_planes().to_vec();
+                    if let Some(plane) = planes.get_mut(index) {
+                        plane.offset += delta;
+                        self.set_clip_planes(planes);
+                    }
+                }
 
-    pub fn render(&mut self) -> anyhow::Result<()> {
-        let size = self.window.inner_size();
-        if size.width == 0 || size.height == 0 {
-            return Ok(());
-        }
+                UiEvent::SetColorGrade(grade) => {
+                    self.renderer.set_color_grade(grade);
+                }
 
-        let output = self.gfx.surface.get_current_texture()?;
-        let view = output.texture.create_view(&wgpu::TextureViewDescriptor::default());
-        let mut encoder = self.gfx.device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
-            label: Some("Render Encoder")
-        });
+                UiEvent::SetDepthOfField(dof) => {
+                    self.renderer.set_depth_of_field(dof);
+                }
 
-        // --- 3D scene -------------------------------------------------------
+                UiEvent::FocusDepthAt { x, y } => {
+                    self.focus_depth_at(x, y);
+                }
 
-        if let Some(ref cloud) = self.gaussian_cloud {
-            let size = self.window.inner_size();
-            self.renderer.render(
-                &mut encoder,
-                &view,
-                &self.gfx.depth_view,
-                &self.camera,
-                (size.width, size.height),
-            );
-        } else {
-            let _ = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
-                label: Some("Clear Pass"),
-                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
-                    view: &view,
-                    resolve_target: None,
-                    depth_slice: None,
-                    ops: wgpu::Operations {
-                        load: wgpu::LoadOp::Clear(wgpu::Color { r: 0.1, g: 0.1, b: 0.1, a: 1.0 }),
-                        store: StoreOp::Store,
-                    },
-                })],
-                depth_stencil_attachment: None,
-                ..Default::default()
-            });
-        }
+                UiEvent::BakeColorGrade => {
+                    let grade = self.renderer.color_grade();
+                    if let Some(cloud) = &mut self.gaussian_cloud {
+                        cloud.apply_color_grade(&grade);
+                        self.renderer.set_color_grade(ColorGrade::default());
+                        self.reload_splats();
+                        self.ui.push_app_event(AppEvent::ColorGradeBaked);
+                        self.ui.push_app_event(AppEvent::Status("Color grade baked into the scene".into()));
+                    }
+                }
 
-        // --- UI -------------------------------------------------------------
+                UiEvent::SetPresentMode(mode) => {
+                    if !self.gfx.set_present_mode(mode.to_wgpu()) {
+                        self.ui.push_app_event(AppEvent::Status(
+                            format!("{} isn't supported on this display, using VSync", mode.label())
+                        ));
+                    }
+                    self.settings.render.present_mode = mode;
+                    self.save_settings();
+                }
 
-        let (full_output, ui_events) = self.ui.draw(&self.window);
+                UiEvent::SetGpu { backend, adapter } => {
+                    self.settings.render.backend = backend;
+                    self.settings.render.adapter = adapter;
+                    self.save_settings();
+                    self.ui.push_app_event(AppEvent::Status("GPU selection applies after a restart".into()));
+                }
 
-        let platform_output = full_output.platform_output.clone();
-        self.ui.egui_state.handle_platform_output(&self.window, platform_output);
+                UiEvent::SetFpsLimit(limit) => {
+                    self.settings.render.fps_limit = limit;
+                    self.save_settings();
+                }
 
-        let shapes = full_output.shapes.clone();
-        let pixels_per_point = full_output.pixels_per_point;
-        let paint_jobs = self.ui.egui_ctx.tessellate(shapes, pixels_per_point);
+                UiEvent::SetRenderScale(scale) => {
+                    // The scene target follows on the next frame
+                    self.settings.render.render_scale = scale;
+                    self.save_settings();
+                }
 
-        let size = self.window.inner_size();
-        let screen_desc = egui_wgpu::ScreenDescriptor {
-            size_in_pixels: [size.width, size.height],
-            pixels_per_point: self.window.scale_factor() as f32,
-        };
+                UiEvent::SetStereo(enabled) => {
+                    if self.gfx.set_stereo(enabled) {
+                        self.ui.update_scene_texture(&self.gfx);
+                    }
+                    self.scene_dirty = true;
+                    self.settings.render.stereo = enabled;
+                    self.save_settings();
+                    self.ui.push_app_event(AppEvent::RenderSettings(self.settings.render.clone()));
+                }
 
-        for (id, delta) in &full_output.textures_delta.set {
-            self.ui.egui_renderer.update_texture(&self.gfx.device, &self.gfx.queue, *id, delta);
-        }
+                UiEvent::SetAppearance(appearance) => {
+                    self.ui.apply_appearance(&appearance);
+                    if let Some(queue_window) = &self.queue_window {
+                        ui::apply_appearance(queue_window.context(), &appearance);
+                    }
+                    self.ui.push_app_event(AppEvent::Appearance(appearance.clone()));
+                    self.settings.appearance = appearance;
+                    self.save_settings();
+                }
 
-        self.ui.egui_renderer.update_buffers(
-            &self.gfx.device,
-            &self.gfx.queue,
-            &mut encoder,
-            &paint_jobs,
-            &screen_desc,
-        );
+                UiEvent::SetConfirmations(confirm) => {
+                    self.ui.push_app_event(AppEvent::Confirmations(confirm.clone()));
+                    self.settings.confirm = confirm;
+                    self.save_settings();
+                }
+
+                UiEvent::Generate(inputs) => {
+                    self.prompt = inputs.prompt.clone();
+                    self.ui.push_app_event(AppEvent::Status(
+                        format!("Generating with {:?}...", inputs.model())
+                    ));
+                    if let Err(e) = self.submit_pipeline(inputs, None) {
+                        self.ui.push_app_event(AppEvent::Status(format!("Error: {}", e)));
+                    }
+                }
+
+                UiEvent::ScheduleJob { inputs, run_at } => {
+                    match self.submit_pipeline(inputs, Some(run_at)) {
+                        Ok(id) => self.ui.push_app_event(AppEvent::Status(format!("Scheduled {}", id))),
+                        Err(e) => self.ui.push_app_event(AppEvent::Status(format!("Error: {}", e))),
+                    }
+                }
+
+                UiEvent::DeleteJobs { ids, delete_files } => {
+                    // Jobs still waiting on the worker would come back as orphans
+                    let (removable, busy): (Vec<_>, Vec<_>) = ids.into_iter().partition(|id| {
+                        self.jobs.get(id).is_none_or(|job| {
+                            job.status.is_finished() || matches!(job.status, JobStatus::Scheduled | JobStatus::Waiting) || job.stuck
+                        })
+                    });
+
+                    if delete_files {
+                        let files: Vec<PathBuf> = removable.iter()
+                            .filter_map(|id| self.jobs.get(id)?.outputs.as_ref())
+                            .flat_map(|outputs| outputs.files().map(Path::to_path_buf))
+                            .collect();
+                        for file in files {
+                            if let Err(e) = std::fs::remove_file(&file)
+                                && e.kind() != std::io::ErrorKind::NotFound
+                            {
+                                log::warn!("Failed to delete {}: {}", file.display(), e);
+                            }
+                        }
+                    }
+
+                    self.jobs.remove(&removable);
+                    if !busy.is_empty() {
+                        self.ui.push_app_event(AppEvent::Status(
+                            format!("Skipped {} unfinished job(s)", busy.len())
+                        ));
+                    }
+                    self.push_jobs();
+                }
+
+                UiEvent::RetryJobs(ids) => {
+                    let retries: Vec<_> = ids.iter()
+                        .filter_map(|id| self.jobs.get(id))
+                        .map(|job| (job.inputs.clone(), job.parent.clone()))
+                        .collect();
+
+                    self.ui.push_app_event(AppEvent::Status(format!("Retrying {} job(s)", retries.len())));
+                    for (inputs, parent) in retries {
+                        self.submit_job(inputs, parent);
+                    }
+                }
+
+                UiEvent::RedecodeJob { id, settings } => {
+                    let Some(job) = self.jobs.get(&id) else {
+                        continue;
+                    };
+                    let redecode = Redecode { latent: String::new(), source: None, settings };
+                    let inputs = JobInputs { redecode: Some(redecode), ..job.inputs.clone() };
+                    self.submit_second_stage(id, inputs);
+                }
+
+                UiEvent::RefineJob { id, settings } => {
+                    let Some(job) = self.jobs.get(&id) else {
+                        continue;
+                    };
+                    let refine = Refine { ply: String::new(), source: None, settings };
+                    let inputs = JobInputs { refine: Some(refine), redecode: None, ..job.inputs.clone() };
+                    self.ui.push_app_event(AppEvent::Status(format!("Refining {}...", id)));
+                    self.submit_second_stage(id, inputs);
+                }
+
+                UiEvent::SetQueuePaused(paused) => {
+                    self.queue_paused = paused;
+                    if !paused {
+                        for (id, inputs) in std::mem::take(&mut self.held_jobs) {
+                            self.send_job(id, inputs);
+                        }
+                    }
+                    if let Some(tray) = &self.tray {
+                        tray.set_queue_paused(paused);
+                    }
+                    self.ui.push_app_event(AppEvent::QueuePaused(paused));
+                    self.ui.push_app_event(AppEvent::Status(
+                        if paused { "Queue paused".into() } else { "Queue resumed".into() }
+                    ));
+                }
+
+                UiEvent::DetachQueue(detached) => {
+                    self.queue_window_request = Some(detached);
+                }
+
+                UiEvent::ShowWindow => {
+                    self.window.set_visible(true);
+                    self.window.set_minimized(false);
+                    self.window.focus_window();
+                }
+
+                UiEvent::OpenOutputFolder => {
+                    let dir = self.settings.output_dir();
+                    if let Err(e) = std::fs::create_dir_all(&dir).and_then(|_| tray::open_path(&dir)) {
+                        self.ui.push_app_event(AppEvent::Status(format!("Failed to open {}: {}", dir.display(), e)));
+                    }
+                }
+
+                UiEvent::CancelJob => {
+                    if self.active_job.is_some() {
+                        self.ui.push_app_event(AppEvent::Status("Cancelling job...".into()));
+                        self.lgm_worker.cancel_active();
+                    }
+                }
+
+                UiEvent::RequeueJob(id) => {
+                    let Some(job) = self.jobs.get(&id) else {
+                        continue;
+                    };
+                    let inputs = job.inputs.clone();
+
+                    // Its dependencies' outputs are looked up again once they're complete
+                    if !job.depends_on.is_empty() {
+                        self.jobs.update(&id, |job| {
+                            job.remote_id = None;
+                            job.status = JobStatus::Waiting;
+                            job.error = None;
+                        });
+                        self.ui.push_app_event(AppEvent::Status(format!("Requeued {}", id)));
+                        self.push_jobs();
+                        continue;
+                    }
+
+                    self.jobs.update(&id, |job| {
+                        job.stuck = false;
+                        job.remote_id = None;
+                        job.status = JobStatus::Queued;
+                        job.error = None;
+                    });
+                    self.ui.push_app_event(AppEvent::Status(format!("Requeued {}", id)));
+                    self.send_job(id, inputs);
+                }
+
+                UiEvent::OpenArtifact { kind, path } => {
+                    if kind.is_splats() {
+                        self.open_file(&path);
+                    } else if let Err(e) = tray::open_path(&path) {
+                        self.ui.push_app_event(AppEvent::Status(format!("Failed to open {}: {}", path.display(), e)));
+                    }
+                }
+
+                UiEvent::ExportArtifact(source) => {
+                    let ui_tx = self.ui.app_event_sender_clone();
+                    let window = self.window.clone();
+                    let name = source.file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or_default();
+
+                    self.rt.spawn_blocking(move || {
+                        if let Some(path) = rfd::FileDialog::new().set_file_name(&name).save_file() {
+                            match std::fs::copy(&source, &path) {
+                                Ok(_) => {
+                                    let _ = ui_tx.send(AppEvent::Status(format!("Exported {}", path.display())));
+                                    let _ = ui_tx.send(AppEvent::ExportFinished(path));
+                                }
+                                Err(e) => {
+                                    let _ = ui_tx.send(AppEvent::Status(format!("Export failed: {}", e)));
+                                    log::error!("Copying {} failed: {}", source.display(), e);
+                                }
+                            }
+                        }
+                        window.request_redraw();
+                    });
+                }
+
+                UiEvent::OpenJobScene(id) => {
+                    self.open_job_scene(&id);
+                }
+
+                UiEvent::ExportJobGif(id) => {
+                    self.export_job_gif(&id);
+                }
+
+                UiEvent::RefreshModelWeights => {
+                    self.poll_model_weights();
+                }
+
+                UiEvent::RefreshWorkerGpus => {
+                    self.poll_worker_gpus();
+                }
+
+                UiEvent::ManageWeights { worker, model, remove } => {
+                    self.manage_weights(worker, model, remove);
+                }
+
+                UiEvent::ExportJobMesh(id) => {
+                    self.export_job_mesh(&id);
+                }
+
+                UiEvent::BrowseRelink(id) => {
+                    let ui_tx = self.ui.app_event_sender_clone();
+                    let window = self.window.clone();
+                    let start_dir = self.output_dir.clone();
+
+                    self.rt.spawn_blocking(move || {
+                        if let Some(path) = rfd::FileDialog::new()
+                            .add_filter("Gaussian splats", &["ply"])
+                            .set_directory(start_dir)
+                            .pick_file()
+                        {
+                            let _ = ui_tx.send(AppEvent::RelinkPicked { id, path });
+                        }
+                        window.request_redraw();
+                    });
+                }
+
+                UiEvent::RelinkJob { id, path } => {
+                    self.jobs.relink(&id, &path);
+                    self.push_jobs();
+                    self.open_job_scene(&id);
+                }
+
+                UiEvent::TagJobs { ids, tag } => {
+                    for id in &ids {
+                        self.jobs.update(id, |job| {
+                            if !job.tags.contains(&tag) {
+                                job.tags.push(tag.clone());
+                            }
+                        });
+                    }
+                    self.push_jobs();
+                }
+
+                UiEvent::ExportJobs { ids, max_ply_bytes, profile } => {
+                    let jobs: Vec<_> = ids.iter().filter_map(|id| self.jobs.get(id)).cloned().collect();
+                    let ui_tx = self.ui.app_event_sender_clone();
+                    let window = self.window.clone();
+
+                    self.rt.spawn_blocking(move || {
+                        if let Some(dir) = rfd::FileDialog::new().pick_folder() {
+                            match jobs::export_jobs(&jobs, &dir, max_ply_bytes, profile) {
+                                Ok(copied) => {
+                                    let _ = ui_tx.send(AppEvent::Status(
+                                        format!("Exported {} job(s), {} output file(s)", jobs.len(), copied)
+                                    ));
+                                    let _ = ui_tx.send(AppEvent::ExportFinished(dir));
+                                }
+                                Err(e) => {
+                                    let _ = ui_tx.send(AppEvent::Status(format!("Export failed: {}", e)));
+                                    log::error!("Job export failed: {}", e);
+                                }
+                            }
+                        }
+                        window.request_redraw();
+                    });
+                }
+
+                UiEvent::ExportPly { profile } => {
+                    let Some(cloud) = self.gaussian_cloud.clone() else {
+                        self.ui.push_app_event(AppEvent::Status("No scene to export".into()));
+                        continue;
+                    };
+                    let ui_tx = self.ui.app_event_sender_clone();
+                    let window = self.window.clone();
+
+                    self.rt.spawn_blocking(move || {
+                        if let Some(path) = rfd::FileDialog::new()
+                            .add_filter("Gaussian splats", &["ply"])
+                            .set_file_name("scene.ply")
+                            .save_file()
+                        {
+                            let result = cloud.to_ply_as(profile)
+                                .map_err(|e| e.to_string())
+                                .and_then(|bytes| std::fs::write(&path, bytes).map_err(|e| e.to_string()));
+                            match result {
+                                Ok(()) => {
+                                    let _ = ui_tx.send(AppEvent::Status(format!("Exported {}", path.display())));
+                                    let _ = ui_tx.send(AppEvent::ExportFinished(path));
+                                }
+                                Err(e) => {
+                                    let _ = ui_tx.send(AppEvent::Status(format!("Export failed: {}", e)));
+                                    log::error!("PLY export failed: {}", e);
+                                }
+                            }
+                        }
+                        window.request_redraw();
+                    });
+                }
+
+                UiEvent::ExportVoxels { resolution } => {
+                    let Some(cloud) = self.gaussian_cloud.clone() else {
+                        self.ui.push_app_event(AppEvent::Status("No scene to export".into()));
+                        continue;
+                    };
+                    let ui_tx = self.ui.app_event_sender_clone();
+                    let window = self.window.clone();
+
+                    self.rt.spawn_blocking(move || {
+                        if let Some(path) = rfd::FileDialog::new()
+                            .add_filter("MagicaVoxel", &["vox"])
+                            .set_file_name("scene.vox")
+                            .save_file()
+                        {
+                            let grid = cloud.to_voxels(resolution);
+                            let result = grid.to_vox()
+                                .map_err(|e| e.to_string())
+                                .and_then(|bytes| std::fs::write(&path, bytes).map_err(|e| e.to_string()));
+                            match result {
+                                Ok(()) => {
+                                    let _ = ui_tx.send(AppEvent::Status(
+                                        format!("Exported {} voxels to {}", grid.filled().count(), path.display())
+                                    ));
+                                    let _ = ui_tx.send(AppEvent::ExportFinished(path));
+                                }
+                                Err(e) => {
+                                    let _ = ui_tx.send(AppEvent::Status(format!("Export failed: {}", e)));
+                                    log::error!("Voxel export failed: {}", e);
+                                }
+                            }
+                        }
+                        window.request_redraw();
+                    });
+                }
+
+                UiEvent::ExportMesh(settings) => {
+                    let Some(cloud) = self.gaussian_cloud.clone() else {
+                        self.ui.push_app_event(AppEvent::Status("No scene to export".into()));
+                        continue;
+                    };
+                    let ui_tx = self.ui.app_event_sender_clone();
+                    let window = self.window.clone();
+
+                    self.rt.spawn_blocking(move || {
+                        if let Some(path) = rfd::FileDialog::new()
+                            .add_filter("glTF binary", &["glb"])
+                            .add_filter("Wavefront OBJ", &["obj"])
+                            .set_file_name("scene.glb")
+                            .save_file()
+                        {
+                            let _ = ui_tx.send(AppEvent::Status("Baking mesh texture...".into()));
+                            window.request_redraw();
+                            let result = cloud.to_textured_mesh(settings)
+                                .map_err(anyhow::Error::from)
+                                .and_then(|mesh| mesh_export::save_textured_mesh(&mesh, &path).map(|()| mesh.triangle_count()));
+                            match result {
+                                Ok(triangles) => {
+                                    let _ = ui_tx.send(AppEvent::Status(
+                                        format!("Exported {} triangles to {}", triangles, path.display())
+                                    ));
+                                    let _ = ui_tx.send(AppEvent::ExportFinished(path));
+                                }
+                                Err(e) => {
+                                    let _ = ui_tx.send(AppEvent::Status(format!("Export failed: {}", e)));
+                                    log::error!("Mesh export failed: {}", e);
+                                }
+                            }
+                        }
+                        window.request_redraw();
+                    });
+                }
+
+                UiEvent::ExportWebViewer => {
+                    let Some(cloud) = self.gaussian_cloud.clone() else {
+                        self.ui.push_app_event(AppEvent::Status("No scene to export".into()));
+                        continue;
+                    };
+                    let ui_tx = self.ui.app_event_sender_clone();
+                    let window = self.window.clone();
+
+                    self.rt.spawn_blocking(move || {
+                        if let Some(path) = rfd::FileDialog::new()
+                            .add_filter("Web page", &["html"])
+                            .set_file_name("scene.html")
+                            .save_file()
+                        {
+                            let title = path.file_stem().map_or("genjutsu".into(), |s| s.to_string_lossy());
+                            match std::fs::write(&path, web_viewer::viewer_html(&cloud, &title)) {
+                                Ok(()) => {
+                                    let _ = ui_tx.send(AppEvent::Status(format!("Web viewer saved to {}", path.display())));
+                                    let _ = ui_tx.send(AppEvent::ExportFinished(path));
+                                }
+                                Err(e) => {
+                                    let _ = ui_tx.send(AppEvent::Status(format!("Export failed: {}", e)));
+                                    log::error!("Web viewer export failed: {}", e);
+                                }
+                            }
+                        }
+                        window.request_redraw();
+                    });
+                }
+
+                UiEvent::BrowseComparison => {
+                    let ui_tx = self.ui.app_event_sender_clone();
+                    let window = self.window.clone();
+                    let start_dir = self.output_dir.clone();
+
+                    self.rt.spawn_blocking(move || {
+                        if let Some(path) = rfd::FileDialog::new()
+                            .add_filter("Gaussian splats", &["ply"])
+                            .set_directory(start_dir)
+                            .pick_file()
+                        {
+                            let _ = ui_tx.send(AppEvent::ComparisonPicked(path));
+                        }
+                        window.request_redraw();
+                    });
+                }
+
+                UiEvent::CompareWith { path, align } => {
+                    if let Err(e) = self.compare_with(&path, align) {
+                        log::error!("Comparison with {} failed: {}", path.display(), e);
+                        self.ui.push_app_event(AppEvent::Status(format!("Comparison failed: {}", e)));
+                    }
+                }
+
+                UiEvent::ShowDifferences(show) => {
+                    self.show_differences = show;
+                    self.reload_splats();
+                }
+
+                UiEvent::ClearComparison => {
+                    self.clear_comparison();
+                }
+
+                UiEvent::BrowseMorphTarget => {
+                    let ui_tx = self.ui.app_event_sender_clone();
+                    let window = self.window.clone();
+                    let start_dir = self.output_dir.clone();
+
+                    self.rt.spawn_blocking(move || {
+                        if let Some(path) = rfd::FileDialog::new()
+                            .add_filter("Gaussian splats", &["ply"])
+                            .set_directory(start_dir)
+                            .pick_file()
+                        {
+                            let _ = ui_tx.send(AppEvent::MorphTargetPicked(path));
+                        }
+                        window.request_redraw();
+                    });
+                }
+
+                UiEvent::MorphTo { path, align } => {
+                    if let Err(e) = self.morph_to(&path, align) {
+                        log::error!("Morph into {} failed: {}", path.display(), e);
+                        self.ui.push_app_event(AppEvent::Status(format!("Morph failed: {}", e)));
+                    }
+                }
+
+                UiEvent::SetMorphPosition(position) => {
+                    self.morph_position = position.clamp(0.0, 1.0);
+                    self.morph_playback = None;
+                    self.reload_splats();
+                }
+
+                UiEvent::PlayMorph(play) => {
+                    // Pick the swing up where the slider is, heading to the target
+                    self.morph_playback = (play && self.morph.is_some()).then_some(self.morph_position * MORPH_DURATION);
+                    self.last_frame = Instant::now();
+                    self.ui.push_app_event(AppEvent::MorphPosition {
+                        position: self.morph_position,
+                        playing: self.morph_playback.is_some(),
+                    });
+                }
+
+                UiEvent::ExportMorph { frames } => {
+                    self.export_morph(frames);
+                }
+
+                UiEvent::ClearMorph => {
+                    self.clear_morph();
+                    self.reload_splats();
+                }
+
+                UiEvent::SelectRegion { shape, additive } => {
+                    if let Some(cloud) = &self.gaussian_cloud {
+                        let (width, height) = self.viewport_size();
+                        let viewport = glam::Vec2::new(width as f32, height as f32);
+                        let picked = selection::select_in_screen(&cloud.positions, &self.camera, viewport, &shape);
+
+                        if !additive || self.selection.len() != cloud.count {
+                            self.selection = vec![false; cloud.count];
+                        }
+                        for i in picked {
+                            self.selection[i] = true;
+                        }
+                        self.push_selection();
+                    }
+                }
+
+                UiEvent::ClearSelection => {
+                    self.selection.clear();
+                    self.push_selection();
+                }
+
+                UiEvent::DeleteSelection | UiEvent::IsolateSelection => {
+                    let keep_selected = matches!(ev, UiEvent::IsolateSelection);
+                    if let Some(cloud) = self.gaussian_cloud.take() {
+                        let kept: Vec<usize> = (0..cloud.count)
+                            .filter(|&i| self.selection.get(i).copied().unwrap_or(false) == keep_selected)
+                            .collect();
+                        let removed = cloud.count - kept.len();
+                        self.load_gaussian_cloud(cloud.subset(&kept));
+                        self.ui.push_app_event(AppEvent::Status(format!("Removed {} splats", removed)));
+                    }
+                }
+
+                UiEvent::PlaceVolume { sphere } => {
+                    if let Some(cloud) = &self.gaussian_cloud {
+                        let bounds = cloud.bounds();
+                        let center = bounds.center();
+                        let size = bounds.size();
+                        let half = size[0].max(size[1]).max(size[2]) * 0.25;
+                        let volume = match sphere {
+                            true => Volume::Sphere { center, radius: half },
+                            false => Volume::Box { center, half_extents: [half; 3] },
+                        };
+                        self.set_volume(Some(volume));
+                    }
+                }
+
+                UiEvent::SetVolume(volume) => {
+                    self.set_volume(volume);
+                }
+
+                UiEvent::DeleteVolume | UiEvent::IsolateVolume => {
+                    let keep_inside = matches!(ev, UiEvent::IsolateVolume);
+                    if let Some(volume) = self.volume
+                        && let Some(cloud) = self.gaussian_cloud.take()
+                    {
+                        let kept: Vec<usize> = (0..cloud.count)
+                            .filter(|&i| volume.contains(cloud.positions[i]) == keep_inside)
+                            .collect();
+                        let removed = cloud.count - kept.len();
+                        self.load_gaussian_cloud(cloud.subset(&kept));
+                        self.ui.push_app_event(AppEvent::Status(format!("Removed {} splats", removed)));
+                    }
+                }
+
+                UiEvent::SetBrush(brush) => {
+                    self.brush = brush;
+                    self.ui.push_app_event(AppEvent::Brush(brush));
+                }
+
+                UiEvent::Paint { x, y } => {
+                    let (width, height) = self.viewport_size();
+                    let viewport = glam::Vec2::new(width as f32, height as f32);
+                    if let Some(brush) = self.brush
+                        && let Some(cloud) = &mut self.gaussian_cloud
+                    {
+                        let weights = brush.weights(&cloud.positions, &self.camera, viewport, glam::Vec2::new(x, y));
+                        cloud.paint(&weights, brush.mode);
+
+                        // Only albedo can be patched in place; heatmaps and
+                        // normals are derived from the whole cloud
+                        if self.renderer.color_mode() == ColorMode::Albedo && !self.show_differences && self.morph.is_none() {
+                            let changed: Vec<usize> = weights.iter().map(|&(i, _)| i).collect();
+                            self.renderer.update_splats(cloud, &changed);
+                        } else {
+                            self.reload_splats();
+                        }
+                        self.scene_dirty = true;
+                    }
+                }
+
+                UiEvent::SnapToGround => {
+                    if let Some(mut cloud) = self.gaussian_cloud.take() {
+                        cloud.snap_to_ground();
+                        self.load_gaussian_cloud(cloud);
+                        self.ui.push_app_event(AppEvent::Status("Snapped scene to the ground".into()));
+                    }
+                }
+
+                UiEvent::NormalizeScene => {
+                    if let Some(mut cloud) = self.gaussian_cloud.take() {
+                        let normalization = cloud.normalize();
+                        log::info!("Normalized scene: center {:?}, scale {}", normalization.center, normalization.scale);
+                        self.load_gaussian_cloud(cloud);
+                        self.ui.push_app_event(AppEvent::Status(
+                            format!("Normalized scene (scaled by {:.3})", normalization.scale)
+                        ));
+                    }
+                }
+
+                UiEvent::ArrayScene(layout) => {
+                    if let Some(cloud) = self.gaussian_cloud.take() {
+                        let placements = layout.placements();
+                        self.load_gaussian_cloud(cloud.arrayed(&placements));
+                        self.ui.push_app_event(AppEvent::Status(
+                            format!("Arrayed the scene into {} copies", placements.len())
+                        ));
+                    }
+                }
+
+                UiEvent::UpdateJobNotes { id, notes } => {
+                    self.jobs.update_notes(&id, &notes);
+                    self.push_jobs();
+                }
+
+                UiEvent::DuplicateJob(id) => {
+                    if let Some(job) = self.jobs.get(&id) {
+                        self.ui.push_app_event(AppEvent::EditJobInputs(job.inputs.clone()));
+                    }
+                }
+
+                UiEvent::PromptChanged(new_prompt) => {
+                    self.prompt = new_prompt;
+                }
+
+                UiEvent::LoadImages => {
+                    let window = self.window.clone();
+                    let worker_tx = self.lgm_worker.command_tx.clone();
+                    let ui_tx = self.ui.app_event_sender_clone();
+
+                    // Spawn file picker on blocking thread pool
+                    self.rt.spawn_blocking(move || {
+                        let _ = ui_tx.send(AppEvent::Status("Opening file dialog...".into()));
+
+                        if let Some(files) = rfd::FileDialog::new()
+                            .add_filter("Images", &["png", "jpg", "jpeg"])
+                            .pick_files()
+                        {
+                            let _ = ui_tx.send(AppEvent::Status("Loading images...".into()));
+
+                            // Load images on this thread
+                            let images: Result<Vec<_>, _> = files.iter()
+                                .enumerate()
+                                .map(|(i, path)| {
+                                    let progress = (i as f32) / (files.len() as f32);
+                                    let _ = ui_tx.send(AppEvent::Progress(progress));
+                                    image::open(path).map(|img| img.to_rgba8())
+                                })
+                                .collect();
+
+                            match images {
+                                Ok(images) => {
+                                    let _ = ui_tx.send(AppEvent::Status("Generating 3D model...".into()));
+
+                                    // Send images to worker for processing
+                                    if let Err(e) = worker_tx.send(crate::worker::WorkerCommand::GenerateFromImages(images)) {
+                                        let _ = ui_tx.send(AppEvent::Status(format!("Worker error: {}", e)));
+                                    }
+                                }
+                                Err(e) => {
+                                    let _ = ui_tx.send(AppEvent::Status(format!("Failed to load images: {}", e)));
+                                    log::error!("Image load error: {}", e);
+                                }
+                            }
+                        } else {
+                            let _ = ui_tx.send(AppEvent::Status("File selection cancelled".into()));
+                        }
+
+                        window.request_redraw();
+                    });
+                }
+
+                UiEvent::LoadPendingScene => {
+                    if let Some((cloud, path)) = self.pending_cloud.take() {
+                        self.load_gaussian_cloud(cloud);
+                        self.scene_path = Some(path);
+                        self.ui.push_app_event(AppEvent::SceneReady);
+                    }
+                }
+
+                UiEvent::RestoreSession => {
+                    self.restore_session();
+                }
+
+                UiEvent::Log(msg) => {
+                    log::info!("UI: {}", msg);
+                }
+            }
+        }
+    }
+
+    pub fn load_scene_from_path(&mut self, path: &Path) -> Result<(), AppError> {
+        let cloud = GaussianCloud::from_ply_cached(path)?;
+        self.load_gaussian_cloud(cloud);
+        self.scene_path = Some(path.to_path_buf());
+        self.ui.push_app_event(AppEvent::SceneReady);
+        Ok(())
+    }
+
+    // --- Session recovery ---------------------------------------------------
+
+    /// What's on screen now
+    fn current_session(&self) -> Session {
+        let scene_path = self.scene_path.clone().filter(|_| self.gaussian_cloud.is_some());
+        let job_id = scene_path.as_ref().and_then(|path| {
+            self.jobs.jobs().iter()
+                .find(|job| job.outputs.as_ref().is_some_and(|o| o.artifacts.iter().any(|a| a.path == *path)))
+                .map(|job| job.id.clone())
+        });
+        Session {
+            job_id,
+            camera: scene_path.as_ref().map(|_| SessionCamera::from_camera(&self.camera)),
+            scene_path,
+            layout: self.ui.layout(),
+        }
+    }
+
+    /// Write the session for recovery after a crash, if it changed
+    fn autosave_session(&mut self) {
+        self.last_autosave = Instant::now();
+        let session = self.current_session();
+        if session == self.saved_session {
+            return;
+        }
+        match session.save(&Session::path()) {
+            Ok(()) => self.saved_session = session,
+            Err(e) => log::warn!("Failed to autosave session: {}", e),
+        }
+    }
+
+    /// Bring back the scene, view and layout of the run that crashed
+    fn restore_session(&mut self) {
+        let Some(session) = self.crashed_session.take() else {
+            return;
+        };
+        log::info!("Restoring previous session");
+
+        let loaded = match (&session.job_id, &session.scene_path) {
+            (Some(id), _) if self.jobs.get(id).is_some() => self.open_job_scene(id),
+            (_, Some(path)) => {
+                self.open_file(path);
+                self.scene_path.as_ref() == Some(path)
+            }
+            _ => false,
+        };
+        if loaded && let Some(camera) = &session.camera {
+            self.camera_transition = None;
+            camera.apply(&mut self.camera);
+        }
+        self.ui.apply_layout(&session.layout);
+        self.scene_dirty = true;
+    }
+
+    /// Whether the queue window should be opened (true) or closed (false);
+    /// windows can only be created from the event loop
+    pub fn take_queue_window_request(&mut self) -> Option<bool> {
+        self.queue_window_request.take()
+    }
+
+    pub fn open_queue_window(&mut self, window: Arc<Window>) {
+        match QueueWindow::new(window, &self.gfx) {
+            Ok(queue_window) => {
+                ui::apply_appearance(queue_window.context(), &self.settings.appearance);
+                queue_window.window.request_redraw();
+                self.queue_window = Some(queue_window);
+                self.ui.set_queue_detached(true);
+            }
+            Err(e) => {
+                log::warn!("Failed to open the queue window: {}", e);
+                self.ui.push_app_event(AppEvent::Status(format!("Couldn't pop out the queue: {}", e)));
+            }
+        }
+    }
+
+    /// Dock the queue back into the main window
+    pub fn close_queue_window(&mut self) {
+        if self.queue_window.take().is_some() {
+            self.ui.set_queue_detached(false);
+            self.window.request_redraw();
+        }
+    }
+
+    /// Handle an event for the queue window; returns whether UI events came
+    /// out of it for the main window to process
+    pub fn queue_window_event(&mut self, event: &WindowEvent) -> bool {
+        let Some(queue_window) = &mut self.queue_window else {
+            return false;
+        };
+
+        if !matches!(event, WindowEvent::RedrawRequested) {
+            if queue_window.on_window_event(&self.gfx, event) {
+                queue_window.window.request_redraw();
+            }
+            return false;
+        }
+
+        let mut sender = UiEventSender::default();
+        let ui = &mut self.ui;
+        if let Err(e) = queue_window.render(&self.gfx, &mut sender, |ctx, s| ui.draw_queue_window(ctx, s)) {
+            log::warn!("Failed to draw the queue window: {}", e);
+        }
+        let events = sender.take_events();
+        let any = !events.is_empty();
+        if self.ui.after_draw_process(events) {
+            queue_window.window.request_redraw();
+        }
+        any
+    }
+
+    /// The app is closing normally; nothing to recover next time
+    pub fn end_session(&mut self) {
+        Session::clear(&Session::path());
+    }
+
+    /// Open a PLY given from outside the app: the command line, which is how
+    /// the OS passes files opened with genjutsu, or a file dropped on the window
+    pub fn open_file(&mut self, path: &Path) {
+        log::info!("Opening {}", path.display());
+        match self.load_scene_from_path(path) {
+            Ok(()) => {
+                self.ui.push_app_event(AppEvent::Status(format!("Loaded {}", path.display())));
+            }
+            Err(e) => {
+                log::error!("Failed to open {}: {}", path.display(), e);
+                self.ui.push_app_event(AppEvent::SceneLoadFailed(e));
+            }
+        }
+    }
+
+    /// Load a finished job's output. If the file moved, look for it in the
+    /// output directory and update the job, or ask the user where it went.
+    /// Returns whether the scene loaded.
+    fn open_job_scene(&mut self, id: &str) -> bool {
+        let Some(recorded) = self.jobs.get(id).and_then(|job| job.outputs.as_ref()?.ply_path()).map(Path::to_path_buf) else {
+            return false;
+        };
+
+        let path = if recorded.is_file() {
+            recorded
+        } else {
+            let search = [self.output_dir.clone(), PathBuf::from("outputs")];
+            match jobs::find_moved_output(&recorded, &search) {
+                Some(found) => {
+                    log::info!("Output of {} moved from {} to {}", id, recorded.display(), found.display());
+                    self.jobs.relink(id, &found);
+                    self.push_jobs();
+                    found
+                }
+                None => {
+                    self.ui.push_app_event(AppEvent::RelinkNeeded { id: id.to_string(), missing: recorded });
+                    return false;
+                }
+            }
+        };
+
+        match self.load_scene_from_path(&path) {
+            Ok(()) => true,
+            Err(e) => {
+                log::error!("Failed to load {}: {}", path.display(), e);
+                self.ui.push_app_event(AppEvent::SceneLoadFailed(e));
+                false
+            }
+        }
+    }
+
+    /// Open a job's output and render a small turntable of it as a GIF next
+    /// to the PLY, recorded as the job's preview
+    fn export_job_gif(&mut self, id: &str) {
+        if !self.open_job_scene(id) {
+            return;
+        }
+        let Some(ply_path) = self.jobs.get(id).and_then(|job| job.outputs.as_ref()?.ply_path()).map(Path::to_path_buf) else {
+            return;
+        };
+        let path = ply_path.with_extension("gif");
+
+        let cameras = capture::sequence_cameras(
+            &self.camera, &self.camera_path, SequenceMotion::Turntable, GIF_FRAMES, GIF_SIZE, GIF_SIZE,
+        );
+        let result = cameras.iter()
+            .map(|camera| capture::render_scene_image(&self.gfx, &mut self.renderer, camera, GIF_SIZE, GIF_SIZE))
+            .collect::<anyhow::Result<Vec<_>>>()
+            .and_then(|frames| capture::save_gif(frames, GIF_FRAME_MS, &path));
+        self.scene_dirty = true;
+
+        match result {
+            Ok(()) => {
+                self.jobs.update(id, |job| {
+                    if let Some(outputs) = &mut job.outputs {
+                        outputs.add(ArtifactKind::PreviewGif, &path);
+                    }
+                });
+                self.push_jobs();
+                self.ui.push_app_event(AppEvent::Status(format!("GIF saved to {}", path.display())));
+                self.ui.push_app_event(AppEvent::ExportFinished(path));
+            }
+            Err(e) => self.ui.push_app_event(AppEvent::Status(format!("GIF export failed: {}", e))),
+        }
+    }
+
+    /// Bake a job's output into a textured GLB next to the PLY, recorded as
+    /// the job's mesh
+    fn export_job_mesh(&mut self, id: &str) {
+        let Some(ply_path) = self.jobs.get(id).and_then(|job| job.outputs.as_ref()?.ply_path()).map(Path::to_path_buf) else {
+            return;
+        };
+        let path = ply_path.with_extension("glb");
+
+        let result = GaussianCloud::from_ply_cached(&ply_path)
+            .map_err(anyhow::Error::from)
+            .and_then(|cloud| Ok(cloud.to_textured_mesh(MeshSettings::default())?))
+            .and_then(|mesh| mesh_export::save_textured_mesh(&mesh, &path).map(|()| mesh.triangle_count()));
+
+        match result {
+            Ok(triangles) => {
+                self.jobs.update(id, |job| {
+                    if let Some(outputs) = &mut job.outputs {
+                        outputs.add(ArtifactKind::Mesh, &path);
+                    }
+                });
+                self.push_jobs();
+                self.ui.push_app_event(AppEvent::Status(format!("Mesh with {} triangles saved to {}", triangles, path.display())));
+                self.ui.push_app_event(AppEvent::ExportFinished(path));
+            }
+            Err(e) => self.ui.push_app_event(AppEvent::Status(format!("Mesh export failed: {}", e))),
+        }
+    }
+
+    /// Compare the open scene against the cloud at `path` and show the
+    /// per-splat distances as a heatmap
+    fn compare_with(&mut self, path: &Path, align: bool) -> Result<(), AppError> {
+        let Some(cloud) = &self.gaussian_cloud else {
+            return Ok(());
+        };
+        let reference = GaussianCloud::from_ply_cached(path)?;
+
+        let aligned;
+        let compared = if align {
+            let alignment = cloud.align_to(&reference)?;
+            log::info!("Aligned to reference in {} iterations, RMS error {:.4}", alignment.iterations, alignment.rms_error);
+            aligned = {
+                let mut aligned = cloud.clone();
+                aligned.apply_alignment(&alignment);
+                aligned
+            };
+            &aligned
+        } else {
+            cloud
+        };
+
+        let comparison = compared.compare(&reference)?;
+        let mut heatmap = cloud.clone();
+        heatmap.colors = comparison.heatmap_colors(comparison.metrics.distances.p95);
+
+        self.difference_cloud = Some(heatmap);
+        self.show_differences = true;
+        self.reload_splats();
+        self.ui.push_app_event(AppEvent::Comparison(Some(comparison.metrics)));
+        Ok(())
+    }
+
+    fn morph_to(&mut self, path: &Path, align: bool) -> Result<(), AppError> {
+        let Some(cloud) = &self.gaussian_cloud else {
+            return Ok(());
+        };
+        let target = GaussianCloud::from_ply_cached(path)?;
+
+        let mut start = cloud.clone();
+        if align {
+            let alignment = start.align_to(&target)?;
+            log::info!("Aligned to morph target in {} iterations, RMS error {:.4}", alignment.iterations, alignment.rms_error);
+            start.apply_alignment(&alignment);
+        }
+
+        let morph = Morph::new(&start, &target)?;
+        self.ui.push_app_event(AppEvent::Morph(Some(morph.pairs())));
+        self.morph = Some(morph);
+        self.morph_position = 0.0;
+        self.morph_playback = None;
+        self.ui.push_app_event(AppEvent::MorphPosition { position: 0.0, playing: false });
+        self.reload_splats();
+        Ok(())
+    }
+
+    /// Stop showing the morph; the caller reloads the splats
+    fn clear_morph(&mut self) {
+        if self.morph.take().is_some() {
+            self.morph_playback = None;
+            self.ui.push_app_event(AppEvent::Morph(None));
+        }
+    }
+
+    /// Render the morph from the scene to the target into a PNG sequence
+    /// under outputs/sequences, from the current view
+    pub fn export_morph(&mut self, frames: usize) {
+        let Some(morph) = &self.morph else {
+            return;
+        };
+
+        let (width, height) = self.gfx.scene.size();
+        let frames = frames.max(2);
+        let timestamp = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map_or(0, |d| d.as_millis());
+        let dir = std::path::PathBuf::from("outputs/sequences").join(format!("morph_{}", timestamp));
+
+        let result = std::fs::create_dir_all(&dir).map_err(anyhow::Error::from).and_then(|()| {
+            for i in 0..frames {
+                self.renderer.load_gaussians(&morph.at(i as f32 / (frames - 1) as f32));
+                let image = capture::render_scene_image(&self.gfx, &mut self.renderer, &self.camera, width, height)?;
+                image.save(dir.join(format!("frame_{:04}.png", i)))?;
+            }
+            Ok(())
+        });
+        match result {
+            Ok(()) => {
+                self.ui.push_app_event(AppEvent::Status(format!("Rendered {} morph frames to {}", frames, dir.display())));
+                self.ui.push_app_event(AppEvent::ExportFinished(dir));
+            }
+            Err(e) => self.ui.push_app_event(AppEvent::Status(format!("Morph export failed: {}", e))),
+        }
+        self.reload_splats();
+        self.scene_dirty = true;
+    }
+
+    fn clear_comparison(&mut self) {
+        if self.difference_cloud.take().is_some() {
+            self.show_differences = false;
+            self.reload_splats();
+            self.ui.push_app_event(AppEvent::Comparison(None));
+        }
+    }
+
+    /// Upload the open scene again, e.g. after a render option changed
+    fn reload_splats(&mut self) {
+        let morphed = self.morph.as_ref().map(|morph| morph.at(self.morph_position));
+        let cloud = match &self.difference_cloud {
+            _ if morphed.is_some() => morphed.as_ref(),
+            Some(heatmap) if self.show_differences => Some(heatmap),
+            _ => self.gaussian_cloud.as_ref(),
+        };
+        if let Some(cloud) = cloud {
+            self.renderer.load_gaussians(cloud);
+            self.renderer.set_selection(&self.selection);
+        }
+    }
+
+    /// Highlight the selection and tell the UI its size
+    fn push_selection(&mut self) {
+        self.renderer.set_selection(&self.selection);
+        self.scene_dirty = true;
+        let count = self.selection.iter().filter(|&&s| s).count();
+        self.ui.push_app_event(AppEvent::SelectionChanged(count));
+    }
+
+    pub fn load_gaussian_cloud(&mut self, mut cloud: GaussianCloud) {
+        // Model outputs sometimes hold NaN or degenerate splats that break sorting
+        match cloud.sanitize() {
+            Ok(report) if !report.is_clean() => {
+                log::warn!("Repaired cloud: {}", report);
+                self.ui.push_app_event(AppEvent::CloudSanitized(report.to_string()));
+            }
+            Ok(_) => {}
+            Err(e) => {
+                self.ui.push_app_event(AppEvent::SceneLoadFailed(e.into()));
+                return;
+            }
+        }
+
+        // Compute bounds
+        let bounds = cloud.bounds();
+        let center = bounds.center();
+        let size = bounds.size();
+        let max_dim = size[0].max(size[1]).max(size[2]);
+
+        log::info!(
+            "Mesh bounds: center [{:.3}, {:.3}, {:.3}], size [{:.3}, {:.3}, {:.3}], max dimension {:.3}",
+            center[0], center[1], center[2], size[0], size[1], size[2], max_dim
+        );
+
+        // Auto-adjust camera distance based on mesh size
+        let (target, distance) = framing(&bounds);
+        self.camera_transition = None;
+        self.camera.distance = distance;
+        self.camera.target = target;
+        self.camera.update_position();
+
+        if !self.selection.is_empty() {
+            self.selection.clear();
+            self.ui.push_app_event(AppEvent::SelectionChanged(0));
+        }
+
+        // A comparison heatmap belongs to the scene it was made for
+        if self.difference_cloud.take().is_some() {
+            self.show_differences = false;
+            self.ui.push_app_event(AppEvent::Comparison(None));
+        }
+        // And a morph starting from it
+        self.clear_morph();
+        // So do the clipping planes and the volume, placed for its size
+        if !self.renderer.clip_planes().is_empty() {
+            self.set_clip_planes(Vec::new());
+        }
+        if self.volume.is_some() {
+            self.set_volume(None);
+        }
+
+        self.renderer.load_gaussians(&cloud);
+        self.ui.push_app_event(AppEvent::CloudStats(cloud.stats()));
+        self.gaussian_cloud = Some(cloud);
+        self.scene_dirty = true;
+    }
+
+    fn set_clip_planes(&mut self, planes: Vec<ClipPlane>) {
+        self.renderer.set_clip_planes(&planes);
+        self.scene_dirty = true;
+        self.ui.push_app_event(AppEvent::ClipPlanes(planes));
+    }
+
+    fn set_volume(&mut self, volume: Option<Volume>) {
+        self.volume = volume;
+        self.ui.push_app_event(AppEvent::Volume(volume));
+    }
+
+    /// The volume's outline as segments in viewport coordinates from 0 to 1,
+    /// leaving out those crossing behind the camera
+    fn volume_outline(&self) -> Vec<[glam::Vec2; 2]> {
+        let Some(volume) = &self.volume else {
+            return Vec::new();
+        };
+        volume.outline()
+            .into_iter()
+            .filter_map(|[a, b]| Some([
+                self.camera.to_viewport(glam::Vec3::from(a))?,
+                self.camera.to_viewport(glam::Vec3::from(b))?,
+            ]))
+            .collect()
+    }
+
+    /// Gizmo of each clipping plane, anchored nearest the scene center with
+    /// arrows a quarter of its size
+    fn clip_handles(&self) -> Vec<Option<ClipHandle>> {
+        let planes = self.renderer.clip_planes();
+        let Some(cloud) = self.gaussian_cloud.as_ref().filter(|_| !planes.is_empty()) else {
+            return Vec::new();
+        };
+        let bounds = cloud.octree().bounds();
+        let size = bounds.size();
+        let length = size[0].max(size[1]).max(size[2]) * 0.25;
+        let center = glam::Vec3::from(bounds.center());
+        planes.iter().map(|plane| plane.handle(&self.camera, center, length)).collect()
+    }
+
+    /// Size in physical pixels of the viewport, which pointer positions from
+    /// the UI are relative to; the scene target differs by the render scale
+    fn viewport_size(&self) -> (u32, u32) {
+        self.ui.viewport_size().unwrap_or_else(|| self.gfx.scene.size())
+    }
+
+    /// Animate the camera to frame the splats under a window pixel,
+    /// or the whole cloud when nothing was hit
+    pub fn focus_at(&mut self, x: f32, y: f32) {
+        let Some(cloud) = &self.gaussian_cloud else {
+            return;
+        };
+
+        let (width, height) = self.viewport_size();
+        let (origin, dir) = self.camera.screen_ray(x, y, width as f32, height as f32);
+
+        let bounds = cloud.bounds();
+        let region = match cloud.raycast(origin.to_array(), dir.to_array()) {
+            Some((index, _)) => {
+                let size = bounds.size();
+                let radius = size[0].max(size[1]).max(size[2]) * 0.15;
+                cloud.bounds_around(cloud.positions[index], radius)
+            }
+            None => bounds,
+        };
+
+        let (target, distance) = framing(&region);
+        self.camera_transition = Some(CameraTransition::new(&self.camera, target, distance, 0.35));
+    }
+
+    /// Focus the depth of field on the splat under viewport pixel (`x`, `y`)
+    pub fn focus_depth_at(&mut self, x: f32, y: f32) {
+        let (Some(cloud), Some(mut dof)) = (&self.gaussian_cloud, self.renderer.depth_of_field()) else {
+            return;
+        };
+
+        let (width, height) = self.viewport_size();
+        let (origin, dir) = self.camera.screen_ray(x, y, width as f32, height as f32);
+        let Some((index, _)) = cloud.raycast(origin.to_array(), dir.to_array()) else {
+            self.ui.push_app_event(AppEvent::Status("Nothing to focus on there".into()));
+            return;
+        };
+
+        // Focus distance is measured along the view axis, like the shader's depth
+        let hit = glam::Vec3::from(cloud.positions[index]);
+        dof.focus_distance = -self.camera.view_matrix().transform_point3(hit).z;
+        self.renderer.set_depth_of_field(Some(dof));
+        self.ui.push_app_event(AppEvent::DepthOfField(Some(dof)));
+    }
+
+    /// Save the current 3D view (without UI) as a PNG under outputs/screenshots
+    pub fn save_screenshot(&mut self) {
+        if self.gaussian_cloud.is_none() {
+            self.ui.push_app_event(AppEvent::Status("Nothing to capture - load a scene first".into()));
+            return;
+        }
+
+        let (width, height) = self.gfx.scene.size();
+        let result = capture::render_scene_image(&self.gfx, &mut self.renderer, &self.camera, width, height)
+            .and_then(|image| capture::save_timestamped_png(&image, std::path::Path::new("outputs/screenshots"), "screenshot"));
+
+        match result {
+            Ok(path) => {
+                self.ui.push_app_event(AppEvent::Status(format!("Screenshot saved to {}", path.display())));
+                self.ui.push_app_event(AppEvent::ExportFinished(path));
+            }
+            Err(e) => self.ui.push_app_event(AppEvent::Status(format!("Screenshot failed: {}", e))),
+        }
+    }
+
+    /// Render `frames` frames of the camera motion offscreen at `width` x `height`,
+    /// independent of the window size, into a folder under outputs/sequences
+    pub fn export_sequence(&mut self, motion: SequenceMotion, width: u32, height: u32, frames: usize) {
+        if self.gaussian_cloud.is_none() {
+            self.ui.push_app_event(AppEvent::Status("Nothing to render - load a scene first".into()));
+            return;
+        }
+        if motion == SequenceMotion::CameraPath && self.camera_path.is_empty() {
+            self.ui.push_app_event(AppEvent::Status("Add camera keyframes before rendering the path".into()));
+            return;
+        }
+
+        let max = self.gfx.device.limits().max_texture_dimension_2d;
+        let (width, height) = (width.clamp(16, max), height.clamp(16, max));
+        let cameras = capture::sequence_cameras(&self.camera, &self.camera_path, motion, frames.max(1), width, height);
+
+        let timestamp = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map_or(0, |d| d.as_millis());
+        let dir = std::path::PathBuf::from("outputs/sequences").join(format!("sequence_{}", timestamp));
+
+        match capture::render_png_sequence(&self.gfx, &mut self.renderer, &cameras, width, height, &dir) {
+            Ok(()) => {
+                self.ui.push_app_event(AppEvent::Status(
+                    format!("Rendered {} frames to {}", cameras.len(), dir.display())
+                ));
+                self.ui.push_app_event(AppEvent::ExportFinished(dir));
+            }
+            Err(e) => self.ui.push_app_event(AppEvent::Status(format!("Sequence export failed: {}", e))),
+        }
+        self.scene_dirty = true;
+    }
+
+    /// Render each camera path keyframe, or just the current view if there are
+    /// none, into a training dataset under outputs/nerf
+    pub fn export_transforms(&mut self, width: u32, height: u32) {
+        if self.gaussian_cloud.is_none() {
+            self.ui.push_app_event(AppEvent::Status("Nothing to render - load a scene first".into()));
+            return;
+        }
+
+        let max = self.gfx.device.limits().max_texture_dimension_2d;
+        let (width, height) = (width.clamp(16, max), height.clamp(16, max));
+        let keyframes = self.camera_path.keyframes();
+        let poses = if keyframes.is_empty() {
+            vec![self.camera.clone()]
+        } else {
+            keyframes.iter()
+                .map(|k| {
+                    let mut camera = self.camera.clone();
+                    k.apply(&mut camera);
+                    camera
+                })
+                .collect()
+        };
+        let cameras: Vec<_> = poses.into_iter()
+            .map(|mut camera| {
+                camera.aspect_ratio = width as f32 / height as f32;
+                camera
+            })
+            .collect();
+
+        let timestamp = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map_or(0, |d| d.as_millis());
+        let dir = std::path::PathBuf::from("outputs/nerf").join(format!("dataset_{}", timestamp));
+
+        match capture::export_transforms(&self.gfx, &mut self.renderer, &cameras, width, height, &dir) {
+            Ok(()) => {
+                self.ui.push_app_event(AppEvent::Status(
+                    format!("Wrote {} views and transforms.json to {}", cameras.len(), dir.display())
+                ));
+                self.ui.push_app_event(AppEvent::ExportFinished(dir));
+            }
+            Err(e) => self.ui.push_app_event(AppEvent::Status(format!("Dataset export failed: {}", e))),
+        }
+        self.scene_dirty = true;
+    }
+
+    /// Remember the window geometry and panel layout for the next run. The
+    /// size and position are kept from before maximizing, so restoring
+    /// brings back the old window.
+    pub fn save_window_state(&mut self) {
+        let maximized = self.window.is_maximized();
+        let window = &mut self.settings.window;
+        window.maximized = maximized;
+        if !maximized && self.window.is_minimized() != Some(true) {
+            let size = self.window.inner_size().to_logical::<f64>(self.window.scale_factor());
+            window.size = Some([size.width, size.height]);
+            window.position = self.window.outer_position().ok().map(|p| [p.x, p.y]);
+        }
+        window.layout = self.ui.layout();
+        self.save_settings();
+    }
+
+    /// When the custom splat shader is next checked for edits, None without one
+    pub fn shader_poll_deadline(&self) -> Option<Instant> {
+        self.shader_watch.as_ref().map(|_| self.last_shader_poll + SHADER_POLL_INTERVAL)
+    }
+
+    /// Recompile the custom splat shader if its file changed since the last
+    /// look, returning whether the splats are now drawn differently. A shader
+    /// that doesn't compile is reported and the last good one kept.
+    pub fn poll_custom_shader(&mut self) -> bool {
+        if self.last_shader_poll.elapsed() < SHADER_POLL_INTERVAL {
+            return false;
+        }
+        self.last_shader_poll = Instant::now();
+        let Some(watch) = &mut self.shader_watch else {
+            return false;
+        };
+        let path = watch.path().display().to_string();
+
+        let result = match watch.poll() {
+            None => return false,
+            Some(Ok(source)) => pollster::block_on(self.renderer.set_shader(Some(&source))),
+            Some(Err(e)) => Err(format!("Can't read {}: {}", path, e)),
+        };
+        match result {
+            Ok(()) => {
+                log::info!("Loaded splat shader {}", path);
+                self.ui.push_app_event(AppEvent::ShaderError(None));
+                true
+            }
+            Err(e) => {
+                log::warn!("Splat shader {} not loaded:\n{}", path, e);
+                self.ui.push_app_event(AppEvent::ShaderError(Some(e)));
+                false
+            }
+        }
+    }
+
+    fn save_settings(&self) {
+        if let Err(e) = self.settings.save() {
+            log::warn!("Failed to save settings: {}", e);
+        }
+    }
+
+    /// True while the camera is moving on its own or splats are still
+    /// streaming to the GPU, so continuous redraws are needed
+    pub fn is_animating(&self) -> bool {
+        self.camera_transition.is_some() || self.auto_orbit.is_some() || self.path_playback.is_some()
+            || self.morph_playback.is_some() || self.renderer.is_uploading()
+    }
+
+    /// Capture set of the photos of the COLMAP model in `dir` with their
+    /// solved cameras, at most [`CAPTURE_PHOTOS`] spread around the object.
+    /// The photos are added to the input image library.
+    fn import_colmap(&mut self, dir: &Path) -> Result<Vec<CaptureView>, AppError> {
+        let scene = ColmapScene::load(dir)?;
+        let photos = colmap::spread(scene.posed_photos()?, *CAPTURE_PHOTOS.end());
+
+        let mut capture = Vec::new();
+        for photo in photos {
+            let Some(source) = ColmapScene::find_photo(dir, &photo.name) else {
+                log::warn!("Skipping {}: not found near {}", photo.name, dir.display());
+                continue;
+            };
+            let image = self.images.add(&source)?;
+            // Cropping to the object takes it off the camera's axis
+            let camera = image.preprocessing.cropped.is_none()
+                .then_some(CaptureCamera { to_object: photo.to_object, fov_y: photo.fov_y });
+            capture.push(CaptureView { path: image.path, azimuth: photo.azimuth, elevation: photo.elevation, camera });
+        }
+        if capture.len() < *CAPTURE_PHOTOS.start() {
+            return Err(AppError::Internal(format!(
+                "Found {} of the model's photos; a capture set takes at least {}", capture.len(), CAPTURE_PHOTOS.start()
+            )));
+        }
+        Ok(capture)
+    }
+
+    /// Record a new job, a version of `parent`'s object if given, and hand
+    /// it to the worker
+    fn submit_job(&mut self, inputs: JobInputs, parent: Option<String>) {
+        let id = self.jobs.insert_version(inputs.clone(), parent);
+        self.send_job(id, inputs);
+    }
+
+    /// Record a job for each stage of the pipeline `inputs` name, each
+    /// waiting for the stage it takes the output of, and send the first
+    /// ones to the worker, or hold them until `run_at`. Returns the id of
+    /// the first job.
+    fn submit_pipeline(&mut self, inputs: JobInputs, run_at: Option<u64>) -> Result<String, AppError> {
+        let graph = match &inputs.pipeline {
+            Some(id) => PipelineGraph::find(id)
+                .ok_or_else(|| AppError::Internal(format!("Unknown pipeline '{}'", id)))?,
+            None => PipelineGraph::new("splats", "Splats").stage("splats", StageKind::TextToGaussians, None, None),
+        };
+        let order = graph.order().map_err(|e| AppError::Internal(e.to_string()))?;
+
+        // Stages running on the worker, with their inputs; others only
+        // change what a worker stage does
+        let mut stages: Vec<(&Stage, JobInputs)> = Vec::new();
+        for stage in order {
+            let model = stage.model.clone().unwrap_or_else(|| inputs.model.clone());
+            match stage.kind {
+                StageKind::TextToGaussians => stages.push((stage, JobInputs { model, ..inputs.clone() })),
+                // Drawn by the job of the stage taking the views
+                StageKind::TextToMultiview => {}
+                StageKind::MultiviewToGaussians => {
+                    // The generator picked in the form, otherwise the one the
+                    // pipeline names
+                    let picked = inputs.params.get("multiview").and_then(Value::as_str);
+                    let config = stage.input.as_deref()
+                        .and_then(|input| graph.get(input))
+                        .filter(|views| views.kind == StageKind::TextToMultiview)
+                        .and_then(|views| TextToMultiviewConfig::from_model(picked.or(views.model.as_deref())?))
+                        .ok_or_else(|| AppError::ModelNotLoaded(format!(
+                            "The workers can't draw the views of pipeline '{}'", graph.name
+                        )))?;
+                    if config.needs_image() && inputs.image.is_none() {
+                        return Err(AppError::GenerationFailed(format!(
+                            "{} draws the views from an input image; pick one first", config.model()
+                        )));
+                    }
+                    if config.needs_capture() && !CAPTURE_PHOTOS.contains(&inputs.capture.len()) {
+                        return Err(AppError::GenerationFailed(format!(
+                            "A capture set takes {} to {} photos, not {}",
+                            CAPTURE_PHOTOS.start(), CAPTURE_PHOTOS.end(), inputs.capture.len()
+                        )));
+                    }
+                    let mut params = inputs.params.clone();
+                    for (name, value) in config.params() {
+                        params.entry(name.to_string()).or_insert(json!(value));
+                    }
+                    params.insert("multiview".to_string(), json!(config.model()));
+                    let mut stage_inputs = JobInputs { model, ..inputs.clone() };
+                    stage_inputs.set_params(params);
+                    stage_inputs.image = inputs.image.clone().filter(|_| config.needs_image());
+                    if !config.needs_capture() {
+                        stage_inputs.capture.clear();
+                    }
+                    stages.push((stage, stage_inputs));
+                }
+                StageKind::Refine => {
+                    let refine = Refine { ply: String::new(), source: None, settings: RefineSettings::default() };
+                    stages.push((stage, JobInputs { refine: Some(refine), redecode: None, ..inputs.clone() }));
+                }
+                StageKind::Export => {
+                    if let Some((_, source)) = stages.iter_mut().find(|(s, _)| stage.input.as_ref() == Some(&s.id)) {
+                        source.export_mesh = true;
+                    }
+                }
+                StageKind::TextToImage | StageKind::ImageToMultiview => {
+                    return Err(AppError::ModelNotLoaded(format!(
+                        "The workers can't run {} stages of pipeline '{}' with {}", stage.kind.label(), graph.name, model
+                    )));
+                }
+            }
+        }
+
+        let mut ids: BTreeMap<&str, String> = BTreeMap::new();
+        let mut first = None;
+        let mut ready = Vec::new();
+        for (stage, stage_inputs) in stages {
+            let dependency = stage.input.as_deref().and_then(|input| ids.get(input)).cloned();
+            let id = match &dependency {
+                Some(dependency) => self.jobs.insert_version(stage_inputs.clone(), Some(dependency.clone())),
+                None => self.jobs.insert(stage_inputs.clone()),
+            };
+            match (dependency, run_at) {
+                (Some(dependency), _) => self.jobs.update(&id, |job| {
+                    job.status = JobStatus::Waiting;
+                    job.depends_on = vec![dependency];
+                }),
+                (None, Some(run_at)) => self.jobs.update(&id, |job| {
+                    job.status = JobStatus::Scheduled;
+                    job.run_at = Some(run_at);
+                }),
+                (None, None) => ready.push((id.clone(), stage_inputs)),
+            }
+            first.get_or_insert_with(|| id.clone());
+            ids.insert(&stage.id, id);
+        }
+
+        for (id, inputs) in ready {
+            self.send_job(id, inputs);
+        }
+        self.push_jobs();
+        first.ok_or_else(|| AppError::Internal(format!("Pipeline '{}' has no stages", graph.name)))
+    }
+
+    /// Submit a job working on `parent`'s output: right away when it's
+    /// there, otherwise once `parent` completes
+    fn submit_second_stage(&mut self, parent: String, inputs: JobInputs) {
+        let Some(job) = self.jobs.get(&parent) else {
+            return;
+        };
+        if job.status.is_finished() {
+            match inputs.with_outputs_of(job) {
+                Ok(inputs) => self.submit_job(inputs, Some(parent)),
+                Err(e) => self.ui.push_app_event(AppEvent::Status(e.to_string())),
+            }
+            return;
+        }
+
+        let id = self.jobs.insert_version(inputs, Some(parent.clone()));
+        self.jobs.update(&id, |job| {
+            job.status = JobStatus::Waiting;
+            job.depends_on = vec![parent.clone()];
+        });
+        self.ui.push_app_event(AppEvent::Status(format!("{} runs once {} is complete", id, parent)));
+        self.push_jobs();
+    }
+
+    /// Hand an existing job record to the worker
+    fn send_job(&mut self, id: String, inputs: JobInputs) {
+        if self.queue_paused {
+            self.held_jobs.push((id, inputs));
+            self.push_jobs();
+            return;
+        }
+        if let Err(e) = self.lgm_worker.send_prompt(id.clone(), inputs) {
+            self.jobs.update(&id, |job| {
+                job.status = JobStatus::Failed;
+                job.error = Some(AppError::Internal(e.clone()));
+            });
+            self.ui.push_app_event(AppEvent::Status(format!("Worker error: {}", e)));
+        }
+        self.push_jobs();
+    }
+
+    /// Send scheduled jobs whose time has come to the worker. Ones that came
+    /// due while the app was closed go out on the next start.
+    fn dispatch_scheduled_jobs(&mut self) {
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs();
+        for (id, inputs) in self.jobs.take_due_scheduled(now) {
+            log::info!("Scheduled job {} is due", id);
+            self.send_job(id, inputs);
+        }
+    }
+
+    /// When the next scheduled job comes due, for the event loop to wake
+    /// up and send it even if nothing else happens until then
+    pub fn next_scheduled_job(&self) -> Option<Instant> {
+        let run_at = self.jobs.next_scheduled()?;
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs();
+        Some(Instant::now() + Duration::from_secs(run_at.saturating_sub(now)))
+    }
+
+    /// Send waiting jobs whose dependencies are complete to the worker, with
+    /// the dependencies' outputs passed along; fail the ones that can't run
+    fn dispatch_waiting_jobs(&mut self) {
+        let machine = &self.jobs.submitter().machine;
+        let resolved: Vec<_> = self.jobs.jobs().iter()
+            .filter(|job| job.status == JobStatus::Waiting && job.is_from_machine(machine))
+            .map(|job| (job.id.clone(), self.jobs.resolve_dependencies(job)))
+            .collect();
+
+        for (id, dependencies) in resolved {
+            match dependencies {
+                Dependencies::Pending => {}
+                Dependencies::Ready(inputs) => {
+                    log::info!("Dependencies of job {} are complete", id);
+                    self.jobs.update(&id, |job| {
+                        job.status = JobStatus::Queued;
+                        job.inputs = (*inputs).clone();
+                    });
+                    self.send_job(id, *inputs);
+                }
+                Dependencies::Failed(e) => {
+                    log::warn!("Job {} can't run: {}", id, e);
+                    self.jobs.update(&id, |job| {
+                        job.status = JobStatus::Failed;
+                        job.error = Some(e);
+                    });
+                    self.push_jobs();
+                }
+            }
+        }
+    }
+
+    /// Ask each configured worker how busy it is, for the queue panel
+    fn poll_worker_loads(&mut self) {
+        self.last_worker_poll = Instant::now();
+        let endpoints = self.settings.workers();
+        let output_dir = self.output_dir.clone();
+        let ui_tx = self.ui.app_event_sender_clone();
+        self.rt.spawn_blocking(move || {
+            let loads = endpoints
+                .into_iter()
+                .map(|e| {
+                    let load = HttpBackend::new(&e.url, output_dir.clone()).with_token(e.token.clone()).load()
+                        .inspect_err(|err| log::debug!("Worker {} unreachable: {}", e.name, err))
+                        .ok();
+                    (e.name, load)
+                })
+                .collect();
+            let _ = ui_tx.send(AppEvent::WorkerLoads(loads));
+        });
+    }
+
+    /// Ask each configured worker which model weights it has, for the model manager
+    fn poll_model_weights(&mut self) {
+        let endpoints = self.settings.workers();
+        let output_dir = self.output_dir.clone();
+        let ui_tx = self.ui.app_event_sender_clone();
+        let window = self.window.clone();
+        self.rt.spawn_blocking(move || {
+            let _ = ui_tx.send(AppEvent::ModelWeights(model_weights(endpoints, &output_dir)));
+            window.request_redraw();
+        });
+    }
+
+    /// Ask each configured worker for its GPUs, for the system info
+    fn poll_worker_gpus(&mut self) {
+        let endpoints = self.settings.workers();
+        let output_dir = self.output_dir.clone();
+        let ui_tx = self.ui.app_event_sender_clone();
+        let window = self.window.clone();
+        self.rt.spawn_blocking(move || {
+            let gpus = endpoints
+                .into_iter()
+                .map(|e| {
+                    let gpus = HttpBackend::new(&e.url, output_dir.clone()).with_token(e.token.clone()).health();
+                    (e.name, gpus)
+                })
+                .collect::<Vec<(String, Result<Vec<WorkerGpu>, AppError>)>>();
+            let _ = ui_tx.send(AppEvent::WorkerGpus(gpus));
+            window.request_redraw();
+        });
+    }
+
+    /// Have worker `worker` fetch or delete `model`'s weights, then report
+    /// the weights again
+    fn manage_weights(&mut self, worker: String, model: String, remove: bool) {
+        let endpoints = self.settings.workers();
+        let Some(endpoint) = endpoints.iter().find(|e| e.name == worker).cloned() else {
+            return;
+        };
+        let output_dir = self.output_dir.clone();
+        let ui_tx = self.ui.app_event_sender_clone();
+        let window = self.window.clone();
+        self.rt.spawn_blocking(move || {
+            let backend = HttpBackend::new(&endpoint.url, output_dir.clone()).with_token(endpoint.token);
+            let result = if remove { backend.remove_weights(&model) } else { backend.download_weights(&model) };
+            if let Err(e) = result {
+                log::error!("Managing {} weights on {} failed: {}", model, worker, e);
+                let _ = ui_tx.send(AppEvent::Status(e.to_string()));
+            }
+            let _ = ui_tx.send(AppEvent::ModelWeights(model_weights(endpoints, &output_dir)));
+            window.request_redraw();
+        });
+    }
+
+    /// Jobs this machine left queued in a previous session will never be
+    /// picked up; other machines sharing the history look after their own
+    fn flag_orphaned_jobs(&mut self) {
+        let orphaned: Vec<_> = self.jobs.jobs().iter()
+            .filter(|job| job.status == JobStatus::Queued && !job.stuck)
+            .filter(|job| job.is_from_machine(&self.jobs.submitter().machine))
+            .map(|job| job.id.clone())
+            .collect();
+
+        for id in orphaned {
+            self.jobs.update(&id, |job| job.stuck = true);
+        }
+    }
+
+    /// Apply `f` to the job the worker was processing and clear it
+    fn finish_active_job(&mut self, f: impl FnOnce(&mut JobMetadata)) {
+        self.set_job_progress(None);
+        if let Some(id) = self.active_job.take() {
+            self.jobs.update(&id, f);
+            self.push_jobs();
+        }
+    }
+
+    fn fail_active_job(&mut self, err: AppError) {
+        self.finish_active_job(|job| {
+            job.status = JobStatus::Failed;
+            job.error = Some(err);
+        });
+    }
+
+    /// Show the active job's progress in the window title, and on the
+    /// taskbar entry where the desktop supports it
+    fn set_job_progress(&mut self, progress: Option<f32>) {
+        if self.job_progress == progress {
+            return;
+        }
+        self.job_progress = progress;
+
+        let prompt = self.active_job.as_ref()
+            .and_then(|id| self.jobs.get(id))
+            .map(|job| job.inputs.prompt.chars().take(40).collect::<String>());
+        let title = match (progress, prompt) {
+            (Some(p), Some(prompt)) => format!("{:.0}% · {} — {}", p * 100.0, prompt, WINDOW_TITLE),
+            (Some(p), None) => format!("{:.0}% — {}", p * 100.0, WINDOW_TITLE),
+            (None, _) => WINDOW_TITLE.to_string(),
+        };
+        self.window.set_title(&title);
+        if let Some(tray) = &self.tray {
+            tray.set_progress(progress);
+        }
+    }
+
+    /// Desktop notification for the active job finishing, if it ran long
+    /// enough for the window to have been put away
+    fn notify_if_hidden(&self, summary: &str, body: &str) {
+        let Some(tray) = &self.tray else {
+            return;
+        };
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs();
+        let long_running = self.active_job.as_ref()
+            .and_then(|id| self.jobs.get(id))
+            .is_some_and(|job| now.saturating_sub(job.created_at) >= NOTIFY_AFTER_SECS);
+
+        if self.paused && long_running {
+            tray.notify(summary, body);
+        }
+    }
+
+    fn push_jobs(&mut self) {
+        self.ui.push_app_event(AppEvent::JobsChanged(self.jobs.jobs().to_vec()));
+    }
+
+    fn push_camera_path(&mut self) {
+        let times = self.camera_path.keyframes().iter().map(|k| k.time).collect();
+        self.ui.push_app_event(AppEvent::CameraPathChanged(times));
+    }
+
+    // --- 3D rendering + UI rendering ---------------------------------------
+
+    /// Scene background as a clear color
+    fn background_color(&self) -> wgpu::Color {
+        let [r, g, b] = self.renderer.background().map(f64::from);
+        wgpu::Color { r, g, b, a: 1.0 }
+    }
+
+    pub fn render(&mut self) -> anyhow::Result<()> {
+        let size = self.window.inner_size();
+        if size.width == 0 || size.height == 0 {
+            return Ok(());
+        }
+
+        let output = self.gfx.surface.get_current_texture()?;
+        let view = output.texture.create_view(&wgpu::TextureViewDescriptor::default());
+        let mut encoder = self.gfx.device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+            label: Some("Render Encoder")
+        });
+
+        // --- UI layout ------------------------------------------------------
+
+        // The UI runs first so the scene is rendered at the size of the
+        // viewport it lays out; the central panel shows the scene target
+        self.ui.set_clip_handles(self.clip_handles());
+        self.ui.set_volume_outline(self.volume_outline());
+        let (full_output, ui_events) = self.ui.draw(&self.window);
+
+        // At a render scale other than 100% the egui image filters it to fit.
+        // In stereo each eye gets half of the viewport's width.
+        let stereo = self.gfx.right_eye.is_some();
+        let scene_size = self.ui.viewport_size()
+            .map(|(width, height)| if stereo { (width / 2, height) } else { (width, height) })
+            .filter(|&(width, height)| width > 0 && height > 0)
+            .map(|viewport| self.settings.render.scene_size(viewport));
+        if let Some((width, height)) = scene_size
+            && self.gfx.resize_scene(width, height)
+        {
+            self.ui.update_scene_texture(&self.gfx);
+            self.camera.aspect_ratio = width as f32 / height as f32;
+            self.scene_dirty = true;
+        }
+
+        // --- 3D scene -------------------------------------------------------
+
+        // The splat pass only runs when the scene changed; UI-only repaints
+        // show the last frame left in the scene target. In stereo the right
+        // eye renders last, so it's the camera compared against.
+        let scene_size = self.gfx.scene.size();
+        let camera = match stereo {
+            true => Eye::Right.camera(&self.camera, DEFAULT_IPD),
+            false => self.camera.clone(),
+        };
+        let scene_changed = self.scene_dirty || self.renderer.needs_redraw(&camera, scene_size);
+
+        if scene_changed && self.gaussian_cloud.is_some() {
+            if let Some(right_eye) = &self.gfx.right_eye {
+                // The eyes share the renderer's uniform buffer, so the left
+                // eye is submitted on its own before the right one's uniforms
+                // are written
+                let mut left_encoder = self.gfx.device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                    label: Some("Left Eye Encoder")
+                });
+                self.renderer.render(
+                    &mut left_encoder,
+                    &self.gfx.scene.view,
+                    &self.gfx.scene.depth_view,
+                    &Eye::Left.camera(&self.camera, DEFAULT_IPD),
+                    scene_size,
+                );
+                self.gfx.queue.submit(std::iter::once(left_encoder.finish()));
+                self.renderer.render(&mut encoder, &right_eye.view, &right_eye.depth_view, &camera, scene_size);
+            } else {
+                self.renderer.render(
+                    &mut encoder,
+                    &self.gfx.scene.view,
+                    &self.gfx.scene.depth_view,
+                    &camera,
+                    scene_size,
+                );
+            }
+
+            let progress = self.renderer.upload_progress();
+            if progress != self.upload_progress {
+                self.upload_progress = progress;
+                self.ui.push_app_event(AppEvent::UploadProgress(progress));
+            }
+        } else if scene_changed {
+            for target in std::iter::once(&self.gfx.scene).chain(&self.gfx.right_eye) {
+                let _ = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                    label: Some("Clear Pass"),
+                    color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                        view: &target.view,
+                        resolve_target: None,
+                        depth_slice: None,
+                        ops: wgpu::Operations {
+                            load: wgpu::LoadOp::Clear(self.background_color()),
+                            store: StoreOp::Store,
+                        },
+                    })],
+                    depth_stencil_attachment: None,
+                    ..Default::default()
+                });
+            }
+        }
+        self.scene_dirty = false;
+
+        // --- UI -------------------------------------------------------------
+
+        let platform_output = full_output.platform_output.clone();
+        self.ui.egui_state.handle_platform_output(&self.window, platform_output);
+
+        let shapes = full_output.shapes.clone();
+        let pixels_per_point = full_output.pixels_per_point;
+        let paint_jobs = self.ui.egui_ctx.tessellate(shapes, pixels_per_point);
+
+        let size = self.window.inner_size();
+        let screen_desc = egui_wgpu::ScreenDescriptor {
+            size_in_pixels: [size.width, size.height],
+            pixels_per_point: self.window.scale_factor() as f32,
+        };
+
+        for (id, delta) in &full_output.textures_delta.set {
+            self.ui.egui_renderer.update_texture(&self.gfx.device, &self.gfx.queue, *id, delta);
+        }
+
+        self.ui.egui_renderer.update_buffers(
+            &self.gfx.device,
+            &self.gfx.queue,
+            &mut encoder,
+            &paint_jobs,
+            &screen_desc,
+        );
 
         // draw UI pass
         {
-            let mut rpass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            let rpass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
                 label: Some("egui pass"),
                 color_attachments: &[Some(wgpu::RenderPassColorAttachment {
                     view: &view,
                     resolve_target: None,
                     depth_slice: None,
                     ops: wgpu::Operations {
-                        load: wgpu::LoadOp::Load,
+                        load: wgpu::LoadOp::Clear(self.background_color()),
                         store: StoreOp::Store,
                     },
                 })],
@@ -386,8 +2793,32 @@ impl AppState {
         output.present();
 
         // Merge UI events + broadcast to panels (child components)
-        self.ui.after_draw_process(full_output, ui_events);
+        if self.ui.after_draw_process(ui_events)
+            && let Some(queue_window) = &self.queue_window
+        {
+            queue_window.window.request_redraw();
+        }
 
         Ok(())
     }
 }
+
+/// Model weights on each of `endpoints`, by worker name
+fn model_weights(endpoints: Vec<WorkerEndpoint>, output_dir: &Path) -> Vec<(String, Result<Vec<ModelWeights>, AppError>)> {
+    endpoints
+        .into_iter()
+        .map(|e| {
+            let weights = HttpBackend::new(&e.url, output_dir.to_path_buf()).with_token(e.token.clone()).list_weights();
+            (e.name, weights)
+        })
+        .collect()
+}
+
+/// Camera target and distance that fit `bounds` in view
+fn framing(bounds: &BoundingBox) -> (glam::Vec3, f32) {
+    let center = bounds.center();
+    let size = bounds.size();
+    let max_dim = size[0].max(size[1]).max(size[2]).max(0.1);
+
+    (glam::Vec3::from_array(center), max_dim * 2.5)
+}
\ No newline at end of file
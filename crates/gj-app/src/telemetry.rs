@@ -0,0 +1,201 @@
+//! Job lifecycle traces: a span per job with a child span per stage (submit,
+//! queued, running, load). Stage durations are always logged; with an OTLP
+//! endpoint configured the traces are also exported over OTLP/HTTP, so the
+//! pipeline's latency can be looked at in Jaeger, Tempo and the like.
+
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+use std::sync::OnceLock;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use serde_json::{json, Value};
+use crate::error::AppError;
+
+/// Where traces go, set once by [`init`]
+struct Exporter {
+    /// Full traces URL, `<endpoint>/v1/traces`
+    url: String,
+    client: reqwest::blocking::Client,
+}
+
+static EXPORTER: OnceLock<Exporter> = OnceLock::new();
+
+/// Export traces to the OTLP/HTTP collector at `endpoint` (e.g.
+/// `http://localhost:4318`), falling back to `OTEL_EXPORTER_OTLP_ENDPOINT`.
+/// Traces are only logged when neither is set.
+pub fn init(endpoint: Option<&str>) {
+    let endpoint = endpoint
+        .map(str::to_string)
+        .or_else(|| std::env::var("OTEL_EXPORTER_OTLP_ENDPOINT").ok())
+        .filter(|e| !e.trim().is_empty());
+    let Some(endpoint) = endpoint else {
+        return;
+    };
+
+    log::info!("Exporting job traces to {}", endpoint);
+    let client = reqwest::blocking::Client::builder()
+        .use_rustls_tls()
+        .timeout(Duration::from_secs(10))
+        .build()
+        .unwrap_or_default();
+    let _ = EXPORTER.set(Exporter {
+        url: format!("{}/v1/traces", endpoint.trim_end_matches('/')),
+        client,
+    });
+}
+
+/// Random id for traces and spans; ids only need to be unique, not secret
+fn random_id() -> u64 {
+    static COUNTER: AtomicU64 = AtomicU64::new(0);
+    let mut hasher = RandomState::new().build_hasher();
+    hasher.write_u64(COUNTER.fetch_add(1, Ordering::Relaxed));
+    hasher.finish()
+}
+
+fn unix_nanos(time: SystemTime) -> String {
+    time.duration_since(UNIX_EPOCH).unwrap_or_default().as_nanos().to_string()
+}
+
+struct Stage {
+    name: &'static str,
+    span_id: u64,
+    start: SystemTime,
+    end: Option<SystemTime>,
+}
+
+/// Timeline of one job in the worker
+pub struct JobTrace {
+    trace_id: u128,
+    span_id: u64,
+    job_id: String,
+    model: String,
+    start: SystemTime,
+    stages: Vec<Stage>,
+}
+
+impl JobTrace {
+    pub fn new(job_id: &str, model: &str) -> Self {
+        Self {
+            trace_id: (u128::from(random_id()) << 64) | u128::from(random_id()),
+            span_id: random_id(),
+            job_id: job_id.to_string(),
+            model: model.to_string(),
+            start: SystemTime::now(),
+            stages: Vec::new(),
+        }
+    }
+
+    /// End the current stage and start `name`; a no-op while already in it
+    pub fn stage(&mut self, name: &'static str) {
+        if self.stages.last().is_some_and(|s| s.name == name && s.end.is_none()) {
+            return;
+        }
+        let now = SystemTime::now();
+        self.end_stage(now);
+        self.stages.push(Stage { name, span_id: random_id(), start: now, end: None });
+    }
+
+    fn end_stage(&mut self, now: SystemTime) {
+        if let Some(stage) = self.stages.last_mut().filter(|s| s.end.is_none()) {
+            stage.end = Some(now);
+            let ms = now.duration_since(stage.start).unwrap_or_default().as_millis() as u64;
+            log::debug!(job_id = self.job_id.as_str(), model = self.model.as_str(), stage = stage.name, duration_ms = ms;
+                "Job {} spent {} ms in {}", self.job_id, ms, stage.name);
+        }
+    }
+
+    /// End the trace, failed with `error` if given, and export it
+    pub fn finish(mut self, error: Option<&AppError>) {
+        let now = SystemTime::now();
+        self.end_stage(now);
+
+        let total = now.duration_since(self.start).unwrap_or_default().as_millis() as u64;
+        log::info!(job_id = self.job_id.as_str(), model = self.model.as_str(), stage = "done", duration_ms = total;
+            "Job {} took {} ms", self.job_id, total);
+
+        if let Some(exporter) = EXPORTER.get() {
+            let body = self.to_otlp(now, error);
+            let request = exporter.client.post(&exporter.url).json(&body);
+            // Off the worker thread, so a slow collector never holds up jobs
+            std::thread::spawn(move || match request.send() {
+                Ok(response) if !response.status().is_success() => {
+                    log::debug!("Trace export rejected: {}", response.status());
+                }
+                Ok(_) => {}
+                Err(e) => log::debug!("Trace export failed: {}", e),
+            });
+        }
+    }
+
+    /// OTLP/HTTP JSON export request for the trace, ended at `end`
+    fn to_otlp(&self, end: SystemTime, error: Option<&AppError>) -> Value {
+        let trace_id = format!("{:032x}", self.trace_id);
+        let attributes = json!([
+            { "key": "job.id", "value": { "stringValue": self.job_id } },
+            { "key": "job.model", "value": { "stringValue": self.model } },
+        ]);
+        let status = match error {
+            Some(e) => json!({ "code": 2, "message": e.to_string() }),
+            None => json!({ "code": 1 }),
+        };
+
+        let mut spans = vec![json!({
+            "traceId": trace_id,
+            "spanId": format!("{:016x}", self.span_id),
+            "name": "job",
+            "kind": 1,
+            "startTimeUnixNano": unix_nanos(self.start),
+            "endTimeUnixNano": unix_nanos(end),
+            "attributes": attributes,
+            "status": status,
+        })];
+        spans.extend(self.stages.iter().map(|stage| json!({
+            "traceId": trace_id,
+            "spanId": format!("{:016x}", stage.span_id),
+            "parentSpanId": format!("{:016x}", self.span_id),
+            "name": stage.name,
+            "kind": 1,
+            "startTimeUnixNano": unix_nanos(stage.start),
+            "endTimeUnixNano": unix_nanos(stage.end.unwrap_or(end)),
+            "attributes": attributes,
+        })));
+
+        json!({
+            "resourceSpans": [{
+                "resource": {
+                    "attributes": [{ "key": "service.name", "value": { "stringValue": "genjutsu" } }]
+                },
+                "scopeSpans": [{
+                    "scope": { "name": "gj-app", "version": env!("CARGO_PKG_VERSION") },
+                    "spans": spans,
+                }],
+            }]
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_job_trace_otlp() {
+        let mut trace = JobTrace::new("job-1", "shap_e");
+        trace.stage("submit");
+        trace.stage("queued");
+        trace.stage("queued");
+        trace.stage("running");
+
+        let otlp = trace.to_otlp(SystemTime::now(), Some(&AppError::OutOfVram("CUDA".into())));
+        let spans = otlp["resourceSpans"][0]["scopeSpans"][0]["spans"].as_array().unwrap();
+        let names: Vec<_> = spans.iter().map(|s| s["name"].as_str().unwrap()).collect();
+        assert_eq!(names, ["job", "submit", "queued", "running"]);
+
+        let root = &spans[0];
+        assert_eq!(root["traceId"].as_str().unwrap().len(), 32);
+        assert_eq!(root["status"]["code"], 2);
+        assert!(spans[1..].iter().all(|s| s["parentSpanId"] == root["spanId"] && s["traceId"] == root["traceId"]));
+        // The last stage is closed by the end of the trace
+        assert_eq!(spans[3]["endTimeUnixNano"], root["endTimeUnixNano"]);
+    }
+}
@@ -0,0 +1,365 @@
+//! Tray icon with quick actions, desktop notifications for jobs that finish
+//! while the window is hidden, and job progress on the taskbar entry. On
+//! Linux all go over D-Bus: a StatusNotifierItem with a
+//! `com.canonical.dbusmenu` menu, `org.freedesktop.Notifications` and the
+//! Unity launcher API. Other platforms get none of them yet.
+
+use std::path::Path;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use winit::event_loop::EventLoopProxy;
+use crate::events::{GjEvent, UiEvent};
+
+pub struct Tray {
+    #[cfg(target_os = "linux")]
+    connection: zbus::Connection,
+    rt: tokio::runtime::Handle,
+    queue_paused: Arc<AtomicBool>,
+}
+
+impl Tray {
+    /// Show the tray icon, sending menu actions to the event loop. None
+    /// where there's no tray to show it in.
+    #[cfg(target_os = "linux")]
+    pub fn spawn(rt: &tokio::runtime::Runtime, proxy: EventLoopProxy<GjEvent>) -> Option<Self> {
+        let queue_paused = Arc::new(AtomicBool::new(false));
+        let actions = Actions { proxy, queue_paused: queue_paused.clone() };
+
+        let connection = rt.block_on(dbus::connect(actions))
+            .inspect_err(|e| log::warn!("No session bus for the tray icon: {}", e))
+            .ok()?;
+
+        Some(Self { connection, rt: rt.handle().clone(), queue_paused })
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    pub fn spawn(_rt: &tokio::runtime::Runtime, _proxy: EventLoopProxy<GjEvent>) -> Option<Self> {
+        log::debug!("Tray icon is only supported on Linux");
+        None
+    }
+
+    /// Post a desktop notification; failures are only logged
+    pub fn notify(&self, summary: &str, body: &str) {
+        #[cfg(target_os = "linux")]
+        {
+            let connection = self.connection.clone();
+            let (summary, body) = (summary.to_string(), body.to_string());
+            self.rt.spawn(async move {
+                if let Err(e) = dbus::notify(&connection, &summary, &body).await {
+                    log::warn!("Failed to show notification: {}", e);
+                }
+            });
+        }
+        #[cfg(not(target_os = "linux"))]
+        let _ = (summary, body, &self.rt);
+    }
+
+    /// Progress bar on the app's taskbar or dock entry; None hides it.
+    /// Only docks speaking the Unity launcher API show it.
+    pub fn set_progress(&self, progress: Option<f32>) {
+        #[cfg(target_os = "linux")]
+        {
+            let connection = self.connection.clone();
+            self.rt.spawn(async move {
+                if let Err(e) = dbus::launcher_progress(&connection, progress).await {
+                    log::debug!("Failed to update taskbar progress: {}", e);
+                }
+            });
+        }
+        #[cfg(not(target_os = "linux"))]
+        let _ = progress;
+    }
+
+    /// Reflect the queue state in the menu's checkmark
+    pub fn set_queue_paused(&self, paused: bool) {
+        if self.queue_paused.swap(paused, Ordering::SeqCst) == paused {
+            return;
+        }
+
+        #[cfg(target_os = "linux")]
+        {
+            let connection = self.connection.clone();
+            self.rt.spawn(async move {
+                if let Err(e) = dbus::menu_changed(&connection).await {
+                    log::debug!("Failed to update the tray menu: {}", e);
+                }
+            });
+        }
+    }
+}
+
+/// What the tray menu can do, sent on as UI events
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+struct Actions {
+    proxy: EventLoopProxy<GjEvent>,
+    queue_paused: Arc<AtomicBool>,
+}
+
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+impl Actions {
+    fn send(&self, event: UiEvent) {
+        // Only fails once the event loop is gone
+        let _ = self.proxy.send_event(GjEvent::Ui(event));
+    }
+}
+
+/// Open a folder in the platform file manager, or a file in its default app
+pub fn open_path(path: &Path) -> std::io::Result<()> {
+    let program = if cfg!(target_os = "windows") {
+        "explorer"
+    } else if cfg!(target_os = "macos") {
+        "open"
+    } else {
+        "xdg-open"
+    };
+    std::process::Command::new(program).arg(path).spawn().map(|_| ())
+}
+
+#[cfg(target_os = "linux")]
+mod dbus {
+    use std::collections::HashMap;
+    use std::sync::atomic::{AtomicU32, Ordering};
+    use zbus::object_server::SignalEmitter;
+    use zbus::zvariant::{OwnedObjectPath, OwnedValue, StructureBuilder, Value};
+    use super::Actions;
+    use crate::events::UiEvent;
+
+    const ITEM_PATH: &str = "/StatusNotifierItem";
+    const MENU_PATH: &str = "/MenuBar";
+
+    // Menu item ids; 0 is the root
+    const SHOW_WINDOW: i32 = 1;
+    const PAUSE_QUEUE: i32 = 2;
+    const SEPARATOR: i32 = 3;
+    const OPEN_OUTPUTS: i32 = 4;
+
+    pub async fn connect(actions: Actions) -> zbus::Result<zbus::Connection> {
+        let actions = std::sync::Arc::new(actions);
+        let name = format!("org.kde.StatusNotifierItem-{}-1", std::process::id());
+
+        let connection = zbus::connection::Builder::session()?
+            .name(name.as_str())?
+            .serve_at(ITEM_PATH, Item { actions: actions.clone() })?
+            .serve_at(MENU_PATH, Menu { actions, revision: AtomicU32::new(1) })?
+            .build()
+            .await?;
+
+        // Without a watcher there's no tray, but notifications still work
+        let registered = connection.call_method(
+            Some("org.kde.StatusNotifierWatcher"),
+            "/StatusNotifierWatcher",
+            Some("org.kde.StatusNotifierWatcher"),
+            "RegisterStatusNotifierItem",
+            &(name.as_str(),),
+        ).await;
+        if let Err(e) = registered {
+            log::info!("No system tray available: {}", e);
+        }
+
+        Ok(connection)
+    }
+
+    pub async fn notify(connection: &zbus::Connection, summary: &str, body: &str) -> zbus::Result<()> {
+        let hints: HashMap<&str, Value> = HashMap::new();
+        connection.call_method(
+            Some("org.freedesktop.Notifications"),
+            "/org/freedesktop/Notifications",
+            Some("org.freedesktop.Notifications"),
+            "Notify",
+            &("Genjutsu", 0u32, "applications-graphics", summary, body, Vec::<&str>::new(), hints, -1i32),
+        ).await?;
+        Ok(())
+    }
+
+    pub async fn launcher_progress(connection: &zbus::Connection, progress: Option<f32>) -> zbus::Result<()> {
+        let mut properties: HashMap<&str, Value> = HashMap::new();
+        properties.insert("progress", Value::from(f64::from(progress.unwrap_or(0.0))));
+        properties.insert("progress-visible", Value::from(progress.is_some()));
+
+        connection.emit_signal(
+            None::<zbus::names::BusName>,
+            "/com/genjutsu/LauncherEntry",
+            "com.canonical.Unity.LauncherEntry",
+            "Update",
+            &("application://genjutsu.desktop", properties),
+        ).await
+    }
+
+    /// Tell the tray host to fetch the menu again
+    pub async fn menu_changed(connection: &zbus::Connection) -> zbus::Result<()> {
+        let menu = connection.object_server().interface::<_, Menu>(MENU_PATH).await?;
+        let revision = menu.get().await.revision.fetch_add(1, Ordering::SeqCst) + 1;
+        Menu::layout_updated(menu.signal_emitter(), revision, 0).await
+    }
+
+    struct Item {
+        actions: std::sync::Arc<Actions>,
+    }
+
+    #[zbus::interface(name = "org.kde.StatusNotifierItem")]
+    impl Item {
+        fn activate(&self, _x: i32, _y: i32) {
+            self.actions.send(UiEvent::ShowWindow);
+        }
+
+        fn secondary_activate(&self, _x: i32, _y: i32) {}
+
+        fn scroll(&self, _delta: i32, _orientation: &str) {}
+
+        #[zbus(property)]
+        fn category(&self) -> &str {
+            "ApplicationStatus"
+        }
+
+        #[zbus(property)]
+        fn id(&self) -> &str {
+            "genjutsu"
+        }
+
+        #[zbus(property)]
+        fn title(&self) -> &str {
+            "Genjutsu"
+        }
+
+        #[zbus(property)]
+        fn status(&self) -> &str {
+            "Active"
+        }
+
+        #[zbus(property)]
+        fn icon_name(&self) -> &str {
+            "applications-graphics"
+        }
+
+        #[zbus(property)]
+        fn item_is_menu(&self) -> bool {
+            false
+        }
+
+        #[zbus(property)]
+        fn menu(&self) -> OwnedObjectPath {
+            OwnedObjectPath::try_from(MENU_PATH).unwrap()
+        }
+    }
+
+    struct Menu {
+        actions: std::sync::Arc<Actions>,
+        revision: AtomicU32,
+    }
+
+    type Properties = HashMap<String, OwnedValue>;
+
+    impl Menu {
+        fn properties(&self, id: i32) -> Properties {
+            let paused = self.actions.queue_paused.load(Ordering::SeqCst);
+            let entries: Vec<(&str, Value)> = match id {
+                SHOW_WINDOW => vec![("label", "Show window".into())],
+                PAUSE_QUEUE => vec![
+                    ("label", "Pause queue".into()),
+                    ("toggle-type", "checkmark".into()),
+                    ("toggle-state", i32::from(paused).into()),
+                ],
+                SEPARATOR => vec![("type", "separator".into())],
+                OPEN_OUTPUTS => vec![("label", "Open outputs folder".into())],
+                _ => vec![("children-display", "submenu".into())],
+            };
+
+            entries.into_iter()
+                .filter_map(|(key, value)| Some((key.to_string(), OwnedValue::try_from(value).ok()?)))
+                .collect()
+        }
+
+        fn node(&self, id: i32, children: Vec<OwnedValue>) -> (i32, Properties, Vec<OwnedValue>) {
+            (id, self.properties(id), children)
+        }
+    }
+
+    #[zbus::interface(name = "com.canonical.dbusmenu")]
+    impl Menu {
+        fn get_layout(
+            &self,
+            _parent_id: i32,
+            _recursion_depth: i32,
+            _property_names: Vec<String>,
+        ) -> (u32, (i32, Properties, Vec<OwnedValue>)) {
+            // The menu is flat, so the whole tree is returned whatever the parent
+            let children = [SHOW_WINDOW, PAUSE_QUEUE, SEPARATOR, OPEN_OUTPUTS]
+                .into_iter()
+                .filter_map(|id| {
+                    let (id, properties, children) = self.node(id, Vec::new());
+                    let child = StructureBuilder::new()
+                        .add_field(id)
+                        .add_field(properties)
+                        .add_field(children)
+                        .build()
+                        .ok()?;
+                    OwnedValue::try_from(Value::from(child)).ok()
+                })
+                .collect();
+
+            (self.revision.load(Ordering::SeqCst), self.node(0, children))
+        }
+
+        fn get_group_properties(&self, ids: Vec<i32>, _property_names: Vec<String>) -> Vec<(i32, Properties)> {
+            ids.into_iter().map(|id| (id, self.properties(id))).collect()
+        }
+
+        fn get_property(&self, id: i32, name: String) -> zbus::fdo::Result<OwnedValue> {
+            self.properties(id).remove(&name)
+                .ok_or_else(|| zbus::fdo::Error::InvalidArgs(format!("No property {} on item {}", name, id)))
+        }
+
+        fn event(&self, id: i32, event_id: String, _data: OwnedValue, _timestamp: u32) {
+            if event_id != "clicked" {
+                return;
+            }
+            match id {
+                SHOW_WINDOW => self.actions.send(UiEvent::ShowWindow),
+                PAUSE_QUEUE => {
+                    let paused = self.actions.queue_paused.load(Ordering::SeqCst);
+                    self.actions.send(UiEvent::SetQueuePaused(!paused));
+                }
+                OPEN_OUTPUTS => self.actions.send(UiEvent::OpenOutputFolder),
+                _ => {}
+            }
+        }
+
+        fn event_group(&self, events: Vec<(i32, String, OwnedValue, u32)>) -> Vec<i32> {
+            for (id, event_id, data, timestamp) in events {
+                self.event(id, event_id, data, timestamp);
+            }
+            Vec::new()
+        }
+
+        fn about_to_show(&self, _id: i32) -> bool {
+            false
+        }
+
+        fn about_to_show_group(&self, _ids: Vec<i32>) -> (Vec<i32>, Vec<i32>) {
+            (Vec::new(), Vec::new())
+        }
+
+        #[zbus(signal)]
+        async fn layout_updated(emitter: &SignalEmitter<'_>, revision: u32, parent: i32) -> zbus::Result<()>;
+
+        #[zbus(property)]
+        fn version(&self) -> u32 {
+            3
+        }
+
+        #[zbus(property)]
+        fn text_direction(&self) -> &str {
+            "ltr"
+        }
+
+        #[zbus(property)]
+        fn status(&self) -> &str {
+            "normal"
+        }
+
+        #[zbus(property)]
+        fn icon_theme_path(&self) -> Vec<String> {
+            Vec::new()
+        }
+    }
+}
@@ -1,11 +1,18 @@
+mod palette;
 mod panels;
+pub mod shortcuts;
 
 use std::sync::Arc;
-use egui::{Context, FullOutput};
+use std::time::Instant;
+use egui::Context;
+use egui_wgpu::wgpu;
+use gj_splat::clipping::ClipHandle;
 use winit::window::Window;
 use crate::events::{AppEvent, UiEvent};
 use crate::gfx::GfxState;
+use crate::settings::{AppearanceSettings, PanelLayout};
 use crate::ui::panels::Panels;
+use crate::ui::shortcuts::{Keymap, ShortcutAction};
 
 pub struct UiState {
     pub(crate) egui_state: egui_winit::State,
@@ -16,12 +23,23 @@ pub struct UiState {
     app_incoming: Vec<AppEvent>,
 
     app_event_tx: std::sync::mpsc::Sender<AppEvent>,
+    app_event_rx: std::sync::mpsc::Receiver<AppEvent>,
 
     panels: Panels,
+    /// egui's handle on the 3D scene target
+    scene_texture: egui::TextureId,
+    /// egui's handle on the right eye's target in stereo
+    right_eye_texture: Option<egui::TextureId>,
+
+    keymap: Keymap,
+    show_shortcuts: bool,
+
+    /// When egui asked to be redrawn without further input (timers, animations)
+    repaint_at: Option<Instant>,
 }
 
 impl UiState {
-    pub fn new(gfx: &GfxState, window: Arc<Window>) -> Self {
+    pub fn new(gfx: &GfxState, window: Arc<Window>, keymap: Keymap) -> Self {
         let egui_ctx = egui::Context::default();
         let egui_state = egui_winit::State::new(
             egui_ctx.clone(),
@@ -32,11 +50,14 @@ impl UiState {
             None,
         );
 
-        let egui_renderer = egui_wgpu::Renderer::new(
+        let mut egui_renderer = egui_wgpu::Renderer::new(
             &gfx.device,
             gfx.config.format,
             egui_wgpu::RendererOptions::default()
         );
+        let scene_texture = egui_renderer.register_native_texture(&gfx.device, &gfx.scene.view, wgpu::FilterMode::Linear);
+        let mut panels = Panels::default();
+        panels.central.scene_texture = Some(scene_texture);
 
         let (tx, rx) = std::sync::mpsc::channel::<AppEvent>();
 
@@ -47,10 +68,79 @@ impl UiState {
             ui_outgoing: Vec::new(),
             app_incoming: Vec::new(),
             app_event_tx: tx,
-            panels: Panels::default(),
+            app_event_rx: rx,
+            panels,
+            scene_texture,
+            right_eye_texture: None,
+            keymap,
+            show_shortcuts: false,
+            repaint_at: None,
         }
     }
 
+    pub fn apply_appearance(&self, appearance: &AppearanceSettings) {
+        apply_appearance(&self.egui_ctx, appearance);
+    }
+
+    /// Point the viewport at the scene targets after they were recreated
+    pub fn update_scene_texture(&mut self, gfx: &GfxState) {
+        self.egui_renderer.update_egui_texture_from_wgpu_texture(
+            &gfx.device,
+            &gfx.scene.view,
+            wgpu::FilterMode::Linear,
+            self.scene_texture,
+        );
+
+        self.right_eye_texture = match (&gfx.right_eye, self.right_eye_texture) {
+            (Some(target), Some(id)) => {
+                self.egui_renderer.update_egui_texture_from_wgpu_texture(&gfx.device, &target.view, wgpu::FilterMode::Linear, id);
+                Some(id)
+            }
+            (Some(target), None) => {
+                Some(self.egui_renderer.register_native_texture(&gfx.device, &target.view, wgpu::FilterMode::Linear))
+            }
+            (None, id) => {
+                if let Some(id) = id {
+                    self.egui_renderer.free_texture(&id);
+                }
+                None
+            }
+        };
+        self.panels.central.right_eye_texture = self.right_eye_texture;
+    }
+
+    /// Size in physical pixels of the area the 3D scene is shown in, as laid
+    /// out by the last frame
+    pub fn viewport_size(&self) -> Option<(u32, u32)> {
+        let rect = self.panels.central.rect?;
+        let size = rect.size() * self.egui_ctx.pixels_per_point();
+        Some((size.x.round() as u32, size.y.round() as u32))
+    }
+
+    /// Place the clipping plane gizmos, one per plane, for the next frame
+    pub fn set_clip_handles(&mut self, handles: Vec<Option<ClipHandle>>) {
+        self.panels.central.clip_handles = handles;
+    }
+
+    /// Outline the volume with these segments, in viewport coordinates from
+    /// 0 to 1, for the next frame
+    pub fn set_volume_outline(&mut self, segments: Vec<[glam::Vec2; 2]>) {
+        self.panels.central.volume_outline = segments;
+    }
+
+    /// Pointer is over the 3D scene, with no panel or window in the way
+    pub fn pointer_over_viewport(&self) -> bool {
+        self.panels.central.hovered
+    }
+
+    pub fn layout(&self) -> PanelLayout {
+        self.panels.layout()
+    }
+
+    pub fn apply_layout(&mut self, layout: &PanelLayout) {
+        self.panels.apply_layout(layout);
+    }
+
     pub fn on_window_event(&mut self, window: &winit::window::Window, event: &winit::event::WindowEvent) -> egui_winit::EventResponse {
         self.egui_state.on_window_event(window, event)
     }
@@ -60,25 +150,73 @@ impl UiState {
         let mut sender = UiEventSender::default();
 
         let full_output = self.egui_ctx.run(raw_input, |ctx| {
+            let queue_focused = self.panels.queue.focused_job().is_some();
+            for action in self.keymap.poll(ctx, queue_focused) {
+                match action {
+                    ShortcutAction::ResetCamera => sender.instant(UiEvent::ResetCamera),
+                    ShortcutAction::Screenshot => sender.instant(UiEvent::Screenshot),
+                    ShortcutAction::ToggleSidePanel => self.panels.show_side = !self.panels.show_side,
+                    ShortcutAction::ToggleLogPanel => self.panels.log.toggle(),
+                    ShortcutAction::FocusPrompt => {
+                        self.panels.show_side = true;
+                        self.panels.side.request_prompt_focus();
+                    }
+                    ShortcutAction::SubmitGeneration => self.panels.side.submit(&mut sender),
+                    ShortcutAction::ToggleAutoOrbit => self.panels.side.toggle_auto_orbit(&mut sender),
+                    ShortcutAction::ToggleInspector => self.panels.inspector.toggle(),
+                    ShortcutAction::ShowShortcuts => self.show_shortcuts = !self.show_shortcuts,
+                    ShortcutAction::DeleteSelection => sender.instant(UiEvent::DeleteSelection),
+                    ShortcutAction::DeleteJob => self.panels.queue.delete_focused(&mut sender),
+                }
+            }
+
             self.panels.draw(ctx, &mut sender);
+            self.keymap.show_cheat_sheet(ctx, &mut self.show_shortcuts);
         });
 
+        self.repaint_at = full_output
+            .viewport_output
+            .get(&egui::ViewportId::ROOT)
+            .and_then(|v| Instant::now().checked_add(v.repaint_delay));
+
         let events = sender.take_events();
         (full_output, events)
     }
 
-    fn draw_panels(&mut self, ctx: &Context) {
-        let mut sender = UiEventSender::default();
-        self.panels.draw(ctx, &mut sender);
+    /// Move the queue panel out into its own window, or back
+    pub fn set_queue_detached(&mut self, detached: bool) {
+        self.panels.queue.detached = detached;
+        self.panels.queue.forget_textures();
+    }
 
-        // merge events
-        self.ui_outgoing.extend(sender.take_events());
+    /// Draw the detached queue panel into the queue window's context
+    pub fn draw_queue_window(&mut self, ctx: &Context, sender: &mut UiEventSender) {
+        self.panels.queue.show_window(ctx, sender);
     }
 
     pub fn push_app_event(&mut self, ev: AppEvent) {
         self.app_incoming.push(ev);
     }
 
+    /// True once a delayed repaint requested by egui is due
+    pub fn repaint_due(&self) -> bool {
+        self.repaint_at.is_some_and(|at| Instant::now() >= at)
+    }
+
+    /// When egui next wants a repaint, if it asked for one
+    pub fn repaint_at(&self) -> Option<Instant> {
+        self.repaint_at
+    }
+
+    /// Queue an event from outside the UI, such as the tray menu
+    pub fn push_ui_event(&mut self, ev: UiEvent) {
+        self.ui_outgoing.push(ev);
+    }
+
+    pub fn has_pending_ui_events(&self) -> bool {
+        !self.ui_outgoing.is_empty()
+    }
+
     pub fn take_ui_events(&mut self) -> Vec<UiEvent> {
         std::mem::take(&mut self.ui_outgoing)
     }
@@ -87,20 +225,42 @@ impl UiState {
         self.app_event_tx.clone()
     }
 
-    /// internal: call after draw_ui to merge events and broadcast app_incoming to panels
-    pub fn after_draw_process(&mut self, full_output: egui::FullOutput, events_from_draw: Vec<UiEvent>) {
+    /// internal: call after draw_ui to merge events and broadcast app_incoming to panels.
+    /// Returns whether any app events were broadcast.
+    pub fn after_draw_process(&mut self, events_from_draw: Vec<UiEvent>) -> bool {
         // collect outgoing ui events
         self.ui_outgoing.extend(events_from_draw);
 
+        // pick up app events sent from background threads
+        self.app_incoming.extend(self.app_event_rx.try_iter());
+
         // broadcast app events to all panels (so child components can react)
+        let any = !self.app_incoming.is_empty();
         for app_ev in self.app_incoming.drain(..) {
             self.panels.on_app_event(&app_ev);
         }
 
         // handle platform output (clipboard, window title, etc.) is done by caller
+        any
     }
 }
 
+/// Switch the theme and tint both the dark and light styles with the accent color
+pub fn apply_appearance(ctx: &Context, appearance: &AppearanceSettings) {
+    let [r, g, b] = appearance.accent;
+    let accent = egui::Color32::from_rgb(r, g, b);
+
+    ctx.set_theme(appearance.theme.to_egui());
+    ctx.all_styles_mut(|style| {
+        let visuals = &mut style.visuals;
+        visuals.selection.bg_fill = accent;
+        visuals.hyperlink_color = accent;
+        visuals.widgets.hovered.bg_stroke.color = accent;
+        visuals.widgets.active.bg_stroke.color = accent;
+        visuals.widgets.open.bg_stroke.color = accent;
+    });
+}
+
 #[derive(Default)]
 pub struct UiEventSender {
     events: Vec<UiEvent>,
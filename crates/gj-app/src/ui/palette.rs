@@ -0,0 +1,63 @@
+//! Status colors shared by the panels. The colorblind-safe variant swaps the
+//! red/green pairs for the Okabe-Ito palette, and every tone also has its own
+//! icon so status never hinges on color alone.
+
+use egui::Color32;
+use crate::jobs::JobStatus;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Tone {
+    Neutral,
+    Info,
+    Success,
+    Warning,
+    Error,
+}
+
+impl Tone {
+    pub fn color(self, colorblind: bool) -> Color32 {
+        match (self, colorblind) {
+            (Self::Neutral, _) => Color32::GRAY,
+            (Self::Info, false) => Color32::LIGHT_BLUE,
+            (Self::Success, false) => Color32::from_rgb(100, 255, 100),
+            (Self::Warning, false) => Color32::from_rgb(255, 200, 80),
+            (Self::Error, false) => Color32::from_rgb(255, 100, 100),
+            // Okabe-Ito sky blue, bluish green, yellow and vermillion
+            (Self::Info, true) => Color32::from_rgb(86, 180, 233),
+            (Self::Success, true) => Color32::from_rgb(0, 158, 115),
+            (Self::Warning, true) => Color32::from_rgb(240, 228, 66),
+            (Self::Error, true) => Color32::from_rgb(213, 94, 0),
+        }
+    }
+
+    pub fn icon(self) -> &'static str {
+        match self {
+            Self::Neutral => "○",
+            Self::Info => "ℹ",
+            Self::Success => "✔",
+            Self::Warning => "⚠",
+            Self::Error => "✖",
+        }
+    }
+}
+
+pub fn status_tone(status: JobStatus) -> Tone {
+    match status {
+        JobStatus::Scheduled | JobStatus::Waiting | JobStatus::Queued => Tone::Neutral,
+        JobStatus::Running => Tone::Info,
+        JobStatus::Completed => Tone::Success,
+        JobStatus::Failed => Tone::Error,
+    }
+}
+
+/// Distinct shape per job status, shown next to its label
+pub fn status_icon(status: JobStatus) -> &'static str {
+    match status {
+        JobStatus::Scheduled => "🕑",
+        JobStatus::Waiting => "⛓",
+        JobStatus::Queued => "⏳",
+        JobStatus::Running => "▶",
+        JobStatus::Completed => "✔",
+        JobStatus::Failed => "✖",
+    }
+}
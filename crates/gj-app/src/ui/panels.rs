@@ -1,18 +1,39 @@
 use egui::Context;
 use crate::events::AppEvent;
 use crate::ui::panels::central_panel::CentralPanel;
+use crate::ui::panels::inspector_panel::InspectorPanel;
+use crate::ui::panels::log_panel::LogPanel;
+use crate::ui::panels::models_panel::ModelsPanel;
+use crate::ui::panels::queue_panel::QueuePanel;
+use crate::ui::panels::relink_dialog::RelinkDialog;
 use crate::ui::panels::side_panel::SidePanel;
+use crate::ui::panels::toasts::Toasts;
 use crate::ui::panels::top_panel::TopPanel;
-use crate::ui::UiEventSender;
+use crate::settings::PanelLayout;
+use crate::ui::{UiComponent, UiEventSender};
 
 mod top_panel;
 mod side_panel;
 mod central_panel;
+mod log_panel;
+mod queue_panel;
+mod toasts;
+mod relink_dialog;
+mod inspector_panel;
+mod models_panel;
 
 pub struct Panels {
     pub top: TopPanel,
     pub side: SidePanel,
     pub central: CentralPanel,
+    pub log: LogPanel,
+    pub queue: QueuePanel,
+    pub toasts: Toasts,
+    pub relink: RelinkDialog,
+    pub inspector: InspectorPanel,
+    pub models: ModelsPanel,
+
+    pub show_side: bool,
 }
 
 impl Default for Panels {
@@ -21,6 +42,13 @@ impl Default for Panels {
             top: TopPanel::default(),
             side: SidePanel::default(),
             central: CentralPanel::default(),
+            log: LogPanel::default(),
+            queue: QueuePanel::default(),
+            toasts: Toasts::default(),
+            relink: RelinkDialog::default(),
+            inspector: InspectorPanel::default(),
+            models: ModelsPanel::default(),
+            show_side: true,
         }
     }
 }
@@ -29,8 +57,45 @@ impl Panels {
     /// Draw all panels. Each panel can push UiEvents into the sender.
     pub fn draw(&mut self, ctx: &Context, sender: &mut UiEventSender) {
         self.top.show(ctx, sender);
-        self.side.show(ctx, sender);
+        if std::mem::take(&mut self.top.toggle_models) {
+            self.models.toggle();
+        }
+        self.log.show(ctx, sender);
+        if self.show_side {
+            self.side.show(ctx, sender);
+        }
+        if !self.queue.detached {
+            self.queue.show(ctx, sender);
+        }
         self.central.show(ctx, sender);
+        self.toasts.show(ctx, sender);
+        self.relink.show(ctx, sender);
+        self.inspector.show(ctx, sender);
+        self.models.show(ctx, sender);
+    }
+
+    /// Current panel sizes and visibility, for saving
+    pub fn layout(&self) -> PanelLayout {
+        PanelLayout {
+            side_width: self.side.width,
+            queue_width: self.queue.width,
+            job_inspector_height: self.queue.inspector_height,
+            log_height: self.log.height,
+            show_side: self.show_side,
+            log_expanded: self.log.expanded,
+            inspector_open: self.inspector.open,
+        }
+    }
+
+    /// Restore a saved layout; sizes take effect as the panels' defaults
+    pub fn apply_layout(&mut self, layout: &PanelLayout) {
+        self.side.width = layout.side_width;
+        self.queue.width = layout.queue_width;
+        self.queue.inspector_height = layout.job_inspector_height;
+        self.log.height = layout.log_height;
+        self.show_side = layout.show_side;
+        self.log.expanded = layout.log_expanded;
+        self.inspector.open = layout.inspector_open;
     }
 
     /// Broadcast AppEvent to each panel (child components can react)
@@ -38,5 +103,11 @@ impl Panels {
         self.top.on_app_event(ev);
         self.side.on_app_event(ev);
         self.central.on_app_event(ev);
+        self.log.on_app_event(ev);
+        self.queue.on_app_event(ev);
+        self.toasts.on_app_event(ev);
+        self.relink.on_app_event(ev);
+        self.inspector.on_app_event(ev);
+        self.models.on_app_event(ev);
     }
 }
\ No newline at end of file
@@ -1,25 +1,378 @@
-use egui::{Color32, Context, RichText};
+use egui::{Color32, Context, Pos2, Rect, Stroke, TextureId};
+use glam::Vec2;
+use gj_splat::clipping::ClipHandle;
+use gj_splat::renderer::{ColorMode, DEFAULT_BACKGROUND};
+use gj_core::paint::PaintMode;
+use gj_splat::selection::{PaintBrush, SelectionShape};
 use crate::events::AppEvent;
-use crate::ui::UiEventSender;
+use crate::events::UiEvent;
+use crate::ui::{UiComponent, UiEventSender};
 
 #[derive(Default)]
-pub struct CentralPanel {}
+pub struct CentralPanel {
+    /// Share of the loaded cloud on the GPU while it streams in
+    upload_progress: Option<f32>,
+    /// Selection being dragged out: the box corners, or the lasso path
+    selection_drag: Option<(SelectionTool, Vec<Pos2>)>,
+    /// Brush that plain drags paint with, instead of orbiting
+    brush: Option<PaintBrush>,
+    /// Where the last dab of the current stroke landed
+    last_dab: Option<Pos2>,
+    /// Where the context menu was opened, for "Frame here"
+    menu_pos: Option<Pos2>,
+    color_mode: ColorMode,
+    background: Option<[f32; 3]>,
+    ground_shadow: bool,
+    stereo: bool,
+    /// Depth of field is on, so Ctrl-click picks the focus
+    dof_enabled: bool,
+
+    /// Offscreen 3D scene, drawn filling the viewport
+    pub scene_texture: Option<TextureId>,
+    /// Right eye's view in stereo, shown beside `scene_texture` as the left
+    pub right_eye_texture: Option<TextureId>,
+    /// Area the scene is shown in, in points
+    pub rect: Option<Rect>,
+    /// Pointer is over the scene and not over a window or popup covering it
+    pub hovered: bool,
+    /// Gizmo of each clipping plane, None while it's behind the camera
+    pub clip_handles: Vec<Option<ClipHandle>>,
+    /// Segments outlining the volume, in viewport coordinates from 0 to 1
+    pub volume_outline: Vec<[Vec2; 2]>,
+}
+
+/// Gizmo colors of the clipping planes, by index
+const CLIP_COLORS: [Color32; 3] = [
+    Color32::from_rgb(230, 90, 90),
+    Color32::from_rgb(90, 200, 120),
+    Color32::from_rgb(90, 150, 240),
+];
+
+/// Background presets offered in the context menu, linear RGB
+const BACKGROUNDS: [(&str, [f32; 3]); 4] = [
+    ("Dark gray", DEFAULT_BACKGROUND),
+    ("Black", [0.0, 0.0, 0.0]),
+    ("Mid gray", [0.2, 0.2, 0.2]),
+    ("White", [1.0, 1.0, 1.0]),
+];
+
+/// Physical pixel position of `pos` within the scene texture shown in `viewport`
+fn scene_pixels(ctx: &Context, viewport: Option<Rect>, pos: Pos2) -> Vec2 {
+    let offset = pos - viewport.map_or(Pos2::ZERO, |r| r.min);
+    Vec2::new(offset.x, offset.y) * ctx.pixels_per_point()
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum SelectionTool {
+    Box,
+    Lasso,
+}
 
 impl CentralPanel {
-    pub fn show(&mut self, ctx: &Context, _sender: &mut UiEventSender) {
+    /// Shift-drag draws a box, Alt-drag a lasso; holding Ctrl as well adds
+    /// to the current selection
+    fn drag_selection(&mut self, ctx: &Context, ui: &egui::Ui, response: &egui::Response, sender: &mut UiEventSender) {
+        let modifiers = ctx.input(|i| i.modifiers);
+        let viewport = self.rect;
+
+        if response.drag_started() {
+            let tool = if modifiers.shift {
+                Some(SelectionTool::Box)
+            } else if modifiers.alt {
+                Some(SelectionTool::Lasso)
+            } else {
+                None
+            };
+            self.selection_drag = tool.zip(response.interact_pointer_pos()).map(|(tool, pos)| (tool, vec![pos]));
+        }
+
+        let Some((tool, points)) = &mut self.selection_drag else {
+            return;
+        };
+
+        if let Some(pos) = response.interact_pointer_pos() {
+            match tool {
+                SelectionTool::Box => {
+                    points.truncate(1);
+                    points.push(pos);
+                }
+                SelectionTool::Lasso => {
+                    if points.last().is_none_or(|last| last.distance(pos) > 2.0) {
+                        points.push(pos);
+                    }
+                }
+            }
+        }
+
+        let stroke = Stroke::new(1.5, Color32::from_rgb(255, 140, 26));
+        let painter = ui.painter();
+        match tool {
+            SelectionTool::Box if points.len() == 2 => {
+                let rect = egui::Rect::from_two_pos(points[0], points[1]);
+                painter.rect_filled(rect, 0.0, Color32::from_rgba_unmultiplied(255, 140, 26, 24));
+                painter.rect_stroke(rect, 0.0, stroke, egui::StrokeKind::Inside);
+            }
+            SelectionTool::Lasso if points.len() > 1 => {
+                painter.add(egui::Shape::closed_line(points.clone(), stroke));
+            }
+            _ => {}
+        }
+
+        if response.drag_stopped() {
+            let to_pixels = |p: &Pos2| scene_pixels(ctx, viewport, *p);
+            let shape = match tool {
+                SelectionTool::Box if points.len() == 2 => {
+                    Some(SelectionShape::rect(to_pixels(&points[0]), to_pixels(&points[1])))
+                }
+                SelectionTool::Lasso if points.len() > 2 => {
+                    Some(SelectionShape::Lasso(points.iter().map(to_pixels).collect()))
+                }
+                _ => None,
+            };
+            if let Some(shape) = shape {
+                sender.instant(UiEvent::SelectRegion { shape, additive: modifiers.command });
+            }
+            self.selection_drag = None;
+        }
+    }
+
+    /// With a brush picked, clicks and plain drags paint dabs along the
+    /// pointer's path, a quarter of the brush radius apart
+    fn paint(&mut self, ctx: &Context, ui: &egui::Ui, response: &egui::Response, sender: &mut UiEventSender) {
+        let Some(brush) = self.brush else {
+            return;
+        };
+        let radius = brush.radius / ctx.pixels_per_point();
+
+        if let Some(pos) = response.hover_pos() {
+            let color = match brush.mode {
+                PaintMode::Opacity { .. } => Color32::from_white_alpha(180),
+                PaintMode::Tint { color, .. } => egui::Rgba::from_rgb(color[0], color[1], color[2]).into(),
+            };
+            ui.painter().circle_stroke(pos, radius, Stroke::new(1.5, color));
+        }
+
+        let modifiers = ctx.input(|i| i.modifiers);
+        let painting = response.clicked() || response.dragged_by(egui::PointerButton::Primary);
+        if painting
+            && !modifiers.shift
+            && !modifiers.alt
+            && let Some(pos) = response.interact_pointer_pos()
+            && (response.clicked() || self.last_dab.is_none_or(|last| last.distance(pos) >= radius * 0.25))
+        {
+            let pixels = scene_pixels(ctx, self.rect, pos);
+            sender.instant(UiEvent::Paint { x: pixels.x, y: pixels.y });
+            self.last_dab = Some(pos);
+        }
+        if response.drag_stopped() {
+            self.last_dab = None;
+        }
+    }
+
+    fn draw_volume(&self, ui: &egui::Ui) {
+        let Some(viewport) = self.rect else {
+            return;
+        };
+        let to_screen = |p: Vec2| viewport.min + egui::vec2(p.x * viewport.width(), p.y * viewport.height());
+        let stroke = Stroke::new(1.5, Color32::from_rgb(240, 200, 60));
+        for [a, b] in &self.volume_outline {
+            ui.painter().line_segment([to_screen(*a), to_screen(*b)], stroke);
+        }
+    }
+
+    /// Arrows along the clipping planes' normals; dragging one moves its
+    /// plane. Drawn over the scene, so the camera doesn't take the drag.
+    fn clip_gizmos(&mut self, ui: &egui::Ui, sender: &mut UiEventSender) {
+        let Some(viewport) = self.rect else {
+            return;
+        };
+        let to_screen = |p: Vec2| viewport.min + egui::vec2(p.x * viewport.width(), p.y * viewport.height());
+
+        for (index, handle) in self.clip_handles.iter().enumerate() {
+            let Some(handle) = handle else {
+                continue;
+            };
+            let (origin, tip) = (to_screen(handle.origin), to_screen(handle.tip));
+            let response = ui.interact(
+                Rect::from_center_size(tip, egui::vec2(16.0, 16.0)),
+                ui.id().with(("clip_handle", index)),
+                egui::Sense::drag(),
+            );
+
+            let color = CLIP_COLORS[index % CLIP_COLORS.len()];
+            let color = if response.hovered() || response.dragged() { color } else { color.gamma_multiply(0.7) };
+            let painter = ui.painter();
+            painter.line_segment([origin, tip], Stroke::new(2.0, color));
+            painter.circle_filled(origin, 3.0, color);
+            painter.circle_filled(tip, 6.0, color);
+
+            if response.dragged() {
+                let axis = tip - origin;
+                let along = response.drag_delta().dot(axis) / axis.length_sq().max(1.0);
+                if along != 0.0 {
+                    sender.instant(UiEvent::MoveClipPlane { index, delta: along * handle.length });
+                }
+            }
+        }
+    }
+
+    /// Right-click menu with the viewer actions, so they don't need the side panel
+    fn context_menu(&mut self, ctx: &Context, response: &egui::Response, sender: &mut UiEventSender) {
+        if response.secondary_clicked() {
+            self.menu_pos = response.interact_pointer_pos();
+        }
+
+        response.context_menu(|ui| {
+            if ui.button("🎯 Reset camera").clicked() {
+                sender.instant(UiEvent::ResetCamera);
+                ui.close();
+            }
+            if ui.button("⛶ Frame scene").clicked() {
+                sender.instant(UiEvent::FrameScene);
+                ui.close();
+            }
+            if let Some(pos) = self.menu_pos
+                && ui.button("🔍 Frame here").on_hover_text("Frame the splats under the cursor").clicked()
+            {
+                let pixels = scene_pixels(ctx, self.rect, pos);
+                sender.instant(UiEvent::FocusAt { x: pixels.x, y: pixels.y });
+                ui.close();
+            }
+            if self.dof_enabled
+                && let Some(pos) = self.menu_pos
+                && ui.button("📷 Focus here").on_hover_text("Keep the splats under the cursor sharp").clicked()
+            {
+                let pixels = scene_pixels(ctx, self.rect, pos);
+                sender.instant(UiEvent::FocusDepthAt { x: pixels.x, y: pixels.y });
+                ui.close();
+            }
+            if ui.button("📸 Screenshot").clicked() {
+                sender.instant(UiEvent::Screenshot);
+                ui.close();
+            }
+
+            ui.separator();
+
+            ui.menu_button("🎨 Shading", |ui| {
+                for mode in ColorMode::ALL {
+                    if ui.radio(self.color_mode == mode, mode.label()).clicked() {
+                        self.color_mode = mode;
+                        sender.instant(UiEvent::SetColorMode(mode));
+                        ui.close();
+                    }
+                }
+            });
+
+            ui.menu_button("🖼 Background", |ui| {
+                let current = self.background.unwrap_or(DEFAULT_BACKGROUND);
+                for (label, color) in BACKGROUNDS {
+                    if ui.radio(current == color, label).clicked() {
+                        self.background = (color != DEFAULT_BACKGROUND).then_some(color);
+                        sender.instant(UiEvent::SetBackground(self.background));
+                        ui.close();
+                    }
+                }
+                ui.horizontal(|ui| {
+                    let mut color = current;
+                    if egui::color_picker::color_edit_button_rgb(ui, &mut color).changed() {
+                        self.background = Some(color);
+                        sender.instant(UiEvent::SetBackground(self.background));
+                    }
+                    ui.label("Custom");
+                });
+            });
+
+            if ui.checkbox(&mut self.ground_shadow, "🌑 Ground shadow").changed() {
+                sender.instant(UiEvent::SetGroundShadow(self.ground_shadow));
+            }
+            if ui.checkbox(&mut self.stereo, "👓 Stereo (experimental)")
+                .on_hover_text("Side-by-side view for each eye, at true scale with one unit per meter")
+                .changed()
+            {
+                sender.instant(UiEvent::SetStereo(self.stereo));
+            }
+        });
+    }
+}
+
+impl UiComponent for CentralPanel {
+    fn show(&mut self, ctx: &Context, sender: &mut UiEventSender) {
         egui::CentralPanel::default()
-            .frame(egui::Frame::default().fill(Color32::TRANSPARENT))
+            .frame(egui::Frame::NONE)
             .show(ctx, |ui| {
                 // Always allocate space to prevent zero-size viewport issues
-                ui.allocate_space(ui.available_size());
+                let response = ui.allocate_response(ui.available_size(), egui::Sense::click_and_drag());
+                self.rect = Some(response.rect);
+                self.hovered = response.hovered();
+                let uv = Rect::from_min_max(Pos2::ZERO, Pos2::new(1.0, 1.0));
+                if let Some(right_eye) = self.right_eye_texture {
+                    let (left, right) = response.rect.split_left_right_at_fraction(0.5);
+                    if let Some(texture) = self.scene_texture {
+                        ui.painter().image(texture, left, uv, Color32::WHITE);
+                    }
+                    ui.painter().image(right_eye, right, uv, Color32::WHITE);
+                } else if let Some(texture) = self.scene_texture {
+                    ui.painter().image(texture, response.rect, uv, Color32::WHITE);
+                }
+
+                // Picking, selection and the gizmos work on the single view
+                // only; halves of a stereo pair would need their own mapping
+                let mono = self.right_eye_texture.is_none();
+
+                // Double-click frames the splats under the cursor (or the whole cloud)
+                if mono
+                    && response.double_clicked()
+                    && let Some(pos) = response.interact_pointer_pos()
+                {
+                    let pixels = scene_pixels(ctx, self.rect, pos);
+                    sender.instant(UiEvent::FocusAt { x: pixels.x, y: pixels.y });
+                }
+
+                // Ctrl-click picks the depth of field's focus
+                if mono
+                    && self.dof_enabled
+                    && response.clicked()
+                    && ctx.input(|i| i.modifiers.command)
+                    && let Some(pos) = response.interact_pointer_pos()
+                {
+                    let pixels = scene_pixels(ctx, self.rect, pos);
+                    sender.instant(UiEvent::FocusDepthAt { x: pixels.x, y: pixels.y });
+                }
+
+                if mono {
+                    self.drag_selection(ctx, ui, &response, sender);
+                    self.paint(ctx, ui, &response, sender);
+                    self.draw_volume(ui);
+                    self.clip_gizmos(ui, sender);
+                }
+                self.context_menu(ctx, &response, sender);
 
                 // Show instructions centered
                 ui.vertical_centered(|ui| {
-                    ui.label("Viewport - 3D scene renders under the UI.");
+                    ui.label("Viewport - the 3D scene renders here.");
                     ui.label("When no cloud is loaded, this area shows instructions.");
+
+                    if let Some(p) = self.upload_progress {
+                        ui.add(egui::ProgressBar::new(p)
+                            .desired_width(240.0)
+                            .text(format!("Uploading splats {:.0}%", p * 100.0)));
+                    }
                 });
             });
     }
 
-    pub fn on_app_event(&mut self, _ev: &AppEvent) {}
+    fn on_app_event(&mut self, ev: &AppEvent) {
+        match ev {
+            AppEvent::UploadProgress(p) => self.upload_progress = *p,
+            AppEvent::ColorMode(mode) => self.color_mode = *mode,
+            AppEvent::DepthOfField(dof) => self.dof_enabled = dof.is_some(),
+            AppEvent::Brush(brush) => self.brush = *brush,
+            AppEvent::RenderSettings(render) => {
+                self.background = render.background;
+                self.ground_shadow = render.ground_shadow;
+                self.stereo = render.stereo;
+            }
+            _ => {}
+        }
+    }
 }
\ No newline at end of file
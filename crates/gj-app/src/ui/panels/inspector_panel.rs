@@ -0,0 +1,268 @@
+use std::path::PathBuf;
+use egui::{Context, RichText};
+use gj_core::cloud_stats::{CloudStats, Distribution, OPACITY_BUCKETS};
+use gj_core::comparison::ComparisonMetrics;
+use gj_core::heatmap::HeatmapQuantity;
+use gj_splat::renderer::ColorMode;
+use crate::events::{AppEvent, UiEvent};
+use crate::ui::{UiComponent, UiEventSender};
+
+/// Floating window with statistics of the loaded cloud
+pub struct InspectorPanel {
+    pub open: bool,
+    stats: Option<CloudStats>,
+    /// Heatmaps are color modes, shared with the shading menus
+    color_mode: ColorMode,
+
+    /// Align with ICP before comparing
+    align: bool,
+    /// Reference picked in the file browser, compared on the next frame
+    picked_reference: Option<PathBuf>,
+    comparison: Option<ComparisonMetrics>,
+    show_differences: bool,
+
+    /// Morph target picked in the file browser, paired on the next frame
+    picked_morph: Option<PathBuf>,
+    /// Splat pairs of the morph shown
+    morph_pairs: Option<usize>,
+    morph_position: f32,
+    morph_playing: bool,
+    morph_frames: usize,
+}
+
+impl Default for InspectorPanel {
+    fn default() -> Self {
+        Self {
+            open: false,
+            stats: None,
+            color_mode: ColorMode::default(),
+            align: false,
+            picked_reference: None,
+            comparison: None,
+            show_differences: false,
+            picked_morph: None,
+            morph_pairs: None,
+            morph_position: 0.0,
+            morph_playing: false,
+            morph_frames: 90,
+        }
+    }
+}
+
+impl InspectorPanel {
+    pub fn toggle(&mut self) {
+        self.open = !self.open;
+    }
+}
+
+fn distribution_row(ui: &mut egui::Ui, label: &str, d: &Distribution) {
+    ui.label(label);
+    ui.label(format!("{:.3}", d.min));
+    ui.label(format!("{:.3}", d.median));
+    ui.label(format!("{:.3}", d.mean));
+    ui.label(format!("{:.3}", d.p95));
+    ui.label(format!("{:.3}", d.max));
+    ui.end_row();
+}
+
+impl UiComponent for InspectorPanel {
+    fn show(&mut self, ctx: &Context, sender: &mut UiEventSender) {
+        if let Some(path) = self.picked_reference.take() {
+            sender.instant(UiEvent::CompareWith { path, align: self.align });
+        }
+        if let Some(path) = self.picked_morph.take() {
+            sender.instant(UiEvent::MorphTo { path, align: self.align });
+        }
+
+        egui::Window::new("🔍 Inspector")
+            .open(&mut self.open)
+            .default_width(320.0)
+            .resizable(false)
+            .show(ctx, |ui| {
+                let Some(stats) = &self.stats else {
+                    ui.label("No cloud loaded.");
+                    return;
+                };
+
+                egui::Grid::new("inspector_summary").num_columns(2).show(ui, |ui| {
+                    ui.label("Splats");
+                    ui.label(stats.count.to_string());
+                    ui.end_row();
+
+                    let size = stats.bounds.size();
+                    ui.label("Bounds");
+                    ui.label(format!("{:.2} × {:.2} × {:.2}", size[0], size[1], size[2]));
+                    ui.end_row();
+
+                    let center = stats.bounds.center();
+                    ui.label("Center");
+                    ui.label(format!("{:.2}, {:.2}, {:.2}", center[0], center[1], center[2]));
+                    ui.end_row();
+
+                    ui.label("SH degree");
+                    ui.label(stats.sh_degree.map_or("none".to_string(), |d| d.to_string()));
+                    ui.end_row();
+
+                    ui.label("Memory");
+                    ui.label(format!("{:.1} MiB", stats.memory_bytes as f32 / (1024.0 * 1024.0)));
+                    ui.end_row();
+                });
+
+                ui.separator();
+                egui::Grid::new("inspector_distributions").num_columns(6).striped(true).show(ui, |ui| {
+                    for header in ["", "min", "median", "mean", "p95", "max"] {
+                        ui.label(RichText::new(header).strong());
+                    }
+                    ui.end_row();
+                    distribution_row(ui, "Opacity", &stats.opacity);
+                    distribution_row(ui, "Scale", &stats.scale);
+                });
+
+                ui.separator();
+                ui.label(RichText::new("Opacity histogram").strong());
+
+                let peak = stats.opacity_histogram.iter().copied().max().unwrap_or(0).max(1);
+                for (i, &count) in stats.opacity_histogram.iter().enumerate() {
+                    let lo = i as f32 / OPACITY_BUCKETS as f32;
+                    ui.horizontal(|ui| {
+                        ui.monospace(format!("{:.1}–{:.1}", lo, lo + 1.0 / OPACITY_BUCKETS as f32));
+                        ui.add(egui::ProgressBar::new(count as f32 / peak as f32)
+                            .desired_width(160.0)
+                            .text(count.to_string()));
+                    });
+                }
+
+                ui.separator();
+                let heatmap = match self.color_mode {
+                    ColorMode::Heatmap(quantity) => Some(quantity),
+                    _ => None,
+                };
+                ui.horizontal(|ui| {
+                    ui.label(RichText::new("Heatmap").strong());
+                    egui::ComboBox::from_id_salt("inspector_heatmap")
+                        .selected_text(heatmap.map_or("Off", HeatmapQuantity::label))
+                        .show_ui(ui, |ui| {
+                            if ui.selectable_label(heatmap.is_none(), "Off").clicked() {
+                                sender.instant(UiEvent::SetColorMode(ColorMode::Albedo));
+                            }
+                            for quantity in HeatmapQuantity::ALL {
+                                if ui.selectable_label(heatmap == Some(quantity), quantity.label()).clicked() {
+                                    sender.instant(UiEvent::SetColorMode(ColorMode::Heatmap(quantity)));
+                                }
+                            }
+                        });
+                });
+                match heatmap {
+                    Some(HeatmapQuantity::Opacity) => {
+                        ui.label(RichText::new("Blue: transparent, red: opaque").small());
+                    }
+                    Some(HeatmapQuantity::ShEnergy) if stats.sh_degree.is_none() => {
+                        ui.label(RichText::new("This cloud has no SH coefficients").small());
+                    }
+                    Some(_) => {
+                        ui.label(RichText::new("Blue: low, red: 95th percentile or higher").small());
+                    }
+                    None => {}
+                }
+
+                ui.separator();
+                ui.label(RichText::new("Compare").strong());
+                ui.horizontal(|ui| {
+                    if ui.button("📂 Compare with PLY…").clicked() {
+                        sender.instant(UiEvent::BrowseComparison);
+                    }
+                    ui.checkbox(&mut self.align, "Align first")
+                        .on_hover_text("Register the scene onto the reference with ICP before measuring");
+                });
+
+                if let Some(metrics) = &self.comparison {
+                    egui::Grid::new("inspector_comparison").num_columns(2).show(ui, |ui| {
+                        for (label, value) in [
+                            ("Chamfer", metrics.chamfer),
+                            ("Mean to reference", metrics.mean_to_reference),
+                            ("Mean from reference", metrics.mean_from_reference),
+                            ("Hausdorff", metrics.hausdorff),
+                        ] {
+                            ui.label(label);
+                            ui.label(format!("{:.4}", value));
+                            ui.end_row();
+                        }
+                    });
+
+                    ui.horizontal(|ui| {
+                        if ui.checkbox(&mut self.show_differences, "Heatmap").changed() {
+                            sender.instant(UiEvent::ShowDifferences(self.show_differences));
+                        }
+                        if ui.button("Clear").clicked() {
+                            sender.instant(UiEvent::ClearComparison);
+                        }
+                    });
+                    ui.label(RichText::new(format!("Blue: matching, red: {:.3} or further", metrics.distances.p95)).small());
+                }
+
+                ui.separator();
+                ui.label(RichText::new("Morph (experimental)").strong());
+                if ui.button("📂 Morph into PLY…")
+                    .on_hover_text("Pair every splat with the nearest one of another cloud and blend between them; uses \"Align first\" too")
+                    .clicked()
+                {
+                    sender.instant(UiEvent::BrowseMorphTarget);
+                }
+
+                if let Some(pairs) = self.morph_pairs {
+                    ui.label(RichText::new(format!("{} splat pairs", pairs)).small());
+                    if ui.add(egui::Slider::new(&mut self.morph_position, 0.0..=1.0).text("Blend")).changed() {
+                        self.morph_playing = false;
+                        sender.instant(UiEvent::SetMorphPosition(self.morph_position));
+                    }
+                    ui.horizontal(|ui| {
+                        let label = if self.morph_playing { "⏸ Pause" } else { "▶ Play" };
+                        if ui.button(label).clicked() {
+                            sender.instant(UiEvent::PlayMorph(!self.morph_playing));
+                        }
+                        if ui.button("Clear").clicked() {
+                            sender.instant(UiEvent::ClearMorph);
+                        }
+                    });
+                    ui.horizontal(|ui| {
+                        ui.add(egui::DragValue::new(&mut self.morph_frames).range(2..=1000).suffix(" frames"));
+                        if ui.button("🎞 Export frames")
+                            .on_hover_text("Render the morph from the current view into a PNG sequence for a video")
+                            .clicked()
+                        {
+                            sender.instant(UiEvent::ExportMorph { frames: self.morph_frames });
+                        }
+                    });
+                }
+            });
+    }
+
+    fn on_app_event(&mut self, ev: &AppEvent) {
+        match ev {
+            AppEvent::CloudStats(stats) => {
+                self.stats = Some(stats.clone());
+            }
+            AppEvent::ColorMode(mode) => {
+                self.color_mode = *mode;
+            }
+            AppEvent::ComparisonPicked(path) => {
+                self.picked_reference = Some(path.clone());
+            }
+            AppEvent::MorphTargetPicked(path) => {
+                self.picked_morph = Some(path.clone());
+            }
+            AppEvent::Morph(pairs) => {
+                self.morph_pairs = *pairs;
+            }
+            AppEvent::MorphPosition { position, playing } => {
+                self.morph_position = *position;
+                self.morph_playing = *playing;
+            }
+            AppEvent::Comparison(metrics) => {
+                self.comparison = *metrics;
+                self.show_differences = metrics.is_some();
+            }
+            _ => {}
+        }
+    }
+}
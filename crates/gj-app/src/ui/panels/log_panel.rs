@@ -0,0 +1,186 @@
+use egui::{Context, RichText};
+use log::Level;
+use crate::events::AppEvent;
+use crate::logging;
+use crate::ui::palette::Tone;
+use crate::ui::{UiComponent, UiEventSender};
+
+/// Collapsible bottom panel showing captured log records
+pub struct LogPanel {
+    pub expanded: bool,
+    pub min_level: Level,
+    pub filter_text: String,
+    /// Height in points while expanded, remembered across runs
+    pub height: f32,
+    colorblind: bool,
+    /// Entries passing the level and text filters, as of `visible_key`
+    visible: Vec<logging::LogEntry>,
+    /// Buffer generation and filters `visible` was taken with
+    visible_key: Option<(u64, Level, String)>,
+}
+
+impl Default for LogPanel {
+    fn default() -> Self {
+        Self {
+            expanded: false,
+            min_level: Level::Info,
+            filter_text: String::new(),
+            height: 180.0,
+            colorblind: false,
+            visible: Vec::new(),
+            visible_key: None,
+        }
+    }
+}
+
+fn level_tone(level: Level) -> Tone {
+    match level {
+        Level::Error => Tone::Error,
+        Level::Warn => Tone::Warning,
+        Level::Info => Tone::Info,
+        Level::Debug | Level::Trace => Tone::Neutral,
+    }
+}
+
+impl UiComponent for LogPanel {
+    fn show(&mut self, ctx: &Context, _sender: &mut UiEventSender) {
+        let buffer = logging::buffer();
+
+        let response = egui::TopBottomPanel::bottom("log_panel")
+            .resizable(self.expanded)
+            .default_height(self.height)
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    let arrow = if self.expanded { "⏷" } else { "⏵" };
+                    if ui.button(format!("{} 📜 Log ({})", arrow, buffer.len())).clicked() {
+                        self.expanded = !self.expanded;
+                    }
+
+                    if !self.expanded {
+                        return;
+                    }
+
+                    ui.separator();
+
+                    egui::ComboBox::from_id_salt("log_level")
+                        .selected_text(self.min_level.as_str())
+                        .show_ui(ui, |ui| {
+                            for level in [Level::Error, Level::Warn, Level::Info, Level::Debug, Level::Trace] {
+                                ui.selectable_value(&mut self.min_level, level, level.as_str());
+                            }
+                        });
+
+                    ui.add(
+                        egui::TextEdit::singleline(&mut self.filter_text)
+                            .hint_text("Filter...")
+                            .desired_width(160.0)
+                    );
+
+                    if ui.button("📋 Copy").clicked() {
+                        self.refresh(buffer);
+                        let text = self.visible
+                            .iter()
+                            .map(|e| format!("[{:>8.2}s {:<5} {}] {}", e.elapsed, e.level, e.target, e.message))
+                            .collect::<Vec<_>>()
+                            .join("\n");
+                        ctx.copy_text(text);
+                    }
+
+                    if ui.button("🗑 Clear").clicked() {
+                        buffer.clear();
+                    }
+                });
+
+                if !self.expanded {
+                    return;
+                }
+
+                ui.separator();
+
+                self.refresh(buffer);
+                egui::ScrollArea::vertical()
+                    .auto_shrink([false, false])
+                    .stick_to_bottom(true)
+                    .show(ui, |ui| {
+                        for entry in &self.visible {
+                            ui.horizontal_wrapped(|ui| {
+                                ui.label(RichText::new(format!("{:>8.2}s", entry.elapsed)).monospace().weak());
+                                ui.label(RichText::new(format!("{:<5}", entry.level)).monospace().color(level_tone(entry.level).color(self.colorblind)));
+                                ui.label(RichText::new(&entry.target).monospace().weak());
+                                ui.label(RichText::new(&entry.message).monospace());
+                            });
+                        }
+                    });
+            });
+        if self.expanded {
+            self.height = response.response.rect.height();
+        }
+    }
+
+    fn on_app_event(&mut self, ev: &AppEvent) {
+        if let AppEvent::Appearance(appearance) = ev {
+            self.colorblind = appearance.colorblind;
+        }
+    }
+}
+
+impl LogPanel {
+    pub fn toggle(&mut self) {
+        self.expanded = !self.expanded;
+    }
+
+    /// Re-read the entries to show, only when the buffer or the filters
+    /// changed since last time
+    fn refresh(&mut self, buffer: &logging::LogBuffer) {
+        let key = (buffer.generation(), self.min_level, self.filter_text.clone());
+        if self.visible_key.as_ref() == Some(&key) {
+            return;
+        }
+
+        let filter = self.filter_text.to_lowercase();
+        self.visible = buffer
+            .snapshot()
+            .into_iter()
+            .filter(|e| e.level <= self.min_level)
+            .filter(|e| filter.is_empty()
+                || e.message.to_lowercase().contains(&filter)
+                || e.target.to_lowercase().contains(&filter))
+            .collect();
+        self.visible_key = Some(key);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn log(buffer: &logging::LogBuffer, level: Level, message: &str) {
+        buffer.push(&log::Record::builder().level(level).target("gj_app").args(format_args!("{}", message)).build());
+    }
+
+    #[test]
+    fn test_refresh_follows_buffer_and_filters() {
+        let buffer = logging::LogBuffer::new();
+        let mut panel = LogPanel::default();
+        log(&buffer, Level::Info, "job submitted");
+        log(&buffer, Level::Debug, "polling");
+
+        panel.refresh(&buffer);
+        assert_eq!(panel.visible.len(), 1);
+        let key = panel.visible_key.clone();
+        panel.refresh(&buffer);
+        assert_eq!(panel.visible_key, key);
+
+        log(&buffer, Level::Warn, "worker slow");
+        panel.refresh(&buffer);
+        assert_eq!(panel.visible.len(), 2);
+
+        panel.filter_text = "SLOW".into();
+        panel.refresh(&buffer);
+        assert_eq!(panel.visible.len(), 1);
+
+        buffer.clear();
+        panel.refresh(&buffer);
+        assert!(panel.visible.is_empty());
+    }
+}
\ No newline at end of file
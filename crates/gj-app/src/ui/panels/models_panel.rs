@@ -0,0 +1,181 @@
+use std::time::{Duration, Instant};
+use egui::{Context, RichText};
+use crate::error::AppError;
+use crate::events::{AppEvent, UiEvent};
+use crate::generator::{ModelWeights, WeightsStatus};
+use crate::ui::palette::Tone;
+use crate::ui::{UiComponent, UiEventSender};
+
+/// How often the weights are checked again while a worker is busy with them
+const BUSY_REFRESH: Duration = Duration::from_secs(2);
+/// How often they're checked otherwise
+const IDLE_REFRESH: Duration = Duration::from_secs(15);
+
+/// Floating window listing the model weights on each worker, with buttons
+/// to fetch or delete them
+#[derive(Default)]
+pub struct ModelsPanel {
+    pub open: bool,
+    /// Weights by worker name, as last reported
+    workers: Vec<(String, Result<Vec<ModelWeights>, AppError>)>,
+    last_refresh: Option<Instant>,
+    /// Opened once by itself for a running download; not again after that
+    auto_opened: bool,
+    /// Worker and model whose removal waits for a second click
+    confirm_remove: Option<(String, String)>,
+    colorblind: bool,
+}
+
+impl ModelsPanel {
+    pub fn toggle(&mut self) {
+        self.open = !self.open;
+    }
+
+    fn any_busy(&self) -> bool {
+        self.workers.iter()
+            .filter_map(|(_, weights)| weights.as_ref().ok())
+            .flatten()
+            .any(|w| w.status.is_busy())
+    }
+}
+
+fn format_size(bytes: u64) -> String {
+    let gib = bytes as f64 / (1024.0 * 1024.0 * 1024.0);
+    if gib >= 1.0 {
+        format!("{:.1} GiB", gib)
+    } else {
+        format!("{:.0} MiB", bytes as f64 / (1024.0 * 1024.0))
+    }
+}
+
+fn status_tone(status: WeightsStatus) -> Tone {
+    match status {
+        WeightsStatus::Ready => Tone::Success,
+        WeightsStatus::Queued | WeightsStatus::Downloading | WeightsStatus::Removing => Tone::Info,
+        WeightsStatus::Missing => Tone::Warning,
+        WeightsStatus::Failed => Tone::Error,
+        WeightsStatus::Unknown => Tone::Neutral,
+    }
+}
+
+impl UiComponent for ModelsPanel {
+    fn show(&mut self, ctx: &Context, sender: &mut UiEventSender) {
+        // Closed, keep watching only for a download to open up for
+        if !self.open && self.auto_opened {
+            self.confirm_remove = None;
+            return;
+        }
+
+        let interval = if self.any_busy() { BUSY_REFRESH } else { IDLE_REFRESH };
+        if self.last_refresh.is_none_or(|t| t.elapsed() >= interval) {
+            self.last_refresh = Some(Instant::now());
+            sender.instant(UiEvent::RefreshModelWeights);
+        }
+        ctx.request_repaint_after(interval);
+        if !self.open {
+            return;
+        }
+
+        let mut open = self.open;
+        egui::Window::new("📦 Models")
+            .open(&mut open)
+            .default_width(420.0)
+            .show(ctx, |ui| {
+                ui.label(RichText::new("Weights the generation workers download on first use").weak().small());
+                if self.workers.is_empty() {
+                    ui.label("Checking workers…");
+                }
+
+                for (worker, weights) in &self.workers {
+                    ui.add_space(4.0);
+                    ui.strong(format!("🖥 {}", worker));
+                    let weights = match weights {
+                        Ok(weights) => weights,
+                        Err(e) => {
+                            ui.label(RichText::new(e.to_string()).color(Tone::Error.color(self.colorblind)).small());
+                            continue;
+                        }
+                    };
+
+                    for w in weights {
+                        ui.separator();
+                        ui.horizontal(|ui| {
+                            ui.label(RichText::new(&w.name).strong());
+                            let tone = status_tone(w.status);
+                            ui.label(RichText::new(format!("{} {}", tone.icon(), w.status.label())).color(tone.color(self.colorblind)));
+                        });
+                        ui.label(RichText::new(&w.description).small());
+
+                        let size = match w.disk_bytes.filter(|&b| b > 0) {
+                            Some(bytes) if w.status == WeightsStatus::Ready => format!("{} on disk", format_size(bytes)),
+                            Some(bytes) => format!("{} of about {}", format_size(bytes), format_size(w.expected_bytes)),
+                            None => format!("About {}", format_size(w.expected_bytes)),
+                        };
+                        ui.label(RichText::new(size).weak().small());
+
+                        if w.status == WeightsStatus::Downloading {
+                            ui.add(egui::ProgressBar::new(w.progress.unwrap_or(0.0)).show_percentage());
+                        }
+                        if let Some(error) = &w.error {
+                            ui.label(RichText::new(error).color(Tone::Error.color(self.colorblind)).small());
+                        }
+
+                        ui.horizontal(|ui| {
+                            let idle = !w.status.is_busy();
+                            if matches!(w.status, WeightsStatus::Missing | WeightsStatus::Failed | WeightsStatus::Unknown)
+                                && ui.add_enabled(idle, egui::Button::new("⬇ Download")).clicked()
+                            {
+                                sender.instant(UiEvent::ManageWeights { worker: worker.clone(), model: w.id.clone(), remove: false });
+                            }
+
+                            let target = (worker.clone(), w.id.clone());
+                            if self.confirm_remove.as_ref() == Some(&target) {
+                                ui.label("Delete these weights?");
+                                if ui.button("🗑 Delete").clicked() {
+                                    sender.instant(UiEvent::ManageWeights { worker: worker.clone(), model: w.id.clone(), remove: true });
+                                    self.confirm_remove = None;
+                                }
+                                if ui.button("Cancel").clicked() {
+                                    self.confirm_remove = None;
+                                }
+                            } else if matches!(w.status, WeightsStatus::Ready | WeightsStatus::Failed)
+                                && ui.add_enabled(idle, egui::Button::new("🗑 Remove"))
+                                    .on_hover_text("Free the disk space; the weights are fetched again by the next job that needs them")
+                                    .clicked()
+                            {
+                                self.confirm_remove = Some(target);
+                            }
+                        });
+                    }
+                }
+
+                ui.add_space(4.0);
+                if ui.button("🔄 Refresh").clicked() {
+                    self.last_refresh = Some(Instant::now());
+                    sender.instant(UiEvent::RefreshModelWeights);
+                }
+            });
+        self.open = open;
+    }
+
+    fn on_app_event(&mut self, ev: &AppEvent) {
+        match ev {
+            AppEvent::ModelWeights(workers) => {
+                self.workers = workers.clone();
+                // A first start fetching gigabytes shouldn't look like a hang
+                let downloading = workers.iter()
+                    .filter_map(|(_, weights)| weights.as_ref().ok())
+                    .flatten()
+                    .any(|w| w.status == WeightsStatus::Downloading);
+                if downloading && !self.auto_opened {
+                    self.auto_opened = true;
+                    self.open = true;
+                }
+            }
+            AppEvent::Appearance(appearance) => {
+                self.colorblind = appearance.colorblind;
+            }
+            _ => {}
+        }
+    }
+}
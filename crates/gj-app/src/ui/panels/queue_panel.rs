@@ -0,0 +1,1234 @@
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+use egui::{Color32, Context, RichText, TextureHandle};
+use image::{AnimationDecoder, RgbaImage};
+use jiff::civil::Date;
+use jiff::tz::TimeZone;
+use jiff::{Timestamp, Zoned};
+use gj_core::pipeline::PipelineGraph;
+use gj_core::ply_profile::PlyProfile;
+use crate::events::{AppEvent, UiEvent};
+use crate::generator::router;
+use crate::jobs::{self, ArtifactKind, DecodeSettings, JobMetadata, JobStatus, RefineSettings, Submitter};
+use crate::settings::ConfirmSettings;
+use crate::ui::palette::{self, Tone};
+use crate::ui::{UiComponent, UiEventSender};
+
+/// Right-hand panel listing generation jobs, newest first
+pub struct QueuePanel {
+    jobs: Vec<JobMetadata>,
+    filter_text: String,
+    /// Hide jobs other people submitted to a shared history
+    mine_only: bool,
+    /// Who jobs from this app are attributed to
+    me: Option<Submitter>,
+
+    // Multi-selection for bulk actions
+    checked: BTreeSet<String>,
+    tag_text: String,
+    /// Decimate exported clouds to fit `web_size_mb`
+    optimize_for_web: bool,
+    web_size_mb: f32,
+    /// Write exported PLYs in SuperSplat's layout and axes
+    supersplat: bool,
+
+    // Job inspector
+    selected: Option<String>,
+    editing_notes: bool,
+    notes_draft: String,
+    /// Settings the selected job's latent is re-decoded with
+    decode: DecodeSettings,
+    /// Settings the selected job's output is refined with
+    refine: RefineSettings,
+
+    /// Current sizes in points, remembered across runs
+    pub width: f32,
+    pub inspector_height: f32,
+
+    /// Decoded preview GIFs by path; None if the file couldn't be read
+    previews: HashMap<PathBuf, Option<GifPreview>>,
+    colorblind: bool,
+
+    /// Job whose card had keyboard focus at the end of the last frame
+    focused: Option<String>,
+    /// Card to give keyboard focus to when it's next drawn
+    focus_request: Option<String>,
+    queue_paused: bool,
+    /// Latest progress of the running job, by id
+    progress: Option<(String, f32)>,
+    /// Jobs on each worker when routing between several; None if unreachable
+    worker_loads: Vec<(String, Option<usize>)>,
+    live: Option<LivePreview>,
+
+    /// Groups folded away by the user
+    collapsed: HashSet<JobGroup>,
+
+    confirm: ConfirmSettings,
+    /// Delete waiting on the confirmation dialog
+    pending_delete: Option<PendingDelete>,
+    /// Shown in its own window rather than docked to the right
+    pub detached: bool,
+}
+
+/// Sections of the job list, in display order
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum JobGroup {
+    /// Queued or running, whatever the day
+    Active,
+    Today,
+    Yesterday,
+    Older,
+}
+
+impl JobGroup {
+    const ALL: [Self; 4] = [Self::Active, Self::Today, Self::Yesterday, Self::Older];
+
+    fn label(self) -> &'static str {
+        match self {
+            Self::Active => "Active",
+            Self::Today => "Today",
+            Self::Yesterday => "Yesterday",
+            Self::Older => "Older",
+        }
+    }
+
+    /// Finished jobs go by the local day they were created on
+    fn of(job: &JobMetadata, today: Date) -> Self {
+        if !job.status.is_finished() {
+            return Self::Active;
+        }
+        let created = Timestamp::from_second(job.created_at as i64)
+            .map(|t| t.to_zoned(TimeZone::system()).date())
+            .unwrap_or(Date::MIN);
+        Self::by_day(created, today)
+    }
+
+    fn by_day(created: Date, today: Date) -> Self {
+        if created >= today {
+            Self::Today
+        } else if today.yesterday().is_ok_and(|yesterday| created == yesterday) {
+            Self::Yesterday
+        } else {
+            Self::Older
+        }
+    }
+}
+
+/// What a delete was asked for by, each with its own "don't ask again"
+#[derive(Clone, Copy, PartialEq)]
+enum DeleteKind {
+    Jobs,
+    ClearCompleted,
+}
+
+struct PendingDelete {
+    kind: DeleteKind,
+    ids: Vec<String>,
+    /// Size of the jobs' output files
+    bytes: u64,
+    delete_files: bool,
+    dont_ask: bool,
+}
+
+/// Seconds each intermediate render is shown for
+const LIVE_FRAME_SECS: f64 = 0.25;
+/// Extra frames the latest render stays up for before the loop restarts
+const LIVE_HOLD_FRAMES: usize = 4;
+
+/// Intermediate renders of the running job so far, played as a loop that
+/// ends on the latest one
+struct LivePreview {
+    job_id: String,
+    /// Every render received, kept so frames can be uploaded again to
+    /// another egui context
+    images: Vec<Arc<RgbaImage>>,
+    /// Textures of the first `frames.len()` images
+    frames: Vec<TextureHandle>,
+}
+
+impl LivePreview {
+    fn new(job_id: String) -> Self {
+        Self { job_id, images: Vec::new(), frames: Vec::new() }
+    }
+
+    fn upload(&mut self, ctx: &Context) {
+        for image in &self.images[self.frames.len()..] {
+            let size = [image.width() as usize, image.height() as usize];
+            let color = egui::ColorImage::from_rgba_unmultiplied(size, image.as_raw());
+            let name = format!("live_preview#{}#{}", self.job_id, self.frames.len());
+            self.frames.push(ctx.load_texture(name, color, egui::TextureOptions::LINEAR));
+        }
+    }
+
+    /// Frame showing `time` seconds into the loop
+    fn frame_at(&self, time: f64) -> Option<&TextureHandle> {
+        let count = self.frames.len();
+        let step = (time / LIVE_FRAME_SECS) as usize % (count + LIVE_HOLD_FRAMES);
+        self.frames.get(step.min(count.checked_sub(1)?))
+    }
+}
+
+/// Animated preview frames, each with the time it ends at in seconds
+struct GifPreview {
+    frames: Vec<(TextureHandle, f64)>,
+}
+
+impl GifPreview {
+    fn load(ctx: &Context, path: &Path) -> anyhow::Result<Self> {
+        let file = std::io::BufReader::new(std::fs::File::open(path)?);
+        let decoder = image::codecs::gif::GifDecoder::new(file)?;
+
+        let mut frames = Vec::new();
+        let mut end = 0.0;
+        for (i, frame) in decoder.into_frames().collect_frames()?.into_iter().enumerate() {
+            let (numer, denom) = frame.delay().numer_denom_ms();
+            end += numer as f64 / denom.max(1) as f64 / 1000.0;
+
+            let buffer = frame.into_buffer();
+            let size = [buffer.width() as usize, buffer.height() as usize];
+            let image = egui::ColorImage::from_rgba_unmultiplied(size, buffer.as_raw());
+            let texture = ctx.load_texture(format!("{}#{}", path.display(), i), image, egui::TextureOptions::LINEAR);
+            frames.push((texture, end));
+        }
+
+        if frames.is_empty() {
+            anyhow::bail!("GIF has no frames");
+        }
+        Ok(Self { frames })
+    }
+
+    /// Frame showing `time` seconds into the loop
+    fn frame_at(&self, time: f64) -> &TextureHandle {
+        let duration = self.frames.last().map_or(0.0, |(_, end)| *end);
+        let t = if duration > 0.0 { time % duration } else { 0.0 };
+        let (texture, _) = self.frames.iter().find(|(_, end)| t < *end).unwrap_or(&self.frames[0]);
+        texture
+    }
+}
+
+impl Default for QueuePanel {
+    fn default() -> Self {
+        Self {
+            jobs: Vec::new(),
+            filter_text: String::new(),
+            mine_only: false,
+            me: None,
+            checked: BTreeSet::new(),
+            tag_text: String::new(),
+            optimize_for_web: false,
+            web_size_mb: 10.0,
+            supersplat: false,
+            selected: None,
+            editing_notes: false,
+            notes_draft: String::new(),
+            decode: DecodeSettings::default(),
+            refine: RefineSettings::default(),
+            width: 280.0,
+            inspector_height: 240.0,
+            previews: HashMap::new(),
+            colorblind: false,
+            focused: None,
+            focus_request: None,
+            queue_paused: false,
+            progress: None,
+            live: None,
+            worker_loads: Vec::new(),
+            collapsed: HashSet::from([JobGroup::Older]),
+            confirm: ConfirmSettings::default(),
+            pending_delete: None,
+            detached: false,
+        }
+    }
+}
+
+/// Stable widget id of a job's card, so focus survives jobs being added
+fn card_id(job_id: &str) -> egui::Id {
+    egui::Id::new(("job_card", job_id))
+}
+
+/// How a job card is drawn, besides the job itself
+struct CardState<'a> {
+    selected: bool,
+    colorblind: bool,
+    /// Estimated seconds left, for running jobs
+    remaining: Option<f64>,
+    /// Latest intermediate render, for running jobs
+    live: Option<&'a TextureHandle>,
+    /// Which of how many versions of its object the job made, when there
+    /// is more than one
+    version: Option<(usize, usize)>,
+}
+
+impl QueuePanel {
+    /// Draw a job card, returning its response. The card takes keyboard
+    /// focus like a button, with a ring drawn around it while focused.
+    fn job_card(
+        ui: &mut egui::Ui,
+        job: &JobMetadata,
+        preview: Option<&GifPreview>,
+        state: CardState<'_>,
+        checked: &mut bool,
+        sender: &mut UiEventSender,
+    ) -> egui::Response {
+        let CardState { selected, colorblind, remaining, live, version } = state;
+        let mut frame = egui::Frame::group(ui.style());
+        if selected {
+            frame = frame.stroke(egui::Stroke::new(1.5, ui.visuals().selection.stroke.color));
+        }
+
+        let shown = frame.show(ui, |ui| {
+            ui.set_width(ui.available_width());
+
+            ui.horizontal(|ui| {
+                ui.checkbox(checked, "");
+                let status = RichText::new(format!("{} {}", palette::status_icon(job.status), job.status.label()));
+                ui.label(status.color(palette::status_tone(job.status).color(colorblind)).strong());
+                ui.label(RichText::new(format!("{} {}", job.inputs.model().icon(), job.inputs.model().name())).small());
+                if let Some((number, count)) = version {
+                    ui.label(RichText::new(format!("🔗 v{}/{}", number, count)).small())
+                        .on_hover_text(format!("{}, version {} of {}", version_kind(job), number, count));
+                }
+                if let Some(worker) = job.remote_id.as_deref().and_then(router::worker_of) {
+                    ui.label(RichText::new(format!("on {}", worker)).small().weak());
+                }
+                if let Some(by) = &job.submitter {
+                    ui.label(RichText::new(format!("by {}", by.user)).small().weak())
+                        .on_hover_text(format!("Submitted from {}", by.machine));
+                }
+                if let Some(remaining) = remaining {
+                    ui.label(RichText::new(format_remaining(remaining)).small().weak());
+                    // Count down between progress updates
+                    ui.ctx().request_repaint_after(std::time::Duration::from_secs(1));
+                }
+            });
+
+            ui.label(&job.inputs.prompt);
+            if let Some(message) = &job.message {
+                ui.label(RichText::new(message).small().color(Tone::Warning.color(colorblind)));
+            }
+
+            if let Some(live) = live {
+                ui.add(egui::Image::new(live).max_width(128.0));
+                ui.ctx().request_repaint_after(std::time::Duration::from_secs_f64(LIVE_FRAME_SECS));
+            } else if let Some(preview) = preview {
+                // The turntable plays while hovered
+                let hovered = ui.rect_contains_pointer(ui.max_rect());
+                let time = if hovered { ui.input(|i| i.time) } else { 0.0 };
+                ui.add(egui::Image::new(preview.frame_at(time)).max_width(128.0));
+                if hovered {
+                    ui.ctx().request_repaint();
+                }
+            }
+
+            let seed = job.inputs.seed.map_or("random".to_string(), |s| s.to_string());
+            let preset = job.inputs.preset.map_or(String::new(), |p| format!("{} · ", p.label()));
+            // Pipelines of one stage are plain generations
+            let pipeline = job.inputs.pipeline.as_deref()
+                .and_then(PipelineGraph::find)
+                .filter(|p| p.stages.len() > 1);
+            let params = match (&job.inputs.redecode, &job.inputs.refine) {
+                (Some(redecode), _) => format!(
+                    "🔁 re-decode · mesh {} · splats ×{:.2}",
+                    redecode.settings.mesh_resolution, redecode.settings.splat_scale
+                ),
+                (None, Some(refine)) => format!(
+                    "✨ refine · {} iterations · guidance {:.0} · seed {}",
+                    refine.settings.iterations, refine.settings.guidance_scale, seed
+                ),
+                (None, None) if job.inputs.params.is_empty() => format!(
+                    "{}guidance {:.1} · {} steps · seed {}",
+                    preset, job.inputs.guidance_scale, job.inputs.num_inference_steps, seed
+                ),
+                (None, None) => {
+                    let params: Vec<String> = job.inputs.params.iter()
+                        .map(|(name, value)| format!("{} {}", name.replace('_', " "), value))
+                        .collect();
+                    format!("{}{} · seed {}", preset, params.join(" · "), seed)
+                }
+            };
+            ui.label(RichText::new(params).small().weak());
+            if let Some(pipeline) = pipeline {
+                let mesh = if job.inputs.export_mesh { " · mesh when done" } else { "" };
+                ui.label(RichText::new(format!("🔀 {}{}", pipeline.name, mesh)).small().weak())
+                    .on_hover_text(pipeline.summary());
+            }
+
+            if !job.tags.is_empty() {
+                ui.horizontal_wrapped(|ui| {
+                    for tag in &job.tags {
+                        ui.label(RichText::new(format!("#{}", tag)).small().color(Color32::LIGHT_BLUE));
+                    }
+                });
+            }
+
+            if job.status == JobStatus::Scheduled
+                && let Some(run_at) = job.run_at
+            {
+                ui.horizontal(|ui| {
+                    let when = Timestamp::from_second(run_at as i64)
+                        .map(|t| t.to_zoned(TimeZone::system()).strftime("%a %H:%M").to_string())
+                        .unwrap_or_default();
+                    ui.label(RichText::new(format!("🕑 Runs {}", when)).small());
+                    if ui.small_button("▶ Run now").clicked() {
+                        sender.instant(UiEvent::RequeueJob(job.id.clone()));
+                    }
+                });
+            }
+
+            if job.status == JobStatus::Waiting {
+                ui.label(RichText::new(format!("⛓ Runs after {}", job.depends_on.join(", "))).small())
+                    .on_hover_text("Sent to the worker with their outputs once they're complete");
+            }
+
+            if job.stuck {
+                ui.horizontal(|ui| {
+                    ui.label(RichText::new(format!("{} Stuck", Tone::Warning.icon())).strong().color(Tone::Warning.color(colorblind)))
+                        .on_hover_text("The service has no record of this job, it will never run");
+                    if ui.small_button("↻ Requeue").clicked() {
+                        sender.instant(UiEvent::RequeueJob(job.id.clone()));
+                    }
+                });
+            }
+
+            if let Some(err) = &job.error {
+                let error_color = Tone::Error.color(colorblind);
+                ui.label(RichText::new(format!("{} {}", Tone::Error.icon(), err.title())).strong().color(error_color));
+                ui.label(RichText::new(err.message()).small().color(error_color));
+                if let Some(hint) = err.remediation() {
+                    ui.label(RichText::new(format!("💡 {}", hint)).small());
+                }
+            }
+
+            ui.horizontal(|ui| {
+                if ui.small_button("⧉ Duplicate & edit").clicked() {
+                    sender.instant(UiEvent::DuplicateJob(job.id.clone()));
+                }
+                if job.outputs.is_some() && ui.small_button("📂 Open").clicked() {
+                    sender.instant(UiEvent::OpenJobScene(job.id.clone()));
+                }
+                if job.status.is_finished()
+                    && ui.small_button("↻ Re-run").on_hover_text("Run the job again, keeping the files of earlier runs").clicked()
+                {
+                    sender.instant(UiEvent::RequeueJob(job.id.clone()));
+                }
+                if job.outputs.is_some()
+                    && ui.small_button("🎞 GIF").on_hover_text("Render a turntable preview GIF").clicked()
+                {
+                    sender.instant(UiEvent::ExportJobGif(job.id.clone()));
+                }
+                if job.outputs.is_some()
+                    && ui.small_button("🔺 Mesh").on_hover_text("Bake a textured GLB mesh for engines that don't render splats").clicked()
+                {
+                    sender.instant(UiEvent::ExportJobMesh(job.id.clone()));
+                }
+                // A job being retried is the worker's active one too
+                let active = job.status == JobStatus::Running || job.message.is_some();
+                if active && ui.small_button("⏹ Cancel").clicked() {
+                    sender.instant(UiEvent::CancelJob);
+                }
+                if !job.notes.is_empty() {
+                    ui.label(RichText::new("📝").small()).on_hover_text(&job.notes);
+                }
+            });
+        });
+
+        let response = ui.interact(shown.response.rect, card_id(&job.id), egui::Sense::click());
+        response.widget_info(|| {
+            let label = format!("{} job: {}", job.status.label(), job.inputs.prompt);
+            egui::WidgetInfo::selected(egui::WidgetType::SelectableLabel, true, selected, label)
+        });
+        if response.has_focus() {
+            let stroke = egui::Stroke::new(2.0, ui.visuals().selection.bg_fill);
+            ui.painter().rect_stroke(response.rect.expand(2.0), 4.0, stroke, egui::StrokeKind::Outside);
+        }
+        response
+    }
+
+    /// One line per configured worker with how busy it is, when jobs are
+    /// routed between several
+    fn worker_loads(&self, ui: &mut egui::Ui) {
+        for (name, load) in &self.worker_loads {
+            let ours = self.jobs.iter()
+                .filter(|j| j.status == JobStatus::Running)
+                .filter(|j| j.remote_id.as_deref().and_then(router::worker_of) == Some(name.as_str()))
+                .count();
+            let (tone, text) = match load {
+                Some(0) => (Tone::Success, format!("{} · idle", name)),
+                Some(load) => (Tone::Info, format!("{} · {} jobs, {} ours", name, load, ours)),
+                None => (Tone::Error, format!("{} · unreachable", name)),
+            };
+            ui.label(RichText::new(format!("{} {}", tone.icon(), text)).small().color(tone.color(self.colorblind)));
+        }
+    }
+
+    /// Estimated seconds left on a running job, from the model's past runs
+    /// and the progress so far
+    fn remaining(&self, job: &JobMetadata) -> Option<f64> {
+        if job.status != JobStatus::Running {
+            return None;
+        }
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs();
+        let elapsed = now.saturating_sub(job.started_at?) as f64;
+        let progress = self.progress.as_ref().filter(|(id, _)| *id == job.id).map(|(_, p)| *p);
+        jobs::estimate_remaining(elapsed, progress, jobs::typical_duration(&self.jobs, &job.inputs.model))
+    }
+
+    /// Whether `job` passes the filter text and the "mine only" toggle
+    fn shown(&self, job: &JobMetadata) -> bool {
+        job.matches_filter(&self.filter_text)
+            && (!self.mine_only || self.me.as_ref().is_none_or(|me| job.is_from(me)))
+    }
+
+    /// Jobs matching the filter by group, newest first within each, as
+    /// indices into `jobs`. Empty groups are left out.
+    fn grouped(&self) -> Vec<(JobGroup, Vec<usize>)> {
+        let today = Zoned::now().date();
+        let mut groups: Vec<(JobGroup, Vec<usize>)> = JobGroup::ALL.iter().map(|g| (*g, Vec::new())).collect();
+        for (i, job) in self.jobs.iter().enumerate().rev().filter(|(_, j)| self.shown(j)) {
+            let group = JobGroup::of(job, today);
+            if let Some((_, members)) = groups.iter_mut().find(|(g, _)| *g == group) {
+                members.push(i);
+            }
+        }
+        groups.retain(|(_, members)| !members.is_empty());
+        groups
+    }
+
+    /// Checked jobs the filter shows, the ones bulk actions apply to.
+    /// Checks on jobs the filter hides are kept for when it's cleared.
+    fn checked_shown(&self) -> Vec<String> {
+        self.jobs
+            .iter()
+            .filter(|j| self.checked.contains(&j.id) && self.shown(j))
+            .map(|j| j.id.clone())
+            .collect()
+    }
+
+    /// Ids of the cards shown, top to bottom
+    fn visible_ids(&self) -> Vec<String> {
+        self.grouped()
+            .into_iter()
+            .filter(|(group, _)| !self.collapsed.contains(group))
+            .flat_map(|(_, members)| members)
+            .map(|i| self.jobs[i].id.clone())
+            .collect()
+    }
+
+    /// Job whose card has keyboard focus, if any
+    pub fn focused_job(&self) -> Option<&str> {
+        self.focused.as_deref()
+    }
+
+    /// Delete the focused job and move focus to the card that takes its place
+    pub fn delete_focused(&mut self, sender: &mut UiEventSender) {
+        let Some(id) = self.focused.take() else {
+            return;
+        };
+        let visible = self.visible_ids();
+        if let Some(i) = visible.iter().position(|v| *v == id) {
+            self.focus_request = visible.get(i + 1).or(i.checked_sub(1).and_then(|i| visible.get(i))).cloned();
+        }
+        self.request_delete(DeleteKind::Jobs, vec![id], sender);
+    }
+
+    /// Delete right away, or ask first if that's still wanted for `kind`.
+    /// Output files are only ever deleted from the dialog.
+    fn request_delete(&mut self, kind: DeleteKind, ids: Vec<String>, sender: &mut UiEventSender) {
+        let ask = match kind {
+            DeleteKind::Jobs => self.confirm.delete_jobs,
+            DeleteKind::ClearCompleted => self.confirm.clear_completed,
+        };
+        if !ask {
+            sender.instant(UiEvent::DeleteJobs { ids, delete_files: false });
+            return;
+        }
+
+        let bytes = jobs::disk_usage(self.jobs.iter().filter(|j| ids.contains(&j.id)));
+        self.pending_delete = Some(PendingDelete { kind, ids, bytes, delete_files: false, dont_ask: false });
+    }
+
+    fn confirm_dialog(&mut self, ctx: &Context, sender: &mut UiEventSender) {
+        let Some(pending) = &mut self.pending_delete else {
+            return;
+        };
+
+        let mut confirmed = false;
+        let mut cancelled = false;
+        let modal = egui::Modal::new(egui::Id::new("confirm_delete")).show(ctx, |ui| {
+            ui.set_max_width(320.0);
+            let count = pending.ids.len();
+            ui.heading(match pending.kind {
+                DeleteKind::Jobs => format!("Delete {} job(s)?", count),
+                DeleteKind::ClearCompleted => format!("Clear {} completed job(s)?", count),
+            });
+            ui.label("They are removed from the queue and can't be restored.");
+            ui.add_space(4.0);
+
+            ui.add_enabled(
+                pending.bytes > 0,
+                egui::Checkbox::new(&mut pending.delete_files, format!("Also delete output files ({})", format_bytes(pending.bytes))),
+            );
+            ui.checkbox(&mut pending.dont_ask, "Don't ask again");
+
+            ui.add_space(4.0);
+            ui.horizontal(|ui| {
+                let delete = egui::Button::new(RichText::new("🗑 Delete").color(Tone::Error.color(self.colorblind)));
+                confirmed = ui.add(delete).clicked();
+                cancelled = ui.button("Cancel").clicked();
+            });
+        });
+
+        if confirmed {
+            let pending = self.pending_delete.take().unwrap();
+            if pending.dont_ask {
+                match pending.kind {
+                    DeleteKind::Jobs => self.confirm.delete_jobs = false,
+                    DeleteKind::ClearCompleted => self.confirm.clear_completed = false,
+                }
+                sender.instant(UiEvent::SetConfirmations(self.confirm.clone()));
+            }
+            sender.instant(UiEvent::DeleteJobs { ids: pending.ids, delete_files: pending.delete_files });
+        } else if cancelled || modal.should_close() {
+            self.pending_delete = None;
+        }
+    }
+
+    /// Arrow keys, Home and End move between cards while one has focus;
+    /// Enter opens the focused job's scene
+    fn keyboard_navigation(&mut self, ctx: &Context, sender: &mut UiEventSender) {
+        let visible = self.visible_ids();
+        let Some(current) = visible.iter().position(|id| ctx.memory(|m| m.has_focus(card_id(id)))) else {
+            return;
+        };
+
+        let (up, down, home, end, enter) = ctx.input_mut(|i| (
+            i.consume_key(egui::Modifiers::NONE, egui::Key::ArrowUp),
+            i.consume_key(egui::Modifiers::NONE, egui::Key::ArrowDown),
+            i.consume_key(egui::Modifiers::NONE, egui::Key::Home),
+            i.consume_key(egui::Modifiers::NONE, egui::Key::End),
+            i.key_pressed(egui::Key::Enter),
+        ));
+
+        let target = if up {
+            current.saturating_sub(1)
+        } else if down {
+            (current + 1).min(visible.len() - 1)
+        } else if home {
+            0
+        } else if end {
+            visible.len() - 1
+        } else {
+            current
+        };
+        if up || down {
+            // Cards are in a list, not egui's spatial focus order
+            ctx.memory_mut(|m| m.move_focus(egui::FocusDirection::None));
+        }
+        if target != current {
+            self.focus_request = Some(visible[target].clone());
+        }
+
+        if enter && self.jobs.iter().any(|j| j.id == visible[current] && j.outputs.is_some()) {
+            sender.instant(UiEvent::OpenJobScene(visible[current].clone()));
+        }
+    }
+
+    /// Selection controls and the actions that apply to every checked job
+    fn bulk_actions(&mut self, ui: &mut egui::Ui, sender: &mut UiEventSender) {
+        ui.horizontal(|ui| {
+            if ui.small_button("☑ Select all").on_hover_text("Select all jobs matching the filter").clicked() {
+                let shown: Vec<_> = self.jobs.iter().filter(|j| self.shown(j)).map(|j| j.id.clone()).collect();
+                self.checked.extend(shown);
+            }
+            if ui.add_enabled(!self.checked.is_empty(), egui::Button::new("☐ None").small()).clicked() {
+                self.checked.clear();
+            }
+        });
+
+        let ids = self.checked_shown();
+        if ids.is_empty() {
+            return;
+        }
+
+        ui.horizontal(|ui| {
+            ui.label(RichText::new(format!("{} selected", ids.len())).strong());
+            let hidden = self.checked.len() - ids.len();
+            if hidden > 0 {
+                ui.label(RichText::new(format!("({} hidden by the filter, left out)", hidden)).weak());
+            }
+        });
+        ui.horizontal(|ui| {
+            if ui.button("🗑 Delete").clicked() {
+                self.request_delete(DeleteKind::Jobs, ids.clone(), sender);
+                self.checked.retain(|id| !ids.contains(id));
+            }
+            if ui.button("🔁 Retry").clicked() {
+                sender.instant(UiEvent::RetryJobs(ids.clone()));
+            }
+            if ui.button("📦 Export").clicked() {
+                sender.instant(UiEvent::ExportJobs {
+                    ids: ids.clone(),
+                    max_ply_bytes: self.optimize_for_web.then_some((self.web_size_mb * 1024.0 * 1024.0) as u64),
+                    profile: if self.supersplat { PlyProfile::SuperSplat } else { PlyProfile::Genjutsu },
+                });
+            }
+        });
+
+        ui.horizontal(|ui| {
+            ui.checkbox(&mut self.optimize_for_web, "Optimize for web")
+                .on_hover_text("Drop the least visible splats so each exported PLY fits the size limit");
+            ui.add_enabled(
+                self.optimize_for_web,
+                egui::DragValue::new(&mut self.web_size_mb).range(0.5..=500.0).speed(0.5).suffix(" MB"),
+            );
+        });
+        ui.checkbox(&mut self.supersplat, "SuperSplat compatible")
+            .on_hover_text("Write PLYs in the reference 3DGS layout and axes, so they load upright in SuperSplat and PlayCanvas");
+
+        ui.horizontal(|ui| {
+            ui.add(
+                egui::TextEdit::singleline(&mut self.tag_text)
+                    .hint_text("tag")
+                    .desired_width(120.0)
+            );
+            let tag = self.tag_text.trim().to_string();
+            if ui.add_enabled(!tag.is_empty(), egui::Button::new("🏷 Tag")).clicked() {
+                sender.instant(UiEvent::TagJobs { ids, tag });
+                self.tag_text.clear();
+            }
+        });
+    }
+
+    fn inspector(&mut self, ui: &mut egui::Ui, sender: &mut UiEventSender) {
+        let Some(job) = self.selected.as_ref().and_then(|id| self.jobs.iter().find(|j| &j.id == id)) else {
+            return;
+        };
+        // Another version picked from the chain, shown from the next frame
+        let mut open_version = None;
+
+        ui.horizontal(|ui| {
+            ui.strong("🔍 Job Inspector");
+            ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                if ui.small_button("✖").clicked() {
+                    self.selected = None;
+                    self.editing_notes = false;
+                }
+            });
+        });
+
+        egui::Grid::new("job_inspector_grid")
+            .num_columns(2)
+            .show(ui, |ui| {
+                ui.label("Id");
+                ui.label(RichText::new(&job.id).monospace());
+                ui.end_row();
+
+                if let Some(remote_id) = &job.remote_id {
+                    ui.label("Service id");
+                    ui.label(RichText::new(remote_id).monospace().small());
+                    ui.end_row();
+                }
+
+                ui.label("Created");
+                ui.label(format_age(job.created_at));
+                ui.end_row();
+            });
+
+        if let Some(outputs) = job.outputs.as_ref().filter(|o| !o.artifacts.is_empty()) {
+            ui.add_space(4.0);
+            ui.label(RichText::new(format!("📁 Files ({})", outputs.artifacts.len())).strong());
+            let current = outputs.ply_path();
+            for artifact in outputs.artifacts.iter().rev() {
+                ui.horizontal(|ui| {
+                    let (kind, path) = (artifact.kind, &artifact.path);
+                    ui.add_enabled_ui(path.is_file(), |ui| {
+                        let open = if kind.is_splats() { "Load in the viewer" } else { "Open" };
+                        if ui.small_button("📂").on_hover_text(open).clicked() {
+                            sender.instant(UiEvent::OpenArtifact { kind, path: path.clone() });
+                        }
+                        if ui.small_button("💾").on_hover_text("Export a copy").clicked() {
+                            sender.instant(UiEvent::ExportArtifact(path.clone()));
+                        }
+                    });
+                    let mut label = format!("{} {}", kind.icon(), kind.label());
+                    if kind == ArtifactKind::Splats && current != Some(path.as_path()) {
+                        label.push_str(" (earlier run)");
+                    }
+                    ui.label(RichText::new(label).small().strong());
+                    let name = path.file_name().map_or_else(|| path.display().to_string(), |n| n.to_string_lossy().into_owned());
+                    ui.label(RichText::new(name).small())
+                        .on_hover_text(format!("{}\n{}", path.display(), format_age(artifact.created_at)));
+                });
+            }
+        }
+
+        if !job.log.is_empty() {
+            ui.add_space(4.0);
+            egui::CollapsingHeader::new(format!("Service log ({} lines)", job.log.len()))
+                .id_salt(("job_log", &job.id))
+                .default_open(job.status == JobStatus::Failed)
+                .show(ui, |ui| {
+                    egui::ScrollArea::both()
+                        .max_height(160.0)
+                        .stick_to_bottom(true)
+                        .show(ui, |ui| {
+                            for line in &job.log {
+                                ui.label(RichText::new(line).monospace().small());
+                            }
+                        });
+                });
+        }
+
+        let versions = jobs::versions(&self.jobs, &job.id);
+        if versions.len() > 1 {
+            ui.add_space(4.0);
+            ui.label(RichText::new("🔗 Versions").strong());
+            for (i, version) in versions.iter().enumerate() {
+                let icon = palette::status_icon(version.status);
+                let text = format!("v{} · {} · {} {}", i + 1, version_kind(version), icon, format_age(version.created_at));
+                if ui.selectable_label(version.id == job.id, text).clicked() && version.id != job.id {
+                    open_version = Some(version.id.clone());
+                }
+            }
+        }
+
+        // Second stages of unfinished jobs wait for them
+        let pending = !job.status.is_finished();
+        let (refine_label, redecode_label) = if pending {
+            ("⛓ Refine when done", "⛓ Re-decode when done")
+        } else {
+            ("✨ Refine", "🔁 Re-decode")
+        };
+
+        if (job.status == JobStatus::Completed && job.outputs.is_some()) || pending {
+            ui.add_space(4.0);
+            egui::CollapsingHeader::new("✨ Refine (SDS)")
+                .id_salt(("job_refine", &job.id))
+                .show(ui, |ui| {
+                    ui.label(RichText::new("Optimize the output against a 2D diffusion prior for its prompt, as a new version").small().weak());
+                    ui.add(
+                        egui::Slider::new(&mut self.refine.iterations, RefineSettings::ITERATIONS)
+                            .logarithmic(true)
+                            .text("Iterations")
+                    ).on_hover_text("More iterations sharpen further but take longer");
+                    ui.add(
+                        egui::Slider::new(&mut self.refine.guidance_scale, RefineSettings::GUIDANCE_SCALES)
+                            .text("Guidance")
+                    ).on_hover_text("How strongly the prior pulls towards the prompt; SDS works best around 100");
+                    ui.horizontal(|ui| {
+                        if ui.button(refine_label).clicked() {
+                            sender.instant(UiEvent::RefineJob { id: job.id.clone(), settings: self.refine });
+                        }
+                        if ui.small_button("Reset").clicked() {
+                            self.refine = RefineSettings::default();
+                        }
+                    });
+                });
+        }
+
+        let keeps_latent = job.inputs.refine.is_none() && job.inputs.redecode.is_none();
+        if job.outputs.as_ref().is_some_and(|o| o.latent.is_some()) || (pending && keeps_latent) {
+            ui.add_space(4.0);
+            egui::CollapsingHeader::new("🔁 Re-decode")
+                .id_salt(("job_redecode", &job.id))
+                .show(ui, |ui| {
+                    ui.label(RichText::new("Decode the kept latent again, without re-running diffusion").small().weak());
+                    ui.add(
+                        egui::Slider::new(&mut self.decode.mesh_resolution, DecodeSettings::MESH_RESOLUTIONS)
+                            .step_by(16.0)
+                            .text("Mesh resolution")
+                    ).on_hover_text("Cells along each side of the grid the mesh is extracted on; finer meshes give more, smaller splats");
+                    ui.add(
+                        egui::Slider::new(&mut self.decode.splat_scale, 0.25..=3.0)
+                            .text("Splat scale")
+                    ).on_hover_text("Multiplier on the splat sizes; larger closes gaps, smaller sharpens");
+                    ui.horizontal(|ui| {
+                        if ui.button(redecode_label).clicked() {
+                            sender.instant(UiEvent::RedecodeJob { id: job.id.clone(), settings: self.decode });
+                        }
+                        if ui.small_button("Reset").clicked() {
+                            self.decode = DecodeSettings::default();
+                        }
+                    });
+                });
+        }
+
+        ui.add_space(4.0);
+        ui.label(RichText::new("Notes").strong());
+
+        if self.editing_notes {
+            ui.add(
+                egui::TextEdit::multiline(&mut self.notes_draft)
+                    .desired_width(f32::INFINITY)
+                    .desired_rows(4)
+                    .hint_text("# heading, - bullet, **bold**, `code`")
+            );
+
+            ui.horizontal(|ui| {
+                if ui.button("💾 Save").clicked() {
+                    sender.instant(UiEvent::UpdateJobNotes {
+                        id: job.id.clone(),
+                        notes: self.notes_draft.clone(),
+                    });
+                    self.editing_notes = false;
+                }
+                if ui.button("Cancel").clicked() {
+                    self.editing_notes = false;
+                }
+            });
+        } else {
+            if job.notes.is_empty() {
+                ui.label(RichText::new("No notes").weak());
+            } else {
+                notes_view(ui, &job.notes);
+            }
+
+            if ui.small_button("✏ Edit notes").clicked() {
+                self.notes_draft = job.notes.clone();
+                self.editing_notes = true;
+            }
+        }
+
+        if let Some(id) = open_version {
+            self.selected = Some(id.clone());
+            self.focus_request = Some(id);
+            self.editing_notes = false;
+        }
+    }
+}
+
+/// How a job made its version of an object
+fn version_kind(job: &JobMetadata) -> &'static str {
+    match (&job.inputs.redecode, &job.inputs.refine) {
+        (Some(_), _) => "re-decoded",
+        (None, Some(_)) => "refined",
+        (None, None) => "generated",
+    }
+}
+
+/// Render notes with a small markdown subset: `#` headings, `-`/`*` bullets,
+/// `**bold**` and `` `code` ``
+fn notes_view(ui: &mut egui::Ui, notes: &str) {
+    for line in notes.lines() {
+        let line = line.trim_end();
+
+        if let Some(heading) = line.strip_prefix("# ") {
+            ui.label(RichText::new(heading).strong().size(15.0));
+            continue;
+        }
+
+        let (prefix, text) = match line.strip_prefix("- ").or_else(|| line.strip_prefix("* ")) {
+            Some(rest) => ("• ", rest),
+            None => ("", line),
+        };
+
+        ui.horizontal_wrapped(|ui| {
+            ui.spacing_mut().item_spacing.x = 0.0;
+            if !prefix.is_empty() {
+                ui.label(prefix);
+            }
+
+            for (i, part) in text.split("**").enumerate() {
+                let bold = i % 2 == 1;
+                for (j, chunk) in part.split('`').enumerate() {
+                    if chunk.is_empty() {
+                        continue;
+                    }
+                    let mut rich = RichText::new(chunk);
+                    if j % 2 == 1 {
+                        rich = rich.code();
+                    }
+                    if bold {
+                        rich = rich.strong();
+                    }
+                    ui.label(rich);
+                }
+            }
+        });
+    }
+}
+
+fn format_bytes(bytes: u64) -> String {
+    format!("{:.1} MiB", bytes as f64 / (1024.0 * 1024.0))
+}
+
+/// "≈ 40s remaining"-style estimate
+fn format_remaining(secs: f64) -> String {
+    let secs = secs.round() as u64;
+    match secs {
+        0 => "finishing…".to_string(),
+        1..60 => format!("≈ {}s remaining", secs),
+        _ => format!("≈ {}m {:02}s remaining", secs / 60, secs % 60),
+    }
+}
+
+/// "12m ago"-style age of a unix timestamp
+fn format_age(created_at: u64) -> String {
+    let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs();
+    let secs = now.saturating_sub(created_at);
+
+    match secs {
+        0..60 => "just now".to_string(),
+        60..3600 => format!("{}m ago", secs / 60),
+        3600..86400 => format!("{}h ago", secs / 3600),
+        _ => format!("{}d ago", secs / 86400),
+    }
+}
+
+impl QueuePanel {
+    /// Draw the queue filling a window of its own, see [`crate::queue_window`]
+    pub fn show_window(&mut self, ctx: &Context, sender: &mut UiEventSender) {
+        egui::CentralPanel::default().show(ctx, |ui| self.contents(ctx, ui, sender));
+        self.confirm_dialog(ctx, sender);
+    }
+
+    /// Drop textures made for the egui context the panel is leaving; they
+    /// mean nothing to another one
+    pub fn forget_textures(&mut self) {
+        self.previews.clear();
+        if let Some(live) = &mut self.live {
+            live.frames.clear();
+        }
+    }
+
+    fn contents(&mut self, ctx: &Context, ui: &mut egui::Ui, sender: &mut UiEventSender) {
+        ui.horizontal(|ui| {
+            ui.heading(format!("📋 Jobs ({})", self.jobs.len()));
+            let (label, hint) = if self.queue_paused {
+                ("▶ Resume", "Send held jobs to the worker")
+            } else {
+                ("⏸ Pause", "Hold new jobs back from the worker")
+            };
+            if ui.small_button(label).on_hover_text(hint).clicked() {
+                sender.instant(UiEvent::SetQueuePaused(!self.queue_paused));
+            }
+
+            let completed: Vec<String> = self.jobs.iter()
+                .filter(|j| j.status == JobStatus::Completed)
+                .map(|j| j.id.clone())
+                .collect();
+            if ui.add_enabled(!completed.is_empty(), egui::Button::new("🧹 Clear completed").small()).clicked() {
+                self.request_delete(DeleteKind::ClearCompleted, completed, sender);
+            }
+
+            let (label, hint) = if self.detached {
+                ("Dock", "Put the queue back in the main window")
+            } else {
+                ("⧉ Pop out", "Move the queue into a window of its own")
+            };
+            if ui.small_button(label).on_hover_text(hint).clicked() {
+                sender.instant(UiEvent::DetachQueue(!self.detached));
+            }
+        });
+        self.worker_loads(ui);
+        ui.separator();
+
+        if self.jobs.is_empty() {
+            ui.label(RichText::new("No jobs yet").weak());
+            return;
+        }
+
+        ui.horizontal(|ui| {
+            ui.checkbox(&mut self.mine_only, "Mine only")
+                .on_hover_text("Hide jobs submitted by others sharing this history");
+            ui.add(
+                egui::TextEdit::singleline(&mut self.filter_text)
+                    .hint_text("Filter by prompt or tag...")
+                    .desired_width(f32::INFINITY)
+            );
+        });
+        self.bulk_actions(ui, sender);
+        ui.separator();
+
+        if self.selected.is_some() {
+            let inspector = egui::TopBottomPanel::bottom("job_inspector")
+                .resizable(true)
+                .default_height(self.inspector_height)
+                .show_inside(ui, |ui| self.inspector(ui, sender));
+            self.inspector_height = inspector.response.rect.height();
+        }
+
+        for path in self.jobs.iter().filter_map(|j| j.outputs.as_ref()?.preview_gif()) {
+            if !self.previews.contains_key(path) {
+                let preview = GifPreview::load(ctx, path)
+                    .inspect_err(|e| log::warn!("Failed to load preview {}: {}", path.display(), e))
+                    .ok();
+                self.previews.insert(path.to_path_buf(), preview);
+            }
+        }
+
+        if let Some(live) = &mut self.live {
+            live.upload(ctx);
+        }
+        let time = ctx.input(|i| i.time);
+
+        self.keyboard_navigation(ctx, sender);
+
+        let groups = self.grouped();
+        let mut clicked = None;
+        let mut focused = None;
+        egui::ScrollArea::vertical()
+            .auto_shrink([false, false])
+            .show(ui, |ui| {
+                for (group, members) in groups {
+                    let open = !self.collapsed.contains(&group);
+                    let header = egui::CollapsingHeader::new(format!("{} ({})", group.label(), members.len()))
+                        .id_salt(("job_group", group.label()))
+                        .open(Some(open))
+                        .show(ui, |ui| {
+                            for &i in &members {
+                                let job = &self.jobs[i];
+                                let selected = self.selected.as_ref() == Some(&job.id);
+                                let mut checked = self.checked.contains(&job.id);
+
+                                let preview = job.outputs.as_ref()
+                                    .and_then(|o| o.preview_gif())
+                                    .and_then(|path| self.previews.get(path)?.as_ref());
+
+                                let live = self.live.as_ref()
+                                    .filter(|live| live.job_id == job.id && job.status == JobStatus::Running)
+                                    .and_then(|live| live.frame_at(time));
+                                let versions = jobs::versions(&self.jobs, &job.id);
+                                let version = versions.iter()
+                                    .position(|v| v.id == job.id)
+                                    .filter(|_| versions.len() > 1)
+                                    .map(|i| (i + 1, versions.len()));
+                                let state = CardState {
+                                    selected,
+                                    colorblind: self.colorblind,
+                                    remaining: self.remaining(job),
+                                    live,
+                                    version,
+                                };
+                                let card = Self::job_card(ui, job, preview, state, &mut checked, sender);
+                                if card.clicked() {
+                                    clicked = Some(job.id.clone());
+                                }
+                                if self.focus_request.as_ref() == Some(&job.id) {
+                                    card.request_focus();
+                                    card.scroll_to_me(None);
+                                    self.focus_request = None;
+                                }
+                                if card.has_focus() {
+                                    focused = Some(job.id.clone());
+                                }
+
+                                if checked {
+                                    self.checked.insert(job.id.clone());
+                                } else {
+                                    self.checked.remove(&job.id);
+                                }
+                            }
+                        });
+                    if header.header_response.clicked() {
+                        if open {
+                            self.collapsed.insert(group);
+                        } else {
+                            self.collapsed.remove(&group);
+                        }
+                    }
+                }
+            });
+
+        self.focused = focused;
+
+        if let Some(id) = clicked
+            && self.selected.as_ref() != Some(&id)
+        {
+            self.selected = Some(id);
+            self.editing_notes = false;
+        }
+    }
+}
+
+impl UiComponent for QueuePanel {
+    fn show(&mut self, ctx: &Context, sender: &mut UiEventSender) {
+        let response = egui::SidePanel::right("queue_panel")
+            .default_width(self.width)
+            .show(ctx, |ui| self.contents(ctx, ui, sender));
+        self.width = response.response.rect.width();
+
+        self.confirm_dialog(ctx, sender);
+    }
+
+    fn on_app_event(&mut self, ev: &AppEvent) {
+        match ev {
+            AppEvent::JobsChanged(jobs) => {
+                self.jobs = jobs.clone();
+                self.checked.retain(|id| jobs.iter().any(|j| &j.id == id));
+                // Renders are only kept while their job runs
+                if self.live.as_ref().is_some_and(|live| !jobs.iter().any(|j| j.id == live.job_id && j.status == JobStatus::Running)) {
+                    self.live = None;
+                }
+            }
+            AppEvent::LocalSubmitter(me) => {
+                self.me = Some(me.clone());
+            }
+            AppEvent::Appearance(appearance) => {
+                self.colorblind = appearance.colorblind;
+            }
+            AppEvent::Confirmations(confirm) => {
+                self.confirm = confirm.clone();
+            }
+            AppEvent::QueuePaused(paused) => {
+                self.queue_paused = *paused;
+            }
+            AppEvent::WorkerLoads(loads) => {
+                self.worker_loads = loads.clone();
+            }
+            AppEvent::JobPreview { id, image } => {
+                if self.live.as_ref().is_none_or(|live| live.job_id != *id) {
+                    self.live = Some(LivePreview::new(id.clone()));
+                }
+                if let Some(live) = &mut self.live {
+                    live.images.push(image.clone());
+                }
+            }
+            AppEvent::Progress(p) => {
+                if let Some(job) = self.jobs.iter().find(|j| j.status == JobStatus::Running) {
+                    self.progress = Some((job.id.clone(), *p));
+                }
+            }
+            // A re-rendered preview replaces the file in place
+            AppEvent::ExportFinished(path) => {
+                self.previews.remove(path);
+            }
+            _ => {}
+        }
+    }
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_day_groups() {
+        let today = jiff::civil::date(2025, 3, 1);
+        assert_eq!(JobGroup::by_day(today, today), JobGroup::Today);
+        assert_eq!(JobGroup::by_day(jiff::civil::date(2025, 2, 28), today), JobGroup::Yesterday);
+        assert_eq!(JobGroup::by_day(jiff::civil::date(2025, 2, 27), today), JobGroup::Older);
+        // Clock skew shouldn't push a fresh job out of Today
+        assert_eq!(JobGroup::by_day(jiff::civil::date(2025, 3, 2), today), JobGroup::Today);
+    }
+
+    #[test]
+    fn test_bulk_actions_skip_hidden_jobs() {
+        let path = std::env::temp_dir().join(format!("gj_queue_bulk_{}.json", std::process::id()));
+        let mut db = jobs::JobDatabase::new(path.clone());
+        let cat = db.insert(jobs::JobInputs { prompt: "a cat".into(), ..Default::default() });
+        let dog = db.insert(jobs::JobInputs { prompt: "a dog".into(), ..Default::default() });
+
+        let mut panel = QueuePanel { jobs: db.jobs().to_vec(), ..Default::default() };
+        panel.checked.extend([cat.clone(), dog.clone()]);
+        assert_eq!(panel.checked_shown().len(), 2);
+
+        // The dog stays checked but out of reach until the filter is cleared
+        panel.filter_text = "cat".into();
+        assert_eq!(panel.checked_shown(), vec![cat]);
+        assert!(panel.checked.contains(&dog));
+
+        let _ = std::fs::remove_file(&path);
+    }
+}
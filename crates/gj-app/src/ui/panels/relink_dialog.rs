@@ -0,0 +1,70 @@
+use std::path::PathBuf;
+use egui::{Context, RichText};
+use crate::events::{AppEvent, UiEvent};
+use crate::ui::{UiComponent, UiEventSender};
+
+/// Asks the user where a job's output file went when it can't be found
+#[derive(Default)]
+pub struct RelinkDialog {
+    /// Job being relinked and the path it used to have
+    target: Option<(String, PathBuf)>,
+    path_text: String,
+}
+
+impl UiComponent for RelinkDialog {
+    fn show(&mut self, ctx: &Context, sender: &mut UiEventSender) {
+        let Some((id, missing)) = &self.target else {
+            return;
+        };
+
+        let mut close = false;
+        egui::Window::new("⚠ Output file missing")
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, egui::Vec2::ZERO)
+            .show(ctx, |ui| {
+                ui.label(format!("The output of {} is no longer at", id));
+                ui.label(RichText::new(missing.display().to_string()).monospace().small());
+                ui.label("and wasn't found in the output directory. Where is it now?");
+
+                ui.add_space(4.0);
+                ui.horizontal(|ui| {
+                    ui.add(egui::TextEdit::singleline(&mut self.path_text).desired_width(320.0));
+                    if ui.button("📂 Browse…").clicked() {
+                        sender.instant(UiEvent::BrowseRelink(id.clone()));
+                    }
+                });
+
+                ui.add_space(4.0);
+                ui.horizontal(|ui| {
+                    let path = PathBuf::from(self.path_text.trim());
+                    if ui.add_enabled(path.is_file(), egui::Button::new("🔗 Relink")).clicked() {
+                        sender.instant(UiEvent::RelinkJob { id: id.clone(), path });
+                        close = true;
+                    }
+                    if ui.button("Cancel").clicked() {
+                        close = true;
+                    }
+                });
+            });
+
+        if close {
+            self.target = None;
+        }
+    }
+
+    fn on_app_event(&mut self, ev: &AppEvent) {
+        match ev {
+            AppEvent::RelinkNeeded { id, missing } => {
+                self.path_text = missing.display().to_string();
+                self.target = Some((id.clone(), missing.clone()));
+            }
+            AppEvent::RelinkPicked { id, path }
+                if self.target.as_ref().is_some_and(|(target, _)| target == id) =>
+            {
+                self.path_text = path.display().to_string();
+            }
+            _ => {}
+        }
+    }
+}
\ No newline at end of file
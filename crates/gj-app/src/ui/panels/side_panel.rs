@@ -1,11 +1,38 @@
-use egui::{Context, RichText, TextEdit, Color32};
+use std::collections::{BTreeMap, HashMap};
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+use egui::{Context, RichText, TextEdit, TextureHandle, Color32};
+use serde_json::{json, Value};
 use gj_core::Model3D;
+use gj_core::array::ArrayLayout;
+use gj_core::color_grading::ColorGrade;
+use gj_core::mesh::{MeshSettings, MESH_MAX_RESOLUTION, TEXTURE_SIZES};
+use gj_core::paint::PaintMode;
+use gj_core::pipeline::{capture_views, PipelineGraph, TextToMultiviewConfig, CAPTURE_PHOTOS};
+use gj_core::ply_profile::PlyProfile;
+use gj_core::volume::Volume;
+use gj_core::voxel::VOX_MAX_SIZE;
+use gj_splat::clipping::{ClipPlane, MAX_CLIP_PLANES};
+use gj_splat::depth_of_field::{DepthOfField, MAX_COC};
+use gj_splat::renderer::ColorMode;
+use gj_splat::selection::PaintBrush;
+use crate::capture::SequenceMotion;
+use crate::error::AppError;
 use crate::events::{AppEvent, UiEvent};
-use crate::ui::UiEventSender;
+use crate::generator::{builtin_presets, ModelPresets, ParamKind, ParamSpec, WorkerGpu};
+use crate::images::InputImage;
+use crate::jobs::{self, CaptureView, JobInputs, Preset};
+use crate::settings::{AppearanceSettings, ConfirmSettings, GpuBackend, PresentMode, RenderSettings, Theme, RENDER_SCALE_RANGE};
+use crate::ui::palette::Tone;
+use crate::ui::{UiComponent, UiEventSender};
 
 pub struct SidePanel {
     // Model selection (currently only Shap-E)
     pub selected_model: Model3D,
+    /// Id of the pipeline new jobs go through, see [`PipelineGraph::builtin`]
+    pipeline: String,
+    /// Ids of the models the generation backend runs, as last reported
+    backend_models: Vec<String>,
 
     // Status
     pub last_status: Option<String>,
@@ -13,153 +40,1508 @@ pub struct SidePanel {
     // Prompt input
     pub prompt_text: String,
     pub is_generating: bool,
+    focus_prompt: bool,
+    /// Image views are drawn from by generators that take one
+    input_image: Option<PathBuf>,
+    /// File picked to add to the library, sent on the next frame
+    picked_image: Option<PathBuf>,
+    /// The input image library, as last reported
+    input_images: Vec<InputImage>,
+    /// Textures of the library's thumbnails by image id; None for ones
+    /// that couldn't be read
+    thumbnails: HashMap<String, Option<TextureHandle>>,
+    /// Photos of a real object, from the library, for the capture views generator
+    capture: Vec<CaptureView>,
+    /// COLMAP model folder picked to import, sent on the next frame
+    picked_colmap: Option<PathBuf>,
+
+    // Generation parameters, by name; see `param_specs`
+    params: BTreeMap<String, Value>,
+    /// Parameters each model takes, as the service describes them
+    model_params: BTreeMap<String, Vec<ParamSpec>>,
+    model_presets: BTreeMap<String, ModelPresets>,
+    pub seed: Option<u64>,
+    /// Local time of day to hold the next job until, as hour and minute
+    run_at: Option<(i8, i8)>,
+    /// GPU memory of the smallest generation worker in MiB, if known
+    worker_vram_mb: Option<u64>,
+    /// Worker GPU new jobs run on, the workers' current one when None
+    device: Option<u32>,
+    /// GPUs on each configured worker by name, as last reported
+    worker_gpus: Vec<(String, Result<Vec<WorkerGpu>, AppError>)>,
+    gpus_refreshed: Option<Instant>,
+    /// Why the generation service can't be used, when it speaks another
+    /// protocol version
+    incompatible: Option<String>,
+
+    // Turntable playback (degrees per second)
+    pub auto_orbit: bool,
+    pub orbit_speed: f32,
+
+    // Camera path keyframes (times in seconds)
+    pub keyframe_time: f32,
+    pub keyframe_times: Vec<f32>,
+    pub path_looping: bool,
+    pub path_playing: bool,
+
+    pub compact_splats: bool,
+    pub color_mode: ColorMode,
+    pub color_grade: ColorGrade,
+    /// Lens settings, kept while the effect is off
+    pub depth_of_field: DepthOfField,
+    pub dof_enabled: bool,
+    pub appearance: AppearanceSettings,
+    confirm: ConfirmSettings,
+    /// Number of selected splats
+    pub selected_splats: usize,
+    clip_planes: Vec<ClipPlane>,
+    volume: Option<Volume>,
+    brush_tool: BrushTool,
+    /// In physical pixels
+    brush_radius: f32,
+    /// Share of opacity a fade dab takes away at the brush center
+    brush_fade: f32,
+    brush_color: [f32; 3],
+    /// How far a tint dab moves colors toward `brush_color`
+    brush_tint: f32,
+    pub render: RenderSettings,
+    /// Custom shader picked in the browser, applied on the next frame
+    picked_shader: Option<PathBuf>,
+    shader_error: Option<String>,
+    pub active_adapter: String,
+    pub adapters: Vec<String>,
+
+    // Cells along the longest axis of a voxel export
+    pub voxel_resolution: usize,
+    mesh: MeshSettings,
+    /// Current width in points, remembered across runs
+    pub width: f32,
+    supersplat_ply: bool,
+    sequence_motion: SequenceMotion,
+    sequence_size: [u32; 2],
+    sequence_frames: usize,
+    array_layout: ArrayLayout,
 }
 
+/// How often worker GPU memory is checked while System Info is open
+const GPU_REFRESH: Duration = Duration::from_secs(5);
+
+/// Starting parameters of each array layout, in world units
+const ARRAY_LAYOUTS: [ArrayLayout; 3] = [
+    ArrayLayout::Grid { columns: 3, rows: 3, spacing: 1.5 },
+    ArrayLayout::Circle { count: 8, radius: 3.0 },
+    ArrayLayout::Scatter { count: 20, extent: 10.0, seed: 0 },
+];
+
 impl Default for SidePanel {
     fn default() -> Self {
         Self {
             selected_model: Model3D::ShapE,
+            pipeline: "splats".to_string(),
+            backend_models: Vec::new(),
             last_status: None,
             prompt_text: String::new(),
             is_generating: false,
+            focus_prompt: false,
+            input_image: None,
+            picked_image: None,
+            input_images: Vec::new(),
+            thumbnails: HashMap::new(),
+            capture: Vec::new(),
+            picked_colmap: None,
+            params: BTreeMap::new(),
+            model_params: BTreeMap::new(),
+            model_presets: BTreeMap::new(),
+            seed: None,
+            run_at: None,
+            worker_vram_mb: None,
+            device: None,
+            worker_gpus: Vec::new(),
+            gpus_refreshed: None,
+            incompatible: None,
+            auto_orbit: false,
+            orbit_speed: 20.0,
+            keyframe_time: 0.0,
+            keyframe_times: Vec::new(),
+            path_looping: false,
+            path_playing: false,
+            compact_splats: true,
+            color_mode: ColorMode::default(),
+            color_grade: ColorGrade::default(),
+            depth_of_field: DepthOfField::default(),
+            dof_enabled: false,
+            appearance: AppearanceSettings::default(),
+            confirm: ConfirmSettings::default(),
+            selected_splats: 0,
+            clip_planes: Vec::new(),
+            volume: None,
+            brush_tool: BrushTool::Off,
+            brush_radius: 40.0,
+            brush_fade: 0.3,
+            brush_color: [1.0, 1.0, 1.0],
+            brush_tint: 0.3,
+            render: RenderSettings::default(),
+            picked_shader: None,
+            shader_error: None,
+            active_adapter: String::new(),
+            adapters: Vec::new(),
+            voxel_resolution: 64,
+            mesh: MeshSettings::default(),
+            width: 340.0,
+            supersplat_ply: false,
+            sequence_motion: SequenceMotion::Turntable,
+            sequence_size: [1920, 1080],
+            sequence_frames: 120,
+            array_layout: ARRAY_LAYOUTS[0],
         }
     }
 }
 
+#[derive(Clone, Copy, PartialEq)]
+enum BrushTool {
+    Off,
+    Fade,
+    Tint,
+}
+
 impl SidePanel {
-    pub fn show(&mut self, ctx: &Context, sender: &mut UiEventSender) {
-        egui::SidePanel::left("side_panel")
-            .default_width(340.0)
-            .show(ctx, |ui| {
-                ui.heading("Genjutsu");
-                ui.separator();
+    /// Brush for the viewport from the paint settings, None while it's off
+    fn brush(&self) -> Option<PaintBrush> {
+        let mode = match self.brush_tool {
+            BrushTool::Off => return None,
+            BrushTool::Fade => PaintMode::Opacity { factor: 1.0 - self.brush_fade },
+            BrushTool::Tint => PaintMode::Tint { color: self.brush_color, strength: self.brush_tint },
+        };
+        Some(PaintBrush { mode, radius: self.brush_radius })
+    }
 
-                // === Model Info ===
-                ui.heading(RichText::new("⚡ Shap-E").size(16.0));
-                ui.add_space(5.0);
+    /// Submit the current prompt, if a generation can be started
+    pub fn submit(&mut self, sender: &mut UiEventSender) {
+        if (self.is_generating && self.run_at.is_none()) || self.prompt_text.trim().is_empty() {
+            return;
+        }
 
-                ui.label(
-                    RichText::new("OpenAI's fast text-to-3D model (~30-60 seconds)")
-                        .small()
-                        .color(Color32::LIGHT_BLUE)
-                );
+        let mut inputs = JobInputs {
+            prompt: self.prompt_text.clone(),
+            model: self.model().id().to_string(),
+            seed: self.seed,
+            device: self.device,
+            ..Default::default()
+        };
+        inputs.set_params(self.param_values());
+        inputs.preset = self.matching_preset();
+        inputs.pipeline = Some(self.pipeline.clone());
+        inputs.image = self.input_image.clone().filter(|_| self.multiview().is_some_and(|c| c.needs_image()));
+        if self.multiview().is_some_and(|c| c.needs_capture()) {
+            inputs.capture = self.capture.clone();
+        }
 
-                ui.separator();
+        // Scheduled jobs don't tie up the form
+        if let Some((hour, minute)) = self.run_at {
+            match jobs::next_local_time(&jiff::Zoned::now(), hour, minute) {
+                Some(run_at) => sender.instant(UiEvent::ScheduleJob { inputs, run_at }),
+                None => log::warn!("Can't schedule a job for {:02}:{:02}", hour, minute),
+            }
+            return;
+        }
 
-                // === Prompt Input ===
-                ui.heading(RichText::new("✨ Text Prompt").size(16.0));
-                ui.add_space(5.0);
+        sender.instant(UiEvent::Generate(inputs));
+        self.is_generating = true;
+    }
 
-                let text_edit = TextEdit::multiline(&mut self.prompt_text)
-                    .desired_width(f32::INFINITY)
-                    .desired_rows(3)
-                    .hint_text("e.g., a red sports car, a medieval sword, a coffee mug...");
+    /// Fill the form from an earlier job's inputs
+    pub fn load_inputs(&mut self, inputs: &JobInputs) {
+        self.prompt_text = inputs.prompt.clone();
+        self.selected_model = inputs.model();
+        if let Some(pipeline) = &inputs.pipeline {
+            self.pipeline = pipeline.clone();
+        }
+        self.params = inputs.param_values();
+        if inputs.image.is_some() {
+            self.input_image = inputs.image.clone();
+        }
+        if !inputs.capture.is_empty() {
+            self.capture = inputs.capture.clone();
+        }
+        self.seed = inputs.seed;
+        self.focus_prompt = true;
+    }
 
-                ui.add(text_edit);
+    /// Model making the splats: the one the pipeline names, otherwise the
+    /// selected one
+    fn model(&self) -> Model3D {
+        PipelineGraph::find(&self.pipeline)
+            .and_then(|p| Model3D::from_id(p.splat_model()?))
+            .unwrap_or(self.selected_model)
+    }
 
-                ui.add_space(8.0);
+    /// Which of the built-in pipelines new jobs go through; ones needing
+    /// models the workers don't run can't be picked
+    fn pipeline_picker(&mut self, ui: &mut egui::Ui) {
+        let pipelines = PipelineGraph::builtin();
+        let selected = pipelines.iter().find(|p| p.id == self.pipeline);
+        ui.horizontal(|ui| {
+            ui.label("Pipeline:");
+            egui::ComboBox::from_id_salt("pipeline")
+                .selected_text(selected.map_or(self.pipeline.as_str(), |p| p.name.as_str()))
+                .show_ui(ui, |ui| {
+                    for pipeline in &pipelines {
+                        let missing: Vec<&str> = pipeline.fixed_models()
+                            .filter(|m| !self.backend_models.iter().any(|b| b == m))
+                            .collect();
+                        let response = ui.add_enabled_ui(missing.is_empty(), |ui| {
+                            ui.selectable_value(&mut self.pipeline, pipeline.id.clone(), &pipeline.name)
+                        }).inner;
+                        response
+                            .on_hover_text(pipeline.summary())
+                            .on_disabled_hover_text(format!("The workers don't run {}", missing.join(", ")));
+                    }
+                });
+        });
+        if let Some(pipeline) = selected {
+            ui.label(RichText::new(pipeline.summary()).small().weak());
+        }
+    }
 
-                let generate_button = ui.add_enabled(
-                    !self.is_generating && !self.prompt_text.trim().is_empty(),
-                    egui::Button::new(
-                        RichText::new("🎨 Generate 3D Model")
-                            .size(14.0)
-                    )
-                        .min_size(egui::vec2(ui.available_width(), 30.0))
-                );
+    /// Parameters of the selected model: the service's schema, or what
+    /// services from before schemas take
+    fn param_specs(&self) -> Vec<ParamSpec> {
+        let model = self.model();
+        let id = model.id();
+        self.model_params.get(id).cloned().unwrap_or_else(|| ParamSpec::builtin(id))
+    }
 
-                if generate_button.clicked() {
-                    sender.instant(UiEvent::GenerateWithModel {
-                        prompt: self.prompt_text.clone(),
-                        model: self.selected_model,
-                    });
-                    self.is_generating = true;
-                }
+    /// `spec` as set in the form, or its default when that's unusable
+    fn param_value(&self, spec: &ParamSpec) -> Value {
+        let value = spec.sanitize(self.params.get(&spec.name));
+        if spec.name == "multiview" && value.as_str().is_some_and(|v| self.missing_input_for(v).is_some()) {
+            return spec.default.clone();
+        }
+        value
+    }
 
-                ui.add_space(5.0);
+    /// Every parameter of the selected model, as set in the form or its default
+    fn param_values(&self) -> BTreeMap<String, Value> {
+        self.param_specs()
+            .into_iter()
+            .map(|spec| {
+                let value = self.param_value(&spec);
+                (spec.name, value)
+            })
+            .collect()
+    }
 
-                ui.separator();
+    /// Views generator of the selected model, for models drawing views
+    fn multiview(&self) -> Option<TextToMultiviewConfig> {
+        let value = self.param_values().remove("multiview")?;
+        TextToMultiviewConfig::from_model(value.as_str()?)
+    }
 
-                // === Example Prompts ===
-                ui.collapsing("💡 Example Prompts", |ui| {
-                    let examples = vec![
-                        "a red sports car",
-                        "a medieval sword",
-                        "a blue crystal gem",
-                        "a wooden chair",
-                        "a futuristic robot",
-                        "a coffee mug",
-                        "a potted plant",
-                        "a castle tower",
-                        "a treasure chest",
-                        "a flying drone",
-                    ];
+    /// What multiview generator `id` needs that the form lacks, if anything
+    fn missing_input_for(&self, id: &str) -> Option<String> {
+        let config = TextToMultiviewConfig::from_model(id)?;
+        if config.needs_image() && self.input_image.is_none() {
+            return Some("Pick an input image first".to_string());
+        }
+        if config.needs_capture() && !CAPTURE_PHOTOS.contains(&self.capture.len()) {
+            return Some(format!("Build a capture set of {} to {} photos first", CAPTURE_PHOTOS.start(), CAPTURE_PHOTOS.end()));
+        }
+        None
+    }
+
+    /// The selected model's views generators, for models drawing views
+    fn multiview_options(&self) -> Vec<TextToMultiviewConfig> {
+        self.param_specs().iter()
+            .flat_map(|spec| match &spec.kind {
+                ParamKind::Choice { options } if spec.name == "multiview" => options.clone(),
+                _ => Vec::new(),
+            })
+            .filter_map(|o| TextToMultiviewConfig::from_model(&o))
+            .collect()
+    }
+
+    /// Whether one of the selected model's views generators takes an input image
+    fn takes_image(&self) -> bool {
+        self.multiview_options().iter().any(|c| c.needs_image())
+    }
+
+    /// Whether one of the selected model's views generators takes a capture set
+    fn takes_capture(&self) -> bool {
+        self.multiview_options().iter().any(|c| c.needs_capture())
+    }
+
+    /// The input image, with buttons to add one to the library or drop it,
+    /// and the library's images to pick from
+    fn input_image_row(&mut self, ui: &mut egui::Ui, sender: &mut UiEventSender) {
+        ui.horizontal(|ui| {
+            ui.label("Input image:");
+            match &self.input_image {
+                Some(path) => {
+                    let name = path.file_name().map_or_else(|| path.display().to_string(), |n| n.to_string_lossy().into_owned());
+                    let source = self.input_images.iter().find(|i| &i.path == path).map_or(path, |i| &i.source);
+                    ui.label(RichText::new(name).monospace()).on_hover_text(source.display().to_string());
+                    if ui.small_button("✖").on_hover_text("Draw views from the prompt alone").clicked() {
+                        self.input_image = None;
+                    }
+                }
+                None => {
+                    ui.label(RichText::new("none").weak());
+                }
+            }
+            if ui.small_button("➕ Add…")
+                .on_hover_text("A PNG or JPEG of the object, for generators that draw views from an image; kept in the library below")
+                .clicked()
+            {
+                sender.instant(UiEvent::BrowseInputImage);
+            }
+        });
 
-                    for example in examples {
-                        if ui.button(example).clicked() {
-                            self.prompt_text = example.to_string();
+        if self.input_images.is_empty() {
+            return;
+        }
+        let images = self.input_images.clone();
+        ui.horizontal_wrapped(|ui| {
+            for image in images.iter().rev() {
+                let selected = self.input_image.as_ref() == Some(&image.path);
+                let hover = format!(
+                    "{}\n{}×{} · {}",
+                    image.source.display(), image.width, image.height, image.preprocessing.describe()
+                );
+                let response = match self.thumbnail(ui.ctx(), image) {
+                    Some(texture) => ui.add(
+                        egui::Button::image(egui::Image::new(&texture).max_size(egui::vec2(48.0, 48.0))).selected(selected)
+                    ),
+                    None => ui.selectable_label(selected, "🖼"),
+                };
+                let response = response.on_hover_text(hover);
+                if response.clicked() {
+                    self.input_image = Some(image.path.clone());
+                }
+                response.context_menu(|ui| {
+                    if self.takes_capture() && ui.button("📷 Add to capture set").clicked() {
+                        // Continue around the object from the last photo
+                        let azimuth = self.capture.last().map_or(0.0, |v| (v.azimuth + 45.0) % 360.0);
+                        self.capture.push(CaptureView { path: image.path.clone(), azimuth, elevation: 0.0, camera: None });
+                        ui.close();
+                    }
+                    if ui.button("🗑 Remove from library").clicked() {
+                        if selected {
+                            self.input_image = None;
                         }
+                        self.capture.retain(|v| v.path != image.path);
+                        sender.instant(UiEvent::RemoveInputImage(image.id.clone()));
+                        ui.close();
                     }
                 });
+            }
+        });
+    }
 
-                ui.separator();
-
-                // === Tips ===
-                ui.collapsing("💭 Prompt Tips", |ui| {
-                    ui.label("✓ Be specific but simple");
-                    ui.label("✓ Describe one object at a time");
-                    ui.label("✓ Include colors and materials");
-                    ui.label("✗ Avoid complex scenes");
-                    ui.label("✗ Don't use abstract concepts");
+    /// The capture set's photos in order, each with where it was taken from
+    /// around the object; marks the four standing in for LGM's views
+    fn capture_set(&mut self, ui: &mut egui::Ui, sender: &mut UiEventSender) {
+        ui.horizontal(|ui| {
+            ui.label("Capture set:");
+            let count = self.capture.len();
+            let text = RichText::new(format!("{} photos", count));
+            if CAPTURE_PHOTOS.contains(&count) {
+                ui.label(text);
+            } else {
+                ui.label(text.color(Tone::Warning.color(self.appearance.colorblind)))
+                    .on_hover_text(format!("Takes {} to {} photos", CAPTURE_PHOTOS.start(), CAPTURE_PHOTOS.end()));
+            }
+            if ui.add_enabled(count > 1, egui::Button::new("Spread evenly").small())
+                .on_hover_text("Set the photos a full circle around the object at its middle, in order")
+                .clicked()
+            {
+                for (i, view) in self.capture.iter_mut().enumerate() {
+                    view.azimuth = i as f32 * 360.0 / count as f32;
+                    view.elevation = 0.0;
+                    view.camera = None;
+                }
+            }
+            if ui.small_button("📂 COLMAP…")
+                .on_hover_text("Build the set from a COLMAP sparse model exported as text, with the photos' solved cameras")
+                .clicked()
+            {
+                sender.instant(UiEvent::BrowseColmap);
+            }
+        });
+        if self.capture.is_empty() {
+            ui.label(RichText::new("Right-click images in the library to add photos of an object, e.g. dropped in the window").small().weak());
+            return;
+        }
 
-                    ui.add_space(5.0);
-                    ui.label(RichText::new("Examples:").strong());
-                    ui.label("  Good: 'a red metal toolbox'");
-                    ui.label("  Bad: 'happiness and joy'");
+        let poses: Vec<(f32, f32)> = self.capture.iter().map(|v| (v.azimuth, v.elevation)).collect();
+        let used = capture_views(&poses);
+        let colorblind = self.appearance.colorblind;
+        let images = self.input_images.clone();
+        let mut moved = None;
+        let mut removed = None;
+        let last = self.capture.len() - 1;
+        for (i, view) in self.capture.iter_mut().enumerate() {
+            ui.horizontal(|ui| {
+                let image = images.iter().find(|image| image.path == view.path);
+                let texture = image.and_then(|image| self.thumbnails.get(&image.id).cloned().flatten());
+                match texture {
+                    Some(texture) => ui.add(egui::Image::new(&texture).max_size(egui::vec2(24.0, 24.0))),
+                    None => ui.label("🖼"),
+                }.on_hover_text(image.map_or(&view.path, |image| &image.source).display().to_string());
+                let azimuth = ui.add(egui::DragValue::new(&mut view.azimuth).range(0.0..=359.0).speed(1.0).suffix("°"))
+                    .on_hover_text("Azimuth: degrees around the object, 0 facing its front and 90 its right side");
+                let elevation = ui.add(egui::DragValue::new(&mut view.elevation).range(-90.0..=90.0).speed(1.0).suffix("°"))
+                    .on_hover_text("Elevation: degrees above the object's middle");
+                // A hint set by hand replaces the solved camera
+                if azimuth.changed() || elevation.changed() {
+                    view.camera = None;
+                }
+                if let Some(camera) = &view.camera {
+                    ui.label("📐").on_hover_text(format!(
+                        "Camera from COLMAP, {:.0}° field of view; the reconstruction uses it over the hint", camera.fov_y
+                    ));
+                }
+                if let Some(slot) = used.and_then(|used| used.iter().position(|&u| u == i)) {
+                    let name = ["front", "right", "back", "left"][slot];
+                    ui.label(RichText::new(format!("● {}", name)).small().color(Tone::Success.color(colorblind)))
+                        .on_hover_text(format!("Stands in for the {} view", name));
+                }
+                ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                    if ui.small_button("✖").on_hover_text("Remove from the capture set").clicked() {
+                        removed = Some(i);
+                    }
+                    if ui.add_enabled(i < last, egui::Button::new("▼").small()).clicked() {
+                        moved = Some((i, i + 1));
+                    }
+                    if ui.add_enabled(i > 0, egui::Button::new("▲").small()).clicked() {
+                        moved = Some((i, i - 1));
+                    }
                 });
+            });
+        }
+        if let Some((a, b)) = moved {
+            self.capture.swap(a, b);
+        }
+        if let Some(i) = removed {
+            self.capture.remove(i);
+        }
+    }
 
-                ui.separator();
+    /// Texture of `image`'s thumbnail, loaded on first use
+    fn thumbnail(&mut self, ctx: &Context, image: &InputImage) -> Option<TextureHandle> {
+        self.thumbnails.entry(image.id.clone())
+            .or_insert_with(|| {
+                let thumbnail = image::open(&image.thumbnail)
+                    .inspect_err(|e| log::warn!("Can't read thumbnail {}: {}", image.thumbnail.display(), e))
+                    .ok()?
+                    .to_rgba8();
+                let size = [thumbnail.width() as usize, thumbnail.height() as usize];
+                let color = egui::ColorImage::from_rgba_unmultiplied(size, thumbnail.as_raw());
+                Some(ctx.load_texture(format!("input_image#{}", image.id), color, egui::TextureOptions::LINEAR))
+            })
+            .clone()
+    }
 
-                // === Status Display ===
-                if let Some(ref s) = self.last_status {
-                    let status_color = if s.contains("Error") || s.contains("Failed") {
-                        Color32::from_rgb(255, 100, 100)
-                    } else if s.contains("Generated") || s.contains("ready") || s.contains("success") {
-                        Color32::from_rgb(100, 255, 100)
-                    } else {
-                        Color32::LIGHT_BLUE
-                    };
+    /// Presets of the selected model: the service's, or what services from
+    /// before presets take
+    fn presets(&self) -> ModelPresets {
+        let model = self.model();
+        let id = model.id();
+        self.model_presets.get(id).cloned().unwrap_or_else(|| builtin_presets(id))
+    }
 
-                    ui.label(
-                        RichText::new(format!("Status: {}", s))
-                            .color(status_color)
-                    );
+    /// Every parameter of the selected model as `preset` sets it; ones it
+    /// leaves out at their defaults
+    fn preset_values(&self, preset: Preset) -> BTreeMap<String, Value> {
+        let presets = self.presets();
+        let values = presets.get(preset.id());
+        self.param_specs()
+            .into_iter()
+            .map(|spec| {
+                let value = spec.sanitize(values.and_then(|v| v.get(&spec.name)));
+                (spec.name, value)
+            })
+            .collect()
+    }
+
+    /// The preset the parameters are at, None when they were changed by hand
+    fn matching_preset(&self) -> Option<Preset> {
+        let presets = self.presets();
+        let values = self.param_values();
+        Preset::ALL.into_iter()
+            .find(|p| presets.contains_key(p.id()) && self.preset_values(*p) == values)
+    }
+
+    /// One button per preset of the selected model
+    fn preset_buttons(&mut self, ui: &mut egui::Ui) {
+        let presets = self.presets();
+        if presets.is_empty() {
+            return;
+        }
+        let current = self.matching_preset();
+        ui.horizontal(|ui| {
+            ui.label("Quality:");
+            for preset in Preset::ALL.into_iter().filter(|p| presets.contains_key(p.id())) {
+                if ui.selectable_label(current == Some(preset), preset.label())
+                    .on_hover_text(preset.description())
+                    .clicked()
+                {
+                    self.params = self.preset_values(preset);
+                }
+            }
+            if current.is_none() {
+                ui.label(RichText::new("Custom").weak())
+                    .on_hover_text("Parameters set by hand under ⚙ Parameters");
+            }
+        });
+    }
+
+    /// A control for each parameter of the selected model
+    fn param_form(&mut self, ui: &mut egui::Ui) {
+        for spec in self.param_specs() {
+            let mut value = self.param_value(&spec);
+            let response = match &spec.kind {
+                ParamKind::Float { min, max } => {
+                    let mut v = value.as_f64().unwrap_or(*min);
+                    let response = ui.add(egui::Slider::new(&mut v, *min..=*max).text(spec.label()));
+                    value = json!(v);
+                    Some(response)
+                }
+                ParamKind::Int { min, max } => {
+                    let mut v = value.as_i64().unwrap_or(*min);
+                    let response = ui.add(egui::Slider::new(&mut v, *min..=*max).text(spec.label()));
+                    value = json!(v);
+                    Some(response)
                 }
+                ParamKind::Bool => {
+                    let mut v = value.as_bool().unwrap_or_default();
+                    let response = ui.checkbox(&mut v, spec.label());
+                    value = json!(v);
+                    Some(response)
+                }
+                ParamKind::Choice { options } => {
+                    let mut v = value.as_str().unwrap_or_default().to_string();
+                    let response = ui.horizontal(|ui| {
+                        ui.label(spec.label());
+                        egui::ComboBox::from_id_salt(("model_param", &spec.name))
+                            .selected_text(v.clone())
+                            .show_ui(ui, |ui| {
+                                for option in options {
+                                    let missing = (spec.name == "multiview").then(|| self.missing_input_for(option)).flatten();
+                                    ui.add_enabled_ui(missing.is_none(), |ui| {
+                                        ui.selectable_value(&mut v, option.clone(), option);
+                                    }).response.on_disabled_hover_text(missing.unwrap_or_default());
+                                }
+                            });
+                    }).response;
+                    value = json!(v);
+                    Some(response)
+                }
+                ParamKind::Unknown => None,
+            };
+            if let Some(response) = response
+                && !spec.description.is_empty()
+            {
+                response.on_hover_text(&spec.description);
+            }
+            self.params.insert(spec.name, value);
+        }
+    }
+
+    /// Give keyboard focus to the prompt box on the next frame
+    pub fn request_prompt_focus(&mut self) {
+        self.focus_prompt = true;
+    }
+
+    pub fn toggle_auto_orbit(&mut self, sender: &mut UiEventSender) {
+        self.auto_orbit = !self.auto_orbit;
+        self.send_auto_orbit(sender);
+    }
 
-                ui.separator();
+    fn send_auto_orbit(&self, sender: &mut UiEventSender) {
+        sender.instant(UiEvent::SetAutoOrbit {
+            enabled: self.auto_orbit,
+            speed: self.orbit_speed,
+        });
+    }
 
-                // === Camera Controls ===
-                ui.heading("🎮 Camera Controls");
-                ui.label("• Left drag: Rotate");
-                ui.label("• Mouse wheel: Zoom");
+    /// Live memory use of each worker GPU, and which one new jobs run on
+    fn worker_gpus_ui(&mut self, ui: &mut egui::Ui, sender: &mut UiEventSender) {
+        if self.gpus_refreshed.is_none_or(|t| t.elapsed() >= GPU_REFRESH) {
+            self.gpus_refreshed = Some(Instant::now());
+            sender.instant(UiEvent::RefreshWorkerGpus);
+        }
+        ui.ctx().request_repaint_after(GPU_REFRESH);
 
-                if ui.button("🔄 Reset Camera").clicked() {
-                    sender.instant(UiEvent::ResetCamera);
+        ui.separator();
+        ui.strong("Worker GPUs");
+        let mut indices: Vec<(u32, String)> = Vec::new();
+        for (endpoint, gpus) in &self.worker_gpus {
+            let gpus = match gpus {
+                Ok(gpus) if gpus.is_empty() => {
+                    ui.label(RichText::new(format!("{}: no GPU reported", endpoint)).weak().small());
+                    continue;
+                }
+                Ok(gpus) => gpus,
+                Err(e) => {
+                    ui.label(RichText::new(format!("{}: {}", endpoint, e)).weak().small());
+                    continue;
+                }
+            };
+            for gpu in gpus {
+                let marker = if gpu.active { "▶ " } else { "" };
+                ui.label(RichText::new(format!("{}{} #{} · {} ({})", marker, endpoint, gpu.index, gpu.name, gpu.worker)).small());
+                let tone = if gpu.usage() > 0.9 { Tone::Warning } else { Tone::Info };
+                ui.add(
+                    egui::ProgressBar::new(gpu.usage())
+                        .text(format!("{:.1} / {:.1} GB", gpu.used_mb as f64 / 1024.0, gpu.total_mb as f64 / 1024.0))
+                        .fill(tone.color(self.appearance.colorblind))
+                );
+                if !indices.iter().any(|(i, _)| *i == gpu.index) {
+                    indices.push((gpu.index, gpu.name.clone()));
                 }
+            }
+        }
+        if self.worker_gpus.is_empty() {
+            ui.label(RichText::new("Checking workers…").weak().small());
+        }
+
+        // Only worth a choice where some worker has more than one
+        if indices.len() > 1 || self.device.is_some() {
+            indices.sort();
+            let selected = match self.device {
+                Some(index) => format!("GPU {}", index),
+                None => "Current GPU".to_string(),
+            };
+            ui.horizontal(|ui| {
+                ui.label("Run jobs on:");
+                egui::ComboBox::from_id_salt("job_device")
+                    .selected_text(selected)
+                    .show_ui(ui, |ui| {
+                        ui.selectable_value(&mut self.device, None, "Current GPU");
+                        for (index, name) in &indices {
+                            ui.selectable_value(&mut self.device, Some(*index), format!("GPU {} · {}", index, name));
+                        }
+                    });
+            })
+                .response
+                .on_hover_text("The next job moves the worker's models to this GPU first, which takes a moment");
+        }
+    }
+
+    /// Expected memory and time of the job as configured, with a warning
+    /// when it likely won't fit on the worker's GPU
+    fn resource_estimate(&self, ui: &mut egui::Ui) {
+        let params = self.param_values();
+        let defaults = JobInputs::default();
+        let steps = params.get("num_inference_steps").and_then(Value::as_u64).map_or(defaults.num_inference_steps, |s| s as u32);
+        let guidance = params.get("guidance_scale").and_then(Value::as_f64).map_or(defaults.guidance_scale, |g| g as f32);
+        let mut estimate = self.model().estimate(steps, guidance);
+        // Each sample draws its own views; the worker sizes its batches to the memory it has
+        let samples = params.get("num_samples").and_then(Value::as_u64).unwrap_or(1).max(1);
+        estimate.time_secs *= samples as u32;
+        ui.label(
+            RichText::new(format!(
+                "≈ {:.1} GB VRAM · ~{}s",
+                estimate.vram_mb as f64 / 1024.0,
+                estimate.time_secs
+            ))
+                .small()
+                .weak()
+        );
+
+        if let Some(available) = self.worker_vram_mb
+            && estimate.exceeds(available)
+        {
+            let warning = Tone::Warning;
+            ui.label(
+                RichText::new(format!(
+                    "{} Likely more than the worker's {:.1} GB of GPU memory",
+                    warning.icon(),
+                    available as f64 / 1024.0
+                ))
+                    .small()
+                    .color(warning.color(self.appearance.colorblind))
+            )
+                .on_hover_text("The job may fail with an out-of-memory error. Try disabling guidance (1.0) or a smaller model.");
+        }
+    }
+}
+
+impl UiComponent for SidePanel {
+    fn show(&mut self, ctx: &Context, sender: &mut UiEventSender) {
+        if let Some(path) = self.picked_shader.take() {
+            sender.instant(UiEvent::SetCustomShader(Some(path)));
+        }
+        if let Some(path) = self.picked_image.take() {
+            sender.instant(UiEvent::AddInputImage(path));
+        }
+        if let Some(dir) = self.picked_colmap.take() {
+            sender.instant(UiEvent::ImportColmap(dir));
+        }
+
+        let response = egui::SidePanel::left("side_panel")
+            .default_width(self.width)
+            .show(ctx, |ui| {
+                egui::ScrollArea::vertical().show(ui, |ui| {
+                    ui.heading("Genjutsu");
+                    ui.separator();
+
+                    // === Model Info ===
+                    let model = self.model();
+                    ui.heading(RichText::new(format!("{} {}", model.icon(), model.name())).size(16.0));
+                    ui.add_space(5.0);
+
+                    ui.label(
+                        RichText::new(model.description())
+                            .small()
+                            .color(Color32::LIGHT_BLUE)
+                    );
+                    ui.add_space(5.0);
+                    self.pipeline_picker(ui);
+
+                    ui.separator();
+
+                    // === Prompt Input ===
+                    ui.heading(RichText::new("✨ Text Prompt").size(16.0));
+                    ui.add_space(5.0);
+
+                    let text_edit = TextEdit::multiline(&mut self.prompt_text)
+                        .desired_width(f32::INFINITY)
+                        .desired_rows(3)
+                        .hint_text("e.g., a red sports car, a medieval sword, a coffee mug...");
+
+                    let prompt_response = ui.add(text_edit);
+                    if std::mem::take(&mut self.focus_prompt) {
+                        prompt_response.request_focus();
+                    }
+                    if self.takes_image() || self.takes_capture() {
+                        self.input_image_row(ui, sender);
+                    }
+                    if self.takes_capture() {
+                        self.capture_set(ui, sender);
+                    }
+
+                    ui.add_space(5.0);
+
+                    self.preset_buttons(ui);
+                    ui.collapsing("⚙ Parameters", |ui| {
+                        self.param_form(ui);
+
+                        ui.horizontal(|ui| {
+                            let mut fixed_seed = self.seed.is_some();
+                            if ui.checkbox(&mut fixed_seed, "Fixed seed").changed() {
+                                self.seed = fixed_seed.then_some(0);
+                            }
+                            if let Some(seed) = &mut self.seed {
+                                ui.add(egui::DragValue::new(seed));
+                            }
+                        });
+
+                        ui.horizontal(|ui| {
+                            let mut scheduled = self.run_at.is_some();
+                            if ui.checkbox(&mut scheduled, "Run at")
+                                .on_hover_text("Hold the job until this time, e.g. to run a batch overnight")
+                                .changed()
+                            {
+                                self.run_at = scheduled.then_some((2, 0));
+                            }
+                            if let Some((hour, minute)) = &mut self.run_at {
+                                ui.add(egui::DragValue::new(hour).range(0..=23).custom_formatter(|v, _| format!("{:02}", v)));
+                                ui.label(":");
+                                ui.add(egui::DragValue::new(minute).range(0..=59).custom_formatter(|v, _| format!("{:02}", v)));
+                            }
+                        });
+                    });
 
-                ui.separator();
+                    self.resource_estimate(ui);
 
-                // === System Info ===
-                ui.collapsing("ℹ️ System Info", |ui| {
-                    ui.label("Model: Shap-E (OpenAI)");
-                    ui.label("Renderer: Gaussian Splatting");
-                    ui.label("Backend: WebGPU (wgpu)");
-                    ui.label("Generation: ~30-60 seconds");
+                    if let Some(reason) = &self.incompatible {
+                        let error = Tone::Error;
+                        ui.label(
+                            RichText::new(format!("{} Incompatible service: {}", error.icon(), reason))
+                                .small()
+                                .color(error.color(self.appearance.colorblind))
+                        )
+                            .on_hover_text("Update the app and the generation service to the same release");
+                    }
+
+                    ui.add_space(8.0);
+
+                    let label = match self.run_at {
+                        Some((hour, minute)) => format!("🕑 Schedule for {:02}:{:02}", hour, minute),
+                        None => "🎨 Generate 3D Model".to_string(),
+                    };
+                    let generate_button = ui.add_enabled(
+                        (!self.is_generating || self.run_at.is_some()) && !self.prompt_text.trim().is_empty(),
+                        egui::Button::new(
+                            RichText::new(label)
+                                .size(14.0)
+                        )
+                            .min_size(egui::vec2(ui.available_width(), 30.0))
+                    );
+
+                    if generate_button.clicked() {
+                        self.submit(sender);
+                    }
+
+                    ui.add_space(5.0);
+
+                    ui.separator();
+
+                    // === Example Prompts ===
+                    ui.collapsing("💡 Example Prompts", |ui| {
+                        let examples = vec![
+                            "a red sports car",
+                            "a medieval sword",
+                            "a blue crystal gem",
+                            "a wooden chair",
+                            "a futuristic robot",
+                            "a coffee mug",
+                            "a potted plant",
+                            "a castle tower",
+                            "a treasure chest",
+                            "a flying drone",
+                        ];
+
+                        for example in examples {
+                            if ui.button(example).clicked() {
+                                self.prompt_text = example.to_string();
+                            }
+                        }
+                    });
+
+                    ui.separator();
+
+                    // === Tips ===
+                    ui.collapsing("💭 Prompt Tips", |ui| {
+                        ui.label("✓ Be specific but simple");
+                        ui.label("✓ Describe one object at a time");
+                        ui.label("✓ Include colors and materials");
+                        ui.label("✗ Avoid complex scenes");
+                        ui.label("✗ Don't use abstract concepts");
+
+                        ui.add_space(5.0);
+                        ui.label(RichText::new("Examples:").strong());
+                        ui.label("  Good: 'a red metal toolbox'");
+                        ui.label("  Bad: 'happiness and joy'");
+                    });
+
+                    ui.separator();
+
+                    // === Status Display ===
+                    if let Some(ref s) = self.last_status {
+                        let tone = if s.contains("Error") || s.contains("Failed") {
+                            Tone::Error
+                        } else if s.contains("Generated") || s.contains("ready") || s.contains("success") {
+                            Tone::Success
+                        } else {
+                            Tone::Info
+                        };
+
+                        ui.label(
+                            RichText::new(format!("{} Status: {}", tone.icon(), s))
+                                .color(tone.color(self.appearance.colorblind))
+                        );
+                    }
+
+                    ui.separator();
+
+                    // === Camera Controls ===
+                    ui.heading("🎮 Camera Controls");
+                    ui.label("• Left drag: Rotate");
+                    ui.label("• Mouse wheel: Zoom");
+                    ui.label("• Double-click: Focus");
+
+                    if ui.button("🔄 Reset Camera").clicked() {
+                        sender.instant(UiEvent::ResetCamera);
+                    }
+
+                    let orbit_toggled = ui.checkbox(&mut self.auto_orbit, "🔁 Auto-orbit").changed();
+                    let speed_changed = ui.add_enabled(
+                        self.auto_orbit,
+                        egui::Slider::new(&mut self.orbit_speed, 5.0..=90.0).text("°/s")
+                    ).changed();
+
+                    if orbit_toggled || speed_changed {
+                        self.send_auto_orbit(sender);
+                    }
+
+                    ui.separator();
+
+                    // === Camera Path ===
+                    ui.collapsing("🎬 Camera Path", |ui| {
+                        ui.horizontal(|ui| {
+                            ui.add(
+                                egui::DragValue::new(&mut self.keyframe_time)
+                                    .speed(0.1)
+                                    .range(0.0..=600.0)
+                                    .suffix(" s")
+                            );
+                            if ui.button("➕ Add keyframe").clicked() {
+                                sender.instant(UiEvent::AddCameraKeyframe(self.keyframe_time));
+                                self.keyframe_time += 2.0;
+                            }
+                        });
+
+                        for (i, time) in self.keyframe_times.iter().enumerate() {
+                            ui.horizontal(|ui| {
+                                ui.label(format!("#{}  {:.1} s", i + 1, time));
+                                if ui.small_button("🗑").clicked() {
+                                    sender.instant(UiEvent::RemoveCameraKeyframe(i));
+                                }
+                            });
+                        }
+
+                        ui.checkbox(&mut self.path_looping, "Loop");
+
+                        ui.horizontal(|ui| {
+                            if self.path_playing {
+                                if ui.button("⏹ Stop").clicked() {
+                                    sender.instant(UiEvent::StopCameraPath);
+                                }
+                            } else if ui.add_enabled(
+                                self.keyframe_times.len() >= 2,
+                                egui::Button::new("▶ Play")
+                            ).clicked() {
+                                sender.instant(UiEvent::PlayCameraPath { looping: self.path_looping });
+                            }
+
+                            if ui.add_enabled(
+                                !self.keyframe_times.is_empty(),
+                                egui::Button::new("Clear")
+                            ).clicked() {
+                                sender.instant(UiEvent::ClearCameraPath);
+                                self.keyframe_time = 0.0;
+                            }
+                        });
+                    });
+
+                    ui.separator();
+
+                    // === Rendering ===
+                    ui.collapsing("🖥 Rendering", |ui| {
+                        if ui.checkbox(&mut self.compact_splats, "Compact splat buffers")
+                            .on_hover_text("Store colors as 8-bit and scales/rotations as 16-bit floats on the GPU. \
+                                Halves VRAM per splat; turn off for full precision.")
+                            .changed()
+                        {
+                            sender.instant(UiEvent::SetCompactSplats(self.compact_splats));
+                        }
+
+                        egui::ComboBox::from_label("Shading")
+                            .selected_text(self.color_mode.label())
+                            .show_ui(ui, |ui| {
+                                for mode in ColorMode::ALL {
+                                    if ui.selectable_value(&mut self.color_mode, mode, mode.label()).changed() {
+                                        sender.instant(UiEvent::SetColorMode(mode));
+                                    }
+                                }
+                            });
+
+                        egui::ComboBox::from_label("Present mode")
+                            .selected_text(self.render.present_mode.label())
+                            .show_ui(ui, |ui| {
+                                for mode in PresentMode::ALL {
+                                    if ui.selectable_value(&mut self.render.present_mode, mode, mode.label()).changed() {
+                                        sender.instant(UiEvent::SetPresentMode(mode));
+                                    }
+                                }
+                            });
+
+                        ui.horizontal(|ui| {
+                            let mut capped = self.render.fps_limit.is_some();
+                            let mut changed = ui.checkbox(&mut capped, "Limit FPS")
+                                .on_hover_text("Cap the frame rate to save power")
+                                .changed();
+                            if changed {
+                                self.render.fps_limit = capped.then_some(60);
+                            }
+                            if let Some(fps) = &mut self.render.fps_limit {
+                                changed |= ui.add(egui::DragValue::new(fps).range(10..=240).suffix(" fps")).changed();
+                            }
+                            if changed {
+                                sender.instant(UiEvent::SetFpsLimit(self.render.fps_limit));
+                            }
+                        });
+
+                        let mut percent = self.render.render_scale * 100.0;
+                        let (min, max) = (*RENDER_SCALE_RANGE.start() * 100.0, *RENDER_SCALE_RANGE.end() * 100.0);
+                        if ui.add(egui::Slider::new(&mut percent, min..=max).step_by(10.0).suffix("%").text("Render scale"))
+                            .on_hover_text("Resolution of the 3D view relative to its size on screen. \
+                                Lower is faster on weak GPUs, higher is smoother and sharpens screenshots.")
+                            .changed()
+                        {
+                            self.render.render_scale = percent / 100.0;
+                            sender.instant(UiEvent::SetRenderScale(self.render.render_scale));
+                        }
+
+                        ui.horizontal(|ui| {
+                            ui.label("Splat shader");
+                            let name = self.render.custom_shader.as_deref()
+                                .and_then(|path| path.file_name())
+                                .map_or("Built-in".into(), |name| name.to_string_lossy());
+                            let label = ui.label(RichText::new(name).monospace());
+                            if let Some(path) = &self.render.custom_shader {
+                                label.on_hover_text(path.display().to_string());
+                            }
+                            if ui.button("📂").on_hover_text("Draw the splats with a WGSL file, reloaded whenever it's saved").clicked() {
+                                sender.instant(UiEvent::BrowseCustomShader);
+                            }
+                            if self.render.custom_shader.is_some()
+                                && ui.button("✖").on_hover_text("Back to the built-in shader").clicked()
+                            {
+                                sender.instant(UiEvent::SetCustomShader(None));
+                            }
+                        });
+                        if let Some(e) = &self.shader_error {
+                            let error = Tone::Error;
+                            ui.label(
+                                RichText::new(format!("{} {}", error.icon(), e))
+                                    .small()
+                                    .monospace()
+                                    .color(error.color(self.appearance.colorblind))
+                            );
+                        }
+
+                        ui.add_space(5.0);
+                        let mut gpu_changed = false;
+
+                        egui::ComboBox::from_label("Graphics API")
+                            .selected_text(self.render.backend.map_or("Auto", GpuBackend::label))
+                            .show_ui(ui, |ui| {
+                                gpu_changed |= ui.selectable_value(&mut self.render.backend, None, "Auto").changed();
+                                for backend in GpuBackend::ALL {
+                                    gpu_changed |= ui.selectable_value(&mut self.render.backend, Some(backend), backend.label()).changed();
+                                }
+                            });
+
+                        egui::ComboBox::from_label("GPU")
+                            .selected_text(self.render.adapter.as_deref().unwrap_or("Default"))
+                            .show_ui(ui, |ui| {
+                                gpu_changed |= ui.selectable_value(&mut self.render.adapter, None, "Default").changed();
+                                for name in &self.adapters {
+                                    gpu_changed |= ui.selectable_value(&mut self.render.adapter, Some(name.clone()), name).changed();
+                                }
+                            });
+
+                        if gpu_changed {
+                            sender.instant(UiEvent::SetGpu {
+                                backend: self.render.backend,
+                                adapter: self.render.adapter.clone(),
+                            });
+                        }
+                        ui.label(RichText::new(format!("Using {} (changes apply after restart)", self.active_adapter)).small());
+                    });
+
+                    ui.separator();
+
+                    // === Transform ===
+                    ui.collapsing("🧭 Transform", |ui| {
+                        if ui.button("⬇ Snap to ground")
+                            .on_hover_text("Turn the object upright along its longest axis and rest it at y = 0")
+                            .clicked()
+                        {
+                            sender.instant(UiEvent::SnapToGround);
+                        }
+                        if ui.button("📐 Normalize")
+                            .on_hover_text("Center at the origin and scale to fit a unit box")
+                            .clicked()
+                        {
+                            sender.instant(UiEvent::NormalizeScene);
+                        }
+
+                        ui.add_space(4.0);
+                        ui.horizontal(|ui| {
+                            egui::ComboBox::from_id_salt("array_layout")
+                                .selected_text(self.array_layout.label())
+                                .show_ui(ui, |ui| {
+                                    for layout in ARRAY_LAYOUTS {
+                                        let selected = layout.label() == self.array_layout.label();
+                                        if ui.selectable_label(selected, layout.label()).clicked() && !selected {
+                                            self.array_layout = layout;
+                                        }
+                                    }
+                                });
+                            if ui.button("▦ Array")
+                                .on_hover_text("Replace the scene with copies of it laid out on the ground")
+                                .clicked()
+                            {
+                                sender.instant(UiEvent::ArrayScene(self.array_layout));
+                            }
+                        });
+                        ui.horizontal(|ui| match &mut self.array_layout {
+                            ArrayLayout::Grid { columns, rows, spacing } => {
+                                ui.add(egui::DragValue::new(columns).range(1..=20).suffix(" cols"));
+                                ui.add(egui::DragValue::new(rows).range(1..=20).suffix(" rows"));
+                                ui.add(egui::DragValue::new(spacing).speed(0.05).range(0.0..=100.0).prefix("spacing "));
+                            }
+                            ArrayLayout::Circle { count, radius } => {
+                                ui.add(egui::DragValue::new(count).range(1..=100).suffix(" copies"));
+                                ui.add(egui::DragValue::new(radius).speed(0.05).range(0.0..=100.0).prefix("radius "));
+                            }
+                            ArrayLayout::Scatter { count, extent, seed } => {
+                                ui.add(egui::DragValue::new(count).range(1..=200).suffix(" copies"));
+                                ui.add(egui::DragValue::new(extent).speed(0.05).range(0.0..=100.0).prefix("area "));
+                                ui.add(egui::DragValue::new(seed).prefix("seed "));
+                            }
+                        });
+
+                        ui.add_space(4.0);
+                        ui.label(RichText::new("Shift-drag: box select, Alt-drag: lasso, +Ctrl: add").small().weak());
+                        ui.label(format!("Selected: {} splats", self.selected_splats));
+                        ui.add_enabled_ui(self.selected_splats > 0, |ui| {
+                            ui.horizontal(|ui| {
+                                if ui.button("🗑 Delete").clicked() {
+                                    sender.instant(UiEvent::DeleteSelection);
+                                }
+                                if ui.button("Isolate").on_hover_text("Keep only the selected splats").clicked() {
+                                    sender.instant(UiEvent::IsolateSelection);
+                                }
+                                if ui.button("Clear").clicked() {
+                                    sender.instant(UiEvent::ClearSelection);
+                                }
+                            });
+                        });
+                    });
+
+                    ui.separator();
+
+                    // === Volume ===
+                    ui.collapsing("📦 Volume", |ui| {
+                        ui.label(RichText::new("Delete or keep everything inside a box or sphere, e.g. a cluster of floaters").small().weak());
+
+                        let Some(volume) = &mut self.volume else {
+                            ui.horizontal(|ui| {
+                                if ui.button("Add box").clicked() {
+                                    sender.instant(UiEvent::PlaceVolume { sphere: false });
+                                }
+                                if ui.button("Add sphere").clicked() {
+                                    sender.instant(UiEvent::PlaceVolume { sphere: true });
+                                }
+                            });
+                            return;
+                        };
+
+                        let mut changed = false;
+                        fn drag(value: &mut f32) -> egui::DragValue<'_> {
+                            egui::DragValue::new(value).speed(0.01).max_decimals(3)
+                        }
+                        let (center, size_label) = match volume {
+                            Volume::Box { center, .. } => (center, "Half size"),
+                            Volume::Sphere { center, .. } => (center, "Radius"),
+                        };
+                        ui.horizontal(|ui| {
+                            ui.label("Center");
+                            for value in center.iter_mut() {
+                                changed |= ui.add(drag(value)).changed();
+                            }
+                        });
+                        ui.horizontal(|ui| {
+                            ui.label(size_label);
+                            match volume {
+                                Volume::Box { half_extents, .. } => {
+                                    for value in half_extents.iter_mut() {
+                                        changed |= ui.add(drag(value).range(0.0..=f32::MAX)).changed();
+                                    }
+                                }
+                                Volume::Sphere { radius, .. } => {
+                                    changed |= ui.add(drag(radius).range(0.0..=f32::MAX)).changed();
+                                }
+                            }
+                        });
+                        if changed {
+                            sender.instant(UiEvent::SetVolume(self.volume));
+                        }
+
+                        ui.horizontal(|ui| {
+                            if ui.button("🗑 Delete inside").clicked() {
+                                sender.instant(UiEvent::DeleteVolume);
+                            }
+                            if ui.button("Keep inside").on_hover_text("Delete everything outside").clicked() {
+                                sender.instant(UiEvent::IsolateVolume);
+                            }
+                            if ui.button("✖").on_hover_text("Remove the volume").clicked() {
+                                sender.instant(UiEvent::SetVolume(None));
+                            }
+                        });
+                    });
+
+                    ui.separator();
+
+                    // === Paint ===
+                    ui.collapsing("🖌 Paint", |ui| {
+                        ui.label(RichText::new("Touch up splats by dragging in the viewport; the camera orbits again once the brush is off").small().weak());
+
+                        let mut changed = false;
+                        ui.horizontal(|ui| {
+                            changed |= ui.selectable_value(&mut self.brush_tool, BrushTool::Off, "Off").changed();
+                            changed |= ui.selectable_value(&mut self.brush_tool, BrushTool::Fade, "Fade")
+                                .on_hover_text("Lower the opacity of the splats under the brush")
+                                .changed();
+                            changed |= ui.selectable_value(&mut self.brush_tool, BrushTool::Tint, "Tint")
+                                .on_hover_text("Blend the colors under the brush toward a color")
+                                .changed();
+                        });
+                        changed |= ui.add(egui::Slider::new(&mut self.brush_radius, 5.0..=200.0).text("Radius").suffix(" px")).changed();
+                        match self.brush_tool {
+                            BrushTool::Fade => {
+                                changed |= ui.add(egui::Slider::new(&mut self.brush_fade, 0.05..=1.0).text("Strength")).changed();
+                            }
+                            BrushTool::Tint => {
+                                ui.horizontal(|ui| {
+                                    changed |= egui::color_picker::color_edit_button_rgb(ui, &mut self.brush_color).changed();
+                                    ui.label("Color");
+                                });
+                                changed |= ui.add(egui::Slider::new(&mut self.brush_tint, 0.05..=1.0).text("Strength")).changed();
+                            }
+                            BrushTool::Off => {}
+                        }
+
+                        if changed {
+                            sender.instant(UiEvent::SetBrush(self.brush()));
+                        }
+                    });
+
+                    ui.separator();
+
+                    // === Clipping ===
+                    ui.collapsing("✂ Clipping", |ui| {
+                        ui.label(RichText::new("Cut the scene open to look inside; drag the arrows in the viewport").small().weak());
+
+                        let mut changed = false;
+                        let mut removed = None;
+                        for (index, plane) in self.clip_planes.iter_mut().enumerate() {
+                            // Planes from this panel are axis-aligned; the sign
+                            // of the normal says which side is kept
+                            let axis = (0..3).max_by(|&a, &b| plane.normal[a].abs().total_cmp(&plane.normal[b].abs())).unwrap_or(0);
+                            let sign = plane.normal[axis].signum();
+                            ui.horizontal(|ui| {
+                                let side = if sign > 0.0 { "≤" } else { "≥" };
+                                ui.label(format!("Keep {} {}", ["x", "y", "z"][axis], side));
+                                let mut position = plane.offset * sign;
+                                if ui.add(egui::DragValue::new(&mut position).speed(0.01).max_decimals(3)).changed() {
+                                    plane.offset = position * sign;
+                                    changed = true;
+                                }
+                                if ui.button("⇄").on_hover_text("Keep the other side").clicked() {
+                                    *plane = plane.flipped();
+                                    changed = true;
+                                }
+                                if ui.button("✖").clicked() {
+                                    removed = Some(index);
+                                }
+                            });
+                        }
+                        if let Some(index) = removed {
+                            self.clip_planes.remove(index);
+                            changed = true;
+                        }
+                        if changed {
+                            sender.instant(UiEvent::SetClipPlanes(self.clip_planes.clone()));
+                        }
+
+                        ui.add_enabled_ui(self.clip_planes.len() < MAX_CLIP_PLANES, |ui| {
+                            ui.horizontal(|ui| {
+                                ui.label("Add plane");
+                                for (axis, label) in ["X", "Y", "Z"].into_iter().enumerate() {
+                                    if ui.button(label).clicked() {
+                                        sender.instant(UiEvent::AddClipPlane { axis });
+                                    }
+                                }
+                            });
+                        });
+                    });
+
+                    ui.separator();
+
+                    // === Color Grading ===
+                    ui.collapsing("🎨 Color Grading", |ui| {
+                        let grade = &mut self.color_grade;
+                        let mut changed = false;
+                        changed |= ui.add(egui::Slider::new(&mut grade.brightness, -0.5..=0.5).text("Brightness")).changed();
+                        changed |= ui.add(egui::Slider::new(&mut grade.contrast, 0.0..=2.0).text("Contrast")).changed();
+                        changed |= ui.add(egui::Slider::new(&mut grade.saturation, 0.0..=2.0).text("Saturation")).changed();
+                        changed |= ui.add(egui::Slider::new(&mut grade.hue, -180.0..=180.0).text("Hue").suffix("°")).changed();
+
+                        ui.horizontal(|ui| {
+                            if ui.add_enabled(!grade.is_identity(), egui::Button::new("Reset")).clicked() {
+                                *grade = ColorGrade::default();
+                                changed = true;
+                            }
+                            if ui.add_enabled(!grade.is_identity(), egui::Button::new("🔥 Bake"))
+                                .on_hover_text("Write the adjustments into the splat colors, e.g. before exporting")
+                                .clicked()
+                            {
+                                sender.instant(UiEvent::BakeColorGrade);
+                            }
+                        });
+
+                        if changed {
+                            sender.instant(UiEvent::SetColorGrade(*grade));
+                        }
+                    });
+
+                    ui.separator();
+
+                    // === Depth of Field ===
+                    ui.collapsing("📷 Depth of Field", |ui| {
+                        let dof = &mut self.depth_of_field;
+                        let mut changed = ui.checkbox(&mut self.dof_enabled, "Blur out of focus").changed();
+                        ui.add_enabled_ui(self.dof_enabled, |ui| {
+                            changed |= ui.add(
+                                egui::Slider::new(&mut dof.focus_distance, 0.1..=50.0)
+                                    .logarithmic(true)
+                                    .text("Focus distance")
+                            ).changed();
+                            changed |= ui.add(egui::Slider::new(&mut dof.aperture, 0.0..=MAX_COC).text("Aperture").suffix(" px")).changed();
+                            ui.label(RichText::new("Ctrl-click the model to focus on it").small().weak());
+                        });
+
+                        if changed {
+                            sender.instant(UiEvent::SetDepthOfField(self.dof_enabled.then_some(*dof)));
+                        }
+                    });
+
+                    ui.separator();
+
+                    // === Export ===
+                    ui.collapsing("📤 Export", |ui| {
+                        ui.horizontal(|ui| {
+                            if ui.button("💾 PLY").clicked() {
+                                let profile = if self.supersplat_ply { PlyProfile::SuperSplat } else { PlyProfile::Genjutsu };
+                                sender.instant(UiEvent::ExportPly { profile });
+                            }
+                            ui.checkbox(&mut self.supersplat_ply, "SuperSplat compatible")
+                                .on_hover_text("Reference 3DGS layout and axes, so the scene loads upright in SuperSplat and PlayCanvas");
+                        });
+                        ui.add_space(4.0);
+                        ui.add(
+                            egui::Slider::new(&mut self.voxel_resolution, 8..=VOX_MAX_SIZE)
+                                .text("Voxel resolution")
+                        );
+                        if ui.button("🧊 Voxels (.vox)")
+                            .on_hover_text("Export the current scene as a MagicaVoxel model")
+                            .clicked()
+                        {
+                            sender.instant(UiEvent::ExportVoxels { resolution: self.voxel_resolution });
+                        }
+                        ui.add_space(4.0);
+                        ui.add(
+                            egui::Slider::new(&mut self.mesh.resolution, 8..=MESH_MAX_RESOLUTION)
+                                .text("Mesh resolution")
+                        ).on_hover_text("Cells along the longest axis of the grid the surface is extracted on");
+                        egui::ComboBox::from_label("Texture size")
+                            .selected_text(format!("{} px", self.mesh.texture_size))
+                            .show_ui(ui, |ui| {
+                                for size in TEXTURE_SIZES {
+                                    ui.selectable_value(&mut self.mesh.texture_size, size, format!("{} px", size));
+                                }
+                            });
+                        if ui.button("🔺 Textured mesh (.glb/.obj)")
+                            .on_hover_text("Export the current scene as a mesh with the splat colors baked into a texture, for engines that don't render splats")
+                            .clicked()
+                        {
+                            sender.instant(UiEvent::ExportMesh(self.mesh));
+                        }
+                        if ui.button("🌐 Web viewer (.html)")
+                            .on_hover_text("A single HTML file that shows the scene in any browser, no app needed")
+                            .clicked()
+                        {
+                            sender.instant(UiEvent::ExportWebViewer);
+                        }
+
+                        ui.add_space(4.0);
+                        ui.label(RichText::new("PNG sequence").strong());
+                        egui::ComboBox::from_label("Motion")
+                            .selected_text(self.sequence_motion.label())
+                            .show_ui(ui, |ui| {
+                                for motion in SequenceMotion::ALL {
+                                    ui.selectable_value(&mut self.sequence_motion, motion, motion.label());
+                                }
+                            });
+                        ui.horizontal(|ui| {
+                            ui.add(egui::DragValue::new(&mut self.sequence_size[0]).range(16..=8192).suffix(" px"));
+                            ui.label("×");
+                            ui.add(egui::DragValue::new(&mut self.sequence_size[1]).range(16..=8192).suffix(" px"));
+                        });
+                        ui.add(egui::Slider::new(&mut self.sequence_frames, 1..=1000).text("Frames"));
+                        if ui.button("🎞 Render sequence")
+                            .on_hover_text("Render numbered PNG frames to outputs/sequences, independent of the window size")
+                            .clicked()
+                        {
+                            let [width, height] = self.sequence_size;
+                            sender.instant(UiEvent::ExportSequence {
+                                motion: self.sequence_motion,
+                                width,
+                                height,
+                                frames: self.sequence_frames,
+                            });
+                        }
+                        if ui.button("📷 NeRF dataset")
+                            .on_hover_text("Render the camera keyframes (or the current view) at this size with a transforms.json for Nerfstudio / Instant-NGP")
+                            .clicked()
+                        {
+                            let [width, height] = self.sequence_size;
+                            sender.instant(UiEvent::ExportTransforms { width, height });
+                        }
+                    });
+
+                    ui.separator();
+
+                    // === Appearance ===
+                    ui.collapsing("🌓 Appearance", |ui| {
+                        let mut changed = false;
+                        egui::ComboBox::from_label("Theme")
+                            .selected_text(self.appearance.theme.label())
+                            .show_ui(ui, |ui| {
+                                for theme in Theme::ALL {
+                                    changed |= ui.selectable_value(&mut self.appearance.theme, theme, theme.label()).changed();
+                                }
+                            });
+                        ui.horizontal(|ui| {
+                            changed |= egui::color_picker::color_edit_button_srgb(ui, &mut self.appearance.accent).changed();
+                            ui.label("Accent color");
+                            if ui.add_enabled(self.appearance.accent != AppearanceSettings::default().accent, egui::Button::new("Reset").small()).clicked() {
+                                self.appearance.accent = AppearanceSettings::default().accent;
+                                changed = true;
+                            }
+                        });
+                        changed |= ui.checkbox(&mut self.appearance.colorblind, "Colorblind-safe status colors")
+                            .on_hover_text("Blue, green and orange instead of red and green")
+                            .changed();
+                        if changed {
+                            sender.instant(UiEvent::SetAppearance(self.appearance.clone()));
+                        }
+                    });
+
+                    ui.separator();
+
+                    // === Confirmations ===
+                    ui.collapsing("⚠ Confirmations", |ui| {
+                        let mut changed = ui.checkbox(&mut self.confirm.delete_jobs, "Ask before deleting jobs").changed();
+                        changed |= ui.checkbox(&mut self.confirm.clear_completed, "Ask before clearing completed jobs").changed();
+                        if changed {
+                            sender.instant(UiEvent::SetConfirmations(self.confirm.clone()));
+                        }
+                    });
+
+                    ui.separator();
+
+                    // === System Info ===
+                    ui.collapsing("ℹ️ System Info", |ui| {
+                        ui.label("Model: Shap-E (OpenAI)");
+                        ui.label("Renderer: Gaussian Splatting");
+                        ui.label("Backend: WebGPU (wgpu)");
+                        ui.label(format!("GPU: {}", self.active_adapter));
+                        ui.label("Generation: ~30-60 seconds");
+                        self.worker_gpus_ui(ui, sender);
+                    });
                 });
             });
+        self.width = response.response.rect.width();
     }
 
-    pub fn on_app_event(&mut self, ev: &AppEvent) {
+    fn on_app_event(&mut self, ev: &AppEvent) {
         match ev {
             AppEvent::Status(s) => {
                 self.last_status = Some(s.clone());
@@ -169,6 +1551,25 @@ impl SidePanel {
                     self.is_generating = false;
                 }
             }
+            AppEvent::WorkerVram(vram_mb) => {
+                self.worker_vram_mb = *vram_mb;
+                self.incompatible = None;
+            }
+            AppEvent::BackendModels(models) => {
+                self.backend_models = models.clone();
+            }
+            AppEvent::ModelParams(params) => {
+                self.model_params = params.clone();
+            }
+            AppEvent::ModelPresets(presets) => {
+                self.model_presets = presets.clone();
+            }
+            AppEvent::WorkerGpus(workers) => {
+                self.worker_gpus = workers.clone();
+            }
+            AppEvent::ServiceIncompatible(err) => {
+                self.incompatible = Some(err.message().to_string());
+            }
             AppEvent::Progress(p) => {
                 self.last_status = Some(format!("Progress: {:.0}%", p * 100.0));
             }
@@ -176,9 +1577,79 @@ impl SidePanel {
                 self.last_status = Some("Scene ready".into());
                 self.is_generating = false;
             }
-            AppEvent::GaussianCloudReady => {
+            AppEvent::GaussianCloudReady
+            | AppEvent::JobCompleted { .. }
+            | AppEvent::JobFailed(_)
+            | AppEvent::ServiceDisconnected(_)
+            | AppEvent::JobStuck(_) => {
                 self.is_generating = false;
             }
+            AppEvent::CameraPathChanged(times) => {
+                self.keyframe_times = times.clone();
+            }
+            AppEvent::CameraPathPlaying(playing) => {
+                self.path_playing = *playing;
+            }
+            AppEvent::SelectionChanged(count) => {
+                self.selected_splats = *count;
+            }
+            AppEvent::ClipPlanes(planes) => {
+                self.clip_planes = planes.clone();
+            }
+            AppEvent::Volume(volume) => {
+                self.volume = *volume;
+            }
+            AppEvent::EditJobInputs(inputs) => {
+                self.load_inputs(inputs);
+            }
+            AppEvent::ColorGradeBaked => {
+                self.color_grade = ColorGrade::default();
+            }
+            AppEvent::DepthOfField(dof) => {
+                self.dof_enabled = dof.is_some();
+                if let Some(dof) = dof {
+                    self.depth_of_field = *dof;
+                }
+            }
+            AppEvent::RenderSettings(render) => {
+                self.render = render.clone();
+            }
+            AppEvent::InputImagePicked(path) => {
+                self.picked_image = Some(path.clone());
+            }
+            AppEvent::InputImages(images) => {
+                self.thumbnails.retain(|id, _| images.iter().any(|i| &i.id == id));
+                self.capture.retain(|v| images.iter().any(|i| i.path == v.path));
+                self.input_images = images.clone();
+            }
+            AppEvent::InputImageAdded(path) => {
+                self.input_image = Some(path.clone());
+            }
+            AppEvent::ColmapPicked(dir) => {
+                self.picked_colmap = Some(dir.clone());
+            }
+            AppEvent::CaptureSet(capture) => {
+                self.capture = capture.clone();
+            }
+            AppEvent::CustomShaderPicked(path) => {
+                self.picked_shader = Some(path.clone());
+            }
+            AppEvent::ShaderError(error) => {
+                self.shader_error = error.clone();
+            }
+            AppEvent::ColorMode(mode) => {
+                self.color_mode = *mode;
+            }
+            AppEvent::Appearance(appearance) => {
+                self.appearance = appearance.clone();
+            }
+            AppEvent::Confirmations(confirm) => {
+                self.confirm = confirm.clone();
+            }
+            AppEvent::GpuAdapters { active, available } => {
+                self.active_adapter = active.clone();
+                self.adapters = available.clone();
+            }
             _ => {}
         }
     }
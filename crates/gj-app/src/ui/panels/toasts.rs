@@ -0,0 +1,189 @@
+use std::time::{Duration, Instant};
+use egui::{Align2, Context, RichText};
+use crate::events::{AppEvent, UiEvent};
+use crate::ui::palette::Tone;
+use crate::ui::{UiComponent, UiEventSender};
+
+/// Most toasts visible at once; older ones are dropped first
+const MAX_TOASTS: usize = 5;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ToastKind {
+    Success,
+    Error,
+    Info,
+}
+
+impl ToastKind {
+    fn tone(&self) -> Tone {
+        match self {
+            Self::Success => Tone::Success,
+            Self::Error => Tone::Error,
+            Self::Info => Tone::Info,
+        }
+    }
+
+    fn label(&self) -> &str {
+        match self {
+            Self::Success => "Done",
+            Self::Error => "Error",
+            Self::Info => "Info",
+        }
+    }
+
+    /// Errors stay up longer so they can be read
+    fn lifetime(&self) -> Duration {
+        match self {
+            Self::Error => Duration::from_secs(10),
+            Self::Success | Self::Info => Duration::from_secs(5),
+        }
+    }
+}
+
+pub struct Toast {
+    pub kind: ToastKind,
+    pub message: String,
+    /// Optional button label and the event it sends
+    pub action: Option<(String, UiEvent)>,
+    created: Instant,
+    lifetime: Duration,
+}
+
+impl Toast {
+    pub fn new(kind: ToastKind, message: impl Into<String>) -> Self {
+        Self {
+            kind,
+            message: message.into(),
+            action: None,
+            created: Instant::now(),
+            lifetime: kind.lifetime(),
+        }
+    }
+
+    /// Keep the toast up for `lifetime` instead of its kind's default
+    pub fn with_lifetime(mut self, lifetime: Duration) -> Self {
+        self.lifetime = lifetime;
+        self
+    }
+
+    pub fn with_action(mut self, label: impl Into<String>, event: UiEvent) -> Self {
+        self.action = Some((label.into(), event));
+        self
+    }
+
+    fn expires_at(&self) -> Instant {
+        self.created + self.lifetime
+    }
+}
+
+/// Transient notifications stacked in the bottom-right corner of the viewport.
+/// Clicking a toast dismisses it.
+#[derive(Default)]
+pub struct Toasts {
+    toasts: Vec<Toast>,
+    colorblind: bool,
+}
+
+impl Toasts {
+    pub fn push(&mut self, toast: Toast) {
+        if self.toasts.len() == MAX_TOASTS {
+            self.toasts.remove(0);
+        }
+        self.toasts.push(toast);
+    }
+}
+
+impl UiComponent for Toasts {
+    fn show(&mut self, ctx: &Context, sender: &mut UiEventSender) {
+        let now = Instant::now();
+        self.toasts.retain(|t| t.expires_at() > now);
+
+        let mut dismissed = Vec::new();
+        let mut offset = -10.0;
+
+        // Newest at the bottom, stacking upwards
+        for (i, toast) in self.toasts.iter().enumerate().rev() {
+            let color = toast.kind.tone().color(self.colorblind);
+            let response = egui::Area::new(egui::Id::new("toast").with(toast.created))
+                .anchor(Align2::RIGHT_BOTTOM, [-10.0, offset])
+                .order(egui::Order::Foreground)
+                .interactable(true)
+                .show(ctx, |ui| {
+                    egui::Frame::popup(ui.style())
+                        .stroke(egui::Stroke::new(1.0, color))
+                        .show(ui, |ui| {
+                            ui.set_max_width(320.0);
+                            ui.horizontal(|ui| {
+                                // Icon and word as well as color, so the kind reads without it
+                                ui.label(RichText::new(format!("{} {}", toast.kind.tone().icon(), toast.kind.label())).color(color).strong());
+                                ui.label(&toast.message);
+                            });
+
+                            if let Some((label, event)) = &toast.action
+                                && ui.button(label).clicked()
+                            {
+                                sender.instant(event.clone());
+                                dismissed.push(i);
+                            }
+                        });
+                })
+                .response;
+
+            if response.interact(egui::Sense::click()).clicked() {
+                dismissed.push(i);
+            }
+
+            offset -= response.rect.height() + 6.0;
+        }
+
+        // The action button click also lands on the toast itself
+        dismissed.sort_unstable();
+        dismissed.dedup();
+        for i in dismissed.into_iter().rev() {
+            self.toasts.remove(i);
+        }
+
+        // Wake up again when the next toast expires
+        if let Some(next) = self.toasts.iter().map(Toast::expires_at).min() {
+            ctx.request_repaint_after(next.saturating_duration_since(now));
+        }
+    }
+
+    fn on_app_event(&mut self, ev: &AppEvent) {
+        let toast = match ev {
+            AppEvent::Appearance(appearance) => {
+                self.colorblind = appearance.colorblind;
+                return;
+            }
+            AppEvent::JobCompleted { gaussians, loaded: true } => {
+                Toast::new(ToastKind::Success, format!("Generation complete ({} Gaussians)", gaussians))
+            }
+            AppEvent::JobCompleted { gaussians, loaded: false } => {
+                Toast::new(ToastKind::Success, format!("Generation complete ({} Gaussians)", gaussians))
+                    .with_action("📂 Click to load scene", UiEvent::LoadPendingScene)
+            }
+            AppEvent::JobFailed(err) | AppEvent::SceneLoadFailed(err) | AppEvent::ServiceIncompatible(err) => {
+                let mut message = format!("{}: {}", err.title(), err.message());
+                if let Some(hint) = err.remediation() {
+                    message.push_str(&format!("\n💡 {}", hint));
+                }
+                Toast::new(ToastKind::Error, message)
+            }
+            AppEvent::CloudSanitized(summary) => Toast::new(ToastKind::Info, format!("Repaired cloud: {}", summary)),
+            AppEvent::ExportFinished(path) => Toast::new(ToastKind::Info, format!("Saved {}", path.display())),
+            AppEvent::ServiceDisconnected(err) => Toast::new(ToastKind::Error, format!("Service disconnected: {}", err)),
+            // Up long enough to notice after the window comes back
+            AppEvent::SessionRecoverable => {
+                Toast::new(ToastKind::Info, "genjutsu didn't close properly last time")
+                    .with_action("↺ Restore previous session", UiEvent::RestoreSession)
+                    .with_lifetime(Duration::from_secs(60))
+            }
+            AppEvent::JobStuck(id) => {
+                Toast::new(ToastKind::Error, "A job is stuck: the service has no record of it")
+                    .with_action("↻ Requeue", UiEvent::RequeueJob(id.clone()))
+            }
+            _ => return,
+        };
+        self.push(toast);
+    }
+}
\ No newline at end of file
@@ -1,25 +1,34 @@
 use egui::{Color32, Context, RichText};
 use crate::events::AppEvent;
-use crate::ui::UiEventSender;
+use crate::ui::{UiComponent, UiEventSender};
 
 #[derive(Default)]
 pub struct TopPanel {
-    // local state / child components may go here
+    /// The models button was clicked; the model manager is toggled by its owner
+    pub toggle_models: bool,
 }
 
-impl TopPanel {
-    pub fn show(&mut self, ctx: &Context, sender: &mut UiEventSender) {
+impl UiComponent for TopPanel {
+    fn show(&mut self, ctx: &Context, _sender: &mut UiEventSender) {
         egui::TopBottomPanel::top("top_panel").show(ctx, |ui| {
             ui.horizontal(|ui| {
                 ui.heading("🎨 genjutsu");
                 ui.separator();
                 ui.label(RichText::new("Status:").color(Color32::LIGHT_BLUE));
                 // status display would be written by side panel pushing AppEvent::Status
+                ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                    if ui.button("📦 Models")
+                        .on_hover_text("Model weights on the generation workers")
+                        .clicked()
+                    {
+                        self.toggle_models = true;
+                    }
+                });
             });
         });
     }
 
-    pub fn on_app_event(&mut self, _ev: &AppEvent) {
+    fn on_app_event(&mut self, _ev: &AppEvent) {
         // react to app events if needed (e.g. update internal text)
     }
 }
\ No newline at end of file
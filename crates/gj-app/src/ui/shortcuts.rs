@@ -0,0 +1,341 @@
+use std::collections::BTreeMap;
+use egui::{Context, Key, KeyboardShortcut, Modifiers, RichText};
+
+/// Actions that can be bound to a keyboard shortcut
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ShortcutAction {
+    ResetCamera,
+    Screenshot,
+    ToggleSidePanel,
+    ToggleLogPanel,
+    FocusPrompt,
+    SubmitGeneration,
+    ToggleAutoOrbit,
+    ToggleInspector,
+    ShowShortcuts,
+    DeleteSelection,
+    DeleteJob,
+}
+
+/// Where a shortcut applies. Actions in scopes that are never active at the
+/// same time may share a key.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ShortcutScope {
+    /// Anywhere in the app
+    Global,
+    /// The viewport, while no job card has keyboard focus
+    Viewport,
+    /// The job queue, while one of its cards has keyboard focus
+    Queue,
+}
+
+impl ShortcutScope {
+    fn overlaps(self, other: Self) -> bool {
+        self == Self::Global || other == Self::Global || self == other
+    }
+}
+
+impl ShortcutAction {
+    /// Action id used in the settings file
+    pub fn id(&self) -> &str {
+        match self {
+            Self::ResetCamera => "reset_camera",
+            Self::Screenshot => "screenshot",
+            Self::ToggleSidePanel => "toggle_side_panel",
+            Self::ToggleLogPanel => "toggle_log_panel",
+            Self::FocusPrompt => "focus_prompt",
+            Self::SubmitGeneration => "submit_generation",
+            Self::ToggleAutoOrbit => "toggle_auto_orbit",
+            Self::ToggleInspector => "toggle_inspector",
+            Self::ShowShortcuts => "show_shortcuts",
+            Self::DeleteSelection => "delete_selection",
+            Self::DeleteJob => "delete_job",
+        }
+    }
+
+    /// Human-readable description for the cheat sheet
+    pub fn description(&self) -> &str {
+        match self {
+            Self::ResetCamera => "Reset camera",
+            Self::Screenshot => "Save screenshot",
+            Self::ToggleSidePanel => "Toggle side panel",
+            Self::ToggleLogPanel => "Toggle log panel",
+            Self::FocusPrompt => "Focus prompt box",
+            Self::SubmitGeneration => "Submit generation",
+            Self::ToggleAutoOrbit => "Toggle auto-orbit",
+            Self::ToggleInspector => "Toggle cloud inspector",
+            Self::ShowShortcuts => "Show keyboard shortcuts",
+            Self::DeleteSelection => "Delete selected splats",
+            Self::DeleteJob => "Delete focused job",
+        }
+    }
+
+    pub fn scope(&self) -> ShortcutScope {
+        match self {
+            Self::DeleteSelection => ShortcutScope::Viewport,
+            Self::DeleteJob => ShortcutScope::Queue,
+            _ => ShortcutScope::Global,
+        }
+    }
+
+    fn default_shortcut(&self) -> KeyboardShortcut {
+        match self {
+            Self::ResetCamera => KeyboardShortcut::new(Modifiers::NONE, Key::R),
+            Self::Screenshot => KeyboardShortcut::new(Modifiers::NONE, Key::F12),
+            Self::ToggleSidePanel => KeyboardShortcut::new(Modifiers::COMMAND, Key::B),
+            Self::ToggleLogPanel => KeyboardShortcut::new(Modifiers::COMMAND, Key::J),
+            Self::FocusPrompt => KeyboardShortcut::new(Modifiers::COMMAND, Key::L),
+            Self::SubmitGeneration => KeyboardShortcut::new(Modifiers::COMMAND, Key::Enter),
+            Self::ToggleAutoOrbit => KeyboardShortcut::new(Modifiers::NONE, Key::O),
+            Self::ToggleInspector => KeyboardShortcut::new(Modifiers::COMMAND, Key::I),
+            Self::ShowShortcuts => KeyboardShortcut::new(Modifiers::NONE, Key::F1),
+            Self::DeleteSelection => KeyboardShortcut::new(Modifiers::NONE, Key::Delete),
+            Self::DeleteJob => KeyboardShortcut::new(Modifiers::NONE, Key::Delete),
+        }
+    }
+
+    /// All bindable actions
+    pub fn all() -> [ShortcutAction; 11] {
+        [
+            Self::ResetCamera,
+            Self::Screenshot,
+            Self::ToggleSidePanel,
+            Self::ToggleLogPanel,
+            Self::FocusPrompt,
+            Self::SubmitGeneration,
+            Self::ToggleAutoOrbit,
+            Self::ToggleInspector,
+            Self::ShowShortcuts,
+            Self::DeleteSelection,
+            Self::DeleteJob,
+        ]
+    }
+}
+
+/// Centralized keybinding map
+pub struct Keymap {
+    bindings: Vec<(ShortcutAction, KeyboardShortcut)>,
+}
+
+impl Default for Keymap {
+    fn default() -> Self {
+        Self::from_settings(&BTreeMap::new())
+    }
+}
+
+impl Keymap {
+    /// Build the keymap from the defaults plus any overrides from the settings file
+    pub fn from_settings(overrides: &BTreeMap<String, String>) -> Self {
+        let mut bindings: Vec<_> = ShortcutAction::all()
+            .into_iter()
+            .map(|action| {
+                let shortcut = overrides
+                    .get(action.id())
+                    .and_then(|s| {
+                        let parsed = parse_shortcut(s);
+                        if parsed.is_none() {
+                            log::warn!("Invalid shortcut '{}' for '{}', using default", s, action.id());
+                        }
+                        parsed
+                    })
+                    .unwrap_or_else(|| action.default_shortcut());
+                (action, shortcut)
+            })
+            .collect();
+
+        // Match the most specific shortcuts first so Ctrl+R doesn't also fire R
+        bindings.sort_by_key(|(_, s)| std::cmp::Reverse(modifier_count(s.modifiers)));
+
+        let keymap = Self { bindings };
+        for (a, b) in keymap.conflicts() {
+            log::warn!("Shortcut for '{}' is also bound to '{}'; only one will fire", a.id(), b.id());
+        }
+        keymap
+    }
+
+    /// Pairs of actions bound to the same shortcut in scopes that can be
+    /// active at once
+    pub fn conflicts(&self) -> Vec<(ShortcutAction, ShortcutAction)> {
+        let mut conflicts = Vec::new();
+        for (i, (a, shortcut_a)) in self.bindings.iter().enumerate() {
+            for (b, shortcut_b) in &self.bindings[i + 1..] {
+                if shortcut_a == shortcut_b && a.scope().overlaps(b.scope()) {
+                    conflicts.push((*a, *b));
+                }
+            }
+        }
+        conflicts
+    }
+
+    /// Current bindings as settings entries (action id -> shortcut)
+    pub fn to_settings(&self) -> BTreeMap<String, String> {
+        self.bindings
+            .iter()
+            .map(|(action, shortcut)| (action.id().to_string(), format_shortcut(shortcut)))
+            .collect()
+    }
+
+    /// Consume the shortcuts pressed this frame.
+    /// Shortcuts without modifiers are ignored while a text field has focus,
+    /// and `queue_focused` picks between the viewport and queue scopes.
+    pub fn poll(&self, ctx: &Context, queue_focused: bool) -> Vec<ShortcutAction> {
+        let typing = ctx.wants_keyboard_input();
+        let inactive = if queue_focused { ShortcutScope::Viewport } else { ShortcutScope::Queue };
+
+        ctx.input_mut(|input| {
+            self.bindings
+                .iter()
+                .filter(|(action, _)| action.scope() != inactive)
+                .filter(|(_, shortcut)| !typing || !shortcut.modifiers.is_none())
+                .filter(|(_, shortcut)| input.consume_shortcut(shortcut))
+                .map(|(action, _)| *action)
+                .collect()
+        })
+    }
+
+    /// Cheat-sheet overlay listing every binding
+    pub fn show_cheat_sheet(&self, ctx: &Context, open: &mut bool) {
+        egui::Window::new("⌨ Keyboard Shortcuts")
+            .open(open)
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, egui::Vec2::ZERO)
+            .show(ctx, |ui| {
+                egui::Grid::new("shortcuts_grid")
+                    .num_columns(2)
+                    .spacing([24.0, 6.0])
+                    .striped(true)
+                    .show(ui, |ui| {
+                        for action in ShortcutAction::all() {
+                            if let Some((_, shortcut)) = self.bindings.iter().find(|(a, _)| *a == action) {
+                                ui.label(action.description());
+                                ui.label(RichText::new(ctx.format_shortcut(shortcut)).monospace().strong());
+                                ui.end_row();
+                            }
+                        }
+                    });
+
+                ui.add_space(5.0);
+                ui.label(
+                    RichText::new(format!("Customize in {}", crate::settings::Settings::path().display()))
+                        .small()
+                        .weak()
+                );
+            });
+    }
+}
+
+fn modifier_count(m: Modifiers) -> usize {
+    [m.alt, m.ctrl || m.command || m.mac_cmd, m.shift].iter().filter(|b| **b).count()
+}
+
+/// Parse a shortcut such as `"Ctrl+Shift+S"` or `"F12"`
+pub fn parse_shortcut(s: &str) -> Option<KeyboardShortcut> {
+    let mut modifiers = Modifiers::NONE;
+    let mut key = None;
+
+    for part in s.split('+').map(str::trim) {
+        match part.to_ascii_lowercase().as_str() {
+            "ctrl" | "control" | "cmd" | "command" => modifiers |= Modifiers::COMMAND,
+            "alt" | "option" => modifiers |= Modifiers::ALT,
+            "shift" => modifiers |= Modifiers::SHIFT,
+            _ => {
+                if key.is_some() {
+                    return None;
+                }
+                key = Some(Key::from_name(part).or_else(|| Key::from_name(&part.to_ascii_uppercase()))?);
+            }
+        }
+    }
+
+    Some(KeyboardShortcut::new(modifiers, key?))
+}
+
+/// Format a shortcut in the form accepted by [`parse_shortcut`]
+pub fn format_shortcut(shortcut: &KeyboardShortcut) -> String {
+    let mut parts = Vec::new();
+    if shortcut.modifiers.command || shortcut.modifiers.ctrl {
+        parts.push("Ctrl");
+    }
+    if shortcut.modifiers.alt {
+        parts.push("Alt");
+    }
+    if shortcut.modifiers.shift {
+        parts.push("Shift");
+    }
+    parts.push(shortcut.logical_key.name());
+    parts.join("+")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_shortcut() {
+        let s = parse_shortcut("Ctrl+Shift+s").unwrap();
+        assert_eq!(s.logical_key, Key::S);
+        assert!(s.modifiers.command && s.modifiers.shift && !s.modifiers.alt);
+
+        assert_eq!(parse_shortcut("F12").unwrap().logical_key, Key::F12);
+        assert!(parse_shortcut("Ctrl+").is_none());
+        assert!(parse_shortcut("A+B").is_none());
+    }
+
+    #[test]
+    fn test_format_roundtrip() {
+        for action in ShortcutAction::all() {
+            let shortcut = action.default_shortcut();
+            assert_eq!(parse_shortcut(&format_shortcut(&shortcut)), Some(shortcut));
+        }
+    }
+
+    #[test]
+    fn test_overrides() {
+        let mut overrides = BTreeMap::new();
+        overrides.insert("reset_camera".to_string(), "Home".to_string());
+        overrides.insert("screenshot".to_string(), "not a key".to_string());
+
+        let keymap = Keymap::from_settings(&overrides);
+        let find = |action| keymap.bindings.iter().find(|(a, _)| *a == action).unwrap().1;
+
+        assert_eq!(find(ShortcutAction::ResetCamera).logical_key, Key::Home);
+        assert_eq!(find(ShortcutAction::Screenshot), ShortcutAction::Screenshot.default_shortcut());
+    }
+
+    #[test]
+    fn test_conflicts() {
+        // Delete removes splats or the focused job, never both
+        let keymap = Keymap::default();
+        assert!(keymap.conflicts().is_empty());
+        let press_delete = |queue_focused| {
+            let input = egui::RawInput {
+                events: vec![egui::Event::Key {
+                    key: Key::Delete,
+                    physical_key: None,
+                    pressed: true,
+                    repeat: false,
+                    modifiers: Modifiers::NONE,
+                }],
+                ..Default::default()
+            };
+            let mut fired = Vec::new();
+            let _ = Context::default().run(input, |ctx| fired = keymap.poll(ctx, queue_focused));
+            fired
+        };
+        assert_eq!(press_delete(false), vec![ShortcutAction::DeleteSelection]);
+        assert_eq!(press_delete(true), vec![ShortcutAction::DeleteJob]);
+
+        let mut overrides = BTreeMap::new();
+        overrides.insert("delete_job".to_string(), "R".to_string());
+        let conflicts = Keymap::from_settings(&overrides).conflicts();
+        assert_eq!(conflicts.len(), 1);
+        assert!(conflicts[0] == (ShortcutAction::ResetCamera, ShortcutAction::DeleteJob)
+            || conflicts[0] == (ShortcutAction::DeleteJob, ShortcutAction::ResetCamera));
+
+        // Same scope, same key
+        overrides.clear();
+        overrides.insert("reset_camera".to_string(), "F12".to_string());
+        assert_eq!(Keymap::from_settings(&overrides).conflicts().len(), 1);
+    }
+}
\ No newline at end of file
@@ -0,0 +1,39 @@
+use base64::Engine;
+use gj_core::gaussian_cloud::GaussianCloud;
+
+/// Single-file WebGL2 viewer; the placeholders are filled in by [`viewer_html`]
+const TEMPLATE: &str = include_str!("web_viewer.html");
+
+/// A self-contained HTML page showing `cloud`, embedded as base64 `.splat`
+/// data, that opens in any modern browser without the app
+pub fn viewer_html(cloud: &GaussianCloud, title: &str) -> String {
+    let data = base64::engine::general_purpose::STANDARD.encode(cloud.to_splat());
+    TEMPLATE
+        .replace("{{SPLAT_BASE64}}", &data)
+        .replace("{{TITLE}}", &escape_html(title))
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_viewer_embeds_cloud() {
+        let mut cloud = GaussianCloud::new();
+        cloud.add_gaussian([0.0; 3], [0.1; 3], [1.0, 0.0, 0.0, 0.0], [1.0; 3], 1.0);
+
+        let html = viewer_html(&cloud, "<b>cat</b>");
+        let encoded = base64::engine::general_purpose::STANDARD.encode(cloud.to_splat());
+
+        assert!(html.contains(&encoded));
+        assert!(html.contains("&lt;b&gt;cat&lt;/b&gt;"));
+        assert!(!html.contains("{{"));
+    }
+}
@@ -1,86 +1,177 @@
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::mpsc::{channel, Sender, Receiver};
 use std::thread::{self, JoinHandle};
-use std::time::Duration;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 use image::RgbaImage;
 use gj_core::gaussian_cloud::GaussianCloud;
-use gj_core::Model3D;
-use serde::{Deserialize, Serialize};
-use gj_core::error::Error;
+use crate::content_filter::{ContentFilter, Verdict};
+use crate::error::AppError;
+use crate::generator::{GenerationBackend, HttpBackend, ModelList, RemoteState, RoutingBackend};
+use crate::settings::WorkerEndpoint;
+use crate::jobs::JobInputs;
+use crate::telemetry::JobTrace;
 
 pub enum WorkerCommand {
     GenerateFromImages(Vec<RgbaImage>),
-    GenerateFromPrompt { prompt: String, model: Model3D },
+    GenerateFromPrompt { job_id: String, inputs: Box<JobInputs> },
+    #[allow(dead_code)]
     CheckStatus(String), // Check job status by ID
+    ListModels,
+    /// Check the prompts of later jobs with this filter
+    SetContentFilter(Box<ContentFilter>),
     Shutdown,
 }
 
 pub enum WorkerResponse {
-    Success(GaussianCloud),
-    Error(String),
+    Success { cloud: Box<GaussianCloud>, path: PathBuf, latent: Option<String>, samples: Vec<PathBuf> },
+    Error(AppError),
     Progress(f32),
     Status(String),
+    /// The worker picked up the job with this local id
+    JobStarted(String),
+    /// Submitting the active job failed for now; trying again after a pause
+    Retrying { attempt: u32, error: AppError },
     JobSubmitted(String), // Job ID
+    /// The backend lost track of the active job; it won't be polled any more
+    JobStuck,
+    /// New service log lines for the active job
+    Log(Vec<String>),
+    /// Intermediate render of the active job
+    Preview(RgbaImage),
+    /// Models the backend can run
+    Models(ModelList),
+    /// The backend speaks another protocol version; jobs will be refused
+    Incompatible(AppError),
 }
 
 pub struct InferenceWorker {
     pub(crate) command_tx: Sender<WorkerCommand>,
     pub(crate) response_rx: Receiver<WorkerResponse>,
+    cancel_requested: Arc<AtomicBool>,
     thread_handle: Option<JoinHandle<()>>,
 }
 
 impl InferenceWorker {
-    pub fn new() -> Self {
+    /// Worker for the generation services, writing results under
+    /// `output_dir`. Jobs are routed between services when there's more
+    /// than one.
+    pub fn new(endpoints: &[WorkerEndpoint], output_dir: PathBuf) -> Self {
+        let mut backends: Vec<(String, Box<dyn GenerationBackend>)> = endpoints
+            .iter()
+            .map(|e| {
+                let backend = HttpBackend::new(&e.url, output_dir.clone()).with_token(e.token.clone());
+                (e.name.clone(), Box::new(backend) as Box<dyn GenerationBackend>)
+            })
+            .collect();
+        if backends.len() == 1 {
+            let (_, backend) = backends.remove(0);
+            return Self::with_backend(backend);
+        }
+        Self::with_backend(Box::new(RoutingBackend::new(backends)))
+    }
+
+    pub fn with_backend(backend: Box<dyn GenerationBackend>) -> Self {
         let (cmd_tx, cmd_rx) = channel::<WorkerCommand>();
         let (resp_tx, resp_rx) = channel::<WorkerResponse>();
+        let cancel_requested = Arc::new(AtomicBool::new(false));
+        let cancel = cancel_requested.clone();
 
         let thread_handle = thread::spawn(move || {
+            let backend = backend.as_ref();
+            let mut filter = ContentFilter::default();
+
             // Worker loop
             loop {
                 match cmd_rx.recv() {
-                    Ok(WorkerCommand::GenerateFromImages(images)) => {
+                    Ok(WorkerCommand::GenerateFromImages(_images)) => {
                         let _ = resp_tx.send(WorkerResponse::Status("Processing images...".into()));
-                        let _ = resp_tx.send(WorkerResponse::Error(
+                        let _ = resp_tx.send(WorkerResponse::Error(AppError::GenerationFailed(
                             "Image-based generation not yet implemented with Shap-E. Use text prompts instead.".into()
-                        ));
+                        )));
                     }
 
-                    Ok(WorkerCommand::GenerateFromPrompt { prompt, model }) => {
+                    Ok(WorkerCommand::GenerateFromPrompt { job_id, inputs }) => {
+                        cancel.store(false, Ordering::SeqCst);
+                        let _ = resp_tx.send(WorkerResponse::JobStarted(job_id.clone()));
                         let _ = resp_tx.send(WorkerResponse::Status(
-                            format!("Submitting job to {} service...", model.name())
+                            format!("Submitting job to {} {}...", inputs.model().name(), backend.name())
                         ));
+                        log::info!(job_id = job_id.as_str(), model = inputs.model.as_str(), stage = "submit";
+                            "Submitting job {} to {}", job_id, backend.name());
+                        let mut trace = JobTrace::new(&job_id, &inputs.model);
+                        trace.stage("submit");
+
+                        if filter.is_active() {
+                            match filter.check(&inputs.prompt) {
+                                Verdict::Allow => {}
+                                Verdict::Warn(reason) => {
+                                    log::warn!(job_id = job_id.as_str(); "Content filter warning for job {}: {}", job_id, reason);
+                                    let _ = resp_tx.send(WorkerResponse::Status(
+                                        format!("⚠ Prompt {}; submitting anyway", reason)
+                                    ));
+                                }
+                                Verdict::Block(reason) => {
+                                    log::warn!(job_id = job_id.as_str(); "Content filter blocked job {}: {}", job_id, reason);
+                                    let e = AppError::Blocked(reason);
+                                    trace.finish(Some(&e));
+                                    let _ = resp_tx.send(WorkerResponse::Error(e));
+                                    continue;
+                                }
+                            }
+                        }
 
                         // Submit job and get job ID
-                        match submit_generation_job(&prompt, model) {
-                            Ok(job_id) => {
-                                let _ = resp_tx.send(WorkerResponse::JobSubmitted(job_id.clone()));
+                        match submit_with_retries(backend, &inputs, &resp_tx, &cancel) {
+                            Ok(remote_id) => {
+                                let _ = resp_tx.send(WorkerResponse::JobSubmitted(remote_id.clone()));
                                 let _ = resp_tx.send(WorkerResponse::Status(
-                                    format!("Job submitted (ID: {})", job_id)
+                                    format!("Job submitted (ID: {})", remote_id)
                                 ));
+                                let job = ActiveJob { id: &job_id, remote_id: &remote_id, model: &inputs.model };
+                                log::info!(job_id = job.id, model = job.model, stage = "dispatch", remote_id = job.remote_id;
+                                    "Job {} submitted as {}", job.id, job.remote_id);
+                                trace.stage("queued");
 
                                 // Poll for status
-                                if let Err(e) = poll_job_status(&job_id, &resp_tx) {
-                                    let _ = resp_tx.send(WorkerResponse::Error(
-                                        format!("Failed to poll job: {}", e)
-                                    ));
+                                let result = poll_job(backend, &job, &mut trace, &resp_tx, &cancel);
+                                trace.finish(result.as_ref().err());
+                                if let Err(e) = result {
+                                    let _ = resp_tx.send(WorkerResponse::Error(e));
                                 }
                             }
                             Err(e) => {
-                                let _ = resp_tx.send(WorkerResponse::Error(
-                                    format!("Failed to submit job: {}", e)
-                                ));
+                                trace.finish(Some(&e));
+                                let _ = resp_tx.send(WorkerResponse::Error(e));
                             }
                         }
                     }
 
-                    Ok(WorkerCommand::CheckStatus(job_id)) => {
-                        if let Err(e) = poll_job_status(&job_id, &resp_tx) {
-                            let _ = resp_tx.send(WorkerResponse::Error(
-                                format!("Failed to check status: {}", e)
-                            ));
+                    Ok(WorkerCommand::CheckStatus(remote_id)) => {
+                        let job = ActiveJob { id: &remote_id, remote_id: &remote_id, model: "" };
+                        let mut trace = JobTrace::new(&remote_id, "");
+                        if let Err(e) = poll_job(backend, &job, &mut trace, &resp_tx, &cancel) {
+                            let _ = resp_tx.send(WorkerResponse::Error(e));
                         }
                     }
 
+                    Ok(WorkerCommand::ListModels) => {
+                        match backend.list_models() {
+                            Ok(models) => {
+                                let _ = resp_tx.send(WorkerResponse::Models(models));
+                            }
+                            Err(e @ AppError::IncompatibleService(_)) => {
+                                let _ = resp_tx.send(WorkerResponse::Incompatible(e));
+                            }
+                            Err(e) => log::warn!("Could not list models from {}: {}", backend.name(), e),
+                        }
+                    }
+
+                    Ok(WorkerCommand::SetContentFilter(new_filter)) => {
+                        filter = *new_filter;
+                    }
+
                     Ok(WorkerCommand::Shutdown) => {
                         break;
                     }
@@ -95,20 +186,30 @@ impl InferenceWorker {
         Self {
             command_tx: cmd_tx,
             response_rx: resp_rx,
+            cancel_requested,
             thread_handle: Some(thread_handle),
         }
     }
 
-    pub fn send_images(&self, images: Vec<RgbaImage>) -> Result<(), String> {
+    pub fn send_prompt(&self, job_id: String, inputs: JobInputs) -> Result<(), String> {
         self.command_tx
-            .send(WorkerCommand::GenerateFromImages(images))
-            .map_err(|e| format!("Failed to send images to worker: {}", e))
+            .send(WorkerCommand::GenerateFromPrompt { job_id, inputs: Box::new(inputs) })
+            .map_err(|e| format!("Failed to send prompt to worker: {}", e))
     }
 
-    pub fn send_prompt(&self, prompt: String, model: Model3D) -> Result<(), String> {
-        self.command_tx
-            .send(WorkerCommand::GenerateFromPrompt { prompt, model })
-            .map_err(|e| format!("Failed to send prompt to worker: {}", e))
+    /// Check the prompts of jobs sent from now on with `filter`
+    pub fn set_content_filter(&self, filter: ContentFilter) {
+        let _ = self.command_tx.send(WorkerCommand::SetContentFilter(Box::new(filter)));
+    }
+
+    /// Ask the backend which models it can run; answered with [`WorkerResponse::Models`]
+    pub fn request_models(&self) {
+        let _ = self.command_tx.send(WorkerCommand::ListModels);
+    }
+
+    /// Cancel the job currently being processed
+    pub fn cancel_active(&self) {
+        self.cancel_requested.store(true, Ordering::SeqCst);
     }
 
     pub fn try_recv_response(&self) -> Option<WorkerResponse> {
@@ -129,164 +230,363 @@ impl Drop for InferenceWorker {
     }
 }
 
-// ============================================================================
-// API Client
-// ============================================================================
-
-#[derive(Serialize)]
-struct GenerateRequest {
-    prompt: String,
-    model: String,
-    guidance_scale: f32,
-    num_inference_steps: usize,
-}
-
-#[derive(Deserialize)]
-struct JobResponse {
-    job_id: String,
-    status: String,
+/// Submissions tried before giving up on an unreachable service
+const SUBMIT_ATTEMPTS: u32 = 6;
+/// Longest wait between submissions
+const MAX_RETRY_DELAY: Duration = Duration::from_secs(60);
+/// Longest nap between looks at the cancel flag while backing off
+const CANCEL_CHECK_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Wait before retry number `attempt` (from 1): `base` doubled per retry,
+/// capped, and scaled by 50-100% going by `jitter` in 0..1 so clients that
+/// lost the service together don't all come back at once
+fn backoff(base: Duration, attempt: u32, jitter: f64) -> Duration {
+    let delay = base.saturating_mul(1 << attempt.saturating_sub(1).min(16)).min(MAX_RETRY_DELAY);
+    delay.mul_f64(0.5 + jitter.clamp(0.0, 1.0) / 2.0)
 }
 
-#[derive(Deserialize)]
-struct JobStatusResponse {
-    job_id: String,
-    status: String,
-    progress: Option<f32>,
-    message: Option<String>,
-    result: Option<JobResult>,
-    error: Option<String>,
+/// Cheap jitter source; nothing here needs real randomness
+fn jitter() -> f64 {
+    let nanos = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().subsec_nanos();
+    nanos as f64 / 1e9
 }
 
-#[derive(Deserialize)]
-struct JobResult {
-    output_path: String,
-    model: String,
-    prompt: String,
+/// Sleep for `delay`, waking early to return true once `cancel` is set
+fn sleep_unless_cancelled(delay: Duration, cancel: &AtomicBool) -> bool {
+    let until = Instant::now() + delay;
+    loop {
+        if cancel.swap(false, Ordering::SeqCst) {
+            return true;
+        }
+        let left = until.saturating_duration_since(Instant::now());
+        if left.is_zero() {
+            return false;
+        }
+        thread::sleep(left.min(CANCEL_CHECK_INTERVAL));
+    }
 }
 
-/// Submit generation job and return job ID
-fn submit_generation_job(prompt: &str, model: Model3D) -> Result<String, String> {
-    let client = reqwest::blocking::Client::new();
-    let url = "http://127.0.0.1:5000/generate";
-
-    let request_body = GenerateRequest {
-        prompt: prompt.to_string(),
-        model: model.id().to_string(),
-        guidance_scale: 15.0,
-        num_inference_steps: 64,
-    };
-
-    let response = client
-        .post(url)
-        .json(&request_body)
-        .send()
-        .map_err(|e| format!("Failed to connect: {}. Make sure FastAPI service is running (cd python && docker-compose up)", e))?;
-
-    if !response.status().is_success() {
-        return Err(format!("Service returned error: {}", response.status()));
+/// Submit a job, retrying with backoff while the service is unreachable
+fn submit_with_retries(
+    backend: &dyn GenerationBackend,
+    inputs: &JobInputs,
+    resp_tx: &Sender<WorkerResponse>,
+    cancel: &AtomicBool,
+) -> Result<String, AppError> {
+    let mut attempt = 1;
+    loop {
+        match backend.submit(inputs) {
+            Err(e) if e.is_transient() && attempt < SUBMIT_ATTEMPTS => {
+                let delay = backoff(backend.retry_delay(), attempt, jitter());
+                attempt += 1;
+                log::warn!("Submitting to {} failed, retrying in {:.1}s: {}", backend.name(), delay.as_secs_f32(), e);
+                let _ = resp_tx.send(WorkerResponse::Retrying { attempt, error: e });
+
+                if sleep_unless_cancelled(delay, cancel) {
+                    return Err(AppError::Cancelled("Cancelled by user".into()));
+                }
+            }
+            result => return result,
+        }
     }
-
-    let result: JobResponse = response
-        .json()
-        .map_err(|e| format!("Failed to parse response: {}", e))?;
-
-    Ok(result.job_id)
 }
 
-/// Poll job status until complete or failed
-fn poll_job_status(job_id: &str, resp_tx: &Sender<WorkerResponse>) -> Result<(), String> {
-    let client = reqwest::blocking::Client::new();
-    let url = format!("http://127.0.0.1:5000/status/{}", job_id);
+/// A submitted job, named in the `job_id`, `model` and `stage` fields of
+/// its log records for `--log-format json`
+struct ActiveJob<'a> {
+    /// Local id
+    id: &'a str,
+    /// Id on the service
+    remote_id: &'a str,
+    model: &'a str,
+}
 
+/// Poll a submitted job until it completes, fails or is cancelled
+fn poll_job(
+    backend: &dyn GenerationBackend,
+    job: &ActiveJob,
+    trace: &mut JobTrace,
+    resp_tx: &Sender<WorkerResponse>,
+    cancel: &AtomicBool,
+) -> Result<(), AppError> {
     let mut last_progress = 0.0;
+    let mut last_message: Option<String> = None;
+    let mut log_lines = 0;
+    let mut last_preview: Option<Vec<u8>> = None;
+    let mut queued_since = Instant::now();
+    let job_id = job.remote_id;
 
     loop {
-        thread::sleep(Duration::from_secs(2)); // Poll every 2 seconds
+        thread::sleep(backend.poll_interval());
 
-        let response = client
-            .get(&url)
-            .send()
-            .map_err(|e| format!("Failed to check status: {}", e))?;
-
-        if !response.status().is_success() {
-            return Err(format!("Status check failed: {}", response.status()));
+        if cancel.swap(false, Ordering::SeqCst) {
+            log::info!(job_id = job.id, model = job.model, stage = "cancel"; "Cancelling job {}", job_id);
+            backend.cancel(job_id)?;
+            return Err(AppError::Cancelled("Cancelled by user".into()));
         }
 
-        let status: JobStatusResponse = response
-            .json()
-            .map_err(|e| format!("Failed to parse status: {}", e))?;
+        let status = backend.status(job_id)?;
+
+        // Fetched after the status so a failure's last words are included
+        match backend.logs(job_id, log_lines) {
+            Ok(lines) if !lines.is_empty() => {
+                log_lines += lines.len();
+                let _ = resp_tx.send(WorkerResponse::Log(lines));
+            }
+            Ok(_) => {}
+            Err(e) => log::debug!("Could not fetch log for job {}: {}", job_id, e),
+        }
 
         // Update progress if changed
-        if let Some(progress) = status.progress {
-            if progress != last_progress {
-                let _ = resp_tx.send(WorkerResponse::Progress(progress));
-                last_progress = progress;
+        if let Some(progress) = status.progress
+            && progress != last_progress
+        {
+            let _ = resp_tx.send(WorkerResponse::Progress(progress));
+            last_progress = progress;
+        }
+
+        if status.state == RemoteState::Running {
+            match backend.preview(job_id) {
+                Ok(Some(png)) if last_preview.as_ref() != Some(&png) => {
+                    match image::load_from_memory(&png) {
+                        Ok(preview) => {
+                            let _ = resp_tx.send(WorkerResponse::Preview(preview.to_rgba8()));
+                        }
+                        Err(e) => log::debug!("Bad preview for job {}: {}", job_id, e),
+                    }
+                    last_preview = Some(png);
+                }
+                Ok(_) => {}
+                Err(e) => log::debug!("Could not fetch preview for job {}: {}", job_id, e),
             }
         }
 
         // Update status message
         if let Some(ref message) = status.message {
+            if last_message.as_ref() != Some(message) {
+                log::info!(job_id = job.id, model = job.model, stage = "progress", progress = status.progress;
+                    "Job {} [{:?}]: {}", job_id, status.state, message);
+                last_message = Some(message.clone());
+            }
             let _ = resp_tx.send(WorkerResponse::Status(message.clone()));
         }
 
-        match status.status.as_str() {
-            "SUCCESS" => {
-                if let Some(result) = status.result {
-                    let _ = resp_tx.send(WorkerResponse::Status(
-                        "Loading generated Gaussians...".into()
-                    ));
-
-                    let output_path = &result.output_path;
-                    let host_path = if output_path.starts_with("/app/outputs/") {
-                        // Docker: /app/outputs/file.ply -> outputs/file.ply
-                        PathBuf::from(output_path.replace("/app/outputs/", "outputs/"))
-                    } else if output_path.starts_with("../outputs/") {
-                        // Local: ../outputs/file.ply -> outputs/file.ply
-                        PathBuf::from(output_path.replace("../outputs/", "outputs/"))
-                    } else if output_path.starts_with("outputs/") {
-                        // Already correct
-                        PathBuf::from(output_path.clone())
-                    } else {
-                        // Unknown format - try to extract just the filename
-                        let filename = std::path::Path::new(&output_path)
-                            .file_name()
-                            .ok_or_else(|| Error::InvalidConfig("Invalid output path".to_string())).unwrap();
-                        PathBuf::from("outputs").join(filename)
-                    };
-
-                    // Load the PLY file
-                    match gj_core::gaussian_cloud::GaussianCloud::from_ply(&host_path) {
-                        Ok(cloud) => {
-                            let _ = resp_tx.send(WorkerResponse::Status(
-                                format!("Loaded {} Gaussians", cloud.count)
-                            ));
-                            let _ = resp_tx.send(WorkerResponse::Success(cloud));
-                            return Ok(());
-                        }
-                        Err(e) => {
-                            return Err(format!("Failed to load .ply: {}", e));
-                        }
-                    }
-                } else {
-                    return Err("Job succeeded but no result path returned".into());
+        match status.state {
+            RemoteState::Succeeded { path, latent, samples } => {
+                trace.stage("download");
+                for output in std::iter::once(&path).chain(&samples) {
+                    fetch_output(backend, job, output, resp_tx, cancel)?;
                 }
+
+                let _ = resp_tx.send(WorkerResponse::Status(
+                    "Loading generated Gaussians...".into()
+                ));
+
+                // Load the PLY file
+                trace.stage("load");
+                log::info!(job_id = job.id, model = job.model, stage = "load";
+                    "Job {} finished, loading {}", job_id, path.display());
+                let cloud = GaussianCloud::from_ply_cached(&path)?;
+                let _ = resp_tx.send(WorkerResponse::Status(
+                    format!("Loaded {} Gaussians", cloud.count)
+                ));
+                let _ = resp_tx.send(WorkerResponse::Success { cloud: Box::new(cloud), path, latent, samples });
+                return Ok(());
             }
 
-            "FAILURE" => {
-                let error_msg = status.error.unwrap_or_else(|| "Unknown error".into());
-                let _ = resp_tx.send(WorkerResponse::Error(error_msg.clone()));
-                return Err(error_msg);
+            RemoteState::Failed(err) => {
+                log::error!(job_id = job.id, model = job.model, stage = "failed"; "Job {} failed: {}", job_id, err);
+                return Err(err);
             }
 
-            "PENDING" | "STARTED" | "RETRY" => {
+            RemoteState::Cancelled => {
+                return Err(AppError::Cancelled("Cancelled on the service".into()));
+            }
+
+            RemoteState::Unknown if queued_since.elapsed() >= backend.stuck_after() => {
+                log::warn!(job_id = job.id, model = job.model, stage = "stuck";
+                    "Job {} has no record on {}, giving up on it", job_id, backend.name());
+                let _ = resp_tx.send(WorkerResponse::JobStuck);
+                return Ok(());
+            }
+
+            RemoteState::Queued | RemoteState::Unknown => {
                 // Continue polling
                 continue;
             }
 
-            _ => {
-                // Unknown status, continue polling
+            RemoteState::Running => {
+                trace.stage("running");
+                queued_since = Instant::now();
                 continue;
             }
         }
     }
+}
+
+/// Bring an output of a finished job to this machine if the backend wrote
+/// it elsewhere, reporting how far the copy has got
+fn fetch_output(
+    backend: &dyn GenerationBackend,
+    job: &ActiveJob,
+    path: &Path,
+    resp_tx: &Sender<WorkerResponse>,
+    cancel: &AtomicBool,
+) -> Result<(), AppError> {
+    let name = path.file_name().map_or_else(|| path.display().to_string(), |n| n.to_string_lossy().into_owned());
+    let mut last_percent = None;
+    let mut progress = |fraction: f32| {
+        let percent = (fraction * 100.0) as u32;
+        if last_percent != Some(percent) {
+            last_percent = Some(percent);
+            let _ = resp_tx.send(WorkerResponse::Status(format!("Downloading {}... {}%", name, percent)));
+        }
+    };
+
+    let result = backend.fetch_output(job.remote_id, path, &mut progress, cancel);
+    if let Err(e) = &result {
+        log::warn!(job_id = job.id, model = job.model, stage = "download";
+            "Fetching {} for job {} failed: {}", path.display(), job.remote_id, e);
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::generator::mock::MockBackend;
+    use super::*;
+
+    /// Collect responses until the job finishes one way or the other
+    fn run_to_end(worker: &InferenceWorker) -> Vec<WorkerResponse> {
+        let deadline = Instant::now() + Duration::from_secs(5);
+        let mut responses = Vec::new();
+
+        while Instant::now() < deadline {
+            match worker.response_rx.recv_timeout(Duration::from_millis(100)) {
+                Ok(r) => {
+                    let done = matches!(
+                        r,
+                        WorkerResponse::Success { .. } | WorkerResponse::Error(_) | WorkerResponse::JobStuck
+                    );
+                    responses.push(r);
+                    if done {
+                        break;
+                    }
+                }
+                Err(_) => continue,
+            }
+        }
+        responses
+    }
+
+    #[test]
+    fn test_worker_loads_result_from_backend() {
+        let mut cloud = GaussianCloud::new();
+        cloud.add_gaussian([0.0; 3], [0.1; 3], [1.0, 0.0, 0.0, 0.0], [1.0, 0.0, 0.0], 1.0);
+        let path = std::env::temp_dir().join(format!("gj_worker_test_{}.ply", std::process::id()));
+        std::fs::write(&path, cloud.to_ply().unwrap()).unwrap();
+
+        let backend = MockBackend::new(vec![
+            RemoteState::Queued,
+            RemoteState::Running,
+            RemoteState::Succeeded { path: path.clone(), latent: None, samples: Vec::new() },
+        ]);
+        let worker = InferenceWorker::with_backend(Box::new(backend));
+        worker.send_prompt("job-1".into(), JobInputs::default()).unwrap();
+
+        let responses = run_to_end(&worker);
+        assert!(matches!(responses.first(), Some(WorkerResponse::JobStarted(id)) if id == "job-1"));
+        assert!(matches!(responses.last(), Some(WorkerResponse::Success { cloud, .. }) if cloud.count == 1));
+
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_worker_downloads_missing_result() {
+        let mut cloud = GaussianCloud::new();
+        cloud.add_gaussian([0.0; 3], [0.1; 3], [1.0, 0.0, 0.0, 0.0], [1.0, 0.0, 0.0], 1.0);
+        let path = std::env::temp_dir().join(format!("gj_worker_download_{}.ply", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+
+        let backend = MockBackend::new(vec![
+            RemoteState::Running,
+            RemoteState::Succeeded { path: path.clone(), latent: None, samples: Vec::new() },
+        ])
+        .with_output(cloud.to_ply().unwrap());
+        let worker = InferenceWorker::with_backend(Box::new(backend));
+        worker.send_prompt("job-1".into(), JobInputs::default()).unwrap();
+
+        let responses = run_to_end(&worker);
+        let downloading: Vec<_> = responses.iter()
+            .filter_map(|r| match r {
+                WorkerResponse::Status(s) if s.starts_with("Downloading") => Some(s.as_str()),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(downloading.len(), 2);
+        assert!(downloading[1].ends_with("100%"), "{}", downloading[1]);
+        assert!(matches!(responses.last(), Some(WorkerResponse::Success { cloud, .. }) if cloud.count == 1));
+
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_worker_reports_backend_failure() {
+        let backend = MockBackend::new(vec![RemoteState::Failed(AppError::OutOfVram("CUDA OOM".into()))]);
+        let worker = InferenceWorker::with_backend(Box::new(backend));
+        worker.send_prompt("job-1".into(), JobInputs::default()).unwrap();
+
+        let responses = run_to_end(&worker);
+        assert!(matches!(responses.last(), Some(WorkerResponse::Error(AppError::OutOfVram(_)))));
+    }
+
+    #[test]
+    fn test_worker_retries_unreachable_service() {
+        let backend = MockBackend::new(vec![RemoteState::Failed(AppError::OutOfVram("CUDA OOM".into()))])
+            .with_submit_failures(2);
+        let worker = InferenceWorker::with_backend(Box::new(backend));
+        worker.send_prompt("job-1".into(), JobInputs::default()).unwrap();
+
+        let responses = run_to_end(&worker);
+        let attempts: Vec<u32> = responses.iter()
+            .filter_map(|r| match r {
+                WorkerResponse::Retrying { attempt, .. } => Some(*attempt),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(attempts, vec![2, 3]);
+        assert!(responses.iter().any(|r| matches!(r, WorkerResponse::JobSubmitted(_))));
+    }
+
+    #[test]
+    fn test_backoff() {
+        let base = Duration::from_secs(1);
+        assert_eq!(backoff(base, 1, 1.0), Duration::from_secs(1));
+        assert_eq!(backoff(base, 3, 1.0), Duration::from_secs(4));
+        assert_eq!(backoff(base, 3, 0.0), Duration::from_secs(2));
+        assert_eq!(backoff(base, 30, 1.0), MAX_RETRY_DELAY);
+    }
+
+    #[test]
+    fn test_sleep_unless_cancelled() {
+        let cancel = AtomicBool::new(false);
+        assert!(!sleep_unless_cancelled(Duration::ZERO, &cancel));
+
+        // A cancel cuts even the longest backoff short
+        cancel.store(true, Ordering::SeqCst);
+        let start = Instant::now();
+        assert!(sleep_unless_cancelled(MAX_RETRY_DELAY, &cancel));
+        assert!(start.elapsed() < Duration::from_secs(1));
+        assert!(!cancel.load(Ordering::SeqCst));
+    }
+
+    #[test]
+    fn test_worker_gives_up_on_unknown_job() {
+        let backend = MockBackend::new(vec![RemoteState::Queued, RemoteState::Unknown]);
+        let worker = InferenceWorker::with_backend(Box::new(backend));
+        worker.send_prompt("job-1".into(), JobInputs::default()).unwrap();
+
+        let responses = run_to_end(&worker);
+        assert!(matches!(responses.last(), Some(WorkerResponse::JobStuck)));
+    }
 }
\ No newline at end of file
@@ -0,0 +1,230 @@
+//! Rigid registration of two clouds with point-to-point ICP on the splat
+//! centers, for comparing generations of the same object.
+
+use rayon::prelude::*;
+use crate::error::{Error, Result};
+use crate::gaussian_cloud::GaussianCloud;
+
+/// Iteration limit for [`GaussianCloud::align_to`]
+const MAX_ITERATIONS: usize = 50;
+
+/// Source centers matched per iteration; larger clouds are subsampled
+const MAX_SAMPLES: usize = 5000;
+
+/// Stop once the RMS error improves by less than this fraction of the
+/// target's size
+const CONVERGENCE_TOLERANCE: f32 = 1e-5;
+
+/// Rigid transform mapping one cloud onto another
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Alignment {
+    /// Quaternion (w, x, y, z), applied before the translation
+    pub rotation: [f32; 4],
+    pub translation: [f32; 3],
+    /// Root mean square distance from the sampled centers to their nearest
+    /// target centers after alignment
+    pub rms_error: f32,
+    pub iterations: usize,
+    /// Whether the error settled before [`MAX_ITERATIONS`]
+    pub converged: bool,
+}
+
+impl Alignment {
+    pub fn transform_point(&self, point: [f32; 3]) -> [f32; 3] {
+        let rotated = rotate(self.rotation, point);
+        std::array::from_fn(|i| rotated[i] + self.translation[i])
+    }
+}
+
+/// Rotate `v` by the unit quaternion `q` (w, x, y, z)
+pub(crate) fn rotate([w, x, y, z]: [f32; 4], v: [f32; 3]) -> [f32; 3] {
+    let m = [
+        [1.0 - 2.0 * (y * y + z * z), 2.0 * (x * y - w * z), 2.0 * (x * z + w * y)],
+        [2.0 * (x * y + w * z), 1.0 - 2.0 * (x * x + z * z), 2.0 * (y * z - w * x)],
+        [2.0 * (x * z - w * y), 2.0 * (y * z + w * x), 1.0 - 2.0 * (x * x + y * y)],
+    ];
+    m.map(|row| row[0] * v[0] + row[1] * v[1] + row[2] * v[2])
+}
+
+/// Hamilton product `a * b`, the rotation `b` followed by `a`
+pub(crate) fn multiply([aw, ax, ay, az]: [f32; 4], [bw, bx, by, bz]: [f32; 4]) -> [f32; 4] {
+    [
+        aw * bw - ax * bx - ay * by - az * bz,
+        aw * bx + ax * bw + ay * bz - az * by,
+        aw * by - ax * bz + ay * bw + az * bx,
+        aw * bz + ax * by - ay * bx + az * bw,
+    ]
+}
+
+fn centroid(points: &[[f32; 3]]) -> [f64; 3] {
+    let mut sum = [0.0f64; 3];
+    for p in points {
+        for (s, &c) in sum.iter_mut().zip(p) {
+            *s += c as f64;
+        }
+    }
+    sum.map(|s| s / points.len().max(1) as f64)
+}
+
+/// Eigenvalues and eigenvectors (as columns) of a symmetric matrix, by
+/// cyclic Jacobi rotations
+pub(crate) fn symmetric_eigen<const N: usize>(mut a: [[f64; N]; N]) -> ([f64; N], [[f64; N]; N]) {
+    let mut v = [[0.0; N]; N];
+    for (i, row) in v.iter_mut().enumerate() {
+        row[i] = 1.0;
+    }
+
+    for _ in 0..50 {
+        let off: f64 = (0..N).flat_map(|p| (p + 1..N).map(move |q| (p, q)))
+            .map(|(p, q)| a[p][q] * a[p][q])
+            .sum();
+        if off < 1e-20 {
+            break;
+        }
+
+        for p in 0..N {
+            for q in p + 1..N {
+                if a[p][q].abs() < 1e-30 {
+                    continue;
+                }
+
+                let theta = (a[q][q] - a[p][p]) / (2.0 * a[p][q]);
+                let t = theta.signum() / (theta.abs() + (theta * theta + 1.0).sqrt());
+                let c = 1.0 / (t * t + 1.0).sqrt();
+                let s = t * c;
+
+                for row in &mut a {
+                    let (kp, kq) = (row[p], row[q]);
+                    row[p] = c * kp - s * kq;
+                    row[q] = s * kp + c * kq;
+                }
+                let (row_p, row_q) = (a[p], a[q]);
+                a[p] = std::array::from_fn(|k| c * row_p[k] - s * row_q[k]);
+                a[q] = std::array::from_fn(|k| s * row_p[k] + c * row_q[k]);
+                for row in &mut v {
+                    let (kp, kq) = (row[p], row[q]);
+                    row[p] = c * kp - s * kq;
+                    row[q] = s * kp + c * kq;
+                }
+            }
+        }
+    }
+
+    (std::array::from_fn(|i| a[i][i]), v)
+}
+
+/// Best rigid transform taking `source[i]` to `target[i]` in the least
+/// squares sense (Horn's closed-form quaternion method)
+fn fit_rigid(source: &[[f32; 3]], target: &[[f32; 3]]) -> ([f32; 4], [f32; 3]) {
+    let mu_s = centroid(source);
+    let mu_t = centroid(target);
+
+    // Cross-covariance s[a][b] = Σ source'_a · target'_b
+    let mut s = [[0.0f64; 3]; 3];
+    for (p, q) in source.iter().zip(target) {
+        let p: [f64; 3] = std::array::from_fn(|i| p[i] as f64 - mu_s[i]);
+        let q: [f64; 3] = std::array::from_fn(|i| q[i] as f64 - mu_t[i]);
+        for (row, pa) in s.iter_mut().zip(p) {
+            for (cell, qb) in row.iter_mut().zip(q) {
+                *cell += pa * qb;
+            }
+        }
+    }
+
+    let [[sxx, sxy, sxz], [syx, syy, syz], [szx, szy, szz]] = s;
+    let n = [
+        [sxx + syy + szz, syz - szy, szx - sxz, sxy - syx],
+        [syz - szy, sxx - syy - szz, sxy + syx, szx + sxz],
+        [szx - sxz, sxy + syx, -sxx + syy - szz, syz + szy],
+        [sxy - syx, szx + sxz, syz + szy, -sxx - syy + szz],
+    ];
+
+    // The optimal rotation is the eigenvector of the largest eigenvalue
+    let (values, vectors) = symmetric_eigen(n);
+    let best = (0..4).max_by(|&a, &b| values[a].total_cmp(&values[b])).unwrap_or(0);
+    let q: [f64; 4] = std::array::from_fn(|i| vectors[i][best]);
+    let length = q.iter().map(|c| c * c).sum::<f64>().sqrt();
+    let rotation = if length > 1e-12 { q.map(|c| (c / length) as f32) } else { [1.0, 0.0, 0.0, 0.0] };
+
+    let rotated = rotate(rotation, mu_s.map(|c| c as f32));
+    let translation = std::array::from_fn(|i| mu_t[i] as f32 - rotated[i]);
+    (rotation, translation)
+}
+
+impl GaussianCloud {
+    /// Find the rigid transform that best maps this cloud's centers onto
+    /// `target`'s with point-to-point ICP. The search starts by matching
+    /// centroids, so the clouds should already be roughly oriented alike.
+    ///
+    /// The cloud itself is unchanged; see [`GaussianCloud::apply_alignment`].
+    pub fn align_to(&self, target: &GaussianCloud) -> Result<Alignment> {
+        if self.count == 0 || target.count == 0 {
+            return Err(Error::InvalidGaussianCloud("Cannot align an empty cloud".to_string()));
+        }
+
+        let stride = self.count.div_ceil(MAX_SAMPLES);
+        let samples: Vec<[f32; 3]> = self.positions.iter()
+            .step_by(stride)
+            .copied()
+            .filter(|p| p.iter().all(|c| c.is_finite()))
+            .collect();
+        if samples.is_empty() {
+            return Err(Error::InvalidGaussianCloud("No finite splat positions".to_string()));
+        }
+
+        let target_size = target.bounds().size();
+        let tolerance = CONVERGENCE_TOLERANCE * target_size[0].max(target_size[1]).max(target_size[2]);
+        let octree = target.octree();
+
+        let mu_s = centroid(&samples);
+        let mu_t = centroid(&target.positions);
+        let mut alignment = Alignment {
+            rotation: [1.0, 0.0, 0.0, 0.0],
+            translation: std::array::from_fn(|i| (mu_t[i] - mu_s[i]) as f32),
+            rms_error: f32::INFINITY,
+            iterations: 0,
+            converged: false,
+        };
+
+        for iteration in 1..=MAX_ITERATIONS {
+            let matches: Vec<([f32; 3], f32)> = samples.par_iter()
+                .filter_map(|&p| {
+                    let (index, distance) = octree.nearest(target, alignment.transform_point(p))?;
+                    Some((target.positions[index], distance))
+                })
+                .collect();
+
+            let rms = (matches.iter().map(|(_, d)| d * d).sum::<f32>() / matches.len().max(1) as f32).sqrt();
+            let improvement = alignment.rms_error - rms;
+            alignment.rms_error = rms;
+            alignment.iterations = iteration;
+            if improvement.abs() < tolerance {
+                alignment.converged = true;
+                break;
+            }
+
+            let matched: Vec<[f32; 3]> = matches.into_iter().map(|(q, _)| q).collect();
+            (alignment.rotation, alignment.translation) = fit_rigid(&samples, &matched);
+        }
+
+        Ok(alignment)
+    }
+
+    /// Move and rotate every splat by `alignment`
+    pub fn apply_alignment(&mut self, alignment: &Alignment) {
+        self.transform(alignment.rotation, alignment.translation);
+    }
+
+    /// Rotate every splat about the origin by the unit quaternion `rotation`
+    /// (w, x, y, z), then move it by `translation`
+    pub fn transform(&mut self, rotation: [f32; 4], translation: [f32; 3]) {
+        for position in &mut self.positions {
+            let rotated = rotate(rotation, *position);
+            *position = std::array::from_fn(|i| rotated[i] + translation[i]);
+        }
+        for r in &mut self.rotations {
+            *r = multiply(rotation, *r);
+        }
+        self.invalidate_octree();
+    }
+}
@@ -0,0 +1,116 @@
+//! Copies of a cloud laid out on a grid, around a circle or scattered at
+//! random, for quickly dressing a generated prop into a test level.
+
+use std::f32::consts::TAU;
+use crate::alignment::rotate;
+use crate::gaussian_cloud::GaussianCloud;
+
+/// How the copies are laid out on the ground plane (x/z), around the
+/// original's position
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ArrayLayout {
+    /// `columns` along x by `rows` along z, `spacing` apart
+    Grid { columns: usize, rows: usize, spacing: f32 },
+    /// `count` copies around a circle of `radius`, each turned to face out
+    Circle { count: usize, radius: f32 },
+    /// `count` copies at random spots in a square `extent` wide, turned at
+    /// random; the same `seed` gives the same layout
+    Scatter { count: usize, extent: f32, seed: u64 },
+}
+
+/// Where one copy goes: turned by `yaw` radians about the vertical axis
+/// through the original's center, then moved by `offset`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Placement {
+    pub yaw: f32,
+    pub offset: [f32; 3],
+}
+
+impl ArrayLayout {
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::Grid { .. } => "Grid",
+            Self::Circle { .. } => "Circle",
+            Self::Scatter { .. } => "Scatter",
+        }
+    }
+
+    pub fn placements(&self) -> Vec<Placement> {
+        match *self {
+            Self::Grid { columns, rows, spacing } => {
+                let centered = |i: usize, n: usize| (i as f32 - (n as f32 - 1.0) / 2.0) * spacing;
+                (0..rows)
+                    .flat_map(|row| (0..columns).map(move |column| Placement {
+                        yaw: 0.0,
+                        offset: [centered(column, columns), 0.0, centered(row, rows)],
+                    }))
+                    .collect()
+            }
+            Self::Circle { count, radius } => (0..count)
+                .map(|i| {
+                    let angle = i as f32 / count as f32 * TAU;
+                    Placement { yaw: angle, offset: [radius * angle.sin(), 0.0, radius * angle.cos()] }
+                })
+                .collect(),
+            Self::Scatter { count, extent, seed } => {
+                let mut rng = SplitMix64(seed);
+                (0..count)
+                    .map(|_| {
+                        let x = (rng.next_f32() - 0.5) * extent;
+                        let z = (rng.next_f32() - 0.5) * extent;
+                        Placement { yaw: rng.next_f32() * TAU, offset: [x, 0.0, z] }
+                    })
+                    .collect()
+            }
+        }
+    }
+}
+
+/// Small seeded generator, so scatters are reproducible without a
+/// dependency
+struct SplitMix64(u64);
+
+impl SplitMix64 {
+    fn next_f32(&mut self) -> f32 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^= z >> 31;
+        // Top 24 bits, exactly representable in [0, 1)
+        (z >> 40) as f32 / (1u64 << 24) as f32
+    }
+}
+
+impl GaussianCloud {
+    /// A copy of the cloud at every placement, merged into one cloud.
+    /// View-dependent color (SH) isn't rotated with the copies.
+    pub fn arrayed(&self, placements: &[Placement]) -> GaussianCloud {
+        let center = self.bounds().center();
+        let mut arrayed = GaussianCloud::with_capacity(self.count * placements.len());
+        if self.sh_coefficients.is_some() {
+            arrayed.sh_coefficients = Some(Vec::with_capacity(self.count * placements.len()));
+        }
+
+        for placement in placements {
+            let half = placement.yaw / 2.0;
+            let rotation = [half.cos(), 0.0, half.sin(), 0.0];
+            // Turn about the center rather than the origin
+            let turned = rotate(rotation, center);
+            let translation = std::array::from_fn(|i| center[i] - turned[i] + placement.offset[i]);
+
+            let mut copy = self.clone();
+            copy.transform(rotation, translation);
+            arrayed.count += copy.count;
+            arrayed.positions.extend(copy.positions);
+            arrayed.scales.extend(copy.scales);
+            arrayed.rotations.extend(copy.rotations);
+            arrayed.colors.extend(copy.colors);
+            arrayed.opacity.extend(copy.opacity);
+            if let (Some(all), Some(sh)) = (&mut arrayed.sh_coefficients, copy.sh_coefficients) {
+                all.extend(sh);
+            }
+        }
+        arrayed
+    }
+}
@@ -0,0 +1,149 @@
+//! Sidecar `.gjc` cache of parsed clouds.
+//!
+//! Stored next to the PLY it was built from, holding the SoA buffers with
+//! positions as f32 and everything else as f16 / u8, so re-opening a file
+//! is a straight copy instead of a parse. The cache records the PLY's size,
+//! modification time and a content hash; a size/mtime match is trusted, and
+//! otherwise the hash decides whether the PLY really changed.
+
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+use half::f16;
+use rayon::prelude::*;
+use crate::error::Result;
+use crate::gaussian_cloud::GaussianCloud;
+
+const MAGIC: &[u8; 4] = b"GJC1";
+/// Magic, count, PLY length, PLY mtime, PLY hash
+const HEADER_SIZE: usize = 4 + 8 * 4;
+
+/// Identity of the PLY a cache was built from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Source {
+    len: u64,
+    /// Nanoseconds since the Unix epoch
+    mtime: u64,
+}
+
+impl Source {
+    fn of(path: &Path) -> Option<Self> {
+        let meta = std::fs::metadata(path).ok()?;
+        let mtime = meta.modified().ok()?.duration_since(UNIX_EPOCH).ok()?.as_nanos() as u64;
+        Some(Self { len: meta.len(), mtime })
+    }
+}
+
+/// Location of the cache for `ply_path`
+pub fn cache_path(ply_path: &Path) -> PathBuf {
+    ply_path.with_extension("gjc")
+}
+
+/// 64-bit FNV-1a; stable across builds, unlike `std`'s hasher
+fn content_hash(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, &b| (hash ^ b as u64).wrapping_mul(0x0100_0000_01b3))
+}
+
+fn file_hash(path: &Path) -> Option<u64> {
+    let file = std::fs::File::open(path).ok()?;
+    // SAFETY: read-only mapping, only used for the duration of the hash
+    let contents = unsafe { memmap2::Mmap::map(&file).ok()? };
+    Some(content_hash(&contents))
+}
+
+fn read_u64(bytes: &[u8], at: usize) -> u64 {
+    u64::from_le_bytes(bytes[at..at + 8].try_into().unwrap())
+}
+
+/// Load the cached cloud for `ply_path` if the cache exists and still matches it
+pub fn load(ply_path: &Path) -> Option<GaussianCloud> {
+    let cache = std::fs::read(cache_path(ply_path)).ok()?;
+    if cache.len() < HEADER_SIZE || &cache[..4] != MAGIC {
+        return None;
+    }
+
+    let count = read_u64(&cache, 4) as usize;
+    let cached = Source { len: read_u64(&cache, 12), mtime: read_u64(&cache, 20) };
+    let hash = read_u64(&cache, 28);
+
+    let source = Source::of(ply_path)?;
+    if source != cached && (source.len != cached.len || file_hash(ply_path)? != hash) {
+        return None;
+    }
+
+    // positions, scales, rotations, opacity, colors
+    let sizes = [count * 12, count * 6, count * 8, count * 2, count * 3];
+    if cache.len() != HEADER_SIZE + sizes.iter().sum::<usize>() {
+        return None;
+    }
+
+    let mut sections = Vec::with_capacity(sizes.len());
+    let mut rest = &cache[HEADER_SIZE..];
+    for size in sizes {
+        let (section, tail) = rest.split_at(size);
+        sections.push(section);
+        rest = tail;
+    }
+
+    let widen = |halves: &[f16]| -> Vec<f32> { halves.par_iter().map(|h| h.to_f32()).collect() };
+
+    let scales: Vec<f16> = bytemuck::pod_collect_to_vec(sections[1]);
+    let rotations: Vec<f16> = bytemuck::pod_collect_to_vec(sections[2]);
+    let opacity: Vec<f16> = bytemuck::pod_collect_to_vec(sections[3]);
+
+    Some(GaussianCloud {
+        count,
+        positions: bytemuck::pod_collect_to_vec(sections[0]),
+        scales: widen(&scales).chunks_exact(3).map(|c| [c[0], c[1], c[2]]).collect(),
+        rotations: widen(&rotations).chunks_exact(4).map(|c| [c[0], c[1], c[2], c[3]]).collect(),
+        colors: sections[4].chunks_exact(3)
+            .map(|c| [c[0] as f32 / 255.0, c[1] as f32 / 255.0, c[2] as f32 / 255.0])
+            .collect(),
+        opacity: widen(&opacity),
+        sh_coefficients: None,
+        octree: Default::default(),
+    })
+}
+
+/// Write the cache for `ply_path`, which `cloud` was parsed from
+pub fn store(ply_path: &Path, cloud: &GaussianCloud) -> Result<()> {
+    let source = Source::of(ply_path)
+        .ok_or_else(|| std::io::Error::other("PLY has no modification time"))?;
+    let hash = file_hash(ply_path).ok_or_else(|| std::io::Error::other("Could not read PLY"))?;
+
+    let narrow = |values: &[f32]| -> Vec<f16> { values.par_iter().map(|&v| f16::from_f32(v)).collect() };
+
+    let mut buffer = Vec::with_capacity(HEADER_SIZE + cloud.count * 31);
+    buffer.extend_from_slice(MAGIC);
+    buffer.extend_from_slice(&(cloud.count as u64).to_le_bytes());
+    buffer.extend_from_slice(&source.len.to_le_bytes());
+    buffer.extend_from_slice(&source.mtime.to_le_bytes());
+    buffer.extend_from_slice(&hash.to_le_bytes());
+
+    buffer.extend_from_slice(bytemuck::cast_slice(&cloud.positions));
+    buffer.extend_from_slice(bytemuck::cast_slice(&narrow(cloud.scales.as_flattened())));
+    buffer.extend_from_slice(bytemuck::cast_slice(&narrow(cloud.rotations.as_flattened())));
+    buffer.extend_from_slice(bytemuck::cast_slice(&narrow(&cloud.opacity)));
+    buffer.extend(cloud.colors.iter().flatten().map(|&c| (c.clamp(0.0, 1.0) * 255.0).round() as u8));
+
+    std::fs::write(cache_path(ply_path), buffer)?;
+    Ok(())
+}
+
+impl GaussianCloud {
+    /// Load a PLY through its `.gjc` sidecar cache, parsing and writing the
+    /// cache when it's missing or stale. Failing to write the cache (e.g. a
+    /// read-only directory) is not an error.
+    pub fn from_ply_cached<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        if let Some(cloud) = load(path) {
+            log::debug!("Loaded {} from cache", path.display());
+            return Ok(cloud);
+        }
+
+        let cloud = Self::from_ply(path)?;
+        if let Err(e) = store(path, &cloud) {
+            log::warn!("Could not write cache for {}: {}", path.display(), e);
+        }
+        Ok(cloud)
+    }
+}
\ No newline at end of file
@@ -0,0 +1,98 @@
+//! Summary statistics of a [`GaussianCloud`], for inspecting generations.
+
+use crate::bounding_box::BoundingBox;
+use crate::gaussian_cloud::GaussianCloud;
+
+/// Buckets in [`CloudStats::opacity_histogram`], evenly spaced over [0, 1]
+pub const OPACITY_BUCKETS: usize = 10;
+
+/// Bytes per splat in the CPU-side buffers, excluding SH coefficients
+const BYTES_PER_SPLAT: usize = size_of::<[f32; 3]>() * 3 + size_of::<[f32; 4]>() + size_of::<f32>();
+
+/// Spread of a per-splat quantity
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Distribution {
+    pub min: f32,
+    pub max: f32,
+    pub mean: f32,
+    pub median: f32,
+    /// 95th percentile, for spotting a tail of oversized splats
+    pub p95: f32,
+}
+
+impl Distribution {
+    /// Summarize the finite values; all zero when there are none
+    pub fn of(values: impl IntoIterator<Item = f32>) -> Self {
+        let mut values: Vec<f32> = values.into_iter().filter(|v| v.is_finite()).collect();
+        if values.is_empty() {
+            return Self::default();
+        }
+
+        values.sort_unstable_by(f32::total_cmp);
+        let at = |q: f32| values[((values.len() - 1) as f32 * q).round() as usize];
+
+        Self {
+            min: values[0],
+            max: values[values.len() - 1],
+            mean: values.iter().sum::<f32>() / values.len() as f32,
+            median: at(0.5),
+            p95: at(0.95),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct CloudStats {
+    pub count: usize,
+    pub bounds: BoundingBox,
+    /// Splat counts per opacity bucket; see [`OPACITY_BUCKETS`]
+    pub opacity_histogram: [usize; OPACITY_BUCKETS],
+    pub opacity: Distribution,
+    /// Largest scale axis of each splat
+    pub scale: Distribution,
+    /// Spherical harmonics degree, None without SH coefficients
+    pub sh_degree: Option<u32>,
+    /// Bytes held by the cloud's buffers
+    pub memory_bytes: usize,
+}
+
+/// SH degree from the number of coefficients per splat, counting all three
+/// color channels, with or without the DC term
+fn sh_degree(coefficients: usize) -> Option<u32> {
+    if coefficients == 0 || !coefficients.is_multiple_of(3) {
+        return None;
+    }
+
+    let per_channel = coefficients / 3;
+    (0..=4u32).find(|d| {
+        let terms = ((d + 1) * (d + 1)) as usize;
+        terms == per_channel || terms == per_channel + 1
+    })
+}
+
+impl GaussianCloud {
+    pub fn stats(&self) -> CloudStats {
+        let mut opacity_histogram = [0; OPACITY_BUCKETS];
+        for &o in &self.opacity {
+            if o.is_finite() {
+                let bucket = (o.clamp(0.0, 1.0) * OPACITY_BUCKETS as f32) as usize;
+                opacity_histogram[bucket.min(OPACITY_BUCKETS - 1)] += 1;
+            }
+        }
+
+        let sh_floats: usize = self.sh_coefficients.iter().flatten().map(Vec::len).sum();
+        let sh_degree = self.sh_coefficients.as_ref()
+            .and_then(|sh| sh.first())
+            .and_then(|first| sh_degree(first.len()));
+
+        CloudStats {
+            count: self.count,
+            bounds: self.bounds(),
+            opacity_histogram,
+            opacity: Distribution::of(self.opacity.iter().copied()),
+            scale: Distribution::of(self.scales.iter().map(|s| s[0].max(s[1]).max(s[2]))),
+            sh_degree,
+            memory_bytes: self.count * BYTES_PER_SPLAT + sh_floats * size_of::<f32>(),
+        }
+    }
+}
@@ -0,0 +1,310 @@
+//! Camera poses from COLMAP's sparse model in its text format
+//! (`cameras.txt` and `images.txt`), so photo sets COLMAP has solved can be
+//! reconstructed from where they were actually taken rather than from
+//! assumed views around the object.
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use crate::error::{Error, Result};
+
+/// A camera of `cameras.txt`, shared by the images taken with it
+#[derive(Debug, Clone, PartialEq)]
+pub struct ColmapCamera {
+    pub id: u32,
+    /// COLMAP's name for the camera model, e.g. PINHOLE or SIMPLE_RADIAL
+    pub model: String,
+    pub width: u32,
+    pub height: u32,
+    /// The model's parameters, focal lengths first
+    pub params: Vec<f64>,
+}
+
+impl ColmapCamera {
+    /// Focal lengths in pixels along x and y, None for models we don't
+    /// know. Distortion is ignored.
+    pub fn focal(&self) -> Option<[f64; 2]> {
+        let f = match self.model.as_str() {
+            "SIMPLE_PINHOLE" | "SIMPLE_RADIAL" | "RADIAL" | "SIMPLE_RADIAL_FISHEYE" | "RADIAL_FISHEYE" => {
+                [*self.params.first()?; 2]
+            }
+            "PINHOLE" | "OPENCV" | "OPENCV_FISHEYE" | "FULL_OPENCV" | "FOV" | "THIN_PRISM_FISHEYE" => {
+                [*self.params.first()?, *self.params.get(1)?]
+            }
+            _ => return None,
+        };
+        Some(f)
+    }
+
+    /// Vertical field of view in degrees of the centered square the photos
+    /// are cropped to
+    pub fn square_fov_y(&self) -> Option<f32> {
+        let [_, fy] = self.focal()?;
+        let side = self.width.min(self.height) as f64;
+        Some((2.0 * (side / (2.0 * fy)).atan()).to_degrees() as f32)
+    }
+}
+
+/// An image of `images.txt`: the photo and the pose it was taken from
+#[derive(Debug, Clone, PartialEq)]
+pub struct ColmapImage {
+    pub id: u32,
+    /// World-to-camera rotation (w, x, y, z)
+    pub rotation: [f64; 4],
+    /// World-to-camera translation
+    pub translation: [f64; 3],
+    pub camera_id: u32,
+    /// Path of the photo, relative to the folder COLMAP was given
+    pub name: String,
+}
+
+impl ColmapImage {
+    /// World-to-camera rotation matrix; its rows are the camera's right,
+    /// down and forward axes in the world
+    fn rotation_matrix(&self) -> [[f64; 3]; 3] {
+        let length = self.rotation.iter().map(|c| c * c).sum::<f64>().sqrt();
+        let [w, x, y, z] = self.rotation.map(|c| c / length);
+        [
+            [1.0 - 2.0 * (y * y + z * z), 2.0 * (x * y - w * z), 2.0 * (x * z + w * y)],
+            [2.0 * (x * y + w * z), 1.0 - 2.0 * (x * x + z * z), 2.0 * (y * z - w * x)],
+            [2.0 * (x * z - w * y), 2.0 * (y * z + w * x), 1.0 - 2.0 * (x * x + y * y)],
+        ]
+    }
+
+    /// Where the camera was in the world
+    pub fn center(&self) -> [f64; 3] {
+        let r = self.rotation_matrix();
+        let t = self.translation;
+        std::array::from_fn(|i| -(r[0][i] * t[0] + r[1][i] * t[1] + r[2][i] * t[2]))
+    }
+}
+
+/// A photo of the object with where it was taken from, in the object's
+/// frame: y up, the first photo's side of the object its front (+z), and
+/// the cameras a mean distance of 1 from it
+#[derive(Debug, Clone, PartialEq)]
+pub struct PosedPhoto {
+    /// Path of the photo, relative to the folder COLMAP was given
+    pub name: String,
+    /// Degrees around the object, 0 facing its front and 90 its right side
+    pub azimuth: f32,
+    /// Degrees above the object's middle
+    pub elevation: f32,
+    /// Camera-to-object transform, row-major, with the camera looking down
+    /// its -z and its y up as in OpenGL
+    pub to_object: [[f32; 4]; 4],
+    /// Vertical field of view in degrees of the photo's centered square
+    pub fov_y: f32,
+}
+
+/// COLMAP's sparse model of a photo set
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ColmapScene {
+    pub cameras: BTreeMap<u32, ColmapCamera>,
+    /// In the order `images.txt` lists them
+    pub images: Vec<ColmapImage>,
+}
+
+fn invalid(file: &str, line: &str) -> Error {
+    Error::InvalidColmap(format!("Can't parse {} line '{}'", file, line.trim()))
+}
+
+impl ColmapScene {
+    /// Parse the contents of `cameras.txt` and `images.txt`
+    pub fn parse(cameras: &str, images: &str) -> Result<Self> {
+        let mut scene = Self::default();
+
+        for line in cameras.lines().filter(|l| !l.trim().is_empty() && !l.trim_start().starts_with('#')) {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            let [id, model, width, height, params @ ..] = fields.as_slice() else {
+                return Err(invalid("cameras.txt", line));
+            };
+            let camera = ColmapCamera {
+                id: id.parse().map_err(|_| invalid("cameras.txt", line))?,
+                model: model.to_string(),
+                width: width.parse().map_err(|_| invalid("cameras.txt", line))?,
+                height: height.parse().map_err(|_| invalid("cameras.txt", line))?,
+                params: params.iter().map(|p| p.parse()).collect::<std::result::Result<_, _>>()
+                    .map_err(|_| invalid("cameras.txt", line))?,
+            };
+            scene.cameras.insert(camera.id, camera);
+        }
+
+        // Each image takes two lines, the second listing its 2D points; that
+        // one is empty for images without any, so only comments are skipped
+        let mut lines = images.lines().filter(|l| !l.trim_start().starts_with('#'));
+        while let Some(line) = lines.next() {
+            if line.trim().is_empty() {
+                continue;
+            }
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            let [id, qw, qx, qy, qz, tx, ty, tz, camera_id, name @ ..] = fields.as_slice() else {
+                return Err(invalid("images.txt", line));
+            };
+            let number = |s: &str| s.parse::<f64>().map_err(|_| invalid("images.txt", line));
+            let image = ColmapImage {
+                id: id.parse().map_err(|_| invalid("images.txt", line))?,
+                rotation: [number(qw)?, number(qx)?, number(qy)?, number(qz)?],
+                translation: [number(tx)?, number(ty)?, number(tz)?],
+                camera_id: camera_id.parse().map_err(|_| invalid("images.txt", line))?,
+                // Names may have spaces
+                name: name.join(" "),
+            };
+            if image.name.is_empty() {
+                return Err(invalid("images.txt", line));
+            }
+            if !scene.cameras.contains_key(&image.camera_id) {
+                return Err(Error::InvalidColmap(format!("Image {} uses unknown camera {}", image.name, image.camera_id)));
+            }
+            scene.images.push(image);
+            lines.next();
+        }
+
+        Ok(scene)
+    }
+
+    /// Read `cameras.txt` and `images.txt` in `dir`, e.g. `sparse/0`
+    pub fn load(dir: &Path) -> Result<Self> {
+        let read = |name: &str| std::fs::read_to_string(dir.join(name)).map_err(|e| {
+            Error::InvalidColmap(format!("Can't read {}: {}", dir.join(name).display(), e))
+        });
+        Self::parse(&read("cameras.txt")?, &read("images.txt")?)
+    }
+
+    /// Where the photo `name` of a model in `dir` is: next to the model, or
+    /// in an `images` folder beside it or beside one of its parents, the
+    /// layout COLMAP's automatic reconstruction makes
+    pub fn find_photo(dir: &Path, name: &str) -> Option<PathBuf> {
+        dir.ancestors()
+            .take(4)
+            .flat_map(|d| [d.join(name), d.join("images").join(name)])
+            .find(|p| p.is_file())
+    }
+
+    /// The point the cameras look at: the one nearest all their optical
+    /// axes, in the least squares sense. None when the axes are parallel.
+    pub fn target(&self) -> Option<[f64; 3]> {
+        let mut a = [[0.0; 3]; 3];
+        let mut b = [0.0; 3];
+        for image in &self.images {
+            let d = image.rotation_matrix()[2];
+            let c = image.center();
+            // Projection onto the plane across the axis: I - d dᵀ
+            for i in 0..3 {
+                for j in 0..3 {
+                    let p = if i == j { 1.0 } else { 0.0 } - d[i] * d[j];
+                    a[i][j] += p;
+                    b[i] += p * c[j];
+                }
+            }
+        }
+        solve(a, b)
+    }
+
+    /// Each image posed around the object the cameras look at, see
+    /// [`PosedPhoto`]. Up is the cameras' mean up direction, which suits
+    /// photos taken holding the camera level.
+    pub fn posed_photos(&self) -> Result<Vec<PosedPhoto>> {
+        let first = self.images.first()
+            .ok_or_else(|| Error::InvalidColmap("The model has no images".to_string()))?;
+        let target = self.target()
+            .ok_or_else(|| Error::InvalidColmap("The cameras don't look at a common point".to_string()))?;
+
+        let mut up = [0.0; 3];
+        for image in &self.images {
+            let down = image.rotation_matrix()[1];
+            up = std::array::from_fn(|i| up[i] - down[i]);
+        }
+        let up = normalize(up)
+            .ok_or_else(|| Error::InvalidColmap("The cameras don't agree on which way is up".to_string()))?;
+        let offset = |image: &ColmapImage| -> [f64; 3] {
+            let c = image.center();
+            std::array::from_fn(|i| c[i] - target[i])
+        };
+        let to_first = offset(first);
+        let along = dot(to_first, up);
+        let front = normalize(std::array::from_fn(|i| to_first[i] - along * up[i]))
+            .ok_or_else(|| Error::InvalidColmap("The first photo is taken from straight above or below".to_string()))?;
+        let right = cross(up, front);
+        let basis = [right, up, front];
+
+        let radius = self.images.iter().map(|i| length(offset(i))).sum::<f64>() / self.images.len() as f64;
+        if radius <= 0.0 {
+            return Err(Error::InvalidColmap("The cameras are all at the object".to_string()));
+        }
+
+        self.images.iter().map(|image| {
+            let camera = &self.cameras[&image.camera_id];
+            let fov_y = camera.square_fov_y().ok_or_else(|| {
+                Error::InvalidColmap(format!("Camera model {} isn't supported", camera.model))
+            })?;
+            let to_object = |v: [f64; 3]| basis.map(|axis| dot(axis, v));
+            let position = to_object(offset(image)).map(|c| c / radius);
+            let [right, down, forward] = image.rotation_matrix().map(to_object);
+
+            let distance = length(position);
+            let azimuth = (position[0].atan2(position[2]).to_degrees() + 360.0) % 360.0;
+            let elevation = if distance > 0.0 { (position[1] / distance).asin().to_degrees() } else { 0.0 };
+            // Columns: right, up and backward, then the position
+            let columns = [right, down.map(|c| -c), forward.map(|c| -c), position];
+            let mut matrix = [[0.0f32; 4]; 4];
+            for (row, cells) in matrix.iter_mut().enumerate().take(3) {
+                for (column, cell) in cells.iter_mut().enumerate() {
+                    *cell = columns[column][row] as f32;
+                }
+            }
+            matrix[3][3] = 1.0;
+
+            Ok(PosedPhoto {
+                name: image.name.clone(),
+                azimuth: azimuth as f32,
+                elevation: elevation as f32,
+                to_object: matrix,
+                fov_y,
+            })
+        }).collect()
+    }
+}
+
+/// At most `count` of `photos`, spread as evenly around the object as
+/// they allow, in order of azimuth
+pub fn spread(mut photos: Vec<PosedPhoto>, count: usize) -> Vec<PosedPhoto> {
+    photos.sort_by(|a, b| a.azimuth.total_cmp(&b.azimuth));
+    if photos.len() <= count {
+        return photos;
+    }
+    let step = photos.len() as f64 / count as f64;
+    (0..count).map(|i| photos[(i as f64 * step) as usize].clone()).collect()
+}
+
+fn dot(a: [f64; 3], b: [f64; 3]) -> f64 {
+    a[0] * b[0] + a[1] * b[1] + a[2] * b[2]
+}
+
+fn cross(a: [f64; 3], b: [f64; 3]) -> [f64; 3] {
+    [a[1] * b[2] - a[2] * b[1], a[2] * b[0] - a[0] * b[2], a[0] * b[1] - a[1] * b[0]]
+}
+
+fn length(v: [f64; 3]) -> f64 {
+    dot(v, v).sqrt()
+}
+
+fn normalize(v: [f64; 3]) -> Option<[f64; 3]> {
+    let l = length(v);
+    (l > 1e-9).then(|| v.map(|c| c / l))
+}
+
+/// Solution of `a x = b` by Cramer's rule, None when `a` is near singular
+fn solve(a: [[f64; 3]; 3], b: [f64; 3]) -> Option<[f64; 3]> {
+    let det = |m: [[f64; 3]; 3]| dot(m[0], cross(m[1], m[2]));
+    let d = det(a);
+    if d.abs() < 1e-9 {
+        return None;
+    }
+    Some(std::array::from_fn(|column| {
+        let mut m = a;
+        for (row, value) in m.iter_mut().zip(b) {
+            row[column] = value;
+        }
+        det(m) / d
+    }))
+}
@@ -0,0 +1,74 @@
+//! Brightness, contrast, saturation and hue adjustments of splat colors.
+//! The renderer applies the same math live in its shader; baking writes it
+//! into the cloud.
+
+use crate::gaussian_cloud::GaussianCloud;
+
+/// Rec. 709 luma weights
+const LUMA: [f32; 3] = [0.2126, 0.7152, 0.0722];
+
+/// Color adjustments, applied in field order and then clamped to [0, 1]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ColorGrade {
+    /// Added to every channel, in [-1, 1]
+    pub brightness: f32,
+    /// Scale around mid-grey; 1 leaves colors unchanged
+    pub contrast: f32,
+    /// Blend from grey (0) through the original (1) to exaggerated colors
+    pub saturation: f32,
+    /// Rotation around the grey axis, in degrees
+    pub hue: f32,
+}
+
+impl Default for ColorGrade {
+    fn default() -> Self {
+        Self {
+            brightness: 0.0,
+            contrast: 1.0,
+            saturation: 1.0,
+            hue: 0.0,
+        }
+    }
+}
+
+impl ColorGrade {
+    pub fn is_identity(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// As packed for the shader: brightness, contrast, saturation, hue in radians
+    pub fn to_array(&self) -> [f32; 4] {
+        [self.brightness, self.contrast, self.saturation, self.hue.to_radians()]
+    }
+
+    pub fn apply(&self, color: [f32; 3]) -> [f32; 3] {
+        let c = color.map(|c| (c + self.brightness - 0.5) * self.contrast + 0.5);
+
+        let luma: f32 = (0..3).map(|i| c[i] * LUMA[i]).sum();
+        let c = c.map(|c| luma + (c - luma) * self.saturation);
+
+        // Rodrigues rotation around (1, 1, 1) / √3
+        let (sin, cos) = self.hue.to_radians().sin_cos();
+        let k = (1.0 - cos) / 3.0;
+        let s = sin / 3f32.sqrt();
+        let c = [
+            c[0] * (cos + k) + c[1] * (k - s) + c[2] * (k + s),
+            c[0] * (k + s) + c[1] * (cos + k) + c[2] * (k - s),
+            c[0] * (k - s) + c[1] * (k + s) + c[2] * (cos + k),
+        ];
+
+        c.map(|c| c.clamp(0.0, 1.0))
+    }
+}
+
+impl GaussianCloud {
+    /// Bake `grade` into the splat colors
+    pub fn apply_color_grade(&mut self, grade: &ColorGrade) {
+        if grade.is_identity() {
+            return;
+        }
+        for color in &mut self.colors {
+            *color = grade.apply(*color);
+        }
+    }
+}
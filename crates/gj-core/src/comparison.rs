@@ -0,0 +1,101 @@
+//! Distance metrics between two clouds, for judging how much a parameter
+//! change moved a generation of the same prompt.
+
+use rayon::prelude::*;
+use crate::cloud_stats::Distribution;
+use crate::error::{Error, Result};
+use crate::gaussian_cloud::GaussianCloud;
+
+/// Summary of how far two clouds' splat centers are apart
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct ComparisonMetrics {
+    /// Mean distance from each compared splat to the nearest reference splat
+    pub mean_to_reference: f32,
+    /// Mean distance from each reference splat to the nearest compared splat
+    pub mean_from_reference: f32,
+    /// Symmetric chamfer distance, the sum of both means
+    pub chamfer: f32,
+    /// Largest nearest distance in either direction
+    pub hausdorff: f32,
+    /// Spread of the per-splat distances to the reference
+    pub distances: Distribution,
+}
+
+#[derive(Clone, Debug)]
+pub struct CloudComparison {
+    pub metrics: ComparisonMetrics,
+    /// Distance from each splat of the compared cloud to the nearest
+    /// reference splat, in splat order
+    pub distances: Vec<f32>,
+}
+
+impl CloudComparison {
+    /// Per-splat heatmap colors, saturating at `max_distance`. The 95th
+    /// percentile makes a good scale, so a few strays don't wash it out.
+    pub fn heatmap_colors(&self, max_distance: f32) -> Vec<[f32; 3]> {
+        let scale = max_distance.max(f32::EPSILON);
+        self.distances.iter().map(|&d| heatmap_color(d / scale)).collect()
+    }
+}
+
+/// Blue → cyan → green → yellow → red ramp for `t` in [0, 1]
+pub fn heatmap_color(t: f32) -> [f32; 3] {
+    const STOPS: [[f32; 3]; 5] = [
+        [0.0, 0.0, 1.0],
+        [0.0, 1.0, 1.0],
+        [0.0, 1.0, 0.0],
+        [1.0, 1.0, 0.0],
+        [1.0, 0.0, 0.0],
+    ];
+
+    let t = if t.is_nan() { 1.0 } else { t.clamp(0.0, 1.0) };
+    let t = t * (STOPS.len() - 1) as f32;
+    let i = (t as usize).min(STOPS.len() - 2);
+    let f = t - i as f32;
+    std::array::from_fn(|c| STOPS[i][c] + (STOPS[i + 1][c] - STOPS[i][c]) * f)
+}
+
+/// Distance from each splat center of `from` to the nearest center of `to`
+fn nearest_distances(from: &GaussianCloud, to: &GaussianCloud) -> Vec<f32> {
+    let octree = to.octree();
+    from.positions.par_iter()
+        .map(|&p| octree.nearest(to, p).map_or(f32::INFINITY, |(_, d)| d))
+        .collect()
+}
+
+fn mean(values: &[f32]) -> f32 {
+    values.iter().sum::<f32>() / values.len().max(1) as f32
+}
+
+impl GaussianCloud {
+    /// Compare this cloud's splat centers against `reference`. The clouds
+    /// are compared where they are; align them first if they may be offset.
+    pub fn compare(&self, reference: &GaussianCloud) -> Result<CloudComparison> {
+        if self.count == 0 || reference.count == 0 {
+            return Err(Error::InvalidGaussianCloud("Cannot compare an empty cloud".to_string()));
+        }
+
+        let distances = nearest_distances(self, reference);
+        let backward = nearest_distances(reference, self);
+
+        let mean_to_reference = mean(&distances);
+        let mean_from_reference = mean(&backward);
+        let hausdorff = distances.iter().chain(&backward).copied().fold(0.0, f32::max);
+
+        Ok(CloudComparison {
+            metrics: ComparisonMetrics {
+                mean_to_reference,
+                mean_from_reference,
+                chamfer: mean_to_reference + mean_from_reference,
+                hausdorff,
+                distances: Distribution::of(distances.iter().copied()),
+            },
+            distances,
+        })
+    }
+
+    /// Symmetric chamfer distance to `other`; see [`ComparisonMetrics::chamfer`]
+    pub fn chamfer_distance(&self, other: &GaussianCloud) -> Result<f32> {
+        Ok(self.compare(other)?.metrics.chamfer)
+    }
+}
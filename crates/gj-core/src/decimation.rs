@@ -0,0 +1,71 @@
+//! Reducing a cloud to fewer splats while keeping the ones that contribute
+//! most to the image, e.g. to fit a file size budget for the web.
+
+use crate::gaussian_cloud::GaussianCloud;
+use crate::ply_profile::PlyProfile;
+
+/// How much a splat contributes to a render: its opacity times the area of
+/// its footprint, approximated from the two largest scale axes
+fn importance(cloud: &GaussianCloud, i: usize) -> f64 {
+    let mut s = cloud.scales[i].map(|s| if s.is_finite() { s.abs() as f64 } else { 0.0 });
+    s.sort_unstable_by(f64::total_cmp);
+    let opacity = cloud.opacity[i].clamp(0.0, 1.0) as f64;
+    opacity * s[1] * s[2]
+}
+
+/// Most splats whose [`GaussianCloud::to_ply_as`] output fits in `bytes`
+pub fn max_splats_for_ply_size(bytes: u64, profile: PlyProfile) -> usize {
+    // Header of an empty cloud, plus room for the digits of the vertex count
+    let header = GaussianCloud::new().to_ply_as(profile).map_or(0, |ply| ply.len()) + 20;
+    (bytes as usize).saturating_sub(header) / profile.vertex_size()
+}
+
+impl GaussianCloud {
+    /// Keep about `target_count` splats, sampled in proportion to their
+    /// importance (opacity × projected area) so large, opaque splats survive
+    /// and the many faint ones are thinned out.
+    ///
+    /// Sampling is systematic rather than random, so the result is the same
+    /// on every call. Returns a copy when the cloud is already small enough.
+    pub fn decimate(&self, target_count: usize) -> Self {
+        if self.count <= target_count {
+            return self.clone();
+        }
+
+        let weights: Vec<f64> = (0..self.count).map(|i| importance(self, i)).collect();
+        let total: f64 = weights.iter().sum();
+
+        let mut picked = vec![false; self.count];
+        let mut kept = 0;
+
+        if total > 0.0 {
+            // One sample every `step` along the cumulative weight; a splat heavier
+            // than a step can be hit more than once but is kept once
+            let step = total / target_count as f64;
+            let mut next = step / 2.0;
+            let mut cumulative = 0.0;
+            for (i, &w) in weights.iter().enumerate() {
+                cumulative += w;
+                while next < cumulative && kept < target_count {
+                    if !picked[i] {
+                        picked[i] = true;
+                        kept += 1;
+                    }
+                    next += step;
+                }
+            }
+        }
+
+        // Fill the slots lost to repeat hits with the most important of the rest
+        if kept < target_count {
+            let mut rest: Vec<usize> = (0..self.count).filter(|&i| !picked[i]).collect();
+            rest.sort_unstable_by(|&a, &b| weights[b].total_cmp(&weights[a]));
+            for i in rest.into_iter().take(target_count - kept) {
+                picked[i] = true;
+            }
+        }
+
+        let indices: Vec<usize> = (0..self.count).filter(|&i| picked[i]).collect();
+        self.subset(&indices)
+    }
+}
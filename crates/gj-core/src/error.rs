@@ -21,4 +21,35 @@ pub enum Error {
 
     #[error("Render error: {0}")]
     RenderError(String),
+
+    #[error("Invalid PLY file: {0}")]
+    InvalidPly(String),
+
+    #[error("Invalid COLMAP model: {0}")]
+    InvalidColmap(String),
+
+    #[error("Generation service unreachable: {0}")]
+    ServiceUnreachable(String),
+
+    #[error("Out of GPU memory: {0}")]
+    OutOfVram(String),
+
+    #[error("Timed out: {0}")]
+    Timeout(String),
+}
+
+impl Error {
+    /// Suggested fix to show alongside the error, if there is one
+    pub fn remediation(&self) -> Option<&'static str> {
+        match self {
+            Self::ModelNotLoaded(_) => Some("Check the worker log for model loading errors; weights may still be downloading"),
+            Self::GenerationFailed(_) => Some("Try a more specific prompt or a higher guidance scale"),
+            Self::InvalidPly(_) => Some("The file is damaged or not a Gaussian splat PLY; try regenerating it"),
+            Self::InvalidColmap(_) => Some("Pick the folder of a sparse model exported as text (colmap model_converter --output_type TXT)"),
+            Self::ServiceUnreachable(_) => Some("Start the generation service (cd python && docker-compose up)"),
+            Self::OutOfVram(_) => Some("Close other GPU applications or lower the inference steps"),
+            Self::Timeout(_) => Some("The service may be busy or stalled; check the worker and retry"),
+            Self::InvalidConfig(_) | Self::InvalidGaussianCloud(_) | Self::IoError(_) | Self::RenderError(_) => None,
+        }
+    }
 }
\ No newline at end of file
@@ -1,5 +1,15 @@
+use std::sync::OnceLock;
 use crate::bounding_box::BoundingBox;
 use crate::error::{Error, Result};
+use crate::octree::Octree;
+
+/// Bytes per vertex record in the PLY layout written by [`GaussianCloud::to_ply`]
+pub(crate) const PLY_VERTEX_SIZE: usize = 59;
+
+/// Decode `N` consecutive little-endian f32s
+fn read_f32s<const N: usize>(bytes: &[u8]) -> [f32; N] {
+    std::array::from_fn(|i| f32::from_le_bytes(bytes[i * 4..i * 4 + 4].try_into().unwrap()))
+}
 
 #[derive(Clone, Debug)]
 pub struct GaussianCloud {
@@ -23,6 +33,15 @@ pub struct GaussianCloud {
 
     /// Optional spherical harmonics coefficients for view-dependent color
     pub sh_coefficients: Option<Vec<Vec<f32>>>,
+
+    /// Spatial index, built on first use; see [`GaussianCloud::octree`]
+    pub(crate) octree: OnceLock<Octree>,
+}
+
+impl Default for GaussianCloud {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl GaussianCloud {
@@ -36,6 +55,7 @@ impl GaussianCloud {
             colors: Vec::new(),
             opacity: Vec::new(),
             sh_coefficients: None,
+            octree: OnceLock::new(),
         }
     }
 
@@ -49,6 +69,7 @@ impl GaussianCloud {
             colors: Vec::with_capacity(capacity),
             opacity: Vec::with_capacity(capacity),
             sh_coefficients: None,
+            octree: OnceLock::new(),
         }
     }
 
@@ -67,6 +88,32 @@ impl GaussianCloud {
         self.colors.push(color);
         self.opacity.push(opacity);
         self.count += 1;
+        self.invalidate_octree();
+    }
+
+    /// New cloud holding the splats at `indices`, in that order
+    pub fn subset(&self, indices: &[usize]) -> Self {
+        Self {
+            count: indices.len(),
+            positions: indices.iter().map(|&i| self.positions[i]).collect(),
+            scales: indices.iter().map(|&i| self.scales[i]).collect(),
+            rotations: indices.iter().map(|&i| self.rotations[i]).collect(),
+            colors: indices.iter().map(|&i| self.colors[i]).collect(),
+            opacity: indices.iter().map(|&i| self.opacity[i]).collect(),
+            sh_coefficients: self.sh_coefficients.as_ref()
+                .map(|sh| indices.iter().map(|&i| sh[i].clone()).collect()),
+            octree: OnceLock::new(),
+        }
+    }
+
+    /// Spatial index over the splat centers, built on first call and cached
+    pub fn octree(&self) -> &Octree {
+        self.octree.get_or_init(|| Octree::build(self))
+    }
+
+    /// Drop the cached octree; call after editing positions or scales directly
+    pub fn invalidate_octree(&mut self) {
+        self.octree.take();
     }
 
     /// Get bounding box of all Gaussians
@@ -88,19 +135,51 @@ impl GaussianCloud {
         BoundingBox { min, max }
     }
 
+    /// Get bounding box of the Gaussians within `radius` of `center`
+    pub fn bounds_around(&self, center: [f32; 3], radius: f32) -> BoundingBox {
+        let mut min = [f32::INFINITY; 3];
+        let mut max = [f32::NEG_INFINITY; 3];
+
+        let nearby = self.octree().within_radius(self, center, radius);
+        if nearby.is_empty() {
+            return BoundingBox { min: center, max: center };
+        }
+
+        for i in nearby {
+            let pos = self.positions[i];
+            for axis in 0..3 {
+                min[axis] = min[axis].min(pos[axis]);
+                max[axis] = max[axis].max(pos[axis]);
+            }
+        }
+
+        BoundingBox { min, max }
+    }
+
+    /// Cast a ray against the Gaussians, treating each one as a sphere sized by its
+    /// largest scale axis. `direction` must be normalized.
+    ///
+    /// Returns the index of the nearest hit and its distance along the ray.
+    pub fn raycast(&self, origin: [f32; 3], direction: [f32; 3]) -> Option<(usize, f32)> {
+        self.octree().raycast(self, origin, direction)
+    }
+
     /// Load GaussianCloud from .ply file
+    ///
+    /// The file is memory-mapped and vertex records are decoded in parallel
+    /// straight into the SoA buffers.
     pub fn from_ply<P: AsRef<std::path::Path>>(path: P) -> Result<Self> {
-        use std::io::Read;
-        use std::fs::File;
+        use rayon::prelude::*;
 
-        let mut file = File::open(path)?;
-        let mut contents = Vec::new();
-        file.read_to_end(&mut contents)?;
+        let file = std::fs::File::open(path)?;
+        // SAFETY: the mapping is only read while parsing; generated files are
+        // written once by the service and not modified while we load them
+        let contents = unsafe { memmap2::Mmap::map(&file)? };
 
         // Parse PLY header
         let header_end = contents.windows(10)
             .position(|w| w == b"end_header")
-            .ok_or_else(|| Error::InvalidGaussianCloud("No end_header found".to_string()))?;
+            .ok_or_else(|| Error::InvalidPly("No end_header found".to_string()))?;
 
         let header = String::from_utf8_lossy(&contents[..header_end]);
 
@@ -109,58 +188,57 @@ impl GaussianCloud {
             .find(|line| line.starts_with("element vertex"))
             .and_then(|line| line.split_whitespace().last())
             .and_then(|s| s.parse::<usize>().ok())
-            .ok_or_else(|| Error::InvalidGaussianCloud("No vertex count found".to_string()))?;
+            .ok_or_else(|| Error::InvalidPly("No vertex count found".to_string()))?;
 
         // Binary data starts after "end_header\n"
-        let data_start = header_end + 10 + 1;
+        let data_start = (header_end + 10 + 1).min(contents.len());
         let data = &contents[data_start..];
 
-        let mut cloud = Self::with_capacity(vertex_count);
-
-        for i in 0..vertex_count {
-            let offset = i * 59;
-            if offset + 59 > data.len() {
-                break;
-            }
-
-            let vertex_data = &data[offset..offset + 59];
-
-            // Position (bytes 0-11)
-            let position = [
-                f32::from_le_bytes([vertex_data[0], vertex_data[1], vertex_data[2], vertex_data[3]]),
-                f32::from_le_bytes([vertex_data[4], vertex_data[5], vertex_data[6], vertex_data[7]]),
-                f32::from_le_bytes([vertex_data[8], vertex_data[9], vertex_data[10], vertex_data[11]]),
-            ];
-
-            // Skip normals (bytes 12-23) - 3 floats
-
-            // Color (bytes 24-26) - 3 unsigned bytes
-            let color = [
-                vertex_data[24] as f32 / 255.0,
-                vertex_data[25] as f32 / 255.0,
-                vertex_data[26] as f32 / 255.0,
-            ];
-
-            // Opacity (bytes 27-30)
-            let opacity = f32::from_le_bytes([vertex_data[27], vertex_data[28], vertex_data[29], vertex_data[30]]);
-
-            // Scale (bytes 31-42)
-            let scale = [
-                f32::from_le_bytes([vertex_data[31], vertex_data[32], vertex_data[33], vertex_data[34]]),
-                f32::from_le_bytes([vertex_data[35], vertex_data[36], vertex_data[37], vertex_data[38]]),
-                f32::from_le_bytes([vertex_data[39], vertex_data[40], vertex_data[41], vertex_data[42]]),
-            ];
-
-            // Rotation (bytes 43-58)
-            let rotation = [
-                f32::from_le_bytes([vertex_data[43], vertex_data[44], vertex_data[45], vertex_data[46]]),
-                f32::from_le_bytes([vertex_data[47], vertex_data[48], vertex_data[49], vertex_data[50]]),
-                f32::from_le_bytes([vertex_data[51], vertex_data[52], vertex_data[53], vertex_data[54]]),
-                f32::from_le_bytes([vertex_data[55], vertex_data[56], vertex_data[57], vertex_data[58]]),
-            ];
-
-            cloud.add_gaussian(position, scale, rotation, color, opacity);
-        }
+        // A truncated file yields the complete records only
+        let count = vertex_count.min(data.len() / PLY_VERTEX_SIZE);
+        let data = &data[..count * PLY_VERTEX_SIZE];
+
+        let mut cloud = Self {
+            count,
+            positions: vec![[0.0; 3]; count],
+            scales: vec![[0.0; 3]; count],
+            rotations: vec![[0.0; 4]; count],
+            colors: vec![[0.0; 3]; count],
+            opacity: vec![0.0; count],
+            sh_coefficients: None,
+            octree: OnceLock::new(),
+        };
+
+        (
+            cloud.positions.par_iter_mut(),
+            cloud.scales.par_iter_mut(),
+            cloud.rotations.par_iter_mut(),
+            cloud.colors.par_iter_mut(),
+            cloud.opacity.par_iter_mut(),
+            data.par_chunks_exact(PLY_VERTEX_SIZE),
+        )
+            .into_par_iter()
+            .with_min_len(4096)
+            .for_each(|(position, scale, rotation, color, opacity, vertex)| {
+                // Position (bytes 0-11), normals skipped (bytes 12-23)
+                *position = read_f32s(&vertex[0..12]);
+
+                // Color (bytes 24-26) - 3 unsigned bytes
+                *color = [
+                    vertex[24] as f32 / 255.0,
+                    vertex[25] as f32 / 255.0,
+                    vertex[26] as f32 / 255.0,
+                ];
+
+                // Opacity (bytes 27-30)
+                *opacity = read_f32s::<1>(&vertex[27..31])[0];
+
+                // Scale (bytes 31-42)
+                *scale = read_f32s(&vertex[31..43]);
+
+                // Rotation (bytes 43-58)
+                *rotation = read_f32s(&vertex[43..59]);
+            });
 
         Ok(cloud)
     }
@@ -195,16 +273,16 @@ impl GaussianCloud {
         writeln!(buffer, "end_header")?;
 
         // Binary data
-        for i in 0..self.count {
+        for (i, normal) in self.normals().iter().enumerate() {
             // Position
             buffer.write_all(&self.positions[i][0].to_le_bytes())?;
             buffer.write_all(&self.positions[i][1].to_le_bytes())?;
             buffer.write_all(&self.positions[i][2].to_le_bytes())?;
 
-            // Normal (placeholder)
-            buffer.write_all(&0.0f32.to_le_bytes())?;
-            buffer.write_all(&0.0f32.to_le_bytes())?;
-            buffer.write_all(&0.0f32.to_le_bytes())?;
+            // Normal
+            buffer.write_all(&normal[0].to_le_bytes())?;
+            buffer.write_all(&normal[1].to_le_bytes())?;
+            buffer.write_all(&normal[2].to_le_bytes())?;
 
             // Color (convert to u8)
             buffer.push((self.colors[i][0] * 255.0) as u8);
@@ -228,18 +306,4 @@ impl GaussianCloud {
 
         Ok(buffer)
     }
-
-    /// Validate that all arrays have consistent length
-    pub fn validate(&self) -> Result<()> {
-        if self.positions.len() != self.count ||
-            self.scales.len() != self.count ||
-            self.rotations.len() != self.count ||
-            self.colors.len() != self.count ||
-            self.opacity.len() != self.count {
-            return Err(Error::InvalidGaussianCloud(
-                "Inconsistent array lengths".to_string()
-            ));
-        }
-        Ok(())
-    }
-}
\ No newline at end of file
+}
@@ -0,0 +1,71 @@
+//! Per-splat quantities shown as heatmaps, for diagnosing why a generation
+//! looks noisy (many faint or tiny splats, strong view-dependent color) or
+//! blobby (a few huge ones).
+
+use rayon::prelude::*;
+use crate::cloud_stats::Distribution;
+use crate::comparison::heatmap_color;
+use crate::gaussian_cloud::GaussianCloud;
+
+/// Density counts neighbors within this share of the bounds' longest side
+const DENSITY_RADIUS: f32 = 0.02;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HeatmapQuantity {
+    Opacity,
+    /// Largest scale axis
+    Scale,
+    /// Magnitude of the SH coefficients, how much the color changes with
+    /// the view; zero without SH
+    ShEnergy,
+    /// Splat centers nearby
+    Density,
+}
+
+impl HeatmapQuantity {
+    pub const ALL: [Self; 4] = [Self::Opacity, Self::Scale, Self::ShEnergy, Self::Density];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Opacity => "Opacity",
+            Self::Scale => "Scale",
+            Self::ShEnergy => "SH energy",
+            Self::Density => "Density",
+        }
+    }
+}
+
+impl GaussianCloud {
+    /// `quantity` for every splat, in splat order
+    pub fn heatmap_values(&self, quantity: HeatmapQuantity) -> Vec<f32> {
+        match quantity {
+            HeatmapQuantity::Opacity => self.opacity.clone(),
+            HeatmapQuantity::Scale => self.scales.iter().map(|s| s[0].max(s[1]).max(s[2])).collect(),
+            HeatmapQuantity::ShEnergy => match &self.sh_coefficients {
+                Some(sh) => sh.iter().map(|c| c.iter().map(|v| v * v).sum::<f32>().sqrt()).collect(),
+                None => vec![0.0; self.count],
+            },
+            HeatmapQuantity::Density => {
+                let size = self.bounds().size();
+                let radius = size[0].max(size[1]).max(size[2]) * DENSITY_RADIUS;
+                let octree = self.octree();
+                self.positions.par_iter()
+                    .map(|&p| octree.within_radius(self, p, radius).len().saturating_sub(1) as f32)
+                    .collect()
+            }
+        }
+    }
+
+    /// Colors showing `quantity`, blue for low to red for high. Opacity
+    /// spans [0, 1]; the rest saturate at their 95th percentile, so a few
+    /// outliers don't wash out the map.
+    pub fn heatmap_colors(&self, quantity: HeatmapQuantity) -> Vec<[f32; 3]> {
+        let values = self.heatmap_values(quantity);
+        let max = match quantity {
+            HeatmapQuantity::Opacity => 1.0,
+            _ => Distribution::of(values.iter().copied()).p95,
+        };
+        let scale = max.max(f32::EPSILON);
+        values.iter().map(|&v| heatmap_color(v / scale)).collect()
+    }
+}
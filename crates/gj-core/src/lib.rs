@@ -1,10 +1,32 @@
-mod bounding_box;
+pub mod bounding_box;
 pub mod pipeline;
+pub mod colmap;
 pub mod error;
 pub mod progress;
-mod camera;
+pub mod camera;
+#[cfg(test)]
 mod tests;
 pub mod gaussian_cloud;
+pub mod cloud_cache;
+pub mod cloud_stats;
+pub mod octree;
+pub mod decimation;
+pub mod voxel;
+pub mod mesh;
+pub mod normals;
+pub mod alignment;
+pub mod array;
+pub mod comparison;
+pub mod heatmap;
+pub mod morph;
+pub mod paint;
+pub mod volume;
+pub mod color_grading;
+pub mod orientation;
+pub mod normalization;
+pub mod splat_format;
+pub mod ply_profile;
+pub mod validation;
 mod model_types;
 
-pub use model_types::{Model3D, ModelType};
\ No newline at end of file
+pub use model_types::{Model3D, ModelType, ResourceEstimate};
\ No newline at end of file
@@ -0,0 +1,400 @@
+//! Textured mesh export, for engines that don't render splats. The surface
+//! is extracted from the voxel grid with surface nets, every quad gets its
+//! own tile of a texture atlas, and each texel is baked from the colors of
+//! the splats around the point of the surface it lands on.
+
+use rayon::prelude::*;
+use crate::error::{Error, Result};
+use crate::gaussian_cloud::GaussianCloud;
+use crate::voxel::{VoxelGrid, OCCUPANCY_THRESHOLD, VOX_MAX_SIZE};
+
+/// Largest grid dimension a mesh is extracted on
+pub const MESH_MAX_RESOLUTION: usize = VOX_MAX_SIZE;
+
+/// Texture sizes offered for the bake
+pub const TEXTURE_SIZES: [u32; 4] = [512, 1024, 2048, 4096];
+
+/// Smallest atlas tile side in texels; the atlas grows past the requested
+/// size rather than going below it
+const MIN_TILE: u32 = 4;
+
+/// Largest atlas side the bake produces
+const MAX_TEXTURE_SIZE: u32 = 8192;
+
+/// Splats within this many cells of a texel contribute to its color
+const BAKE_RADIUS: f32 = 1.5;
+
+/// Corner offsets of a grid cube, x in bit 0, y in bit 1, z in bit 2
+const CUBE_CORNERS: [[usize; 3]; 8] = [
+    [0, 0, 0], [1, 0, 0], [0, 1, 0], [1, 1, 0],
+    [0, 0, 1], [1, 0, 1], [0, 1, 1], [1, 1, 1],
+];
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct MeshSettings {
+    /// Cells along the longest axis of the grid the surface is extracted on
+    pub resolution: usize,
+    /// Requested side of the square texture atlas, in texels
+    pub texture_size: u32,
+}
+
+impl Default for MeshSettings {
+    fn default() -> Self {
+        Self { resolution: 96, texture_size: 2048 }
+    }
+}
+
+/// Triangle mesh with a baked diffuse texture
+#[derive(Clone, Debug)]
+pub struct TexturedMesh {
+    pub positions: Vec<[f32; 3]>,
+    pub normals: Vec<[f32; 3]>,
+    /// Texture coordinates with the origin at the top left, as in glTF
+    pub uvs: Vec<[f32; 2]>,
+    /// Counter-clockwise triangles
+    pub indices: Vec<u32>,
+    /// Side of the square texture in texels
+    pub texture_size: u32,
+    /// RGBA8 texels, row by row from the top
+    pub texture: Vec<u8>,
+}
+
+impl TexturedMesh {
+    pub fn triangle_count(&self) -> usize {
+        self.indices.len() / 3
+    }
+
+    /// Wavefront OBJ referencing the material library `mtl_name`
+    pub fn to_obj(&self, mtl_name: &str) -> String {
+        use std::fmt::Write;
+
+        let mut obj = String::with_capacity(self.positions.len() * 100);
+        let _ = writeln!(obj, "# genjutsu textured mesh\nmtllib {}\nusemtl splats", mtl_name);
+        for [x, y, z] in &self.positions {
+            let _ = writeln!(obj, "v {} {} {}", x, y, z);
+        }
+        // OBJ puts the texture origin at the bottom left
+        for [u, v] in &self.uvs {
+            let _ = writeln!(obj, "vt {} {}", u, 1.0 - v);
+        }
+        for [x, y, z] in &self.normals {
+            let _ = writeln!(obj, "vn {} {} {}", x, y, z);
+        }
+        for triangle in self.indices.chunks_exact(3) {
+            let [a, b, c] = [triangle[0] + 1, triangle[1] + 1, triangle[2] + 1];
+            let _ = writeln!(obj, "f {a}/{a}/{a} {b}/{b}/{b} {c}/{c}/{c}");
+        }
+        obj
+    }
+
+    /// Material library for [`to_obj`](Self::to_obj) with `texture_name` as the diffuse map
+    pub fn to_mtl(&self, texture_name: &str) -> String {
+        format!("newmtl splats\nKa 1 1 1\nKd 1 1 1\nKs 0 0 0\nd 1\nillum 1\nmap_Kd {}\n", texture_name)
+    }
+
+    /// Binary glTF with the texture embedded; `png` is the texture encoded as PNG
+    pub fn to_glb(&self, png: &[u8]) -> Vec<u8> {
+        let mut bin = Vec::new();
+        let mut views = Vec::new();
+        let mut push_view = |bin: &mut Vec<u8>, bytes: &[u8], target: Option<u32>| {
+            let offset = bin.len();
+            bin.extend_from_slice(bytes);
+            while !bin.len().is_multiple_of(4) {
+                bin.push(0);
+            }
+            let target = target.map(|t| format!(",\"target\":{}", t)).unwrap_or_default();
+            views.push(format!("{{\"buffer\":0,\"byteOffset\":{},\"byteLength\":{}{}}}", offset, bytes.len(), target));
+        };
+        push_view(&mut bin, bytemuck::cast_slice(&self.positions), Some(34962));
+        push_view(&mut bin, bytemuck::cast_slice(&self.normals), Some(34962));
+        push_view(&mut bin, bytemuck::cast_slice(&self.uvs), Some(34962));
+        push_view(&mut bin, bytemuck::cast_slice(&self.indices), Some(34963));
+        push_view(&mut bin, png, None);
+
+        let mut min = [f32::INFINITY; 3];
+        let mut max = [f32::NEG_INFINITY; 3];
+        for p in &self.positions {
+            for axis in 0..3 {
+                min[axis] = min[axis].min(p[axis]);
+                max[axis] = max[axis].max(p[axis]);
+            }
+        }
+
+        let vertices = self.positions.len();
+        let json = format!(
+            concat!(
+                "{{\"asset\":{{\"version\":\"2.0\",\"generator\":\"genjutsu\"}},",
+                "\"scene\":0,\"scenes\":[{{\"nodes\":[0]}}],\"nodes\":[{{\"mesh\":0}}],",
+                "\"meshes\":[{{\"primitives\":[{{\"attributes\":{{\"POSITION\":0,\"NORMAL\":1,\"TEXCOORD_0\":2}},\"indices\":3,\"material\":0}}]}}],",
+                "\"materials\":[{{\"name\":\"splats\",\"pbrMetallicRoughness\":{{\"baseColorTexture\":{{\"index\":0}},\"metallicFactor\":0,\"roughnessFactor\":1}}}}],",
+                "\"textures\":[{{\"source\":0,\"sampler\":0}}],",
+                "\"samplers\":[{{\"magFilter\":9729,\"minFilter\":9987}}],",
+                "\"images\":[{{\"bufferView\":4,\"mimeType\":\"image/png\"}}],",
+                "\"accessors\":[",
+                "{{\"bufferView\":0,\"componentType\":5126,\"count\":{vertices},\"type\":\"VEC3\",\"min\":[{},{},{}],\"max\":[{},{},{}]}},",
+                "{{\"bufferView\":1,\"componentType\":5126,\"count\":{vertices},\"type\":\"VEC3\"}},",
+                "{{\"bufferView\":2,\"componentType\":5126,\"count\":{vertices},\"type\":\"VEC2\"}},",
+                "{{\"bufferView\":3,\"componentType\":5125,\"count\":{indices},\"type\":\"SCALAR\"}}],",
+                "\"bufferViews\":[{views}],\"buffers\":[{{\"byteLength\":{length}}}]}}",
+            ),
+            min[0], min[1], min[2], max[0], max[1], max[2],
+            vertices = vertices,
+            indices = self.indices.len(),
+            views = views.join(","),
+            length = bin.len(),
+        );
+        let mut json = json.into_bytes();
+        while !json.len().is_multiple_of(4) {
+            json.push(b' ');
+        }
+
+        let total = 12 + 8 + json.len() + 8 + bin.len();
+        let mut glb = Vec::with_capacity(total);
+        glb.extend_from_slice(b"glTF");
+        glb.extend_from_slice(&2u32.to_le_bytes());
+        glb.extend_from_slice(&(total as u32).to_le_bytes());
+        glb.extend_from_slice(&(json.len() as u32).to_le_bytes());
+        glb.extend_from_slice(b"JSON");
+        glb.extend_from_slice(&json);
+        glb.extend_from_slice(&(bin.len() as u32).to_le_bytes());
+        glb.extend_from_slice(b"BIN\0");
+        glb.extend_from_slice(&bin);
+        glb
+    }
+}
+
+impl VoxelGrid {
+    /// Surface through the grid where the occupancy crosses the fill
+    /// threshold, as vertices and counter-clockwise quads facing out.
+    ///
+    /// Surface nets: the occupancy is sampled at cell centers, with a ring of
+    /// empty samples around the grid so the surface is closed. Every cube
+    /// between eight samples the surface passes through gets one vertex, at
+    /// the mean of the points where it crosses the cube's edges, and every
+    /// sample edge it crosses gets a quad joining the four cubes around it.
+    pub fn surface_nets(&self) -> (Vec<[f32; 3]>, Vec<[u32; 4]>) {
+        // Samples, one more on each side than the grid has cells
+        let samples = self.size.map(|s| s + 2);
+        let value = |q: [usize; 3]| -> f32 {
+            if (0..3).any(|axis| q[axis] == 0 || q[axis] > self.size[axis]) {
+                return 0.0;
+            }
+            let cell = q.map(|c| c - 1);
+            self.occupancy[self.index(cell)].min(2.0 * OCCUPANCY_THRESHOLD)
+        };
+        let inside = |q: [usize; 3]| value(q) >= OCCUPANCY_THRESHOLD;
+        let world = |p: [f32; 3]| -> [f32; 3] {
+            std::array::from_fn(|axis| self.bounds.min[axis] + (p[axis] - 0.5) * self.cell_size)
+        };
+
+        let cubes = samples.map(|s| s - 1);
+        let cube_index = |c: [usize; 3]| c[0] + cubes[0] * (c[1] + cubes[1] * c[2]);
+        let mut cube_vertex = vec![u32::MAX; cubes[0] * cubes[1] * cubes[2]];
+        let mut vertices = Vec::new();
+
+        for z in 0..cubes[2] {
+            for y in 0..cubes[1] {
+                for x in 0..cubes[0] {
+                    let corners = CUBE_CORNERS.map(|[dx, dy, dz]| [x + dx, y + dy, z + dz]);
+                    let values = corners.map(value);
+                    let filled = values.iter().filter(|&&v| v >= OCCUPANCY_THRESHOLD).count();
+                    if filled == 0 || filled == 8 {
+                        continue;
+                    }
+
+                    let mut sum = [0.0f32; 3];
+                    let mut crossings = 0;
+                    for a in 0..8 {
+                        for axis in 0..3 {
+                            let b = a | 1 << axis;
+                            if a & 1 << axis != 0 || (values[a] >= OCCUPANCY_THRESHOLD) == (values[b] >= OCCUPANCY_THRESHOLD) {
+                                continue;
+                            }
+                            let t = (OCCUPANCY_THRESHOLD - values[a]) / (values[b] - values[a]);
+                            for (i, s) in sum.iter_mut().enumerate() {
+                                *s += corners[a][i] as f32 + if i == axis { t } else { 0.0 };
+                            }
+                            crossings += 1;
+                        }
+                    }
+
+                    cube_vertex[cube_index([x, y, z])] = vertices.len() as u32;
+                    vertices.push(world(sum.map(|s| s / crossings as f32)));
+                }
+            }
+        }
+
+        let mut quads = Vec::new();
+        for z in 0..samples[2] - 1 {
+            for y in 0..samples[1] - 1 {
+                for x in 0..samples[0] - 1 {
+                    let q = [x, y, z];
+                    let here = inside(q);
+                    for axis in 0..3 {
+                        // Edges on the outer faces lie in the empty ring and never cross
+                        let (b, c) = ((axis + 1) % 3, (axis + 2) % 3);
+                        if q[b] == 0 || q[c] == 0 {
+                            continue;
+                        }
+                        let mut next = q;
+                        next[axis] += 1;
+                        if here == inside(next) {
+                            continue;
+                        }
+
+                        // The four cubes around the edge, counter-clockwise about +axis
+                        let around = [(1, 1), (0, 1), (0, 0), (1, 0)].map(|(db, dc)| {
+                            let mut cube = q;
+                            cube[b] -= db;
+                            cube[c] -= dc;
+                            cube_vertex[cube_index(cube)]
+                        });
+                        quads.push(if here { around } else { [around[0], around[3], around[2], around[1]] });
+                    }
+                }
+            }
+        }
+
+        (vertices, quads)
+    }
+}
+
+fn sub(a: [f32; 3], b: [f32; 3]) -> [f32; 3] {
+    [a[0] - b[0], a[1] - b[1], a[2] - b[2]]
+}
+
+fn cross(a: [f32; 3], b: [f32; 3]) -> [f32; 3] {
+    [a[1] * b[2] - a[2] * b[1], a[2] * b[0] - a[0] * b[2], a[0] * b[1] - a[1] * b[0]]
+}
+
+fn lerp3(points: [[f32; 3]; 3], weights: [f32; 3]) -> [f32; 3] {
+    std::array::from_fn(|axis| points.iter().zip(weights).map(|(p, w)| p[axis] * w).sum())
+}
+
+impl GaussianCloud {
+    /// Extract a closed surface from the splats with `settings.resolution`
+    /// cells along the longest axis, unwrap it into a texture atlas and bake
+    /// the splat colors into it
+    pub fn to_textured_mesh(&self, settings: MeshSettings) -> Result<TexturedMesh> {
+        if settings.resolution > MESH_MAX_RESOLUTION {
+            return Err(Error::InvalidConfig(format!(
+                "Mesh resolution {} exceeds the limit of {}", settings.resolution, MESH_MAX_RESOLUTION
+            )));
+        }
+
+        let grid = self.to_voxels(settings.resolution);
+        let (vertices, quads) = grid.surface_nets();
+        if quads.is_empty() {
+            return Err(Error::InvalidGaussianCloud(
+                "No surface to mesh; the splats are too sparse at this resolution".into()
+            ));
+        }
+
+        // Smooth normals from the quads around each vertex, weighted by area
+        let mut vertex_normals = vec![[0.0f32; 3]; vertices.len()];
+        for quad in &quads {
+            let [p0, p1, p2, p3] = quad.map(|v| vertices[v as usize]);
+            let normal = cross(sub(p2, p0), sub(p3, p1));
+            for &v in quad {
+                for (n, c) in vertex_normals[v as usize].iter_mut().zip(normal) {
+                    *n += c;
+                }
+            }
+        }
+        for normal in &mut vertex_normals {
+            let length = normal.iter().map(|c| c * c).sum::<f32>().sqrt();
+            *normal = if length > 0.0 { normal.map(|c| c / length) } else { [0.0, 1.0, 0.0] };
+        }
+
+        // One square tile per quad, laid out in rows
+        let tiles = ((quads.len() as f64).sqrt().ceil() as u32).max(1);
+        let tile = (settings.texture_size / tiles).max(MIN_TILE);
+        let texture_size = tiles * tile;
+        if texture_size > MAX_TEXTURE_SIZE {
+            return Err(Error::InvalidConfig(format!(
+                "{} faces don't fit a {}px texture; lower the mesh resolution", quads.len(), MAX_TEXTURE_SIZE
+            )));
+        }
+
+        // Tile corners sit on the centers of the tile's outer texels, so
+        // filtering at an edge never reaches into the neighboring tile
+        let tile_uv = |index: usize, [s, t]: [f32; 2]| -> [f32; 2] {
+            let origin = [index as u32 % tiles, index as u32 / tiles].map(|o| (o * tile) as f32 + 0.5);
+            let span = (tile - 1) as f32;
+            [(origin[0] + s * span) / texture_size as f32, (origin[1] + t * span) / texture_size as f32]
+        };
+        let corner_uvs = [[0.0, 0.0], [1.0, 0.0], [1.0, 1.0], [0.0, 1.0]];
+
+        let mut positions = Vec::with_capacity(quads.len() * 4);
+        let mut normals = Vec::with_capacity(quads.len() * 4);
+        let mut uvs = Vec::with_capacity(quads.len() * 4);
+        let mut indices = Vec::with_capacity(quads.len() * 6);
+        for (i, quad) in quads.iter().enumerate() {
+            let base = positions.len() as u32;
+            for (&v, corner) in quad.iter().zip(corner_uvs) {
+                positions.push(vertices[v as usize]);
+                normals.push(vertex_normals[v as usize]);
+                uvs.push(tile_uv(i, corner));
+            }
+            indices.extend([base, base + 1, base + 2, base, base + 2, base + 3]);
+        }
+
+        let octree = self.octree();
+        let radius = BAKE_RADIUS * grid.cell_size;
+        let sigma_sq = (0.5 * grid.cell_size).powi(2);
+        let color_at = |p: [f32; 3]| -> [f32; 3] {
+            let mut sum = [0.0f32; 3];
+            let mut weight = 0.0f32;
+            for i in octree.within_radius(self, p, radius) {
+                let opacity = self.opacity[i];
+                if opacity.is_nan() || opacity <= 0.0 {
+                    continue;
+                }
+                let d_sq = sub(self.positions[i], p).iter().map(|c| c * c).sum::<f32>();
+                let w = opacity * (-d_sq / (2.0 * sigma_sq)).exp();
+                for (s, c) in sum.iter_mut().zip(self.colors[i]) {
+                    *s += c * w;
+                }
+                weight += w;
+            }
+            if weight > f32::EPSILON {
+                sum.map(|s| s / weight)
+            } else {
+                octree.nearest(self, p).map(|(i, _)| self.colors[i]).unwrap_or([0.5; 3])
+            }
+        };
+
+        let baked: Vec<Vec<[u8; 4]>> = quads.par_iter()
+            .map(|quad| {
+                let [p0, p1, p2, p3] = quad.map(|v| vertices[v as usize]);
+                let span = (tile - 1) as f32;
+                let mut texels = Vec::with_capacity((tile * tile) as usize);
+                for y in 0..tile {
+                    for x in 0..tile {
+                        let (s, t) = (x as f32 / span, y as f32 / span);
+                        // Barycentric within whichever of the quad's two triangles holds the texel
+                        let p = if t <= s {
+                            lerp3([p0, p1, p2], [1.0 - s, s - t, t])
+                        } else {
+                            lerp3([p0, p2, p3], [1.0 - t, s, t - s])
+                        };
+                        let [r, g, b] = color_at(p).map(|c| (c.clamp(0.0, 1.0) * 255.0).round() as u8);
+                        texels.push([r, g, b, 255]);
+                    }
+                }
+                texels
+            })
+            .collect();
+
+        let mut texture = vec![0u8; (texture_size * texture_size * 4) as usize];
+        for (i, texels) in baked.iter().enumerate() {
+            let origin = [i as u32 % tiles * tile, i as u32 / tiles * tile];
+            for (row, line) in texels.chunks_exact(tile as usize).enumerate() {
+                let start = (((origin[1] + row as u32) * texture_size + origin[0]) * 4) as usize;
+                texture[start..start + line.len() * 4].copy_from_slice(bytemuck::cast_slice(line));
+            }
+        }
+
+        Ok(TexturedMesh { positions, normals, uvs, indices, texture_size, texture })
+    }
+}
@@ -1,7 +1,10 @@
 /// Unified model type definition shared across the application
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
 pub enum Model3D {
+    #[default]
     ShapE,
+    /// Reconstructs splats from four views it has drawn first
+    Lgm,
 }
 
 impl Model3D {
@@ -9,6 +12,7 @@ impl Model3D {
     pub fn name(&self) -> &str {
         match self {
             Self::ShapE => "Shap-E",
+            Self::Lgm => "LGM",
         }
     }
 
@@ -16,13 +20,20 @@ impl Model3D {
     pub fn id(&self) -> &str {
         match self {
             Self::ShapE => "shap_e",
+            Self::Lgm => "lgm",
         }
     }
 
+    /// Look up a model by its API id
+    pub fn from_id(id: &str) -> Option<Self> {
+        Self::all().into_iter().find(|m| m.id() == id)
+    }
+
     /// Human-readable description
     pub fn description(&self) -> &str {
         match self {
             Self::ShapE => "OpenAI's Shap-E - Fast text-to-3D (30-60 sec)",
+            Self::Lgm => "Large Multi-View Gaussian Model - Splats from four generated views (30-90 sec)",
         }
     }
 
@@ -30,13 +41,14 @@ impl Model3D {
     pub fn icon(&self) -> &str {
         match self {
             Self::ShapE => "⚡",
+            Self::Lgm => "🔷",
         }
     }
 
     /// Model type (scene vs object)
     pub fn model_type(&self) -> ModelType {
         match self {
-            Self::ShapE => ModelType::Object,
+            Self::ShapE | Self::Lgm => ModelType::Object,
         }
     }
 
@@ -44,6 +56,32 @@ impl Model3D {
     pub fn estimated_time_secs(&self) -> u32 {
         match self {
             Self::ShapE => 45,  // ~30-60 seconds
+            Self::Lgm => 60,
+        }
+    }
+
+    /// Rough peak GPU memory and run time of a job with these parameters,
+    /// from runs on the reference worker
+    pub fn estimate(&self, num_inference_steps: u32, guidance_scale: f32) -> ResourceEstimate {
+        match self {
+            Self::ShapE => {
+                // text300M and the transmitter in fp16; classifier-free
+                // guidance doubles the batch. Steps only cost time.
+                let guided = guidance_scale != 1.0;
+                ResourceEstimate {
+                    vram_mb: if guided { 5_200 } else { 4_800 },
+                    time_secs: 30 + num_inference_steps / 2,
+                }
+            }
+            Self::Lgm => {
+                // Stable Diffusion with its ControlNet and LGM itself stay
+                // loaded together; steps are per view, of four
+                let guided = guidance_scale != 1.0;
+                ResourceEstimate {
+                    vram_mb: if guided { 7_000 } else { 6_000 },
+                    time_secs: 10 + 4 * num_inference_steps / 5,
+                }
+            }
         }
     }
 
@@ -51,12 +89,28 @@ impl Model3D {
     pub fn quality(&self) -> Quality {
         match self {
             Self::ShapE => Quality::High,
+            Self::Lgm => Quality::VeryHigh,
         }
     }
 
     /// All available models
-    pub fn all() -> [Model3D; 1] {
-        [Self::ShapE]
+    pub fn all() -> [Model3D; 2] {
+        [Self::ShapE, Self::Lgm]
+    }
+}
+
+/// Expected cost of one generation job
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ResourceEstimate {
+    /// Peak GPU memory in MiB
+    pub vram_mb: u64,
+    pub time_secs: u32,
+}
+
+impl ResourceEstimate {
+    /// Whether the job likely won't fit on a GPU with `available_mb` MiB
+    pub fn exceeds(&self, available_mb: u64) -> bool {
+        self.vram_mb > available_mb
     }
 }
 
@@ -73,12 +127,6 @@ pub enum Quality {
     VeryHigh,
 }
 
-impl Default for Model3D {
-    fn default() -> Self {
-        Self::ShapE
-    }
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -86,6 +134,9 @@ mod tests {
     #[test]
     fn test_model_ids() {
         assert_eq!(Model3D::ShapE.id(), "shap_e");
+        assert_eq!(Model3D::from_id("shap_e"), Some(Model3D::ShapE));
+        assert_eq!(Model3D::from_id("lgm"), Some(Model3D::Lgm));
+        assert_eq!(Model3D::from_id("nope"), None);
     }
 
     #[test]
@@ -93,8 +144,18 @@ mod tests {
         assert_eq!(Model3D::ShapE.model_type(), ModelType::Object);
     }
 
+    #[test]
+    fn test_estimates() {
+        let short = Model3D::ShapE.estimate(16, 15.0);
+        let long = Model3D::ShapE.estimate(256, 15.0);
+        assert!(long.time_secs > short.time_secs);
+        assert_eq!(long.vram_mb, short.vram_mb);
+        assert!(short.exceeds(4_096));
+        assert!(!short.exceeds(8_192));
+    }
+
     #[test]
     fn test_all_models() {
-        assert_eq!(Model3D::all().len(), 1);
+        assert_eq!(Model3D::all().len(), 2);
     }
 }
\ No newline at end of file
@@ -0,0 +1,93 @@
+//! Morphing one cloud into another, for showing how two generations relate,
+//! e.g. the ends of a prompt interpolation. Every splat is paired with the
+//! nearest splat of the other cloud, so align the clouds first for sensible
+//! pairs.
+
+use rayon::prelude::*;
+use crate::error::{Error, Result};
+use crate::gaussian_cloud::GaussianCloud;
+
+/// Paired splats of two clouds, ready to interpolate
+#[derive(Clone, Debug)]
+pub struct Morph {
+    /// The start cloud's splat of each pair
+    from: GaussianCloud,
+    /// The end cloud's splat of each pair
+    to: GaussianCloud,
+}
+
+/// Index of the splat of `cloud` nearest each of `points`
+fn nearest_indices(points: &[[f32; 3]], cloud: &GaussianCloud) -> Vec<usize> {
+    let octree = cloud.octree();
+    points.par_iter()
+        .map(|&p| octree.nearest(cloud, p).map_or(0, |(i, _)| i))
+        .collect()
+}
+
+fn lerp(a: f32, b: f32, t: f32) -> f32 {
+    a + (b - a) * t
+}
+
+impl Morph {
+    /// Pair each splat of `from` with its nearest in `to`, then each splat
+    /// of `to` left unpaired with its nearest in `from`, so neither cloud
+    /// has parts vanish halfway
+    pub fn new(from: &GaussianCloud, to: &GaussianCloud) -> Result<Self> {
+        if from.count == 0 || to.count == 0 {
+            return Err(Error::InvalidGaussianCloud("Cannot morph an empty cloud".to_string()));
+        }
+
+        let mut targets = nearest_indices(&from.positions, to);
+        let mut paired = vec![false; to.count];
+        for &j in &targets {
+            paired[j] = true;
+        }
+        let unpaired: Vec<usize> = (0..to.count).filter(|&j| !paired[j]).collect();
+        let unpaired_positions: Vec<[f32; 3]> = unpaired.iter().map(|&j| to.positions[j]).collect();
+
+        let mut sources: Vec<usize> = (0..from.count).collect();
+        sources.extend(nearest_indices(&unpaired_positions, from));
+        targets.extend(unpaired);
+
+        Ok(Self { from: from.subset(&sources), to: to.subset(&targets) })
+    }
+
+    /// Number of splat pairs, the size of every in-between cloud
+    pub fn pairs(&self) -> usize {
+        self.from.count
+    }
+
+    /// The in-between cloud at `t`, from 0 for the start to 1 for the end.
+    /// SH coefficients are kept only when both clouds have the same degree.
+    pub fn at(&self, t: f32) -> GaussianCloud {
+        let t = t.clamp(0.0, 1.0);
+        let (a, b) = (&self.from, &self.to);
+        let lerp3 = |x: &[f32; 3], y: &[f32; 3]| std::array::from_fn(|i| lerp(x[i], y[i], t));
+
+        let mut cloud = GaussianCloud::with_capacity(a.count);
+        cloud.count = a.count;
+        cloud.positions = a.positions.iter().zip(&b.positions).map(|(x, y)| lerp3(x, y)).collect();
+        cloud.scales = a.scales.iter().zip(&b.scales).map(|(x, y)| lerp3(x, y)).collect();
+        cloud.colors = a.colors.iter().zip(&b.colors).map(|(x, y)| lerp3(x, y)).collect();
+        cloud.opacity = a.opacity.iter().zip(&b.opacity).map(|(&x, &y)| lerp(x, y, t)).collect();
+        // Normalized lerp along the shorter way around
+        cloud.rotations = a.rotations.iter().zip(&b.rotations)
+            .map(|(x, y)| {
+                let dot: f32 = (0..4).map(|i| x[i] * y[i]).sum();
+                let sign = if dot < 0.0 { -1.0 } else { 1.0 };
+                let q: [f32; 4] = std::array::from_fn(|i| lerp(x[i], y[i] * sign, t));
+                let norm = q.iter().map(|v| v * v).sum::<f32>().sqrt().max(f32::EPSILON);
+                q.map(|v| v / norm)
+            })
+            .collect();
+        cloud.sh_coefficients = match (&a.sh_coefficients, &b.sh_coefficients) {
+            (Some(x), Some(y)) if x.first().map(Vec::len) == y.first().map(Vec::len) => Some(
+                x.iter().zip(y)
+                    .map(|(x, y)| x.iter().zip(y).map(|(&x, &y)| lerp(x, y, t)).collect())
+                    .collect()
+            ),
+            _ => None,
+        };
+        cloud
+    }
+}
@@ -0,0 +1,75 @@
+//! Re-centering and uniform rescaling into a unit box, so assets compose at
+//! a common size and frame consistently.
+
+use crate::gaussian_cloud::GaussianCloud;
+
+/// Transform applied by [`GaussianCloud::normalize`]: a point `p` maps to
+/// `(p - center) * scale`
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Normalization {
+    /// Bounds center before normalizing
+    pub center: [f32; 3],
+    pub scale: f32,
+}
+
+impl Default for Normalization {
+    fn default() -> Self {
+        Self { center: [0.0; 3], scale: 1.0 }
+    }
+}
+
+impl Normalization {
+    pub fn apply(&self, point: [f32; 3]) -> [f32; 3] {
+        std::array::from_fn(|i| (point[i] - self.center[i]) * self.scale)
+    }
+
+    /// Map a normalized point back to the original space
+    pub fn invert(&self, point: [f32; 3]) -> [f32; 3] {
+        std::array::from_fn(|i| point[i] / self.scale + self.center[i])
+    }
+
+    /// This normalization followed by `next`, as one
+    pub fn then(&self, next: &Normalization) -> Normalization {
+        Normalization {
+            center: std::array::from_fn(|i| self.center[i] + next.center[i] / self.scale),
+            scale: self.scale * next.scale,
+        }
+    }
+}
+
+impl GaussianCloud {
+    /// Move the bounds center to the origin and scale uniformly so the
+    /// longest side is 1. Splat sizes scale along. Returns the transform so
+    /// it can be undone with [`GaussianCloud::denormalize`].
+    pub fn normalize(&mut self) -> Normalization {
+        if self.count == 0 {
+            return Normalization::default();
+        }
+
+        let bounds = self.bounds();
+        let size = bounds.size();
+        let longest = size[0].max(size[1]).max(size[2]);
+        let normalization = Normalization {
+            center: bounds.center(),
+            scale: if longest > f32::EPSILON { 1.0 / longest } else { 1.0 },
+        };
+
+        self.rescale(|p| normalization.apply(p), normalization.scale);
+        normalization
+    }
+
+    /// Undo an earlier [`GaussianCloud::normalize`]
+    pub fn denormalize(&mut self, normalization: &Normalization) {
+        self.rescale(|p| normalization.invert(p), 1.0 / normalization.scale);
+    }
+
+    fn rescale(&mut self, map: impl Fn([f32; 3]) -> [f32; 3], factor: f32) {
+        for position in &mut self.positions {
+            *position = map(*position);
+        }
+        for scale in &mut self.scales {
+            *scale = scale.map(|s| s * factor);
+        }
+        self.invalidate_octree();
+    }
+}
@@ -0,0 +1,44 @@
+//! Per-splat normals estimated from the covariance. A well-fitted surface
+//! splat is flat, so its shortest axis is perpendicular to the surface.
+
+use rayon::prelude::*;
+use crate::gaussian_cloud::GaussianCloud;
+
+/// Normal of one splat: the rotated axis with the smallest scale, unit
+/// length. Unusable rotations fall back to the identity.
+pub fn splat_normal(rotation: [f32; 4], scale: [f32; 3]) -> [f32; 3] {
+    let length = rotation.iter().map(|r| r * r).sum::<f32>().sqrt();
+    let [w, x, y, z] = if length.is_finite() && length > f32::EPSILON {
+        rotation.map(|r| r / length)
+    } else {
+        [1.0, 0.0, 0.0, 0.0]
+    };
+
+    let axis = (0..3).min_by(|&a, &b| scale[a].total_cmp(&scale[b])).unwrap_or(2);
+
+    // Column `axis` of the rotation matrix
+    match axis {
+        0 => [1.0 - 2.0 * (y * y + z * z), 2.0 * (x * y + w * z), 2.0 * (x * z - w * y)],
+        1 => [2.0 * (x * y - w * z), 1.0 - 2.0 * (x * x + z * z), 2.0 * (y * z + w * x)],
+        _ => [2.0 * (x * z + w * y), 2.0 * (y * z - w * x), 1.0 - 2.0 * (x * x + y * y)],
+    }
+}
+
+impl GaussianCloud {
+    /// Unit normal of every splat, see [`splat_normal`]. The sign of an axis
+    /// is arbitrary, so normals are flipped to face away from the center of
+    /// the bounds, which is outward for the single objects we generate.
+    pub fn normals(&self) -> Vec<[f32; 3]> {
+        let center = self.bounds().center();
+
+        self.positions.par_iter()
+            .zip(self.rotations.par_iter())
+            .zip(self.scales.par_iter())
+            .map(|((position, &rotation), &scale)| {
+                let normal = splat_normal(rotation, scale);
+                let outward: f32 = (0..3).map(|i| normal[i] * (position[i] - center[i])).sum();
+                if outward < 0.0 { normal.map(|n| -n) } else { normal }
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,338 @@
+//! Octree over splat centers.
+//!
+//! Built once per cloud (see [`GaussianCloud::octree`]) and used for frustum
+//! culling, ray picking, level-of-detail selection and neighborhood queries.
+//! Every node owns a contiguous range of `indices`, so collecting a whole
+//! subtree is a slice copy.
+
+use crate::bounding_box::BoundingBox;
+use crate::gaussian_cloud::GaussianCloud;
+
+/// Nodes with this many splats or fewer are not split further
+const LEAF_SIZE: usize = 32;
+
+/// Deepest level a node can sit at, so stacks of coincident splats terminate
+const MAX_DEPTH: u32 = 16;
+
+#[derive(Clone, Debug)]
+struct Node {
+    /// Tight bounds of the splat centers in the node
+    bounds: BoundingBox,
+    /// Largest splat radius in the node, padding `bounds` for ray and frustum tests
+    max_radius: f32,
+    depth: u32,
+    /// Range into `Octree::indices`
+    start: usize,
+    end: usize,
+    /// Children are stored consecutively from `first_child`; none for leaves
+    first_child: usize,
+    child_count: usize,
+}
+
+impl Node {
+    fn is_leaf(&self) -> bool {
+        self.child_count == 0
+    }
+
+    fn children(&self) -> std::ops::Range<usize> {
+        self.first_child..self.first_child + self.child_count
+    }
+
+    /// Squared distance from `p` to the node's bounds, 0 inside
+    fn distance_sq(&self, p: [f32; 3]) -> f32 {
+        (0..3)
+            .map(|i| {
+                let d = (self.bounds.min[i] - p[i]).max(p[i] - self.bounds.max[i]).max(0.0);
+                d * d
+            })
+            .sum()
+    }
+
+    /// Entry distance of the ray into the padded bounds, if it hits them
+    fn ray_entry(&self, origin: [f32; 3], direction: [f32; 3]) -> Option<f32> {
+        let mut t_min = 0.0f32;
+        let mut t_max = f32::INFINITY;
+
+        for i in 0..3 {
+            let min = self.bounds.min[i] - self.max_radius;
+            let max = self.bounds.max[i] + self.max_radius;
+
+            if direction[i].abs() < f32::EPSILON {
+                if origin[i] < min || origin[i] > max {
+                    return None;
+                }
+                continue;
+            }
+
+            let t0 = (min - origin[i]) / direction[i];
+            let t1 = (max - origin[i]) / direction[i];
+            t_min = t_min.max(t0.min(t1));
+            t_max = t_max.min(t0.max(t1));
+            if t_min > t_max {
+                return None;
+            }
+        }
+
+        Some(t_min)
+    }
+
+    /// Whether the padded bounds are entirely behind any of the planes
+    fn outside(&self, planes: &[[f32; 4]]) -> bool {
+        planes.iter().any(|&[nx, ny, nz, d]| {
+            let n = [nx, ny, nz];
+            // Corner of the box furthest along the plane normal
+            let corner: [f32; 3] = std::array::from_fn(|i| {
+                if n[i] >= 0.0 { self.bounds.max[i] } else { self.bounds.min[i] }
+            });
+            let length = (nx * nx + ny * ny + nz * nz).sqrt();
+            n[0] * corner[0] + n[1] * corner[1] + n[2] * corner[2] + d < -self.max_radius * length
+        })
+    }
+}
+
+/// Spatial index over the centers of a [`GaussianCloud`]. Splats with
+/// non-finite positions are left out.
+#[derive(Clone, Debug, Default)]
+pub struct Octree {
+    nodes: Vec<Node>,
+    /// Splat indices, grouped so every node owns a contiguous range
+    indices: Vec<usize>,
+}
+
+/// Largest scale axis of splat `i`, or 0 when the scale is unusable
+fn splat_radius(cloud: &GaussianCloud, i: usize) -> f32 {
+    let [x, y, z] = cloud.scales[i];
+    let radius = x.max(y).max(z);
+    if radius.is_finite() && radius > 0.0 { radius } else { 0.0 }
+}
+
+fn distance_sq(a: [f32; 3], b: [f32; 3]) -> f32 {
+    (a[0] - b[0]).powi(2) + (a[1] - b[1]).powi(2) + (a[2] - b[2]).powi(2)
+}
+
+impl Octree {
+    pub fn build(cloud: &GaussianCloud) -> Self {
+        let indices: Vec<usize> = (0..cloud.count)
+            .filter(|&i| cloud.positions[i].iter().all(|c| c.is_finite()))
+            .collect();
+
+        let mut tree = Self { nodes: Vec::new(), indices };
+        if tree.indices.is_empty() {
+            return tree;
+        }
+
+        let root = tree.make_node(cloud, 0, tree.indices.len(), 0);
+        tree.nodes.push(root);
+        tree.split(cloud, 0);
+        tree
+    }
+
+    fn make_node(&self, cloud: &GaussianCloud, start: usize, end: usize, depth: u32) -> Node {
+        let mut min = [f32::INFINITY; 3];
+        let mut max = [f32::NEG_INFINITY; 3];
+        let mut max_radius = 0.0f32;
+
+        for &i in &self.indices[start..end] {
+            let pos = cloud.positions[i];
+            for axis in 0..3 {
+                min[axis] = min[axis].min(pos[axis]);
+                max[axis] = max[axis].max(pos[axis]);
+            }
+            max_radius = max_radius.max(splat_radius(cloud, i));
+        }
+
+        Node {
+            bounds: BoundingBox { min, max },
+            max_radius,
+            depth,
+            start,
+            end,
+            first_child: 0,
+            child_count: 0,
+        }
+    }
+
+    fn split(&mut self, cloud: &GaussianCloud, node: usize) {
+        let Node { bounds, depth, start, end, .. } = self.nodes[node].clone();
+        if end - start <= LEAF_SIZE || depth >= MAX_DEPTH || bounds.size().iter().all(|&s| s <= 0.0) {
+            return;
+        }
+
+        let center = bounds.center();
+        let octant = |i: usize| {
+            let pos = cloud.positions[i];
+            (pos[0] >= center[0]) as usize | ((pos[1] >= center[1]) as usize) << 1 | ((pos[2] >= center[2]) as usize) << 2
+        };
+        self.indices[start..end].sort_unstable_by_key(|&i| octant(i));
+
+        let first_child = self.nodes.len();
+        let mut child_start = start;
+        while child_start < end {
+            let key = octant(self.indices[child_start]);
+            let child_end = child_start + self.indices[child_start..end].partition_point(|&i| octant(i) == key);
+            let child = self.make_node(cloud, child_start, child_end, depth + 1);
+            self.nodes.push(child);
+            child_start = child_end;
+        }
+
+        self.nodes[node].first_child = first_child;
+        self.nodes[node].child_count = self.nodes.len() - first_child;
+        for child in first_child..self.nodes.len() {
+            self.split(cloud, child);
+        }
+    }
+
+    /// Bounds of all indexed splat centers
+    pub fn bounds(&self) -> BoundingBox {
+        self.nodes.first().map(|root| root.bounds.clone()).unwrap_or_default()
+    }
+
+    /// Number of levels below the root
+    pub fn depth(&self) -> u32 {
+        self.nodes.iter().map(|n| n.depth).max().unwrap_or(0)
+    }
+
+    /// Indices of splats whose centers lie within `radius` of `center`
+    pub fn within_radius(&self, cloud: &GaussianCloud, center: [f32; 3], radius: f32) -> Vec<usize> {
+        let radius_sq = radius * radius;
+        let mut found = Vec::new();
+        let mut stack: Vec<usize> = if self.nodes.is_empty() { Vec::new() } else { vec![0] };
+
+        while let Some(n) = stack.pop() {
+            let node = &self.nodes[n];
+            if node.distance_sq(center) > radius_sq {
+                continue;
+            }
+
+            if node.is_leaf() {
+                found.extend(self.indices[node.start..node.end].iter()
+                    .copied()
+                    .filter(|&i| distance_sq(cloud.positions[i], center) <= radius_sq));
+            } else {
+                stack.extend(node.children());
+            }
+        }
+
+        found
+    }
+
+    /// Nearest splat center to `point` and its distance
+    pub fn nearest(&self, cloud: &GaussianCloud, point: [f32; 3]) -> Option<(usize, f32)> {
+        let mut best: Option<(usize, f32)> = None;
+        let mut stack: Vec<usize> = if self.nodes.is_empty() { Vec::new() } else { vec![0] };
+
+        while let Some(n) = stack.pop() {
+            let node = &self.nodes[n];
+            if best.is_some_and(|(_, d)| node.distance_sq(point) >= d) {
+                continue;
+            }
+
+            if node.is_leaf() {
+                for &i in &self.indices[node.start..node.end] {
+                    let d = distance_sq(cloud.positions[i], point);
+                    if best.is_none_or(|(_, best_d)| d < best_d) {
+                        best = Some((i, d));
+                    }
+                }
+            } else {
+                // Visit the closest child first so the others are more likely pruned
+                let mut children: Vec<usize> = node.children().collect();
+                children.sort_by(|&a, &b| self.nodes[b].distance_sq(point).total_cmp(&self.nodes[a].distance_sq(point)));
+                stack.extend(children);
+            }
+        }
+
+        best.map(|(i, d)| (i, d.sqrt()))
+    }
+
+    /// Cast a ray against the splats, each treated as a sphere sized by its
+    /// largest scale axis. `direction` must be normalized.
+    ///
+    /// Returns the index of the nearest hit and its distance along the ray.
+    pub fn raycast(&self, cloud: &GaussianCloud, origin: [f32; 3], direction: [f32; 3]) -> Option<(usize, f32)> {
+        let mut nearest: Option<(usize, f32)> = None;
+        let mut stack: Vec<usize> = if self.nodes.is_empty() { Vec::new() } else { vec![0] };
+
+        while let Some(n) = stack.pop() {
+            let node = &self.nodes[n];
+            let Some(entry) = node.ray_entry(origin, direction) else {
+                continue;
+            };
+            if nearest.is_some_and(|(_, best)| entry > best) {
+                continue;
+            }
+
+            if !node.is_leaf() {
+                stack.extend(node.children());
+                continue;
+            }
+
+            for &i in &self.indices[node.start..node.end] {
+                let radius = splat_radius(cloud, i);
+                if radius <= 0.0 {
+                    continue;
+                }
+
+                let pos = cloud.positions[i];
+                let to_center = [pos[0] - origin[0], pos[1] - origin[1], pos[2] - origin[2]];
+                let t = to_center[0] * direction[0] + to_center[1] * direction[1] + to_center[2] * direction[2];
+                if t < 0.0 {
+                    continue;
+                }
+
+                let dist_sq = distance_sq(pos, origin) - t * t;
+                if dist_sq > radius * radius {
+                    continue;
+                }
+
+                let hit = (t - (radius * radius - dist_sq).sqrt()).max(0.0);
+                if nearest.is_none_or(|(_, best)| hit < best) {
+                    nearest = Some((i, hit));
+                }
+            }
+        }
+
+        nearest
+    }
+
+    /// Indices of splats that may be visible inside the convex volume bounded
+    /// by `planes`, each `[nx, ny, nz, d]` with the inside where `n·p + d >= 0`.
+    /// Whole nodes are rejected, so a few splats just outside may be kept.
+    pub fn cull(&self, planes: &[[f32; 4]]) -> Vec<usize> {
+        let mut visible = Vec::new();
+        let mut stack: Vec<usize> = if self.nodes.is_empty() { Vec::new() } else { vec![0] };
+
+        while let Some(n) = stack.pop() {
+            let node = &self.nodes[n];
+            if node.outside(planes) {
+                continue;
+            }
+
+            if node.is_leaf() {
+                visible.extend_from_slice(&self.indices[node.start..node.end]);
+            } else {
+                stack.extend(node.children());
+            }
+        }
+
+        visible
+    }
+
+    /// One representative splat per node at `depth` (or per shallower leaf),
+    /// for a coarse level of detail. Deeper levels give more splats.
+    pub fn lod(&self, depth: u32) -> Vec<usize> {
+        let mut picked = Vec::new();
+        let mut stack: Vec<usize> = if self.nodes.is_empty() { Vec::new() } else { vec![0] };
+
+        while let Some(n) = stack.pop() {
+            let node = &self.nodes[n];
+            if node.depth >= depth || node.is_leaf() {
+                picked.push(self.indices[node.start]);
+            } else {
+                stack.extend(node.children());
+            }
+        }
+
+        picked
+    }
+}
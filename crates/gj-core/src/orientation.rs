@@ -0,0 +1,96 @@
+//! Upright estimation and ground snapping for generated objects, which come
+//! out of the models at arbitrary orientations.
+
+use crate::alignment::{rotate, symmetric_eigen};
+use crate::gaussian_cloud::GaussianCloud;
+
+/// Shortest rotation (w, x, y, z) taking unit vector `from` onto +Y
+fn rotation_to_up(from: [f32; 3]) -> [f32; 4] {
+    // Half-way quaternion: [1 + from·up, from × up], normalized
+    let [x, y, z] = from;
+    let w = 1.0 + y;
+    if w < 1e-6 {
+        // Pointing straight down: turn half way around x
+        return [0.0, 1.0, 0.0, 0.0];
+    }
+
+    let q = [w, -z, 0.0, x];
+    let length = q.iter().map(|c| c * c).sum::<f32>().sqrt();
+    q.map(|c| c / length)
+}
+
+impl GaussianCloud {
+    /// Principal axis of the splat centers, weighted by opacity, as a unit
+    /// vector pointing away from the denser end of the cloud, since objects
+    /// are usually heavier at their base. None for empty clouds.
+    pub fn principal_axis(&self) -> Option<[f32; 3]> {
+        let weights: Vec<f64> = self.opacity.iter().map(|&o| o.clamp(0.0, 1.0) as f64).collect();
+        let total: f64 = weights.iter().sum();
+        if self.count == 0 || total <= 0.0 {
+            return None;
+        }
+
+        let mut mean = [0.0f64; 3];
+        for (p, w) in self.positions.iter().zip(&weights) {
+            for (m, &c) in mean.iter_mut().zip(p) {
+                *m += c as f64 * w;
+            }
+        }
+        let mean = mean.map(|m| m / total);
+
+        let mut covariance = [[0.0f64; 3]; 3];
+        for (p, w) in self.positions.iter().zip(&weights) {
+            let d: [f64; 3] = std::array::from_fn(|i| p[i] as f64 - mean[i]);
+            for (row, da) in covariance.iter_mut().zip(d) {
+                for (cell, db) in row.iter_mut().zip(d) {
+                    *cell += w * da * db;
+                }
+            }
+        }
+
+        let (values, vectors) = symmetric_eigen(covariance);
+        let largest = (0..3).max_by(|&a, &b| values[a].total_cmp(&values[b]))?;
+        let axis: [f32; 3] = std::array::from_fn(|i| vectors[i][largest] as f32);
+
+        // Compare the mass in the first and last third along the axis
+        let along: Vec<f64> = self.positions.iter()
+            .map(|p| (0..3).map(|i| (p[i] as f64 - mean[i]) * axis[i] as f64).sum())
+            .collect();
+        let (lo, hi) = along.iter().fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), &t| (lo.min(t), hi.max(t)));
+        let third = (hi - lo) / 3.0;
+        let (mut tail, mut head) = (0.0, 0.0);
+        for (&t, w) in along.iter().zip(&weights) {
+            if t < lo + third {
+                tail += w;
+            } else if t > hi - third {
+                head += w;
+            }
+        }
+
+        Some(if head > tail { axis.map(|a| -a) } else { axis })
+    }
+
+    /// Rotation (w, x, y, z) that turns the principal axis up; identity for
+    /// empty clouds
+    pub fn upright_rotation(&self) -> [f32; 4] {
+        self.principal_axis().map_or([1.0, 0.0, 0.0, 0.0], rotation_to_up)
+    }
+
+    /// Turn the cloud upright about its center, see
+    /// [`GaussianCloud::upright_rotation`], then move it vertically so its
+    /// lowest splat center sits at y = 0
+    pub fn snap_to_ground(&mut self) {
+        if self.count == 0 {
+            return;
+        }
+
+        let rotation = self.upright_rotation();
+        let center = self.bounds().center();
+        let rotated_center = rotate(rotation, center);
+        let translation = std::array::from_fn(|i| center[i] - rotated_center[i]);
+        self.transform(rotation, translation);
+
+        let floor = self.bounds().min[1];
+        self.transform([1.0, 0.0, 0.0, 0.0], [0.0, -floor, 0.0]);
+    }
+}
@@ -0,0 +1,35 @@
+//! Brush dabs touching up splats in place, for fading out floaters or
+//! evening out blotchy color before an export.
+
+use crate::gaussian_cloud::GaussianCloud;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PaintMode {
+    /// Multiply opacity by `factor` under the brush center
+    Opacity { factor: f32 },
+    /// Blend color toward `color` (RGB in [0, 1]) by `strength` under the
+    /// brush center
+    Tint { color: [f32; 3], strength: f32 },
+}
+
+impl GaussianCloud {
+    /// Apply one dab to the splats in `weights`, pairs of splat index and
+    /// brush falloff in [0, 1]; the effect fades out with the falloff.
+    /// View-dependent color (SH) is left alone.
+    pub fn paint(&mut self, weights: &[(usize, f32)], mode: PaintMode) {
+        for &(i, weight) in weights {
+            let weight = weight.clamp(0.0, 1.0);
+            match mode {
+                PaintMode::Opacity { factor } => {
+                    self.opacity[i] *= 1.0 - weight * (1.0 - factor.clamp(0.0, 1.0));
+                }
+                PaintMode::Tint { color, strength } => {
+                    let t = weight * strength.clamp(0.0, 1.0);
+                    for (c, target) in self.colors[i].iter_mut().zip(color) {
+                        *c += (target - *c) * t;
+                    }
+                }
+            }
+        }
+    }
+}
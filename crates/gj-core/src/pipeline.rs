@@ -1,7 +1,8 @@
+use std::ops::RangeInclusive;
 use std::time::Duration;
 
 use crate::gaussian_cloud::GaussianCloud;
-use crate::error::Result;
+use crate::error::{Error, Result};
 
 /// Trait for 3D generation pipelines
 pub trait Pipeline3D: Send + Sync {
@@ -50,4 +51,318 @@ impl PipelineConfig {
             grid_resolution: 128,
         }
     }
-}
\ No newline at end of file
+}
+
+/// Photos a capture set takes, see [`TextToMultiviewConfig::Capture`]
+pub const CAPTURE_PHOTOS: RangeInclusive<usize> = 4..=16;
+
+/// Cameras of the four views LGM reconstructs from, as azimuth and
+/// elevation in degrees: front, right, back, left
+pub const LGM_VIEWS: [(f32, f32); 4] = [(0.0, 0.0), (90.0, 0.0), (180.0, 0.0), (270.0, 0.0)];
+
+/// How a multiview stage draws its views
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum TextToMultiviewConfig {
+    /// Stable Diffusion once per view, each held to its camera by a
+    /// ControlNet depth hint of a proxy shape. Every view starts from the
+    /// same seed.
+    StableDiffusion {
+        inference_steps: u32,
+        guidance_scale: f32,
+        /// Weight of the depth hint; lower lets the object take its own shape
+        controlnet_scale: f32,
+    },
+    /// ImageDream, drawing the four views in one diffusion conditioned on
+    /// the submission's input image as well as its prompt. Keeps closer to
+    /// the object than text alone, but needs an image.
+    ImageDream {
+        inference_steps: u32,
+        guidance_scale: f32,
+    },
+    /// Nothing drawn: photos of a real object stand in for the views, the
+    /// one taken nearest each view's camera, see [`capture_views`]
+    Capture,
+}
+
+impl TextToMultiviewConfig {
+    pub fn stable_diffusion_default() -> Self {
+        Self::StableDiffusion {
+            inference_steps: 30,
+            guidance_scale: 7.5,
+            controlnet_scale: 1.0,
+        }
+    }
+
+    pub fn imagedream_default() -> Self {
+        Self::ImageDream {
+            inference_steps: 30,
+            guidance_scale: 5.0,
+        }
+    }
+
+    /// Default settings of the generator with id `model`
+    pub fn from_model(model: &str) -> Option<Self> {
+        match model {
+            "sd_controlnet" => Some(Self::stable_diffusion_default()),
+            "imagedream" => Some(Self::imagedream_default()),
+            "capture" => Some(Self::Capture),
+            _ => None,
+        }
+    }
+
+    /// Id of the worker's generator, the `multiview` parameter of LGM jobs
+    pub fn model(&self) -> &'static str {
+        match self {
+            Self::StableDiffusion { .. } => "sd_controlnet",
+            Self::ImageDream { .. } => "imagedream",
+            Self::Capture => "capture",
+        }
+    }
+
+    /// Whether the views are drawn from an input image, which the
+    /// submission must then supply
+    pub fn needs_image(&self) -> bool {
+        matches!(self, Self::ImageDream { .. })
+    }
+
+    /// Whether the views are photos, which the submission must supply as
+    /// a capture set of [`CAPTURE_PHOTOS`]
+    pub fn needs_capture(&self) -> bool {
+        matches!(self, Self::Capture)
+    }
+
+    /// The settings as the worker's parameters, by name
+    pub fn params(&self) -> Vec<(&'static str, f64)> {
+        match *self {
+            Self::StableDiffusion { inference_steps, guidance_scale, controlnet_scale } => vec![
+                ("num_inference_steps", inference_steps as f64),
+                ("guidance_scale", guidance_scale as f64),
+                ("controlnet_scale", controlnet_scale as f64),
+            ],
+            Self::ImageDream { inference_steps, guidance_scale } => vec![
+                ("num_inference_steps", inference_steps as f64),
+                ("guidance_scale", guidance_scale as f64),
+            ],
+            Self::Capture => Vec::new(),
+        }
+    }
+}
+
+/// Which photo of a capture set stands in for each of [`LGM_VIEWS`], by
+/// index into `poses` (azimuth and elevation in degrees). Closest pairs are
+/// matched first and no photo is used twice, so a set shot mostly from the
+/// front still fills the back from the photos nearest it. None for sets of
+/// fewer than four.
+pub fn capture_views(poses: &[(f32, f32)]) -> Option<[usize; 4]> {
+    if poses.len() < LGM_VIEWS.len() {
+        return None;
+    }
+    let direction = |(azimuth, elevation): (f32, f32)| {
+        let (az, el) = (azimuth.to_radians(), elevation.to_radians());
+        [el.cos() * az.sin(), el.sin(), el.cos() * az.cos()]
+    };
+    let mut pairs: Vec<(f32, usize, usize)> = Vec::new();
+    for (view, &camera) in LGM_VIEWS.iter().enumerate() {
+        let c = direction(camera);
+        for (photo, &pose) in poses.iter().enumerate() {
+            let p = direction(pose);
+            pairs.push((c[0] * p[0] + c[1] * p[1] + c[2] * p[2], view, photo));
+        }
+    }
+    // Most aligned first; ties go to the earlier photo
+    pairs.sort_by(|a, b| b.0.total_cmp(&a.0).then(a.2.cmp(&b.2)));
+
+    let mut chosen = [None; 4];
+    let mut used = vec![false; poses.len()];
+    for (_, view, photo) in pairs {
+        if chosen[view].is_none() && !used[photo] {
+            chosen[view] = Some(photo);
+            used[photo] = true;
+        }
+    }
+    Some(chosen.map(|c| c.unwrap_or_default()))
+}
+
+/// What flows from one pipeline stage to the next
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Artifact {
+    /// The submission's prompt
+    Text,
+    Image,
+    /// Consistent views of one object around it
+    Multiview,
+    Gaussians,
+    /// A file written for the user, the end of a branch
+    File,
+}
+
+/// A step of a multi-stage generation
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StageKind {
+    TextToImage,
+    TextToMultiview,
+    ImageToMultiview,
+    MultiviewToGaussians,
+    TextToGaussians,
+    /// Score distillation of the splats towards the prompt
+    Refine,
+    /// Textured mesh baked from the splats
+    Export,
+}
+
+impl StageKind {
+    pub fn input(self) -> Artifact {
+        match self {
+            Self::TextToImage | Self::TextToMultiview | Self::TextToGaussians => Artifact::Text,
+            Self::ImageToMultiview => Artifact::Image,
+            Self::MultiviewToGaussians => Artifact::Multiview,
+            Self::Refine | Self::Export => Artifact::Gaussians,
+        }
+    }
+
+    pub fn output(self) -> Artifact {
+        match self {
+            Self::TextToImage => Artifact::Image,
+            Self::TextToMultiview | Self::ImageToMultiview => Artifact::Multiview,
+            Self::MultiviewToGaussians | Self::TextToGaussians | Self::Refine => Artifact::Gaussians,
+            Self::Export => Artifact::File,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::TextToImage => "Image",
+            Self::TextToMultiview | Self::ImageToMultiview => "Multiview",
+            Self::MultiviewToGaussians | Self::TextToGaussians => "Splats",
+            Self::Refine => "Refine",
+            Self::Export => "Mesh",
+        }
+    }
+
+    /// Whether the stage runs a generation model, see [`Stage::model`]
+    pub fn needs_model(self) -> bool {
+        !matches!(self, Self::Refine | Self::Export)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Stage {
+    /// Unique within the pipeline
+    pub id: String,
+    pub kind: StageKind,
+    /// Stage whose output this one takes, the prompt when None
+    pub input: Option<String>,
+    /// Model id running the stage; the model picked for the submission
+    /// when None
+    pub model: Option<String>,
+}
+
+/// Stages of a generation and how their outputs feed each other. Each
+/// stage takes one input, but an output may feed several stages.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PipelineGraph {
+    pub id: String,
+    pub name: String,
+    pub stages: Vec<Stage>,
+}
+
+impl PipelineGraph {
+    pub fn new(id: &str, name: &str) -> Self {
+        Self { id: id.to_string(), name: name.to_string(), stages: Vec::new() }
+    }
+
+    /// Add a stage taking the output of `input`, or the prompt when None
+    pub fn stage(mut self, id: &str, kind: StageKind, input: Option<&str>, model: Option<&str>) -> Self {
+        self.stages.push(Stage {
+            id: id.to_string(),
+            kind,
+            input: input.map(str::to_string),
+            model: model.map(str::to_string),
+        });
+        self
+    }
+
+    pub fn get(&self, id: &str) -> Option<&Stage> {
+        self.stages.iter().find(|s| s.id == id)
+    }
+
+    /// The stages, each after the one it takes the output of. Fails when
+    /// an input is missing or of the wrong type, or the stages form a loop.
+    pub fn order(&self) -> Result<Vec<&Stage>> {
+        for (i, stage) in self.stages.iter().enumerate() {
+            if self.stages[..i].iter().any(|s| s.id == stage.id) {
+                return Err(Error::InvalidConfig(format!("Stage '{}' appears twice", stage.id)));
+            }
+            let given = match &stage.input {
+                None => Artifact::Text,
+                Some(input) => self.get(input)
+                    .ok_or_else(|| Error::InvalidConfig(format!("Stage '{}' takes unknown stage '{}'", stage.id, input)))?
+                    .kind
+                    .output(),
+            };
+            if given != stage.kind.input() {
+                return Err(Error::InvalidConfig(format!(
+                    "Stage '{}' takes {:?} but is given {:?}", stage.id, stage.kind.input(), given
+                )));
+            }
+        }
+
+        let mut ordered: Vec<&Stage> = Vec::with_capacity(self.stages.len());
+        while ordered.len() < self.stages.len() {
+            let ready: Vec<&Stage> = self.stages.iter()
+                .filter(|s| !ordered.iter().any(|o| o.id == s.id))
+                .filter(|s| s.input.as_ref().is_none_or(|input| ordered.iter().any(|o| &o.id == input)))
+                .collect();
+            if ready.is_empty() {
+                return Err(Error::InvalidConfig(format!("Stages of pipeline '{}' form a loop", self.id)));
+            }
+            ordered.extend(ready);
+        }
+        Ok(ordered)
+    }
+
+    /// Models the stages name themselves, which the workers need besides
+    /// the one picked for the submission
+    pub fn fixed_models(&self) -> impl Iterator<Item = &str> {
+        self.stages.iter().filter_map(|s| s.model.as_deref())
+    }
+
+    /// Model the pipeline makes its splats with, when it names one
+    pub fn splat_model(&self) -> Option<&str> {
+        self.stages.iter()
+            .find(|s| s.kind.output() == Artifact::Gaussians && s.kind.needs_model())?
+            .model
+            .as_deref()
+    }
+
+    /// Stage kinds from the prompt to the first splats, e.g. for listing
+    /// the pipeline as "Text → Image → Multiview → Splats"
+    pub fn summary(&self) -> String {
+        let mut parts = vec!["Text"];
+        parts.extend(self.stages.iter().map(|s| s.kind.label()));
+        parts.join(" → ")
+    }
+
+    /// Pipelines the app offers
+    pub fn builtin() -> Vec<Self> {
+        use StageKind::*;
+        vec![
+            Self::new("splats", "Splats")
+                .stage("splats", TextToGaussians, None, None),
+            Self::new("refined", "Refined splats")
+                .stage("splats", TextToGaussians, None, None)
+                .stage("refine", Refine, Some("splats"), None),
+            Self::new("mesh", "Splats and mesh")
+                .stage("splats", TextToGaussians, None, None)
+                .stage("mesh", Export, Some("splats"), None),
+            Self::new("multiview", "Multiview reconstruction")
+                .stage("views", TextToMultiview, None, Some(TextToMultiviewConfig::stable_diffusion_default().model()))
+                .stage("splats", MultiviewToGaussians, Some("views"), Some("lgm")),
+        ]
+    }
+
+    /// A built-in pipeline by id
+    pub fn find(id: &str) -> Option<Self> {
+        Self::builtin().into_iter().find(|p| p.id == id)
+    }
+}
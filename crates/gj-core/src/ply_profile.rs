@@ -0,0 +1,87 @@
+//! PLY layouts for other tools. SuperSplat and PlayCanvas expect the layout
+//! of the reference 3D Gaussian Splatting code: spherical harmonic DC colors,
+//! logit opacities, log scales, and the y-down, z-forward axes of the COLMAP
+//! scenes it trains on.
+
+use crate::alignment::{multiply, rotate};
+use crate::error::Result;
+use crate::gaussian_cloud::{GaussianCloud, PLY_VERTEX_SIZE};
+
+/// Zeroth-order spherical harmonic basis constant
+const SH_C0: f32 = 0.282_094_8;
+
+/// Half turn about x, from our y-up axes to y-down, z-forward
+const FLIP_YZ: [f32; 4] = [0.0, 1.0, 0.0, 0.0];
+
+/// Property layout and axis convention of an exported PLY
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum PlyProfile {
+    /// The layout [`GaussianCloud::from_ply`] reads back
+    #[default]
+    Genjutsu,
+    /// Reference 3DGS layout, for SuperSplat and PlayCanvas
+    SuperSplat,
+}
+
+impl PlyProfile {
+    /// Bytes per vertex record
+    pub fn vertex_size(&self) -> usize {
+        match self {
+            PlyProfile::Genjutsu => PLY_VERTEX_SIZE,
+            // Position, normal, 3 DC terms, opacity, 3 scales, 4 rotation terms
+            PlyProfile::SuperSplat => 17 * size_of::<f32>(),
+        }
+    }
+}
+
+fn logit(p: f32) -> f32 {
+    let p = p.clamp(1e-6, 1.0 - 1e-6);
+    (p / (1.0 - p)).ln()
+}
+
+impl GaussianCloud {
+    /// Export to PLY in the given profile
+    pub fn to_ply_as(&self, profile: PlyProfile) -> Result<Vec<u8>> {
+        match profile {
+            PlyProfile::Genjutsu => self.to_ply(),
+            PlyProfile::SuperSplat => self.to_supersplat_ply(),
+        }
+    }
+
+    /// Export to PLY in the reference 3DGS layout, turned a half turn about
+    /// x so the scene loads upright in SuperSplat
+    pub fn to_supersplat_ply(&self) -> Result<Vec<u8>> {
+        use std::io::Write;
+
+        let mut buffer = Vec::with_capacity(self.count * PlyProfile::SuperSplat.vertex_size() + 512);
+
+        writeln!(buffer, "ply")?;
+        writeln!(buffer, "format binary_little_endian 1.0")?;
+        writeln!(buffer, "element vertex {}", self.count)?;
+        for property in [
+            "x", "y", "z", "nx", "ny", "nz", "f_dc_0", "f_dc_1", "f_dc_2", "opacity",
+            "scale_0", "scale_1", "scale_2", "rot_0", "rot_1", "rot_2", "rot_3",
+        ] {
+            writeln!(buffer, "property float {}", property)?;
+        }
+        writeln!(buffer, "end_header")?;
+
+        for i in 0..self.count {
+            let position = rotate(FLIP_YZ, self.positions[i]);
+            let rotation = multiply(FLIP_YZ, self.rotations[i]);
+            let length = rotation.iter().map(|c| c * c).sum::<f32>().sqrt().max(f32::EPSILON);
+
+            let values = position.into_iter()
+                .chain([0.0; 3])
+                .chain(self.colors[i].map(|c| (c - 0.5) / SH_C0))
+                .chain([logit(self.opacity[i])])
+                .chain(self.scales[i].map(|s| s.max(1e-8).ln()))
+                .chain(rotation.map(|c| c / length));
+            for value in values {
+                buffer.write_all(&value.to_le_bytes())?;
+            }
+        }
+
+        Ok(buffer)
+    }
+}
@@ -0,0 +1,36 @@
+//! The compact `.splat` layout read by most web splat viewers: 32 bytes per
+//! splat with no header.
+
+use crate::gaussian_cloud::GaussianCloud;
+
+/// Bytes per record written by [`GaussianCloud::to_splat`]
+pub const SPLAT_RECORD_SIZE: usize = 32;
+
+fn unit_to_byte(v: f32) -> u8 {
+    (v.clamp(0.0, 1.0) * 255.0).round() as u8
+}
+
+impl GaussianCloud {
+    /// Encode as `.splat` records: position and scale as little-endian f32s,
+    /// RGBA color with the opacity as alpha, then the normalized rotation
+    /// quaternion (w, x, y, z) mapped from [-1, 1] to bytes
+    pub fn to_splat(&self) -> Vec<u8> {
+        let mut buffer = Vec::with_capacity(self.count * SPLAT_RECORD_SIZE);
+
+        for i in 0..self.count {
+            for c in self.positions[i].into_iter().chain(self.scales[i]) {
+                buffer.extend_from_slice(&c.to_le_bytes());
+            }
+
+            buffer.extend(self.colors[i].map(unit_to_byte));
+            buffer.push(unit_to_byte(self.opacity[i]));
+
+            let rotation = self.rotations[i];
+            let length = rotation.iter().map(|c| c * c).sum::<f32>().sqrt();
+            let rotation = if length > f32::EPSILON { rotation.map(|c| c / length) } else { [1.0, 0.0, 0.0, 0.0] };
+            buffer.extend(rotation.map(|c| (c * 128.0 + 128.0).round().clamp(0.0, 255.0) as u8));
+        }
+
+        buffer
+    }
+}
@@ -1,57 +1,689 @@
-#[cfg(test)]
-mod tests {
-    use crate::gaussian_cloud::GaussianCloud;
-    use crate::pipeline::PipelineConfig;
-    use super::*;
+use crate::cloud_cache;
+use crate::colmap::{self, ColmapScene};
+use crate::validation;
+use crate::decimation;
+use crate::normals;
+use crate::alignment::Alignment;
+use crate::array::{ArrayLayout, Placement};
+use crate::comparison;
+use crate::heatmap::HeatmapQuantity;
+use crate::mesh::MeshSettings;
+use crate::morph::Morph;
+use crate::paint::PaintMode;
+use crate::color_grading::ColorGrade;
+use crate::gaussian_cloud::GaussianCloud;
+use crate::pipeline::{capture_views, PipelineConfig, PipelineGraph, StageKind, TextToMultiviewConfig};
+use crate::ply_profile::PlyProfile;
+use crate::splat_format::SPLAT_RECORD_SIZE;
+use crate::volume::Volume;
 
-    #[test]
-    fn test_gaussian_cloud_creation() {
-        let mut cloud = GaussianCloud::new();
+#[test]
+fn test_gaussian_cloud_creation() {
+    let mut cloud = GaussianCloud::new();
 
-        cloud.add_gaussian(
-            [0.0, 0.0, 0.0],
-            [1.0, 1.0, 1.0],
-            [1.0, 0.0, 0.0, 0.0],
-            [1.0, 0.0, 0.0],
-            1.0,
-        );
+    cloud.add_gaussian(
+        [0.0, 0.0, 0.0],
+        [1.0, 1.0, 1.0],
+        [1.0, 0.0, 0.0, 0.0],
+        [1.0, 0.0, 0.0],
+        1.0,
+    );
 
-        assert_eq!(cloud.count, 1);
-        assert!(cloud.validate().is_ok());
+    assert_eq!(cloud.count, 1);
+    assert!(cloud.validate().is_ok());
+}
+
+#[test]
+fn test_bounding_box() {
+    let mut cloud = GaussianCloud::new();
+
+    cloud.add_gaussian([1.0, 2.0, 3.0], [0.1; 3], [1.0, 0.0, 0.0, 0.0], [1.0; 3], 1.0);
+    cloud.add_gaussian([-1.0, -2.0, -3.0], [0.1; 3], [1.0, 0.0, 0.0, 0.0], [1.0; 3], 1.0);
+
+    let bounds = cloud.bounds();
+    assert_eq!(bounds.min, [-1.0, -2.0, -3.0]);
+    assert_eq!(bounds.max, [1.0, 2.0, 3.0]);
+    assert_eq!(bounds.center(), [0.0, 0.0, 0.0]);
+}
+
+#[test]
+fn test_ply_export() {
+    let mut cloud = GaussianCloud::new();
+    cloud.add_gaussian([0.0; 3], [1.0; 3], [1.0, 0.0, 0.0, 0.0], [1.0; 3], 1.0);
+
+    let ply = cloud.to_ply().unwrap();
+    assert!(!ply.is_empty());
+    assert!(ply.starts_with(b"ply\n"));
+}
+
+#[test]
+fn test_ply_roundtrip() {
+    let mut cloud = GaussianCloud::new();
+    for i in 0..10_000 {
+        let f = i as f32;
+        cloud.add_gaussian([f, -f, f * 0.5], [0.1, 0.2, 0.3], [1.0, 0.0, f, 0.0], [1.0, 0.0, 0.2], 0.5);
+    }
+
+    let path = std::env::temp_dir().join(format!("gj_ply_roundtrip_{}.ply", std::process::id()));
+    std::fs::write(&path, cloud.to_ply().unwrap()).unwrap();
+    let loaded = GaussianCloud::from_ply(&path).unwrap();
+    let _ = std::fs::remove_file(&path);
+
+    assert_eq!(loaded.count, cloud.count);
+    assert!(loaded.validate().is_ok());
+    assert_eq!(loaded.positions, cloud.positions);
+    assert_eq!(loaded.scales, cloud.scales);
+    assert_eq!(loaded.rotations, cloud.rotations);
+    assert_eq!(loaded.opacity, cloud.opacity);
+    assert_eq!(loaded.colors[9_999], [1.0, 0.0, 51.0 / 255.0]);
+}
+
+#[test]
+fn test_cloud_cache() {
+    let mut cloud = GaussianCloud::new();
+    cloud.add_gaussian([1.5, -2.0, 3.25], [0.5, 0.25, 0.125], [1.0, 0.0, 0.0, 0.0], [1.0, 0.0, 0.2], 0.75);
+
+    let path = std::env::temp_dir().join(format!("gj_cache_test_{}.ply", std::process::id()));
+    std::fs::write(&path, cloud.to_ply().unwrap()).unwrap();
+    let _ = std::fs::remove_file(cloud_cache::cache_path(&path));
+
+    assert!(cloud_cache::load(&path).is_none());
+    let parsed = GaussianCloud::from_ply_cached(&path).unwrap();
+    let cached = cloud_cache::load(&path).expect("cache written after first parse");
+    assert_eq!(cached.positions, parsed.positions);
+    assert_eq!(cached.scales, parsed.scales);
+    assert_eq!(cached.opacity, parsed.opacity);
+    assert_eq!(cached.colors, parsed.colors);
+
+    // Changing the PLY invalidates the cache
+    cloud.add_gaussian([0.0; 3], [1.0; 3], [1.0, 0.0, 0.0, 0.0], [1.0; 3], 1.0);
+    std::fs::write(&path, cloud.to_ply().unwrap()).unwrap();
+    assert!(cloud_cache::load(&path).is_none());
+    assert_eq!(GaussianCloud::from_ply_cached(&path).unwrap().count, 2);
+
+    let _ = std::fs::remove_file(cloud_cache::cache_path(&path));
+    let _ = std::fs::remove_file(&path);
+}
+
+#[test]
+fn test_pipeline_config() {
+    let config = PipelineConfig::lgm_default();
+
+    match config {
+        PipelineConfig::LGM { inference_steps, .. } => {
+            assert_eq!(inference_steps, 50);
+        }
+        _ => panic!("Wrong config type"),
+    }
+}
+
+#[test]
+fn test_pipeline_graph_order() {
+    let graph = PipelineGraph::new("branches", "Branches")
+        .stage("mesh", StageKind::Export, Some("refine"), None)
+        .stage("refine", StageKind::Refine, Some("splats"), None)
+        .stage("splats", StageKind::TextToGaussians, None, None);
+    let order: Vec<&str> = graph.order().unwrap().iter().map(|s| s.id.as_str()).collect();
+    assert_eq!(order, ["splats", "refine", "mesh"]);
+
+    for graph in PipelineGraph::builtin() {
+        assert!(graph.order().is_ok(), "{}", graph.id);
+    }
+    assert_eq!(PipelineGraph::find("multiview").unwrap().summary(), "Text → Multiview → Splats");
+
+    // A mesh can't be refined, nor splats made without their views
+    let mistyped = PipelineGraph::new("bad", "Bad")
+        .stage("splats", StageKind::TextToGaussians, None, None)
+        .stage("mesh", StageKind::Export, Some("splats"), None)
+        .stage("refine", StageKind::Refine, Some("mesh"), None);
+    assert!(mistyped.order().is_err());
+    let unknown = PipelineGraph::new("bad", "Bad").stage("splats", StageKind::MultiviewToGaussians, Some("views"), None);
+    assert!(unknown.order().is_err());
+    let looped = PipelineGraph::new("bad", "Bad")
+        .stage("a", StageKind::Refine, Some("b"), None)
+        .stage("b", StageKind::Refine, Some("a"), None);
+    assert!(looped.order().is_err());
+}
+
+#[test]
+fn test_text_to_multiview_config() {
+    let config = TextToMultiviewConfig::from_model("sd_controlnet").unwrap();
+    assert_eq!(config, TextToMultiviewConfig::stable_diffusion_default());
+    assert_eq!(config.model(), "sd_controlnet");
+    assert!(config.params().iter().any(|&(name, value)| name == "num_inference_steps" && value == 30.0));
+    assert!(!config.needs_image());
+
+    let imagedream = TextToMultiviewConfig::from_model("imagedream").unwrap();
+    assert!(imagedream.needs_image());
+    assert_eq!(imagedream.model(), "imagedream");
+    assert!(imagedream.params().iter().all(|&(name, _)| name != "controlnet_scale"));
+    assert_eq!(TextToMultiviewConfig::from_model("zero123"), None);
+
+    let capture = TextToMultiviewConfig::from_model("capture").unwrap();
+    assert!(capture.needs_capture() && !capture.needs_image());
+    assert!(capture.params().is_empty());
+}
+
+#[test]
+fn test_capture_views() {
+    // Eight photos a walk around the object, starting at its left
+    let ring: Vec<(f32, f32)> = (0..8).map(|i| (270.0 + 45.0 * i as f32, 10.0)).collect();
+    assert_eq!(capture_views(&ring), Some([2, 4, 6, 0]));
+
+    // Mostly from the front: the back still gets a photo of its own
+    let front = [(0.0, 0.0), (10.0, 0.0), (-10.0, 0.0), (80.0, 20.0), (5.0, 45.0)];
+    let views = capture_views(&front).unwrap();
+    assert_eq!(views[0], 0);
+    assert_eq!(views[1], 3);
+    let mut distinct = views.to_vec();
+    distinct.sort();
+    distinct.dedup();
+    assert_eq!(distinct.len(), 4);
+
+    assert_eq!(capture_views(&ring[..3]), None);
+
+    let multiview = PipelineGraph::find("multiview").unwrap();
+    assert_eq!(multiview.splat_model(), Some("lgm"));
+    assert_eq!(PipelineGraph::find("splats").unwrap().splat_model(), None);
+}
+
+#[test]
+fn test_colmap_poses() {
+    let cameras = "# Camera list with one line of data per camera:\n1 PINHOLE 640 480 500 500 320 240\n";
+    // Four cameras 4 from an object at (1, 2, 3) in a z-up world, its front
+    // towards -y; the side ones 20° up. The third has no 2D points.
+    let images = "\
+# Image list with two lines of data per image:
+1 0.707107 0.707107 0 0 -1 3 2 1 photo_0.jpg
+10 20 -1
+2 0.40558 0.579228 0.579228 -0.40558 -2 2.477058 5.965753 1 photo_1.jpg
+30 40 -1
+3 0 0 0.707107 -0.707107 1 3 6 1 photo 2.jpg
+
+4 0.40558 0.579228 -0.579228 0.40558 2 3.161098 4.086368 1 photo_3.jpg
+50 60 -1
+";
+    let scene = ColmapScene::parse(cameras, images).unwrap();
+    assert_eq!(scene.images.len(), 4);
+    assert_eq!(scene.images[2].name, "photo 2.jpg");
+    let target = scene.target().unwrap();
+    assert!(target.iter().zip([1.0, 2.0, 3.0]).all(|(a, b)| (a - b).abs() < 1e-4), "{:?}", target);
+
+    let photos = scene.posed_photos().unwrap();
+    let expected = [(0.0, 0.0), (90.0, 20.0), (180.0, 0.0), (270.0, 20.0)];
+    for (photo, (azimuth, elevation)) in photos.iter().zip(expected) {
+        assert!((photo.azimuth - azimuth).abs() < 0.01, "{} at {}", photo.name, photo.azimuth);
+        assert!((photo.elevation - elevation).abs() < 0.01, "{} at {}", photo.name, photo.elevation);
+        assert!((photo.fov_y - 51.28).abs() < 0.01);
     }
+    // The first camera sits in front at unit distance, looking down -z
+    let m = photos[0].to_object;
+    assert!((m[2][3] - 1.0).abs() < 1e-4 && m[0][3].abs() < 1e-4 && m[1][3].abs() < 1e-4);
+    assert!((m[2][2] - 1.0).abs() < 1e-4 && (m[1][1] - 1.0).abs() < 1e-4);
 
-    #[test]
-    fn test_bounding_box() {
-        let mut cloud = GaussianCloud::new();
+    let spread = colmap::spread(photos.clone(), 2);
+    assert_eq!(spread.iter().map(|p| p.name.as_str()).collect::<Vec<_>>(), ["photo_0.jpg", "photo 2.jpg"]);
+    assert_eq!(colmap::spread(photos, 8).len(), 4);
 
-        cloud.add_gaussian([1.0, 2.0, 3.0], [0.1; 3], [1.0, 0.0, 0.0, 0.0], [1.0; 3], 1.0);
-        cloud.add_gaussian([-1.0, -2.0, -3.0], [0.1; 3], [1.0, 0.0, 0.0, 0.0], [1.0; 3], 1.0);
+    assert!(ColmapScene::parse(cameras, "1 0.7 0.7 0 0 -1 3 2 9 photo.jpg\n\n").is_err());
+    assert!(ColmapScene::parse("1 PINHOLE 640\n", "").is_err());
+}
 
-        let bounds = cloud.bounds();
-        assert_eq!(bounds.min, [-1.0, -2.0, -3.0]);
-        assert_eq!(bounds.max, [1.0, 2.0, 3.0]);
-        assert_eq!(bounds.center(), [0.0, 0.0, 0.0]);
+#[test]
+fn test_raycast_hits_nearest() {
+    let mut cloud = GaussianCloud::new();
+    cloud.add_gaussian([0.0, 0.0, -5.0], [0.5; 3], [1.0, 0.0, 0.0, 0.0], [1.0; 3], 1.0);
+    cloud.add_gaussian([0.0, 0.0, -2.0], [0.5; 3], [1.0, 0.0, 0.0, 0.0], [1.0; 3], 1.0);
+    cloud.add_gaussian([3.0, 0.0, -1.0], [0.5; 3], [1.0, 0.0, 0.0, 0.0], [1.0; 3], 1.0);
+
+    let (index, t) = cloud.raycast([0.0; 3], [0.0, 0.0, -1.0]).unwrap();
+    assert_eq!(index, 1);
+    assert!((t - 1.5).abs() < 1e-5);
+
+    assert!(cloud.raycast([0.0; 3], [0.0, 1.0, 0.0]).is_none());
+}
+#[test]
+fn test_octree_queries() {
+    let mut cloud = GaussianCloud::new();
+    for x in 0..20 {
+        for y in 0..20 {
+            for z in 0..20 {
+                cloud.add_gaussian([x as f32, y as f32, z as f32], [0.1; 3], [1.0, 0.0, 0.0, 0.0], [1.0; 3], 1.0);
+            }
+        }
     }
 
-    #[test]
-    fn test_ply_export() {
-        let mut cloud = GaussianCloud::new();
-        cloud.add_gaussian([0.0; 3], [1.0; 3], [1.0, 0.0, 0.0, 0.0], [1.0; 3], 1.0);
+    let octree = cloud.octree();
+    assert!(octree.depth() > 0);
+
+    let (index, dist) = octree.nearest(&cloud, [4.2, 7.9, 11.1]).unwrap();
+    assert_eq!(cloud.positions[index], [4.0, 8.0, 11.0]);
+    assert!((dist - (0.04f32 + 0.01 + 0.01).sqrt()).abs() < 1e-5);
+
+    // Center plus its six face neighbors
+    assert_eq!(octree.within_radius(&cloud, [10.0, 10.0, 10.0], 1.0).len(), 7);
+
+    // Half-space x <= 4.5 keeps the first five slabs
+    let visible = octree.cull(&[[-1.0, 0.0, 0.0, 4.5]]);
+    assert!(visible.iter().all(|&i| cloud.positions[i][0] <= 5.0));
+    assert!(visible.len() >= 5 * 400);
+
+    assert_eq!(octree.lod(0).len(), 1);
+    assert!(octree.lod(2).len() > octree.lod(1).len());
+}
+
+#[test]
+fn test_cloud_stats() {
+    let mut cloud = GaussianCloud::new();
+    cloud.add_gaussian([0.0; 3], [0.1, 0.2, 0.3], [1.0, 0.0, 0.0, 0.0], [1.0; 3], 0.05);
+    cloud.add_gaussian([2.0; 3], [0.5, 0.1, 0.1], [1.0, 0.0, 0.0, 0.0], [1.0; 3], 0.95);
+    cloud.add_gaussian([1.0; 3], [1.0, 1.0, 1.0], [1.0, 0.0, 0.0, 0.0], [1.0; 3], 1.0);
+    cloud.sh_coefficients = Some(vec![vec![0.0; 45]; 3]);
+
+    let stats = cloud.stats();
+    assert_eq!(stats.count, 3);
+    assert_eq!(stats.bounds.max, [2.0; 3]);
+    assert_eq!(stats.opacity_histogram[0], 1);
+    assert_eq!(stats.opacity_histogram[9], 2);
+    assert_eq!(stats.scale.min, 0.3);
+    assert_eq!(stats.scale.median, 0.5);
+    assert_eq!(stats.scale.max, 1.0);
+    assert_eq!(stats.sh_degree, Some(3));
+    assert_eq!(stats.memory_bytes, 3 * 56 + 3 * 45 * 4);
+}
 
-        let ply = cloud.to_ply().unwrap();
-        assert!(!ply.is_empty());
-        assert!(ply.starts_with(b"ply\n"));
+#[test]
+fn test_sanitize() {
+    let mut cloud = GaussianCloud::new();
+    cloud.add_gaussian([0.0; 3], [0.1; 3], [1.0, 0.0, 0.0, 0.0], [0.5; 3], 0.5);
+    cloud.add_gaussian([f32::NAN, 0.0, 0.0], [0.1; 3], [1.0, 0.0, 0.0, 0.0], [0.5; 3], 0.5);
+    cloud.add_gaussian([1.0; 3], [0.0, -0.2, 0.1], [0.0; 4], [1.5, 0.5, 0.5], 2.0);
+
+    let report = cloud.validate().unwrap();
+    assert_eq!(report.non_finite_positions, 1);
+    assert_eq!(report.bad_scales, 1);
+    assert_eq!(report.bad_rotations, 1);
+    assert_eq!(report.bad_opacity, 1);
+    assert_eq!(report.bad_colors, 1);
+
+    let report = cloud.sanitize().unwrap();
+    assert_eq!(report.removed, 1);
+    assert_eq!(cloud.count, 2);
+    assert_eq!(cloud.scales[1], [validation::MIN_SCALE, 0.2, 0.1]);
+    assert_eq!(cloud.rotations[1], [1.0, 0.0, 0.0, 0.0]);
+    assert_eq!(cloud.opacity[1], 1.0);
+    assert_eq!(cloud.colors[1], [1.0, 0.5, 0.5]);
+    assert!(cloud.validate().unwrap().is_clean());
+}
+
+#[test]
+fn test_decimate_keeps_important_splats() {
+    let mut cloud = GaussianCloud::new();
+    for i in 0..1000 {
+        // Every tenth splat is large and opaque
+        let (scale, opacity) = if i % 10 == 0 { (1.0, 1.0) } else { (0.01, 0.1) };
+        cloud.add_gaussian([i as f32, 0.0, 0.0], [scale; 3], [1.0, 0.0, 0.0, 0.0], [1.0; 3], opacity);
     }
 
-    #[test]
-    fn test_pipeline_config() {
-        let config = PipelineConfig::lgm_default();
+    let decimated = cloud.decimate(150);
+    assert_eq!(decimated.count, 150);
+    assert!(decimated.validate().is_ok());
+    assert_eq!(decimated.scales.iter().filter(|s| s[0] == 1.0).count(), 100);
+
+    assert_eq!(cloud.decimate(5000).count, 1000);
+
+    let budget = 30 * 1024;
+    let fitted = cloud.decimate(decimation::max_splats_for_ply_size(budget, PlyProfile::Genjutsu));
+    assert!(fitted.count > 400);
+    assert!(fitted.to_ply().unwrap().len() as u64 <= budget);
+}
+
+#[test]
+fn test_voxelize_and_vox_export() {
+    let mut cloud = GaussianCloud::new();
+    cloud.add_gaussian([0.0; 3], [0.1; 3], [1.0, 0.0, 0.0, 0.0], [1.0, 0.0, 0.0], 1.0);
+    cloud.add_gaussian([3.9, 1.9, 0.9], [0.1; 3], [1.0, 0.0, 0.0, 0.0], [0.0, 0.0, 1.0], 1.0);
+    cloud.add_gaussian([4.0, 2.0, 1.0], [0.1; 3], [1.0, 0.0, 0.0, 0.0], [0.0, 0.0, 1.0], 0.2);
+
+    let grid = cloud.to_voxels(4);
+    assert_eq!(grid.size, [4, 2, 1]);
+    assert!(grid.is_filled([0, 0, 0]));
+    assert!(grid.is_filled([3, 1, 0]));
+    assert_eq!(grid.filled().count(), 2);
 
-        match config {
-            PipelineConfig::LGM { inference_steps, .. } => {
-                assert_eq!(inference_steps, 50);
+    let vox = grid.to_vox().unwrap();
+    assert!(vox.starts_with(b"VOX "));
+    // Header, MAIN, SIZE, XYZI with two voxels, RGBA
+    assert_eq!(vox.len(), 8 + 12 + (12 + 12) + (12 + 4 + 8) + (12 + 1024));
+
+    assert!(cloud.to_voxels(300).to_vox().is_err());
+}
+
+#[test]
+fn test_textured_mesh() {
+    // A solid red ball of splats
+    let mut cloud = GaussianCloud::new();
+    for x in -10..=10 {
+        for y in -10..=10 {
+            for z in -10..=10 {
+                let p = [x as f32 * 0.1, y as f32 * 0.1, z as f32 * 0.1];
+                if p.iter().map(|c| c * c).sum::<f32>() <= 1.0 {
+                    cloud.add_gaussian(p, [0.05; 3], [1.0, 0.0, 0.0, 0.0], [1.0, 0.0, 0.0], 1.0);
+                }
             }
-            _ => panic!("Wrong config type"),
         }
     }
-}
\ No newline at end of file
+
+    let mesh = cloud.to_textured_mesh(MeshSettings { resolution: 8, texture_size: 64 }).unwrap();
+    assert!(mesh.triangle_count() > 0);
+    assert_eq!(mesh.positions.len(), mesh.uvs.len());
+    assert_eq!(mesh.positions.len(), mesh.normals.len());
+    assert_eq!(mesh.texture.len(), (mesh.texture_size * mesh.texture_size * 4) as usize);
+    assert!(mesh.uvs.iter().flatten().all(|c| (0.0..=1.0).contains(c)));
+
+    // Closed around the center, every triangle facing out
+    for triangle in mesh.indices.chunks_exact(3) {
+        let [a, b, c] = [0, 1, 2].map(|i| mesh.positions[triangle[i] as usize]);
+        let e1 = [b[0] - a[0], b[1] - a[1], b[2] - a[2]];
+        let e2 = [c[0] - a[0], c[1] - a[1], c[2] - a[2]];
+        let normal = [e1[1] * e2[2] - e1[2] * e2[1], e1[2] * e2[0] - e1[0] * e2[2], e1[0] * e2[1] - e1[1] * e2[0]];
+        assert!(normal.iter().zip(a).map(|(n, p)| n * p).sum::<f32>() > 0.0);
+    }
+
+    // Every texel the UVs reach is baked red
+    for [u, v] in &mesh.uvs {
+        let [x, y] = [u, v].map(|c| (c * mesh.texture_size as f32) as usize);
+        let texel = (y * mesh.texture_size as usize + x) * 4;
+        assert_eq!(&mesh.texture[texel..texel + 4], &[255, 0, 0, 255]);
+    }
+
+    let obj = mesh.to_obj("ball.mtl");
+    assert!(obj.contains("mtllib ball.mtl"));
+    assert_eq!(obj.lines().filter(|l| l.starts_with("f ")).count(), mesh.triangle_count());
+    assert!(mesh.to_mtl("ball.png").contains("map_Kd ball.png"));
+
+    let glb = mesh.to_glb(&[1, 2, 3]);
+    assert!(glb.starts_with(b"glTF"));
+    assert_eq!(u32::from_le_bytes(glb[8..12].try_into().unwrap()) as usize, glb.len());
+    assert_eq!(glb.len() % 4, 0);
+
+    assert!(GaussianCloud::new().to_textured_mesh(MeshSettings::default()).is_err());
+}
+
+#[test]
+fn test_normals_from_smallest_axis() {
+    let h = std::f32::consts::FRAC_1_SQRT_2;
+    let normal = normals::splat_normal([h, 0.0, 0.0, h], [0.01, 0.1, 0.1]);
+    assert!((normal[0]).abs() < 1e-5 && (normal[1] - 1.0).abs() < 1e-5 && normal[2].abs() < 1e-5);
+
+    let mut cloud = GaussianCloud::new();
+    cloud.add_gaussian([1.0, 0.0, 0.0], [0.01, 0.1, 0.1], [1.0, 0.0, 0.0, 0.0], [1.0; 3], 1.0);
+    cloud.add_gaussian([-1.0, 0.0, 0.0], [0.01, 0.1, 0.1], [1.0, 0.0, 0.0, 0.0], [1.0; 3], 1.0);
+
+    // Both face away from the center
+    assert_eq!(cloud.normals(), vec![[1.0, 0.0, 0.0], [-1.0, 0.0, 0.0]]);
+}
+
+#[test]
+fn test_align_recovers_rigid_transform() {
+    // Scattered points in an asymmetric box so the fit is unambiguous
+    let hash = |i: usize, k: f32| ((i as f32 * k).sin() * 43758.547).fract().abs();
+    let mut source = GaussianCloud::new();
+    for i in 0..400 {
+        let position = [hash(i, 12.9898) * 2.0, hash(i, 78.233), hash(i, 37.719) * 0.5];
+        source.add_gaussian(position, [0.01; 3], [1.0, 0.0, 0.0, 0.0], [1.0; 3], 1.0);
+    }
+
+    let half_angle = 10f32.to_radians() / 2.0;
+    let moved = Alignment {
+        rotation: [half_angle.cos(), 0.0, half_angle.sin(), 0.0],
+        translation: [0.3, -0.2, 0.1],
+        rms_error: 0.0,
+        iterations: 0,
+        converged: true,
+    };
+    let mut target = source.clone();
+    target.apply_alignment(&moved);
+
+    let alignment = source.align_to(&target).unwrap();
+    assert!(alignment.converged);
+    assert!(alignment.rms_error < 1e-3, "rms {}", alignment.rms_error);
+    for &p in source.positions.iter().take(10) {
+        let (a, b) = (alignment.transform_point(p), moved.transform_point(p));
+        assert!((0..3).all(|i| (a[i] - b[i]).abs() < 1e-3));
+    }
+
+    assert!(GaussianCloud::new().align_to(&target).is_err());
+}
+
+#[test]
+fn test_compare_clouds() {
+    let mut a = GaussianCloud::new();
+    a.add_gaussian([0.0; 3], [0.1; 3], [1.0, 0.0, 0.0, 0.0], [1.0; 3], 1.0);
+    a.add_gaussian([1.0, 0.0, 0.0], [0.1; 3], [1.0, 0.0, 0.0, 0.0], [1.0; 3], 1.0);
+
+    let mut b = a.clone();
+    assert_eq!(a.chamfer_distance(&b).unwrap(), 0.0);
+
+    b.add_gaussian([1.0, 2.0, 0.0], [0.1; 3], [1.0, 0.0, 0.0, 0.0], [1.0; 3], 1.0);
+    let comparison = b.compare(&a).unwrap();
+    assert_eq!(comparison.distances, vec![0.0, 0.0, 2.0]);
+    assert!((comparison.metrics.mean_to_reference - 2.0 / 3.0).abs() < 1e-6);
+    assert_eq!(comparison.metrics.mean_from_reference, 0.0);
+    assert_eq!(comparison.metrics.hausdorff, 2.0);
+
+    let colors = comparison.heatmap_colors(2.0);
+    assert_eq!(colors[0], [0.0, 0.0, 1.0]);
+    assert_eq!(colors[2], [1.0, 0.0, 0.0]);
+    assert_eq!(comparison::heatmap_color(0.5), [0.0, 1.0, 0.0]);
+
+    assert!(b.compare(&GaussianCloud::new()).is_err());
+}
+
+#[test]
+fn test_color_grade() {
+    let close = |a: [f32; 3], b: [f32; 3]| (0..3).all(|i| (a[i] - b[i]).abs() < 1e-5);
+    let color = [0.8, 0.4, 0.2];
+    assert!(close(ColorGrade::default().apply(color), color));
+
+    let grey = ColorGrade { saturation: 0.0, ..Default::default() }.apply(color);
+    assert!(close(grey, [grey[0]; 3]));
+
+    let hue = ColorGrade { hue: 120.0, ..Default::default() };
+    assert!(close(hue.apply([1.0, 0.0, 0.0]), [0.0, 1.0, 0.0]));
+
+    let mut cloud = GaussianCloud::new();
+    cloud.add_gaussian([0.0; 3], [0.1; 3], [1.0, 0.0, 0.0, 0.0], color, 1.0);
+    cloud.apply_color_grade(&ColorGrade { brightness: 0.5, ..Default::default() });
+    assert!(close(cloud.colors[0], [1.0, 0.9, 0.7]));
+}
+
+#[test]
+fn test_snap_to_ground() {
+    // A rod along x whose dense base is at -x
+    let mut cloud = GaussianCloud::new();
+    for i in 0..40 {
+        let x = i as f32 * 0.1;
+        let copies = if x < 1.0 { 4 } else { 1 };
+        for j in 0..copies {
+            let offset = j as f32 * 0.01;
+            cloud.add_gaussian([x, 5.0 + offset, offset], [0.01; 3], [1.0, 0.0, 0.0, 0.0], [1.0; 3], 1.0);
+        }
+    }
+
+    let axis = cloud.principal_axis().unwrap();
+    assert!(axis[0] > 0.99, "axis {:?}", axis);
+
+    cloud.snap_to_ground();
+    let bounds = cloud.bounds();
+    let size = bounds.size();
+    assert!(bounds.min[1].abs() < 1e-5);
+    assert!(size[1] > 3.8 && size[0] < 0.1);
+    // The dense end is now at the bottom
+    assert!(cloud.positions[0][1] < 0.01);
+}
+
+#[test]
+fn test_normalize_roundtrip() {
+    let mut cloud = GaussianCloud::new();
+    cloud.add_gaussian([2.0, 2.0, 2.0], [0.4; 3], [1.0, 0.0, 0.0, 0.0], [1.0; 3], 1.0);
+    cloud.add_gaussian([6.0, 4.0, 2.0], [0.4; 3], [1.0, 0.0, 0.0, 0.0], [1.0; 3], 1.0);
+    let original = cloud.positions.clone();
+
+    let normalization = cloud.normalize();
+    let bounds = cloud.bounds();
+    assert_eq!(bounds.center(), [0.0; 3]);
+    assert_eq!(bounds.size(), [1.0, 0.5, 0.0]);
+    assert_eq!(cloud.scales[0], [0.1; 3]);
+
+    // Normalizing again is a no-op, and composing keeps the original transform
+    let again = cloud.normalize();
+    assert_eq!(normalization.then(&again), normalization);
+
+    cloud.denormalize(&normalization);
+    assert_eq!(cloud.positions, original);
+    assert_eq!(normalization.invert(normalization.apply([1.0, 2.0, 3.0])), [1.0, 2.0, 3.0]);
+}
+
+#[test]
+fn test_splat_export() {
+    let mut cloud = GaussianCloud::new();
+    cloud.add_gaussian([1.0, 2.0, 3.0], [0.1, 0.2, 0.3], [0.0, 0.0, 2.0, 0.0], [1.0, 0.5, 0.0], 0.5);
+    cloud.add_gaussian([0.0; 3], [0.1; 3], [1.0, 0.0, 0.0, 0.0], [1.0; 3], 1.0);
+
+    let bytes = cloud.to_splat();
+    assert_eq!(bytes.len(), 2 * SPLAT_RECORD_SIZE);
+
+    let record = &bytes[..SPLAT_RECORD_SIZE];
+    assert_eq!(f32::from_le_bytes(record[8..12].try_into().unwrap()), 3.0);
+    assert_eq!(f32::from_le_bytes(record[20..24].try_into().unwrap()), 0.3);
+    assert_eq!(&record[24..28], &[255, 128, 0, 128]);
+    // Rotations are normalized before packing
+    assert_eq!(&record[28..32], &[128, 128, 255, 128]);
+}
+
+#[test]
+fn test_supersplat_ply_layout() {
+    let mut cloud = GaussianCloud::new();
+    cloud.add_gaussian([1.0, 2.0, 3.0], [1.0; 3], [1.0, 0.0, 0.0, 0.0], [0.5; 3], 0.5);
+
+    let ply = cloud.to_supersplat_ply().unwrap();
+    let header = GaussianCloud::new().to_supersplat_ply().unwrap();
+    assert_eq!(ply.len(), header.len() + PlyProfile::SuperSplat.vertex_size());
+
+    let header_end = ply.windows(11).position(|w| w == b"end_header\n").unwrap() + 11;
+    let values: Vec<f32> = ply[header_end..].chunks_exact(4)
+        .map(|b| f32::from_le_bytes(b.try_into().unwrap()))
+        .collect();
+
+    // Flipped to y-down, z-forward; mid grey, half opacity and unit scale encode to zero
+    assert_eq!(&values[0..3], &[1.0, -2.0, -3.0]);
+    assert!(values[6..13].iter().all(|v| v.abs() < 1e-5));
+    assert_eq!(&values[13..17], &[0.0, 1.0, 0.0, 0.0]);
+}
+
+#[test]
+fn test_heatmap_values() {
+    let mut cloud = GaussianCloud::new();
+    for i in 0..5 {
+        cloud.add_gaussian([i as f32 * 0.001, 0.0, 0.0], [0.1, 0.2, 0.05], [1.0, 0.0, 0.0, 0.0], [1.0; 3], 0.5);
+    }
+    // Far from the cluster, so it has no neighbors
+    cloud.add_gaussian([10.0, 0.0, 0.0], [2.0, 0.1, 0.1], [1.0, 0.0, 0.0, 0.0], [1.0; 3], 1.0);
+
+    assert_eq!(cloud.heatmap_values(HeatmapQuantity::Opacity)[5], 1.0);
+    assert_eq!(cloud.heatmap_values(HeatmapQuantity::Scale)[..2], [0.2, 0.2]);
+    assert_eq!(cloud.heatmap_values(HeatmapQuantity::ShEnergy), vec![0.0; 6]);
+
+    let density = cloud.heatmap_values(HeatmapQuantity::Density);
+    assert_eq!(density[0], 4.0);
+    assert_eq!(density[5], 0.0);
+
+    let colors = cloud.heatmap_colors(HeatmapQuantity::Opacity);
+    assert_eq!(colors[5], comparison::heatmap_color(1.0));
+    assert_eq!(colors[0], comparison::heatmap_color(0.5));
+}
+
+#[test]
+fn test_paint() {
+    let mut cloud = GaussianCloud::new();
+    for _ in 0..3 {
+        cloud.add_gaussian([0.0; 3], [0.1; 3], [1.0, 0.0, 0.0, 0.0], [1.0, 1.0, 1.0], 0.8);
+    }
+
+    cloud.paint(&[(0, 1.0), (1, 0.5)], PaintMode::Opacity { factor: 0.5 });
+    assert_eq!(cloud.opacity, vec![0.4, 0.6, 0.8]);
+
+    cloud.paint(&[(0, 1.0), (2, 0.5)], PaintMode::Tint { color: [1.0, 0.0, 0.0], strength: 1.0 });
+    assert_eq!(cloud.colors[0], [1.0, 0.0, 0.0]);
+    assert_eq!(cloud.colors[1], [1.0, 1.0, 1.0]);
+    assert_eq!(cloud.colors[2], [1.0, 0.5, 0.5]);
+}
+
+#[test]
+fn test_volume_contains() {
+    let cube = Volume::Box { center: [0.0; 3], half_extents: [0.5, 1.0, 0.5] };
+    assert!(cube.contains([0.4, -0.9, 0.5]));
+    assert!(!cube.contains([0.6, 0.0, 0.0]));
+    assert_eq!(cube.outline().len(), 12);
+    assert!(cube.outline().iter().all(|[a, b]| (0..3).filter(|&i| a[i] != b[i]).count() == 1));
+
+    let sphere = Volume::Sphere { center: [1.0, 0.0, 0.0], radius: 1.0 };
+    assert!(sphere.contains([2.0, 0.0, 0.0]));
+    assert!(!sphere.contains([1.0, 0.8, 0.8]));
+    assert_eq!(sphere.center(), [1.0, 0.0, 0.0]);
+}
+
+#[test]
+fn test_array_layouts() {
+    let grid = ArrayLayout::Grid { columns: 3, rows: 2, spacing: 2.0 }.placements();
+    assert_eq!(grid.len(), 6);
+    assert_eq!(grid[0].offset, [-2.0, 0.0, -1.0]);
+    assert_eq!(grid[5].offset, [2.0, 0.0, 1.0]);
+
+    let circle = ArrayLayout::Circle { count: 4, radius: 1.0 }.placements();
+    assert!((circle[1].offset[0] - 1.0).abs() < 1e-6 && circle[1].offset[2].abs() < 1e-6);
+
+    let scatter = ArrayLayout::Scatter { count: 10, extent: 4.0, seed: 7 };
+    assert_eq!(scatter.placements(), scatter.placements());
+    assert!(scatter.placements().iter().all(|p| p.offset[0].abs() <= 2.0 && p.offset[2].abs() <= 2.0));
+
+    // A half turn about the center swaps the ends of a rod
+    let mut cloud = GaussianCloud::new();
+    cloud.add_gaussian([1.0, 0.0, 0.0], [0.1; 3], [1.0, 0.0, 0.0, 0.0], [1.0; 3], 1.0);
+    cloud.add_gaussian([3.0, 0.0, 0.0], [0.1; 3], [1.0, 0.0, 0.0, 0.0], [1.0; 3], 1.0);
+    let placements = [
+        Placement { yaw: 0.0, offset: [0.0, 0.0, 5.0] },
+        Placement { yaw: std::f32::consts::PI, offset: [0.0; 3] },
+    ];
+    let arrayed = cloud.arrayed(&placements);
+    assert_eq!(arrayed.count, 4);
+    assert_eq!(arrayed.positions[0], [1.0, 0.0, 5.0]);
+    assert!((arrayed.positions[2][0] - 3.0).abs() < 1e-5 && (arrayed.positions[3][0] - 1.0).abs() < 1e-5);
+    assert!(arrayed.validate().is_ok());
+}
+
+#[test]
+fn test_morph() {
+    let mut from = GaussianCloud::new();
+    from.add_gaussian([0.0, 0.0, 0.0], [0.1; 3], [1.0, 0.0, 0.0, 0.0], [0.0; 3], 1.0);
+    let mut to = GaussianCloud::new();
+    to.add_gaussian([1.0, 0.0, 0.0], [0.3; 3], [1.0, 0.0, 0.0, 0.0], [1.0; 3], 0.5);
+    to.add_gaussian([0.0, 2.0, 0.0], [0.1; 3], [0.0, 1.0, 0.0, 0.0], [1.0; 3], 1.0);
+
+    // The lone start splat is shared by both end splats
+    let morph = Morph::new(&from, &to).unwrap();
+    assert_eq!(morph.pairs(), 2);
+    assert_eq!(morph.at(0.0).positions, vec![[0.0; 3], [0.0; 3]]);
+    assert_eq!(morph.at(1.0).positions, to.positions);
+
+    let half = morph.at(0.5);
+    assert_eq!(half.positions[0], [0.5, 0.0, 0.0]);
+    assert_eq!(half.opacity[0], 0.75);
+    assert_eq!(half.colors[0], [0.5; 3]);
+    let q = half.rotations[1];
+    assert!((q[0] - q[1]).abs() < 1e-6 && (q.iter().map(|v| v * v).sum::<f32>() - 1.0).abs() < 1e-5);
+    assert!(half.validate().is_ok());
+
+    assert!(Morph::new(&GaussianCloud::new(), &to).is_err());
+}
@@ -0,0 +1,162 @@
+//! Detection and repair of splat records that break sorting or rendering,
+//! such as NaN positions or zero scales in model outputs.
+
+use std::fmt;
+use crate::error::{Error, Result};
+use crate::gaussian_cloud::GaussianCloud;
+
+/// Smallest scale a splat is clamped to, so it still has some extent
+pub const MIN_SCALE: f32 = 1e-6;
+
+/// Counts of bad records found in a cloud. A record can be counted under
+/// several fields.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ValidationReport {
+    /// NaN or infinite position; removed by [`GaussianCloud::sanitize`]
+    pub non_finite_positions: usize,
+    /// Scale axis that is non-finite, zero or negative
+    pub bad_scales: usize,
+    /// Rotation that is non-finite or zero-length
+    pub bad_rotations: usize,
+    /// NaN opacity (removed) or one outside [0, 1] (clamped)
+    pub bad_opacity: usize,
+    /// Color channel that is NaN or outside [0, 1]
+    pub bad_colors: usize,
+    /// Records dropped by [`GaussianCloud::sanitize`]
+    pub removed: usize,
+}
+
+impl ValidationReport {
+    pub fn is_clean(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+impl fmt::Display for ValidationReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_clean() {
+            return write!(f, "no problems found");
+        }
+
+        let parts: Vec<String> = [
+            (self.non_finite_positions, "invalid positions"),
+            (self.bad_scales, "bad scales"),
+            (self.bad_rotations, "bad rotations"),
+            (self.bad_opacity, "bad opacities"),
+            (self.bad_colors, "bad colors"),
+            (self.removed, "splats removed"),
+        ]
+            .into_iter()
+            .filter(|&(n, _)| n > 0)
+            .map(|(n, what)| format!("{} {}", n, what))
+            .collect();
+
+        write!(f, "{}", parts.join(", "))
+    }
+}
+
+fn scale_ok(scale: &[f32; 3]) -> bool {
+    scale.iter().all(|s| s.is_finite() && *s > 0.0)
+}
+
+fn rotation_ok(rotation: &[f32; 4]) -> bool {
+    let length_sq: f32 = rotation.iter().map(|r| r * r).sum();
+    length_sq.is_finite() && length_sq > f32::EPSILON
+}
+
+/// Keep the elements whose entry in `keep` is true
+fn retain_kept<T>(values: &mut Vec<T>, keep: &[bool]) {
+    let mut i = 0;
+    values.retain(|_| {
+        i += 1;
+        keep[i - 1]
+    });
+}
+
+fn unit(value: f32) -> bool {
+    (0.0..=1.0).contains(&value)
+}
+
+impl GaussianCloud {
+    /// Check that all arrays have consistent length and count records that
+    /// would break sorting or rendering.
+    ///
+    /// Inconsistent arrays are an error; bad records are only reported.
+    pub fn validate(&self) -> Result<ValidationReport> {
+        if self.positions.len() != self.count ||
+            self.scales.len() != self.count ||
+            self.rotations.len() != self.count ||
+            self.colors.len() != self.count ||
+            self.opacity.len() != self.count {
+            return Err(Error::InvalidGaussianCloud(
+                "Inconsistent array lengths".to_string()
+            ));
+        }
+
+        let mut report = ValidationReport::default();
+        for i in 0..self.count {
+            report.non_finite_positions += !self.positions[i].iter().all(|p| p.is_finite()) as usize;
+            report.bad_scales += !scale_ok(&self.scales[i]) as usize;
+            report.bad_rotations += !rotation_ok(&self.rotations[i]) as usize;
+            report.bad_opacity += !unit(self.opacity[i]) as usize;
+            report.bad_colors += !self.colors[i].iter().all(|&c| unit(c)) as usize;
+        }
+
+        Ok(report)
+    }
+
+    /// Repair bad records in place: splats with a non-finite position or
+    /// opacity are removed, scales are made positive and at least
+    /// [`MIN_SCALE`], rotations are normalized (identity when unusable), and
+    /// opacity and colors are clamped to [0, 1].
+    pub fn sanitize(&mut self) -> Result<ValidationReport> {
+        let mut report = self.validate()?;
+        if report.is_clean() {
+            return Ok(report);
+        }
+
+        let keep: Vec<bool> = (0..self.count)
+            .map(|i| self.positions[i].iter().all(|p| p.is_finite()) && !self.opacity[i].is_nan())
+            .collect();
+
+        retain_kept(&mut self.positions, &keep);
+        retain_kept(&mut self.scales, &keep);
+        retain_kept(&mut self.rotations, &keep);
+        retain_kept(&mut self.colors, &keep);
+        retain_kept(&mut self.opacity, &keep);
+        if let Some(sh) = &mut self.sh_coefficients
+            && sh.len() == keep.len()
+        {
+            retain_kept(sh, &keep);
+        }
+
+        report.removed = self.count - self.positions.len();
+        self.count = self.positions.len();
+
+        for scale in &mut self.scales {
+            for s in scale.iter_mut() {
+                *s = if s.is_finite() { s.abs().max(MIN_SCALE) } else { MIN_SCALE };
+            }
+        }
+
+        for rotation in &mut self.rotations {
+            let length = rotation.iter().map(|r| r * r).sum::<f32>().sqrt();
+            *rotation = if length.is_finite() && length > f32::EPSILON {
+                rotation.map(|r| r / length)
+            } else {
+                [1.0, 0.0, 0.0, 0.0]
+            };
+        }
+
+        for o in &mut self.opacity {
+            *o = o.clamp(0.0, 1.0);
+        }
+
+        for color in &mut self.colors {
+            *color = color.map(|c| if c.is_nan() { 0.0 } else { c.clamp(0.0, 1.0) });
+        }
+
+        self.invalidate_octree();
+        Ok(report)
+    }
+}
@@ -0,0 +1,65 @@
+//! Box and sphere volumes placed in the scene, for deleting or isolating
+//! the splats inside them in one go. Quicker than brushing away a large
+//! cluster of floaters.
+
+/// Segments per circle of a sphere's outline
+const CIRCLE_SEGMENTS: usize = 48;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Volume {
+    /// Axis-aligned box
+    Box { center: [f32; 3], half_extents: [f32; 3] },
+    Sphere { center: [f32; 3], radius: f32 },
+}
+
+impl Volume {
+    pub fn center(&self) -> [f32; 3] {
+        match self {
+            Self::Box { center, .. } | Self::Sphere { center, .. } => *center,
+        }
+    }
+
+    /// Whether `point` is inside, boundary included
+    pub fn contains(&self, point: [f32; 3]) -> bool {
+        match self {
+            Self::Box { center, half_extents } => {
+                (0..3).all(|i| (point[i] - center[i]).abs() <= half_extents[i])
+            }
+            Self::Sphere { center, radius } => {
+                let d: f32 = (0..3).map(|i| (point[i] - center[i]).powi(2)).sum();
+                d <= radius * radius
+            }
+        }
+    }
+
+    /// Line segments outlining the volume, for drawing it over the scene:
+    /// a box's edges, or a sphere's circles around each axis
+    pub fn outline(&self) -> Vec<[[f32; 3]; 2]> {
+        match *self {
+            Self::Box { center, half_extents } => {
+                let corner = |i: usize| std::array::from_fn(|axis| {
+                    let sign = if i >> axis & 1 == 1 { 1.0 } else { -1.0 };
+                    center[axis] + sign * half_extents[axis]
+                });
+                // Corners differing in one bit share an edge
+                (0..8)
+                    .flat_map(|i| (0..3).map(move |axis| (i, i | 1 << axis)))
+                    .filter(|&(i, j)| i != j)
+                    .map(|(i, j)| [corner(i), corner(j)])
+                    .collect()
+            }
+            Self::Sphere { center, radius } => {
+                let point = |axis: usize, k: usize| {
+                    let angle = k as f32 / CIRCLE_SEGMENTS as f32 * std::f32::consts::TAU;
+                    let mut p = center;
+                    p[(axis + 1) % 3] += radius * angle.cos();
+                    p[(axis + 2) % 3] += radius * angle.sin();
+                    p
+                };
+                (0..3)
+                    .flat_map(|axis| (0..CIRCLE_SEGMENTS).map(move |k| [point(axis, k), point(axis, k + 1)]))
+                    .collect()
+            }
+        }
+    }
+}
@@ -0,0 +1,153 @@
+//! Voxelization of Gaussian clouds and MagicaVoxel (`.vox`) export, for
+//! pulling generated props into voxel-based games.
+
+use crate::bounding_box::BoundingBox;
+use crate::error::{Error, Result};
+use crate::gaussian_cloud::GaussianCloud;
+
+/// Accumulated opacity a cell needs to count as filled
+pub(crate) const OCCUPANCY_THRESHOLD: f32 = 0.5;
+
+/// Largest grid dimension MagicaVoxel accepts
+pub const VOX_MAX_SIZE: usize = 256;
+
+/// Levels per channel of the fixed `.vox` palette (6³ = 216 colors)
+const PALETTE_LEVELS: usize = 6;
+
+/// Regular grid over a cloud's bounds with per-cell opacity and color
+#[derive(Clone, Debug)]
+pub struct VoxelGrid {
+    /// Cells along x, y and z
+    pub size: [usize; 3],
+    pub bounds: BoundingBox,
+    /// Edge length of a cell
+    pub cell_size: f32,
+    /// Opacity accumulated in each cell, x fastest
+    pub occupancy: Vec<f32>,
+    /// Opacity-weighted mean color of each cell
+    pub colors: Vec<[f32; 3]>,
+}
+
+impl VoxelGrid {
+    pub(crate) fn index(&self, [x, y, z]: [usize; 3]) -> usize {
+        x + self.size[0] * (y + self.size[1] * z)
+    }
+
+    pub fn is_filled(&self, cell: [usize; 3]) -> bool {
+        self.occupancy[self.index(cell)] >= OCCUPANCY_THRESHOLD
+    }
+
+    /// Filled cells and their colors
+    pub fn filled(&self) -> impl Iterator<Item = ([usize; 3], [f32; 3])> + '_ {
+        let [sx, sy, sz] = self.size;
+        (0..sz).flat_map(move |z| (0..sy).flat_map(move |y| (0..sx).map(move |x| [x, y, z])))
+            .filter(|&cell| self.is_filled(cell))
+            .map(|cell| (cell, self.colors[self.index(cell)]))
+    }
+
+    /// Encode as a MagicaVoxel `.vox` file. The cloud's y-up is mapped to the
+    /// format's z-up, and colors are snapped to a fixed 216-color palette.
+    pub fn to_vox(&self) -> Result<Vec<u8>> {
+        if self.size.iter().any(|&s| s > VOX_MAX_SIZE) {
+            return Err(Error::InvalidConfig(format!(
+                "Grid {:?} exceeds the .vox limit of {} per axis", self.size, VOX_MAX_SIZE
+            )));
+        }
+
+        let quantize = |c: f32| ((c.clamp(0.0, 1.0) * (PALETTE_LEVELS - 1) as f32).round()) as usize;
+
+        let voxels: Vec<[u8; 4]> = self.filled()
+            .map(|([x, y, z], [r, g, b])| {
+                let color = 1 + quantize(r) * PALETTE_LEVELS * PALETTE_LEVELS + quantize(g) * PALETTE_LEVELS + quantize(b);
+                [x as u8, z as u8, y as u8, color as u8]
+            })
+            .collect();
+
+        let mut size = Vec::with_capacity(12);
+        for s in [self.size[0], self.size[2], self.size[1]] {
+            size.extend_from_slice(&(s as u32).to_le_bytes());
+        }
+
+        let mut xyzi = Vec::with_capacity(4 + voxels.len() * 4);
+        xyzi.extend_from_slice(&(voxels.len() as u32).to_le_bytes());
+        xyzi.extend(voxels.iter().flatten());
+
+        // Palette entry i is color index i + 1; unused entries are black
+        let mut rgba = vec![0u8; 256 * 4];
+        let level = |l: usize| (l * 255 / (PALETTE_LEVELS - 1)) as u8;
+        for r in 0..PALETTE_LEVELS {
+            for g in 0..PALETTE_LEVELS {
+                for b in 0..PALETTE_LEVELS {
+                    let i = r * PALETTE_LEVELS * PALETTE_LEVELS + g * PALETTE_LEVELS + b;
+                    rgba[i * 4..i * 4 + 4].copy_from_slice(&[level(r), level(g), level(b), 255]);
+                }
+            }
+        }
+
+        let mut children = Vec::new();
+        write_chunk(&mut children, b"SIZE", &size, &[]);
+        write_chunk(&mut children, b"XYZI", &xyzi, &[]);
+        write_chunk(&mut children, b"RGBA", &rgba, &[]);
+
+        let mut buffer = Vec::with_capacity(8 + 12 + children.len());
+        buffer.extend_from_slice(b"VOX ");
+        buffer.extend_from_slice(&150u32.to_le_bytes());
+        write_chunk(&mut buffer, b"MAIN", &[], &children);
+        Ok(buffer)
+    }
+}
+
+fn write_chunk(buffer: &mut Vec<u8>, id: &[u8; 4], content: &[u8], children: &[u8]) {
+    buffer.extend_from_slice(id);
+    buffer.extend_from_slice(&(content.len() as u32).to_le_bytes());
+    buffer.extend_from_slice(&(children.len() as u32).to_le_bytes());
+    buffer.extend_from_slice(content);
+    buffer.extend_from_slice(children);
+}
+
+impl GaussianCloud {
+    /// Rasterize splat centers into a grid with `resolution` cells along the
+    /// longest axis of the bounds. Each splat adds its opacity to the cell
+    /// holding its center, and cell colors are opacity-weighted means.
+    pub fn to_voxels(&self, resolution: usize) -> VoxelGrid {
+        let resolution = resolution.max(1);
+        let bounds = self.bounds();
+        let extent = bounds.size();
+        let cell_size = (extent[0].max(extent[1]).max(extent[2]) / resolution as f32).max(f32::EPSILON);
+        let size = extent.map(|e| ((e / cell_size).ceil() as usize).clamp(1, resolution));
+
+        let cells = size[0] * size[1] * size[2];
+        let mut grid = VoxelGrid {
+            size,
+            bounds: bounds.clone(),
+            cell_size,
+            occupancy: vec![0.0; cells],
+            colors: vec![[0.0; 3]; cells],
+        };
+
+        for i in 0..self.count {
+            let pos = self.positions[i];
+            let opacity = self.opacity[i];
+            if !pos.iter().all(|p| p.is_finite()) || opacity.is_nan() || opacity <= 0.0 {
+                continue;
+            }
+
+            let cell = std::array::from_fn(|axis| {
+                (((pos[axis] - bounds.min[axis]) / cell_size) as usize).min(size[axis] - 1)
+            });
+            let index = grid.index(cell);
+            grid.occupancy[index] += opacity;
+            for (sum, c) in grid.colors[index].iter_mut().zip(self.colors[i]) {
+                *sum += c * opacity;
+            }
+        }
+
+        for (color, &weight) in grid.colors.iter_mut().zip(&grid.occupancy) {
+            if weight > 0.0 {
+                *color = color.map(|c| c / weight);
+            }
+        }
+
+        grid
+    }
+}
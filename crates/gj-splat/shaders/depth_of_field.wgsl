@@ -0,0 +1,79 @@
+// Depth of field: a gather blur whose radius follows each pixel's circle of
+// confusion, from the splat color and the coverage-weighted depth written by
+// fs_depth in gaussian.wgsl
+
+struct Params {
+    focus_distance: f32,
+    // Blur radius in pixels far behind the focus plane
+    aperture: f32,
+    _padding: vec2<f32>,
+}
+
+@group(0) @binding(0)
+var color_tex: texture_2d<f32>;
+@group(0) @binding(1)
+var depth_tex: texture_2d<f32>;
+@group(0) @binding(2)
+var color_sampler: sampler;
+@group(0) @binding(3)
+var<uniform> params: Params;
+
+// Keep in sync with depth_of_field.rs
+const MAX_COC: f32 = 16.0;
+const FAR: f32 = 1000.0;
+const TAPS: u32 = 48u;
+const GOLDEN_ANGLE: f32 = 2.39996323;
+
+@vertex
+fn vs_main(@builtin(vertex_index) index: u32) -> @builtin(position) vec4<f32> {
+    // One triangle covering the whole target
+    let uv = vec2<f32>(f32((index << 1u) & 2u), f32(index & 2u));
+    return vec4<f32>(uv * 2.0 - 1.0, 0.0, 1.0);
+}
+
+// Same as circle_of_confusion in depth_of_field.rs
+fn coc(depth: f32) -> f32 {
+    return min(params.aperture * abs(depth - params.focus_distance) / max(depth, 0.001), MAX_COC);
+}
+
+// View depth at `pos`; empty pixels are far away
+fn depth_at(pos: vec2<i32>) -> f32 {
+    let size = vec2<i32>(textureDimensions(depth_tex));
+    let acc = textureLoad(depth_tex, clamp(pos, vec2<i32>(0), size - 1), 0).rg;
+    if acc.y < 0.001 {
+        return FAR;
+    }
+    return acc.x / acc.y;
+}
+
+@fragment
+fn fs_main(@builtin(position) frag: vec4<f32>) -> @location(0) vec4<f32> {
+    let pos = vec2<i32>(frag.xy);
+    let radius = coc(depth_at(pos));
+    if radius < 0.5 {
+        return textureLoad(color_tex, pos, 0);
+    }
+
+    let size = vec2<f32>(textureDimensions(color_tex));
+    var sum = vec4<f32>(0.0);
+    var weight = 0.0;
+    for (var i = 0u; i < TAPS; i++) {
+        // Golden-angle spiral filling the disc evenly
+        let r = sqrt((f32(i) + 0.5) / f32(TAPS)) * radius;
+        let a = f32(i) * GOLDEN_ANGLE;
+        let offset = vec2<f32>(cos(a), sin(a)) * r;
+
+        // A tap only counts if its own blur reaches this far, so sharp
+        // surfaces don't smear onto the blurred ones around them
+        let tap_coc = coc(depth_at(pos + vec2<i32>(offset)));
+        let w = clamp(tap_coc - r + 1.0, 0.0, 1.0);
+
+        sum += textureSampleLevel(color_tex, color_sampler, (frag.xy + offset) / size, 0.0) * w;
+        weight += w;
+    }
+
+    if weight < 0.001 {
+        return textureLoad(color_tex, pos, 0);
+    }
+    return sum / weight;
+}
@@ -4,23 +4,27 @@ struct Uniforms {
     view_proj: mat4x4<f32>,
     view: mat4x4<f32>,
     camera_pos: vec3<f32>,
-    _padding1: f32,
+    // Height of the lowest splat, where the ground shadow falls
+    ground_height: f32,
     viewport: vec2<f32>,
     focal: vec2<f32>,
+    // brightness, contrast, saturation, hue (radians)
+    color_grade: vec4<f32>,
+    // Normal and offset of each clipping plane; unused ones are all zero
+    clip_planes: array<vec4<f32>, 3>,
 }
 
 @group(0) @binding(0)
 var<uniform> uniforms: Uniforms;
 
+// Instance attributes arrive as f32, f16 or unorm8 depending on the
+// renderer's SplatPrecision; the shader sees them all as f32
 struct VertexInput {
     @location(0) quad_pos: vec2<f32>,
     @location(1) position: vec3<f32>,
-    @location(2) _padding1: f32,
-    @location(3) color: vec3<f32>,
-    @location(4) opacity: f32,
-    @location(5) scale: vec3<f32>,
-    @location(6) _padding2: f32,
-    @location(7) rotation: vec4<f32>,
+    @location(2) color: vec4<f32>,
+    @location(3) scale_opacity: vec4<f32>,
+    @location(4) rotation: vec4<f32>,
 }
 
 struct VertexOutput {
@@ -28,17 +32,64 @@ struct VertexOutput {
     @location(0) color: vec3<f32>,
     @location(1) opacity: f32,
     @location(2) uv: vec2<f32>,
+    // 1 for selected splats
+    @location(3) selected: f32,
+    // Distance in front of the camera
+    @location(4) depth: f32,
+}
+
+// Same adjustments as gj_core's ColorGrade::apply
+fn grade_color(color: vec3<f32>) -> vec3<f32> {
+    let g = uniforms.color_grade;
+    var c = (color + g.x - 0.5) * g.y + 0.5;
+
+    let luma = dot(c, vec3<f32>(0.2126, 0.7152, 0.0722));
+    c = mix(vec3<f32>(luma), c, g.z);
+
+    // Rodrigues rotation around the grey axis
+    let cos_h = cos(g.w);
+    let k = (1.0 - cos_h) / 3.0;
+    let s = sin(g.w) / sqrt(3.0);
+    c = vec3<f32>(
+        dot(c, vec3<f32>(cos_h + k, k - s, k + s)),
+        dot(c, vec3<f32>(k + s, cos_h + k, k - s)),
+        dot(c, vec3<f32>(k - s, k + s, cos_h + k)),
+    );
+
+    return clamp(c, vec3<f32>(0.0), vec3<f32>(1.0));
+}
+
+// Splats centered in front of a clipping plane are cut away
+fn is_clipped(position: vec3<f32>) -> bool {
+    for (var i = 0; i < 3; i++) {
+        let plane = uniforms.clip_planes[i];
+        if dot(plane.xyz, position) > plane.w {
+            return true;
+        }
+    }
+    return false;
+}
+
+// Vertex outside the clip volume, so the whole quad is dropped
+fn culled() -> VertexOutput {
+    var out: VertexOutput;
+    out.clip_position = vec4<f32>(0.0, 0.0, 2.0, 1.0);
+    return out;
 }
 
 @vertex
 fn vs_main(in: VertexInput) -> VertexOutput {
+    if is_clipped(in.position) {
+        return culled();
+    }
     var out: VertexOutput;
 
     // Project center
     let clip_pos = uniforms.view_proj * vec4<f32>(in.position, 1.0);
 
     // Use actual scale from data (much larger multiplier)
-    let avg_scale = (in.scale.x + in.scale.y + in.scale.z) / 3.0;
+    let scale = in.scale_opacity.xyz;
+    let avg_scale = (scale.x + scale.y + scale.z) / 3.0;
     let radius = avg_scale * 500.0; // Increased from 100.0 to 500.0
 
     // Create billboard quad
@@ -57,29 +108,105 @@ fn vs_main(in: VertexInput) -> VertexOutput {
         1.0
     );
 
-    out.color = in.color;
-    out.opacity = in.opacity;
+    // Alpha flags selected splats, which are tinted orange
+    out.color = mix(grade_color(in.color.rgb), vec3<f32>(1.0, 0.55, 0.1), in.color.a * 0.6);
+    out.opacity = in.scale_opacity.w;
     out.uv = in.quad_pos;
+    out.selected = in.color.a;
+    out.depth = distance_factor;
 
     return out;
 }
 
-@fragment
-fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+// How much wider a splat's shadow is than the splat, for a soft edge
+const SHADOW_SPREAD: f32 = 1.5;
+
+// The splat flattened straight down onto the ground plane, as a disc of
+// about its on-screen size lying on the ground
+@vertex
+fn vs_shadow(in: VertexInput) -> VertexOutput {
+    if is_clipped(in.position) {
+        return culled();
+    }
+    var out: VertexOutput;
+
+    // vs_main sizes splats in pixels; this is the same size in world units
+    let scale = in.scale_opacity.xyz;
+    let avg_scale = (scale.x + scale.y + scale.z) / 3.0;
+    let radius = avg_scale * 500.0 / uniforms.focal.y * SHADOW_SPREAD;
+
+    let ground = vec3<f32>(in.position.x, uniforms.ground_height, in.position.z);
+    let corner = ground + vec3<f32>(in.quad_pos.x, 0.0, in.quad_pos.y) * radius;
+    out.clip_position = uniforms.view_proj * vec4<f32>(corner, 1.0);
+
+    out.color = vec3<f32>(0.0);
+    out.opacity = in.scale_opacity.w;
+    out.uv = in.quad_pos;
+    out.selected = 0.0;
+    out.depth = 0.0;
+
+    return out;
+}
+
+// Opacity of the splat at `uv`, 0 outside its circle
+fn splat_alpha(in: VertexOutput) -> f32 {
     // Softer gaussian falloff
     let dist = length(in.uv);
 
-    // Discard outside circle
+    // Nothing outside circle
     if dist > 1.0 {
-        discard;
+        return 0.0;
     }
 
     // Softer falloff (reduced exponent for larger visible area)
-    let alpha = exp(-dist * dist * 1.0) * in.opacity;  // Changed from 2.0 to 1.0
+    return exp(-dist * dist * 1.0) * in.opacity;  // Changed from 2.0 to 1.0
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    let alpha = splat_alpha(in);
 
     if alpha < 0.005 {  // Lower threshold
         discard;
     }
 
     return vec4<f32>(in.color * alpha, alpha);
-}
\ No newline at end of file
+}
+
+// Coverage of the selected splats for the selection outline, blended back
+// to front so unselected splats in front hide it
+@fragment
+fn fs_mask(in: VertexOutput) -> @location(0) vec4<f32> {
+    let alpha = splat_alpha(in);
+
+    if alpha < 0.005 {
+        discard;
+    }
+
+    return vec4<f32>(in.selected, 0.0, 0.0, alpha);
+}
+
+// Coverage-weighted view depth for depth of field: red accumulates depth
+// and green coverage, composited back to front like the colors
+@fragment
+fn fs_depth(in: VertexOutput) -> @location(0) vec4<f32> {
+    let alpha = splat_alpha(in);
+
+    if alpha < 0.005 {
+        discard;
+    }
+
+    return vec4<f32>(in.depth * alpha, alpha, 0.0, alpha);
+}
+
+// Footprint coverage for the ground shadow mask
+@fragment
+fn fs_shadow(in: VertexOutput) -> @location(0) vec4<f32> {
+    let alpha = splat_alpha(in);
+
+    if alpha < 0.005 {
+        discard;
+    }
+
+    return vec4<f32>(alpha, 0.0, 0.0, alpha);
+}
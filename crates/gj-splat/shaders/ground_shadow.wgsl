@@ -0,0 +1,38 @@
+// Ground shadow: the splats' footprint on the ground plane (see vs_shadow in
+// gaussian.wgsl), softened and darkened over the cleared background
+
+@group(0) @binding(0)
+var mask: texture_2d<f32>;
+
+// Darkness where the footprint is solid
+const STRENGTH: f32 = 0.55;
+// Blur radius in pixels
+const RADIUS: i32 = 3;
+
+@vertex
+fn vs_main(@builtin(vertex_index) index: u32) -> @builtin(position) vec4<f32> {
+    // One triangle covering the whole target
+    let uv = vec2<f32>(f32((index << 1u) & 2u), f32(index & 2u));
+    return vec4<f32>(uv * 2.0 - 1.0, 0.0, 1.0);
+}
+
+@fragment
+fn fs_main(@builtin(position) frag: vec4<f32>) -> @location(0) vec4<f32> {
+    let pos = vec2<i32>(frag.xy);
+    let size = vec2<i32>(textureDimensions(mask));
+
+    var sum = 0.0;
+    for (var y = -RADIUS; y <= RADIUS; y++) {
+        for (var x = -RADIUS; x <= RADIUS; x++) {
+            sum += textureLoad(mask, clamp(pos + vec2<i32>(x, y), vec2<i32>(0), size - 1), 0).r;
+        }
+    }
+    let taps = f32((2 * RADIUS + 1) * (2 * RADIUS + 1));
+    let shadow = sum / taps * STRENGTH;
+    if shadow <= 0.0 {
+        discard;
+    }
+
+    // Premultiplied black, darkening what's below
+    return vec4<f32>(0.0, 0.0, 0.0, shadow);
+}
@@ -0,0 +1,46 @@
+// Selection outline: an edge around the coverage mask of the selected
+// splats (see fs_mask in gaussian.wgsl), blended over the rendered scene
+
+@group(0) @binding(0)
+var mask: texture_2d<f32>;
+
+// Same orange as the selection tint, linear RGB
+const OUTLINE_COLOR: vec3<f32> = vec3<f32>(1.0, 0.55, 0.1);
+// Outline width in pixels
+const RADIUS: i32 = 2;
+
+@vertex
+fn vs_main(@builtin(vertex_index) index: u32) -> @builtin(position) vec4<f32> {
+    // One triangle covering the whole target
+    let uv = vec2<f32>(f32((index << 1u) & 2u), f32(index & 2u));
+    return vec4<f32>(uv * 2.0 - 1.0, 0.0, 1.0);
+}
+
+// How much of the pixel at `pos` the selection covers, firmed up so soft
+// splat edges don't blur the outline
+fn coverage(pos: vec2<i32>) -> f32 {
+    let size = vec2<i32>(textureDimensions(mask));
+    let raw = textureLoad(mask, clamp(pos, vec2<i32>(0), size - 1), 0).r;
+    return smoothstep(0.1, 0.4, raw);
+}
+
+@fragment
+fn fs_main(@builtin(position) frag: vec4<f32>) -> @location(0) vec4<f32> {
+    let pos = vec2<i32>(frag.xy);
+    let inside = coverage(pos);
+
+    var nearby = 0.0;
+    for (var y = -RADIUS; y <= RADIUS; y++) {
+        for (var x = -RADIUS; x <= RADIUS; x++) {
+            nearby = max(nearby, coverage(pos + vec2<i32>(x, y)));
+        }
+    }
+
+    // Drawn just outside the selection; clear inside it and away from it
+    let edge = clamp(nearby - inside, 0.0, 1.0);
+    if edge <= 0.0 {
+        discard;
+    }
+
+    return vec4<f32>(OUTLINE_COLOR * edge, edge);
+}
@@ -1,4 +1,4 @@
-use glam::{Mat4, Vec3};
+use glam::{Mat4, Vec2, Vec3};
 
 #[derive(Clone, Debug)]
 pub struct Camera {
@@ -33,9 +33,11 @@ impl Default for Camera {
 
 impl Camera {
     pub fn new(target: Vec3, distance: f32) -> Self {
-        let mut camera = Self::default();
-        camera.target = target;
-        camera.distance = distance;
+        let mut camera = Self {
+            target,
+            distance,
+            ..Self::default()
+        };
         camera.update_position();
         camera
     }
@@ -87,4 +89,63 @@ impl Camera {
     pub fn view_projection_matrix(&self) -> Mat4 {
         self.projection_matrix() * self.view_matrix()
     }
+
+    /// Where `point` shows in the viewport, from 0 to 1 with y down; None
+    /// when it's behind the near plane
+    pub fn to_viewport(&self, point: Vec3) -> Option<Vec2> {
+        let clip = self.view_projection_matrix() * point.extend(1.0);
+        (clip.w > self.near).then(|| Vec2::new(clip.x / clip.w * 0.5 + 0.5, 0.5 - clip.y / clip.w * 0.5))
+    }
+
+    /// World-space ray through a pixel of a viewport of the given size.
+    /// Returns (origin, normalized direction).
+    pub fn screen_ray(&self, x: f32, y: f32, width: f32, height: f32) -> (Vec3, Vec3) {
+        let ndc_x = 2.0 * x / width - 1.0;
+        let ndc_y = 1.0 - 2.0 * y / height;
+
+        let inv_view_proj = self.view_projection_matrix().inverse();
+        let near = inv_view_proj.project_point3(Vec3::new(ndc_x, ndc_y, 0.0));
+        let far = inv_view_proj.project_point3(Vec3::new(ndc_x, ndc_y, 1.0));
+
+        (near, (far - near).normalize())
+    }
+}
+
+/// Smoothly moves the camera target and distance to new values over a fixed duration
+#[derive(Clone, Debug)]
+pub struct CameraTransition {
+    from_target: Vec3,
+    to_target: Vec3,
+    from_distance: f32,
+    to_distance: f32,
+    elapsed: f32,
+    duration: f32,
+}
+
+impl CameraTransition {
+    pub fn new(camera: &Camera, target: Vec3, distance: f32, duration: f32) -> Self {
+        Self {
+            from_target: camera.target,
+            to_target: target,
+            from_distance: camera.distance,
+            to_distance: distance,
+            elapsed: 0.0,
+            duration: duration.max(f32::EPSILON),
+        }
+    }
+
+    /// Advance the transition by `dt` seconds and apply it to the camera.
+    /// Returns true once the transition has finished.
+    pub fn step(&mut self, camera: &mut Camera, dt: f32) -> bool {
+        self.elapsed = (self.elapsed + dt).min(self.duration);
+
+        let t = self.elapsed / self.duration;
+        let eased = t * t * (3.0 - 2.0 * t);
+
+        camera.target = self.from_target.lerp(self.to_target, eased);
+        camera.distance = self.from_distance + (self.to_distance - self.from_distance) * eased;
+        camera.update_position();
+
+        self.elapsed >= self.duration
+    }
 }
\ No newline at end of file
@@ -0,0 +1,147 @@
+use glam::Vec3;
+use crate::camera::Camera;
+
+/// Camera pose captured at a point in time
+#[derive(Clone, Debug, PartialEq)]
+pub struct CameraKeyframe {
+    pub time: f32,
+    pub target: Vec3,
+    pub distance: f32,
+    pub azimuth: f32,
+    pub elevation: f32,
+}
+
+impl CameraKeyframe {
+    pub fn from_camera(time: f32, camera: &Camera) -> Self {
+        Self {
+            time,
+            target: camera.target,
+            distance: camera.distance,
+            azimuth: camera.azimuth,
+            elevation: camera.elevation,
+        }
+    }
+
+    /// Apply this pose to a camera (lens settings are left untouched)
+    pub fn apply(&self, camera: &mut Camera) {
+        camera.target = self.target;
+        camera.distance = self.distance.max(0.1);
+        camera.azimuth = self.azimuth;
+        camera.elevation = self.elevation.clamp(-89.0, 89.0);
+        camera.update_position();
+    }
+}
+
+/// Keyframed camera path, interpolated with Catmull-Rom splines over the
+/// orbit parameters so the camera never cuts through the target
+#[derive(Clone, Debug, Default)]
+pub struct CameraPath {
+    keyframes: Vec<CameraKeyframe>,
+}
+
+impl CameraPath {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Capture the camera pose at `time`, replacing any keyframe already at that time
+    pub fn add_keyframe(&mut self, time: f32, camera: &Camera) {
+        let keyframe = CameraKeyframe::from_camera(time.max(0.0), camera);
+
+        match self.keyframes.iter().position(|k| k.time >= keyframe.time) {
+            Some(i) if self.keyframes[i].time == keyframe.time => self.keyframes[i] = keyframe,
+            Some(i) => self.keyframes.insert(i, keyframe),
+            None => self.keyframes.push(keyframe),
+        }
+    }
+
+    pub fn remove_keyframe(&mut self, index: usize) {
+        if index < self.keyframes.len() {
+            self.keyframes.remove(index);
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.keyframes.clear();
+    }
+
+    pub fn keyframes(&self) -> &[CameraKeyframe] {
+        &self.keyframes
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.keyframes.is_empty()
+    }
+
+    /// Time of the last keyframe
+    pub fn duration(&self) -> f32 {
+        self.keyframes.last().map_or(0.0, |k| k.time)
+    }
+
+    /// Keyframe azimuths, each moved by whole turns to the shortest arc from
+    /// the one before, so a path from 350° to 10° turns 20° rather than 340°
+    fn unwrapped_azimuths(&self) -> Vec<f32> {
+        let mut azimuths: Vec<f32> = Vec::with_capacity(self.keyframes.len());
+        for keyframe in &self.keyframes {
+            let azimuth = match azimuths.last() {
+                Some(&previous) => {
+                    let delta = (keyframe.azimuth - previous).rem_euclid(360.0);
+                    previous + if delta > 180.0 { delta - 360.0 } else { delta }
+                }
+                None => keyframe.azimuth,
+            };
+            azimuths.push(azimuth);
+        }
+        azimuths
+    }
+
+    /// Interpolated pose at `time`, clamped to the ends of the path
+    pub fn sample(&self, time: f32) -> Option<CameraKeyframe> {
+        let first = self.keyframes.first()?;
+        let last = self.keyframes.last()?;
+        let azimuths = self.unwrapped_azimuths();
+
+        if time <= first.time {
+            return Some(CameraKeyframe { time, ..first.clone() });
+        }
+        if time >= last.time {
+            return Some(CameraKeyframe { time, azimuth: azimuths[azimuths.len() - 1], ..last.clone() });
+        }
+
+        // Segment [i, i + 1] contains `time`
+        let i = self.keyframes.iter().rposition(|k| k.time <= time)?;
+        let k1 = &self.keyframes[i];
+        let k2 = &self.keyframes[i + 1];
+        let (i0, i3) = (i.saturating_sub(1), (i + 2).min(self.keyframes.len() - 1));
+        let k0 = &self.keyframes[i0];
+        let k3 = &self.keyframes[i3];
+
+        let t = (time - k1.time) / (k2.time - k1.time);
+
+        Some(CameraKeyframe {
+            time,
+            target: catmull_rom_vec3(k0.target, k1.target, k2.target, k3.target, t),
+            distance: catmull_rom(k0.distance, k1.distance, k2.distance, k3.distance, t),
+            azimuth: catmull_rom(azimuths[i0], azimuths[i], azimuths[i + 1], azimuths[i3], t),
+            elevation: catmull_rom(k0.elevation, k1.elevation, k2.elevation, k3.elevation, t),
+        })
+    }
+}
+
+fn catmull_rom(p0: f32, p1: f32, p2: f32, p3: f32, t: f32) -> f32 {
+    let t2 = t * t;
+    let t3 = t2 * t;
+
+    0.5 * ((2.0 * p1)
+        + (p2 - p0) * t
+        + (2.0 * p0 - 5.0 * p1 + 4.0 * p2 - p3) * t2
+        + (3.0 * p1 - p0 - 3.0 * p2 + p3) * t3)
+}
+
+fn catmull_rom_vec3(p0: Vec3, p1: Vec3, p2: Vec3, p3: Vec3, t: f32) -> Vec3 {
+    Vec3::new(
+        catmull_rom(p0.x, p1.x, p2.x, p3.x, t),
+        catmull_rom(p0.y, p1.y, p2.y, p3.y, t),
+        catmull_rom(p0.z, p1.z, p2.z, p3.z, t),
+    )
+}
\ No newline at end of file
@@ -0,0 +1,68 @@
+//! Clipping planes that cut away the splats on one side, for looking inside
+//! a generated object. Splats are kept or dropped whole, by their center.
+
+use glam::{Vec2, Vec3};
+use crate::camera::Camera;
+
+/// Planes the shader has room for
+pub const MAX_CLIP_PLANES: usize = 3;
+
+/// Cuts away the splats whose centers are in front of the plane, on the
+/// side `normal` points to
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ClipPlane {
+    /// Unit normal
+    pub normal: [f32; 3],
+    /// Distance of the plane from the origin along `normal`
+    pub offset: f32,
+}
+
+/// Screen position of a plane's gizmo, in viewport coordinates from 0 to 1
+/// with y down
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ClipHandle {
+    /// Where the plane passes closest to the scene center
+    pub origin: Vec2,
+    /// End of the normal arrow
+    pub tip: Vec2,
+    /// World length of the arrow, to turn drags along it into offsets
+    pub length: f32,
+}
+
+impl ClipPlane {
+    /// Plane across world axis `axis` (0 for x, 1 for y, 2 for z) at
+    /// `offset`, cutting away the positive side
+    pub fn axis(axis: usize, offset: f32) -> Self {
+        let mut normal = [0.0; 3];
+        normal[axis.min(2)] = 1.0;
+        Self { normal, offset }
+    }
+
+    /// Same plane, cutting away the other side
+    pub fn flipped(self) -> Self {
+        Self { normal: self.normal.map(|n| -n), offset: -self.offset }
+    }
+
+    /// Whether a splat centered at `point` is kept
+    pub fn keeps(&self, point: [f32; 3]) -> bool {
+        Vec3::from(self.normal).dot(Vec3::from(point)) <= self.offset
+    }
+
+    /// Normal and offset as the shader reads them
+    pub(crate) fn to_array(self) -> [f32; 4] {
+        let [x, y, z] = self.normal;
+        [x, y, z, self.offset]
+    }
+
+    /// Gizmo for the plane, anchored nearest `center` with an arrow `length`
+    /// long; None when the anchor is behind the camera
+    pub fn handle(&self, camera: &Camera, center: Vec3, length: f32) -> Option<ClipHandle> {
+        let normal = Vec3::from(self.normal);
+        let origin = center - (normal.dot(center) - self.offset) * normal;
+        Some(ClipHandle {
+            origin: camera.to_viewport(origin)?,
+            tip: camera.to_viewport(origin + normal * length)?,
+            length,
+        })
+    }
+}
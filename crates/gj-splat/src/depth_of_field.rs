@@ -0,0 +1,227 @@
+//! Depth-of-field post effect. The splats are drawn into an offscreen color
+//! target, along with a coverage-weighted view depth, and then blurred onto
+//! the output by how far each pixel is from the focus plane.
+
+/// Largest blur radius in pixels; keep in sync with depth_of_field.wgsl
+pub const MAX_COC: f32 = 16.0;
+
+/// Accumulated depth (red) and coverage (green), see `fs_depth`
+pub(crate) const DEPTH_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rg16Float;
+
+/// Lens settings for the blur
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DepthOfField {
+    /// Distance in front of the camera that stays sharp
+    pub focus_distance: f32,
+    /// Blur radius in pixels of things far behind the focus plane
+    pub aperture: f32,
+}
+
+impl Default for DepthOfField {
+    fn default() -> Self {
+        Self { focus_distance: 3.0, aperture: 6.0 }
+    }
+}
+
+impl DepthOfField {
+    /// Blur radius in pixels of a point `depth` in front of the camera
+    pub fn circle_of_confusion(&self, depth: f32) -> f32 {
+        (self.aperture * (depth - self.focus_distance).abs() / depth.max(0.001)).min(MAX_COC)
+    }
+}
+
+#[repr(C)]
+#[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable)]
+struct Params {
+    focus_distance: f32,
+    aperture: f32,
+    _padding: [f32; 2],
+}
+
+/// Offscreen targets sized to the viewport they were last drawn at
+pub(crate) struct DofTargets {
+    size: (u32, u32),
+    pub color_view: wgpu::TextureView,
+    pub depth_view: wgpu::TextureView,
+    bind_group: wgpu::BindGroup,
+}
+
+/// Pipeline and targets of the blur
+pub(crate) struct DofPass {
+    format: wgpu::TextureFormat,
+    pipeline: wgpu::RenderPipeline,
+    layout: wgpu::BindGroupLayout,
+    sampler: wgpu::Sampler,
+    params: wgpu::Buffer,
+    targets: Option<DofTargets>,
+}
+
+impl DofPass {
+    pub fn new(device: &wgpu::Device, format: wgpu::TextureFormat) -> Self {
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Depth of Field Shader"),
+            source: wgpu::ShaderSource::Wgsl(include_str!("../shaders/depth_of_field.wgsl").into()),
+        });
+
+        let texture_entry = |binding, filterable| wgpu::BindGroupLayoutEntry {
+            binding,
+            visibility: wgpu::ShaderStages::FRAGMENT,
+            ty: wgpu::BindingType::Texture {
+                sample_type: wgpu::TextureSampleType::Float { filterable },
+                view_dimension: wgpu::TextureViewDimension::D2,
+                multisampled: false,
+            },
+            count: None,
+        };
+        let layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("Depth of Field Bind Group Layout"),
+            entries: &[
+                texture_entry(0, true),
+                texture_entry(1, false),
+                wgpu::BindGroupLayoutEntry {
+                    binding: 2,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 3,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+            ],
+        });
+
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Depth of Field Pipeline Layout"),
+            bind_group_layouts: &[&layout],
+            push_constant_ranges: &[],
+        });
+        let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Depth of Field Pipeline"),
+            layout: Some(&pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                entry_point: Some("vs_main"),
+                compilation_options: Default::default(),
+                buffers: &[],
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                entry_point: Some("fs_main"),
+                compilation_options: Default::default(),
+                targets: &[Some(wgpu::ColorTargetState {
+                    format,
+                    blend: None,
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+            }),
+            primitive: wgpu::PrimitiveState::default(),
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState::default(),
+            multiview: None,
+            cache: None,
+        });
+
+        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            label: Some("Depth of Field Sampler"),
+            address_mode_u: wgpu::AddressMode::ClampToEdge,
+            address_mode_v: wgpu::AddressMode::ClampToEdge,
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            ..Default::default()
+        });
+        let params = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Depth of Field Params"),
+            size: std::mem::size_of::<Params>() as u64,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        Self { format, pipeline, layout, sampler, params, targets: None }
+    }
+
+    /// Targets for a `size` viewport, recreated when the size changed
+    pub fn targets(&mut self, device: &wgpu::Device, size: (u32, u32)) -> &DofTargets {
+        if self.targets.as_ref().is_none_or(|t| t.size != size) {
+            self.targets = Some(self.create_targets(device, size));
+        }
+        self.targets.as_ref().unwrap()
+    }
+
+    fn create_targets(&self, device: &wgpu::Device, size: (u32, u32)) -> DofTargets {
+        let create = |label, format| {
+            device.create_texture(&wgpu::TextureDescriptor {
+                label: Some(label),
+                size: wgpu::Extent3d {
+                    width: size.0.max(1),
+                    height: size.1.max(1),
+                    depth_or_array_layers: 1,
+                },
+                mip_level_count: 1,
+                sample_count: 1,
+                dimension: wgpu::TextureDimension::D2,
+                format,
+                usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::TEXTURE_BINDING,
+                view_formats: &[],
+            }).create_view(&wgpu::TextureViewDescriptor::default())
+        };
+        let color_view = create("Depth of Field Color", self.format);
+        let depth_view = create("Depth of Field Depth", DEPTH_FORMAT);
+
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("Depth of Field Bind Group"),
+            layout: &self.layout,
+            entries: &[
+                wgpu::BindGroupEntry { binding: 0, resource: wgpu::BindingResource::TextureView(&color_view) },
+                wgpu::BindGroupEntry { binding: 1, resource: wgpu::BindingResource::TextureView(&depth_view) },
+                wgpu::BindGroupEntry { binding: 2, resource: wgpu::BindingResource::Sampler(&self.sampler) },
+                wgpu::BindGroupEntry { binding: 3, resource: self.params.as_entire_binding() },
+            ],
+        });
+        DofTargets { size, color_view, depth_view, bind_group }
+    }
+
+    /// Blur the color target onto `view`
+    pub fn composite(
+        &self,
+        encoder: &mut wgpu::CommandEncoder,
+        queue: &wgpu::Queue,
+        view: &wgpu::TextureView,
+        settings: &DepthOfField,
+    ) {
+        let Some(targets) = &self.targets else {
+            return;
+        };
+        let params = Params {
+            focus_distance: settings.focus_distance,
+            aperture: settings.aperture,
+            _padding: [0.0; 2],
+        };
+        queue.write_buffer(&self.params, 0, bytemuck::bytes_of(&params));
+
+        let mut pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("Depth of Field Pass"),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view,
+                depth_slice: None,
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Load,
+                    store: wgpu::StoreOp::Store,
+                },
+            })],
+            depth_stencil_attachment: None,
+            timestamp_writes: None,
+            occlusion_query_set: None,
+        });
+        pass.set_pipeline(&self.pipeline);
+        pass.set_bind_group(0, &targets.bind_group, &[]);
+        pass.draw(0..3, 0..1);
+    }
+}
@@ -1,4 +1,12 @@
 pub mod camera;
+pub mod camera_path;
+pub mod clipping;
+pub mod depth_of_field;
+mod ground_shadow;
 pub mod renderer;
+pub mod selection;
+pub mod sort;
+pub mod stereo;
+#[cfg(test)]
 mod tests;
 
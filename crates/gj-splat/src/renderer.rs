@@ -1,6 +1,27 @@
+use half::f16;
+use rayon::prelude::*;
 use wgpu::util::DeviceExt;
+use gj_core::color_grading::ColorGrade;
 use gj_core::gaussian_cloud::GaussianCloud;
+use gj_core::heatmap::HeatmapQuantity;
 use crate::camera::Camera;
+use crate::clipping::{ClipPlane, MAX_CLIP_PLANES};
+use crate::depth_of_field::{self, DepthOfField, DofPass};
+use crate::ground_shadow::{self, ShadowPass};
+use crate::sort::DepthSorter;
+
+/// How far the view direction may turn before splats are re-sorted
+const SORT_THRESHOLD_DEGREES: f32 = 2.0;
+
+/// The built-in splat shader, used unless a custom one is set
+pub const SPLAT_SHADER: &str = include_str!("../shaders/gaussian.wgsl");
+
+/// Largest instance buffer; bigger clouds are split over several buffers
+const CHUNK_BYTES: u64 = 64 << 20;
+
+/// Most instance bytes written to the GPU per frame, so huge clouds stream
+/// in over several frames instead of stalling one
+const MAX_UPLOAD_BYTES_PER_FRAME: usize = 64 << 20;
 
 // Quad vertices for instanced rendering (4 corners of a billboard)
 const QUAD_VERTICES: &[[f32; 2]] = &[
@@ -12,45 +33,360 @@ const QUAD_VERTICES: &[[f32; 2]] = &[
 
 const QUAD_INDICES: &[u16] = &[0, 1, 2, 2, 1, 3];
 
+/// How splat attributes are stored in the instance buffer
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SplatPrecision {
+    /// Everything as f32, 64 bytes per splat
+    Full,
+    /// Position as f32, color as u8 and the rest as f16, 32 bytes per splat
+    #[default]
+    Compact,
+}
+
+/// What the splat colors show
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ColorMode {
+    /// The splats' own colors
+    #[default]
+    Albedo,
+    /// Estimated normals mapped from [-1, 1] to RGB
+    Normals,
+    /// A per-splat quantity, blue for low to red for high
+    Heatmap(HeatmapQuantity),
+}
+
+impl ColorMode {
+    /// Shading modes; the heatmaps are offered separately as diagnostics
+    pub const ALL: [Self; 2] = [Self::Albedo, Self::Normals];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Albedo => "Color",
+            Self::Normals => "Normals",
+            Self::Heatmap(quantity) => quantity.label(),
+        }
+    }
+}
+
 #[repr(C)]
 #[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable)]
 struct GaussianInstance {
     position: [f32; 3],
-    _padding1: f32,
-    color: [f32; 3],
-    opacity: f32,
-    scale: [f32; 3],
-    _padding2: f32,
+    _padding: f32,
+    /// rgb, a 1 when selected
+    color: [f32; 4],
+    /// xyz scale, w opacity
+    scale_opacity: [f32; 4],
     rotation: [f32; 4],
 }
 
+#[repr(C)]
+#[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable)]
+struct CompactGaussianInstance {
+    position: [f32; 3],
+    /// rgb, a 255 when selected
+    color: [u8; 4],
+    scale_opacity: [f16; 4],
+    rotation: [f16; 4],
+}
+
+const FULL_ATTRIBUTES: [wgpu::VertexAttribute; 4] = wgpu::vertex_attr_array![
+    1 => Float32x3, // position
+    2 => Float32x4, // color
+    3 => Float32x4, // scale, opacity
+    4 => Float32x4, // rotation
+];
+
+const COMPACT_ATTRIBUTES: [wgpu::VertexAttribute; 4] = wgpu::vertex_attr_array![
+    1 => Float32x3, // position
+    2 => Unorm8x4,  // color
+    3 => Float16x4, // scale, opacity
+    4 => Float16x4, // rotation
+];
+
+/// One instance buffer holding a contiguous range of the sorted splats
+struct InstanceChunk {
+    buffer: wgpu::Buffer,
+    /// Index of the first splat in this chunk
+    first: usize,
+    instances: u32,
+    /// Sort generation last written to the buffer; None until first uploaded
+    written: Option<u64>,
+}
+
+impl SplatPrecision {
+    fn instance_layout(self) -> wgpu::VertexBufferLayout<'static> {
+        match self {
+            Self::Full => wgpu::VertexBufferLayout {
+                array_stride: std::mem::size_of::<GaussianInstance>() as u64,
+                step_mode: wgpu::VertexStepMode::Instance,
+                attributes: &FULL_ATTRIBUTES,
+            },
+            Self::Compact => wgpu::VertexBufferLayout {
+                array_stride: std::mem::size_of::<CompactGaussianInstance>() as u64,
+                step_mode: wgpu::VertexStepMode::Instance,
+                attributes: &COMPACT_ATTRIBUTES,
+            },
+        }
+    }
+}
+
 #[repr(C)]
 #[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable)]
 struct Uniforms {
     view_proj: [[f32; 4]; 4],
     view: [[f32; 4]; 4],
     camera_pos: [f32; 3],
-    _padding1: f32,
+    /// Height of the lowest splat, where the ground shadow falls
+    ground_height: f32,
     viewport: [f32; 2],
     focal: [f32; 2],
+    /// See [`ColorGrade::to_array`]
+    color_grade: [f32; 4],
+    /// Normal and offset of each clipping plane; unused ones are all zero
+    clip_planes: [[f32; 4]; MAX_CLIP_PLANES],
+}
+
+impl Uniforms {
+    fn new(
+        camera: &Camera,
+        viewport_size: (u32, u32),
+        color_grade: &ColorGrade,
+        ground_height: f32,
+        clip_planes: [[f32; 4]; MAX_CLIP_PLANES],
+    ) -> Self {
+        // Calculate focal length from FOV
+        let fov_rad = camera.fov.to_radians();
+        let focal_y = viewport_size.1 as f32 / (2.0 * (fov_rad / 2.0).tan());
+        let focal_x = focal_y * camera.aspect_ratio;
+
+        Self {
+            view_proj: camera.view_projection_matrix().to_cols_array_2d(),
+            view: camera.view_matrix().to_cols_array_2d(),
+            camera_pos: camera.position.to_array(),
+            ground_height,
+            viewport: [viewport_size.0 as f32, viewport_size.1 as f32],
+            focal: [focal_x, focal_y],
+            color_grade: color_grade.to_array(),
+            clip_planes,
+        }
+    }
+}
+
+/// Dark gray clear color, linear RGB
+pub const DEFAULT_BACKGROUND: [f32; 3] = [0.1, 0.1, 0.1];
+
+/// Coverage of the selected splats, outlined over the scene
+const MASK_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::R8Unorm;
+
+/// What a draw of the splats produces
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SplatPass {
+    /// The splats themselves
+    Color,
+    /// Coverage of the selected splats, for the outline
+    SelectionMask,
+    /// View depth, for depth of field
+    Depth,
+    /// Footprint on the ground plane
+    GroundShadow,
+}
+
+impl SplatPass {
+    const ALL: [Self; 4] = [Self::Color, Self::SelectionMask, Self::Depth, Self::GroundShadow];
+
+    /// Vertex and fragment entry points in gaussian.wgsl
+    fn entry_points(self) -> (&'static str, &'static str) {
+        match self {
+            Self::Color => ("vs_main", "fs_main"),
+            Self::SelectionMask => ("vs_main", "fs_mask"),
+            Self::Depth => ("vs_main", "fs_depth"),
+            Self::GroundShadow => ("vs_shadow", "fs_shadow"),
+        }
+    }
+
+    fn label(self, precision: SplatPrecision) -> &'static str {
+        match (self, precision) {
+            (Self::Color, SplatPrecision::Full) => "Gaussian Pipeline",
+            (Self::Color, SplatPrecision::Compact) => "Compact Gaussian Pipeline",
+            (Self::SelectionMask, SplatPrecision::Full) => "Selection Mask Pipeline",
+            (Self::SelectionMask, SplatPrecision::Compact) => "Compact Selection Mask Pipeline",
+            (Self::Depth, SplatPrecision::Full) => "Splat Depth Pipeline",
+            (Self::Depth, SplatPrecision::Compact) => "Compact Splat Depth Pipeline",
+            (Self::GroundShadow, SplatPrecision::Full) => "Ground Shadow Mask Pipeline",
+            (Self::GroundShadow, SplatPrecision::Compact) => "Compact Ground Shadow Mask Pipeline",
+        }
+    }
+
+    /// Attachment drawn into, for a scene in `format`
+    fn target(self, format: wgpu::TextureFormat) -> wgpu::ColorTargetState {
+        // Premultiplied alpha, back to front
+        let over = wgpu::BlendComponent {
+            src_factor: wgpu::BlendFactor::One,
+            dst_factor: wgpu::BlendFactor::OneMinusSrcAlpha,
+            operation: wgpu::BlendOperation::Add,
+        };
+        // The strongest footprint over a pixel wins
+        let max = wgpu::BlendComponent {
+            src_factor: wgpu::BlendFactor::One,
+            dst_factor: wgpu::BlendFactor::One,
+            operation: wgpu::BlendOperation::Max,
+        };
+        let (format, blend) = match self {
+            Self::Color => (format, wgpu::BlendState { color: over, alpha: over }),
+            Self::SelectionMask => (MASK_FORMAT, wgpu::BlendState::ALPHA_BLENDING),
+            Self::Depth => (depth_of_field::DEPTH_FORMAT, wgpu::BlendState { color: over, alpha: over }),
+            Self::GroundShadow => (ground_shadow::MASK_FORMAT, wgpu::BlendState { color: max, alpha: max }),
+        };
+        wgpu::ColorTargetState {
+            format,
+            blend: Some(blend),
+            write_mask: wgpu::ColorWrites::ALL,
+        }
+    }
+}
+
+/// A splat pass's pipeline for each instance precision
+struct SplatPipelines {
+    full: wgpu::RenderPipeline,
+    compact: wgpu::RenderPipeline,
+}
+
+impl SplatPipelines {
+    fn new(
+        device: &wgpu::Device,
+        layout: &wgpu::PipelineLayout,
+        shader: &wgpu::ShaderModule,
+        pass: SplatPass,
+        format: wgpu::TextureFormat,
+    ) -> Self {
+        Self {
+            full: create_pipeline(device, layout, shader, SplatPrecision::Full, pass, pass.target(format)),
+            compact: create_pipeline(device, layout, shader, SplatPrecision::Compact, pass, pass.target(format)),
+        }
+    }
+
+    fn get(&self, precision: SplatPrecision) -> &wgpu::RenderPipeline {
+        match precision {
+            SplatPrecision::Full => &self.full,
+            SplatPrecision::Compact => &self.compact,
+        }
+    }
+}
+
+/// Every splat pass's pipelines, built from one shader module
+struct SplatShader {
+    color: SplatPipelines,
+    selection_mask: SplatPipelines,
+    depth: SplatPipelines,
+    ground_shadow: SplatPipelines,
+}
+
+impl SplatShader {
+    fn new(
+        device: &wgpu::Device,
+        layout: &wgpu::PipelineLayout,
+        source: &str,
+        format: wgpu::TextureFormat,
+    ) -> Self {
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Gaussian Shader"),
+            source: wgpu::ShaderSource::Wgsl(source.into()),
+        });
+        let pipelines = |pass| SplatPipelines::new(device, layout, &shader, pass, format);
+        Self {
+            color: pipelines(SplatPass::Color),
+            selection_mask: pipelines(SplatPass::SelectionMask),
+            depth: pipelines(SplatPass::Depth),
+            ground_shadow: pipelines(SplatPass::GroundShadow),
+        }
+    }
+}
+
+/// Check that `source` compiles and has every entry point the renderer
+/// draws with, returning the compiler's report otherwise
+pub fn check_shader(source: &str) -> Result<(), String> {
+    use wgpu::naga;
+
+    let module = naga::front::wgsl::parse_str(source).map_err(|e| e.emit_to_string(source))?;
+    naga::valid::Validator::new(naga::valid::ValidationFlags::all(), naga::valid::Capabilities::all())
+        .validate(&module)
+        .map_err(|e| e.emit_to_string(source))?;
+
+    for pass in SplatPass::ALL {
+        let (vertex, fragment) = pass.entry_points();
+        for (name, stage) in [(vertex, naga::ShaderStage::Vertex), (fragment, naga::ShaderStage::Fragment)] {
+            if !module.entry_points.iter().any(|e| e.name == name && e.stage == stage) {
+                return Err(format!("Missing {:?} entry point `{}`", stage, name));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Selection mask sized to the viewport it was last drawn at
+struct OutlineTarget {
+    size: (u32, u32),
+    view: wgpu::TextureView,
+    bind_group: wgpu::BindGroup,
 }
 
 pub struct GaussianRenderer {
     device: wgpu::Device,
     queue: wgpu::Queue,
-    pipeline: wgpu::RenderPipeline,
+    format: wgpu::TextureFormat,
+    pipeline_layout: wgpu::PipelineLayout,
+    shader: SplatShader,
+    // Selection outline: the selected splats' coverage is drawn into a mask,
+    // then edge-detected over the scene
+    outline_pipeline: wgpu::RenderPipeline,
+    outline_layout: wgpu::BindGroupLayout,
+    outline_target: Option<OutlineTarget>,
+    /// Any loaded splat is selected
+    has_selection: bool,
+    dof_pass: DofPass,
+    /// Lens blur, off when None
+    depth_of_field: Option<DepthOfField>,
+    shadow_pass: ShadowPass,
+    ground_shadow: bool,
+    /// Lowest splat center of the loaded cloud
+    ground_height: f32,
+    clip_planes: Vec<ClipPlane>,
+    /// Precision used by the next [`GaussianRenderer::load_gaussians`]
+    precision: SplatPrecision,
+    /// Color mode used by the next [`GaussianRenderer::load_gaussians`]
+    color_mode: ColorMode,
+    /// Applied in the shader, so changing it needs no reload
+    color_grade: ColorGrade,
+    /// Clear color behind the splats, linear RGB
+    background: [f32; 3],
 
     quad_vertex_buffer: wgpu::Buffer,
     quad_index_buffer: wgpu::Buffer,
-    instance_buffer: Option<wgpu::Buffer>,
+    /// Instance buffers of the loaded cloud and the precision they were built with
+    chunks: Vec<InstanceChunk>,
+    chunk_precision: SplatPrecision,
+    /// Next chunk to check for a pending upload, so uploads go round-robin
+    upload_cursor: usize,
+
+    // CPU copies of the loaded splats, in load order, for depth sorting
+    splat_positions: Vec<[f32; 3]>,
+    /// Cloud index of each loaded splat
+    splat_indices: Vec<usize>,
+    instance_data: Vec<u8>,
+    instance_stride: usize,
+    sorted_data: Vec<u8>,
+    /// Bumped on every sort; chunks holding an older generation are stale
+    sort_generation: u64,
+    sorter: DepthSorter,
 
     uniform_buffer: wgpu::Buffer,
     bind_group: wgpu::BindGroup,
 
     num_gaussians: u32,
 
-    // Cache last camera state to avoid redundant updates
-    last_view_proj: Option<[[f32; 4]; 4]>,
+    // Cache last uniforms to avoid redundant updates
+    last_uniforms: Option<Uniforms>,
 }
 
 impl GaussianRenderer {
@@ -59,13 +395,6 @@ impl GaussianRenderer {
         queue: wgpu::Queue,
         format: wgpu::TextureFormat,
     ) -> Self {
-        // Use the simplified, faster shader
-        let shader_source = include_str!("../shaders/gaussian.wgsl");
-        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
-            label: Some("Gaussian Shader"),
-            source: wgpu::ShaderSource::Wgsl(shader_source.into()),
-        });
-
         // Create quad buffers
         let quad_vertex_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
             label: Some("Quad Vertex Buffer"),
@@ -115,124 +444,160 @@ impl GaussianRenderer {
             push_constant_ranges: &[],
         });
 
-        let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
-            label: Some("Gaussian Pipeline"),
-            layout: Some(&pipeline_layout),
-            vertex: wgpu::VertexState {
-                module: &shader,
-                entry_point: Some("vs_main"),
-                compilation_options: Default::default(),
-                buffers: &[
-                    // Quad vertices (per-vertex)
-                    wgpu::VertexBufferLayout {
-                        array_stride: std::mem::size_of::<[f32; 2]>() as u64,
-                        step_mode: wgpu::VertexStepMode::Vertex,
-                        attributes: &[wgpu::VertexAttribute {
-                            format: wgpu::VertexFormat::Float32x2,
-                            offset: 0,
-                            shader_location: 0,
-                        }],
-                    },
-                    // Gaussian instances (per-instance)
-                    wgpu::VertexBufferLayout {
-                        array_stride: std::mem::size_of::<GaussianInstance>() as u64,
-                        step_mode: wgpu::VertexStepMode::Instance,
-                        attributes: &[
-                            wgpu::VertexAttribute {
-                                format: wgpu::VertexFormat::Float32x3,
-                                offset: 0,
-                                shader_location: 1, // position
-                            },
-                            wgpu::VertexAttribute {
-                                format: wgpu::VertexFormat::Float32,
-                                offset: 12,
-                                shader_location: 2, // _padding1
-                            },
-                            wgpu::VertexAttribute {
-                                format: wgpu::VertexFormat::Float32x3,
-                                offset: 16,
-                                shader_location: 3, // color
-                            },
-                            wgpu::VertexAttribute {
-                                format: wgpu::VertexFormat::Float32,
-                                offset: 28,
-                                shader_location: 4, // opacity
-                            },
-                            wgpu::VertexAttribute {
-                                format: wgpu::VertexFormat::Float32x3,
-                                offset: 32,
-                                shader_location: 5, // scale
-                            },
-                            wgpu::VertexAttribute {
-                                format: wgpu::VertexFormat::Float32,
-                                offset: 44,
-                                shader_location: 6, // _padding2
-                            },
-                            wgpu::VertexAttribute {
-                                format: wgpu::VertexFormat::Float32x4,
-                                offset: 48,
-                                shader_location: 7, // rotation
-                            },
-                        ],
-                    },
-                ],
-            },
-            fragment: Some(wgpu::FragmentState {
-                module: &shader,
-                entry_point: Some("fs_main"),
-                compilation_options: Default::default(),
-                targets: &[Some(wgpu::ColorTargetState {
-                    format,
-                    blend: Some(wgpu::BlendState {
-                        color: wgpu::BlendComponent {
-                            src_factor: wgpu::BlendFactor::One,
-                            dst_factor: wgpu::BlendFactor::OneMinusSrcAlpha,
-                            operation: wgpu::BlendOperation::Add,
-                        },
-                        alpha: wgpu::BlendComponent {
-                            src_factor: wgpu::BlendFactor::One,
-                            dst_factor: wgpu::BlendFactor::OneMinusSrcAlpha,
-                            operation: wgpu::BlendOperation::Add,
-                        },
-                    }),
-                    write_mask: wgpu::ColorWrites::ALL,
-                })],
-            }),
-            primitive: wgpu::PrimitiveState {
-                topology: wgpu::PrimitiveTopology::TriangleList,
-                front_face: wgpu::FrontFace::Ccw,
-                cull_mode: None, // Don't cull for splats
-                ..Default::default()
-            },
-            depth_stencil: Some(wgpu::DepthStencilState {
-                format: wgpu::TextureFormat::Depth32Float,
-                depth_write_enabled: false, // Splats use alpha blending, not depth
-                depth_compare: wgpu::CompareFunction::Less,
-                stencil: wgpu::StencilState::default(),
-                bias: wgpu::DepthBiasState::default(),
-            }),
-            multisample: wgpu::MultisampleState::default(),
-            multiview: None,
-            cache: None,
+        let shader = SplatShader::new(&device, &pipeline_layout, SPLAT_SHADER, format);
+        let dof_pass = DofPass::new(&device, format);
+        let shadow_pass = ShadowPass::new(&device, format);
+
+        let outline_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("Outline Bind Group Layout"),
+            entries: &[wgpu::BindGroupLayoutEntry {
+                binding: 0,
+                visibility: wgpu::ShaderStages::FRAGMENT,
+                ty: wgpu::BindingType::Texture {
+                    sample_type: wgpu::TextureSampleType::Float { filterable: false },
+                    view_dimension: wgpu::TextureViewDimension::D2,
+                    multisampled: false,
+                },
+                count: None,
+            }],
         });
+        let outline_pipeline = create_outline_pipeline(&device, &outline_layout, SplatPass::Color.target(format));
 
         Self {
             device,
             queue,
-            pipeline,
+            format,
+            pipeline_layout,
+            shader,
+            outline_pipeline,
+            outline_layout,
+            outline_target: None,
+            has_selection: false,
+            dof_pass,
+            depth_of_field: None,
+            shadow_pass,
+            ground_shadow: false,
+            ground_height: 0.0,
+            clip_planes: Vec::new(),
+            precision: SplatPrecision::default(),
+            color_mode: ColorMode::default(),
+            color_grade: ColorGrade::default(),
+            background: DEFAULT_BACKGROUND,
             quad_vertex_buffer,
             quad_index_buffer,
-            instance_buffer: None,
+            chunks: Vec::new(),
+            chunk_precision: SplatPrecision::default(),
+            upload_cursor: 0,
+            splat_positions: Vec::new(),
+            splat_indices: Vec::new(),
+            instance_data: Vec::new(),
+            instance_stride: 0,
+            sorted_data: Vec::new(),
+            sort_generation: 0,
+            sorter: DepthSorter::new(SORT_THRESHOLD_DEGREES),
             uniform_buffer,
             bind_group,
             num_gaussians: 0,
-            last_view_proj: None,
+            last_uniforms: None,
         }
     }
 
+    pub fn precision(&self) -> SplatPrecision {
+        self.precision
+    }
+
+    /// Choose the buffer precision; takes effect on the next load
+    pub fn set_precision(&mut self, precision: SplatPrecision) {
+        self.precision = precision;
+    }
+
+    pub fn color_mode(&self) -> ColorMode {
+        self.color_mode
+    }
+
+    /// Choose what the splat colors show; takes effect on the next load
+    pub fn set_color_mode(&mut self, mode: ColorMode) {
+        self.color_mode = mode;
+    }
+
+    pub fn background(&self) -> [f32; 3] {
+        self.background
+    }
+
+    /// Set the clear color, in linear RGB; takes effect on the next frame
+    pub fn set_background(&mut self, background: [f32; 3]) {
+        self.background = background;
+    }
+
+    pub fn color_grade(&self) -> ColorGrade {
+        self.color_grade
+    }
+
+    /// Adjust colors live; takes effect on the next frame
+    pub fn set_color_grade(&mut self, grade: ColorGrade) {
+        self.color_grade = grade;
+    }
+
+    pub fn depth_of_field(&self) -> Option<DepthOfField> {
+        self.depth_of_field
+    }
+
+    /// Blur what's out of focus, or stop with None; takes effect on the next frame
+    pub fn set_depth_of_field(&mut self, dof: Option<DepthOfField>) {
+        self.depth_of_field = dof;
+        self.last_uniforms = None;
+    }
+
+    /// Draw the splats with `source` in place of the built-in shader, or go
+    /// back to the built-in one. A shader that doesn't compile is reported
+    /// and the current one kept.
+    pub async fn set_shader(&mut self, source: Option<&str>) -> Result<(), String> {
+        let source = source.unwrap_or(SPLAT_SHADER);
+        check_shader(source)?;
+
+        // Bindings or vertex inputs that don't match only show up here
+        self.device.push_error_scope(wgpu::ErrorFilter::Validation);
+        let shader = SplatShader::new(&self.device, &self.pipeline_layout, source, self.format);
+        if let Some(e) = self.device.pop_error_scope().await {
+            return Err(e.to_string());
+        }
+
+        self.shader = shader;
+        self.last_uniforms = None;
+        Ok(())
+    }
+
+    pub fn clip_planes(&self) -> &[ClipPlane] {
+        &self.clip_planes
+    }
+
+    /// Cut the splats with up to [`MAX_CLIP_PLANES`] planes; extra planes are ignored
+    pub fn set_clip_planes(&mut self, planes: &[ClipPlane]) {
+        self.clip_planes = planes.iter().take(MAX_CLIP_PLANES).copied().collect();
+        self.last_uniforms = None;
+    }
+
+    fn uniforms(&self, camera: &Camera, viewport_size: (u32, u32)) -> Uniforms {
+        let mut clip_planes = [[0.0; 4]; MAX_CLIP_PLANES];
+        for (slot, plane) in clip_planes.iter_mut().zip(&self.clip_planes) {
+            *slot = plane.to_array();
+        }
+        Uniforms::new(camera, viewport_size, &self.color_grade, self.ground_height, clip_planes)
+    }
+
+    pub fn ground_shadow(&self) -> bool {
+        self.ground_shadow
+    }
+
+    /// Shade the ground under the scene; takes effect on the next frame
+    pub fn set_ground_shadow(&mut self, enabled: bool) {
+        self.ground_shadow = enabled;
+        self.last_uniforms = None;
+    }
+
     pub fn load_gaussians(&mut self, cloud: &GaussianCloud) {
         // Much more permissive filtering
-        let instances: Vec<GaussianInstance> = (0..cloud.count)
+        let kept: Vec<usize> = (0..cloud.count)
             .filter(|&i| {
                 // Only filter out obviously bad data
                 cloud.positions[i][0].is_finite() &&
@@ -240,35 +605,244 @@ impl GaussianRenderer {
                     cloud.positions[i][2].is_finite() &&
                     cloud.opacity[i] > 0.001  // Very low threshold
             })
-            .map(|i| GaussianInstance {
-                position: cloud.positions[i],
-                _padding1: 0.0,
-                color: cloud.colors[i],
-                opacity: cloud.opacity[i] * 1.5,  // Boost opacity for visibility
-                scale: [
-                    cloud.scales[i][0] * 3.0,  // Scale up significantly
-                    cloud.scales[i][1] * 3.0,
-                    cloud.scales[i][2] * 3.0,
-                ],
-                _padding2: 0.0,
-                rotation: cloud.rotations[i],
-            })
             .collect();
 
-        self.instance_buffer = Some(
-            self.device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
-                label: Some("Instance Buffer"),
-                contents: bytemuck::cast_slice(&instances),
-                usage: wgpu::BufferUsages::VERTEX,
+        let colors = match self.color_mode {
+            ColorMode::Albedo => None,
+            ColorMode::Normals => Some(cloud.normals().into_iter().map(|n| n.map(|n| n * 0.5 + 0.5)).collect()),
+            ColorMode::Heatmap(quantity) => Some(cloud.heatmap_colors(quantity)),
+        };
+        let color = |i: usize| colors.as_ref().map_or(cloud.colors[i], |colors: &Vec<[f32; 3]>| colors[i]);
+
+        let contents: Vec<u8> = match self.precision {
+            SplatPrecision::Full => {
+                let instances: Vec<GaussianInstance> = kept.iter()
+                    .map(|&i| {
+                        let [r, g, b] = color(i);
+                        GaussianInstance {
+                            position: cloud.positions[i],
+                            _padding: 0.0,
+                            color: [r, g, b, 0.0],
+                            scale_opacity: scale_opacity(cloud, i),
+                            rotation: cloud.rotations[i],
+                        }
+                    })
+                    .collect();
+                bytemuck::cast_slice(&instances).to_vec()
+            }
+            SplatPrecision::Compact => {
+                let instances: Vec<CompactGaussianInstance> = kept.iter()
+                    .map(|&i| {
+                        let [r, g, b] = color(i).map(|c| (c.clamp(0.0, 1.0) * 255.0).round() as u8);
+                        CompactGaussianInstance {
+                            position: cloud.positions[i],
+                            color: [r, g, b, 0],
+                            scale_opacity: scale_opacity(cloud, i).map(f16::from_f32),
+                            rotation: cloud.rotations[i].map(f16::from_f32),
+                        }
+                    })
+                    .collect();
+                bytemuck::cast_slice(&instances).to_vec()
+            }
+        };
+
+        let stride = self.precision.instance_layout().array_stride;
+        let chunk_bytes = CHUNK_BYTES.min(self.device.limits().max_buffer_size);
+        let per_chunk = (chunk_bytes / stride).max(1) as usize;
+
+        // Buffers are created empty; the data streams in from render()
+        self.chunks = (0..kept.len())
+            .step_by(per_chunk)
+            .map(|first| {
+                let instances = per_chunk.min(kept.len() - first);
+                InstanceChunk {
+                    buffer: self.device.create_buffer(&wgpu::BufferDescriptor {
+                        label: Some("Instance Buffer"),
+                        size: instances as u64 * stride,
+                        usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
+                        mapped_at_creation: false,
+                    }),
+                    first,
+                    instances: instances as u32,
+                    written: None,
+                }
             })
-        );
+            .collect();
+        self.chunk_precision = self.precision;
+        self.upload_cursor = 0;
 
-        self.num_gaussians = instances.len() as u32;
-        self.last_view_proj = None;
+        self.splat_positions = kept.iter().map(|&i| cloud.positions[i]).collect();
+        self.ground_height = self.splat_positions.iter().map(|p| p[1]).reduce(f32::min).unwrap_or(0.0);
+        self.splat_indices = kept;
+        self.instance_data = contents;
+        self.instance_stride = stride as usize;
+        self.sorted_data.clear();
+        self.sorter.invalidate();
 
-        println!("Loaded {} / {} gaussians ({:.1}% kept)",
-                 instances.len(), cloud.count,
-                 100.0 * instances.len() as f32 / cloud.count.max(1) as f32);
+        self.num_gaussians = self.splat_indices.len() as u32;
+        self.has_selection = false;
+        self.last_uniforms = None;
+
+        log::info!("Loaded {} / {} gaussians ({:.1}% kept), {:.1} MiB of {:?} instance data",
+                   self.num_gaussians, cloud.count,
+                   100.0 * self.num_gaussians as f32 / cloud.count.max(1) as f32,
+                   self.instance_data.len() as f32 / (1024.0 * 1024.0), self.precision);
+    }
+
+    /// Highlight and outline the splats whose cloud index is set in
+    /// `selected`; indices past its end are unselected. Kept until the next load.
+    pub fn set_selection(&mut self, selected: &[bool]) {
+        self.has_selection = self.splat_indices.iter().any(|&i| selected.get(i).copied().unwrap_or(false));
+
+        let stride = self.instance_stride;
+        let flag_offset = match self.chunk_precision {
+            SplatPrecision::Full => std::mem::offset_of!(GaussianInstance, color) + 3 * size_of::<f32>(),
+            SplatPrecision::Compact => std::mem::offset_of!(CompactGaussianInstance, color) + 3,
+        };
+
+        for (k, &i) in self.splat_indices.iter().enumerate() {
+            let flag = selected.get(i).copied().unwrap_or(false);
+            let at = k * stride + flag_offset;
+            match self.chunk_precision {
+                SplatPrecision::Full => {
+                    let value: f32 = if flag { 1.0 } else { 0.0 };
+                    self.instance_data[at..at + 4].copy_from_slice(&value.to_ne_bytes());
+                }
+                SplatPrecision::Compact => self.instance_data[at] = if flag { 255 } else { 0 },
+            }
+        }
+
+        // Re-sort to copy the flags into the sorted data and upload it again
+        self.sorter.invalidate();
+        self.last_uniforms = None;
+    }
+
+    /// Rewrite the colors and opacities of the splats at cloud indices
+    /// `changed`, after they were edited in place, without reloading the
+    /// rest. Only for albedo colors: the other color modes derive theirs
+    /// from the whole cloud and need [`Self::load_gaussians`].
+    pub fn update_splats(&mut self, cloud: &GaussianCloud, changed: &[usize]) {
+        let stride = self.instance_stride;
+        for &i in changed {
+            // Splats dropped on load have nothing to update
+            let Ok(k) = self.splat_indices.binary_search(&i) else {
+                continue;
+            };
+            let bytes = &mut self.instance_data[k * stride..(k + 1) * stride];
+            let [r, g, b] = cloud.colors[i];
+
+            // The selection flag in the color's last channel is kept
+            match self.chunk_precision {
+                SplatPrecision::Full => {
+                    let mut instance: GaussianInstance = bytemuck::pod_read_unaligned(bytes);
+                    instance.color = [r, g, b, instance.color[3]];
+                    instance.scale_opacity = scale_opacity(cloud, i);
+                    bytes.copy_from_slice(bytemuck::bytes_of(&instance));
+                }
+                SplatPrecision::Compact => {
+                    let mut instance: CompactGaussianInstance = bytemuck::pod_read_unaligned(bytes);
+                    let [r, g, b] = [r, g, b].map(|c| (c.clamp(0.0, 1.0) * 255.0).round() as u8);
+                    instance.color = [r, g, b, instance.color[3]];
+                    instance.scale_opacity = scale_opacity(cloud, i).map(f16::from_f32);
+                    bytes.copy_from_slice(bytemuck::bytes_of(&instance));
+                }
+            }
+        }
+
+        // Re-sort to copy the edits into the sorted data and upload it again
+        self.sorter.invalidate();
+        self.last_uniforms = None;
+    }
+
+    /// Fraction of the loaded cloud on the GPU, None once it's all there
+    pub fn upload_progress(&self) -> Option<f32> {
+        let uploaded: u32 = self.chunks.iter()
+            .filter(|c| c.written.is_some())
+            .map(|c| c.instances)
+            .sum();
+
+        (uploaded < self.num_gaussians).then(|| uploaded as f32 / self.num_gaussians as f32)
+    }
+
+    /// Whether uploads are still pending and more frames are needed to finish them
+    pub fn is_uploading(&self) -> bool {
+        self.chunks.iter().any(|c| c.written != Some(self.sort_generation))
+    }
+
+    /// Sort for `camera` and upload everything still pending, ignoring the
+    /// per-frame budget. For one-off renders that must show the whole cloud.
+    pub fn finish_uploads(&mut self, camera: &Camera) {
+        self.sort_for(camera);
+        self.upload_chunks(usize::MAX);
+    }
+
+    /// Re-sort the instances back to front if the view direction turned
+    /// enough since the last sort; otherwise the previous order is reused
+    fn sort_for(&mut self, camera: &Camera) {
+        let view_dir = (camera.target - camera.position).normalize_or_zero();
+        if view_dir == glam::Vec3::ZERO || !self.sorter.needs_sort(view_dir) {
+            return;
+        }
+
+        let order = self.sorter.sort(&self.splat_positions, view_dir);
+        let stride = self.instance_stride;
+
+        self.sorted_data.resize(self.instance_data.len(), 0);
+        self.sorted_data
+            .par_chunks_exact_mut(stride)
+            .zip(order.par_iter())
+            .for_each(|(dst, &src)| {
+                let src = src as usize * stride;
+                dst.copy_from_slice(&self.instance_data[src..src + stride]);
+            });
+
+        self.sort_generation += 1;
+    }
+
+    /// Write stale chunks from the sorted data, up to the per-frame budget.
+    /// While a re-sort streams in, chunks from the previous order keep being
+    /// drawn; a small turn barely changes which splats each chunk holds.
+    fn upload_chunks(&mut self, max_bytes: usize) {
+        // Nothing to write until the first sort of this cloud
+        if self.sorted_data.len() != self.instance_data.len() {
+            return;
+        }
+
+        let mut budget = max_bytes;
+        let count = self.chunks.len();
+
+        for _ in 0..count {
+            let chunk = &mut self.chunks[self.upload_cursor];
+            let start = chunk.first * self.instance_stride;
+            let len = chunk.instances as usize * self.instance_stride;
+
+            if chunk.written != Some(self.sort_generation) {
+                // Always make progress, even if one chunk exceeds the budget
+                if len > budget && budget < max_bytes {
+                    break;
+                }
+                self.queue.write_buffer(&chunk.buffer, 0, &self.sorted_data[start..start + len]);
+                chunk.written = Some(self.sort_generation);
+                budget = budget.saturating_sub(len);
+            }
+
+            self.upload_cursor = (self.upload_cursor + 1) % count;
+            if budget == 0 {
+                break;
+            }
+        }
+    }
+
+    /// Whether drawing `camera` at `viewport_size` would change the image
+    /// from the last frame rendered
+    pub fn needs_redraw(&self, camera: &Camera, viewport_size: (u32, u32)) -> bool {
+        if self.num_gaussians == 0 {
+            return false;
+        }
+
+        let uniforms = self.uniforms(camera, viewport_size);
+        self.is_uploading()
+            || self.last_uniforms.is_none_or(|last| bytemuck::bytes_of(&last) != bytemuck::bytes_of(&uniforms))
     }
 
     pub fn render(
@@ -284,43 +858,47 @@ impl GaussianRenderer {
             return;
         }
 
-        // Calculate focal length from FOV
-        let fov_rad = camera.fov.to_radians();
-        let focal_y = viewport_size.1 as f32 / (2.0 * (fov_rad / 2.0).tan());
-        let focal_x = focal_y * camera.aspect_ratio;
+        self.sort_for(camera);
+        self.upload_chunks(MAX_UPLOAD_BYTES_PER_FRAME);
 
-        let view_proj = camera.view_projection_matrix().to_cols_array_2d();
+        let uniforms = self.uniforms(camera, viewport_size);
 
-        // Only update uniforms if camera actually changed
-        let needs_update = self.last_view_proj.as_ref() != Some(&view_proj);
+        // Only update uniforms if the camera or viewport actually changed
+        let needs_update = self.last_uniforms
+            .is_none_or(|last| bytemuck::bytes_of(&last) != bytemuck::bytes_of(&uniforms));
 
         if needs_update {
-            let uniforms = Uniforms {
-                view_proj,
-                view: camera.view_matrix().to_cols_array_2d(),
-                camera_pos: camera.position.to_array(),
-                _padding1: 0.0,
-                viewport: [viewport_size.0 as f32, viewport_size.1 as f32],
-                focal: [focal_x, focal_y],
-            };
-
             self.queue.write_buffer(&self.uniform_buffer, 0, bytemuck::cast_slice(&[uniforms]));
-            self.last_view_proj = Some(view_proj);
+            self.last_uniforms = Some(uniforms);
         }
 
+        // With depth of field the splats go to an offscreen target first
+        let dof = self.depth_of_field.filter(|d| d.aperture > 0.0);
+        let dof_view = dof.map(|_| self.dof_pass.targets(&self.device, viewport_size).color_view.clone());
+        let scene_view = dof_view.as_ref().unwrap_or(view);
+
+        let background = wgpu::Color {
+            r: self.background[0] as f64,
+            g: self.background[1] as f64,
+            b: self.background[2] as f64,
+            a: 1.0,
+        };
+        // The shadow pass clears the background itself, under the shadow
+        let load = if self.ground_shadow {
+            self.render_ground_shadow(encoder, scene_view, depth_view, viewport_size, background);
+            wgpu::LoadOp::Load
+        } else {
+            wgpu::LoadOp::Clear(background)
+        };
+
         let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
             label: Some("Gaussian Render Pass"),
             color_attachments: &[Some(wgpu::RenderPassColorAttachment {
-                view,
+                view: scene_view,
                 depth_slice: None,
                 resolve_target: None,
                 ops: wgpu::Operations {
-                    load: wgpu::LoadOp::Clear(wgpu::Color {
-                        r: 0.1,
-                        g: 0.1,
-                        b: 0.1,
-                        a: 1.0,
-                    }),
+                    load,
                     store: wgpu::StoreOp::Store,
                 },
             })],
@@ -336,16 +914,290 @@ impl GaussianRenderer {
             occlusion_query_set: None,
         });
 
-        render_pass.set_pipeline(&self.pipeline);
+        self.draw_splats(&mut render_pass, self.shader.color.get(self.chunk_precision));
+        drop(render_pass);
+
+        if let Some(dof) = dof {
+            self.render_depth(encoder, depth_view, viewport_size);
+            self.dof_pass.composite(encoder, &self.queue, view, &dof);
+        }
+
+        if self.has_selection {
+            self.render_outline(encoder, view, depth_view, viewport_size);
+        }
+    }
+
+    fn draw_splats(&self, render_pass: &mut wgpu::RenderPass<'_>, pipeline: &wgpu::RenderPipeline) {
+        render_pass.set_pipeline(pipeline);
         render_pass.set_bind_group(0, &self.bind_group, &[]);
         render_pass.set_vertex_buffer(0, self.quad_vertex_buffer.slice(..));
+        render_pass.set_index_buffer(self.quad_index_buffer.slice(..), wgpu::IndexFormat::Uint16);
+
+        // Chunks hold consecutive ranges of the sorted splats, so drawing
+        // them in order keeps the whole cloud back to front
+        for chunk in self.chunks.iter().filter(|c| c.written.is_some()) {
+            render_pass.set_vertex_buffer(1, chunk.buffer.slice(..));
+
+            // Draw instanced quads - 6 indices per quad, one instance per splat
+            render_pass.draw_indexed(0..6, 0, 0..chunk.instances);
+        }
+    }
+
+    /// Draw the splats' footprint into the shadow mask, then clear `view` to
+    /// `background` with the shadow on it
+    fn render_ground_shadow(
+        &mut self,
+        encoder: &mut wgpu::CommandEncoder,
+        view: &wgpu::TextureView,
+        depth_view: &wgpu::TextureView,
+        viewport_size: (u32, u32),
+        background: wgpu::Color,
+    ) {
+        let mask = self.shadow_pass.mask_view(&self.device, viewport_size).clone();
+        let mut mask_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("Ground Shadow Mask Pass"),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view: &mask,
+                depth_slice: None,
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Clear(wgpu::Color::TRANSPARENT),
+                    store: wgpu::StoreOp::Store,
+                },
+            })],
+            depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment {
+                view: depth_view,
+                depth_ops: Some(wgpu::Operations {
+                    load: wgpu::LoadOp::Clear(1.0),
+                    store: wgpu::StoreOp::Store,
+                }),
+                stencil_ops: None,
+            }),
+            timestamp_writes: None,
+            occlusion_query_set: None,
+        });
+        self.draw_splats(&mut mask_pass, self.shader.ground_shadow.get(self.chunk_precision));
+        drop(mask_pass);
 
-        if let Some(ref instance_buffer) = self.instance_buffer {
-            render_pass.set_vertex_buffer(1, instance_buffer.slice(..));
-            render_pass.set_index_buffer(self.quad_index_buffer.slice(..), wgpu::IndexFormat::Uint16);
+        self.shadow_pass.composite(encoder, view, background);
+    }
 
-            // Draw instanced quads - 6 indices per quad, num_gaussians instances
-            render_pass.draw_indexed(0..6, 0, 0..self.num_gaussians);
+    /// Draw the splats' view depth for the depth of field blur
+    fn render_depth(&mut self, encoder: &mut wgpu::CommandEncoder, depth_view: &wgpu::TextureView, viewport_size: (u32, u32)) {
+        let target = self.dof_pass.targets(&self.device, viewport_size).depth_view.clone();
+        let mut depth_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("Splat Depth Pass"),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view: &target,
+                depth_slice: None,
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Clear(wgpu::Color::TRANSPARENT),
+                    store: wgpu::StoreOp::Store,
+                },
+            })],
+            depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment {
+                view: depth_view,
+                depth_ops: Some(wgpu::Operations {
+                    load: wgpu::LoadOp::Load,
+                    store: wgpu::StoreOp::Store,
+                }),
+                stencil_ops: None,
+            }),
+            timestamp_writes: None,
+            occlusion_query_set: None,
+        });
+        self.draw_splats(&mut depth_pass, self.shader.depth.get(self.chunk_precision));
+    }
+
+    /// Draw the selected splats' coverage into the mask, then outline it on `view`
+    fn render_outline(
+        &mut self,
+        encoder: &mut wgpu::CommandEncoder,
+        view: &wgpu::TextureView,
+        depth_view: &wgpu::TextureView,
+        viewport_size: (u32, u32),
+    ) {
+        if self.outline_target.as_ref().is_none_or(|t| t.size != viewport_size) {
+            self.outline_target = Some(self.create_outline_target(viewport_size));
         }
+        let Some(target) = &self.outline_target else {
+            return;
+        };
+
+        let mut mask_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("Selection Mask Pass"),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view: &target.view,
+                depth_slice: None,
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Clear(wgpu::Color::TRANSPARENT),
+                    store: wgpu::StoreOp::Store,
+                },
+            })],
+            // Splats don't write depth, so the cleared buffer is just reused
+            depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment {
+                view: depth_view,
+                depth_ops: Some(wgpu::Operations {
+                    load: wgpu::LoadOp::Load,
+                    store: wgpu::StoreOp::Store,
+                }),
+                stencil_ops: None,
+            }),
+            timestamp_writes: None,
+            occlusion_query_set: None,
+        });
+        self.draw_splats(&mut mask_pass, self.shader.selection_mask.get(self.chunk_precision));
+        drop(mask_pass);
+
+        let mut outline_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("Selection Outline Pass"),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view,
+                depth_slice: None,
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Load,
+                    store: wgpu::StoreOp::Store,
+                },
+            })],
+            depth_stencil_attachment: None,
+            timestamp_writes: None,
+            occlusion_query_set: None,
+        });
+        outline_pass.set_pipeline(&self.outline_pipeline);
+        outline_pass.set_bind_group(0, &target.bind_group, &[]);
+        outline_pass.draw(0..3, 0..1);
+    }
+
+    fn create_outline_target(&self, size: (u32, u32)) -> OutlineTarget {
+        let texture = self.device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("Selection Mask"),
+            size: wgpu::Extent3d {
+                width: size.0.max(1),
+                height: size.1.max(1),
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: MASK_FORMAT,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::TEXTURE_BINDING,
+            view_formats: &[],
+        });
+        let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+        let bind_group = self.device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("Outline Bind Group"),
+            layout: &self.outline_layout,
+            entries: &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: wgpu::BindingResource::TextureView(&view),
+            }],
+        });
+        OutlineTarget { size, view, bind_group }
     }
-}
\ No newline at end of file
+}
+
+/// Pipeline drawing `precision` instances for `pass`
+/// Instance scale and opacity of splat `i`, scaled up significantly and
+/// with opacity boosted for visibility
+fn scale_opacity(cloud: &GaussianCloud, i: usize) -> [f32; 4] {
+    let s = cloud.scales[i];
+    [s[0] * 3.0, s[1] * 3.0, s[2] * 3.0, cloud.opacity[i] * 1.5]
+}
+
+fn create_pipeline(
+    device: &wgpu::Device,
+    layout: &wgpu::PipelineLayout,
+    shader: &wgpu::ShaderModule,
+    precision: SplatPrecision,
+    pass: SplatPass,
+    target: wgpu::ColorTargetState,
+) -> wgpu::RenderPipeline {
+    let (vertex, fragment) = pass.entry_points();
+    device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+        label: Some(pass.label(precision)),
+        layout: Some(layout),
+        vertex: wgpu::VertexState {
+            module: shader,
+            entry_point: Some(vertex),
+            compilation_options: Default::default(),
+            buffers: &[
+                // Quad vertices (per-vertex)
+                wgpu::VertexBufferLayout {
+                    array_stride: std::mem::size_of::<[f32; 2]>() as u64,
+                    step_mode: wgpu::VertexStepMode::Vertex,
+                    attributes: &[wgpu::VertexAttribute {
+                        format: wgpu::VertexFormat::Float32x2,
+                        offset: 0,
+                        shader_location: 0,
+                    }],
+                },
+                // Gaussian instances (per-instance)
+                precision.instance_layout(),
+            ],
+        },
+        fragment: Some(wgpu::FragmentState {
+            module: shader,
+            entry_point: Some(fragment),
+            compilation_options: Default::default(),
+            targets: &[Some(target)],
+        }),
+        primitive: wgpu::PrimitiveState {
+            topology: wgpu::PrimitiveTopology::TriangleList,
+            front_face: wgpu::FrontFace::Ccw,
+            cull_mode: None, // Don't cull for splats
+            ..Default::default()
+        },
+        depth_stencil: Some(wgpu::DepthStencilState {
+            format: wgpu::TextureFormat::Depth32Float,
+            depth_write_enabled: false, // Splats use alpha blending, not depth
+            depth_compare: wgpu::CompareFunction::Less,
+            stencil: wgpu::StencilState::default(),
+            bias: wgpu::DepthBiasState::default(),
+        }),
+        multisample: wgpu::MultisampleState::default(),
+        multiview: None,
+        cache: None,
+    })
+}
+
+/// Full-screen pass outlining the selection mask over the scene
+fn create_outline_pipeline(
+    device: &wgpu::Device,
+    mask_layout: &wgpu::BindGroupLayout,
+    target: wgpu::ColorTargetState,
+) -> wgpu::RenderPipeline {
+    let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+        label: Some("Outline Shader"),
+        source: wgpu::ShaderSource::Wgsl(include_str!("../shaders/outline.wgsl").into()),
+    });
+    let layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+        label: Some("Outline Pipeline Layout"),
+        bind_group_layouts: &[mask_layout],
+        push_constant_ranges: &[],
+    });
+
+    device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+        label: Some("Outline Pipeline"),
+        layout: Some(&layout),
+        vertex: wgpu::VertexState {
+            module: &shader,
+            entry_point: Some("vs_main"),
+            compilation_options: Default::default(),
+            buffers: &[],
+        },
+        fragment: Some(wgpu::FragmentState {
+            module: &shader,
+            entry_point: Some("fs_main"),
+            compilation_options: Default::default(),
+            targets: &[Some(target)],
+        }),
+        primitive: wgpu::PrimitiveState::default(),
+        depth_stencil: None,
+        multisample: wgpu::MultisampleState::default(),
+        multiview: None,
+        cache: None,
+    })
+}
@@ -0,0 +1,91 @@
+//! Picking splats by a rectangle or lasso drawn in screen space, or under a
+//! paint brush.
+
+use glam::{Mat4, Vec2, Vec3, Vec4};
+use gj_core::paint::PaintMode;
+use rayon::prelude::*;
+use crate::camera::Camera;
+
+/// Region of the viewport, in physical pixels
+#[derive(Debug, Clone, PartialEq)]
+pub enum SelectionShape {
+    Rect { min: Vec2, max: Vec2 },
+    /// Closed polygon through the points
+    Lasso(Vec<Vec2>),
+}
+
+impl SelectionShape {
+    /// Rectangle spanned by two corners in any order
+    pub fn rect(a: Vec2, b: Vec2) -> Self {
+        Self::Rect { min: a.min(b), max: a.max(b) }
+    }
+
+    pub fn contains(&self, point: Vec2) -> bool {
+        match self {
+            Self::Rect { min, max } => point.cmpge(*min).all() && point.cmple(*max).all(),
+            Self::Lasso(points) => {
+                // Even-odd rule: count edges crossed by a ray to +x
+                let mut inside = false;
+                for (i, a) in points.iter().enumerate() {
+                    let b = points[(i + 1) % points.len()];
+                    if (a.y > point.y) != (b.y > point.y)
+                        && point.x < a.x + (point.y - a.y) / (b.y - a.y) * (b.x - a.x)
+                    {
+                        inside = !inside;
+                    }
+                }
+                inside
+            }
+        }
+    }
+}
+
+fn project(view_proj: &Mat4, point: [f32; 3], viewport: Vec2) -> Option<Vec2> {
+    let clip = *view_proj * Vec4::from((Vec3::from_array(point), 1.0));
+    if clip.w <= f32::EPSILON {
+        return None;
+    }
+
+    let ndc = clip.truncate() / clip.w;
+    Some(Vec2::new(ndc.x + 1.0, 1.0 - ndc.y) * 0.5 * viewport)
+}
+
+/// Indices of the splats whose centers project into `shape` for `camera`
+/// looking at a viewport of `viewport` pixels. Splats behind the camera are
+/// never selected.
+pub fn select_in_screen(positions: &[[f32; 3]], camera: &Camera, viewport: Vec2, shape: &SelectionShape) -> Vec<usize> {
+    let view_proj = camera.view_projection_matrix();
+
+    positions.par_iter()
+        .enumerate()
+        .filter(|&(_, &p)| project(&view_proj, p, viewport).is_some_and(|screen| shape.contains(screen)))
+        .map(|(i, _)| i)
+        .collect()
+}
+
+/// Circular brush painting the splats under the cursor, strongest at its
+/// center and fading out to its edge
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PaintBrush {
+    pub mode: PaintMode,
+    /// In physical pixels
+    pub radius: f32,
+}
+
+impl PaintBrush {
+    /// Splats whose centers project within the brush at `center`, with
+    /// their falloff from 1 at the center to 0 at the edge. Like selection,
+    /// the brush reaches through to splats hidden behind others.
+    pub fn weights(&self, positions: &[[f32; 3]], camera: &Camera, viewport: Vec2, center: Vec2) -> Vec<(usize, f32)> {
+        let view_proj = camera.view_projection_matrix();
+        let radius = self.radius.max(1.0);
+
+        positions.par_iter()
+            .enumerate()
+            .filter_map(|(i, &p)| {
+                let t = project(&view_proj, p, viewport)?.distance(center) / radius;
+                (t < 1.0).then(|| (i, (1.0 - t * t).powi(2)))
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,43 @@
+use glam::Vec3;
+use rayon::prelude::*;
+
+/// Back-to-front ordering of splats for alpha blending.
+///
+/// Sorting by depth along the view direction gives the same order from any
+/// camera position, so the order only goes stale when the view direction
+/// turns. The sorter remembers the direction of its last sort and asks for a
+/// new one once the camera has turned further than the threshold.
+pub struct DepthSorter {
+    /// Cosine of the largest view direction change that keeps the last order
+    threshold_cos: f32,
+    last_dir: Option<Vec3>,
+}
+
+impl DepthSorter {
+    pub fn new(threshold_degrees: f32) -> Self {
+        Self {
+            threshold_cos: threshold_degrees.to_radians().cos(),
+            last_dir: None,
+        }
+    }
+
+    /// Whether the last order is stale for a camera looking along `view_dir` (normalized)
+    pub fn needs_sort(&self, view_dir: Vec3) -> bool {
+        self.last_dir.is_none_or(|last| last.dot(view_dir) < self.threshold_cos)
+    }
+
+    /// Forget the last order, e.g. because the splats changed
+    pub fn invalidate(&mut self) {
+        self.last_dir = None;
+    }
+
+    /// Indices of `positions` from farthest to nearest along `view_dir`
+    pub fn sort(&mut self, positions: &[[f32; 3]], view_dir: Vec3) -> Vec<u32> {
+        let mut order: Vec<u32> = (0..positions.len() as u32).collect();
+        let depth = |i: u32| view_dir.dot(Vec3::from_array(positions[i as usize]));
+        order.par_sort_unstable_by(|&a, &b| depth(b).total_cmp(&depth(a)));
+
+        self.last_dir = Some(view_dir);
+        order
+    }
+}
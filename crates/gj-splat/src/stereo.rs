@@ -0,0 +1,33 @@
+//! Per-eye cameras for stereo viewing. Both eyes look along the same
+//! direction, side by side, so with a world unit taken as one meter and the
+//! default eye separation the scene appears at its true scale.
+
+use crate::camera::Camera;
+
+/// Average distance between the pupils, in meters
+pub const DEFAULT_IPD: f32 = 0.063;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Eye {
+    Left,
+    Right,
+}
+
+impl Eye {
+    pub const BOTH: [Self; 2] = [Self::Left, Self::Right];
+
+    /// `camera` moved sideways by half of `ipd` to this eye's side
+    pub fn camera(self, camera: &Camera, ipd: f32) -> Camera {
+        let side = match self {
+            Self::Left => -0.5,
+            Self::Right => 0.5,
+        };
+        let forward = (camera.target - camera.position).normalize();
+        let offset = forward.cross(camera.up).normalize() * ipd * side;
+        Camera {
+            position: camera.position + offset,
+            target: camera.target + offset,
+            ..camera.clone()
+        }
+    }
+}
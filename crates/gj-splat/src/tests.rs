@@ -1,19 +1,208 @@
-#[cfg(test)]
-mod tests {
-    use crate::camera::Camera;
-    use super::*;
-
-    #[test]
-    fn test_camera_creation() {
-        let camera = Camera::default();
-        assert_eq!(camera.distance, 3.0);
-    }
-
-    #[test]
-    fn test_camera_rotation() {
-        let mut camera = Camera::default();
-        camera.rotate(45.0, 30.0);
-        assert_eq!(camera.azimuth, 45.0);
-        assert_eq!(camera.elevation, 30.0);
-    }
-}
\ No newline at end of file
+use glam::Vec3;
+use gj_core::paint::PaintMode;
+use crate::camera::{Camera, CameraTransition};
+use crate::camera_path::CameraPath;
+use crate::clipping::ClipPlane;
+use crate::depth_of_field::{DepthOfField, MAX_COC};
+use crate::renderer::{self, SPLAT_SHADER};
+use crate::selection::{self, PaintBrush, SelectionShape};
+use crate::sort::DepthSorter;
+use crate::stereo::{Eye, DEFAULT_IPD};
+
+/// Camera 3 units out on +Z, looking straight at the origin
+fn front_camera() -> Camera {
+    let mut camera = Camera { distance: 3.0, elevation: 0.0, ..Camera::default() };
+    camera.update_position();
+    camera
+}
+
+#[test]
+fn test_camera_creation() {
+    let camera = Camera::default();
+    assert_eq!(camera.distance, 2.0);
+    assert_eq!(camera.elevation, 20.0);
+}
+
+#[test]
+fn test_camera_rotation() {
+    let mut camera = Camera::default();
+    camera.rotate(45.0, 30.0);
+    assert_eq!(camera.azimuth, 45.0);
+    assert_eq!(camera.elevation, 50.0);
+
+    // Elevation stops short of the poles
+    camera.rotate(0.0, 90.0);
+    assert_eq!(camera.elevation, 89.0);
+}
+
+#[test]
+fn test_screen_ray_through_center() {
+    let camera = front_camera();
+    let (origin, dir) = camera.screen_ray(50.0, 50.0, 100.0, 100.0);
+    assert!((dir - Vec3::new(0.0, 0.0, -1.0)).length() < 1e-4);
+    assert!(origin.z > 2.0 && origin.z < 3.0);
+}
+
+#[test]
+fn test_camera_transition() {
+    let mut camera = Camera::default();
+    let mut transition = CameraTransition::new(&camera, Vec3::new(1.0, 0.0, 0.0), 5.0, 0.5);
+
+    assert!(!transition.step(&mut camera, 0.25));
+    assert!(camera.target.x > 0.0 && camera.target.x < 1.0);
+
+    assert!(transition.step(&mut camera, 0.25));
+    assert_eq!(camera.target, Vec3::new(1.0, 0.0, 0.0));
+    assert_eq!(camera.distance, 5.0);
+}
+
+#[test]
+fn test_camera_path_sampling() {
+    let mut camera = Camera::default();
+    let mut path = CameraPath::new();
+
+    path.add_keyframe(2.0, &camera);
+    camera.rotate(90.0, 0.0);
+    path.add_keyframe(0.0, &camera);
+
+    assert_eq!(path.keyframes().len(), 2);
+    assert_eq!(path.keyframes()[0].azimuth, 90.0);
+    assert_eq!(path.duration(), 2.0);
+
+    assert_eq!(path.sample(-1.0).unwrap().azimuth, 90.0);
+    assert_eq!(path.sample(5.0).unwrap().azimuth, 0.0);
+
+    let mid = path.sample(1.0).unwrap();
+    assert!(mid.azimuth > 0.0 && mid.azimuth < 90.0);
+}
+
+#[test]
+fn test_camera_path_azimuth_wraps() {
+    let mut camera = Camera { azimuth: 350.0, ..Camera::default() };
+    let mut path = CameraPath::new();
+    path.add_keyframe(0.0, &camera);
+    camera.azimuth = 10.0;
+    path.add_keyframe(2.0, &camera);
+    camera.azimuth = -330.0;
+    path.add_keyframe(4.0, &camera);
+
+    // Through 0°, not back around through 180°
+    let mid = path.sample(1.0).unwrap().azimuth;
+    assert!(mid > 350.0 && mid < 370.0, "{mid}");
+    assert!((path.sample(2.0).unwrap().azimuth - 370.0).abs() < 1e-3);
+    let mid = path.sample(3.0).unwrap().azimuth;
+    assert!(mid > 370.0 && mid < 390.0, "{mid}");
+    assert!((path.sample(5.0).unwrap().azimuth - 390.0).abs() < 1e-3);
+}
+
+#[test]
+fn test_depth_sort_back_to_front() {
+    let positions = [[0.0, 0.0, -1.0], [0.0, 0.0, -5.0], [0.0, 0.0, -3.0]];
+    let mut sorter = DepthSorter::new(2.0);
+
+    let order = sorter.sort(&positions, Vec3::NEG_Z);
+    assert_eq!(order, vec![1, 2, 0]);
+}
+
+#[test]
+fn test_depth_sort_cached_for_small_turns() {
+    let mut sorter = DepthSorter::new(2.0);
+    assert!(sorter.needs_sort(Vec3::NEG_Z));
+
+    sorter.sort(&[[0.0; 3]], Vec3::NEG_Z);
+    let slight = glam::Quat::from_rotation_y(1.0f32.to_radians()) * Vec3::NEG_Z;
+    let large = glam::Quat::from_rotation_y(5.0f32.to_radians()) * Vec3::NEG_Z;
+    assert!(!sorter.needs_sort(slight));
+    assert!(sorter.needs_sort(large));
+
+    sorter.invalidate();
+    assert!(sorter.needs_sort(Vec3::NEG_Z));
+}
+#[test]
+fn test_select_in_screen() {
+    let camera = front_camera();
+    let viewport = glam::Vec2::new(160.0, 90.0);
+    let center = viewport / 2.0;
+    // Centered, right of center, and behind the camera
+    let positions = [[0.0, 0.0, 0.0], [1.0, 0.0, 0.0], [0.0, 0.0, 5.0]];
+
+    let rect = SelectionShape::rect(center + 5.0, center - 5.0);
+    assert_eq!(selection::select_in_screen(&positions, &camera, viewport, &rect), vec![0]);
+
+    let all = SelectionShape::rect(glam::Vec2::ZERO, viewport);
+    assert_eq!(selection::select_in_screen(&positions, &camera, viewport, &all), vec![0, 1]);
+
+    let triangle = SelectionShape::Lasso(vec![
+        center + glam::Vec2::new(10.0, -40.0),
+        center + glam::Vec2::new(80.0, 0.0),
+        center + glam::Vec2::new(10.0, 40.0),
+    ]);
+    assert_eq!(selection::select_in_screen(&positions, &camera, viewport, &triangle), vec![1]);
+}
+
+#[test]
+fn test_paint_brush_weights() {
+    let camera = front_camera();
+    let viewport = glam::Vec2::new(160.0, 90.0);
+    let center = viewport / 2.0;
+    let positions = [[0.0, 0.0, 0.0], [0.05, 0.0, 0.0], [1.0, 0.0, 0.0], [0.0, 0.0, 5.0]];
+
+    let brush = PaintBrush { mode: PaintMode::Opacity { factor: 0.5 }, radius: 20.0 };
+    let weights = brush.weights(&positions, &camera, viewport, center);
+    assert_eq!(weights.len(), 2);
+    assert_eq!(weights[0], (0, 1.0));
+    assert_eq!(weights[1].0, 1);
+    assert!(weights[1].1 > 0.0 && weights[1].1 < 1.0);
+}
+
+#[test]
+fn test_circle_of_confusion() {
+    let dof = DepthOfField { focus_distance: 2.0, aperture: 8.0 };
+    assert_eq!(dof.circle_of_confusion(2.0), 0.0);
+    // Far behind the focus plane the blur approaches the aperture
+    assert!((dof.circle_of_confusion(1000.0) - 8.0).abs() < 0.1);
+    // Close to the camera it grows quickly, up to the cap
+    assert!(dof.circle_of_confusion(1.0) > dof.circle_of_confusion(4.0));
+    assert_eq!(dof.circle_of_confusion(0.01), MAX_COC);
+}
+
+#[test]
+fn test_check_shader() {
+    assert_eq!(renderer::check_shader(SPLAT_SHADER), Ok(()));
+
+    let err = renderer::check_shader("fn broken( {").unwrap_err();
+    assert!(err.contains("error"), "{err}");
+
+    // A kernel that leaves out a pass the renderer draws is refused
+    let without_shadow = SPLAT_SHADER.replace("fn fs_shadow", "fn fs_shadow_unused");
+    let err = renderer::check_shader(&without_shadow).unwrap_err();
+    assert!(err.contains("fs_shadow"), "{err}");
+}
+
+#[test]
+fn test_clip_plane() {
+    let plane = ClipPlane::axis(0, 0.5);
+    assert!(plane.keeps([0.2, 5.0, 0.0]));
+    assert!(!plane.keeps([0.8, 0.0, 0.0]));
+    let flipped = plane.flipped();
+    assert!(!flipped.keeps([0.2, 5.0, 0.0]));
+    assert!(flipped.keeps([0.8, 0.0, 0.0]));
+
+    // The gizmo sits where the plane crosses the view axis, pointing right
+    let camera = front_camera();
+    let handle = plane.handle(&camera, Vec3::ZERO, 0.5).unwrap();
+    assert!((handle.origin.y - 0.5).abs() < 1e-5);
+    assert!(handle.origin.x > 0.5 && handle.tip.x > handle.origin.x);
+    assert!(ClipPlane::axis(2, 5.0).handle(&camera, Vec3::ZERO, 0.5).is_none());
+}
+
+#[test]
+fn test_eye_cameras() {
+    let camera = Camera::default();
+    let left = Eye::Left.camera(&camera, DEFAULT_IPD);
+    let right = Eye::Right.camera(&camera, DEFAULT_IPD);
+    assert!((right.position - left.position - Vec3::X * DEFAULT_IPD).length() < 1e-6);
+    assert!((left.position + right.position - camera.position * 2.0).length() < 1e-6);
+    // Parallel axes, not toed in
+    assert!(((right.target - right.position) - (camera.target - camera.position)).length() < 1e-6);
+}
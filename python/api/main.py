@@ -1,10 +1,11 @@
 """
 FastAPI service for job submission and status
 """
-from fastapi import FastAPI, HTTPException
-from fastapi.responses import JSONResponse
+from fastapi import Depends, FastAPI, Header, HTTPException, Request
+from fastapi.responses import FileResponse, JSONResponse, Response
 from pydantic import BaseModel, Field
-from typing import Optional
+from typing import Any, Dict, List, Optional
+import hmac
 import sys
 from pathlib import Path
 
@@ -12,16 +13,77 @@ from pathlib import Path
 sys.path.insert(0, str(Path(__file__).parent))
 
 from shared.celery_app import celery_app
-from shared.config import OUTPUT_DIR
+from shared.config import OUTPUT_DIR, AVAILABLE_MODELS, API_TOKEN, CAPTURE_PHOTOS, MODEL_PARAMS, MULTIVIEW_MODELS, MODEL_PRESETS, MODEL_WEIGHTS, PROTOCOL_VERSION, SSL_CERTFILE, SSL_KEYFILE
+from shared.job_log import append_log, read_log
+from shared.model_params import resolve_params
+from shared.job_preview import read_preview, store_preview
+from shared.job_registry import mark_submitted, is_known
+from shared.model_weights import QUEUED, list_weights, set_state, get_state
+from shared.uploads import UnsupportedImage, UploadTooLarge, save_upload, upload_path
+from shared.worker_gpu import list_gpus, smallest_gpu_mb
 
 app = FastAPI(title="Genjutsu 3D Generation API")
 
 
+def require_token(authorization: Optional[str] = Header(default=None)):
+    """Reject requests without the configured bearer token, if there is one"""
+    if API_TOKEN is None:
+        return
+    if not hmac.compare_digest(authorization or "", f"Bearer {API_TOKEN}"):
+        raise HTTPException(
+            status_code=401,
+            detail="Missing or wrong API token",
+            headers={"WWW-Authenticate": "Bearer"}
+        )
+
+
+# Everything but the service banner and health check
+authorized = [Depends(require_token)]
+
+
+class CaptureCamera(BaseModel):
+    to_object: List[List[float]] = Field(..., min_length=4, max_length=4, description="Camera-to-object transform, row-major, OpenGL axes, the cameras a mean distance of 1 from the object")
+    fov_y: float = Field(..., gt=0.0, lt=180.0, description="Vertical field of view in degrees of the photo's centered square")
+
+
+class CaptureView(BaseModel):
+    upload: str = Field(..., description="Upload id of the photo, from POST /uploads")
+    azimuth: float = Field(default=0.0, description="Degrees around the object it was taken from, 0 facing its front and 90 its right side")
+    elevation: float = Field(default=0.0, ge=-90.0, le=90.0, description="Degrees above the object's middle it was taken from")
+    camera: Optional[CaptureCamera] = Field(default=None, description="Where exactly it was taken, e.g. solved by COLMAP; used over azimuth and elevation")
+
+
 class GenerateRequest(BaseModel):
     prompt: str = Field(..., description="Text description of 3D object")
     model: str = Field(default="shap_e", description="Model to use")
     guidance_scale: float = Field(default=15.0, ge=1.0, le=30.0)
     num_inference_steps: int = Field(default=64, ge=16, le=256)
+    seed: Optional[int] = Field(default=None, description="Fixed seed for reproducible results")
+    output_dir: Optional[str] = Field(default=None, description="Directory to write the result to, OUTPUT_DIR when unset")
+    image: Optional[str] = Field(default=None, description="Upload id of a conditioning image, from POST /uploads")
+    capture: List[CaptureView] = Field(default_factory=list, description="Photos of a real object with where they were taken from, for the capture multiview model")
+    params: Dict[str, Any] = Field(default_factory=dict, description="Values of the model's parameters from /models, by name; these win over guidance_scale and num_inference_steps")
+    preset: Optional[str] = Field(default=None, description="Preset from /models to start from, e.g. draft; params win over it")
+    device: Optional[int] = Field(default=None, ge=0, description="Index of the worker GPU to run on, see /health; the current one when unset")
+
+
+class RedecodeRequest(BaseModel):
+    latent: str = Field(..., description="File name of a kept latent, from an earlier result's latent_path")
+    model: str = Field(default="shap_e", description="Model that sampled the latent")
+    mesh_resolution: int = Field(default=128, ge=32, le=512, description="Cells along each side of the mesh extraction grid")
+    splat_scale: float = Field(default=1.0, gt=0.0, le=10.0, description="Multiplier on the splat sizes")
+    output_dir: Optional[str] = Field(default=None, description="Directory to write the result to, OUTPUT_DIR when unset")
+    device: Optional[int] = Field(default=None, ge=0, description="Index of the worker GPU to run on, see /health; the current one when unset")
+
+
+class RefineRequest(BaseModel):
+    source: str = Field(..., description="File name of an earlier job's result to refine")
+    prompt: str = Field(..., description="Text description the result is refined towards")
+    iterations: int = Field(default=500, ge=10, le=5000, description="Optimization steps")
+    guidance_scale: float = Field(default=100.0, ge=1.0, le=200.0, description="Classifier-free guidance of the diffusion prior")
+    seed: Optional[int] = Field(default=None, description="Fixed seed for reproducible results")
+    output_dir: Optional[str] = Field(default=None, description="Directory to write the result to, OUTPUT_DIR when unset")
+    device: Optional[int] = Field(default=None, ge=0, description="Index of the worker GPU to run on, see /health; the current one when unset")
 
 
 class JobResponse(BaseModel):
@@ -32,7 +94,7 @@ class JobResponse(BaseModel):
 
 class JobStatusResponse(BaseModel):
     job_id: str
-    status: str  # PENDING, STARTED, SUCCESS, FAILURE, RETRY
+    status: str  # PENDING, STARTED, SUCCESS, FAILURE, RETRY, UNKNOWN
     progress: Optional[float] = None
     message: Optional[str] = None
     result: Optional[dict] = None
@@ -44,6 +106,7 @@ async def root():
     return {
         "service": "Genjutsu 3D Generation API",
         "version": "2.0",
+        "protocol": PROTOCOL_VERSION,
         "docs": "/docs"
     }
 
@@ -62,15 +125,22 @@ async def health():
     stats = celery_app.control.inspect().stats()
     active_workers = len(stats) if stats else 0
 
+    try:
+        gpus = list_gpus()
+    except Exception:
+        gpus = []
+
     return {
         "status": "healthy" if redis_status == "connected" else "degraded",
+        "protocol": PROTOCOL_VERSION,
         "redis": redis_status,
         "workers": active_workers,
+        "gpus": gpus,
         "output_dir": str(OUTPUT_DIR)
     }
 
 
-@app.get("/workers")
+@app.get("/workers", dependencies=authorized)
 async def list_workers():
     """List active Celery workers"""
     stats = celery_app.control.inspect().stats()
@@ -82,13 +152,103 @@ async def list_workers():
     }
 
 
-@app.post("/generate", response_model=JobResponse)
+@app.get("/models", dependencies=authorized)
+async def list_models():
+    """
+    List the model ids that can be passed to /generate with the schema of
+    each one's `params` and its presets, the generators an LGM job can
+    draw its views with, and the GPU memory in MiB of the smallest worker
+    (null until a GPU worker has started). Clients check `protocol` here
+    before submitting anything.
+    """
+    try:
+        vram_mb = smallest_gpu_mb()
+    except Exception:
+        vram_mb = None
+    return {
+        "models": AVAILABLE_MODELS,
+        "params": {model: MODEL_PARAMS.get(model, []) for model in AVAILABLE_MODELS},
+        "presets": {model: MODEL_PRESETS.get(model, {}) for model in AVAILABLE_MODELS},
+        "multiview": MULTIVIEW_MODELS,
+        "vram_mb": vram_mb,
+        "protocol": PROTOCOL_VERSION
+    }
+
+
+@app.get("/weights", dependencies=authorized)
+async def list_model_weights():
+    """
+    Weights of each model the workers fetch: download status (missing,
+    queued, downloading, ready, removing, failed, or unknown until a worker
+    has started), size on disk and download progress
+    """
+    try:
+        return {"weights": list_weights()}
+    except Exception as e:
+        raise HTTPException(status_code=500, detail=str(e))
+
+
+def _queue_weights_task(task_name: str, model_id: str, message: str) -> JobResponse:
+    if model_id not in MODEL_WEIGHTS:
+        raise HTTPException(status_code=404, detail=f"Unknown model '{model_id}'")
+
+    try:
+        task = celery_app.send_task(task_name, args=[model_id])
+        mark_submitted(task.id)
+        state = get_state(model_id) or {}
+        set_state(model_id, QUEUED, state.get('disk_bytes'))
+        return JobResponse(job_id=task.id, status="submitted", message=message)
+
+    except Exception as e:
+        raise HTTPException(status_code=500, detail=str(e))
+
+
+@app.post("/weights/{model_id}/download", response_model=JobResponse, dependencies=authorized)
+async def download_model_weights(model_id: str):
+    """
+    Fetch a model's weights on the next free worker, ahead of its first job
+
+    Returns job_id for tracking progress, like /generate; GET /weights shows
+    how far the download got
+    """
+    return _queue_weights_task('worker.download_weights', model_id, f"Download of '{model_id}' weights submitted")
+
+
+@app.delete("/weights/{model_id}", response_model=JobResponse, dependencies=authorized)
+async def remove_model_weights(model_id: str):
+    """Delete a model's weights on the next free worker to free its disk space"""
+    return _queue_weights_task('worker.remove_weights', model_id, f"Removal of '{model_id}' weights submitted")
+
+
+@app.post("/generate", response_model=JobResponse, dependencies=authorized)
 async def generate(request: GenerateRequest):
     """
     Submit a 3D generation job
     
     Returns job_id for tracking progress
     """
+    if request.image is not None and upload_path(request.image) is None:
+        raise HTTPException(status_code=422, detail=f"Unknown upload '{request.image}'")
+    if request.capture:
+        low, high = CAPTURE_PHOTOS
+        if not low <= len(request.capture) <= high:
+            raise HTTPException(status_code=422, detail=f"A capture set takes {low} to {high} photos, not {len(request.capture)}")
+        for view in request.capture:
+            if upload_path(view.upload) is None:
+                raise HTTPException(status_code=422, detail=f"Unknown upload '{view.upload}'")
+
+    # Older clients only send the two Shap-E knobs as fields of their own
+    names = {p['name'] for p in MODEL_PARAMS.get(request.model, [])}
+    legacy = {'guidance_scale': request.guidance_scale, 'num_inference_steps': request.num_inference_steps}
+    presets = MODEL_PRESETS.get(request.model, {})
+    if request.preset is not None and request.preset not in presets:
+        raise HTTPException(status_code=422, detail=f"Model '{request.model}' has no preset '{request.preset}'")
+    preset = presets.get(request.preset, {})
+    try:
+        params = resolve_params(request.model, {**{k: v for k, v in legacy.items() if k in names}, **preset, **request.params})
+    except ValueError as e:
+        raise HTTPException(status_code=422, detail=str(e))
+
     try:
         # Submit task to Celery
         task = celery_app.send_task(
@@ -96,10 +256,17 @@ async def generate(request: GenerateRequest):
             args=[
                 request.prompt,
                 request.model,
-                request.guidance_scale,
-                request.num_inference_steps
+                params.get('guidance_scale', request.guidance_scale),
+                params.get('num_inference_steps', request.num_inference_steps),
+                request.seed,
+                request.output_dir,
+                request.image,
+                request.device,
+                params,
+                [view.model_dump() for view in request.capture]
             ]
         )
+        mark_submitted(task.id)
 
         return JobResponse(
             job_id=task.id,
@@ -111,7 +278,78 @@ async def generate(request: GenerateRequest):
         raise HTTPException(status_code=500, detail=str(e))
 
 
-@app.get("/status/{job_id}", response_model=JobStatusResponse)
+@app.post("/redecode", response_model=JobResponse, dependencies=authorized)
+async def redecode(request: RedecodeRequest):
+    """
+    Decode the latent an earlier job kept into a new result, with other
+    decode settings and without running diffusion again
+
+    Returns job_id for tracking progress, like /generate
+    """
+    if Path(request.latent).name != request.latent:
+        raise HTTPException(status_code=422, detail="latent must be a file name")
+
+    try:
+        task = celery_app.send_task(
+            'worker.redecode_3d',
+            args=[
+                request.latent,
+                request.model,
+                request.mesh_resolution,
+                request.splat_scale,
+                request.output_dir,
+                request.device
+            ]
+        )
+        mark_submitted(task.id)
+
+        return JobResponse(
+            job_id=task.id,
+            status="submitted",
+            message=f"Re-decode submitted for model '{request.model}'"
+        )
+
+    except Exception as e:
+        raise HTTPException(status_code=500, detail=str(e))
+
+
+@app.post("/refine", response_model=JobResponse, dependencies=authorized)
+async def refine(request: RefineRequest):
+    """
+    Refine an earlier job's result with score distillation (SDS) against a
+    2D diffusion prior, as a second-stage job
+
+    Returns job_id for tracking progress, like /generate
+    """
+    if Path(request.source).name != request.source:
+        raise HTTPException(status_code=422, detail="source must be a file name")
+
+    try:
+        task = celery_app.send_task(
+            'worker.refine_3d',
+            args=[
+                request.source,
+                request.prompt,
+                request.iterations,
+                request.guidance_scale,
+                request.seed,
+                request.output_dir,
+                request.device
+            ]
+        )
+        mark_submitted(task.id)
+
+        return JobResponse(
+            job_id=task.id,
+            status="submitted",
+            message="Refinement submitted"
+        )
+
+    except Exception as e:
+        raise HTTPException(status_code=500, detail=str(e))
+
+
+@app.get("/status/{job_id}", response_model=JobStatusResponse, dependencies=authorized)
 async def get_status(job_id: str):
     """
     Get job status and result
@@ -122,6 +360,7 @@ async def get_status(job_id: str):
     - SUCCESS: Job completed successfully
     - FAILURE: Job failed
     - RETRY: Job is being retried
+    - UNKNOWN: No record of the job, e.g. the queue was lost in a restart
     """
     try:
         result = celery_app.AsyncResult(job_id)
@@ -131,7 +370,11 @@ async def get_status(job_id: str):
             status=result.state
         )
 
-        if result.state == 'PENDING':
+        if result.state == 'PENDING' and not is_known(job_id):
+            response.status = "UNKNOWN"
+            response.message = "No record of this job"
+
+        elif result.state == 'PENDING':
             response.message = "Job is queued"
 
         elif result.state == 'STARTED':
@@ -160,7 +403,114 @@ async def get_status(job_id: str):
         raise HTTPException(status_code=500, detail=str(e))
 
 
-@app.delete("/cancel/{job_id}")
+@app.post("/uploads", dependencies=authorized)
+async def upload_image(request: Request):
+    """
+    Store the PNG or JPEG in the request body as a conditioning image
+
+    Returns an upload id to pass as `image` to /generate.
+    """
+    data = await request.body()
+    try:
+        upload_id, width, height = save_upload(data)
+    except UploadTooLarge as e:
+        raise HTTPException(status_code=413, detail=str(e))
+    except UnsupportedImage as e:
+        raise HTTPException(status_code=415, detail=str(e))
+    except ValueError as e:
+        raise HTTPException(status_code=422, detail=str(e))
+    except Exception as e:
+        raise HTTPException(status_code=500, detail=str(e))
+    return {"upload_id": upload_id, "width": width, "height": height}
+
+
+class JobLogAppend(BaseModel):
+    lines: List[str]
+
+
+@app.get("/job/{job_id}/log", dependencies=authorized)
+async def get_job_log(job_id: str, since: int = 0):
+    """Log lines of a job, starting at line `since`"""
+    try:
+        lines = read_log(job_id, since)
+        return {"job_id": job_id, "since": since, "lines": lines}
+    except Exception as e:
+        raise HTTPException(status_code=500, detail=str(e))
+
+
+@app.post("/job/{job_id}/log", dependencies=authorized)
+async def append_job_log(job_id: str, request: JobLogAppend):
+    """Append lines to a job's log"""
+    try:
+        append_log(job_id, *request.lines)
+        return {"job_id": job_id, "appended": len(request.lines)}
+    except Exception as e:
+        raise HTTPException(status_code=500, detail=str(e))
+
+
+@app.get("/job/{job_id}/preview", dependencies=authorized)
+async def get_job_preview(job_id: str):
+    """Latest intermediate render of a job, as PNG"""
+    try:
+        png = read_preview(job_id)
+    except Exception as e:
+        raise HTTPException(status_code=500, detail=str(e))
+    if png is None:
+        raise HTTPException(status_code=404, detail="No preview yet")
+    return Response(content=png, media_type="image/png", headers={"Cache-Control": "no-store"})
+
+
+@app.post("/job/{job_id}/preview", dependencies=authorized)
+async def upload_job_preview(job_id: str, request: Request):
+    """Replace a job's preview with the PNG in the request body"""
+    png = await request.body()
+    try:
+        store_preview(job_id, png)
+    except ValueError as e:
+        raise HTTPException(status_code=415, detail=str(e))
+    except Exception as e:
+        raise HTTPException(status_code=500, detail=str(e))
+    return {"job_id": job_id, "bytes": len(png)}
+
+
+# Content types of the files a job leaves behind
+OUTPUT_MEDIA_TYPES = {
+    ".ply": "application/octet-stream",
+    ".png": "image/png",
+    ".jpg": "image/jpeg",
+    ".gif": "image/gif",
+    ".mp4": "video/mp4",
+}
+
+
+@app.get("/job/{job_id}/files/{name}", dependencies=authorized)
+async def get_job_file(job_id: str, name: str):
+    """
+    An output file of a finished job, for clients that don't share OUTPUT_DIR
+
+    Serves the result itself and anything written next to it under the same
+    name (thumbnails, turntable videos). Supports Range requests, so large
+    files can be resumed or streamed. Only available while the job's result
+    is kept (CELERY_RESULT_EXPIRES).
+    """
+    result = celery_app.AsyncResult(job_id)
+    if result.state != 'SUCCESS' or not isinstance(result.result, dict):
+        raise HTTPException(status_code=404, detail="Job has no outputs")
+
+    output = Path(result.result['output_path'])
+    if Path(name).name != name or not name.startswith(output.stem):
+        raise HTTPException(status_code=404, detail="Not an output of this job")
+
+    # The worker reports its own path, which in a container is its mount of OUTPUT_DIR
+    path = next((p for p in (output.parent / name, OUTPUT_DIR / name) if p.is_file()), None)
+    if path is None:
+        raise HTTPException(status_code=404, detail="Output file is gone")
+
+    media_type = OUTPUT_MEDIA_TYPES.get(path.suffix.lower(), "application/octet-stream")
+    return FileResponse(path, media_type=media_type, filename=name)
+
+
+@app.delete("/cancel/{job_id}", dependencies=authorized)
 async def cancel_job(job_id: str):
     """Cancel a running job"""
     try:
@@ -170,7 +520,7 @@ async def cancel_job(job_id: str):
         raise HTTPException(status_code=500, detail=str(e))
 
 
-@app.get("/queue")
+@app.get("/queue", dependencies=authorized)
 async def queue_info():
     """Get queue statistics"""
     inspect = celery_app.control.inspect()
@@ -184,4 +534,4 @@ async def queue_info():
 
 if __name__ == "__main__":
     import uvicorn
-    uvicorn.run(app, host="0.0.0.0", port=5000)
\ No newline at end of file
+    uvicorn.run(app, host="0.0.0.0", port=5000, ssl_certfile=SSL_CERTFILE, ssl_keyfile=SSL_KEYFILE)
\ No newline at end of file
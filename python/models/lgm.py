@@ -0,0 +1,253 @@
+"""
+LGM: Large Multi-View Gaussian Model, reconstructing splats from four
+views of an object in one forward pass. The views come from a multiview
+generator, see MULTIVIEW_MODELS.
+"""
+
+import io
+import os
+import sys
+from contextlib import nullcontext
+import numpy as np
+import torch
+import torch.nn.functional as F
+from pathlib import Path
+
+from .model import Model3DBase, sample_path
+from .multiview import GENERATORS, nearest_photos, views_grid
+from .splat_ply import write_splats
+
+# Checkpoint of the large model, fp16 with the fixed rotation activation
+LGM_REPO = "ashawkey/LGM"
+LGM_CHECKPOINT = "model_fp16_fixrot.safetensors"
+# Checkout of https://github.com/3DTopia/LGM, whose `core` package has the
+# network; it isn't installable with pip
+LGM_PATH = Path(os.getenv('LGM_PATH', Path(__file__).parent.parent / 'LGM'))
+# Splats more transparent than this are dropped, as LGM's own export does
+MIN_OPACITY = 0.005
+# Splat sets reconstructed in one forward pass; 0 fits as many as the free
+# GPU memory takes, at LGM_SAMPLE_MB each
+LGM_BATCH_SIZE = int(os.getenv('LGM_BATCH_SIZE', '0'))
+# GPU memory each further set in a batch takes, measured on the large model in fp16
+LGM_SAMPLE_MB = 1200
+
+IMAGENET_MEAN = (0.485, 0.456, 0.406)
+IMAGENET_STD = (0.229, 0.224, 0.225)
+
+
+class LgmModel(Model3DBase):
+    """LGM fed by a multiview generator"""
+
+    # For generators drawing the views from an image, or taking them from photos
+    supports_image = True
+    supports_capture = True
+
+    def __init__(self, device="cuda", track_download=None):
+        """
+        Args:
+            track_download: Context manager factory taking a weights id,
+                wrapped around loading a generator that may fetch weights
+        """
+        super().__init__(device)
+        self.model = None
+        # Multiview generators by id, loaded by their first job
+        self.generators = {}
+        self.track_download = track_download or (lambda model_id: nullcontext())
+
+    def load(self) -> bool:
+        """Load the LGM network"""
+        try:
+            print("  Loading LGM...")
+
+            if str(LGM_PATH) not in sys.path:
+                sys.path.insert(0, str(LGM_PATH))
+            from core.models import LGM
+            from core.options import config_defaults
+            from huggingface_hub import hf_hub_download
+            from safetensors.torch import load_file
+
+            self.opt = config_defaults['big']
+            model = LGM(self.opt)
+            model.load_state_dict(load_file(hf_hub_download(LGM_REPO, LGM_CHECKPOINT)), strict=False)
+            self.model = model.half().to(self.device).eval()
+            self.rays = self.model.prepare_default_rays(self.device)
+
+            self.is_loaded = True
+            print("  ✓ LGM loaded successfully")
+            return True
+
+        except Exception as e:
+            print(f"  ✗ Failed to load LGM: {e}")
+            print(f"  → Clone https://github.com/3DTopia/LGM to {LGM_PATH} (or set LGM_PATH) and install its requirements")
+            return False
+
+    def _generator(self, multiview: str):
+        if multiview not in self.generators:
+            if multiview not in GENERATORS:
+                raise ValueError(f"Unknown multiview generator '{multiview}'")
+            generator = GENERATORS[multiview](self.device)
+            with self.track_download(multiview):
+                loaded = generator.load()
+            if not loaded:
+                raise RuntimeError(f"Multiview generator '{multiview}' failed to load, see the worker log")
+            self.generators[multiview] = generator
+        return self.generators[multiview]
+
+    def generate(self, prompt: str, output_path: Path, **kwargs) -> Path:
+        """
+        Draw the views of `prompt`, then reconstruct splats from them. With
+        `num_samples` above 1, draws that many view sets, each from the next
+        seed, and reconstructs them in batches; the first is the result.
+        A capture set has one set of views, so it's drawn once; when the
+        cameras of the photos standing in for the views are known, the
+        reconstruction is given their rays rather than the default views'.
+        """
+        if not self.is_loaded:
+            raise RuntimeError("LGM not loaded")
+
+        multiview = kwargs.get('multiview', 'sd_controlnet')
+        image_path = kwargs.get('image_path')
+        capture = kwargs.get('capture')
+        seed = kwargs.get('seed')
+        num_samples = max(1, int(kwargs.get('num_samples', 1)))
+        progress_callback = kwargs.get('progress_callback')
+        preview_callback = kwargs.get('preview_callback')
+        if seed is None:
+            seed = int(torch.randint(0, 2**31 - 1, (1,)).item())
+
+        print(f"  Generating with LGM: '{prompt}'")
+        print(f"  [1/2] Drawing {num_samples} view set(s) with {multiview}...")
+
+        generator = self._generator(multiview)
+        if generator.needs_image and image_path is None:
+            raise ValueError(f"'{multiview}' draws the views from an input image; none was given")
+        if generator.needs_capture:
+            if not capture:
+                raise ValueError(f"'{multiview}' takes the views from a capture set; none was given")
+            num_samples = 1
+        rays = self._capture_rays(capture) if generator.needs_capture else None
+
+        view_sets = []
+        for i in range(num_samples):
+            def view_progress(share: float, message: str):
+                if progress_callback is not None:
+                    label = f"Sample {i + 1}/{num_samples}: {message}" if num_samples > 1 else message
+                    progress_callback(0.1 + 0.7 * (i + share) / num_samples, label)
+
+            views = generator.generate(
+                prompt,
+                image_path=image_path,
+                capture=capture,
+                guidance_scale=kwargs.get('guidance_scale', 7.5),
+                num_inference_steps=kwargs.get('num_inference_steps', 30),
+                controlnet_scale=kwargs.get('controlnet_scale', 1.0),
+                seed=seed + i,
+                progress_callback=view_progress,
+            )
+            view_sets.append(views)
+
+            # Kept next to the result; the views say more about a bad result than the splats
+            grid = views_grid(views)
+            grid.save(sample_path(output_path, i).with_suffix('.views.png'))
+            if preview_callback is not None:
+                png = io.BytesIO()
+                grid.resize((256, 256)).save(png, format='PNG')
+                preview_callback(png.getvalue())
+
+        print("  [2/2] Reconstructing splats...")
+        paths = [sample_path(output_path, i) for i in range(num_samples)]
+        batch_size = self._batch_size(num_samples)
+        for start in range(0, num_samples, batch_size):
+            end = min(start + batch_size, num_samples)
+            if progress_callback is not None:
+                progress_callback(0.8 + 0.2 * start / num_samples, f"Reconstructing splats {start + 1}-{end} of {num_samples}")
+            self._reconstruct(view_sets[start:end], paths[start:end], rays)
+        print(f"  ✓ Saved to {output_path}" + (f" and {num_samples - 1} more" if num_samples > 1 else ""))
+        return output_path
+
+    def _batch_size(self, count: int) -> int:
+        """View sets to reconstruct at once, of `count`: LGM_BATCH_SIZE, or what fits the free memory"""
+        if LGM_BATCH_SIZE > 0:
+            return min(count, LGM_BATCH_SIZE)
+        if not torch.cuda.is_available():
+            return 1
+        free, _ = torch.cuda.mem_get_info(self.device)
+        return max(1, min(count, free // (LGM_SAMPLE_MB * 1024 * 1024)))
+
+    def _capture_rays(self, capture: list):
+        """
+        Rays of the cameras of the photos standing in for the views, see
+        nearest_photos, as LGM takes them; None unless all four are known.
+        The cameras are moved as one so the front one sits where LGM's does.
+        """
+        cameras = [capture[i][3] for i in nearest_photos([(az, el) for _, az, el, _ in capture])]
+        if any(camera is None for camera in cameras):
+            return None
+
+        from core.utils import get_rays
+        from kiui.cam import orbit_camera
+
+        poses = [np.array(camera['to_object'], dtype=np.float32) for camera in cameras]
+        for pose in poses:
+            # Ours are a mean distance of 1 from the object, LGM's cam_radius
+            pose[:3, 3] *= self.opt.cam_radius
+        front = orbit_camera(0, 0, radius=self.opt.cam_radius)
+        transform = front @ np.linalg.inv(poses[0])
+
+        rays = []
+        for pose, camera in zip(poses, cameras):
+            origins, directions = get_rays(torch.from_numpy((transform @ pose).astype(np.float32)), self.opt.input_size, self.opt.input_size, camera['fov_y'])
+            # Plücker coordinates, as prepare_default_rays makes them
+            rays.append(torch.cat([torch.cross(origins, directions, dim=-1), directions], dim=-1))
+        return torch.stack(rays).permute(0, 3, 1, 2).contiguous().to(self.device)
+
+    @torch.no_grad()
+    def _reconstruct(self, view_sets: list, output_paths: list, rays=None):
+        """
+        Splats from each set of four views in one forward pass, written as
+        the app's PLY layout to the matching path. `rays` are the views'
+        cameras, the default views' when None.
+        """
+        rays = self.rays if rays is None else rays
+        mean = torch.tensor(IMAGENET_MEAN, device=self.device).view(1, 3, 1, 1)
+        std = torch.tensor(IMAGENET_STD, device=self.device).view(1, 3, 1, 1)
+        batch = []
+        for views in view_sets:
+            images = np.stack([np.asarray(v.resize((256, 256)), dtype=np.float32) / 255.0 for v in views])
+            images = torch.from_numpy(images).permute(0, 3, 1, 2).to(self.device)
+            images = F.interpolate(images, size=(self.opt.input_size, self.opt.input_size), mode='bilinear', align_corners=False)
+            images = (images - mean) / std
+            # [4, 9, H, W]: the views with the rays of their cameras
+            batch.append(torch.cat([images, rays], dim=1))
+
+        with torch.autocast(device_type='cuda', dtype=torch.float16):
+            # [B, N, 14], a row per splat
+            gaussians = self.model.forward_gaussians(torch.stack(batch))
+
+        for splats, path in zip(gaussians.float().cpu().numpy(), output_paths):
+            self._write(splats, path)
+
+    @staticmethod
+    def _write(gaussians: np.ndarray, output_path: Path):
+        """
+        Write LGM's activated splats, rows of position, opacity, scale,
+        rotation (w, x, y, z) and color, turned from y-up to the app's z-up
+        """
+        gaussians = gaussians[gaussians[:, 3] > MIN_OPACITY]
+        positions = gaussians[:, 0:3]
+        positions = np.stack([positions[:, 0], -positions[:, 2], positions[:, 1]], axis=-1)
+
+        # A quarter turn about x, applied before each splat's own rotation
+        w, x, y, z = gaussians[:, 7:11].T
+        s = np.sqrt(0.5)
+        rotations = np.stack([s * (w - x), s * (x + w), s * (y - z), s * (z + y)], axis=-1)
+
+        write_splats(output_path, positions, gaussians[:, 11:14], gaussians[:, 3], gaussians[:, 4:7], rotations)
+
+    def get_name(self) -> str:
+        return "LGM"
+
+    def get_estimated_time(self, **kwargs) -> int:
+        # Four views of diffusion dominate; the reconstruction takes a second
+        per_sample = 10 + 4 * kwargs.get('num_inference_steps', 30) // 5
+        return per_sample * max(1, int(kwargs.get('num_samples', 1)))
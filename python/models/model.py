@@ -1,10 +1,35 @@
 from abc import ABC, abstractmethod
 from pathlib import Path
 
+# Suffix of the latent a model keeps next to its result, see Model3DBase.decode
+LATENT_SUFFIX = ".latent.pt"
+
+
+def latent_path(output_path: Path) -> Path:
+    """Where the latent behind the result at `output_path` is kept"""
+    return output_path.with_name(output_path.stem + LATENT_SUFFIX)
+
+
+def sample_path(output_path: Path, index: int) -> Path:
+    """
+    Where sample `index` of a job drawing several is written: the first at
+    `output_path`, the others next to it as <stem>.2.ply and on
+    """
+    if index == 0:
+        return output_path
+    return output_path.with_name(f"{output_path.stem}.{index + 1}{output_path.suffix}")
+
 
 class Model3DBase(ABC):
     """Base class for all 3D generation models"""
 
+    # Whether generate() uses an `image_path` conditioning image
+    supports_image = False
+    # Whether generate() uses a `capture` set of photos of a real object
+    supports_capture = False
+    # Whether generate() keeps its latent at latent_path() for decode()
+    supports_decode = False
+
     def __init__(self, device="cuda"):
         self.device = device
         self.is_loaded = False
@@ -22,13 +47,32 @@ class Model3DBase(ABC):
         Args:
             prompt: Text description or image path
             output_path: Where to save .ply file
-            **kwargs: Model-specific parameters
+            **kwargs: Model-specific parameters; `image_path` for models
+                that support image conditioning, `capture` as a list of
+                (path, azimuth, elevation, camera) for those taking photos,
+                the camera a dict of to_object and fov_y or None. Models taking
+                `num_samples` write the further samples at sample_path().
 
         Returns:
             Path to generated .ply file
         """
         pass
 
+    def decode(self, latent_file: Path, output_path: Path, **kwargs) -> Path:
+        """
+        Turn a latent kept by an earlier generate() into a .ply again, with
+        other decode settings, without running diffusion.
+
+        Args:
+            latent_file: The latent, as saved at latent_path()
+            output_path: Where to save .ply file
+            **kwargs: Model-specific decode settings
+
+        Returns:
+            Path to generated .ply file
+        """
+        raise NotImplementedError(f"{self.get_name()} can't decode a kept latent")
+
     @abstractmethod
     def get_name(self) -> str:
         """Return model name"""
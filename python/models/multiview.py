@@ -0,0 +1,358 @@
+"""
+Multiview generation: the four views of an object LGM reconstructs splats
+from. Stable Diffusion draws them from text once per view, kept to each
+view's camera by a ControlNet depth hint of a proxy shape; ImageDream draws
+them together from an input image and text. A capture set of photos of a
+real object needs no drawing: the photo nearest each view stands in for it.
+"""
+
+import numpy as np
+import torch
+from PIL import Image
+
+# Stable Diffusion and the depth ControlNet trained against it
+SD_MODEL = "stable-diffusion-v1-5/stable-diffusion-v1-5"
+CONTROLNET_MODEL = "lllyasviel/control_v11f1p_sd15_depth"
+# ImageDream's image-prompt multiview model, converted to diffusers by LGM's author
+IMAGEDREAM_MODEL = "ashawkey/imagedream-ipmv-diffusers"
+# Share of the side of ImageDream's input the object is scaled to fill
+IMAGEDREAM_FILL = 0.8
+# Side of each view, the resolution SD 1.5 was trained at
+VIEW_SIZE = 512
+# Cameras of the views as (azimuth, elevation) in degrees, in the order LGM
+# takes them: front, right, back, left
+VIEW_CAMERAS = [(0.0, 0.0), (90.0, 0.0), (180.0, 0.0), (270.0, 0.0)]
+# Added to the prompt of each view; SD knows these words better than the hint
+VIEW_PROMPTS = ["front view", "side view", "back view", "side view"]
+NEGATIVE_PROMPT = "cropped, out of frame, multiple objects, cluttered background, text, watermark"
+# Camera distance and vertical field of view LGM was trained with
+CAMERA_RADIUS = 1.5
+FOV_Y = 49.1
+# Half extents of the proxy ellipsoid the depth hints show, x (width),
+# y (height) and z (depth); unequal so front and side views differ
+PROXY_EXTENTS = (0.45, 0.5, 0.3)
+
+
+def proxy_depth(azimuth: float, elevation: float, size: int = VIEW_SIZE) -> Image.Image:
+    """
+    Depth hint of the proxy ellipsoid seen from a camera on the orbit LGM
+    uses, as ControlNet takes it: near is white, background black
+    """
+    az, el = np.deg2rad(azimuth), np.deg2rad(elevation)
+    # y up; azimuth 0 looks at the front (+z) of the object
+    eye = CAMERA_RADIUS * np.array([np.cos(el) * np.sin(az), np.sin(el), np.cos(el) * np.cos(az)])
+    forward = -eye / np.linalg.norm(eye)
+    right = np.cross(forward, [0.0, 1.0, 0.0])
+    right /= np.linalg.norm(right)
+    up = np.cross(right, forward)
+
+    tan_half = np.tan(np.deg2rad(FOV_Y) / 2)
+    ys, xs = np.meshgrid(np.arange(size), np.arange(size), indexing='ij')
+    u = ((xs + 0.5) / size * 2 - 1) * tan_half
+    v = (1 - (ys + 0.5) / size * 2) * tan_half
+    rays = forward + u[..., None] * right + v[..., None] * up
+    rays /= np.linalg.norm(rays, axis=-1, keepdims=True)
+
+    # Intersect in the space where the ellipsoid is the unit sphere
+    extents = np.array(PROXY_EXTENTS)
+    o, d = eye / extents, rays / extents
+    a = (d * d).sum(-1)
+    b = 2 * (d * o).sum(-1)
+    c = (o * o).sum() - 1
+    disc = b * b - 4 * a * c
+    hit = disc > 0
+    t = np.where(hit, (-b - np.sqrt(np.maximum(disc, 0))) / (2 * a), np.inf)
+
+    near, far = CAMERA_RADIUS - extents.max(), CAMERA_RADIUS + extents.max()
+    depth = np.where(hit, 1 - (t - near) / (far - near), 0)
+    pixels = (np.clip(depth, 0, 1) * 255).astype(np.uint8)
+    return Image.fromarray(pixels).convert('RGB')
+
+
+class SdControlNetMultiview:
+    """
+    Four views of one object from Stable Diffusion with a depth ControlNet.
+    Every view starts from the same seed, so they share a look; the hints
+    and view prompts put the camera where LGM expects it.
+    """
+
+    # Whether generate() needs an `image_path`, or a `capture` set
+    needs_image = False
+    needs_capture = False
+
+    def __init__(self, device="cuda"):
+        self.device = device
+        self.is_loaded = False
+
+    def load(self) -> bool:
+        """Load Stable Diffusion and the ControlNet. Returns True if successful."""
+        try:
+            print("  Loading Stable Diffusion + ControlNet...")
+
+            from diffusers import ControlNetModel, StableDiffusionControlNetPipeline, UniPCMultistepScheduler
+
+            controlnet = ControlNetModel.from_pretrained(CONTROLNET_MODEL, torch_dtype=torch.float16)
+            pipe = StableDiffusionControlNetPipeline.from_pretrained(
+                SD_MODEL, controlnet=controlnet, torch_dtype=torch.float16, safety_checker=None
+            )
+            pipe.scheduler = UniPCMultistepScheduler.from_config(pipe.scheduler.config)
+            self.pipe = pipe.to(self.device)
+            self.pipe.set_progress_bar_config(disable=True)
+
+            self.is_loaded = True
+            print("  ✓ Stable Diffusion + ControlNet loaded successfully")
+            return True
+
+        except Exception as e:
+            print(f"  ✗ Failed to load Stable Diffusion + ControlNet: {e}")
+            print(f"  → Make sure you installed: pip install diffusers transformers")
+            return False
+
+    def generate(self, prompt: str, **kwargs) -> list:
+        """
+        The views of `prompt` as RGB images, in VIEW_CAMERAS order
+
+        Args:
+            prompt: Text description of the object
+            **kwargs: guidance_scale, num_inference_steps, controlnet_scale,
+                seed, and progress_callback(share, message) called as each
+                view is done
+        """
+        if not self.is_loaded:
+            raise RuntimeError("Stable Diffusion + ControlNet not loaded")
+
+        guidance_scale = kwargs.get('guidance_scale', 7.5)
+        num_inference_steps = kwargs.get('num_inference_steps', 30)
+        controlnet_scale = kwargs.get('controlnet_scale', 1.0)
+        seed = kwargs.get('seed')
+        progress_callback = kwargs.get('progress_callback')
+        if seed is None:
+            seed = int(torch.randint(0, 2**31 - 1, (1,)).item())
+
+        views = []
+        for i, ((azimuth, elevation), view_prompt) in enumerate(zip(VIEW_CAMERAS, VIEW_PROMPTS)):
+            print(f"    View {i + 1}/{len(VIEW_CAMERAS)}: azimuth {azimuth:.0f}°, {view_prompt}")
+            generator = torch.Generator(device=self.device).manual_seed(seed)
+            image = self.pipe(
+                f"{prompt}, {view_prompt}, single object, centered, plain white background",
+                image=proxy_depth(azimuth, elevation),
+                negative_prompt=NEGATIVE_PROMPT,
+                num_inference_steps=num_inference_steps,
+                guidance_scale=guidance_scale,
+                controlnet_conditioning_scale=controlnet_scale,
+                generator=generator,
+            ).images[0]
+            views.append(image.convert('RGB'))
+            if progress_callback is not None:
+                progress_callback((i + 1) / len(VIEW_CAMERAS), f"Drew view {i + 1} of {len(VIEW_CAMERAS)}")
+        return views
+
+
+class ImageDreamMultiview:
+    """
+    Four views of the object in an input image from ImageDream, denoised
+    together so they agree with each other; the prompt fills in what the
+    image doesn't show. The pipeline comes from the LGM checkout, which is
+    on the path once LGM is loaded.
+    """
+
+    needs_image = True
+    needs_capture = False
+
+    def __init__(self, device="cuda"):
+        self.device = device
+        self.is_loaded = False
+
+    def load(self) -> bool:
+        """Load ImageDream. Returns True if successful."""
+        try:
+            print("  Loading ImageDream...")
+
+            from mvdream.pipeline_mvdream import MVDreamPipeline
+
+            self.pipe = MVDreamPipeline.from_pretrained(
+                IMAGEDREAM_MODEL, torch_dtype=torch.float16, trust_remote_code=True
+            ).to(self.device)
+
+            self.is_loaded = True
+            print("  ✓ ImageDream loaded successfully")
+            return True
+
+        except Exception as e:
+            print(f"  ✗ Failed to load ImageDream: {e}")
+            print(f"  → ImageDream's pipeline comes with the LGM checkout; see LGM_PATH")
+            return False
+
+    def generate(self, prompt: str, **kwargs) -> list:
+        """
+        The views of the object in `image_path` as RGB images, in
+        VIEW_CAMERAS order
+
+        Args:
+            prompt: Text description of the object
+            **kwargs: image_path, guidance_scale, num_inference_steps, seed,
+                and progress_callback(share, message) called every few steps
+        """
+        if not self.is_loaded:
+            raise RuntimeError("ImageDream not loaded")
+
+        image_path = kwargs.get('image_path')
+        if image_path is None:
+            raise ValueError("ImageDream draws views from an input image; none was given")
+        guidance_scale = kwargs.get('guidance_scale', 5.0)
+        num_inference_steps = kwargs.get('num_inference_steps', 30)
+        seed = kwargs.get('seed')
+        progress_callback = kwargs.get('progress_callback')
+
+        generator = torch.Generator(device=self.device)
+        if seed is not None:
+            generator.manual_seed(seed)
+
+        def step_callback(step: int, timestep, latents):
+            if progress_callback is not None and (step + 1) % 5 == 0:
+                progress_callback((step + 1) / num_inference_steps, f"Denoising views, step {step + 1}/{num_inference_steps}")
+
+        print(f"    Drawing views of {image_path.name}")
+        # [4, 256, 256, 3] floats, starting with the left view
+        images = self.pipe(
+            prompt,
+            object_image(image_path),
+            guidance_scale=guidance_scale,
+            num_inference_steps=num_inference_steps,
+            elevation=0,
+            generator=generator,
+            callback=step_callback,
+        )
+        views = [images[i] for i in (1, 2, 3, 0)]
+        return [Image.fromarray((np.clip(v, 0, 1) * 255).astype(np.uint8)) for v in views]
+
+
+class CaptureMultiview:
+    """
+    Four views of a real object from a capture set: the photo taken nearest
+    each view's camera, centered as ImageDream's input is. Photos off the
+    views' cameras make for a worse reconstruction, so sets should circle
+    the object at about its middle, unless their cameras are known; those
+    are cropped square around the camera's axis instead, and LGM is given
+    their actual rays.
+    """
+
+    needs_image = False
+    needs_capture = True
+
+    def __init__(self, device="cuda"):
+        self.device = device
+        self.is_loaded = False
+
+    def load(self) -> bool:
+        """Nothing to load"""
+        self.is_loaded = True
+        return True
+
+    def generate(self, prompt: str, **kwargs) -> list:
+        """
+        The photos standing in for the views as RGB images, in VIEW_CAMERAS
+        order
+
+        Args:
+            prompt: Unused; the photos show the object
+            **kwargs: capture as a list of (path, azimuth, elevation,
+                camera), and progress_callback(share, message)
+        """
+        capture = kwargs.get('capture')
+        if not capture or len(capture) < len(VIEW_CAMERAS):
+            raise ValueError(f"A capture set needs at least {len(VIEW_CAMERAS)} photos")
+        progress_callback = kwargs.get('progress_callback')
+
+        views = []
+        for (azimuth, elevation), photo in zip(VIEW_CAMERAS, nearest_photos([(az, el) for _, az, el, _ in capture])):
+            path, photo_azimuth, photo_elevation, camera = capture[photo]
+            print(f"    View at azimuth {azimuth:.0f}°: photo {photo + 1} ({photo_azimuth:.0f}°, {photo_elevation:.0f}°)")
+            pixels = object_image(path) if camera is None else square_image(path)
+            views.append(Image.fromarray((pixels * 255).astype(np.uint8)))
+        if progress_callback is not None:
+            progress_callback(1.0, f"Picked 4 of {len(capture)} photos")
+        return views
+
+
+def nearest_photos(poses: list) -> list:
+    """
+    Index of the photo standing in for each of VIEW_CAMERAS, of photos taken
+    at `poses` as (azimuth, elevation): the most aligned pairs go first and a
+    photo stands in for one view at most. Matches the app's capture_views.
+    """
+    def direction(azimuth, elevation):
+        az, el = np.deg2rad(azimuth), np.deg2rad(elevation)
+        return np.array([np.cos(el) * np.sin(az), np.sin(el), np.cos(el) * np.cos(az)])
+
+    pairs = []
+    for view, camera in enumerate(VIEW_CAMERAS):
+        for photo, pose in enumerate(poses):
+            pairs.append((float(direction(*camera) @ direction(*pose)), view, photo))
+    # Most aligned first; ties go to the earlier photo
+    pairs.sort(key=lambda p: (-p[0], p[2]))
+
+    chosen = [None] * len(VIEW_CAMERAS)
+    used = set()
+    for _, view, photo in pairs:
+        if chosen[view] is None and photo not in used:
+            chosen[view] = photo
+            used.add(photo)
+    return chosen
+
+
+def object_image(path) -> np.ndarray:
+    """
+    The object in the image at `path` as ImageDream takes it: centered,
+    filling IMAGEDREAM_FILL of a square on white, as floats. A transparent
+    background tells the object apart; opaque images are only padded square.
+    """
+    image = Image.open(path).convert('RGBA')
+    alpha = np.asarray(image)[..., 3]
+    ys, xs = np.nonzero(alpha > 0)
+    if len(xs) == 0:
+        raise ValueError("The input image is fully transparent")
+    if (alpha < 255).any():
+        image = image.crop((xs.min(), ys.min(), xs.max() + 1, ys.max() + 1))
+        fill = IMAGEDREAM_FILL
+    else:
+        fill = 1.0
+
+    side = int(max(image.size) / fill)
+    square = Image.new('RGBA', (side, side), (255, 255, 255, 0))
+    square.paste(image, ((side - image.width) // 2, (side - image.height) // 2))
+    square = square.resize((VIEW_SIZE // 2, VIEW_SIZE // 2), Image.LANCZOS)
+
+    rgba = np.asarray(square, dtype=np.float32) / 255.0
+    return rgba[..., :3] * rgba[..., 3:4] + (1 - rgba[..., 3:4])
+
+
+def square_image(path) -> np.ndarray:
+    """
+    The centered square of the image at `path`, at the side object_image()
+    gives, on white, as floats; the crop keeps the camera's axis in the middle
+    """
+    image = Image.open(path).convert('RGBA')
+    side = min(image.size)
+    left, top = (image.width - side) // 2, (image.height - side) // 2
+    square = image.crop((left, top, left + side, top + side)).resize((VIEW_SIZE // 2, VIEW_SIZE // 2), Image.LANCZOS)
+
+    rgba = np.asarray(square, dtype=np.float32) / 255.0
+    return rgba[..., :3] * rgba[..., 3:4] + (1 - rgba[..., 3:4])
+
+
+# Multiview generators by id, see MULTIVIEW_MODELS
+GENERATORS = {
+    'sd_controlnet': SdControlNetMultiview,
+    'imagedream': ImageDreamMultiview,
+    'capture': CaptureMultiview,
+}
+
+
+def views_grid(views: list) -> Image.Image:
+    """The four views side by side in a 2x2 grid, front first"""
+    size = views[0].size[0]
+    grid = Image.new('RGB', (size * 2, size * 2), 'white')
+    for i, view in enumerate(views):
+        grid.paste(view.resize((size, size)), ((i % 2) * size, (i // 2) * size))
+    return grid
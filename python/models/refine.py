@@ -0,0 +1,247 @@
+"""
+SDS refinement: a second stage in the style of DreamGaussian that optimizes
+the splats of an earlier result against a 2D diffusion prior, sharpening
+the coarse shape and colors a fast first stage produces
+"""
+
+import io
+import math
+import numpy as np
+import torch
+import torch.nn.functional as F
+from pathlib import Path
+from PIL import Image
+
+from .splat_ply import read_splats, write_splats
+
+# Diffusion prior the renders are scored against
+SD_MODEL = "stabilityai/stable-diffusion-2-1-base"
+# Side of the renders, the resolution the prior was trained at
+RENDER_SIZE = 512
+# Vertical field of view of the training cameras, in degrees
+FOV_Y = 49.1
+# Elevations the object is viewed from, in degrees; results are z-up like Shap-E's
+ELEVATION_RANGE = (-30.0, 30.0)
+# Noise levels scored at, as a share of the schedule; annealed from the
+# first (coarse shape) to the second (fine detail) over a run
+TIMESTEP_RANGE = (0.98, 0.02)
+# Intermediate renders per job
+PREVIEW_COUNT = 8
+
+
+class SdsRefiner:
+    """
+    Score distillation sampling on Gaussian splats. The splat count is kept
+    as it is: there is no densification or pruning, only the splats' own
+    parameters are optimized.
+    """
+
+    def __init__(self, device="cuda"):
+        self.device = device
+        self.is_loaded = False
+
+    def load(self) -> bool:
+        """Load the diffusion prior. Returns True if successful."""
+        try:
+            print("  Loading SDS refiner...")
+
+            from diffusers import DDIMScheduler, StableDiffusionPipeline
+            from gsplat import rasterization
+
+            self.rasterization = rasterization
+
+            pipe = StableDiffusionPipeline.from_pretrained(SD_MODEL, torch_dtype=torch.float16).to(self.device)
+            self.tokenizer = pipe.tokenizer
+            self.text_encoder = pipe.text_encoder
+            self.vae = pipe.vae
+            self.unet = pipe.unet
+            for module in (self.text_encoder, self.vae, self.unet):
+                module.requires_grad_(False)
+
+            self.scheduler = DDIMScheduler.from_pretrained(SD_MODEL, subfolder="scheduler")
+            self.alphas = self.scheduler.alphas_cumprod.to(self.device)
+
+            self.is_loaded = True
+            print("  ✓ SDS refiner loaded successfully")
+            return True
+
+        except Exception as e:
+            print(f"  ✗ Failed to load SDS refiner: {e}")
+            print(f"  → Make sure you installed: pip install diffusers gsplat")
+            return False
+
+    def refine(self, source: Path, output_path: Path, prompt: str, **kwargs) -> Path:
+        """
+        Refine the splats in `source` towards `prompt` and save them to `output_path`
+
+        Args:
+            source: PLY to start from, usually an earlier job's result
+            output_path: Where to save .ply file
+            prompt: Text description the renders are scored against
+            **kwargs: `iterations`, `guidance_scale`, `seed`, and
+                `progress_callback(progress, message)` and
+                `preview_callback(png)` for reporting
+
+        Returns:
+            Path to generated .ply file
+        """
+        if not self.is_loaded:
+            raise RuntimeError("SDS refiner not loaded")
+
+        iterations = kwargs.get('iterations', 500)
+        guidance_scale = kwargs.get('guidance_scale', 100.0)
+        seed = kwargs.get('seed')
+        progress_callback = kwargs.get('progress_callback')
+        preview_callback = kwargs.get('preview_callback')
+
+        if seed is not None:
+            torch.manual_seed(seed)
+
+        splats = read_splats(source)
+        count = len(splats['positions'])
+        if count == 0:
+            raise ValueError(f"{source.name} has no splats to refine")
+
+        print(f"  Refining {count} splats from {source.name}: '{prompt}'")
+        print(f"  Iterations: {iterations}")
+        print(f"  Guidance scale: {guidance_scale}")
+
+        def tensor(key):
+            return torch.tensor(splats[key], device=self.device)
+
+        positions = tensor('positions')
+        center = positions.mean(dim=0)
+        extent = (positions - center).norm(dim=-1).max().clamp(min=1e-3).item()
+
+        # Optimized unconstrained, mapped into range when rendering
+        means = tensor('positions').requires_grad_()
+        log_scales = tensor('scales').clamp(min=1e-6).log().requires_grad_()
+        quats = tensor('rotations').requires_grad_()
+        opacity_logits = torch.logit(tensor('opacities').clamp(1e-4, 1 - 1e-4)).requires_grad_()
+        color_logits = torch.logit(tensor('colors').clamp(1e-4, 1 - 1e-4)).requires_grad_()
+
+        # Learning rates after DreamGaussian's, positions relative to the object's size
+        optimizer = torch.optim.Adam([
+            {'params': [means], 'lr': 1e-3 * extent},
+            {'params': [log_scales], 'lr': 5e-3},
+            {'params': [quats], 'lr': 5e-3},
+            {'params': [opacity_logits], 'lr': 5e-2},
+            {'params': [color_logits], 'lr': 1e-2},
+        ])
+
+        embeddings = torch.cat([self._embed(prompt), self._embed("")])
+        focal = 0.5 * RENDER_SIZE / math.tan(math.radians(FOV_Y) / 2)
+        intrinsics = torch.tensor(
+            [[focal, 0, RENDER_SIZE / 2], [0, focal, RENDER_SIZE / 2], [0, 0, 1]],
+            device=self.device
+        )
+        # Far enough for the whole object to fit in the view
+        distance = extent / math.sin(math.radians(FOV_Y) / 2)
+        preview_every = max(1, iterations // PREVIEW_COUNT)
+
+        for step in range(iterations):
+            azimuth = np.random.uniform(0.0, 360.0)
+            elevation = np.random.uniform(*ELEVATION_RANGE)
+            view = _orbit_view(center, distance, azimuth, elevation)
+
+            image = self._render(means, quats, log_scales.exp(), opacity_logits.sigmoid(), color_logits.sigmoid(), view, intrinsics)
+
+            share = TIMESTEP_RANGE[0] + (TIMESTEP_RANGE[1] - TIMESTEP_RANGE[0]) * step / max(1, iterations - 1)
+            timestep = int(share * (self.scheduler.config.num_train_timesteps - 1))
+            loss = self._sds_loss(image, timestep, embeddings, guidance_scale)
+
+            optimizer.zero_grad()
+            loss.backward()
+            optimizer.step()
+
+            if progress_callback is not None and step % 10 == 0:
+                progress_callback(0.1 + 0.85 * step / iterations, f"Refining ({step}/{iterations})")
+            if preview_callback is not None and step % preview_every == 0:
+                self._send_preview(image, preview_callback)
+
+        print("  Saving refined splats...")
+        with torch.no_grad():
+            write_splats(
+                output_path,
+                means.cpu().numpy(),
+                color_logits.sigmoid().cpu().numpy(),
+                opacity_logits.sigmoid().cpu().numpy(),
+                log_scales.exp().cpu().numpy(),
+                F.normalize(quats, dim=-1).cpu().numpy(),
+            )
+
+        print(f"  ✓ Saved to {output_path}")
+        return output_path
+
+    def _embed(self, text: str):
+        """Text encoder output for `text`, as the UNet is conditioned on"""
+        tokens = self.tokenizer(
+            [text],
+            padding='max_length',
+            max_length=self.tokenizer.model_max_length,
+            truncation=True,
+            return_tensors='pt'
+        )
+        with torch.no_grad():
+            return self.text_encoder(tokens.input_ids.to(self.device))[0]
+
+    def _render(self, means, quats, scales, opacities, colors, view, intrinsics):
+        """Render the splats from `view` on white, as a (1, 3, H, W) image in [0, 1]"""
+        rendered, _, _ = self.rasterization(
+            means, quats, scales, opacities, colors,
+            view[None], intrinsics[None],
+            RENDER_SIZE, RENDER_SIZE,
+            backgrounds=torch.ones(1, 3, device=self.device)
+        )
+        return rendered.permute(0, 3, 1, 2).clamp(0.0, 1.0)
+
+    def _sds_loss(self, image, timestep: int, embeddings, guidance_scale: float):
+        """
+        Loss whose gradient on the image's latents is the SDS gradient: how
+        far the prior's noise estimate is from the noise actually added
+        """
+        latents = self.vae.encode(image.half() * 2.0 - 1.0).latent_dist.sample() * self.vae.config.scaling_factor
+
+        with torch.no_grad():
+            noise = torch.randn_like(latents)
+            t = torch.tensor([timestep], device=self.device)
+            noisy = self.scheduler.add_noise(latents, noise, t)
+            noise_pred = self.unet(torch.cat([noisy] * 2), torch.cat([t] * 2), encoder_hidden_states=embeddings).sample
+            cond, uncond = noise_pred.chunk(2)
+            noise_pred = uncond + guidance_scale * (cond - uncond)
+            grad = torch.nan_to_num((1.0 - self.alphas[timestep]) * (noise_pred - noise))
+
+        target = (latents - grad).detach()
+        return 0.5 * F.mse_loss(latents.float(), target.float(), reduction='sum')
+
+    def _send_preview(self, image, preview_callback):
+        """Hand over a render as PNG; failures only cost the preview"""
+        try:
+            pixels = (image[0].detach().permute(1, 2, 0).cpu().numpy() * 255).astype(np.uint8)
+            buffer = io.BytesIO()
+            Image.fromarray(pixels).resize((RENDER_SIZE // 4, RENDER_SIZE // 4)).save(buffer, format='PNG')
+            preview_callback(buffer.getvalue())
+        except Exception as e:
+            print(f"    Could not render preview: {e}")
+
+
+def _orbit_view(center, distance: float, azimuth: float, elevation: float):
+    """World-to-camera matrix (OpenCV axes) looking at `center` from the given angles, z up"""
+    azimuth, elevation = math.radians(azimuth), math.radians(elevation)
+    offset = torch.tensor([
+        math.cos(elevation) * math.cos(azimuth),
+        math.cos(elevation) * math.sin(azimuth),
+        math.sin(elevation),
+    ], device=center.device)
+    eye = center + distance * offset
+
+    forward = F.normalize(center - eye, dim=0)
+    up = torch.tensor([0.0, 0.0, 1.0], device=center.device)
+    right = F.normalize(torch.linalg.cross(forward, up), dim=0)
+    down = torch.linalg.cross(forward, right)
+
+    rotation = torch.stack([right, down, forward])
+    view = torch.eye(4, device=center.device)
+    view[:3, :3] = rotation
+    view[:3, 3] = -rotation @ eye
+    return view
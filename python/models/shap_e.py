@@ -3,17 +3,27 @@ Shap-E: Text-to-3D generation using OpenAI's Shap-E model
 Fast inference (~1 minute) with high-quality Gaussian splat output
 """
 
+import io
 import torch
 import numpy as np
 from pathlib import Path
 from PIL import Image
 
-from .model import Model3DBase
+from .model import Model3DBase, latent_path
+
+# Side of the intermediate renders, in pixels
+PREVIEW_SIZE = 64
+# Intermediate renders per job; each costs a decode of the latent
+PREVIEW_COUNT = 8
+# Cells along each side of the grid the mesh is extracted on, Shap-E's own default
+DEFAULT_MESH_RESOLUTION = 128
 
 
 class ShapEModel(Model3DBase):
     """OpenAI Shap-E: Fast text-to-3D generation"""
 
+    supports_decode = True
+
     def __init__(self, device="cuda"):
         super().__init__(device)
         self.text_model = None
@@ -60,6 +70,13 @@ class ShapEModel(Model3DBase):
 
         guidance_scale = kwargs.get('guidance_scale', 15.0)
         num_inference_steps = kwargs.get('num_inference_steps', 64)
+        seed = kwargs.get('seed')
+        preview_callback = kwargs.get('preview_callback')
+        mesh_resolution = kwargs.get('mesh_resolution', DEFAULT_MESH_RESOLUTION)
+        splat_scale = kwargs.get('splat_scale', 1.0)
+
+        if seed is not None:
+            torch.manual_seed(seed)
 
         print(f"  Generating with Shap-E: '{prompt}'")
         print(f"  Guidance scale: {guidance_scale}")
@@ -67,35 +84,64 @@ class ShapEModel(Model3DBase):
 
         # Generate latents
         print("  [1/3] Generating latent representation...")
-        latents = self.sample_latents(
-            batch_size=1,
-            model=self.text_model,
-            diffusion=self.diffusion_from_config(self.load_config('diffusion')),
-            guidance_scale=guidance_scale,
-            model_kwargs=dict(texts=[prompt]),
-            progress=True,
-            clip_denoised=True,
-            use_fp16=True,
-            use_karras=True,
-            karras_steps=num_inference_steps,
-            sigma_min=1e-3,
-            sigma_max=160,
-            s_churn=0,
-        )
-
-        print("  [2/3] Decoding to mesh...")
-
-        # Get the mesh with higher resolution
+        if preview_callback is None:
+            latents = self.sample_latents(
+                batch_size=1,
+                model=self.text_model,
+                diffusion=self.diffusion_from_config(self.load_config('diffusion')),
+                guidance_scale=guidance_scale,
+                model_kwargs=dict(texts=[prompt]),
+                progress=True,
+                clip_denoised=True,
+                use_fp16=True,
+                use_karras=True,
+                karras_steps=num_inference_steps,
+                sigma_min=1e-3,
+                sigma_max=160,
+                s_churn=0,
+            )
+        else:
+            latents = self._sample_latents_with_previews(prompt, guidance_scale, num_inference_steps, preview_callback)
+
+        # Kept so the result can be decoded again with other settings
+        torch.save(latents[0].detach().cpu(), latent_path(output_path))
+
+        self._decode(latents[0], output_path, mesh_resolution, splat_scale)
+        return output_path
+
+    def decode(self, latent_file: Path, output_path: Path, **kwargs) -> Path:
+        """Decode a latent saved by generate() again, at another mesh resolution or splat scale"""
+        if not self.is_loaded:
+            raise RuntimeError("Shap-E not loaded")
+
+        mesh_resolution = kwargs.get('mesh_resolution', DEFAULT_MESH_RESOLUTION)
+        splat_scale = kwargs.get('splat_scale', 1.0)
+
+        print(f"  Re-decoding Shap-E latent {latent_file.name}")
+        print(f"  Mesh resolution: {mesh_resolution}")
+        print(f"  Splat scale: {splat_scale}")
+
+        latent = torch.load(latent_file, map_location=self.device)
+        self._decode(latent, output_path, mesh_resolution, splat_scale)
+        return output_path
+
+    def _decode(self, latent, output_path: Path, mesh_resolution: int, splat_scale: float):
+        """Extract a mesh from the latent and write it out as splats"""
+        print(f"  [2/3] Decoding to mesh ({mesh_resolution}³ grid)...")
+
         from shap_e.util.notebooks import decode_latent_mesh
 
-        # Try different resolutions to get better quality
+        renderer = self.diffusion_model.renderer
+        default_resolution = renderer.grid_size
+        renderer.grid_size = mesh_resolution
         try:
-            # High resolution (slower but better)
-            mesh = decode_latent_mesh(self.diffusion_model, latents[0]).tri_mesh()
+            mesh = decode_latent_mesh(self.diffusion_model, latent).tri_mesh()
             print(f"    ✓ Generated mesh with {len(mesh.verts)} vertices")
         except Exception as e:
             print(f"    ✗ Failed to decode mesh: {e}")
             raise
+        finally:
+            renderer.grid_size = default_resolution
 
         # Check if mesh is degenerate
         vertices = mesh.verts
@@ -107,10 +153,61 @@ class ShapEModel(Model3DBase):
             print(f"    Consider using a different prompt or higher guidance scale")
 
         print("  [3/3] Converting to Gaussian splats...")
-        self._export_to_ply(mesh, output_path)
+        self._export_to_ply(mesh, output_path, splat_scale)
 
         print(f"  ✓ Saved to {output_path}")
-        return output_path
+
+    def _sample_latents_with_previews(self, prompt, guidance_scale, num_inference_steps, preview_callback):
+        """
+        Same sampling as sample_latents, rendering the current estimate of
+        the result every few steps and passing it to preview_callback as PNG
+        """
+        from shap_e.diffusion.k_diffusion import karras_sample_progressive
+        from shap_e.util.notebooks import create_pan_cameras
+
+        model = self.text_model
+        device = next(model.parameters()).device
+        model_kwargs = model.cached_model_kwargs(1, dict(texts=[prompt]))
+        if guidance_scale != 1.0 and guidance_scale != 0.0:
+            model_kwargs = {k: torch.cat([v, torch.zeros_like(v)], dim=0) for k, v in model_kwargs.items()}
+
+        cameras = create_pan_cameras(PREVIEW_SIZE, device)
+        every = max(1, num_inference_steps // PREVIEW_COUNT)
+
+        sample = None
+        with torch.autocast(device_type=device.type, enabled=True):
+            samples = karras_sample_progressive(
+                diffusion=self.diffusion_from_config(self.load_config('diffusion')),
+                model=model,
+                shape=(1, model.d_latent),
+                steps=num_inference_steps,
+                clip_denoised=True,
+                progress=True,
+                model_kwargs=model_kwargs,
+                device=device,
+                sigma_min=1e-3,
+                sigma_max=160,
+                s_churn=0,
+                guidance_scale=guidance_scale,
+            )
+            for step, sample in enumerate(samples):
+                if step % every == 0:
+                    self._send_preview(sample['pred_xstart'][0], cameras, preview_callback)
+
+        return sample['x']
+
+    def _send_preview(self, latent, cameras, preview_callback):
+        """Render one view of a latent and hand it over; failures only cost the preview"""
+        from shap_e.util.notebooks import decode_latent_images
+
+        try:
+            with torch.no_grad():
+                images = decode_latent_images(self.diffusion_model, latent.float(), cameras, rendering_mode='stf')
+            buffer = io.BytesIO()
+            images[0].save(buffer, format='PNG')
+            preview_callback(buffer.getvalue())
+        except Exception as e:
+            print(f"    Could not render preview: {e}")
 
     def get_name(self) -> str:
         return "Shap-E"
@@ -120,8 +217,8 @@ class ShapEModel(Model3DBase):
         num_steps = kwargs.get('num_inference_steps', 64)
         return 30 + (num_steps * 0.5)  # ~30-60 seconds
 
-    def _export_to_ply(self, mesh, output_path):
-        """Convert Shap-E mesh to PLY with proper Gaussian splat data"""
+    def _export_to_ply(self, mesh, output_path, splat_scale=1.0):
+        """Convert Shap-E mesh to PLY with proper Gaussian splat data, splat sizes times `splat_scale`"""
         import numpy as np
         from scipy.spatial import cKDTree
 
@@ -170,6 +267,8 @@ class ShapEModel(Model3DBase):
         scales[:, 1] = avg_distances * 1.5  # Increased from 0.5
         scales[:, 2] = avg_distances * 1.2  # Increased from 0.4
 
+        scales *= splat_scale
+
         # More permissive clamping
         scales = np.clip(scales, 0.01, 1.0)  # Larger max
 
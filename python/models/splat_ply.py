@@ -0,0 +1,75 @@
+"""
+Reading and writing splats in the PLY layout the app loads
+(GaussianCloud::from_ply): fixed 59-byte little-endian records with 8-bit
+colors, plain opacity and linear scales
+"""
+
+import numpy as np
+from pathlib import Path
+
+VERTEX_DTYPE = np.dtype([
+    ('x', '<f4'), ('y', '<f4'), ('z', '<f4'),
+    ('nx', '<f4'), ('ny', '<f4'), ('nz', '<f4'),
+    ('red', 'u1'), ('green', 'u1'), ('blue', 'u1'),
+    ('opacity', '<f4'),
+    ('scale_0', '<f4'), ('scale_1', '<f4'), ('scale_2', '<f4'),
+    ('rot_0', '<f4'), ('rot_1', '<f4'), ('rot_2', '<f4'), ('rot_3', '<f4'),
+])
+
+END_HEADER = b"end_header\n"
+
+
+def read_splats(path: Path) -> dict:
+    """
+    Splats of a PLY as float32 arrays: positions (N, 3), colors (N, 3) in
+    [0, 1], opacities (N,), scales (N, 3) and rotations (N, 4) as w, x, y, z
+    """
+    data = Path(path).read_bytes()
+    end = data.find(END_HEADER)
+    if end < 0:
+        raise ValueError(f"{path} is not a PLY: no end_header")
+
+    header = data[:end].decode('ascii', errors='replace')
+    count = next(
+        (int(line.split()[-1]) for line in header.splitlines() if line.startswith("element vertex")),
+        None
+    )
+    if count is None:
+        raise ValueError(f"{path} has no vertex count")
+
+    vertices = np.frombuffer(data, VERTEX_DTYPE, count, offset=end + len(END_HEADER))
+
+    def columns(*names):
+        return np.stack([vertices[n].astype(np.float32) for n in names], axis=-1)
+
+    return {
+        'positions': columns('x', 'y', 'z'),
+        'colors': columns('red', 'green', 'blue') / 255.0,
+        'opacities': vertices['opacity'].astype(np.float32),
+        'scales': columns('scale_0', 'scale_1', 'scale_2'),
+        'rotations': columns('rot_0', 'rot_1', 'rot_2', 'rot_3'),
+    }
+
+
+def write_splats(path: Path, positions, colors, opacities, scales, rotations):
+    """Write splats given as in read_splats(); normals are left at zero"""
+    count = len(positions)
+    vertices = np.zeros(count, VERTEX_DTYPE)
+    for i, axis in enumerate('xyz'):
+        vertices[axis] = positions[:, i]
+    for i, channel in enumerate(('red', 'green', 'blue')):
+        vertices[channel] = np.clip(np.round(colors[:, i] * 255.0), 0, 255)
+    vertices['opacity'] = opacities
+    for i in range(3):
+        vertices[f'scale_{i}'] = scales[:, i]
+    for i in range(4):
+        vertices[f'rot_{i}'] = rotations[:, i]
+
+    header = "ply\nformat binary_little_endian 1.0\n" f"element vertex {count}\n"
+    for name in VERTEX_DTYPE.names:
+        kind = 'uchar' if VERTEX_DTYPE[name] == np.uint8 else 'float'
+        header += f"property {kind} {name}\n"
+
+    with open(path, 'wb') as f:
+        f.write(header.encode('ascii') + END_HEADER)
+        f.write(vertices.tobytes())
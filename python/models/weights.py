@@ -0,0 +1,103 @@
+"""
+Where each model's weights live in the worker's download caches, and
+fetching or deleting them without loading the model
+"""
+import shutil
+from pathlib import Path
+
+from . import lgm, multiview
+from .refine import SD_MODEL
+
+# Shap-E checkpoints and configs ShapEModel.load() and generate() use
+SHAP_E_CHECKPOINTS = ['text300M', 'transmitter']
+SHAP_E_CONFIGS = ['text300M', 'transmitter', 'diffusion']
+
+# Suffix of a Shap-E download in progress, renamed once complete
+PARTIAL_SUFFIX = ".tmp"
+
+# Hugging Face repos of the models fetched through the hub, by id
+HF_REPOS = {
+    'sds_refine': [SD_MODEL],
+    'lgm': [lgm.LGM_REPO],
+    'sd_controlnet': [multiview.SD_MODEL, multiview.CONTROLNET_MODEL],
+    'imagedream': [multiview.IMAGEDREAM_MODEL],
+}
+
+
+def _shap_e_urls() -> list:
+    from shap_e.models.download import CONFIG_PATHS, MODEL_PATHS
+    return [MODEL_PATHS[n] for n in SHAP_E_CHECKPOINTS] + [CONFIG_PATHS[n] for n in SHAP_E_CONFIGS]
+
+
+def _shap_e_files() -> list:
+    from shap_e.models.download import default_cache_dir
+    return [Path(default_cache_dir()) / url.split("/")[-1] for url in _shap_e_urls()]
+
+
+def _hf_repo_dir(repo_id: str) -> Path:
+    from huggingface_hub.constants import HF_HUB_CACHE
+    return Path(HF_HUB_CACHE) / ("models--" + repo_id.replace("/", "--"))
+
+
+def _tree_bytes(path: Path) -> int:
+    if path.is_file():
+        return path.stat().st_size
+    if path.is_dir():
+        # The hub cache links snapshots to blobs; count the blobs once
+        return sum(p.stat().st_size for p in path.rglob("*") if p.is_file() and not p.is_symlink())
+    return 0
+
+
+def is_downloaded(model_id: str) -> bool:
+    """Whether everything `model_id` needs is on disk"""
+    if model_id == 'shap_e':
+        return all(f.is_file() for f in _shap_e_files())
+    if model_id in HF_REPOS:
+        return all((_hf_repo_dir(repo) / "snapshots").is_dir() for repo in HF_REPOS[model_id])
+    raise ValueError(f"Unknown model '{model_id}'")
+
+
+def disk_bytes(model_id: str) -> int:
+    """Space `model_id`'s weights take, including a download in progress"""
+    if model_id == 'shap_e':
+        return sum(
+            _tree_bytes(f) + _tree_bytes(f.with_name(f.name + PARTIAL_SUFFIX))
+            for f in _shap_e_files()
+        )
+    if model_id in HF_REPOS:
+        return sum(_tree_bytes(_hf_repo_dir(repo)) for repo in HF_REPOS[model_id])
+    raise ValueError(f"Unknown model '{model_id}'")
+
+
+def download(model_id: str):
+    """Fetch whatever of `model_id`'s weights is missing"""
+    if model_id == 'shap_e':
+        from shap_e.models.download import default_cache_dir, fetch_file_cached
+        for url in _shap_e_urls():
+            fetch_file_cached(url, progress=True, cache_dir=default_cache_dir())
+    elif model_id == 'lgm':
+        from huggingface_hub import hf_hub_download
+        hf_hub_download(lgm.LGM_REPO, lgm.LGM_CHECKPOINT)
+    elif model_id == 'imagedream':
+        # A custom pipeline, which DiffusionPipeline.download won't fetch without running its code
+        from huggingface_hub import snapshot_download
+        snapshot_download(multiview.IMAGEDREAM_MODEL)
+    elif model_id in HF_REPOS:
+        from diffusers import DiffusionPipeline
+        for repo in HF_REPOS[model_id]:
+            DiffusionPipeline.download(repo)
+    else:
+        raise ValueError(f"Unknown model '{model_id}'")
+
+
+def remove(model_id: str):
+    """Delete `model_id`'s weights; they're fetched again on next use"""
+    if model_id == 'shap_e':
+        for f in _shap_e_files():
+            for path in (f, f.with_name(f.name + PARTIAL_SUFFIX)):
+                path.unlink(missing_ok=True)
+    elif model_id in HF_REPOS:
+        for repo in HF_REPOS[model_id]:
+            shutil.rmtree(_hf_repo_dir(repo), ignore_errors=True)
+    else:
+        raise ValueError(f"Unknown model '{model_id}'")
@@ -80,6 +80,16 @@ cd shap-e
 pip install -e .
 cd ..
 
+# Install LGM, imported from python/LGM by the worker
+echo ""
+echo "Installing LGM..."
+if [ -d "LGM" ]; then
+    echo "LGM directory already exists, skipping clone..."
+else
+    git clone https://github.com/3DTopia/LGM.git
+fi
+pip install tyro kiui roma safetensors git+https://github.com/ashawkey/diff-gaussian-rasterization
+
 # Create outputs directory
 mkdir -p ../outputs
 
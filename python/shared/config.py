@@ -15,6 +15,14 @@ CELERY_TASK_TIME_LIMIT = 3600  # 1 hour max
 CELERY_TASK_SOFT_TIME_LIMIT = 3300  # 55 minutes soft limit
 CELERY_RESULT_EXPIRES = 3600  # Results expire after 1 hour
 
+# Clients must send "Authorization: Bearer <API_TOKEN>" when set; required
+# once the API is reachable from other machines
+API_TOKEN = os.getenv('API_TOKEN') or None
+
+# Serve HTTPS with this certificate and key when both are set
+SSL_CERTFILE = os.getenv('SSL_CERTFILE') or None
+SSL_KEYFILE = os.getenv('SSL_KEYFILE') or None
+
 # Output directory
 OUTPUT_DIR = Path(os.getenv('OUTPUT_DIR', '../outputs'))
 OUTPUT_DIR.mkdir(parents=True, exist_ok=True)
@@ -22,6 +30,123 @@ OUTPUT_DIR.mkdir(parents=True, exist_ok=True)
 # Device
 DEVICE = 'cuda' if os.getenv('CUDA_VISIBLE_DEVICES') else 'cpu'
 
+# Shape of the API's requests and responses; bump on any change a client
+# could trip over. The app refuses to talk to a service on another version.
+PROTOCOL_VERSION = 1
+
+# Models the worker can run, by id
+AVAILABLE_MODELS = ['shap_e', 'lgm']
+
+# Generators of the views LGM reconstructs from, by id; the `multiview`
+# parameter of an LGM job picks one. imagedream needs the job's `image`;
+# capture draws nothing and takes the views from the job's `capture` photos.
+MULTIVIEW_MODELS = ['sd_controlnet', 'imagedream', 'capture']
+# Photos a capture set takes
+CAPTURE_PHOTOS = (4, 16)
+
+# Weights the worker fetches on first use, by id: what they are and roughly
+# how much they take on disk once downloaded
+MODEL_WEIGHTS = {
+    'shap_e': {
+        'name': "Shap-E",
+        'description': "Text-conditional latent diffusion and its decoder, for text-to-3D",
+        'size_mb': 3000,
+    },
+    'sds_refine': {
+        'name': "Stable Diffusion 2.1 base",
+        'description': "Diffusion prior of SDS refinement, fetched by the first refinement",
+        'size_mb': 5200,
+    },
+    'lgm': {
+        'name': "LGM",
+        'description': "Large Multi-View Gaussian Model, reconstructing splats from four views",
+        'size_mb': 850,
+    },
+    'sd_controlnet': {
+        'name': "Stable Diffusion 1.5 + depth ControlNet",
+        'description': "Draws the views of LGM jobs from text, fetched by the first one",
+        'size_mb': 5500,
+    },
+    'imagedream': {
+        'name': "ImageDream",
+        'description': "Draws the views of LGM jobs from an input image and text, fetched by the first one",
+        'size_mb': 5000,
+    },
+}
+
+# Fetch missing Shap-E weights while the worker starts; with 0 it starts
+# without them and they're downloaded on request (POST /weights/shap_e/download)
+FETCH_WEIGHTS_ON_START = os.getenv('FETCH_WEIGHTS_ON_START', '1') != '0'
+
 # Job defaults
 DEFAULT_GUIDANCE_SCALE = 15.0
-DEFAULT_NUM_INFERENCE_STEPS = 64
\ No newline at end of file
+DEFAULT_NUM_INFERENCE_STEPS = 64
+# Knobs each model takes, as /models reports them to clients: the form they
+# render and what /generate accepts in `params`. Types are float, int, bool
+# or choice (with `options`); numbers have a min and max.
+MODEL_PARAMS = {
+    'shap_e': [
+        {
+            'name': 'guidance_scale', 'label': "Guidance", 'type': 'float',
+            'min': 1.0, 'max': 30.0, 'default': DEFAULT_GUIDANCE_SCALE,
+            'description': "How closely the result follows the prompt; 1 turns guidance off and saves memory",
+        },
+        {
+            'name': 'num_inference_steps', 'label': "Steps", 'type': 'int',
+            'min': 16, 'max': 256, 'default': DEFAULT_NUM_INFERENCE_STEPS,
+            'description': "Diffusion steps; more is slower and a little cleaner",
+        },
+        {
+            'name': 'mesh_resolution', 'label': "Mesh resolution", 'type': 'int',
+            'min': 32, 'max': 512, 'default': 128,
+            'description': "Cells along each side of the grid the surface is extracted on",
+        },
+        {
+            'name': 'splat_scale', 'label': "Splat scale", 'type': 'float',
+            'min': 0.1, 'max': 10.0, 'default': 1.0,
+            'description': "Multiplier on the splat sizes",
+        },
+    ],
+    'lgm': [
+        {
+            'name': 'multiview', 'label': "Views from", 'type': 'choice',
+            'options': MULTIVIEW_MODELS, 'default': 'sd_controlnet',
+            'description': "What draws the four views LGM reconstructs the splats from; imagedream draws them from the input image, capture takes them from the capture set's photos",
+        },
+        {
+            'name': 'guidance_scale', 'label': "Guidance", 'type': 'float',
+            'min': 1.0, 'max': 20.0, 'default': 7.5,
+            'description': "How closely the views follow the prompt; ImageDream works best around 5",
+        },
+        {
+            'name': 'num_inference_steps', 'label': "Steps", 'type': 'int',
+            'min': 16, 'max': 100, 'default': 30,
+            'description': "Diffusion steps of each view",
+        },
+        {
+            'name': 'controlnet_scale', 'label': "Camera hint strength", 'type': 'float',
+            'min': 0.0, 'max': 2.0, 'default': 1.0,
+            'description': "How strictly each view keeps to its camera's depth hint; lower lets the object take its own shape. Stable Diffusion only",
+        },
+        {
+            'name': 'num_samples', 'label': "Samples", 'type': 'int',
+            'min': 1, 'max': 8, 'default': 1,
+            'description': "Splat sets drawn from the prompt, each from the next seed; reconstructed together, so more cost little beyond their views",
+        },
+    ],
+}
+
+# One-click settings for people who'd rather not learn diffusion knobs, by
+# model and preset id; values of MODEL_PARAMS, the rest stay at defaults
+MODEL_PRESETS = {
+    'shap_e': {
+        'draft': {'guidance_scale': 12.0, 'num_inference_steps': 32, 'mesh_resolution': 64},
+        'standard': {'guidance_scale': 15.0, 'num_inference_steps': 64, 'mesh_resolution': 128},
+        'high': {'guidance_scale': 17.5, 'num_inference_steps': 128, 'mesh_resolution': 256},
+    },
+    'lgm': {
+        'draft': {'num_inference_steps': 20},
+        'standard': {'num_inference_steps': 30},
+        'high': {'num_inference_steps': 50},
+    },
+}
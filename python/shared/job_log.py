@@ -0,0 +1,64 @@
+"""
+Per-job log lines, kept in Redis next to the Celery results
+"""
+import redis
+from .config import REDIS_URL, CELERY_RESULT_EXPIRES
+
+# Keep the tail of very chatty jobs only
+MAX_LOG_LINES = 1000
+
+_redis = redis.Redis.from_url(REDIS_URL, decode_responses=True)
+
+
+def _key(job_id: str) -> str:
+    return f"genjutsu:job_log:{job_id}"
+
+
+def append_log(job_id: str, *lines: str):
+    """Append lines to a job's log"""
+    if not lines:
+        return
+    key = _key(job_id)
+    pipe = _redis.pipeline()
+    pipe.rpush(key, *lines)
+    pipe.ltrim(key, -MAX_LOG_LINES, -1)
+    pipe.expire(key, CELERY_RESULT_EXPIRES)
+    pipe.execute()
+
+
+def read_log(job_id: str, since: int = 0) -> list:
+    """Log lines of a job, starting at line `since`"""
+    return _redis.lrange(_key(job_id), since, -1)
+
+
+class JobLogWriter:
+    """
+    File-like object that copies complete lines into a job's log while
+    still writing through to the original stream
+    """
+
+    def __init__(self, job_id: str, stream):
+        self.job_id = job_id
+        self.stream = stream
+        self.buffer = ""
+
+    def write(self, text: str):
+        self.stream.write(text)
+        self.buffer += text
+        *lines, self.buffer = self.buffer.split("\n")
+        lines = [line.rstrip("\r") for line in lines if line.strip()]
+        if lines:
+            try:
+                append_log(self.job_id, *lines)
+            except redis.RedisError:
+                pass
+        return len(text)
+
+    def flush(self):
+        if self.buffer.strip():
+            try:
+                append_log(self.job_id, self.buffer)
+            except redis.RedisError:
+                pass
+        self.buffer = ""
+        self.stream.flush()
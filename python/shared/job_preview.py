@@ -0,0 +1,31 @@
+"""
+Latest intermediate render of each running job, kept in Redis as PNG bytes
+"""
+from typing import Optional
+import redis
+from .config import REDIS_URL, CELERY_RESULT_EXPIRES
+
+PNG_SIGNATURE = b"\x89PNG\r\n\x1a\n"
+
+# Previews are thumbnails; anything bigger is a mistake
+MAX_PREVIEW_BYTES = 2 * 1024 * 1024
+
+_redis = redis.Redis.from_url(REDIS_URL)
+
+
+def _key(job_id: str) -> str:
+    return f"genjutsu:job_preview:{job_id}"
+
+
+def store_preview(job_id: str, png: bytes):
+    """Replace a job's preview image"""
+    if not png.startswith(PNG_SIGNATURE):
+        raise ValueError("Preview must be a PNG image")
+    if len(png) > MAX_PREVIEW_BYTES:
+        raise ValueError(f"Preview is larger than {MAX_PREVIEW_BYTES} bytes")
+    _redis.set(_key(job_id), png, ex=CELERY_RESULT_EXPIRES)
+
+
+def read_preview(job_id: str) -> Optional[bytes]:
+    """A job's latest preview image, or None if it has none yet"""
+    return _redis.get(_key(job_id))
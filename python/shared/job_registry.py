@@ -0,0 +1,23 @@
+"""
+Record of submitted jobs, so a status check can tell a queued job from one
+the service has never heard of (Celery reports both as PENDING)
+"""
+import redis
+from .config import REDIS_URL, CELERY_RESULT_EXPIRES
+
+# Queued jobs can wait a while before a worker picks them up
+REGISTRY_EXPIRES = CELERY_RESULT_EXPIRES * 24
+
+_redis = redis.Redis.from_url(REDIS_URL, decode_responses=True)
+
+
+def _key(job_id: str) -> str:
+    return f"genjutsu:job:{job_id}"
+
+
+def mark_submitted(job_id: str):
+    _redis.set(_key(job_id), 1, ex=REGISTRY_EXPIRES)
+
+
+def is_known(job_id: str) -> bool:
+    return bool(_redis.exists(_key(job_id)))
@@ -0,0 +1,40 @@
+"""
+Checking the `params` of a job against its model's schema in MODEL_PARAMS
+"""
+from typing import Any, Dict
+from .config import MODEL_PARAMS
+
+
+def resolve_params(model: str, params: Dict[str, Any]) -> Dict[str, Any]:
+    """
+    Every parameter of `model`, from `params` or else its default, checked
+    against the schema. Raises ValueError on unknown names or bad values.
+    """
+    schema = {p['name']: p for p in MODEL_PARAMS.get(model, [])}
+    unknown = set(params) - set(schema)
+    if unknown:
+        raise ValueError(f"Model '{model}' has no parameter(s) {', '.join(sorted(unknown))}")
+
+    resolved = {}
+    for name, spec in schema.items():
+        value = params.get(name, spec['default'])
+        kind = spec['type']
+        if kind == 'bool':
+            if not isinstance(value, bool):
+                raise ValueError(f"{name} must be true or false")
+        elif kind == 'choice':
+            if value not in spec['options']:
+                raise ValueError(f"{name} must be one of {', '.join(map(str, spec['options']))}")
+        else:
+            if isinstance(value, bool) or not isinstance(value, (int, float)):
+                raise ValueError(f"{name} must be a number")
+            if kind == 'int':
+                if value != int(value):
+                    raise ValueError(f"{name} must be a whole number")
+                value = int(value)
+            else:
+                value = float(value)
+            if not spec['min'] <= value <= spec['max']:
+                raise ValueError(f"{name} must be between {spec['min']} and {spec['max']}")
+        resolved[name] = value
+    return resolved
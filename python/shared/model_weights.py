@@ -0,0 +1,54 @@
+"""
+Download state of each model's weights, published by the workers so clients
+can show what's installed and what a first job would still have to fetch.
+With several workers the state is the one reported last.
+"""
+import json
+from typing import Optional
+import redis
+from .config import REDIS_URL, MODEL_WEIGHTS
+
+_redis = redis.Redis.from_url(REDIS_URL, decode_responses=True)
+
+_PREFIX = "genjutsu:weights:"
+
+# Not on disk
+MISSING = "missing"
+# Download or removal requested, waiting for a worker
+QUEUED = "queued"
+DOWNLOADING = "downloading"
+READY = "ready"
+REMOVING = "removing"
+# The last download failed; `error` says why
+FAILED = "failed"
+
+
+def set_state(model_id: str, status: str, disk_bytes: Optional[int] = None, progress: Optional[float] = None, error: Optional[str] = None):
+    """Record where `model_id`'s weights are at"""
+    _redis.set(_PREFIX + model_id, json.dumps({
+        'status': status,
+        'disk_bytes': disk_bytes,
+        'progress': progress,
+        'error': error,
+    }))
+
+
+def get_state(model_id: str) -> Optional[dict]:
+    """The state last recorded for `model_id`, or None if no worker reported it"""
+    value = _redis.get(_PREFIX + model_id)
+    return json.loads(value) if value is not None else None
+
+
+def list_weights() -> list:
+    """Every model's weights with their state; `unknown` until a worker reports"""
+    weights = []
+    for model_id, info in MODEL_WEIGHTS.items():
+        state = get_state(model_id) or {'status': "unknown", 'disk_bytes': None, 'progress': None, 'error': None}
+        weights.append({
+            'id': model_id,
+            'name': info['name'],
+            'description': info['description'],
+            'expected_bytes': info['size_mb'] * 1024 * 1024,
+            **state,
+        })
+    return weights
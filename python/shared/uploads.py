@@ -0,0 +1,71 @@
+"""
+Conditioning images uploaded by clients, stored as RGBA PNGs under OUTPUT_DIR
+so every worker sharing the outputs mount can read them
+"""
+import io
+import uuid
+from pathlib import Path
+from typing import Optional, Tuple
+from PIL import Image, UnidentifiedImageError
+from .config import OUTPUT_DIR
+
+UPLOAD_DIR = OUTPUT_DIR / 'uploads'
+
+# Formats accepted, as Pillow names them
+ACCEPTED_FORMATS = {'PNG', 'JPEG'}
+MAX_UPLOAD_BYTES = 20 * 1024 * 1024
+# Conditioning models work at a few hundred pixels; this is only a sanity cap
+MAX_UPLOAD_SIDE = 4096
+MIN_UPLOAD_SIDE = 32
+
+
+class UploadTooLarge(ValueError):
+    pass
+
+
+class UnsupportedImage(ValueError):
+    pass
+
+
+def save_upload(data: bytes) -> Tuple[str, int, int]:
+    """
+    Validate an uploaded PNG or JPEG and store it re-encoded as RGBA PNG
+
+    Returns the upload id and the image's width and height. Raises
+    UploadTooLarge or UnsupportedImage for bodies we won't keep, and
+    ValueError for images of the wrong size.
+    """
+    if len(data) > MAX_UPLOAD_BYTES:
+        raise UploadTooLarge(f"Image is larger than {MAX_UPLOAD_BYTES // (1024 * 1024)} MB")
+
+    try:
+        image = Image.open(io.BytesIO(data))
+        if image.format not in ACCEPTED_FORMATS:
+            raise UnsupportedImage(f"Only PNG and JPEG images are accepted, not {image.format}")
+        width, height = image.size
+        # Checked before decoding, so huge images never hit memory
+        if max(width, height) > MAX_UPLOAD_SIDE or min(width, height) < MIN_UPLOAD_SIDE:
+            raise ValueError(
+                f"Image is {width}x{height}; sides must be {MIN_UPLOAD_SIDE} to {MAX_UPLOAD_SIDE} pixels"
+            )
+        image = image.convert('RGBA')
+    except (UnidentifiedImageError, Image.DecompressionBombError, OSError) as e:
+        raise UnsupportedImage(f"Not a readable image: {e}")
+
+    upload_id = uuid.uuid4().hex
+    UPLOAD_DIR.mkdir(parents=True, exist_ok=True)
+    # Saved aside first, so a half-written file never has a valid id
+    partial = UPLOAD_DIR / f"{upload_id}.part"
+    image.save(partial, format='PNG')
+    partial.rename(UPLOAD_DIR / f"{upload_id}.png")
+    return upload_id, width, height
+
+
+def upload_path(upload_id: str) -> Optional[Path]:
+    """Stored image of an upload, or None for ids we never handed out"""
+    try:
+        upload_id = uuid.UUID(hex=upload_id).hex
+    except ValueError:
+        return None
+    path = UPLOAD_DIR / f"{upload_id}.png"
+    return path if path.is_file() else None
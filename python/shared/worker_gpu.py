@@ -0,0 +1,55 @@
+"""
+GPU memory of each worker, so the API can tell clients what jobs will fit,
+and the live state of every GPU a worker has, for /health
+"""
+import json
+from typing import List, Optional
+import redis
+from .config import REDIS_URL, CELERY_RESULT_EXPIRES
+
+# Refreshed on every job; a worker gone this long is forgotten
+GPU_EXPIRES = CELERY_RESULT_EXPIRES * 24
+
+_redis = redis.Redis.from_url(REDIS_URL, decode_responses=True)
+
+_PREFIX = "genjutsu:worker_gpu:"
+
+# Published every few seconds by a running worker; stale sooner than that
+# means it stopped
+GPU_STATS_EXPIRES = 15
+
+_STATS_PREFIX = "genjutsu:worker_gpus:"
+
+
+def register_gpu(worker: str, total_mb: int):
+    """Record the memory of the GPU `worker` runs jobs on"""
+    _redis.set(_PREFIX + worker, total_mb, ex=GPU_EXPIRES)
+
+
+def smallest_gpu_mb() -> Optional[int]:
+    """Memory of the smallest registered worker GPU, or None if there are none"""
+    keys = list(_redis.scan_iter(_PREFIX + "*"))
+    if not keys:
+        return None
+    sizes = [int(v) for v in _redis.mget(keys) if v is not None]
+    return min(sizes) if sizes else None
+
+
+def publish_gpus(worker: str, gpus: List[dict]):
+    """
+    Record the GPUs of `worker`: dicts with index, name, total_mb, used_mb
+    and whether jobs run on it (active)
+    """
+    _redis.set(_STATS_PREFIX + worker, json.dumps(gpus), ex=GPU_STATS_EXPIRES)
+
+
+def list_gpus() -> List[dict]:
+    """GPUs of every running worker, each with a `worker` host name"""
+    keys = sorted(_redis.scan_iter(_STATS_PREFIX + "*"))
+    gpus = []
+    for key, value in zip(keys, _redis.mget(keys) if keys else []):
+        if value is None:
+            continue
+        worker = key[len(_STATS_PREFIX):]
+        gpus.extend({**gpu, 'worker': worker} for gpu in json.loads(value))
+    return gpus
@@ -1,7 +1,12 @@
 """
 Celery worker for 3D generation tasks
 """
+import socket
 import sys
+import threading
+import time
+import traceback
+from contextlib import contextmanager, redirect_stdout, redirect_stderr
 from pathlib import Path
 from datetime import datetime
 import torch
@@ -10,37 +15,225 @@ import torch
 sys.path.insert(0, str(Path(__file__).parent))
 
 from shared.celery_app import celery_app
-from shared.config import OUTPUT_DIR, DEVICE
+from shared import model_weights
+from shared.config import OUTPUT_DIR, DEVICE, FETCH_WEIGHTS_ON_START, MODEL_WEIGHTS, MULTIVIEW_MODELS
+from shared.job_log import JobLogWriter
+from shared.job_preview import store_preview
+from shared.uploads import upload_path
+from shared.worker_gpu import publish_gpus, register_gpu
+from models import weights
+from models.lgm import LgmModel
+from models.model import LATENT_SUFFIX, latent_path, sample_path
+from models.refine import SdsRefiner
 from models.shap_e import ShapEModel
 
+# Seconds between size checks of weights being downloaded
+WEIGHTS_PROGRESS_INTERVAL = 2.0
+# Seconds between reports of GPU memory use, see /health
+GPU_STATS_INTERVAL = 5.0
+
+# Index of the GPU jobs run on; a job asking for another moves the models
+ACTIVE_GPU = 0
+
+
+def _device() -> str:
+    """Torch device of the active GPU, or DEVICE when there's no GPU"""
+    return f"cuda:{ACTIVE_GPU}" if DEVICE == 'cuda' else DEVICE
+
+
+def _is_downloaded(model_id: str) -> bool:
+    try:
+        return weights.is_downloaded(model_id)
+    except Exception:
+        return False
+
+
+def _publish_weights(*model_ids):
+    """Record which weights are on disk, for every model when none are given"""
+    for model_id in model_ids or MODEL_WEIGHTS:
+        try:
+            status = model_weights.READY if weights.is_downloaded(model_id) else model_weights.MISSING
+            model_weights.set_state(model_id, status, weights.disk_bytes(model_id))
+        except Exception as e:
+            print(f"Could not publish state of {model_id} weights: {e}")
+
+
+@contextmanager
+def _tracking_download(model_id: str):
+    """
+    Publish the size of `model_id`'s weights on disk as download progress
+    while the block runs, e.g. a model load that fetches what's missing.
+    Models without weights, like the capture multiview, pass through.
+    """
+    if model_id not in MODEL_WEIGHTS:
+        yield
+        return
+    if _is_downloaded(model_id):
+        yield
+        _publish_weights(model_id)
+        return
+
+    expected = MODEL_WEIGHTS[model_id]['size_mb'] * 1024 * 1024
+    done = threading.Event()
+
+    def report():
+        while not done.wait(WEIGHTS_PROGRESS_INTERVAL):
+            try:
+                size = weights.disk_bytes(model_id)
+                model_weights.set_state(model_id, model_weights.DOWNLOADING, size, min(size / expected, 0.99))
+            except Exception as e:
+                print(f"Could not publish download progress: {e}")
+
+    model_weights.set_state(model_id, model_weights.DOWNLOADING, weights.disk_bytes(model_id), 0.0)
+    reporter = threading.Thread(target=report, daemon=True)
+    reporter.start()
+    try:
+        yield
+    except Exception as e:
+        done.set()
+        reporter.join()
+        model_weights.set_state(model_id, model_weights.FAILED, weights.disk_bytes(model_id), error=str(e))
+        raise
+    done.set()
+    reporter.join()
+    _publish_weights(model_id)
+
+
+def _load_shap_e():
+    print("Loading Shap-E...")
+    shap_e = ShapEModel(_device())
+    with _tracking_download('shap_e'):
+        loaded = shap_e.load()
+    if loaded:
+        MODELS['shap_e'] = shap_e
+        print("✓ Shap-E ready")
+    else:
+        print("✗ Shap-E failed to load")
+
 
 # Load models on worker startup
 print("=" * 60)
 print("Initializing Genjutsu Worker")
 print("=" * 60)
-print(f"Device: {DEVICE}")
+print(f"Device: {_device()}")
 print(f"Output: {OUTPUT_DIR}")
 print()
 
 # Initialize models
 MODELS = {}
 
-print("Loading Shap-E...")
-shap_e = ShapEModel(DEVICE)
-if shap_e.load():
-    MODELS['shap_e'] = shap_e
-    print("✓ Shap-E ready")
+_publish_weights()
+if FETCH_WEIGHTS_ON_START or _is_downloaded('shap_e'):
+    _load_shap_e()
 else:
-    print("✗ Shap-E failed to load")
+    print("Shap-E weights not downloaded; fetch them with POST /weights/shap_e/download")
 
 print()
 print(f"Loaded {len(MODELS)} model(s)")
 print("=" * 60)
 print()
 
+# Loaded by the first refinement job; its diffusion prior is large and most
+# workers never run one
+REFINER = None
+
+
+def _load_lgm():
+    """Load LGM for its first job; its view generators load with their first job"""
+    lgm = LgmModel(_device(), track_download=_tracking_download)
+    with _tracking_download('lgm'):
+        loaded = lgm.load()
+    if not loaded:
+        raise RuntimeError("LGM failed to load, see the worker log")
+    MODELS['lgm'] = lgm
+
+
+# Models too large to load on every start, loaded by their first job
+LAZY_MODELS = {'lgm': _load_lgm}
+
+
+def _refiner() -> SdsRefiner:
+    global REFINER
+    if REFINER is None:
+        refiner = SdsRefiner(_device())
+        with _tracking_download('sds_refine'):
+            loaded = refiner.load()
+        if not loaded:
+            raise RuntimeError("SDS refiner failed to load, see the worker log")
+        REFINER = refiner
+    return REFINER
+
+
+def _register_gpu():
+    """Tell the API how much GPU memory jobs have here"""
+    if not torch.cuda.is_available():
+        return
+    total_mb = torch.cuda.get_device_properties(ACTIVE_GPU).total_memory // (1024 * 1024)
+    try:
+        register_gpu(socket.gethostname(), total_mb)
+    except Exception as e:
+        print(f"Could not register GPU: {e}")
+
+
+def _gpu_used_mb(index: int) -> int:
+    """Memory in use on a GPU by every process, or by this one without NVML"""
+    try:
+        return torch.cuda.device_memory_used(index) // (1024 * 1024)
+    except Exception:
+        # Asking the driver directly would open a context on every GPU
+        return torch.cuda.memory_reserved(index) // (1024 * 1024)
+
+
+def _report_gpus():
+    """Publish the memory use of every GPU while the worker runs"""
+    host = socket.gethostname()
+    while True:
+        try:
+            gpus = []
+            for index in range(torch.cuda.device_count()):
+                props = torch.cuda.get_device_properties(index)
+                gpus.append({
+                    'index': index,
+                    'name': props.name,
+                    'total_mb': props.total_memory // (1024 * 1024),
+                    'used_mb': _gpu_used_mb(index),
+                    'active': index == ACTIVE_GPU,
+                })
+            publish_gpus(host, gpus)
+        except Exception as e:
+            print(f"Could not publish GPU stats: {e}")
+        time.sleep(GPU_STATS_INTERVAL)
+
+
+def _use_gpu(index: int = None):
+    """
+    Run jobs on GPU `index` from now on, moving the loaded models there;
+    None keeps the current one
+    """
+    global ACTIVE_GPU, REFINER
+    if index is None or index == ACTIVE_GPU:
+        return
+    if not torch.cuda.is_available() or index >= torch.cuda.device_count():
+        raise ValueError(f"This worker has no GPU {index}")
+
+    print(f"Switching from GPU {ACTIVE_GPU} to GPU {index}...")
+    reload_shap_e = 'shap_e' in MODELS
+    MODELS.clear()
+    REFINER = None
+    torch.cuda.empty_cache()
+    ACTIVE_GPU = index
+    _register_gpu()
+    if reload_shap_e:
+        _load_shap_e()
+
+
+_register_gpu()
+if torch.cuda.is_available():
+    threading.Thread(target=_report_gpus, daemon=True).start()
+
 
 @celery_app.task(name='worker.generate_3d', bind=True)
-def generate_3d(self, prompt: str, model_name: str, guidance_scale: float, num_inference_steps: int):
+def generate_3d(self, prompt: str, model_name: str, guidance_scale: float, num_inference_steps: int, seed: int = None, output_dir: str = None, image: str = None, device: int = None, params: dict = None, capture: list = None):
     """
     Generate 3D model from text prompt
 
@@ -50,10 +243,36 @@ def generate_3d(self, prompt: str, model_name: str, guidance_scale: float, num_i
         model_name: Model to use
         guidance_scale: Guidance scale parameter
         num_inference_steps: Number of diffusion steps
+        seed: Fixed random seed, or None for a random result
+        output_dir: Directory to write the result to, OUTPUT_DIR when None
+            or not visible to this worker
+        image: Upload id of a conditioning image, see /uploads
+        device: Index of the GPU to run on, the current one when None
+        params: The model's parameters by name, checked against MODEL_PARAMS;
+            these win over guidance_scale and num_inference_steps
+        capture: Photos of a real object as dicts of upload id, azimuth,
+            elevation and optionally camera, for models that reconstruct
+            from them
 
     Returns:
         dict with output_path and metadata
     """
+    # Everything printed while the job runs (ours and the model's) also goes
+    # to the job log, served at /job/{id}/log
+    _register_gpu()
+    stdout = JobLogWriter(self.request.id, sys.stdout)
+    stderr = JobLogWriter(self.request.id, sys.stderr)
+    try:
+        with redirect_stdout(stdout), redirect_stderr(stderr):
+            _use_gpu(device)
+            params = {'guidance_scale': guidance_scale, 'num_inference_steps': num_inference_steps, **(params or {})}
+            return _generate_3d(self, prompt, model_name, params, seed, output_dir, image, capture)
+    finally:
+        stdout.flush()
+        stderr.flush()
+
+
+def _generate_3d(self, prompt: str, model_name: str, params: dict, seed: int = None, output_dir: str = None, image: str = None, capture: list = None):
     try:
         # Update state to STARTED
         self.update_state(
@@ -65,26 +284,53 @@ def generate_3d(self, prompt: str, model_name: str, guidance_scale: float, num_i
         )
 
         # Check model exists
+        if model_name not in MODELS and model_name in LAZY_MODELS:
+            self.update_state(state='STARTED', meta={'progress': 0.0, 'message': f'Loading {model_name}...'})
+            LAZY_MODELS[model_name]()
         if model_name not in MODELS:
+            if model_name in MODEL_WEIGHTS and not _is_downloaded(model_name):
+                raise ValueError(f"Weights for '{model_name}' aren't downloaded; download them in the model manager")
             raise ValueError(f"Model '{model_name}' not available")
 
         model = MODELS[model_name]
 
+        image_path = None
+        if image is not None:
+            if not model.supports_image:
+                raise ValueError(f"Model '{model_name}' doesn't take a conditioning image")
+            image_path = upload_path(image)
+            if image_path is None:
+                raise ValueError(f"Upload '{image}' not found")
+
+        # The capture set's photos as (path, azimuth, elevation, camera)
+        capture_views = None
+        if capture:
+            if not model.supports_capture:
+                raise ValueError(f"Model '{model_name}' doesn't take a capture set")
+            capture_views = []
+            for view in capture:
+                path = upload_path(view['upload'])
+                if path is None:
+                    raise ValueError(f"Upload '{view['upload']}' not found")
+                capture_views.append((path, float(view['azimuth']), float(view['elevation']), view.get('camera')))
+
         # Create output path
         timestamp = datetime.now().strftime("%Y%m%d_%H%M%S")
         safe_prompt = "".join(c for c in prompt if c.isalnum() or c in (' ', '_')).strip()
         safe_prompt = safe_prompt[:50].replace(' ', '_')
 
         output_name = f"{model_name}_{safe_prompt}_{timestamp}.ply"
-        output_path = OUTPUT_DIR / output_name
+        out_dir = _output_dir(output_dir)
+        output_path = (out_dir / output_name).resolve()
 
         print(f"\n{'='*60}")
         print(f"Job ID: {self.request.id}")
         print(f"Model: {model.get_name()}")
         print(f"Prompt: {prompt}")
         print(f"Output: {output_path}")
-        print(f"Guidance: {guidance_scale}")
-        print(f"Steps: {num_inference_steps}")
+        for name, value in params.items():
+            print(f"{name}: {value}")
+        print(f"Seed: {seed if seed is not None else 'random'}")
         print(f"{'='*60}\n")
 
         # Progress callback
@@ -98,6 +344,13 @@ def generate_3d(self, prompt: str, model_name: str, guidance_scale: float, num_i
             )
             print(f"[{progress*100:.0f}%] {message}")
 
+        # Intermediate renders, served at /job/{id}/preview
+        def preview_callback(png: bytes):
+            try:
+                store_preview(self.request.id, png)
+            except Exception as e:
+                print(f"    Could not store preview: {e}")
+
         # Update progress
         progress_callback(0.1, 'Initializing model...')
 
@@ -106,8 +359,12 @@ def generate_3d(self, prompt: str, model_name: str, guidance_scale: float, num_i
             result_path = model.generate(
                 prompt,
                 output_path,
-                guidance_scale=guidance_scale,
-                num_inference_steps=num_inference_steps
+                seed=seed,
+                image_path=image_path,
+                capture=capture_views,
+                progress_callback=progress_callback,
+                preview_callback=preview_callback,
+                **params
             )
         except ValueError as e:
             # Generation failed - return helpful error
@@ -126,17 +383,280 @@ def generate_3d(self, prompt: str, model_name: str, guidance_scale: float, num_i
         # Return result
         return {
             'output_path': str(result_path),
+            'latent_path': _kept_latent(model, result_path),
+            'sample_paths': _samples(result_path, params),
             'model': model_name,
             'prompt': prompt,
-            'guidance_scale': guidance_scale,
-            'num_inference_steps': num_inference_steps
+            **params,
+            'seed': seed
         }
 
     except Exception as e:
+        traceback.print_exc()
         print(f"\n✗ Job failed: {str(e)}\n")
         raise
 
 
+
+def _kept_latent(model, result_path: Path):
+    """Path of the latent the model kept for re-decoding the result, if it did"""
+    latent = latent_path(Path(result_path))
+    return str(latent) if model.supports_decode and latent.is_file() else None
+
+
+def _samples(result_path: Path, params: dict) -> list:
+    """Paths of the further samples a job drawing several wrote next to its result"""
+    paths = (sample_path(Path(result_path), i) for i in range(1, int(params.get('num_samples', 1))))
+    return [str(p) for p in paths if p.is_file()]
+
+
+def _output_dir(output_dir: str = None) -> Path:
+    """
+    The client's directory only exists here when we share its filesystem;
+    in a container we write to the mounted OUTPUT_DIR instead
+    """
+    return Path(output_dir) if output_dir and Path(output_dir).is_dir() else OUTPUT_DIR
+
+
+def _find_output(name: str, out_dir: Path):
+    """An earlier job's file by name, next to the results or in OUTPUT_DIR"""
+    return next((d / name for d in (out_dir, OUTPUT_DIR) if (d / name).is_file()), None)
+
+
+@celery_app.task(name='worker.redecode_3d', bind=True)
+def redecode_3d(self, latent: str, model_name: str, mesh_resolution: int, splat_scale: float, output_dir: str = None, device: int = None):
+    """
+    Decode the latent an earlier job kept into a new result, skipping diffusion
+
+    Args:
+        self: Task instance (for progress updates)
+        latent: File name of the latent, from an earlier result's latent_path
+        model_name: Model that sampled the latent
+        mesh_resolution: Cells along each side of the mesh extraction grid
+        splat_scale: Multiplier on the splat sizes
+        output_dir: Directory to write the result to, OUTPUT_DIR when None
+            or not visible to this worker
+        device: Index of the GPU to run on, the current one when None
+
+    Returns:
+        dict with output_path and metadata
+    """
+    _register_gpu()
+    stdout = JobLogWriter(self.request.id, sys.stdout)
+    stderr = JobLogWriter(self.request.id, sys.stderr)
+    try:
+        with redirect_stdout(stdout), redirect_stderr(stderr):
+            _use_gpu(device)
+            return _redecode_3d(self, latent, model_name, mesh_resolution, splat_scale, output_dir)
+    finally:
+        stdout.flush()
+        stderr.flush()
+
+
+def _redecode_3d(self, latent: str, model_name: str, mesh_resolution: int, splat_scale: float, output_dir: str = None):
+    try:
+        self.update_state(state='STARTED', meta={'progress': 0.0, 'message': 'Starting re-decode...'})
+
+        if model_name not in MODELS:
+            raise ValueError(f"Model '{model_name}' not available")
+        model = MODELS[model_name]
+        if not model.supports_decode:
+            raise ValueError(f"Model '{model_name}' can't re-decode a latent")
+
+        # Written next to the result it came from, wherever that was
+        out_dir = _output_dir(output_dir)
+        latent_file = _find_output(latent, out_dir)
+        if latent_file is None:
+            raise ValueError(f"Latent '{latent}' not found; it may have been deleted")
+
+        timestamp = datetime.now().strftime("%Y%m%d_%H%M%S")
+        source = latent_file.name.removesuffix(LATENT_SUFFIX)
+        output_path = (out_dir / f"{source}_redecode_{timestamp}.ply").resolve()
+
+        print(f"\n{'='*60}")
+        print(f"Job ID: {self.request.id}")
+        print(f"Model: {model.get_name()}")
+        print(f"Latent: {latent_file}")
+        print(f"Output: {output_path}")
+        print(f"{'='*60}\n")
+
+        self.update_state(state='STARTED', meta={'progress': 0.1, 'message': 'Decoding latent...'})
+        result_path = model.decode(
+            latent_file,
+            output_path,
+            mesh_resolution=mesh_resolution,
+            splat_scale=splat_scale
+        )
+        self.update_state(state='STARTED', meta={'progress': 1.0, 'message': 'Complete!'})
+
+        return {
+            'output_path': str(result_path),
+            'latent_path': str(latent_file),
+            'model': model_name,
+            'prompt': '',
+            'mesh_resolution': mesh_resolution,
+            'splat_scale': splat_scale
+        }
+
+    except Exception as e:
+        traceback.print_exc()
+        print(f"\n✗ Re-decode failed: {str(e)}\n")
+        raise
+
+
+
+@celery_app.task(name='worker.refine_3d', bind=True)
+def refine_3d(self, source: str, prompt: str, iterations: int, guidance_scale: float, seed: int = None, output_dir: str = None, device: int = None):
+    """
+    Refine an earlier job's result with score distillation against a 2D
+    diffusion prior, the second stage of DreamGaussian
+
+    Args:
+        self: Task instance (for progress updates)
+        source: File name of the result to refine
+        prompt: Text description the result is refined towards
+        iterations: Optimization steps
+        guidance_scale: Classifier-free guidance of the prior
+        seed: Fixed random seed, or None for a random result
+        output_dir: Directory to write the result to, OUTPUT_DIR when None
+            or not visible to this worker
+        device: Index of the GPU to run on, the current one when None
+
+    Returns:
+        dict with output_path and metadata
+    """
+    _register_gpu()
+    stdout = JobLogWriter(self.request.id, sys.stdout)
+    stderr = JobLogWriter(self.request.id, sys.stderr)
+    try:
+        with redirect_stdout(stdout), redirect_stderr(stderr):
+            _use_gpu(device)
+            return _refine_3d(self, source, prompt, iterations, guidance_scale, seed, output_dir)
+    finally:
+        stdout.flush()
+        stderr.flush()
+
+
+def _refine_3d(self, source: str, prompt: str, iterations: int, guidance_scale: float, seed: int = None, output_dir: str = None):
+    try:
+        self.update_state(state='STARTED', meta={'progress': 0.0, 'message': 'Starting refinement...'})
+
+        out_dir = _output_dir(output_dir)
+        source_file = _find_output(source, out_dir)
+        if source_file is None:
+            raise ValueError(f"Result '{source}' not found; it may have been moved or deleted")
+
+        timestamp = datetime.now().strftime("%Y%m%d_%H%M%S")
+        output_path = (out_dir / f"{source_file.stem}_refined_{timestamp}.ply").resolve()
+
+        print(f"\n{'='*60}")
+        print(f"Job ID: {self.request.id}")
+        print(f"Refining: {source_file}")
+        print(f"Prompt: {prompt}")
+        print(f"Output: {output_path}")
+        print(f"Seed: {seed if seed is not None else 'random'}")
+        print(f"{'='*60}\n")
+
+        def progress_callback(progress: float, message: str):
+            self.update_state(state='STARTED', meta={'progress': progress, 'message': message})
+
+        def preview_callback(png: bytes):
+            try:
+                store_preview(self.request.id, png)
+            except Exception as e:
+                print(f"    Could not store preview: {e}")
+
+        progress_callback(0.05, 'Loading diffusion prior...')
+        result_path = _refiner().refine(
+            source_file,
+            output_path,
+            prompt,
+            iterations=iterations,
+            guidance_scale=guidance_scale,
+            seed=seed,
+            progress_callback=progress_callback,
+            preview_callback=preview_callback
+        )
+        progress_callback(1.0, 'Complete!')
+
+        return {
+            'output_path': str(result_path),
+            'model': 'sds_refine',
+            'prompt': prompt,
+            'source': source,
+            'iterations': iterations,
+            'guidance_scale': guidance_scale,
+            'seed': seed
+        }
+
+    except Exception as e:
+        traceback.print_exc()
+        print(f"\n✗ Refinement failed: {str(e)}\n")
+        raise
+
+
+@celery_app.task(name='worker.download_weights', bind=True)
+def download_weights(self, model_id: str):
+    """
+    Fetch a model's weights ahead of its first job, and load the model if
+    this worker runs it
+
+    Args:
+        self: Task instance (for progress updates)
+        model_id: Key of MODEL_WEIGHTS
+
+    Returns:
+        dict with the model id and the weights' size on disk
+    """
+    try:
+        self.update_state(state='STARTED', meta={'progress': 0.0, 'message': f'Downloading {model_id} weights...'})
+        with _tracking_download(model_id):
+            weights.download(model_id)
+        if model_id == 'shap_e' and 'shap_e' not in MODELS:
+            _load_shap_e()
+        # The others load with their first job
+        return {'model': model_id, 'disk_bytes': weights.disk_bytes(model_id)}
+
+    except Exception as e:
+        traceback.print_exc()
+        print(f"\n✗ Download of {model_id} weights failed: {str(e)}\n")
+        raise
+
+
+@celery_app.task(name='worker.remove_weights')
+def remove_weights(model_id: str):
+    """
+    Unload a model and delete its weights to free the disk space; they're
+    fetched again by its next job
+
+    Args:
+        model_id: Key of MODEL_WEIGHTS
+
+    Returns:
+        dict with the model id
+    """
+    global REFINER
+    try:
+        model_weights.set_state(model_id, model_weights.REMOVING, weights.disk_bytes(model_id))
+        if model_id in ('shap_e', 'lgm'):
+            MODELS.pop(model_id, None)
+        elif model_id == 'sds_refine':
+            REFINER = None
+        elif model_id in MULTIVIEW_MODELS and 'lgm' in MODELS:
+            MODELS['lgm'].generators.pop(model_id, None)
+        if torch.cuda.is_available():
+            torch.cuda.empty_cache()
+
+        weights.remove(model_id)
+        _publish_weights(model_id)
+        return {'model': model_id}
+
+    except Exception as e:
+        traceback.print_exc()
+        model_weights.set_state(model_id, model_weights.FAILED, error=str(e))
+        raise
+
+
 if __name__ == '__main__':
     # Start worker
     celery_app.worker_main([